@@ -0,0 +1,230 @@
+// src/admin_cli.rs
+use crate::core::database::DatabaseConfig;
+use crate::core::{data_residency, maintenance};
+use anyhow::Result;
+use clap::{Parser, Subcommand};
+use graflog::app_log;
+use std::path::PathBuf;
+
+#[derive(Parser)]
+#[command(name = "cvenom admin")]
+#[command(about = "Maintenance tasks for the CV generator, e.g. nightly from cron")]
+pub struct AdminCli {
+    #[command(subcommand)]
+    pub command: AdminCommand,
+
+    #[arg(long, env = "CVENOM_DATABASE_PATH", default_value = "data/tenants.db")]
+    pub database_path: PathBuf,
+    #[arg(long, env = "CVENOM_TENANT_DATA_PATH", default_value = "data")]
+    pub data_dir: PathBuf,
+    #[arg(long, env = "CVENOM_OUTPUT_PATH", default_value = "output")]
+    pub output_dir: PathBuf,
+    #[arg(long, env = "CVENOM_TEMPLATES_PATH", default_value = "templates")]
+    pub templates_dir: PathBuf,
+}
+
+#[derive(Subcommand)]
+pub enum AdminCommand {
+    /// Delete generated PDFs older than N days (default 90). Dry-run by default.
+    Prune {
+        #[arg(long, default_value = "90")]
+        days: i64,
+        #[arg(long, help = "Actually delete — omit to do a dry run")]
+        execute: bool,
+    },
+    /// Delete cached picture variants older than N days (default 30) or beyond
+    /// --max-mb per data region, and expired cached service answers.
+    /// Dry-run by default.
+    PruneCaches {
+        #[arg(long, default_value = "30")]
+        days: u64,
+        #[arg(long, default_value = "500")]
+        max_mb: u64,
+        #[arg(long, help = "Actually delete — omit to do a dry run")]
+        execute: bool,
+    },
+    /// Check every person for missing files, broken TOML and corrupt pictures.
+    Verify {
+        #[arg(long, help = "Also compile each language (slow)")]
+        compile: bool,
+    },
+    /// Rebuild the profile name index of every user folder.
+    Reindex,
+    /// Reclaim free space in the SQLite database.
+    Vacuum,
+    /// Rotate the log file once it exceeds --max-mb, keeping --keep old copies.
+    RotateLogs {
+        #[arg(long, env = "LOG_PATH_CVENOM", default_value = "/var/log/cvenom.log")]
+        log_path: PathBuf,
+        #[arg(long, default_value = "50")]
+        max_mb: u64,
+        #[arg(long, default_value = "5")]
+        keep: usize,
+    },
+    /// Run every task in turn: prune and prune-caches (executed), verify,
+    /// reindex, vacuum, rotate-logs.
+    Nightly {
+        #[arg(long, default_value = "90")]
+        prune_days: i64,
+        #[arg(long, env = "LOG_PATH_CVENOM", default_value = "/var/log/cvenom.log")]
+        log_path: PathBuf,
+    },
+}
+
+pub async fn handle_admin_command(cli: AdminCli) -> Result<()> {
+    let mut db_config = DatabaseConfig::new(cli.database_path.clone());
+    db_config.init_pool().await?;
+    db_config.migrate().await?;
+    let pool = db_config.pool()?;
+
+    data_residency::init(&cli.data_dir, &cli.output_dir);
+    data_residency::load(pool).await?;
+
+    let tasks = match cli.command {
+        AdminCommand::Prune { days, execute } => vec![Task::Prune { days, execute }],
+        AdminCommand::PruneCaches { days, max_mb, execute } => {
+            vec![Task::PruneCaches { days, max_mb, execute }]
+        }
+        AdminCommand::Verify { compile } => vec![Task::Verify { compile }],
+        AdminCommand::Reindex => vec![Task::Reindex],
+        AdminCommand::Vacuum => vec![Task::Vacuum],
+        AdminCommand::RotateLogs { log_path, max_mb, keep } => {
+            vec![Task::RotateLogs { log_path, max_mb, keep }]
+        }
+        AdminCommand::Nightly { prune_days, log_path } => vec![
+            Task::Prune { days: prune_days, execute: true },
+            Task::PruneCaches { days: 30, max_mb: 500, execute: true },
+            Task::Verify { compile: false },
+            Task::Reindex,
+            Task::Vacuum,
+            Task::RotateLogs { log_path, max_mb: 50, keep: 5 },
+        ],
+    };
+
+    // A failing task does not stop the others; the exit status reports it.
+    let mut failed = Vec::new();
+    for task in tasks {
+        let name = task.name();
+        if let Err(e) = run(&cli, pool, task).await {
+            app_log!(error, "❌ {} failed: {:#}", name, e);
+            failed.push(name);
+        }
+    }
+    if !failed.is_empty() {
+        anyhow::bail!("{} task(s) failed: {}", failed.len(), failed.join(", "));
+    }
+    Ok(())
+}
+
+enum Task {
+    Prune { days: i64, execute: bool },
+    PruneCaches { days: u64, max_mb: u64, execute: bool },
+    Verify { compile: bool },
+    Reindex,
+    Vacuum,
+    RotateLogs { log_path: PathBuf, max_mb: u64, keep: usize },
+}
+
+impl Task {
+    fn name(&self) -> &'static str {
+        match self {
+            Task::Prune { .. } => "prune",
+            Task::PruneCaches { .. } => "prune-caches",
+            Task::Verify { .. } => "verify",
+            Task::Reindex => "reindex",
+            Task::Vacuum => "vacuum",
+            Task::RotateLogs { .. } => "rotate-logs",
+        }
+    }
+}
+
+async fn run(cli: &AdminCli, pool: &sqlx::SqlitePool, task: Task) -> Result<()> {
+    match task {
+        Task::Prune { days, execute } => {
+            let before = chrono::Utc::now() - chrono::Duration::days(days);
+            let report = maintenance::prune_outputs(pool, &cli.output_dir, before, execute).await?;
+            let mode = if execute { "DELETED" } else { "DRY RUN" };
+            app_log!(
+                info,
+                "[{}] {} generated PDF(s) older than {} days ({} KB)",
+                mode,
+                report.deleted,
+                days,
+                report.bytes / 1024
+            );
+            if !execute && report.deleted > 0 {
+                app_log!(info, "Re-run with --execute to delete them.");
+            }
+        }
+        Task::PruneCaches { days, max_mb, execute } => {
+            let max_age = std::time::Duration::from_secs(days * 24 * 60 * 60);
+            let report =
+                maintenance::prune_caches(&cli.data_dir, max_age, max_mb * 1024 * 1024, execute)
+                    .await?;
+            let mode = if execute { "DELETED" } else { "DRY RUN" };
+            app_log!(
+                info,
+                "[{}] {} cached file(s): pictures older than {} days or over {} MB, \
+                 expired service answers ({} KB)",
+                mode,
+                report.deleted,
+                days,
+                max_mb,
+                report.bytes / 1024
+            );
+        }
+        Task::Verify { compile } => {
+            let report = maintenance::verify(&cli.templates_dir, &cli.data_dir, compile).await?;
+            if report.broken.is_empty() {
+                app_log!(info, "✅ All {} person(s) passed the integrity checks.", report.checked);
+            } else {
+                app_log!(
+                    info,
+                    "⚠️  {} of {} person(s) failed the integrity checks:",
+                    report.broken.len(),
+                    report.checked
+                );
+                for (person, checks) in &report.broken {
+                    app_log!(info, "  {} ({})", person, checks.join(", "));
+                }
+            }
+        }
+        Task::Reindex => {
+            let users = maintenance::reindex(&cli.data_dir).await?;
+            app_log!(info, "✅ Rebuilt the profile name index of {} user folder(s).", users);
+        }
+        Task::Vacuum => {
+            let (before, after) = maintenance::vacuum(pool, &cli.database_path).await?;
+            app_log!(info, "✅ Database vacuumed: {} KB -> {} KB.", before / 1024, after / 1024);
+        }
+        Task::RotateLogs { log_path, max_mb, keep } => {
+            if maintenance::rotate_log(&log_path, max_mb * 1024 * 1024, keep)? {
+                app_log!(info, "✅ Rotated {}.", log_path.display());
+            } else {
+                app_log!(info, "{} is under {} MB, not rotated.", log_path.display(), max_mb);
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn admin_arguments_parse_as_the_readme_documents() {
+        AdminCli::command().debug_assert();
+
+        let cli = AdminCli::try_parse_from(["admin", "prune-caches", "--days", "7", "--execute"])
+            .unwrap();
+        assert!(matches!(
+            cli.command,
+            AdminCommand::PruneCaches { days: 7, max_mb: 500, execute: true }
+        ));
+        let cli = AdminCli::try_parse_from(["admin", "nightly", "--prune-days", "30"]).unwrap();
+        assert!(matches!(cli.command, AdminCommand::Nightly { prune_days: 30, .. }));
+        assert!(AdminCli::try_parse_from(["admin", "prune-everything"]).is_err());
+    }
+}
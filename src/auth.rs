@@ -1,4 +1,7 @@
 use crate::core::database::{DatabaseConfig, Tenant, TenantRepository, TenantService};
+use crate::core::owner;
+use crate::core::profile_access::MemberRole;
+use crate::core::tenant_cache;
 use crate::web::handlers::referral_handlers::credit_referral;
 // src/auth.rs
 use crate::web::ServerConfig;
@@ -11,10 +14,11 @@ use rocket::request::{FromRequest, Outcome};
 use rocket::{Request, State};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FirebaseUser {
     pub uid: String,
     pub email: String,
@@ -48,6 +52,7 @@ impl From<Claims> for FirebaseUser {
     }
 }
 
+#[derive(Clone)]
 pub struct AuthConfig {
     pub project_id: String,
     /// kid → PEM public key. Refreshed when Google rotates Firebase keys (~6 h).
@@ -57,6 +62,33 @@ pub struct AuthConfig {
     /// Expected `aud` claim in OIDC tokens (e.g. "https://api.cvenom.com").
     /// Read from CVENOM_OIDC_AUDIENCE env var; None → OIDC path disabled.
     pub oidc_audience: Option<String>,
+    /// Pinned Firebase keys (same JSON shape as Google's x509 endpoint).
+    /// Read from FIREBASE_KEYS_FILE; used when Google is unreachable and
+    /// rewritten after every successful fetch.
+    pub firebase_keys_file: Option<PathBuf>,
+}
+
+/// How `start_web_server` reacts when no Firebase keys can be loaded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuthStartupMode {
+    /// Refuse to start (default).
+    Strict,
+    /// Start anyway; authenticated requests get 401 until keys arrive.
+    Degraded,
+}
+
+impl AuthStartupMode {
+    /// Read AUTH_STARTUP_MODE (`strict` | `degraded`).
+    pub fn from_env() -> Self {
+        match std::env::var("AUTH_STARTUP_MODE")
+            .unwrap_or_default()
+            .to_lowercase()
+            .as_str()
+        {
+            "degraded" => Self::Degraded,
+            _ => Self::Strict,
+        }
+    }
 }
 
 impl AuthConfig {
@@ -65,11 +97,80 @@ impl AuthConfig {
         if let Some(ref aud) = oidc_audience {
             app_log!(info, "OIDC downstream auth enabled — audience: {}", aud);
         }
+        let firebase_keys_file = std::env::var("FIREBASE_KEYS_FILE").ok().map(PathBuf::from);
+        if let Some(ref path) = firebase_keys_file {
+            app_log!(info, "Pinned Firebase keys file: {}", path.display());
+        }
         Self {
             project_id,
             firebase_keys: Arc::new(RwLock::new(HashMap::new())),
             oidc_jwks: Arc::new(RwLock::new(None)),
             oidc_audience,
+            firebase_keys_file,
+        }
+    }
+
+    /// Load Firebase keys at startup: Google first, then the pinned file.
+    /// In `Degraded` mode a total failure is logged and the server starts with
+    /// an empty key cache; in `Strict` mode it is returned as an error.
+    pub async fn init_firebase_keys(&self, mode: AuthStartupMode) -> Result<()> {
+        let fetch_err = match self.update_firebase_keys().await {
+            Ok(()) => return Ok(()),
+            Err(e) => e,
+        };
+        app_log!(warn, "Failed to fetch Firebase keys: {}", fetch_err);
+
+        match self.load_pinned_firebase_keys().await {
+            Ok(n) => {
+                app_log!(warn, "Running with {} pinned Firebase key(s) (offline verification)", n);
+                Ok(())
+            }
+            Err(pin_err) if mode == AuthStartupMode::Degraded => {
+                app_log!(
+                    error,
+                    "No Firebase keys available ({}); starting in degraded mode — authenticated requests will be rejected until keys can be fetched",
+                    pin_err
+                );
+                Ok(())
+            }
+            Err(pin_err) => Err(fetch_err.context(format!("pinned keys unavailable: {}", pin_err))),
+        }
+    }
+
+    /// Replace the key cache with the pinned keys file. Returns the key count.
+    pub async fn load_pinned_firebase_keys(&self) -> Result<usize> {
+        let path = self
+            .firebase_keys_file
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("FIREBASE_KEYS_FILE not set"))?;
+        let raw = tokio::fs::read_to_string(path).await?;
+        let keys: HashMap<String, String> = serde_json::from_str(&raw)?;
+        if keys.is_empty() {
+            anyhow::bail!("{} contains no keys", path.display());
+        }
+
+        let mut cache = self.firebase_keys.write().await;
+        *cache = keys;
+        Ok(cache.len())
+    }
+
+    /// Persist freshly fetched keys to the pinned file (write-then-rename).
+    async fn pin_firebase_keys(&self, keys: &HashMap<String, String>) {
+        let Some(path) = self.firebase_keys_file.as_ref() else {
+            return;
+        };
+        let tmp = path.with_extension("tmp");
+        let result = async {
+            if let Some(parent) = path.parent() {
+                tokio::fs::create_dir_all(parent).await?;
+            }
+            tokio::fs::write(&tmp, serde_json::to_vec_pretty(keys)?).await?;
+            tokio::fs::rename(&tmp, path).await?;
+            anyhow::Ok(())
+        }
+        .await;
+        if let Err(e) = result {
+            app_log!(warn, "Failed to pin Firebase keys to {}: {}", path.display(), e);
         }
     }
 
@@ -84,6 +185,7 @@ impl AuthConfig {
 
         let response = client.get(url).send().await?;
         let keys: HashMap<String, String> = response.json().await?;
+        self.pin_firebase_keys(&keys).await;
 
         let mut cache = self.firebase_keys.write().await;
         *cache = keys;
@@ -182,9 +284,11 @@ async fn verify_google_oidc_token(token: &str, auth_config: &AuthConfig) -> Resu
 }
 
 /// Authenticated user with tenant information
+#[derive(Clone)]
 pub struct AuthenticatedUser {
     pub firebase_user: FirebaseUser,
     pub tenant: Tenant,
+    pub role: MemberRole,
 }
 
 impl AuthenticatedUser {
@@ -204,6 +308,21 @@ impl AuthenticatedUser {
         &self.tenant.tenant_name
     }
 
+    /// Key of the caller's rows in account-scoped tables; see
+    /// [`crate::core::owner`].
+    pub fn account_key(&self) -> String {
+        owner::account_key(self.email())
+    }
+
+    /// Key of the caller's tenant in tenant-wide tables.
+    pub fn tenant_id(&self) -> i64 {
+        self.tenant.id
+    }
+
+    pub fn role(&self) -> MemberRole {
+        self.role
+    }
+
     /// User's preferred email language (falls back to "en").
     pub fn lang(&self) -> &str {
         self.tenant.preferred_lang.as_deref().unwrap_or("en")
@@ -368,6 +487,24 @@ impl<'r> FromRequest<'r> for AuthenticatedUser {
             }
         };
 
+        // Recently resolved: skip the database (see core::tenant_cache).
+        if let Some(identity) = tenant_cache::get(&firebase_user.email) {
+            if identity.tenant.archived_at.is_some() {
+                return Outcome::Error((Status::Locked, AuthError::TenantArchived));
+            }
+            let role = identity
+                .granted_role
+                .as_deref()
+                .and_then(MemberRole::parse)
+                .unwrap_or_else(|| MemberRole::default_for(&identity.tenant));
+            req.local_cache(|| RequestUserEmail(Some(firebase_user.email.clone())));
+            return Outcome::Success(AuthenticatedUser {
+                firebase_user,
+                tenant: identity.tenant,
+                role,
+            });
+        }
+
         // ── Tenant lookup / creation ──────────────────────────────────────────
         let pool = match db_config.pool() {
             Ok(pool) => pool,
@@ -395,6 +532,11 @@ impl<'r> FromRequest<'r> for AuthenticatedUser {
             }
         };
 
+        // Archived accounts stay rejected until restored (see core::tenant_archive).
+        if tenant.archived_at.is_some() {
+            return Outcome::Error((Status::Locked, AuthError::TenantArchived));
+        }
+
         // Grant free-offer welcome credits to brand-new users — SYNCHRONOUS.
         if is_new_user {
             const WELCOME_CREDITS: i64 = 100;
@@ -471,6 +613,7 @@ impl<'r> FromRequest<'r> for AuthenticatedUser {
         }
 
         // Fire-and-forget: update last_seen_at so the retention cleanup knows this user is active.
+        // Cache hits skip this, so it runs at most once per TENANT_CACHE_TTL_SECS.
         if let Ok(pool) = db_config.pool() {
             let touch_pool = pool.clone();
             let touch_email = firebase_user.email.clone();
@@ -487,13 +630,43 @@ impl<'r> FromRequest<'r> for AuthenticatedUser {
             tenant.tenant_name
         );
 
+        let granted_role = match TenantRepository::new(pool)
+            .get_member_role(&firebase_user.email)
+            .await
+        {
+            Ok(granted) => granted,
+            Err(e) => {
+                app_log!(error, "Failed to load role for {}: {}", firebase_user.email, e);
+                return Outcome::Error((Status::InternalServerError, AuthError::DatabaseError));
+            }
+        };
+        let role = granted_role
+            .as_deref()
+            .and_then(MemberRole::parse)
+            .unwrap_or_else(|| MemberRole::default_for(&tenant));
+        tenant_cache::put(
+            &firebase_user.email,
+            tenant_cache::CachedIdentity {
+                tenant: tenant.clone(),
+                granted_role,
+            },
+        );
+
+        req.local_cache(|| RequestUserEmail(Some(firebase_user.email.clone())));
+
         Outcome::Success(AuthenticatedUser {
             firebase_user,
             tenant,
+            role,
         })
     }
 }
 
+/// Email of the user authenticated on the current request, cached by the
+/// [`AuthenticatedUser`] guard so response fairings can attribute the request.
+/// `None` when the route did not authenticate.
+pub struct RequestUserEmail(pub Option<String>);
+
 #[derive(Debug)]
 pub enum AuthError {
     MissingToken,
@@ -502,6 +675,7 @@ pub enum AuthError {
     NotAuthorized,
     DatabaseError,
     SignupRequired,
+    TenantArchived,
 }
 
 impl AuthError {
@@ -513,6 +687,7 @@ impl AuthError {
             AuthError::NotAuthorized => "User not authorized for this tenant. Signup coming soon!",
             AuthError::DatabaseError => "Database error occurred",
             AuthError::SignupRequired => "Signup required. Coming soon!",
+            AuthError::TenantArchived => "This account is archived. Contact support to restore it.",
         }
     }
 }
@@ -539,10 +714,16 @@ async fn verify_firebase_token(token: &str, auth_config: &AuthConfig) -> Result<
                 "Firebase key ID '{}' not in cache — refreshing public keys",
                 kid
             );
-            auth_config
-                .update_firebase_keys()
-                .await
-                .map_err(|e| anyhow::anyhow!("Failed to refresh Firebase keys: {}", e))?;
+            if let Err(e) = auth_config.update_firebase_keys().await {
+                // Offline: the pinned file may have been updated out of band.
+                auth_config.load_pinned_firebase_keys().await.map_err(|pin_err| {
+                    anyhow::anyhow!(
+                        "Failed to refresh Firebase keys: {} (pinned keys: {})",
+                        e,
+                        pin_err
+                    )
+                })?;
+            }
 
             let keys = auth_config.firebase_keys.read().await;
             keys.get(&kid)
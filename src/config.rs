@@ -2,6 +2,37 @@
 // use anyhow::{Context, Result};
 use std::path::PathBuf;
 
+/// File a generation delivers. DOCX is converted from Typst's HTML export with
+/// pandoc: the content is editable, the template's page layout is not kept.
+/// HTML is that export made self-contained, for templates declaring it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum OutputFormat {
+    #[default]
+    Pdf,
+    Docx,
+    Html,
+}
+
+impl OutputFormat {
+    /// `None` for an unsupported format; absent means PDF.
+    pub fn parse(format: Option<&str>) -> Option<Self> {
+        match format.map(|f| f.trim().to_lowercase()).as_deref() {
+            None | Some("") | Some("pdf") => Some(Self::Pdf),
+            Some("docx") => Some(Self::Docx),
+            Some("html") => Some(Self::Html),
+            _ => None,
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Pdf => "pdf",
+            Self::Docx => "docx",
+            Self::Html => "html",
+        }
+    }
+}
+
 pub struct CvConfig {
     pub profile_name: String,
     pub lang: String,
@@ -9,6 +40,8 @@ pub struct CvConfig {
     pub output_dir: PathBuf,
     pub data_dir: PathBuf,
     pub templates_dir: PathBuf,
+    /// Templates uploaded by the tenant, offered next to the server's.
+    pub tenant_templates_dir: Option<PathBuf>,
     pub root_dir: PathBuf,
     /// Forward the profile's custom colors to Typst; false → use template defaults.
     pub use_custom_colors: bool,
@@ -19,6 +52,28 @@ pub struct CvConfig {
     /// Absolute path to the selected brand's directory inside the tenant data
     /// dir (used to find `logo.png`). `None` when no brand is selected.
     pub brand_dir: Option<PathBuf>,
+    /// Template version to compile with; `None` uses the template's current files.
+    pub template_version: Option<String>,
+    /// Generation features to enable; `None` enables all the template supports.
+    pub features: Option<Vec<String>>,
+    /// Fail instead of leaving out an image that can't be used.
+    pub strict_assets: bool,
+    /// Check every file the sources reference before compiling and fail
+    /// with all the problems (see `core::asset_check`).
+    pub strict: bool,
+    /// Render only the first page, as a PNG at this resolution, instead of the PDF.
+    pub preview_ppi: Option<u32>,
+    /// Text stamped across every page, e.g. `DRAFT` for unapproved profiles.
+    pub watermark: Option<String>,
+    /// When a requested draft was generated, printed at the foot of each page.
+    pub draft_generated_at: Option<String>,
+    pub format: OutputFormat,
+    /// Export a PDF as PDF/A-2b.
+    pub pdf_a: bool,
+    /// Title, author and creation date written into the PDF.
+    pub pdf_metadata: crate::core::typst_compiler::PdfMetadata,
+    /// Extra `sys.inputs`, e.g. the person's tags (see `core::person_tags`).
+    pub inputs: Vec<(String, String)>,
 }
 
 impl CvConfig {
@@ -39,10 +94,22 @@ impl CvConfig {
             output_dir: PathBuf::from("output"),
             data_dir: PathBuf::from("data"),
             templates_dir: PathBuf::from("templates"),
+            tenant_templates_dir: None,
             root_dir: current_dir,
             use_custom_colors: false,
             brand: None,
             brand_dir: None,
+            template_version: None,
+            features: None,
+            strict_assets: false,
+            strict: false,
+            preview_ppi: None,
+            watermark: None,
+            draft_generated_at: None,
+            format: OutputFormat::Pdf,
+            pdf_a: false,
+            pdf_metadata: Default::default(),
+            inputs: Vec::new(),
         }
     }
 
@@ -51,6 +118,68 @@ impl CvConfig {
         self
     }
 
+    pub fn with_template_version(mut self, version: String) -> Self {
+        self.template_version = Some(version);
+        self
+    }
+
+    /// Enable only these generation features (see `core::template_features`).
+    pub fn with_features(mut self, features: Vec<String>) -> Self {
+        self.features = Some(features);
+        self
+    }
+
+    pub fn with_strict_assets(mut self, strict: bool) -> Self {
+        self.strict_assets = strict;
+        self
+    }
+
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    pub fn with_preview(mut self, ppi: u32) -> Self {
+        self.preview_ppi = Some(ppi);
+        self
+    }
+
+    pub fn with_watermark(mut self, text: String) -> Self {
+        self.watermark = Some(text);
+        self
+    }
+
+    /// A draft: stamped DRAFT, with its generation time at the foot of each
+    /// page; templates also get `draft` and `draft.generated_at` inputs.
+    pub fn with_draft(mut self, generated_at: String) -> Self {
+        self.watermark = Some("DRAFT".to_string());
+        self.inputs.push(("draft".to_string(), "true".to_string()));
+        self.inputs
+            .push(("draft.generated_at".to_string(), generated_at.clone()));
+        self.draft_generated_at = Some(generated_at);
+        self
+    }
+
+    pub fn with_format(mut self, format: OutputFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    pub fn with_pdf_a(mut self, enabled: bool) -> Self {
+        self.pdf_a = enabled;
+        self
+    }
+
+    pub fn with_pdf_metadata(mut self, metadata: crate::core::typst_compiler::PdfMetadata) -> Self {
+        self.pdf_metadata = metadata;
+        self
+    }
+
+    pub fn with_inputs(mut self, inputs: Vec<(String, String)>) -> Self {
+        self.inputs.extend(inputs);
+        self
+    }
+
     pub fn with_output_dir(mut self, dir: PathBuf) -> Self {
         self.output_dir = dir;
         self
@@ -66,6 +195,11 @@ impl CvConfig {
         self
     }
 
+    pub fn with_tenant_templates(mut self, dir: PathBuf) -> Self {
+        self.tenant_templates_dir = Some(dir);
+        self
+    }
+
     pub fn with_custom_colors(mut self, enabled: bool) -> Self {
         self.use_custom_colors = enabled;
         self
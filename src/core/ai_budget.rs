@@ -0,0 +1,205 @@
+// src/core/ai_budget.rs
+//! Monthly per-tenant budgets for AI-backed endpoints.
+//!
+//! Job analysis, optimization and translation each call a cv-import endpoint
+//! that runs an LLM, and every call is already recorded in `service_calls`
+//! ([`crate::core::service_usage`]). A budget caps the successful calls a
+//! tenant makes to one [`AiFeature`] per calendar month (UTC). The admin sets
+//! it per tenant; otherwise `AI_BUDGET_<FEATURE>` (e.g. `AI_BUDGET_ANALYSIS`)
+//! applies, and without either the feature is unlimited. Requests are checked
+//! before they start, so one that fans out (a bench match) can end slightly
+//! over budget.
+
+use anyhow::Result;
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
+use serde::Serialize;
+use sqlx::SqlitePool;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AiFeature {
+    Analysis,
+    Optimization,
+    Translation,
+}
+
+impl AiFeature {
+    pub const ALL: [AiFeature; 3] = [Self::Analysis, Self::Optimization, Self::Translation];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Analysis => "analysis",
+            Self::Optimization => "optimization",
+            Self::Translation => "translation",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|feature| feature.as_str() == value.trim().to_lowercase())
+    }
+
+    /// cv-import endpoint whose calls the budget counts.
+    fn endpoint(&self) -> &'static str {
+        match self {
+            Self::Analysis => "/jobs-match",
+            Self::Optimization => "/optimize",
+            Self::Translation => "/translate",
+        }
+    }
+
+    fn default_limit(&self) -> Option<i64> {
+        std::env::var(format!("AI_BUDGET_{}", self.as_str().to_uppercase()))
+            .ok()
+            .and_then(|v| v.trim().parse().ok())
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BudgetStatus {
+    pub feature: AiFeature,
+    /// Calls allowed this month; `None` when unlimited.
+    pub limit: Option<i64>,
+    pub used: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remaining: Option<i64>,
+    pub resets_at: DateTime<Utc>,
+}
+
+impl BudgetStatus {
+    pub fn exceeded(&self) -> bool {
+        self.limit.is_some_and(|limit| self.used >= limit)
+    }
+}
+
+/// Start of the month containing `now` and of the next one.
+fn month_window(now: DateTime<Utc>) -> (DateTime<Utc>, DateTime<Utc>) {
+    let start = NaiveDate::from_ymd_opt(now.year(), now.month(), 1).unwrap_or_default();
+    let next = if now.month() == 12 {
+        NaiveDate::from_ymd_opt(now.year() + 1, 1, 1)
+    } else {
+        NaiveDate::from_ymd_opt(now.year(), now.month() + 1, 1)
+    }
+    .unwrap_or_default();
+    let midnight = |date: NaiveDate| date.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc();
+    (midnight(start), midnight(next))
+}
+
+/// Budget of `feature` for tenant `tenant_id` and what was used of it this
+/// month.
+pub async fn status(pool: &SqlitePool, tenant_id: i64, feature: AiFeature) -> Result<BudgetStatus> {
+    let (start, resets_at) = month_window(Utc::now());
+    let stored: Option<i64> = sqlx::query_scalar(
+        "SELECT monthly_calls FROM ai_budgets WHERE tenant_id = ? AND feature = ?",
+    )
+    .bind(tenant_id)
+    .bind(feature.as_str())
+    .fetch_optional(pool)
+    .await?;
+    let used: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM service_calls WHERE tenant_id = ? AND endpoint = ? \
+         AND status BETWEEN 200 AND 299 AND created_at >= ?",
+    )
+    .bind(tenant_id)
+    .bind(feature.endpoint())
+    .bind(start)
+    .fetch_one(pool)
+    .await?;
+    let limit = stored.or_else(|| feature.default_limit());
+    Ok(BudgetStatus {
+        feature,
+        limit,
+        used,
+        remaining: limit.map(|limit| (limit - used).max(0)),
+        resets_at,
+    })
+}
+
+/// [`status`] of every feature.
+pub async fn statuses(pool: &SqlitePool, tenant_id: i64) -> Result<Vec<BudgetStatus>> {
+    let mut all = Vec::new();
+    for feature in AiFeature::ALL {
+        all.push(status(pool, tenant_id, feature).await?);
+    }
+    Ok(all)
+}
+
+/// `tenant` has used up its `feature` budget for the month.
+#[derive(Debug, Clone)]
+pub struct BudgetExceeded(pub BudgetStatus);
+
+impl fmt::Display for BudgetExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "The monthly {} budget of {} calls is used up; it resets on {}",
+            self.0.feature.as_str(),
+            self.0.limit.unwrap_or_default(),
+            self.0.resets_at.format("%Y-%m-%d")
+        )
+    }
+}
+
+impl std::error::Error for BudgetExceeded {}
+
+/// Fails with [`BudgetExceeded`] when tenant `tenant_id` may not call
+/// `feature` again this month.
+pub async fn check(pool: &SqlitePool, tenant_id: i64, feature: AiFeature) -> Result<()> {
+    let status = status(pool, tenant_id, feature).await?;
+    if status.exceeded() {
+        return Err(BudgetExceeded(status).into());
+    }
+    Ok(())
+}
+
+/// Set the monthly call budget of `feature` for tenant `tenant_id`; `None`
+/// goes back to the server default.
+pub async fn set_budget(
+    pool: &SqlitePool,
+    tenant_id: i64,
+    feature: AiFeature,
+    monthly_calls: Option<i64>,
+) -> Result<()> {
+    match monthly_calls {
+        Some(calls) => {
+            sqlx::query(
+                "INSERT INTO ai_budgets (tenant_id, feature, monthly_calls, updated_at) \
+                 VALUES (?, ?, ?, ?) ON CONFLICT(tenant_id, feature) DO UPDATE SET \
+                 monthly_calls = excluded.monthly_calls, updated_at = excluded.updated_at",
+            )
+            .bind(tenant_id)
+            .bind(feature.as_str())
+            .bind(calls.max(0))
+            .bind(Utc::now())
+            .execute(pool)
+            .await?;
+        }
+        None => {
+            sqlx::query("DELETE FROM ai_budgets WHERE tenant_id = ? AND feature = ?")
+                .bind(tenant_id)
+                .bind(feature.as_str())
+                .execute(pool)
+                .await?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn budgets_reset_at_the_next_month() {
+        let (start, reset) = month_window(Utc.with_ymd_and_hms(2025, 12, 31, 23, 59, 0).unwrap());
+        assert_eq!(start, Utc.with_ymd_and_hms(2025, 12, 1, 0, 0, 0).unwrap());
+        assert_eq!(reset, Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap());
+
+        let (_, reset) = month_window(Utc.with_ymd_and_hms(2026, 2, 1, 0, 0, 0).unwrap());
+        assert_eq!(reset, Utc.with_ymd_and_hms(2026, 3, 1, 0, 0, 0).unwrap());
+        assert_eq!(AiFeature::parse(" Translation"), Some(AiFeature::Translation));
+    }
+}
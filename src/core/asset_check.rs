@@ -0,0 +1,216 @@
+// src/core/asset_check.rs
+//! Strict generation: the files the CV's Typst sources reference are checked
+//! before compiling, so every missing or broken one is reported at once
+//! instead of one compile error per attempt.
+//!
+//! From the entry file, `import` and `include` are followed through the
+//! workspace and the string literals given to `image`, `read`, `json`,
+//! `yaml`, `toml`, `csv`, `xml`, `cbor`, `bibliography` and `plugin` are
+//! collected. Each must be in the workspace and usable: an image that
+//! decodes, TOML and JSON that parse, text that is UTF-8. A file the source
+//! only uses when the input of the same name is set
+//! (`sys.inputs.at("company_logo.png", …)`) is optional. Like the linter,
+//! the scan is naive: paths built at compile time (the profile photo, client
+//! logos) are not seen, and the generator passes those only when the file
+//! is there.
+
+use std::collections::{BTreeSet, VecDeque};
+use std::path::{Component, Path, PathBuf};
+
+use crate::core::typst_lint::{is_ident_char, is_ident_start, string_arg};
+
+const SOURCE_TAKERS: &[&str] = &["import", "include"];
+const FILE_TAKERS: &[&str] = &[
+    "image", "read", "json", "yaml", "toml", "csv", "xml", "cbor", "bibliography", "plugin",
+];
+const INPUT_GUARD: &str = "sys.inputs.at(";
+
+/// Referenced files that are missing or unusable; nothing was compiled.
+#[derive(Debug)]
+pub struct MissingAssets {
+    /// One line per file, e.g. "'logo.svg' (main.typ): not found".
+    pub problems: Vec<String>,
+}
+
+impl std::fmt::Display for MissingAssets {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} file(s) referenced by the CV are missing or invalid: {}",
+            self.problems.len(),
+            self.problems.join("; ")
+        )
+    }
+}
+
+impl std::error::Error for MissingAssets {}
+
+/// String literals of `source` given to a file-taking call, with whether
+/// the file is Typst source itself. Commented-out lines are skipped.
+fn references(source: &str) -> Vec<(String, bool)> {
+    let source: String = source
+        .lines()
+        .filter(|line| !line.trim_start().starts_with("//"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let optional: Vec<&str> = source
+        .match_indices(INPUT_GUARD)
+        .filter_map(|(at, _)| string_arg(&source, at + INPUT_GUARD.len()))
+        .collect();
+
+    let bytes = source.as_bytes();
+    let mut found = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        // `sys.inputs.at` and other field accesses are not calls of interest.
+        let after_ident = i > 0 && (is_ident_char(bytes[i - 1]) || bytes[i - 1] == b'.');
+        if !is_ident_start(bytes[i]) || after_ident {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        while i < bytes.len() && is_ident_char(bytes[i]) {
+            i += 1;
+        }
+        let ident = &source[start..i];
+        let is_source = SOURCE_TAKERS.contains(&ident);
+        if !is_source && !(FILE_TAKERS.contains(&ident) && bytes.get(i) == Some(&b'(')) {
+            continue;
+        }
+        if let Some(path) = string_arg(&source, i) {
+            if !path.starts_with('@') && !optional.contains(&path) {
+                found.push((path.to_string(), is_source));
+            }
+        }
+    }
+    found
+}
+
+/// `reference` made in `from`, relative to the workspace root.
+fn resolve(from: &Path, reference: &str) -> Result<PathBuf, String> {
+    let joined = match reference.strip_prefix('/') {
+        Some(rooted) => PathBuf::from(rooted),
+        None => from.parent().unwrap_or(Path::new("")).join(reference),
+    };
+    let mut resolved = PathBuf::new();
+    for component in joined.components() {
+        match component {
+            Component::Normal(part) => resolved.push(part),
+            Component::CurDir => {}
+            Component::ParentDir if resolved.pop() => {}
+            _ => return Err("outside the workspace".to_string()),
+        }
+    }
+    Ok(resolved)
+}
+
+/// Why the file at `path` can't be used, if it can't.
+fn unusable(path: &Path) -> Option<String> {
+    let Ok(bytes) = std::fs::read(path) else {
+        return Some("not found".to_string());
+    };
+    let extension = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let text = || std::str::from_utf8(&bytes).ok();
+    let problem = match extension.as_str() {
+        "png" | "jpg" | "jpeg" | "gif" | "webp" => match image::guess_format(&bytes) {
+            Ok(image::ImageFormat::Png | image::ImageFormat::Jpeg | image::ImageFormat::WebP) => {
+                image::ImageReader::new(std::io::Cursor::new(&bytes))
+                    .with_guessed_format()
+                    .ok()
+                    .and_then(|reader| reader.into_dimensions().ok())
+                    .is_none()
+                    .then_some("not a readable image")
+            }
+            Ok(_) => None,
+            Err(_) if bytes.is_empty() => Some("empty"),
+            Err(_) => Some("not an image"),
+        },
+        "svg" => (!text().is_some_and(|t| t.contains("<svg"))).then_some("not an SVG image"),
+        "toml" => text()
+            .and_then(|t| t.parse::<toml::Value>().ok())
+            .is_none()
+            .then_some("not valid TOML"),
+        "json" => serde_json::from_slice::<serde_json::Value>(&bytes)
+            .is_err()
+            .then_some("not valid JSON"),
+        "typ" | "yaml" | "yml" | "csv" | "xml" | "txt" | "bib" => {
+            text().is_none().then_some("not UTF-8 text")
+        }
+        _ => None,
+    };
+    problem.map(str::to_string)
+}
+
+/// Every problem with the files `entry` references, directly or through the
+/// sources it imports, in the workspace at `root`.
+pub fn check(root: &Path, entry: &str) -> Vec<String> {
+    let mut problems = Vec::new();
+    let mut seen = BTreeSet::from([PathBuf::from(entry)]);
+    let mut sources = VecDeque::from([PathBuf::from(entry)]);
+    while let Some(source) = sources.pop_front() {
+        let Ok(content) = std::fs::read_to_string(root.join(&source)) else {
+            continue;
+        };
+        for (reference, is_source) in references(&content) {
+            let referenced_by = source.display();
+            let file = match resolve(&source, &reference) {
+                Ok(file) => file,
+                Err(reason) => {
+                    problems.push(format!("'{}' ({}): {}", reference, referenced_by, reason));
+                    continue;
+                }
+            };
+            if !seen.insert(file.clone()) {
+                continue;
+            }
+            match unusable(&root.join(&file)) {
+                Some(reason) => {
+                    problems.push(format!("'{}' ({}): {}", reference, referenced_by, reason))
+                }
+                None if is_source => sources.push_back(file),
+                None => {}
+            }
+        }
+    }
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_problem_is_reported_in_one_pass() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::write(
+            root.join("main.typ"),
+            "#import \"template.typ\": conf\n\
+             #let details = toml(\"cv_params.toml\")\n\
+             // #image(\"old.png\")\n\
+             #if sys.inputs.at(\"company_logo.png\", default: none) != none { image(\"company_logo.png\") }\n\
+             #import \"@preview/cetz:0.2.0\"",
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("template.typ"),
+            "#image(\"icons/github.svg\") #image(\"/banner.png\") #read(\"../secret\")",
+        )
+        .unwrap();
+        std::fs::write(root.join("cv_params.toml"), "name = ").unwrap();
+        std::fs::create_dir(root.join("icons")).unwrap();
+        std::fs::write(root.join("icons/github.svg"), "<svg></svg>").unwrap();
+
+        assert_eq!(
+            check(root, "main.typ"),
+            vec![
+                "'cv_params.toml' (main.typ): not valid TOML",
+                "'/banner.png' (template.typ): not found",
+                "'../secret' (template.typ): outside the workspace",
+            ]
+        );
+    }
+}
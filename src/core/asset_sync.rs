@@ -0,0 +1,489 @@
+// src/core/asset_sync.rs
+//! Template and font files kept identical across instances.
+//!
+//! Behind several instances, a template installed from the registry or a
+//! font added to one node lands on that node's disk only. With
+//! `ASSET_SYNC_URL` set (an S3-compatible bucket, given like
+//! `S3_UPLOAD_URL`) and `ASSET_SYNC_ACCESS_KEY_ID`,
+//! `ASSET_SYNC_SECRET_ACCESS_KEY` and `ASSET_SYNC_REGION`, every instance
+//! runs a [`pass`] every `ASSET_SYNC_INTERVAL_SECS` (default 30) and right
+//! after a registry install. The bucket holds `assets/manifest.json` — a
+//! version and the SHA-256 of every synced file — and each file's content
+//! once, under `assets/blobs/<sha256>`.
+//!
+//! A pass compares the bucket's manifest, the local files and what the last
+//! pass left (`.asset-sync.json` in the templates directory). Local changes
+//! since then are uploaded and published as the next version, guarded by the
+//! manifest's ETag so two instances publishing at once can't overwrite each
+//! other (the loser publishes on its next pass). Remote changes are
+//! downloaded, checked against their hash and renamed into place one file at
+//! a time, then the templates are re-read and the fonts re-discovered: no
+//! restart, and a request never sees a half-written file. When both sides
+//! changed a file, the local one wins and is published.
+//!
+//! Synced are the templates installed from the registry (directories with
+//! [`INSTALLED_MARKER`], their archived versions included) and the fonts in
+//! `ASSET_SYNC_FONTS_DIR`, a directory kept for them alone (never the system
+//! fonts); no fonts are synced when it is unset. Templates shipped with the
+//! server are never touched, so a rolling deploy doesn't mix versions; tenant
+//! templates live in the tenant's data folder, which instances share.
+//!
+//! Once an instance has synced, a bucket without a manifest (wiped, or a
+//! wrong `ASSET_SYNC_URL`) fails the pass instead of reading as "everything
+//! was deleted".
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use graflog::app_log;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Component, Path, PathBuf};
+use std::sync::{LazyLock, Mutex};
+use std::time::Duration;
+
+use crate::core::presigned_uploads::S3Config;
+use crate::core::template_registry::INSTALLED_MARKER;
+use crate::core::{template_engine, typst_compiler};
+use crate::utils::sha256_hex;
+
+const MANIFEST_KEY: &str = "assets/manifest.json";
+const BLOB_PREFIX: &str = "assets/blobs";
+const STATE_FILE: &str = ".asset-sync.json";
+const DEFAULT_INTERVAL_SECS: u64 = 30;
+const TEMPLATES_AREA: &str = "templates";
+const FONTS_AREA: &str = "fonts";
+/// Suffix of files being downloaded, never synced themselves.
+const PART_SUFFIX: &str = ".sync-part";
+
+/// Synced files by path (`templates/<id>/…`, `fonts/…`) to SHA-256.
+type Files = BTreeMap<String, String>;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Manifest {
+    pub version: u64,
+    pub files: Files,
+}
+
+/// Outcome of the last pass, for `GET /admin/templates/state`.
+#[derive(Debug, Clone, Serialize)]
+pub struct SyncStatus {
+    /// Manifest version this instance is at.
+    pub version: u64,
+    pub files: usize,
+    pub last_pass_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+}
+
+static CONFIG: LazyLock<Option<S3Config>> = LazyLock::new(|| S3Config::from_env_prefix("ASSET_SYNC"));
+static STATUS: Mutex<Option<SyncStatus>> = Mutex::new(None);
+static WAKE: LazyLock<tokio::sync::Notify> = LazyLock::new(tokio::sync::Notify::new);
+
+/// The bucket assets are synced through, when configured.
+pub fn config() -> Option<&'static S3Config> {
+    CONFIG.as_ref()
+}
+
+pub fn interval() -> Duration {
+    let secs = std::env::var("ASSET_SYNC_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(DEFAULT_INTERVAL_SECS);
+    Duration::from_secs(secs.max(1))
+}
+
+/// Start the next pass now rather than at the next tick, e.g. after an
+/// install, so the other instances get the files sooner.
+pub fn request_sync() {
+    WAKE.notify_one();
+}
+
+/// Wait for the next tick of `interval` or a [`request_sync`].
+pub async fn wait(interval: &mut tokio::time::Interval) {
+    tokio::select! {
+        _ = interval.tick() => {}
+        _ = WAKE.notified() => {}
+    }
+}
+
+pub fn status() -> Option<SyncStatus> {
+    STATUS.lock().unwrap_or_else(|p| p.into_inner()).clone()
+}
+
+/// Directory the synced fonts live in, `ASSET_SYNC_FONTS_DIR`; the Typst
+/// compiler discovers fonts there too.
+pub fn fonts_dir() -> Option<PathBuf> {
+    std::env::var_os("ASSET_SYNC_FONTS_DIR")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+}
+
+/// Local file of synced `path`, if it is a well-formed one.
+fn local_path(path: &str, templates_dir: &Path, fonts_dir: Option<&Path>) -> Option<PathBuf> {
+    let (area, rest) = path.split_once('/')?;
+    let relative = Path::new(rest);
+    if !relative.components().all(|c| matches!(c, Component::Normal(_))) || rest.ends_with(PART_SUFFIX) {
+        return None;
+    }
+    match area {
+        TEMPLATES_AREA if relative.components().count() > 1 => Some(templates_dir.join(relative)),
+        FONTS_AREA => fonts_dir.map(|dir| dir.join(relative)),
+        _ => None,
+    }
+}
+
+/// Every file under `dir` as `<prefix>/<relative path>`, with its hash.
+fn hash_tree(dir: &Path, prefix: &str, files: &mut Files) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = format!("{}/{}", prefix, entry.file_name().to_string_lossy());
+        if path.is_dir() {
+            hash_tree(&path, &name, files);
+        } else if !name.ends_with(PART_SUFFIX) {
+            if let Ok(content) = std::fs::read(&path) {
+                files.insert(name, sha256_hex(&content));
+            }
+        }
+    }
+}
+
+/// The synced files on this instance, and the ids of the templates shipped
+/// with the server (which remote files never replace).
+fn scan(templates_dir: &Path, fonts_dir: Option<&Path>) -> (Files, BTreeSet<String>) {
+    let mut files = Files::new();
+    let mut shipped = BTreeSet::new();
+    for entry in std::fs::read_dir(templates_dir).into_iter().flatten().flatten() {
+        let path = entry.path();
+        if !path.is_dir() {
+            continue;
+        }
+        let id = entry.file_name().to_string_lossy().to_string();
+        if path.join(INSTALLED_MARKER).is_file() {
+            hash_tree(&path, &format!("{}/{}", TEMPLATES_AREA, id), &mut files);
+        } else {
+            shipped.insert(id);
+        }
+    }
+    if let Some(dir) = fonts_dir {
+        hash_tree(dir, FONTS_AREA, &mut files);
+    }
+    (files, shipped)
+}
+
+/// What a pass does.
+#[derive(Debug, Default, PartialEq)]
+struct Plan {
+    /// Remote files to download, with their hash.
+    fetch: Files,
+    /// Local files removed on another instance.
+    delete: Vec<String>,
+    /// Local changes to publish: the new hash, or `None` when removed.
+    publish: BTreeMap<String, Option<String>>,
+}
+
+/// Reconcile `remote` and `local` given `last`, what both were after the
+/// previous pass; without one (first pass), remote files win and local-only
+/// files are published.
+fn plan(remote: &Files, local: &Files, last: Option<&Files>) -> Plan {
+    let bootstrap: Files;
+    let last = match last {
+        Some(last) => last,
+        None => {
+            bootstrap = local
+                .iter()
+                .filter(|(path, _)| remote.contains_key(*path))
+                .map(|(path, hash)| (path.clone(), hash.clone()))
+                .collect();
+            &bootstrap
+        }
+    };
+    let paths: BTreeSet<&String> = remote.keys().chain(local.keys()).chain(last.keys()).collect();
+    let mut plan = Plan::default();
+    for path in paths {
+        let (remote, local, last) = (remote.get(path), local.get(path), last.get(path));
+        if local != last {
+            plan.publish.insert(path.clone(), local.cloned());
+        } else if remote != local {
+            match remote {
+                Some(hash) => {
+                    plan.fetch.insert(path.clone(), hash.clone());
+                }
+                None => plan.delete.push(path.clone()),
+            }
+        }
+    }
+    plan
+}
+
+fn client() -> reqwest::Client {
+    reqwest::Client::builder()
+        .timeout(Duration::from_secs(60))
+        .build()
+        .unwrap_or_default()
+}
+
+/// The bucket's manifest and its ETag; an empty one when there is none yet.
+/// Missing after this instance synced (`synced`), it is an error: removing
+/// every synced file over a wiped bucket or a wrong URL would be worse.
+async fn fetch_manifest(s3: &S3Config, synced: bool) -> Result<(Manifest, Option<String>)> {
+    let url = s3.presign("GET", MANIFEST_KEY, 300, Utc::now());
+    let response = client().get(&url).send().await.context("object storage unreachable")?;
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        if synced {
+            bail!(
+                "the bucket has no {} although this instance synced with it; check \
+                 ASSET_SYNC_URL, or remove {} to publish this instance's files again",
+                MANIFEST_KEY,
+                STATE_FILE
+            );
+        }
+        return Ok((Manifest::default(), None));
+    }
+    if !response.status().is_success() {
+        bail!("object storage answered {} for the manifest", response.status());
+    }
+    let etag = response
+        .headers()
+        .get(reqwest::header::ETAG)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string);
+    let manifest = serde_json::from_slice(&response.bytes().await?).context("invalid asset manifest")?;
+    Ok((manifest, etag))
+}
+
+/// Replace the manifest, unless another instance did since `etag`.
+async fn put_manifest(s3: &S3Config, manifest: &Manifest, etag: Option<&str>) -> Result<()> {
+    let url = s3.presign("PUT", MANIFEST_KEY, 300, Utc::now());
+    let request = client().put(&url).body(serde_json::to_vec(manifest)?);
+    let request = match etag {
+        Some(etag) => request.header(reqwest::header::IF_MATCH, etag),
+        None => request.header(reqwest::header::IF_NONE_MATCH, "*"),
+    };
+    let response = request.send().await.context("object storage unreachable")?;
+    match response.status() {
+        status if status.is_success() => Ok(()),
+        reqwest::StatusCode::PRECONDITION_FAILED | reqwest::StatusCode::CONFLICT => {
+            bail!("another instance published first; retrying on the next pass")
+        }
+        status => bail!("object storage answered {} for the manifest", status),
+    }
+}
+
+async fn upload_blob(s3: &S3Config, hash: &str, content: Vec<u8>) -> Result<()> {
+    let url = s3.presign("PUT", &format!("{}/{}", BLOB_PREFIX, hash), 300, Utc::now());
+    let response = client().put(&url).body(content).send().await?;
+    anyhow::ensure!(
+        response.status().is_success(),
+        "object storage answered {} for a file",
+        response.status()
+    );
+    Ok(())
+}
+
+async fn download_blob(s3: &S3Config, hash: &str) -> Result<Vec<u8>> {
+    let url = s3.presign("GET", &format!("{}/{}", BLOB_PREFIX, hash), 300, Utc::now());
+    let response = client().get(&url).send().await?;
+    anyhow::ensure!(
+        response.status().is_success(),
+        "object storage answered {} for a file",
+        response.status()
+    );
+    let content = response.bytes().await?.to_vec();
+    anyhow::ensure!(sha256_hex(&content) == hash, "a file does not match its hash");
+    Ok(content)
+}
+
+/// Write `content` next to `target`, then rename it into place.
+async fn replace(target: &Path, content: &[u8]) -> Result<()> {
+    if let Some(parent) = target.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    let part = PathBuf::from(format!("{}{}", target.display(), PART_SUFFIX));
+    tokio::fs::write(&part, content).await?;
+    tokio::fs::rename(&part, target)
+        .await
+        .with_context(|| format!("Failed to replace {}", target.display()))
+}
+
+fn load_state(templates_dir: &Path) -> Option<Manifest> {
+    let content = std::fs::read(templates_dir.join(STATE_FILE)).ok()?;
+    serde_json::from_slice(&content).ok()
+}
+
+/// One sync with the bucket; returns the manifest version reached.
+pub async fn pass(s3: &S3Config, templates_dir: &Path) -> Result<u64> {
+    let fonts_dir = fonts_dir();
+    let fonts_dir = fonts_dir.as_deref();
+    let last = load_state(templates_dir).map(|state| state.files);
+    let (mut remote, etag) = fetch_manifest(s3, last.is_some()).await?;
+    let (local, shipped) = scan(templates_dir, fonts_dir);
+    // Remote files this instance can't or won't write are left out.
+    let visible: Files = remote
+        .files
+        .iter()
+        .filter(|(path, _)| local_path(path, templates_dir, fonts_dir).is_some())
+        .filter(|(path, _)| {
+            let id = path.strip_prefix("templates/").and_then(|rest| rest.split('/').next());
+            !id.is_some_and(|id| shipped.contains(id))
+        })
+        .map(|(path, hash)| (path.clone(), hash.clone()))
+        .collect();
+    let plan = plan(&visible, &local, last.as_ref());
+
+    let (mut templates_changed, mut fonts_changed) = (false, false);
+    let mut touched = |path: &str| match path.split('/').next() == Some(FONTS_AREA) {
+        true => fonts_changed = true,
+        false => templates_changed = true,
+    };
+    for (path, hash) in &plan.fetch {
+        let Some(target) = local_path(path, templates_dir, fonts_dir) else {
+            continue;
+        };
+        let content = download_blob(s3, hash)
+            .await
+            .with_context(|| format!("Failed to download {}", path))?;
+        replace(&target, &content).await?;
+        touched(path);
+    }
+    for path in &plan.delete {
+        if let Some(target) = local_path(path, templates_dir, fonts_dir) {
+            let _ = tokio::fs::remove_file(&target).await;
+            // A template whose last file went goes too.
+            if let Some(parent) = target.parent() {
+                let _ = tokio::fs::remove_dir(parent).await;
+            }
+            touched(path);
+        }
+    }
+    if templates_changed {
+        template_engine::templates_changed();
+    }
+    if fonts_changed {
+        typst_compiler::reload_fonts();
+    }
+
+    if !plan.publish.is_empty() {
+        let known: BTreeSet<&String> = remote.files.values().collect();
+        for (path, hash) in plan.publish.iter().filter_map(|(p, h)| Some((p, h.as_ref()?))) {
+            if known.contains(hash) {
+                continue;
+            }
+            let content = local_path(path, templates_dir, fonts_dir)
+                .map(std::fs::read)
+                .transpose()?
+                .unwrap_or_default();
+            upload_blob(s3, hash, content)
+                .await
+                .with_context(|| format!("Failed to upload {}", path))?;
+        }
+        let mut files = remote.files.clone();
+        for (path, hash) in &plan.publish {
+            match hash {
+                Some(hash) => files.insert(path.clone(), hash.clone()),
+                None => files.remove(path),
+            };
+        }
+        if files != remote.files {
+            remote = Manifest {
+                version: remote.version + 1,
+                files,
+            };
+            put_manifest(s3, &remote, etag.as_deref()).await?;
+            app_log!(
+                info,
+                "[assets] Published {} change(s) as version {}",
+                plan.publish.len(),
+                remote.version
+            );
+        }
+    }
+    if !plan.fetch.is_empty() || !plan.delete.is_empty() {
+        app_log!(
+            info,
+            "[assets] Synced version {}: {} file(s) updated, {} removed",
+            remote.version,
+            plan.fetch.len(),
+            plan.delete.len()
+        );
+    }
+
+    // What this instance now has in common with the bucket.
+    let state = Manifest {
+        version: remote.version,
+        files: remote
+            .files
+            .into_iter()
+            .filter(|(path, _)| visible.contains_key(path) || local.contains_key(path))
+            .collect(),
+    };
+    if load_state(templates_dir).as_ref() != Some(&state) {
+        tokio::fs::write(templates_dir.join(STATE_FILE), serde_json::to_vec(&state)?).await?;
+    }
+    Ok(state.version)
+}
+
+/// Run a pass and record its outcome for [`status`].
+pub async fn run_pass(s3: &S3Config, templates_dir: &Path) {
+    let outcome = pass(s3, templates_dir).await;
+    let mut status = STATUS.lock().unwrap_or_else(|p| p.into_inner());
+    let previous = status.as_ref().map(|s| s.version).unwrap_or_default();
+    *status = Some(match outcome {
+        Ok(version) => SyncStatus {
+            version,
+            files: load_state(templates_dir).map(|s| s.files.len()).unwrap_or_default(),
+            last_pass_at: Utc::now(),
+            last_error: None,
+        },
+        Err(e) => {
+            app_log!(warn, "[assets] Sync failed: {:#}", e);
+            SyncStatus {
+                version: previous,
+                files: load_state(templates_dir).map(|s| s.files.len()).unwrap_or_default(),
+                last_pass_at: Utc::now(),
+                last_error: Some(format!("{:#}", e)),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn files(entries: &[(&str, &str)]) -> Files {
+        entries.iter().map(|(p, h)| (p.to_string(), h.to_string())).collect()
+    }
+
+    #[test]
+    fn changes_flow_both_ways_and_local_wins_conflicts() {
+        let last = files(&[("fonts/a.ttf", "1"), ("fonts/b.ttf", "1"), ("fonts/c.ttf", "1")]);
+        // Another instance changed a and removed b; this one changed c and
+        // added d.
+        let remote = files(&[("fonts/a.ttf", "2"), ("fonts/c.ttf", "2")]);
+        let local = files(&[
+            ("fonts/a.ttf", "1"),
+            ("fonts/b.ttf", "1"),
+            ("fonts/c.ttf", "3"),
+            ("fonts/d.ttf", "1"),
+        ]);
+        let plan = plan(&remote, &local, Some(&last));
+        assert_eq!(plan.fetch, files(&[("fonts/a.ttf", "2")]));
+        assert_eq!(plan.delete, vec!["fonts/b.ttf"]);
+        assert_eq!(
+            plan.publish,
+            BTreeMap::from([
+                ("fonts/c.ttf".to_string(), Some("3".to_string())),
+                ("fonts/d.ttf".to_string(), Some("1".to_string())),
+            ])
+        );
+
+        // A first pass takes the bucket's files and publishes only new ones.
+        let plan = super::plan(&remote, &local, None);
+        assert_eq!(plan.fetch, files(&[("fonts/a.ttf", "2"), ("fonts/c.ttf", "2")]));
+        assert!(plan.delete.is_empty());
+        assert_eq!(plan.publish.keys().collect::<Vec<_>>(), vec!["fonts/b.ttf", "fonts/d.ttf"]);
+    }
+}
@@ -0,0 +1,215 @@
+// src/core/audit_log.rs
+//! Tenant activity trail and the signed compliance report built from it.
+//!
+//! Handlers [`record`] CV generations, data exports (PDF and file downloads)
+//! and deletions in `audit_log`. Rows outlive the profiles and accounts they
+//! describe, so a report still covers a deletion after the fact. The report
+//! is rendered by Typst from `templates/audit_report.typ` and signed with
+//! HMAC-SHA256 under `AUDIT_SIGNING_KEY`: the digest of the report data is
+//! printed in the document, and the signature of the PDF bytes is returned
+//! alongside it.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use graflog::app_log;
+use serde::Serialize;
+use sqlx::SqlitePool;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::core::typst_compiler;
+
+const REPORT_TEMPLATE: &str = "audit_report.typ";
+const COMPILE_TIMEOUT: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuditAction {
+    CvGenerated,
+    DataExported,
+    ProfileDeleted,
+    AccountDeleted,
+    SecretChanged,
+    TenantRenamed,
+    GenerationDeleted,
+}
+
+impl AuditAction {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::CvGenerated => "cv_generated",
+            Self::DataExported => "data_exported",
+            Self::ProfileDeleted => "profile_deleted",
+            Self::AccountDeleted => "account_deleted",
+            Self::SecretChanged => "secret_changed",
+            Self::TenantRenamed => "tenant_renamed",
+            Self::GenerationDeleted => "generation_deleted",
+        }
+    }
+}
+
+/// Record `action` of `user_email` for tenant `tenant_id`. Fire-and-forget:
+/// a failed insert is logged and never fails the request.
+pub fn record(
+    pool: &SqlitePool,
+    tenant_id: i64,
+    user_email: &str,
+    action: AuditAction,
+    subject: &str,
+) {
+    let pool = pool.clone();
+    let (user_email, subject) = (user_email.to_lowercase(), subject.to_string());
+    tokio::spawn(async move {
+        let inserted = sqlx::query(
+            "INSERT INTO audit_log (tenant_id, user_email, action, subject, created_at) \
+             VALUES (?, ?, ?, ?, ?)",
+        )
+        .bind(tenant_id)
+        .bind(&user_email)
+        .bind(action.name())
+        .bind(&subject)
+        .bind(Utc::now())
+        .execute(&pool)
+        .await;
+        if let Err(e) = inserted {
+            app_log!(
+                warn,
+                "Failed to record {} of {}: {}",
+                action.name(),
+                user_email,
+                e
+            );
+        }
+    });
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct AuditEntry {
+    pub created_at: DateTime<Utc>,
+    pub user_email: String,
+    pub action: String,
+    /// Profile or file the action was about.
+    pub subject: String,
+}
+
+/// Entries of tenant `tenant_id` in `[from, to)`, oldest first.
+pub async fn entries(
+    pool: &SqlitePool,
+    tenant_id: i64,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<Vec<AuditEntry>> {
+    let rows = sqlx::query_as::<_, AuditEntry>(
+        "SELECT created_at, user_email, action, subject FROM audit_log \
+         WHERE tenant_id = ? AND created_at >= ? AND created_at < ? ORDER BY id",
+    )
+    .bind(tenant_id)
+    .bind(from)
+    .bind(to)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows)
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct AuditTotals {
+    pub generations: usize,
+    pub exports: usize,
+    pub deletions: usize,
+}
+
+/// Everything the report template prints; written to `report.json`.
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditReport {
+    pub report_id: String,
+    pub tenant: String,
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub generated_at: DateTime<Utc>,
+    pub generated_by: String,
+    pub totals: AuditTotals,
+    pub entries: Vec<AuditEntry>,
+    /// HMAC-SHA256 of this report serialised with an empty `digest`.
+    pub digest: String,
+}
+
+impl AuditReport {
+    pub fn new(
+        tenant: &str,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        generated_by: &str,
+        entries: Vec<AuditEntry>,
+    ) -> Self {
+        let mut totals = AuditTotals::default();
+        for entry in &entries {
+            match entry.action.as_str() {
+                "cv_generated" => totals.generations += 1,
+                "data_exported" => totals.exports += 1,
+                "profile_deleted" | "account_deleted" | "generation_deleted" => totals.deletions += 1,
+                _ => {}
+            }
+        }
+        Self {
+            report_id: uuid::Uuid::new_v4().to_string(),
+            tenant: tenant.to_string(),
+            from,
+            to,
+            generated_at: Utc::now(),
+            generated_by: generated_by.to_lowercase(),
+            totals,
+            entries,
+            digest: String::new(),
+        }
+    }
+
+    /// Fill in `digest` with the signature of the report data.
+    pub fn sign(mut self, key: &[u8]) -> Result<Self> {
+        self.digest.clear();
+        let data = serde_json::to_vec(&self).context("Failed to serialize report")?;
+        self.digest = sign(key, &data);
+        Ok(self)
+    }
+}
+
+/// Signing key from `AUDIT_SIGNING_KEY`; `None` disables reports.
+pub fn signing_key() -> Option<Vec<u8>> {
+    std::env::var("AUDIT_SIGNING_KEY")
+        .ok()
+        .filter(|k| !k.trim().is_empty())
+        .map(String::into_bytes)
+}
+
+/// Hex HMAC-SHA256 of `data` under the signing key.
+pub fn sign(key: &[u8], data: &[u8]) -> String {
+    crate::utils::hmac_sha256_hex(key, data)
+}
+
+/// Compile the report to PDF in a scratch directory.
+pub async fn render_pdf(templates_dir: &Path, report: &AuditReport) -> Result<Vec<u8>> {
+    let scratch = std::env::temp_dir().join(format!("cvenom-audit-{}", report.report_id));
+    let result = render_in(templates_dir, report, &scratch).await;
+    let _ = tokio::fs::remove_dir_all(&scratch).await;
+    result
+}
+
+async fn render_in(templates_dir: &Path, report: &AuditReport, scratch: &Path) -> Result<Vec<u8>> {
+    tokio::fs::create_dir_all(scratch).await?;
+    tokio::fs::copy(
+        templates_dir.join(REPORT_TEMPLATE),
+        scratch.join("main.typ"),
+    )
+    .await
+    .with_context(|| format!("Missing {}", REPORT_TEMPLATE))?;
+    tokio::fs::write(scratch.join("report.json"), serde_json::to_vec(report)?).await?;
+
+    let compiled = typst_compiler::compile_with_timeout(
+        scratch,
+        "main.typ",
+        Vec::new(),
+        typst_compiler::Output::Pdf,
+        COMPILE_TIMEOUT,
+    )
+    .await
+    .context("Failed to compile the report")?;
+    Ok(compiled.bytes)
+}
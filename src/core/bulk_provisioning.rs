@@ -0,0 +1,341 @@
+// src/core/bulk_provisioning.rs
+//! Onboarding a company in one call: its domain tenant, the members' roles
+//! and, optionally, each member's own profile.
+//!
+//! Members come as JSON or as CSV (`email,name,role,scaffold`, header
+//! optional). Every row is validated before anything is written; one invalid
+//! row rejects the whole batch. Profiles are scaffolded first (see
+//! [`provisioning::ensure_self_profile`]), then the tenant and roles are
+//! written in a single transaction; if that fails, the profiles created by
+//! this run are removed again. Re-running a batch is safe: an existing
+//! domain tenant is reused and existing profiles are kept.
+
+use anyhow::{bail, Context, Result};
+use chrono::Utc;
+use graflog::app_log;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use crate::core::database::{get_tenant_folder_path, TenantRepository};
+use crate::core::profile_access::MemberRole;
+use crate::core::{provisioning, tenant_cache};
+
+pub const MAX_MEMBERS: usize = 1000;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BulkMember {
+    pub email: String,
+    #[serde(default)]
+    pub name: Option<String>,
+    /// `member` or `editor`; the tenant default when omitted.
+    #[serde(default)]
+    pub role: Option<String>,
+    /// Create the member's own profile now.
+    #[serde(default)]
+    pub scaffold: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct InvalidMember {
+    /// 1-based position in the batch.
+    pub row: usize,
+    pub email: String,
+    pub error: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MemberOutcome {
+    pub email: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub role: Option<String>,
+    /// The member's own profile, when scaffolded.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+    pub profile_created: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BulkProvisionReport {
+    pub domain: String,
+    pub tenant_name: String,
+    pub tenant_created: bool,
+    pub dry_run: bool,
+    pub roles_granted: usize,
+    pub profiles_created: usize,
+    pub members: Vec<MemberOutcome>,
+}
+
+/// Members of a CSV batch: `email,name,role,scaffold`, header optional.
+pub fn parse_csv(content: &str) -> Result<Vec<BulkMember>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(content.as_bytes());
+    let field = |record: &csv::StringRecord, i: usize| {
+        record
+            .get(i)
+            .filter(|v| !v.is_empty())
+            .map(str::to_string)
+    };
+    let mut members = Vec::new();
+    for (i, record) in reader.records().enumerate() {
+        let record = record.with_context(|| format!("CSV line {}", i + 1))?;
+        let email = field(&record, 0).unwrap_or_default();
+        if i == 0 && email.eq_ignore_ascii_case("email") {
+            continue;
+        }
+        if email.is_empty() && record.iter().all(str::is_empty) {
+            continue;
+        }
+        let scaffold = field(&record, 3)
+            .map(|v| matches!(v.to_lowercase().as_str(), "true" | "yes" | "1" | "y"))
+            .unwrap_or(false);
+        members.push(BulkMember {
+            email,
+            name: field(&record, 1),
+            role: field(&record, 2),
+            scaffold,
+        });
+    }
+    Ok(members)
+}
+
+/// Problems with the batch, one per offending row; empty when it is valid.
+pub fn validate(domain: &str, members: &[BulkMember]) -> Vec<InvalidMember> {
+    let suffix = format!("@{}", domain);
+    let mut seen = HashSet::new();
+    let mut invalid = Vec::new();
+    for (i, member) in members.iter().enumerate() {
+        let email = member.email.trim().to_lowercase();
+        let error = if email.is_empty() {
+            Some("Email is required".to_string())
+        } else if !email.ends_with(&suffix) || email.len() == suffix.len() {
+            Some(format!("Not an address of {}", domain))
+        } else if !seen.insert(email.clone()) {
+            Some("Listed twice".to_string())
+        } else {
+            member
+                .role
+                .as_deref()
+                .filter(|role| MemberRole::parse(role).is_none())
+                .map(|role| format!("Unknown role '{}'", role))
+        };
+        if let Some(error) = error {
+            invalid.push(InvalidMember {
+                row: i + 1,
+                email: member.email.clone(),
+                error,
+            });
+        }
+    }
+    invalid
+}
+
+/// Provision a validated batch; see the module docs.
+pub async fn provision(
+    pool: &SqlitePool,
+    data_dir: &Path,
+    templates_dir: &Path,
+    domain: &str,
+    tenant_name: &str,
+    members: &[BulkMember],
+    dry_run: bool,
+) -> Result<BulkProvisionReport> {
+    if members.len() > MAX_MEMBERS {
+        bail!("At most {} members per batch", MAX_MEMBERS);
+    }
+    let repo = TenantRepository::new(pool);
+    let existing: Option<String> = sqlx::query_scalar(
+        "SELECT tenant_name FROM tenants WHERE domain = ? AND is_active = TRUE",
+    )
+    .bind(domain)
+    .fetch_optional(pool)
+    .await?;
+    let tenant_created = existing.is_none();
+    let tenant_name = existing.unwrap_or_else(|| tenant_name.to_string());
+
+    let mut outcomes = Vec::with_capacity(members.len());
+    for member in members {
+        let email = member.email.trim().to_lowercase();
+        let mut warnings = Vec::new();
+        if let Some(personal) = repo.find_by_email_or_domain(&email).await? {
+            if personal.email.is_some() {
+                warnings.push(format!(
+                    "Has a personal account ({}), which keeps taking precedence",
+                    personal.tenant_name
+                ));
+            }
+        }
+        outcomes.push(MemberOutcome {
+            email,
+            role: member
+                .role
+                .as_deref()
+                .and_then(MemberRole::parse)
+                .map(|r| r.as_str().to_string()),
+            profile: None,
+            profile_created: false,
+            warnings,
+        });
+    }
+
+    let mut report = BulkProvisionReport {
+        domain: domain.to_string(),
+        tenant_name,
+        tenant_created,
+        dry_run,
+        roles_granted: outcomes.iter().filter(|o| o.role.is_some()).count(),
+        profiles_created: 0,
+        members: outcomes,
+    };
+    if dry_run {
+        return Ok(report);
+    }
+
+    let mut created_dirs: Vec<PathBuf> = Vec::new();
+    let scaffolded = scaffold(
+        data_dir,
+        templates_dir,
+        members,
+        &mut report.members,
+        &mut created_dirs,
+    )
+    .await;
+    let written = match scaffolded {
+        Ok(()) => write_records(pool, &report).await,
+        Err(e) => Err(e),
+    };
+    if let Err(e) = written {
+        for dir in &created_dirs {
+            if let Err(cleanup) = tokio::fs::remove_dir_all(dir).await {
+                app_log!(
+                    error,
+                    "[bulk] Failed to remove {} after a failed batch: {}",
+                    dir.display(),
+                    cleanup
+                );
+            }
+        }
+        return Err(e);
+    }
+
+    tenant_cache::invalidate_all();
+    report.profiles_created = created_dirs.len();
+    Ok(report)
+}
+
+async fn scaffold(
+    data_dir: &Path,
+    templates_dir: &Path,
+    members: &[BulkMember],
+    outcomes: &mut [MemberOutcome],
+    created_dirs: &mut Vec<PathBuf>,
+) -> Result<()> {
+    let total = members.iter().filter(|m| m.scaffold).count();
+    let mut done = 0;
+    for (member, outcome) in members.iter().zip(outcomes.iter_mut()) {
+        if !member.scaffold {
+            continue;
+        }
+        let own = provisioning::ensure_self_profile(
+            &outcome.email,
+            member.name.as_deref(),
+            data_dir,
+            templates_dir,
+        )
+        .await
+        .with_context(|| format!("Failed to scaffold a profile for {}", outcome.email))?;
+        match own {
+            Some(own) => {
+                if own.created {
+                    created_dirs
+                        .push(get_tenant_folder_path(&outcome.email, &data_dir.to_path_buf())
+                            .join(&own.profile));
+                }
+                outcome.profile_created = own.created;
+                outcome.profile = Some(own.profile);
+            }
+            None => outcome
+                .warnings
+                .push("Already has profiles; none was scaffolded".to_string()),
+        }
+        done += 1;
+        if done % 25 == 0 || done == total {
+            app_log!(info, "[bulk] Scaffolded {}/{} profiles", done, total);
+        }
+    }
+    Ok(())
+}
+
+async fn write_records(pool: &SqlitePool, report: &BulkProvisionReport) -> Result<()> {
+    let mut tx = pool.begin().await?;
+    if report.tenant_created {
+        let now = Utc::now();
+        sqlx::query(
+            "INSERT INTO tenants (email, domain, tenant_name, created_at, updated_at, is_active) \
+             VALUES (NULL, ?, ?, ?, ?, TRUE)",
+        )
+        .bind(&report.domain)
+        .bind(&report.tenant_name)
+        .bind(now)
+        .bind(now)
+        .execute(&mut *tx)
+        .await
+        .context("Failed to create the domain tenant")?;
+    }
+    for member in &report.members {
+        let Some(role) = &member.role else {
+            continue;
+        };
+        sqlx::query(
+            "INSERT INTO member_roles (email, role) VALUES (?, ?) \
+             ON CONFLICT(email) DO UPDATE SET role = excluded.role, updated_at = datetime('now')",
+        )
+        .bind(&member.email)
+        .bind(role)
+        .execute(&mut *tx)
+        .await
+        .with_context(|| format!("Failed to grant {} to {}", role, member.email))?;
+    }
+    tx.commit().await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn csv_batches_are_parsed_and_validated() {
+        let members = parse_csv(
+            "email,name,role,scaffold\n\
+             jane@acme.ch, Jane Doe, editor, yes\n\
+             john@acme.ch,,,\n\
+             \n\
+             JANE@acme.ch,Jane again\n\
+             eve@other.com,Eve,member\n\
+             bob@acme.ch,Bob,owner,no\n",
+        )
+        .unwrap();
+        assert_eq!(members.len(), 5);
+        assert_eq!(members[0].name.as_deref(), Some("Jane Doe"));
+        assert!(members[0].scaffold);
+        assert!(members[1].role.is_none() && !members[1].scaffold);
+
+        let invalid = validate("acme.ch", &members);
+        let rows: Vec<(usize, &str)> = invalid.iter().map(|i| (i.row, i.error.as_str())).collect();
+        assert_eq!(
+            rows,
+            vec![
+                (3, "Listed twice"),
+                (4, "Not an address of acme.ch"),
+                (5, "Unknown role 'owner'"),
+            ]
+        );
+    }
+}
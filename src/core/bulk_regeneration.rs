@@ -0,0 +1,488 @@
+// src/core/bulk_regeneration.rs
+//! Re-generation of a tenant's CVs after a template update.
+//!
+//! An admin [`start`]s a run for a tenant, optionally restricted to one
+//! template. Every person of every member whose latest generation is known
+//! (see [`template_versions`]) becomes an item, regenerated with the template
+//! and language it last used. Items are processed `REGENERATION_CONCURRENCY`
+//! at a time (default 2), each also waiting for a slot of its member's
+//! generation scheduler, so live generations keep their share. A member's
+//! account (see [`crate::core::owner`]) also decides the approval policy and
+//! the person tags of their items.
+//!
+//! Runs and items live in the database: an item is `pending` until its
+//! outcome is recorded as `done`, `failed` or `skipped` (the person was
+//! deleted, or is unapproved under the `block` policy). A run interrupted by a
+//! restart is picked up again by [`resume`] and only processes what is still
+//! pending. [`report`] counts the outcomes and lists the items that were not
+//! regenerated.
+
+use anyhow::{bail, Result};
+use chrono::{DateTime, Utc};
+use futures::stream::{self, StreamExt};
+use graflog::app_log;
+use serde::Serialize;
+use sqlx::SqlitePool;
+use std::path::{Path, PathBuf};
+
+use crate::core::database::{Tenant, TenantRepository};
+use crate::core::person_tags::{self, PersonTags};
+use crate::core::profile_approval::{self, ApprovalPolicy};
+use crate::core::{data_residency, generation_scheduler, owner, template_versions, FsOps};
+use crate::{CvConfig, CvGenerator};
+
+const DEFAULT_CONCURRENCY: usize = 2;
+const MAX_FAILURES_LISTED: usize = 100;
+
+fn concurrency() -> usize {
+    std::env::var("REGENERATION_CONCURRENCY")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .filter(|n| *n > 0)
+        .unwrap_or(DEFAULT_CONCURRENCY)
+}
+
+/// Where profiles, outputs and templates live.
+#[derive(Debug, Clone)]
+pub struct Directories {
+    pub data_dir: PathBuf,
+    pub output_dir: PathBuf,
+    pub templates_dir: PathBuf,
+}
+
+impl Directories {
+    fn tenant_data(&self, tenant: &str) -> PathBuf {
+        data_residency::route(&self.data_dir, tenant).join(tenant)
+    }
+
+    fn tenant_output(&self, tenant: &str) -> PathBuf {
+        data_residency::route(&self.output_dir, tenant).join(tenant)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ItemStatus {
+    Pending,
+    Done,
+    Failed,
+    Skipped,
+}
+
+impl ItemStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Done => "done",
+            Self::Failed => "failed",
+            Self::Skipped => "skipped",
+        }
+    }
+
+    fn parse(value: &str) -> Self {
+        match value {
+            "pending" => Self::Pending,
+            "done" => Self::Done,
+            "skipped" => Self::Skipped,
+            _ => Self::Failed,
+        }
+    }
+}
+
+/// A person that was not regenerated.
+#[derive(Debug, Clone, Serialize)]
+pub struct RegenerationIssue {
+    /// Member folder the person belongs to.
+    pub user: String,
+    pub profile: String,
+    pub lang: String,
+    pub status: ItemStatus,
+    pub error: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RegenerationReport {
+    pub id: String,
+    pub tenant: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
+    pub requested_by: String,
+    /// Every item has an outcome.
+    pub finished: bool,
+    pub total: usize,
+    pub pending: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub skipped: usize,
+    /// Failed and skipped items, the first 100.
+    pub issues: Vec<RegenerationIssue>,
+    pub created_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+#[derive(sqlx::FromRow)]
+struct RunRow {
+    id: String,
+    tenant: String,
+    template: Option<String>,
+    requested_by: String,
+    created_at: DateTime<Utc>,
+    finished_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+struct ItemRow {
+    user_folder: String,
+    profile: String,
+    template: String,
+    lang: String,
+    status: String,
+    error: Option<String>,
+}
+
+enum Outcome {
+    Done(String),
+    Failed(String),
+    Skipped(String),
+}
+
+/// Persons of `tenant_dir` with their last generation's template and
+/// language, restricted to `template` when given.
+async fn plan_items(tenant_dir: &Path, template: Option<&str>) -> Result<Vec<ItemRow>> {
+    let mut items = Vec::new();
+    let mut users = tokio::fs::read_dir(tenant_dir).await?;
+    while let Some(user) = users.next_entry().await? {
+        if !user.file_type().await?.is_dir() {
+            continue;
+        }
+        let user_folder = user.file_name().to_string_lossy().to_string();
+        for profile in FsOps::list_profiles(&user.path()).await? {
+            let history = template_versions::load(&user.path().join(&profile));
+            let Some(last) = history.generations.last() else {
+                continue;
+            };
+            if template.is_some_and(|t| t != last.template) {
+                continue;
+            }
+            items.push(ItemRow {
+                user_folder: user_folder.clone(),
+                profile,
+                template: last.template.clone(),
+                lang: last.lang.clone(),
+                status: ItemStatus::Pending.as_str().to_string(),
+                error: None,
+            });
+        }
+    }
+    Ok(items)
+}
+
+/// Record a run for `tenant` and its items; [`run`] processes it.
+pub async fn start(
+    pool: &SqlitePool,
+    dirs: &Directories,
+    tenant: &str,
+    requested_by: &str,
+    template: Option<&str>,
+) -> Result<RegenerationReport> {
+    let tenant_dir = dirs.tenant_data(tenant);
+    if tenant.is_empty() || tenant.contains(['/', '\\', '.']) || !tenant_dir.is_dir() {
+        bail!("No data directory for tenant '{}'", tenant);
+    }
+    let unfinished: Option<String> = sqlx::query_scalar(
+        "SELECT id FROM regeneration_runs WHERE tenant = ? AND finished_at IS NULL",
+    )
+    .bind(tenant)
+    .fetch_optional(pool)
+    .await?;
+    if let Some(id) = unfinished {
+        bail!("Regeneration {} of '{}' is still running", id, tenant);
+    }
+
+    let items = plan_items(&tenant_dir, template).await?;
+    let id = uuid::Uuid::new_v4().to_string();
+    let now = Utc::now();
+    let mut tx = pool.begin().await?;
+    sqlx::query(
+        "INSERT INTO regeneration_runs (id, tenant, template, requested_by, created_at) \
+         VALUES (?, ?, ?, ?, ?)",
+    )
+    .bind(&id)
+    .bind(tenant)
+    .bind(template)
+    .bind(requested_by)
+    .bind(now)
+    .execute(&mut *tx)
+    .await?;
+    for item in &items {
+        sqlx::query(
+            "INSERT INTO regeneration_items (run_id, user_folder, profile, template, lang, \
+             status, updated_at) VALUES (?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(&id)
+        .bind(&item.user_folder)
+        .bind(&item.profile)
+        .bind(&item.template)
+        .bind(&item.lang)
+        .bind(&item.status)
+        .bind(now)
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+    app_log!(
+        info,
+        "{} started regeneration {} of {} CVs for {}",
+        requested_by,
+        id,
+        items.len(),
+        tenant
+    );
+
+    report(pool, &id)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("Regeneration {} vanished", id))
+}
+
+/// Outcome counts and issues of run `id`.
+pub async fn report(pool: &SqlitePool, id: &str) -> Result<Option<RegenerationReport>> {
+    let Some(run) = sqlx::query_as::<_, RunRow>(
+        "SELECT id, tenant, template, requested_by, created_at, finished_at \
+         FROM regeneration_runs WHERE id = ?",
+    )
+    .bind(id)
+    .fetch_optional(pool)
+    .await?
+    else {
+        return Ok(None);
+    };
+    let items = sqlx::query_as::<_, ItemRow>(
+        "SELECT user_folder, profile, template, lang, status, error FROM regeneration_items \
+         WHERE run_id = ? ORDER BY user_folder, profile",
+    )
+    .bind(id)
+    .fetch_all(pool)
+    .await?;
+
+    let count = |status: ItemStatus| {
+        items
+            .iter()
+            .filter(|i| ItemStatus::parse(&i.status) == status)
+            .count()
+    };
+    let issues = items
+        .iter()
+        .filter(|i| matches!(ItemStatus::parse(&i.status), ItemStatus::Failed | ItemStatus::Skipped))
+        .take(MAX_FAILURES_LISTED)
+        .map(|i| RegenerationIssue {
+            user: i.user_folder.clone(),
+            profile: i.profile.clone(),
+            lang: i.lang.clone(),
+            status: ItemStatus::parse(&i.status),
+            error: i.error.clone().unwrap_or_default(),
+        })
+        .collect();
+    Ok(Some(RegenerationReport {
+        total: items.len(),
+        pending: count(ItemStatus::Pending),
+        succeeded: count(ItemStatus::Done),
+        failed: count(ItemStatus::Failed),
+        skipped: count(ItemStatus::Skipped),
+        issues,
+        finished: run.finished_at.is_some(),
+        id: run.id,
+        tenant: run.tenant,
+        template: run.template,
+        requested_by: run.requested_by,
+        created_at: run.created_at,
+        finished_at: run.finished_at,
+    }))
+}
+
+async fn regenerate(
+    dirs: &Directories,
+    tenant: Option<&Tenant>,
+    tenant_folder: &str,
+    policy: ApprovalPolicy,
+    tags: Option<&PersonTags>,
+    item: &ItemRow,
+) -> Outcome {
+    let user_dir = dirs.tenant_data(tenant_folder).join(&item.user_folder);
+    let profile_dir = user_dir.join(&item.profile);
+    if !profile_dir.is_dir() {
+        return Outcome::Skipped("The person no longer exists".to_string());
+    }
+    let approved = policy == ApprovalPolicy::Off || profile_approval::is_approved(&profile_dir);
+    if !approved && policy == ApprovalPolicy::Block {
+        return Outcome::Skipped("The person is not approved".to_string());
+    }
+
+    let mut config = CvConfig::new(&item.profile, &item.lang)
+        .with_template(item.template.clone())
+        .with_data_dir(user_dir)
+        .with_output_dir(
+            dirs.tenant_output(tenant_folder)
+                .join(&item.user_folder)
+                .join(&item.profile),
+        )
+        .with_templates_dir(dirs.templates_dir.clone())
+        .with_inputs(person_tags::typst_inputs(tags.unwrap_or(&PersonTags::default())));
+    if !approved {
+        config = config.with_watermark("DRAFT".to_string());
+    }
+
+    // The person first, then a slot (see `CvGenerator::lock`).
+    let generator = match CvGenerator::new(config) {
+        Ok(generator) => generator,
+        Err(e) => return Outcome::Failed(format!("{:#}", e)),
+    };
+    if let Err(busy) = generator.lock().await {
+        return Outcome::Failed(busy.to_string());
+    }
+    let _permit = match tenant {
+        Some(tenant) => Some(generation_scheduler::acquire_for(tenant).await),
+        None => None,
+    };
+    let generated = generator.generate().await;
+    match generated {
+        Ok(path) => Outcome::Done(
+            path.file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default(),
+        ),
+        Err(e) => Outcome::Failed(format!("{:#}", e)),
+    }
+}
+
+async fn record(pool: &SqlitePool, id: &str, item: &ItemRow, outcome: &Outcome) -> Result<()> {
+    let (status, error) = match outcome {
+        Outcome::Done(_) => (ItemStatus::Done, None),
+        Outcome::Failed(e) => (ItemStatus::Failed, Some(e.as_str())),
+        Outcome::Skipped(reason) => (ItemStatus::Skipped, Some(reason.as_str())),
+    };
+    sqlx::query(
+        "UPDATE regeneration_items SET status = ?, error = ?, updated_at = ? \
+         WHERE run_id = ? AND user_folder = ? AND profile = ?",
+    )
+    .bind(status.as_str())
+    .bind(error)
+    .bind(Utc::now())
+    .bind(id)
+    .bind(&item.user_folder)
+    .bind(&item.profile)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Process the pending items of run `id`, then mark it finished.
+pub async fn run(pool: SqlitePool, dirs: Directories, id: String) -> Result<()> {
+    let Some(run) = sqlx::query_as::<_, RunRow>(
+        "SELECT id, tenant, template, requested_by, created_at, finished_at \
+         FROM regeneration_runs WHERE id = ?",
+    )
+    .bind(&id)
+    .fetch_optional(&pool)
+    .await?
+    else {
+        bail!("Regeneration {} not found", id);
+    };
+    let pending = sqlx::query_as::<_, ItemRow>(
+        "SELECT user_folder, profile, template, lang, status, error FROM regeneration_items \
+         WHERE run_id = ? AND status = 'pending' ORDER BY user_folder, profile",
+    )
+    .bind(&id)
+    .fetch_all(&pool)
+    .await?;
+
+    // `run.tenant` is a data folder; a domain folder holds members of the
+    // domain tenant and of their own email tenants. A member without an
+    // account row gets the default policy and only the run's concurrency
+    // as a bound.
+    let tenants = TenantRepository::new(&pool).list_active().await?;
+
+    stream::iter(pending)
+        .for_each_concurrent(concurrency(), |item| {
+            let (pool, dirs, id, tenants, folder) = (&pool, &dirs, &id, &tenants, &run.tenant);
+            async move {
+                let tenant = owner::tenant_of_account(tenants, folder, &item.user_folder);
+                let policy = match tenant {
+                    Some(tenant) => profile_approval::policy_for(pool, tenant.id).await,
+                    None => ApprovalPolicy::default(),
+                };
+                let owner = owner::account_key_of(folder, &item.user_folder);
+                let tags = match person_tags::get(pool, &owner, &item.profile).await {
+                    Ok(tags) => Some(tags),
+                    Err(e) => {
+                        app_log!(warn, "Regeneration {}: no tags for {}: {}", id, item.profile, e);
+                        None
+                    }
+                };
+                let outcome =
+                    regenerate(dirs, tenant, folder, policy, tags.as_ref(), &item).await;
+                if let Outcome::Failed(e) = &outcome {
+                    app_log!(warn, "Regeneration {}: {}/{} failed: {}", id, item.user_folder, item.profile, e);
+                }
+                if let Err(e) = record(pool, id, &item, &outcome).await {
+                    app_log!(error, "Regeneration {}: failed to record {}: {}", id, item.profile, e);
+                }
+            }
+        })
+        .await;
+
+    sqlx::query("UPDATE regeneration_runs SET finished_at = ? WHERE id = ?")
+        .bind(Utc::now())
+        .bind(&id)
+        .execute(&pool)
+        .await?;
+    if let Some(report) = report(&pool, &id).await? {
+        app_log!(
+            info,
+            "Regeneration {} of {} finished: {} done, {} failed, {} skipped",
+            id,
+            report.tenant,
+            report.succeeded,
+            report.failed,
+            report.skipped
+        );
+    }
+    Ok(())
+}
+
+/// Runs a restart interrupted; [`run`] each again for what is left.
+pub async fn resume(pool: &SqlitePool) -> Result<Vec<String>> {
+    Ok(sqlx::query_scalar(
+        "SELECT id FROM regeneration_runs WHERE finished_at IS NULL ORDER BY created_at",
+    )
+    .fetch_all(pool)
+    .await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn only_persons_with_a_known_generation_are_planned() {
+        let dir = tempfile::tempdir().unwrap();
+        let user = dir.path().join("jane_at_example_com");
+        for profile in ["jane", "john", "never_generated"] {
+            std::fs::create_dir_all(user.join(profile)).unwrap();
+            std::fs::write(user.join(profile).join("cv_params.toml"), "").unwrap();
+        }
+        template_versions::record_generation(&user.join("jane"), "keyteo", "1.0.0", "fr", &[]);
+        template_versions::record_generation(&user.join("john"), "default", "1.0.0", "en", &[]);
+
+        let all = plan_items(dir.path(), None).await.unwrap();
+        let planned: Vec<(&str, &str, &str)> = all
+            .iter()
+            .map(|i| (i.profile.as_str(), i.template.as_str(), i.lang.as_str()))
+            .collect();
+        assert_eq!(planned, vec![("jane", "keyteo", "fr"), ("john", "default", "en")]);
+
+        let keyteo = plan_items(dir.path(), Some("keyteo")).await.unwrap();
+        assert_eq!(keyteo.len(), 1);
+        assert_eq!(keyteo[0].user_folder, "jane_at_example_com");
+    }
+}
@@ -0,0 +1,140 @@
+// src/core/client_logos.rs
+//! Client logos shown next to experiences.
+//!
+//! Consulting CVs often show the client of each mission. A person keeps one
+//! small logo per client in `<person>/assets/client_logos/<key>.png`, and the
+//! experiences name it by key:
+//!
+//! ```typst
+//! #dated_experience("Lead developer", company: "Acme", logo: "acme", date: "2023")
+//! ```
+//!
+//! Every template accepts `logo:`; those listing the `client_logos` feature
+//! render it. When that feature is enabled, the logos are copied to
+//! `client_logos/` in the workspace and their keys passed to Typst as
+//! `--input client_logos=acme,globex`, so a key without a logo is skipped
+//! instead of failing the compilation.
+
+use anyhow::{bail, Result};
+use image::GenericImageView;
+use std::path::{Path, PathBuf};
+
+use crate::core::image_slots::{self, ASSETS_DIR};
+
+pub const LOGOS_DIR: &str = "client_logos";
+/// Largest upload accepted.
+pub const MAX_UPLOAD_BYTES: usize = 2 * 1024 * 1024;
+/// Logos are scaled down to fit this many pixels on each side.
+const MAX_SIDE: u32 = 256;
+
+/// Logo keys: lowercase letters, digits and `_`, like image slot names.
+pub fn valid_key(key: &str) -> bool {
+    image_slots::valid_name(key)
+}
+
+fn logos_dir(person_dir: &Path) -> PathBuf {
+    person_dir.join(ASSETS_DIR).join(LOGOS_DIR)
+}
+
+pub fn logo_path(person_dir: &Path, key: &str) -> PathBuf {
+    logos_dir(person_dir).join(format!("{}.png", key))
+}
+
+/// Keys of the person's logos, sorted.
+pub fn list(person_dir: &Path) -> Vec<String> {
+    keys_in(&logos_dir(person_dir))
+}
+
+/// Keys of the logos copied to `workspace` by [`copy_to_workspace`].
+pub fn in_workspace(workspace: &Path) -> Vec<String> {
+    keys_in(&workspace.join(LOGOS_DIR))
+}
+
+fn keys_in(dir: &Path) -> Vec<String> {
+    let mut keys: Vec<String> = std::fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter_map(|entry| {
+                    let name = entry.file_name().to_string_lossy().to_string();
+                    let key = name.strip_suffix(".png")?;
+                    valid_key(key).then(|| key.to_string())
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    keys.sort();
+    keys
+}
+
+/// `bytes` as a PNG of at most [`MAX_SIDE`] pixels per side.
+pub async fn to_logo_png(bytes: Vec<u8>) -> Result<Vec<u8>> {
+    if bytes.len() > MAX_UPLOAD_BYTES {
+        bail!("Logos are limited to {} MB", MAX_UPLOAD_BYTES / (1024 * 1024));
+    }
+    let png = image_slots::to_png(bytes).await?;
+    tokio::task::spawn_blocking(move || -> Result<Vec<u8>> {
+        let img = image::load_from_memory_with_format(&png, image::ImageFormat::Png)?;
+        let (width, height) = img.dimensions();
+        if width <= MAX_SIDE && height <= MAX_SIDE {
+            return Ok(png);
+        }
+        let mut out = Vec::new();
+        img.thumbnail(MAX_SIDE, MAX_SIDE)
+            .write_to(&mut std::io::Cursor::new(&mut out), image::ImageFormat::Png)?;
+        Ok(out)
+    })
+    .await?
+}
+
+/// Copy the person's logos to `client_logos/` under `workspace`; returns the
+/// keys copied. Files that are not PNG are returned apart.
+pub fn copy_to_workspace(
+    person_dir: &Path,
+    workspace: &Path,
+) -> Result<(Vec<String>, Vec<String>)> {
+    let (mut copied, mut invalid) = (Vec::new(), Vec::new());
+    for key in list(person_dir) {
+        let bytes = std::fs::read(logo_path(person_dir, &key))?;
+        if !image_slots::is_png(&bytes) {
+            invalid.push(key);
+            continue;
+        }
+        let dir = workspace.join(LOGOS_DIR);
+        std::fs::create_dir_all(&dir)?;
+        std::fs::write(dir.join(format!("{}.png", key)), bytes)?;
+        copied.push(key);
+    }
+    Ok((copied, invalid))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn logos_are_listed_by_key_and_scaled_down() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = logos_dir(tmp.path());
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let mut big = Vec::new();
+        image::DynamicImage::new_rgba8(1024, 512)
+            .write_to(&mut std::io::Cursor::new(&mut big), image::ImageFormat::Png)
+            .unwrap();
+        let logo = to_logo_png(big).await.unwrap();
+        let scaled = image::load_from_memory(&logo).unwrap();
+        assert_eq!(scaled.dimensions(), (256, 128));
+
+        std::fs::write(dir.join("globex.png"), &logo).unwrap();
+        std::fs::write(dir.join("acme.png"), &logo).unwrap();
+        std::fs::write(dir.join("Not A Key.png"), &logo).unwrap();
+        assert_eq!(list(tmp.path()), vec!["acme", "globex"]);
+
+        let workspace = tmp.path().join("ws");
+        let (copied, invalid) = copy_to_workspace(tmp.path(), &workspace).unwrap();
+        assert_eq!(copied.len(), 2);
+        assert!(invalid.is_empty());
+        assert_eq!(in_workspace(&workspace), vec!["acme", "globex"]);
+    }
+}
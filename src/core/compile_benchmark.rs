@@ -0,0 +1,210 @@
+// src/core/compile_benchmark.rs
+//! Compile throughput benchmark for capacity planning.
+//!
+//! Runs N compilations of the sample profile (the same scratch-dir compile as
+//! the template health check) across the installed templates, `concurrency`
+//! at a time, and reports latency percentiles plus the CPU time and peak
+//! memory of the server, which compiles in-process. Benchmarks bypass the
+//! generation scheduler, so they measure raw capacity — and compete with live
+//! traffic while they run. Only one benchmark runs at a time.
+
+use anyhow::{bail, Result};
+use futures::stream::{self, StreamExt};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::core::template_health;
+
+pub const MAX_RUNS: usize = 200;
+pub const MAX_CONCURRENCY: usize = 32;
+
+static RUNNING: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TemplateTiming {
+    pub template: String,
+    pub runs: usize,
+    pub failed: usize,
+    pub p50_ms: u64,
+    pub p95_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BenchmarkReport {
+    pub runs: usize,
+    pub concurrency: usize,
+    pub cores: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub wall_ms: u64,
+    /// Successful compilations per minute at this concurrency.
+    pub per_minute: f64,
+    pub p50_ms: u64,
+    pub p95_ms: u64,
+    pub max_ms: u64,
+    /// CPU time (user + system) used by the server during the run.
+    pub cpu_seconds: f64,
+    /// Average share of all cores kept busy by Typst during the run.
+    pub cpu_utilization: f64,
+    /// Largest resident set of the server since it started.
+    pub peak_compile_rss_mb: Option<f64>,
+    pub templates: Vec<TemplateTiming>,
+    /// First few compile errors, for diagnosing failures.
+    pub errors: Vec<String>,
+}
+
+/// Nearest-rank percentile of sorted `values`.
+fn percentile(sorted: &[u64], p: f64) -> u64 {
+    if sorted.is_empty() {
+        return 0;
+    }
+    let rank = ((p / 100.0) * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+/// CPU time and max RSS (KiB on Linux) of this process; compilations run
+/// in-process.
+#[cfg(unix)]
+fn process_usage() -> Option<(Duration, u64)> {
+    let mut usage = std::mem::MaybeUninit::<libc::rusage>::uninit();
+    // SAFETY: getrusage fills the struct it is given and returns 0 on success.
+    if unsafe { libc::getrusage(libc::RUSAGE_SELF, usage.as_mut_ptr()) } != 0 {
+        return None;
+    }
+    // SAFETY: initialized by the successful call above.
+    let usage = unsafe { usage.assume_init() };
+    let time = |tv: libc::timeval| {
+        Duration::from_secs(tv.tv_sec as u64) + Duration::from_micros(tv.tv_usec as u64)
+    };
+    Some((
+        time(usage.ru_utime) + time(usage.ru_stime),
+        usage.ru_maxrss as u64,
+    ))
+}
+
+#[cfg(not(unix))]
+fn process_usage() -> Option<(Duration, u64)> {
+    None
+}
+
+struct RunGuard;
+
+impl Drop for RunGuard {
+    fn drop(&mut self) {
+        RUNNING.store(false, Ordering::SeqCst);
+    }
+}
+
+/// Compile the sample profile `runs` times, cycling through `templates`.
+pub async fn run(
+    templates_dir: &Path,
+    templates: &[String],
+    runs: usize,
+    concurrency: usize,
+) -> Result<BenchmarkReport> {
+    if templates.is_empty() {
+        bail!("No templates to benchmark");
+    }
+    if RUNNING.swap(true, Ordering::SeqCst) {
+        bail!("A benchmark is already running");
+    }
+    let _guard = RunGuard;
+    let runs = runs.clamp(1, MAX_RUNS);
+    let concurrency = concurrency.clamp(1, MAX_CONCURRENCY);
+
+    let before = process_usage();
+    let started = Instant::now();
+    let results: Vec<(String, u64, Result<(), String>)> =
+        stream::iter((0..runs).map(|i| templates[i % templates.len()].clone()))
+            .map(|template| async move {
+                let compile_started = Instant::now();
+                let result = template_health::check_template(templates_dir, &template).await;
+                (
+                    template,
+                    compile_started.elapsed().as_millis() as u64,
+                    result,
+                )
+            })
+            .buffer_unordered(concurrency)
+            .collect()
+            .await;
+    let wall = started.elapsed();
+    let after = process_usage();
+
+    let mut all = Vec::new();
+    let mut by_template: BTreeMap<String, (Vec<u64>, usize)> = BTreeMap::new();
+    let mut errors = Vec::new();
+    for (template, ms, result) in results {
+        let entry = by_template.entry(template.clone()).or_default();
+        match result {
+            Ok(()) => {
+                all.push(ms);
+                entry.0.push(ms);
+            }
+            Err(e) => {
+                entry.1 += 1;
+                if errors.len() < 5 {
+                    errors.push(format!("{}: {}", template, e));
+                }
+            }
+        }
+    }
+    all.sort_unstable();
+
+    let cores = std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1);
+    let cpu = match (before, after) {
+        (Some((cpu_before, _)), Some((cpu_after, _))) => cpu_after.saturating_sub(cpu_before),
+        _ => Duration::ZERO,
+    };
+    let wall_secs = wall.as_secs_f64().max(f64::EPSILON);
+
+    Ok(BenchmarkReport {
+        runs,
+        concurrency,
+        cores,
+        succeeded: all.len(),
+        failed: runs - all.len(),
+        wall_ms: wall.as_millis() as u64,
+        per_minute: all.len() as f64 * 60.0 / wall_secs,
+        p50_ms: percentile(&all, 50.0),
+        p95_ms: percentile(&all, 95.0),
+        max_ms: all.last().copied().unwrap_or(0),
+        cpu_seconds: cpu.as_secs_f64(),
+        cpu_utilization: cpu.as_secs_f64() / (wall_secs * cores as f64),
+        peak_compile_rss_mb: after.map(|(_, kib)| kib as f64 / 1024.0),
+        templates: by_template
+            .into_iter()
+            .map(|(template, (mut timings, failed))| {
+                timings.sort_unstable();
+                TemplateTiming {
+                    template,
+                    runs: timings.len() + failed,
+                    failed,
+                    p50_ms: percentile(&timings, 50.0),
+                    p95_ms: percentile(&timings, 95.0),
+                }
+            })
+            .collect(),
+        errors,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nearest_rank_percentiles() {
+        let values: Vec<u64> = (1..=20).collect();
+        assert_eq!(percentile(&values, 50.0), 10);
+        assert_eq!(percentile(&values, 95.0), 19);
+        assert_eq!(percentile(&values, 100.0), 20);
+        assert_eq!(percentile(&[7], 95.0), 7);
+        assert_eq!(percentile(&[], 50.0), 0);
+    }
+}
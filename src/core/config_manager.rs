@@ -4,13 +4,86 @@
 use anyhow::{Context, Result};
 use graflog::app_log;
 use serde::Deserialize;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 
 #[derive(Debug, Clone)]
 pub struct ConfigManager {
     pub environment: EnvironmentConfig,
     pub service: ServiceConfig,
     pub cv: Option<CvConfig>,
+    /// In-process HTTPS; `None` = plain HTTP (the usual reverse-proxy setup).
+    pub tls: Option<TlsSettings>,
+    pub features: FeatureFlags,
+}
+
+/// Optional features of the deployment, settled once at startup and
+/// installed with [`set_features`].
+#[derive(Debug, Clone)]
+pub struct FeatureFlags {
+    /// Watching, commenting on and reviewing the profiles of other members.
+    pub sharing: bool,
+    pub pdf_a: bool,
+    /// Templates uploaded by a tenant for its own use.
+    pub tenant_templates: bool,
+    /// `pandoc`, which DOCX generation runs, is on the PATH.
+    pub docx_export: bool,
+}
+
+impl Default for FeatureFlags {
+    fn default() -> Self {
+        Self {
+            sharing: true,
+            pdf_a: true,
+            tenant_templates: true,
+            docx_export: true,
+        }
+    }
+}
+
+impl FeatureFlags {
+    /// Names accepted in `CVENOM_DISABLED_FEATURES`.
+    pub const NAMES: &[&str] = &["sharing", "pdf_a", "tenant_templates"];
+
+    /// `CVENOM_DISABLED_FEATURES` lists the features turned off,
+    /// comma-separated; DOCX export is on when `pandoc` is found.
+    fn load() -> Self {
+        let disabled = std::env::var("CVENOM_DISABLED_FEATURES").unwrap_or_default();
+        let on = |name: &str| {
+            !disabled
+                .split(',')
+                .any(|f| f.trim().eq_ignore_ascii_case(name))
+        };
+        Self {
+            sharing: on("sharing"),
+            pdf_a: on("pdf_a"),
+            tenant_templates: on("tenant_templates"),
+            docx_export: ConfigManager::docx_available(),
+        }
+    }
+}
+
+static FEATURES: OnceLock<FeatureFlags> = OnceLock::new();
+
+/// Install the flags [`ConfigManager::load`] read; once, before serving.
+pub fn set_features(flags: FeatureFlags) {
+    let _ = FEATURES.set(flags);
+}
+
+/// The deployment's features; all on when none were installed (tests and
+/// the CLI).
+pub fn features() -> &'static FeatureFlags {
+    FEATURES.get_or_init(FeatureFlags::default)
+}
+
+/// Certificate chain + private key (PEM) for serving HTTPS directly.
+/// Requires building with `--features tls`.
+#[derive(Debug, Clone)]
+pub struct TlsSettings {
+    pub cert_path: PathBuf,
+    pub key_path: PathBuf,
+    /// How often the files are checked for renewal (CVENOM_TLS_RELOAD_SECS, default 300).
+    pub reload_interval_secs: u64,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -42,14 +115,46 @@ impl ConfigManager {
     pub fn load() -> Result<Self> {
         let environment = Self::load_environment()?;
         let service = Self::load_service()?;
+        let tls = Self::load_tls()?;
+        let features = FeatureFlags::load();
 
         Ok(Self {
             environment,
             service,
             cv: None,
+            tls,
+            features,
         })
     }
 
+    /// Load optional TLS settings. Both CVENOM_TLS_CERT and CVENOM_TLS_KEY must
+    /// be set together.
+    fn load_tls() -> Result<Option<TlsSettings>> {
+        let cert = std::env::var("CVENOM_TLS_CERT")
+            .ok()
+            .filter(|v| !v.is_empty());
+        let key = std::env::var("CVENOM_TLS_KEY")
+            .ok()
+            .filter(|v| !v.is_empty());
+
+        match (cert, key) {
+            (None, None) => Ok(None),
+            (Some(cert), Some(key)) => {
+                let reload_interval_secs = std::env::var("CVENOM_TLS_RELOAD_SECS")
+                    .ok()
+                    .and_then(|v| v.parse::<u64>().ok())
+                    .unwrap_or(300);
+                app_log!(info, "TLS enabled, cert: {}, key: {}", cert, key);
+                Ok(Some(TlsSettings {
+                    cert_path: PathBuf::from(cert),
+                    key_path: PathBuf::from(key),
+                    reload_interval_secs,
+                }))
+            }
+            _ => anyhow::bail!("CVENOM_TLS_CERT and CVENOM_TLS_KEY must be set together"),
+        }
+    }
+
     /// Load environment configuration from mandatory environment variables
     fn load_environment() -> Result<EnvironmentConfig> {
         app_log!(info, "Loading environment configuration from env vars");
@@ -143,6 +248,290 @@ impl ConfigManager {
     }
 }
 
+/// Outcome of [`ConfigManager::validate`]: every problem found, in one place.
+#[derive(Debug, Default)]
+pub struct ConfigReport {
+    pub errors: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+impl ConfigReport {
+    fn error(&mut self, msg: impl Into<String>) {
+        self.errors.push(msg.into());
+    }
+
+    fn warn(&mut self, msg: impl Into<String>) {
+        self.warnings.push(msg.into());
+    }
+
+    pub fn has_errors(&self) -> bool {
+        !self.errors.is_empty()
+    }
+}
+
+impl std::fmt::Display for ConfigReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "Configuration check: {} error(s), {} warning(s)",
+            self.errors.len(),
+            self.warnings.len()
+        )?;
+        for e in &self.errors {
+            writeln!(f, "  ✗ {}", e)?;
+        }
+        for w in &self.warnings {
+            writeln!(f, "  ! {}", w)?;
+        }
+        Ok(())
+    }
+}
+
+/// Optional numeric env vars that silently fall back to a default when unparseable.
+const NUMERIC_ENV_VARS: &[&str] = &[
+    "BENCH_MATCH_CONCURRENCY",
+    "BENCH_MATCH_MAX_PROFILES",
+    "CVENOM_TLS_RELOAD_SECS",
+    "DATA_RETENTION_DAYS",
+    "DISK_MIN_FREE_INODES",
+    "DISK_MIN_FREE_MB",
+    "EVENT_EXPORT_FLUSH_SECS",
+    "FIREBASE_KEYS_REFRESH_SECS",
+    "GENERATION_LOCK_WAIT_SECS",
+    "GENERATION_MAX_CONCURRENCY",
+    "GENERATION_TIMEOUT_SECS",
+    "JOB_MATCH_CACHE_DAYS",
+    "PROFILE_REFRESH_DAYS",
+    "TEMPLATE_HEALTH_INTERVAL_SECS",
+    "TEMPLATE_HEALTH_TIMEOUT_SECS",
+];
+
+impl ConfigManager {
+    /// Whether `pandoc`, which DOCX generation runs, is on the PATH.
+    pub fn docx_available() -> bool {
+        std::process::Command::new("pandoc")
+            .arg("--version")
+            .output()
+            .is_ok_and(|o| o.status.success())
+    }
+
+    /// Check everything the server needs before it binds: directories exist
+    /// (or can be created) and are writable, templates are present, the port is
+    /// free, and related env vars are set consistently.
+    pub fn validate(&self, port: u16) -> ConfigReport {
+        let mut report = ConfigReport::default();
+        let env = &self.environment;
+
+        check_writable_dir(
+            &mut report,
+            "CVENOM_TENANT_DATA_PATH",
+            &env.tenant_data_path,
+        );
+        check_writable_dir(&mut report, "CVENOM_OUTPUT_PATH", &env.output_path);
+        if let Some(db_dir) = env
+            .database_path
+            .parent()
+            .filter(|p| !p.as_os_str().is_empty())
+        {
+            check_writable_dir(&mut report, "CVENOM_DATABASE_PATH (parent)", db_dir);
+        }
+        check_templates_dir(&mut report, &env.templates_path);
+
+        if let Err(e) = std::net::TcpListener::bind(("0.0.0.0", port)) {
+            report.error(format!(
+                "ROCKET_PORT {} is not available ({}) — stop the other process or pick another port",
+                port, e
+            ));
+        }
+
+        if !self.features.docx_export {
+            report.warn("`pandoc` binary not found on PATH — DOCX generation is unavailable");
+        }
+        if let Ok(disabled) = std::env::var("CVENOM_DISABLED_FEATURES") {
+            for name in disabled.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+                if !FeatureFlags::NAMES.iter().any(|known| known.eq_ignore_ascii_case(name)) {
+                    report.warn(format!(
+                        "CVENOM_DISABLED_FEATURES names unknown feature '{}' (known: {})",
+                        name,
+                        FeatureFlags::NAMES.join(", ")
+                    ));
+                }
+            }
+        }
+
+        if let Some(tls) = &self.tls {
+            if !cfg!(feature = "tls") {
+                report.error("CVENOM_TLS_CERT/CVENOM_TLS_KEY are set but this binary was built without `--features tls`");
+            }
+            for (name, path) in [
+                ("CVENOM_TLS_CERT", &tls.cert_path),
+                ("CVENOM_TLS_KEY", &tls.key_path),
+            ] {
+                if let Err(e) = std::fs::File::open(path) {
+                    report.error(format!(
+                        "{} ({}) cannot be read: {}",
+                        name,
+                        path.display(),
+                        e
+                    ));
+                }
+            }
+        }
+
+        if let Some(static_dir) = std::env::var("CVENOM_STATIC_DIR")
+            .ok()
+            .filter(|v| !v.trim().is_empty())
+        {
+            if !Path::new(&static_dir).join("index.html").is_file() {
+                report.error(format!(
+                    "CVENOM_STATIC_DIR ({}) has no index.html — point it at the frontend build output",
+                    static_dir
+                ));
+            }
+        }
+
+        self.check_env_consistency(&mut report);
+        report
+    }
+
+    fn check_env_consistency(&self, report: &mut ConfigReport) {
+        let set = |name: &str| std::env::var(name).is_ok_and(|v| !v.trim().is_empty());
+
+        if !set("CVENOM_GOOGLE_PROJECT_ID") {
+            report
+                .error("CVENOM_GOOGLE_PROJECT_ID is not set — Firebase tokens cannot be verified");
+        }
+
+        for (name, value) in [
+            (
+                "JOB_MATCHING_API_URL",
+                Some(self.service.job_matching_url.clone()),
+            ),
+            ("CV_SERVICE_URL", std::env::var("CV_SERVICE_URL").ok()),
+        ] {
+            if let Some(url) = value {
+                if !(url.starts_with("http://") || url.starts_with("https://")) {
+                    report.error(format!("{} must be an http(s) URL, got '{}'", name, url));
+                }
+            }
+        }
+
+        if self.service.timeout_seconds == 0 {
+            report.error("SERVICE_TIMEOUT must be greater than 0");
+        }
+
+        for name in NUMERIC_ENV_VARS {
+            if let Ok(v) = std::env::var(name) {
+                if v.trim().parse::<u64>().is_err() {
+                    report.warn(format!(
+                        "{}='{}' is not a number — the default is used",
+                        name, v
+                    ));
+                }
+            }
+        }
+
+        if set("EVENT_EXPORT_PUSH_URL") && !set("EVENT_EXPORT_DIR") {
+            report.warn("EVENT_EXPORT_PUSH_URL is set but EVENT_EXPORT_DIR is not — no events will be exported");
+        }
+        if set("STRIPE_SECRET_KEY") != set("STRIPE_PUBLISHABLE_KEY") {
+            report.warn("Only one of STRIPE_SECRET_KEY / STRIPE_PUBLISHABLE_KEY is set — payments will fail");
+        }
+        if let Ok(mode) = std::env::var("AUTH_STARTUP_MODE") {
+            if !matches!(mode.to_lowercase().as_str(), "strict" | "degraded") {
+                report.warn(format!(
+                    "AUTH_STARTUP_MODE='{}' is unknown — using strict",
+                    mode
+                ));
+            }
+        }
+        if std::env::var("AUTH_STARTUP_MODE").is_ok_and(|m| m.eq_ignore_ascii_case("degraded"))
+            && !set("FIREBASE_KEYS_FILE")
+        {
+            report.warn("AUTH_STARTUP_MODE=degraded without FIREBASE_KEYS_FILE — an offline start rejects every login");
+        }
+    }
+}
+
+/// A directory must exist and be writable, or be creatable under a writable ancestor.
+fn check_writable_dir(report: &mut ConfigReport, name: &str, dir: &Path) {
+    if dir.exists() {
+        if !dir.is_dir() {
+            report.error(format!("{} ({}) is not a directory", name, dir.display()));
+            return;
+        }
+        let probe = dir.join(format!(".cvenom-write-check-{}", std::process::id()));
+        match std::fs::write(&probe, b"") {
+            Ok(()) => {
+                let _ = std::fs::remove_file(&probe);
+            }
+            Err(e) => report.error(format!(
+                "{} ({}) is not writable: {} — fix ownership/permissions",
+                name,
+                dir.display(),
+                e
+            )),
+        }
+        return;
+    }
+
+    match dir.ancestors().skip(1).find(|a| a.exists()) {
+        Some(ancestor)
+            if ancestor
+                .metadata()
+                .map(|m| !m.permissions().readonly())
+                .unwrap_or(false) =>
+        {
+            report.warn(format!("{} ({}) does not exist yet and will be created", name, dir.display()));
+        }
+        _ => report.error(format!(
+            "{} ({}) does not exist and cannot be created — create it or point the variable elsewhere",
+            name,
+            dir.display()
+        )),
+    }
+}
+
+fn check_templates_dir(report: &mut ConfigReport, dir: &Path) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        report.error(format!(
+            "CVENOM_TEMPLATES_PATH ({}) does not exist or is unreadable — point it at the repo's templates/ folder",
+            dir.display()
+        ));
+        return;
+    };
+
+    let templates = entries
+        .flatten()
+        .filter(|e| e.path().join("main.typ").is_file())
+        .count();
+    if templates == 0 {
+        report.error(format!(
+            "CVENOM_TEMPLATES_PATH ({}) contains no templates (no <name>/main.typ)",
+            dir.display()
+        ));
+    }
+    if !dir.join("default").join("main.typ").is_file() {
+        report.error(format!(
+            "CVENOM_TEMPLATES_PATH ({}) has no 'default' template — unknown template requests fall back to it",
+            dir.display()
+        ));
+    }
+    for shared in [
+        "profile_template.toml",
+        "experiences_template.typ",
+        "common.typ",
+        "font_config.typ",
+    ] {
+        if !dir.join(shared).is_file() {
+            report.warn(format!(
+                "Templates directory is missing shared file {}",
+                shared
+            ));
+        }
+    }
+}
+
 impl CvConfig {
     /// Get profile configuration file path
     pub fn profile_config_path(&self) -> PathBuf {
@@ -172,3 +561,32 @@ impl CvConfig {
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn templates_dir_without_default_is_an_error() {
+        let tmp = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(tmp.path().join("tech")).unwrap();
+        std::fs::write(tmp.path().join("tech/main.typ"), "").unwrap();
+
+        let mut report = ConfigReport::default();
+        check_templates_dir(&mut report, tmp.path());
+        assert_eq!(report.errors.len(), 1);
+        assert!(report.errors[0].contains("'default'"));
+    }
+
+    #[test]
+    fn missing_dir_under_writable_parent_is_only_a_warning() {
+        let tmp = tempfile::tempdir().unwrap();
+        let mut report = ConfigReport::default();
+        check_writable_dir(
+            &mut report,
+            "CVENOM_OUTPUT_PATH",
+            &tmp.path().join("out/pdfs"),
+        );
+        assert!(!report.has_errors());
+        assert_eq!(report.warnings.len(), 1);
+    }
+}
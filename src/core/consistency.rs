@@ -0,0 +1,254 @@
+// src/core/consistency.rs
+//! Reconciling database records with a user's profile directories.
+//!
+//! Comments, watches, person tags and attributes, and editor drafts name
+//! profiles by directory name, so a directory renamed or removed by hand
+//! leaves them pointing at nothing. [`plan`] lists those records, and the
+//! profile name index ([`crate::core::profile_lookup`]) when it is out of
+//! date. A record whose
+//! profile is gone is moved to the one remaining profile with the same
+//! accent- and case-insensitive name if there is exactly one, and deleted
+//! otherwise. [`apply`] performs the fixes.
+//!
+//! These records belong to the account whose folder holds the profile (see
+//! [`crate::core::owner`]), so only that folder counts. Generation history
+//! and skills are read from the profile directories themselves
+//! ([`crate::core::template_versions`], the CV files), so they cannot drift;
+//! deleted generations and jobs are history of what was, and stay.
+
+use anyhow::Result;
+use graflog::app_log;
+use serde::Serialize;
+use sqlx::SqlitePool;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+use crate::core::{owner, person_tags, profile_comments, profile_lookup, profile_watch, FsOps};
+use crate::utils::fold_name;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecordKind {
+    Comments,
+    Watches,
+    /// Person tags and attributes.
+    Tags,
+    Draft,
+    NameIndex,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum Fix {
+    /// Point the records at this profile instead.
+    Move { to: String },
+    Delete,
+    Rebuild,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Mismatch {
+    pub kind: RecordKind,
+    /// Profile the records name; the draft's path for drafts.
+    pub target: String,
+    /// Rows concerned.
+    pub records: usize,
+    pub fix: Fix,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ConsistencyReport {
+    pub email: String,
+    /// Account key the comments, watches and tags are stored under.
+    pub owner: String,
+    /// Profile directories found.
+    pub profiles: usize,
+    pub mismatches: Vec<Mismatch>,
+    /// False for a dry run.
+    pub applied: bool,
+}
+
+/// Where records naming the missing `profile` should go, among `present`.
+fn fix_for(profile: &str, present: &BTreeSet<String>) -> Fix {
+    let folded = fold_name(profile);
+    let mut candidates = present.iter().filter(|p| fold_name(p) == folded);
+    match (candidates.next(), candidates.next()) {
+        (Some(to), None) => Fix::Move { to: to.clone() },
+        _ => Fix::Delete,
+    }
+}
+
+async fn count_by_profile(
+    pool: &SqlitePool,
+    table: &str,
+    owner: &str,
+) -> Result<Vec<(String, i64)>> {
+    let rows = sqlx::query_as::<_, (String, i64)>(&format!(
+        "SELECT profile, COUNT(*) FROM {} WHERE owner = ? GROUP BY profile",
+        table
+    ))
+    .bind(owner)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows)
+}
+
+/// Records of `email` that name profiles not on disk.
+pub async fn plan(pool: &SqlitePool, email: &str, tenant_dir: &Path) -> Result<ConsistencyReport> {
+    let own: BTreeSet<String> = FsOps::list_profiles(tenant_dir).await?.into_iter().collect();
+    let owner = owner::account_key(email);
+    let mut mismatches = Vec::new();
+
+    for (tables, kind) in [
+        (&["profile_comments"][..], RecordKind::Comments),
+        (&["profile_watches"][..], RecordKind::Watches),
+        (&["person_tags", "person_attributes"][..], RecordKind::Tags),
+    ] {
+        let mut counts: BTreeMap<String, i64> = BTreeMap::new();
+        for table in tables {
+            for (profile, records) in count_by_profile(pool, table, &owner).await? {
+                *counts.entry(profile).or_default() += records;
+            }
+        }
+        for (profile, records) in counts {
+            if !own.contains(&profile) {
+                mismatches.push(Mismatch {
+                    kind,
+                    fix: fix_for(&profile, &own),
+                    target: profile,
+                    records: records as usize,
+                });
+            }
+        }
+    }
+
+    let drafts: Vec<String> =
+        sqlx::query_scalar("SELECT path FROM file_drafts WHERE user_email = ? ORDER BY path")
+            .bind(email.to_lowercase())
+            .fetch_all(pool)
+            .await?;
+    for path in drafts {
+        let Some((profile, file)) = path.split_once('/') else {
+            continue;
+        };
+        if tenant_dir.join(profile).is_dir() {
+            continue;
+        }
+        let fix = match fix_for(profile, &own) {
+            Fix::Move { to } => Fix::Move {
+                to: format!("{}/{}", to, file),
+            },
+            fix => fix,
+        };
+        mismatches.push(Mismatch {
+            kind: RecordKind::Draft,
+            target: path,
+            records: 1,
+            fix,
+        });
+    }
+
+    let index_path = tenant_dir.join(profile_lookup::INDEX_FILE);
+    if index_path.exists() {
+        let indexed: BTreeSet<String> = std::fs::read_to_string(&index_path)
+            .ok()
+            .and_then(|raw| serde_json::from_str::<BTreeMap<String, Vec<String>>>(&raw).ok())
+            .map(|index| index.into_values().flatten().collect())
+            .unwrap_or_default();
+        if indexed != own {
+            mismatches.push(Mismatch {
+                kind: RecordKind::NameIndex,
+                target: profile_lookup::INDEX_FILE.to_string(),
+                records: indexed.symmetric_difference(&own).count(),
+                fix: Fix::Rebuild,
+            });
+        }
+    }
+
+    Ok(ConsistencyReport {
+        email: email.to_lowercase(),
+        owner,
+        profiles: own.len(),
+        mismatches,
+        applied: false,
+    })
+}
+
+/// Perform the fixes of `report`.
+pub async fn apply(
+    pool: &SqlitePool,
+    tenant_dir: &Path,
+    mut report: ConsistencyReport,
+) -> Result<ConsistencyReport> {
+    let owner = report.owner.clone();
+    for mismatch in &report.mismatches {
+        let target = mismatch.target.as_str();
+        match (mismatch.kind, &mismatch.fix) {
+            (RecordKind::Comments, Fix::Move { to }) => {
+                profile_comments::rename_profile(pool, &owner, target, to).await?
+            }
+            (RecordKind::Comments, _) => {
+                profile_comments::delete_profile(pool, &owner, target).await?
+            }
+            (RecordKind::Watches, Fix::Move { to }) => {
+                profile_watch::rename_profile(pool, &owner, target, to).await?
+            }
+            (RecordKind::Watches, _) => profile_watch::delete_profile(pool, &owner, target).await?,
+            (RecordKind::Tags, Fix::Move { to }) => {
+                person_tags::rename_profile(pool, &owner, target, to).await?
+            }
+            (RecordKind::Tags, _) => person_tags::delete_profile(pool, &owner, target).await?,
+            (RecordKind::Draft, Fix::Move { to }) => {
+                // A draft already saved under the new path wins.
+                sqlx::query(
+                    "UPDATE OR IGNORE file_drafts SET path = ? WHERE user_email = ? AND path = ?",
+                )
+                .bind(to)
+                .bind(&report.email)
+                .bind(target)
+                .execute(pool)
+                .await?;
+                sqlx::query("DELETE FROM file_drafts WHERE user_email = ? AND path = ?")
+                    .bind(&report.email)
+                    .bind(target)
+                    .execute(pool)
+                    .await?;
+            }
+            (RecordKind::Draft, _) => {
+                sqlx::query("DELETE FROM file_drafts WHERE user_email = ? AND path = ?")
+                    .bind(&report.email)
+                    .bind(target)
+                    .execute(pool)
+                    .await?;
+            }
+            (RecordKind::NameIndex, _) => profile_lookup::refresh(tenant_dir).await?,
+        }
+    }
+    app_log!(
+        info,
+        "Reconciled {} record groups of {} with its profiles",
+        report.mismatches.len(),
+        report.email
+    );
+    report.applied = true;
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_profiles_move_to_a_single_lookalike() {
+        let present: BTreeSet<String> =
+            ["elodie", "jose", "josé"].iter().map(|s| s.to_string()).collect();
+        assert_eq!(
+            fix_for("Élodie", &present),
+            Fix::Move {
+                to: "elodie".to_string()
+            }
+        );
+        assert_eq!(fix_for("Jose", &present), Fix::Delete);
+        assert_eq!(fix_for("marc", &present), Fix::Delete);
+    }
+}
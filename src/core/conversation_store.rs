@@ -0,0 +1,146 @@
+// src/core/conversation_store.rs
+//! Per-conversation history of request/response summaries.
+//!
+//! The chat UI and the api0 gateway send a `conversation_id` with their
+//! requests and every standard response echoes it back. The web layer records
+//! one row per turn (see `web::conversations`) so the UI can restore context
+//! after a reload and later analyses can reference earlier turns. Only short
+//! summaries are stored — never PDFs or full CV payloads.
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::SqlitePool;
+
+/// Longest summary stored per side of a turn, in characters.
+pub const MAX_SUMMARY_CHARS: usize = 2000;
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct ConversationTurn {
+    pub conversation_id: String,
+    pub method: String,
+    pub path: String,
+    pub status: i64,
+    pub request_summary: String,
+    pub response_summary: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct ConversationInfo {
+    pub conversation_id: String,
+    pub turns: i64,
+    pub last_activity: DateTime<Utc>,
+}
+
+/// Truncate on a char boundary, marking the cut.
+pub fn truncate_summary(text: &str) -> String {
+    let trimmed = text.trim();
+    match trimmed.char_indices().nth(MAX_SUMMARY_CHARS) {
+        Some((cut, _)) => format!("{}…", &trimmed[..cut]),
+        None => trimmed.to_string(),
+    }
+}
+
+/// One-line summary of a JSON response: its `message` or `error`, falling
+/// back to the compact JSON itself.
+pub fn summarize_response(body: &serde_json::Value) -> String {
+    let text = body
+        .get("message")
+        .or_else(|| body.get("error"))
+        .and_then(|v| v.as_str())
+        .map(str::to_string)
+        .unwrap_or_else(|| body.to_string());
+    truncate_summary(&text)
+}
+
+pub async fn record(pool: &SqlitePool, user_email: &str, turn: &ConversationTurn) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO conversations \
+         (conversation_id, user_email, method, path, status, request_summary, response_summary, created_at) \
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+    )
+    .bind(&turn.conversation_id)
+    .bind(user_email)
+    .bind(&turn.method)
+    .bind(&turn.path)
+    .bind(turn.status)
+    .bind(&turn.request_summary)
+    .bind(&turn.response_summary)
+    .bind(turn.created_at)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Turns of one conversation, oldest first. Scoped to the owner so ids from
+/// other users return nothing.
+pub async fn history(
+    pool: &SqlitePool,
+    user_email: &str,
+    conversation_id: &str,
+    limit: i64,
+) -> Result<Vec<ConversationTurn>> {
+    let mut turns = sqlx::query_as::<_, ConversationTurn>(
+        "SELECT conversation_id, method, path, status, request_summary, response_summary, created_at \
+         FROM conversations \
+         WHERE user_email = ? AND conversation_id = ? \
+         ORDER BY id DESC LIMIT ?",
+    )
+    .bind(user_email)
+    .bind(conversation_id)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+    turns.reverse();
+    Ok(turns)
+}
+
+/// The user's conversations, most recently active first.
+pub async fn list(
+    pool: &SqlitePool,
+    user_email: &str,
+    limit: i64,
+) -> Result<Vec<ConversationInfo>> {
+    let rows = sqlx::query_as::<_, ConversationInfo>(
+        "SELECT conversation_id, COUNT(*) AS turns, MAX(created_at) AS last_activity \
+         FROM conversations WHERE user_email = ? \
+         GROUP BY conversation_id ORDER BY last_activity DESC LIMIT ?",
+    )
+    .bind(user_email)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows)
+}
+
+pub async fn delete(pool: &SqlitePool, user_email: &str, conversation_id: &str) -> Result<u64> {
+    let result =
+        sqlx::query("DELETE FROM conversations WHERE user_email = ? AND conversation_id = ?")
+            .bind(user_email)
+            .bind(conversation_id)
+            .execute(pool)
+            .await?;
+    Ok(result.rows_affected())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summarizes_message_or_error() {
+        let ok = serde_json::json!({"success": true, "message": "PDF generated"});
+        let err = serde_json::json!({"success": false, "error": "Profile not found"});
+        let other = serde_json::json!({"success": true});
+        assert_eq!(summarize_response(&ok), "PDF generated");
+        assert_eq!(summarize_response(&err), "Profile not found");
+        assert_eq!(summarize_response(&other), r#"{"success":true}"#);
+
+        let long = "é".repeat(MAX_SUMMARY_CHARS + 5);
+        assert_eq!(
+            truncate_summary(&long).chars().count(),
+            MAX_SUMMARY_CHARS + 1
+        );
+    }
+}
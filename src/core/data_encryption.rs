@@ -0,0 +1,463 @@
+// src/core/data_encryption.rs
+//! Optional at-rest encryption of CV content.
+//!
+//! For the tenants listed in `DATA_ENCRYPTION_TENANTS` (comma-separated
+//! tenant folder names, or `*`), [`FsOps`](crate::core::FsOps) encrypts each
+//! person's `cv_params.toml` and `experiences*.typ` with AES-256-GCM when it
+//! writes them, and decrypts them when it reads them. Encrypted files start
+//! with a header naming the tenant and the key version:
+//!
+//! ```text
+//! CVENC1 | tenant length (1) | tenant | key version (4, BE) | nonce (12) | ciphertext
+//! ```
+//!
+//! so plaintext and encrypted files can sit side by side — a tenant's data is
+//! migrated by [`reencrypt`], and files keep opening after the tenant is
+//! removed from the list. Each tenant has a keyring, `<data_dir>/<tenant>/
+//! .data-keys.json`, whose keys are wrapped under the secrets master key
+//! (`SECRETS_MASTER_KEY`, see [`crate::core::secrets`]). [`rotate`] adds a
+//! new current key; older keys stay in the keyring so existing files still
+//! open until [`reencrypt`] rewrites them. A file is opened with the keyring
+//! of the tenant folder it is read from; one whose header names another
+//! tenant is refused.
+//!
+//! Pictures and other binary assets are not covered. Code that reads CV
+//! content without `FsOps` goes through [`read`], [`read_to_string`] or
+//! [`copy_plain`].
+
+use anyhow::{bail, Context, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::{DateTime, Utc};
+use graflog::app_log;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+use crate::core::secrets::{self, KEY_LEN, NONCE_LEN};
+use crate::core::{data_residency, profile_lock, FsOps};
+
+const MAGIC: &[u8] = b"CVENC1";
+const KEYRING_FILE: &str = ".data-keys.json";
+
+static STATE: OnceLock<DataEncryption> = OnceLock::new();
+
+enum Tenants {
+    All,
+    Listed(HashSet<String>),
+}
+
+struct DataEncryption {
+    data_dir: PathBuf,
+    master: Option<Vec<u8>>,
+    tenants: Tenants,
+    /// Unwrapped keyrings by tenant.
+    keyrings: Mutex<HashMap<String, Keyring>>,
+}
+
+#[derive(Clone)]
+struct Keyring {
+    current: u32,
+    keys: BTreeMap<u32, Vec<u8>>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct StoredKey {
+    nonce: String,
+    wrapped: String,
+    created_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct StoredKeyring {
+    current: u32,
+    keys: BTreeMap<u32, StoredKey>,
+}
+
+/// Result of [`rotate`] and [`reencrypt`].
+#[derive(Debug, Clone, Serialize)]
+pub struct KeyRotationReport {
+    pub tenant: String,
+    pub key_version: u32,
+    pub files_reencrypted: usize,
+}
+
+/// Read the configuration; called once at startup.
+pub fn init(data_dir: &Path) {
+    let listed = std::env::var("DATA_ENCRYPTION_TENANTS").unwrap_or_default();
+    let tenants = if listed.trim() == "*" {
+        Tenants::All
+    } else {
+        Tenants::Listed(
+            listed
+                .split(',')
+                .map(|t| t.trim().to_string())
+                .filter(|t| !t.is_empty())
+                .collect(),
+        )
+    };
+    let master = secrets::master_key_from_env();
+    let configured = !matches!(&tenants, Tenants::Listed(t) if t.is_empty());
+    if configured && master.is_none() {
+        app_log!(
+            error,
+            "DATA_ENCRYPTION_TENANTS is set but SECRETS_MASTER_KEY is not: data is written unencrypted"
+        );
+    } else if configured {
+        app_log!(info, "At-rest encryption enabled for: {}", listed.trim());
+    }
+    let _ = STATE.set(DataEncryption {
+        data_dir: data_dir.to_path_buf(),
+        master,
+        tenants,
+        keyrings: Mutex::new(HashMap::new()),
+    });
+}
+
+/// Whether `path` is CV content this module encrypts.
+pub fn is_protected(path: &Path) -> bool {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy())
+        .unwrap_or_default();
+    name == "cv_params.toml" || (name.starts_with("experiences") && name.ends_with(".typ"))
+}
+
+/// `<tenant>/<user>/<person>/<file>` → tenant. Hidden folders of the data
+/// root are not tenants: files staged under
+/// [`STAGING_DIR`](crate::core::import_staging::STAGING_DIR) stay plaintext
+/// until the import is committed, and are sealed for their tenant then.
+fn tenant_of(path: &Path) -> Option<String> {
+    let tenant = path.parent()?.parent()?.parent()?.file_name()?;
+    let tenant = tenant.to_string_lossy();
+    (!tenant.starts_with('.')).then(|| tenant.to_string())
+}
+
+fn encrypted(bytes: &[u8]) -> bool {
+    bytes.starts_with(MAGIC)
+}
+
+fn seal_bytes(key: &[u8], tenant: &str, version: u32, plain: &[u8]) -> Result<Vec<u8>> {
+    let (nonce, ciphertext) = secrets::encrypt(key, tenant.as_bytes(), plain)?;
+    let tenant_len = u8::try_from(tenant.len()).context("Tenant name too long")?;
+    let mut out = Vec::with_capacity(MAGIC.len() + 1 + tenant.len() + 4 + NONCE_LEN + ciphertext.len());
+    out.extend_from_slice(MAGIC);
+    out.push(tenant_len);
+    out.extend_from_slice(tenant.as_bytes());
+    out.extend_from_slice(&version.to_be_bytes());
+    out.extend_from_slice(&nonce);
+    out.extend_from_slice(&ciphertext);
+    Ok(out)
+}
+
+/// Header fields of an encrypted file: tenant, key version, nonce, ciphertext.
+fn parse(bytes: &[u8]) -> Result<(String, u32, &[u8], &[u8])> {
+    let rest = bytes.strip_prefix(MAGIC).context("Not an encrypted file")?;
+    let (&tenant_len, rest) = rest.split_first().context("Truncated header")?;
+    let tenant_len = tenant_len as usize;
+    if rest.len() < tenant_len + 4 + NONCE_LEN {
+        bail!("Truncated header");
+    }
+    let (tenant, rest) = rest.split_at(tenant_len);
+    let (version, rest) = rest.split_at(4);
+    let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+    let version = u32::from_be_bytes(version.try_into().expect("4 bytes"));
+    Ok((String::from_utf8_lossy(tenant).to_string(), version, nonce, ciphertext))
+}
+
+fn open_bytes(keys: &BTreeMap<u32, Vec<u8>>, bytes: &[u8]) -> Result<Vec<u8>> {
+    let (tenant, version, nonce, ciphertext) = parse(bytes)?;
+    let key = keys
+        .get(&version)
+        .with_context(|| format!("Key version {} of {} is missing", version, tenant))?;
+    secrets::decrypt(key, tenant.as_bytes(), nonce, ciphertext)
+}
+
+impl DataEncryption {
+    fn covers(&self, tenant: &str) -> bool {
+        match &self.tenants {
+            Tenants::All => true,
+            Tenants::Listed(tenants) => tenants.contains(tenant),
+        }
+    }
+
+    fn master(&self) -> Result<&[u8]> {
+        self.master
+            .as_deref()
+            .context("At-rest encryption needs SECRETS_MASTER_KEY")
+    }
+
+    fn keyring_path(&self, tenant: &str) -> PathBuf {
+        data_residency::route(&self.data_dir, tenant)
+            .join(tenant)
+            .join(KEYRING_FILE)
+    }
+
+    fn key_aad(tenant: &str, version: u32) -> Vec<u8> {
+        format!("data-key/{}/{}", tenant, version).into_bytes()
+    }
+
+    /// The tenant's keyring, created with a first key if `create`.
+    fn keyring(&self, tenant: &str, create: bool) -> Result<Keyring> {
+        let mut cache = self
+            .keyrings
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some(keyring) = cache.get(tenant) {
+            return Ok(keyring.clone());
+        }
+        let master = self.master()?;
+        let path = self.keyring_path(tenant);
+        let keyring = match std::fs::read(&path) {
+            Ok(raw) => {
+                let stored: StoredKeyring = serde_json::from_slice(&raw)
+                    .with_context(|| format!("Corrupt keyring {}", path.display()))?;
+                let mut keys = BTreeMap::new();
+                for (version, key) in &stored.keys {
+                    let key = secrets::decrypt(
+                        master,
+                        &Self::key_aad(tenant, *version),
+                        &STANDARD.decode(&key.nonce)?,
+                        &STANDARD.decode(&key.wrapped)?,
+                    )
+                    .with_context(|| format!("Failed to unwrap key {} of {}", version, tenant))?;
+                    keys.insert(*version, key);
+                }
+                Keyring {
+                    current: stored.current,
+                    keys,
+                }
+            }
+            Err(_) if create => {
+                let keyring = Keyring {
+                    current: 0,
+                    keys: BTreeMap::new(),
+                };
+                self.add_key(tenant, keyring)?
+            }
+            Err(e) => {
+                return Err(e).with_context(|| format!("No keyring for {}", tenant));
+            }
+        };
+        cache.insert(tenant.to_string(), keyring.clone());
+        Ok(keyring)
+    }
+
+    /// `keyring` plus a new current key, saved to disk.
+    fn add_key(&self, tenant: &str, mut keyring: Keyring) -> Result<Keyring> {
+        let master = self.master()?;
+        let version = keyring.current + 1;
+        let key: [u8; KEY_LEN] = rand::random();
+        keyring.keys.insert(version, key.to_vec());
+        keyring.current = version;
+
+        let path = self.keyring_path(tenant);
+        let mut stored = match std::fs::read(&path) {
+            Ok(raw) => serde_json::from_slice::<StoredKeyring>(&raw)
+                .with_context(|| format!("Corrupt keyring {}", path.display()))?,
+            Err(_) => StoredKeyring {
+                current: 0,
+                keys: BTreeMap::new(),
+            },
+        };
+        let (nonce, wrapped) = secrets::encrypt(master, &Self::key_aad(tenant, version), &key)?;
+        stored.keys.insert(
+            version,
+            StoredKey {
+                nonce: STANDARD.encode(nonce),
+                wrapped: STANDARD.encode(wrapped),
+                created_at: Utc::now(),
+            },
+        );
+        stored.current = version;
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let tmp = path.with_extension(format!("{}.tmp", uuid::Uuid::new_v4()));
+        std::fs::write(&tmp, serde_json::to_vec_pretty(&stored)?)?;
+        std::fs::rename(&tmp, &path)
+            .with_context(|| format!("Failed to save keyring {}", path.display()))?;
+        app_log!(info, "Data key version {} created for {}", version, tenant);
+        Ok(keyring)
+    }
+}
+
+/// Content to store at `path`: encrypted if the file is CV content of a
+/// tenant with at-rest encryption, unchanged otherwise (or if already
+/// encrypted).
+pub fn seal_for(path: &Path, content: &[u8]) -> Result<Cow<'_, [u8]>> {
+    seal_if(path, content, is_protected(path))
+}
+
+/// Like [`seal_for`] for a file derived from CV content under a user folder
+/// (`<tenant>/<user>/<dir>/<file>`), e.g. a cached service answer.
+pub fn seal_derived(path: &Path, content: &[u8]) -> Result<Cow<'_, [u8]>> {
+    seal_if(path, content, true)
+}
+
+fn seal_if(path: &Path, content: &[u8], protected: bool) -> Result<Cow<'_, [u8]>> {
+    let Some(state) = STATE.get() else {
+        return Ok(Cow::Borrowed(content));
+    };
+    let tenant = match tenant_of(path) {
+        Some(tenant)
+            if protected
+                && !encrypted(content)
+                && state.covers(&tenant)
+                && state.master.is_some() =>
+        {
+            tenant
+        }
+        _ => return Ok(Cow::Borrowed(content)),
+    };
+    let keyring = state.keyring(&tenant, true)?;
+    let key = &keyring.keys[&keyring.current];
+    Ok(Cow::Owned(seal_bytes(key, &tenant, keyring.current, content)?))
+}
+
+/// The tenant whose keyring opens the encrypted file at `path`: the one the
+/// path lives under, which must also be the one named in the header — a file
+/// copied in from another tenant is refused rather than opened with that
+/// tenant's keys.
+fn reading_tenant(path: &Path, bytes: &[u8]) -> Result<String> {
+    let tenant = tenant_of(path).context("Encrypted file outside a tenant folder")?;
+    let (header, ..) = parse(bytes)?;
+    if header != tenant {
+        bail!("Encrypted for tenant {}, but read from tenant {}", header, tenant);
+    }
+    Ok(tenant)
+}
+
+/// `bytes` as read from `path`, decrypted if they are an encrypted file.
+pub fn open(path: &Path, bytes: Vec<u8>) -> Result<Vec<u8>> {
+    if !encrypted(&bytes) {
+        return Ok(bytes);
+    }
+    let state = STATE
+        .get()
+        .context("Encrypted file, but at-rest encryption is not initialised")?;
+    let tenant = reading_tenant(path, &bytes)?;
+    let keyring = state.keyring(&tenant, false)?;
+    open_bytes(&keyring.keys, &bytes)
+}
+
+/// `std::fs::read` that decrypts.
+pub fn read(path: &Path) -> Result<Vec<u8>> {
+    let bytes = std::fs::read(path).with_context(|| format!("Failed to read file: {}", path.display()))?;
+    open(path, bytes).with_context(|| format!("Failed to decrypt {}", path.display()))
+}
+
+/// `std::fs::read_to_string` that decrypts.
+pub fn read_to_string(path: &Path) -> Result<String> {
+    String::from_utf8(read(path)?).with_context(|| format!("{} is not UTF-8", path.display()))
+}
+
+/// Copy `src` to `dest` in plaintext, e.g. into a compile workspace.
+pub fn copy_plain(src: &Path, dest: &Path) -> Result<()> {
+    std::fs::write(dest, read(src)?)
+        .with_context(|| format!("Failed to copy {} to {}", src.display(), dest.display()))
+}
+
+/// Add a new current key for `tenant`; existing files keep their key.
+pub fn rotate(tenant: &str) -> Result<u32> {
+    let state = STATE.get().context("At-rest encryption is not initialised")?;
+    let keyring = state.keyring(tenant, true)?;
+    let rotated = if keyring.current == 0 {
+        keyring
+    } else {
+        state.add_key(tenant, keyring)?
+    };
+    state
+        .keyrings
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .insert(tenant.to_string(), rotated.clone());
+    Ok(rotated.current)
+}
+
+/// Rewrite the CV content of every person of `tenant` through [`FsOps`], so
+/// each file is sealed with the current key (or decrypted, for a tenant no
+/// longer listed).
+pub async fn reencrypt(tenant_dir: &Path) -> Result<usize> {
+    let mut rewritten = 0;
+    let mut users = tokio::fs::read_dir(tenant_dir)
+        .await
+        .with_context(|| format!("Failed to read {}", tenant_dir.display()))?;
+    while let Some(user) = users.next_entry().await? {
+        if !user.file_type().await?.is_dir() {
+            continue;
+        }
+        for person in FsOps::list_profiles(&user.path()).await? {
+            let person_dir = user.path().join(&person);
+            let _lock = profile_lock::write(&person_dir).await;
+            let mut files = tokio::fs::read_dir(&person_dir).await?;
+            while let Some(file) = files.next_entry().await? {
+                let path = file.path();
+                if !is_protected(&path) || !file.file_type().await?.is_file() {
+                    continue;
+                }
+                let content = open(&path, tokio::fs::read(&path).await?)
+                    .with_context(|| format!("Failed to decrypt {}", path.display()))?;
+                FsOps::write_bytes_safe(&path, &content).await?;
+                rewritten += 1;
+            }
+        }
+    }
+    Ok(rewritten)
+}
+
+/// Encrypt any plaintext CV content of one person, e.g. after a staged import
+/// (written outside the tenant) was moved into place.
+pub async fn protect_dir(person_dir: &Path) -> Result<()> {
+    let mut files = tokio::fs::read_dir(person_dir).await?;
+    while let Some(file) = files.next_entry().await? {
+        let path = file.path();
+        if !is_protected(&path) {
+            continue;
+        }
+        let bytes = tokio::fs::read(&path).await?;
+        if let Cow::Owned(_) = seal_for(&path, &bytes)? {
+            FsOps::write_bytes_safe(&path, &bytes).await?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sealed_files_open_with_their_key_version() {
+        let keys: BTreeMap<u32, Vec<u8>> = [(1, vec![1u8; KEY_LEN]), (2, vec![2u8; KEY_LEN])].into();
+        let old = seal_bytes(&keys[&1], "acme", 1, b"name = \"Ada\"").unwrap();
+        let new = seal_bytes(&keys[&2], "acme", 2, b"name = \"Ada\"").unwrap();
+        assert!(encrypted(&old) && old != new);
+        assert_eq!(open_bytes(&keys, &old).unwrap(), b"name = \"Ada\"");
+        assert_eq!(open_bytes(&keys, &new).unwrap(), b"name = \"Ada\"");
+
+        // A retired key or another tenant's header fails.
+        let only_new: BTreeMap<u32, Vec<u8>> = [(2, vec![2u8; KEY_LEN])].into();
+        assert!(open_bytes(&only_new, &old).is_err());
+        let mut other = seal_bytes(&keys[&1], "acmf", 1, b"x").unwrap();
+        other[MAGIC.len() + 4] = b'e';
+        assert!(open_bytes(&keys, &other).is_err());
+
+        assert_eq!(
+            tenant_of(Path::new("data/acme/ada_example_com/ada/cv_params.toml")).as_deref(),
+            Some("acme")
+        );
+        let staged = Path::new("data/.import_staging/4f2c/profile/cv_params.toml");
+        assert_eq!(tenant_of(staged), None);
+        let sealed = seal_bytes(&keys[&1], "acme", 1, b"x").unwrap();
+        let acme = Path::new("data/acme/ada_example_com/ada/cv_params.toml");
+        let evil = Path::new("data/evil/ada_example_com/ada/cv_params.toml");
+        assert_eq!(reading_tenant(acme, &sealed).unwrap(), "acme");
+        assert!(reading_tenant(evil, &sealed).is_err());
+        assert!(reading_tenant(Path::new("cv_params.toml"), &sealed).is_err());
+        assert!(is_protected(Path::new("x/experiences_fr.typ")));
+        assert!(!is_protected(Path::new("x/profile.png")));
+    }
+}
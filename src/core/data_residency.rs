@@ -0,0 +1,417 @@
+// src/core/data_residency.rs
+//! Data residency: tenants tagged with a region keep their files in that
+//! region's storage.
+//!
+//! `DATA_REGIONS` lists the regions and their storage roots
+//! (`eu=/mnt/eu,ch=/mnt/ch`); each root holds `data/` and `output/` laid out
+//! like the server's own `data_dir` and `output_dir`. Tenants (data folder
+//! names) are tagged in `tenant_regions`; untagged tenants stay in the
+//! default storage. [`route`] is applied by
+//! [`get_tenant_folder_path`](crate::core::database::get_tenant_folder_path)
+//! and [`get_tenant_output_path`](crate::core::database::get_tenant_output_path),
+//! so every handler working under a tenant folder lands in the right region.
+//! Shared caches and staging areas use [`data_root_of`] so they stay in the
+//! region of the file they derive from, and [`ensure_same_region`] refuses
+//! copies and moves that would cross regions.
+//!
+//! Generated files keep one URL scheme in every region,
+//! `/outputs/<tenant>/<user>/<person>/<file>`: [`output_relative`] turns a
+//! file into that path and [`output_file`] resolves it back through the
+//! region of its first segment, the tenant.
+//!
+//! Tags are cached in memory at startup ([`load`]) because path resolution
+//! is synchronous. A tenant that already has files cannot change region:
+//! migrating data between regions is an offline operation.
+
+use anyhow::{bail, Context, Result};
+use chrono::Utc;
+use graflog::app_log;
+use serde::Serialize;
+use sqlx::SqlitePool;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::{OnceLock, RwLock};
+
+pub const DEFAULT_REGION: &str = "default";
+
+static STATE: OnceLock<Residency> = OnceLock::new();
+
+#[derive(Debug, Clone)]
+struct Region {
+    data_dir: PathBuf,
+    output_dir: PathBuf,
+}
+
+struct Residency {
+    data_dir: PathBuf,
+    output_dir: PathBuf,
+    regions: BTreeMap<String, Region>,
+    /// Region of each tagged tenant.
+    tenants: RwLock<HashMap<String, String>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TenantRegion {
+    pub tenant: String,
+    pub region: String,
+    pub data_dir: PathBuf,
+}
+
+pub fn valid_region(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= 32
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+}
+
+fn parse_regions(spec: &str) -> BTreeMap<String, Region> {
+    spec.split(',')
+        .filter_map(|entry| {
+            let (name, root) = entry.split_once('=')?;
+            let (name, root) = (name.trim().to_lowercase(), PathBuf::from(root.trim()));
+            if !valid_region(&name) || name == DEFAULT_REGION || root.as_os_str().is_empty() {
+                app_log!(warn, "Ignoring DATA_REGIONS entry '{}'", entry.trim());
+                return None;
+            }
+            let region = Region {
+                data_dir: root.join("data"),
+                output_dir: root.join("output"),
+            };
+            Some((name, region))
+        })
+        .collect()
+}
+
+/// Read `DATA_REGIONS`; called once at startup.
+pub fn init(data_dir: &Path, output_dir: &Path) {
+    let regions = parse_regions(&std::env::var("DATA_REGIONS").unwrap_or_default());
+    if !regions.is_empty() {
+        let names: Vec<&str> = regions.keys().map(String::as_str).collect();
+        app_log!(info, "Data regions: {}", names.join(", "));
+    }
+    let _ = STATE.set(Residency {
+        data_dir: data_dir.to_path_buf(),
+        output_dir: output_dir.to_path_buf(),
+        regions,
+        tenants: RwLock::new(HashMap::new()),
+    });
+}
+
+/// Load the tenant tags into memory; returns how many tenants are tagged.
+pub async fn load(pool: &SqlitePool) -> Result<usize> {
+    let Some(state) = STATE.get() else {
+        return Ok(0);
+    };
+    let rows: Vec<(String, String)> = sqlx::query_as("SELECT tenant, region FROM tenant_regions")
+        .fetch_all(pool)
+        .await?;
+    let mut tenants = state
+        .tenants
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    for (tenant, region) in rows {
+        if !state.regions.contains_key(&region) {
+            // Never fall back to the default storage for a tagged tenant.
+            app_log!(
+                error,
+                "Tenant {} is in region '{}', which is not in DATA_REGIONS",
+                tenant,
+                region
+            );
+        }
+        tenants.insert(tenant, region);
+    }
+    Ok(tenants.len())
+}
+
+impl Residency {
+    fn region_of(&self, tenant: &str) -> Option<String> {
+        self.tenants
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .get(tenant)
+            .cloned()
+    }
+
+    fn route(&self, base: &Path, tenant: &str) -> PathBuf {
+        let Some(region) = self.region_of(tenant) else {
+            return base.to_path_buf();
+        };
+        // An unconfigured region resolves to a path that does not exist
+        // rather than to the default storage.
+        let missing = Region {
+            data_dir: PathBuf::from(format!("/nonexistent-region-{}/data", region)),
+            output_dir: PathBuf::from(format!("/nonexistent-region-{}/output", region)),
+        };
+        let roots = self.regions.get(&region).unwrap_or(&missing);
+        if base == self.data_dir {
+            roots.data_dir.clone()
+        } else if base == self.output_dir {
+            roots.output_dir.clone()
+        } else {
+            base.to_path_buf()
+        }
+    }
+
+    /// Region whose storage holds `path`.
+    fn region_of_path(&self, path: &Path) -> &str {
+        self.regions
+            .iter()
+            .find(|(_, r)| path.starts_with(&r.data_dir) || path.starts_with(&r.output_dir))
+            .map(|(name, _)| name.as_str())
+            .unwrap_or(DEFAULT_REGION)
+    }
+
+    fn output_relative<'a>(&self, file: &'a Path) -> &'a Path {
+        self.regions
+            .values()
+            .map(|r| r.output_dir.as_path())
+            .chain(std::iter::once(self.output_dir.as_path()))
+            .find_map(|root| file.strip_prefix(root).ok())
+            .unwrap_or(file)
+    }
+
+    fn output_file(&self, relative: &Path) -> PathBuf {
+        let tenant = relative
+            .components()
+            .next()
+            .map(|c| c.as_os_str().to_string_lossy().to_string())
+            .unwrap_or_default();
+        self.route(&self.output_dir, &tenant).join(relative)
+    }
+
+    fn data_root(&self, region: &str) -> &Path {
+        self.regions
+            .get(region)
+            .map(|r| r.data_dir.as_path())
+            .unwrap_or(&self.data_dir)
+    }
+}
+
+/// Root to use instead of `base` (the server's `data_dir` or `output_dir`)
+/// for `tenant`'s files.
+pub fn route(base: &Path, tenant: &str) -> PathBuf {
+    match STATE.get() {
+        Some(state) => state.route(base, tenant),
+        None => base.to_path_buf(),
+    }
+}
+
+/// Region of `tenant` (`default` when untagged).
+pub fn region_of(tenant: &str) -> String {
+    STATE
+        .get()
+        .and_then(|state| state.region_of(tenant))
+        .unwrap_or_else(|| DEFAULT_REGION.to_string())
+}
+
+/// Data root of the region holding `path`, for caches and staging areas
+/// derived from it; `data_dir` for the default region.
+pub fn data_root_of(data_dir: &Path, path: &Path) -> PathBuf {
+    match STATE.get() {
+        Some(state) => state.data_root(state.region_of_path(path)).to_path_buf(),
+        None => data_dir.to_path_buf(),
+    }
+}
+
+/// Data roots of every region, the default first.
+pub fn data_roots(data_dir: &Path) -> Vec<PathBuf> {
+    let mut roots = vec![data_dir.to_path_buf()];
+    if let Some(state) = STATE.get() {
+        roots.extend(state.regions.values().map(|r| r.data_dir.clone()));
+    }
+    roots
+}
+
+/// Output roots of every region, the default first.
+pub fn output_roots(output_dir: &Path) -> Vec<PathBuf> {
+    let mut roots = vec![output_dir.to_path_buf()];
+    if let Some(state) = STATE.get() {
+        roots.extend(state.regions.values().map(|r| r.output_dir.clone()));
+    }
+    roots
+}
+
+/// `file`, a generated file in any region, relative to its output root —
+/// the path `/outputs` serves it under.
+pub fn output_relative<'a>(output_dir: &Path, file: &'a Path) -> &'a Path {
+    match STATE.get() {
+        Some(state) => state.output_relative(file),
+        None => file.strip_prefix(output_dir).unwrap_or(file),
+    }
+}
+
+/// The generated file at `relative` (see [`output_relative`]), in the region
+/// of the tenant it starts with.
+pub fn output_file(output_dir: &Path, relative: &Path) -> PathBuf {
+    match STATE.get() {
+        Some(state) => state.output_file(relative),
+        None => output_dir.join(relative),
+    }
+}
+
+/// Refuse to copy or move `src` to `dest` when they are in different regions.
+pub fn ensure_same_region(src: &Path, dest: &Path) -> Result<()> {
+    let Some(state) = STATE.get() else {
+        return Ok(());
+    };
+    let (from, to) = (state.region_of_path(src), state.region_of_path(dest));
+    if from != to {
+        bail!(
+            "Refusing to copy {} ({}) to {} ({}): data must stay in its region",
+            src.display(),
+            from,
+            dest.display(),
+            to
+        );
+    }
+    Ok(())
+}
+
+/// Configured regions, the default first.
+pub fn regions() -> Vec<String> {
+    let mut names = vec![DEFAULT_REGION.to_string()];
+    if let Some(state) = STATE.get() {
+        names.extend(state.regions.keys().cloned());
+    }
+    names
+}
+
+/// Tag `tenant` with `region` (`default` removes the tag). Fails when the
+/// region is unknown or the tenant already has files elsewhere.
+pub async fn set_region(pool: &SqlitePool, tenant: &str, region: &str) -> Result<TenantRegion> {
+    let state = STATE.get().context("Data residency is not initialised")?;
+    if region != DEFAULT_REGION && !state.regions.contains_key(region) {
+        bail!("Unknown region '{}'", region);
+    }
+    let current = state.route(&state.data_dir, tenant).join(tenant);
+    let current_region = region_of(tenant);
+    if current_region != region && has_files(&current).await {
+        bail!(
+            "{} already has data in region '{}'; migrate it offline first",
+            tenant,
+            current_region
+        );
+    }
+
+    if region == DEFAULT_REGION {
+        sqlx::query("DELETE FROM tenant_regions WHERE tenant = ?")
+            .bind(tenant)
+            .execute(pool)
+            .await?;
+    } else {
+        sqlx::query(
+            "INSERT INTO tenant_regions (tenant, region, updated_at) VALUES (?, ?, ?) \
+             ON CONFLICT(tenant) DO UPDATE SET region = excluded.region, \
+             updated_at = excluded.updated_at",
+        )
+        .bind(tenant)
+        .bind(region)
+        .bind(Utc::now())
+        .execute(pool)
+        .await?;
+    }
+    {
+        let mut tenants = state
+            .tenants
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if region == DEFAULT_REGION {
+            tenants.remove(tenant);
+        } else {
+            tenants.insert(tenant.to_string(), region.to_string());
+        }
+    }
+    Ok(TenantRegion {
+        tenant: tenant.to_string(),
+        region: region.to_string(),
+        data_dir: state.data_root(region).join(tenant),
+    })
+}
+
+/// Every tagged tenant.
+pub fn list() -> Vec<TenantRegion> {
+    let Some(state) = STATE.get() else {
+        return Vec::new();
+    };
+    let tenants = state
+        .tenants
+        .read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    let mut list: Vec<TenantRegion> = tenants
+        .iter()
+        .map(|(tenant, region)| TenantRegion {
+            tenant: tenant.clone(),
+            region: region.clone(),
+            data_dir: state.route(&state.data_dir, tenant).join(tenant),
+        })
+        .collect();
+    list.sort_by(|a, b| a.tenant.cmp(&b.tenant));
+    list
+}
+
+async fn has_files(dir: &Path) -> bool {
+    match tokio::fs::read_dir(dir).await {
+        Ok(mut entries) => matches!(entries.next_entry().await, Ok(Some(_))),
+        Err(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tagged_tenants_resolve_to_their_region() {
+        let residency = Residency {
+            data_dir: PathBuf::from("data"),
+            output_dir: PathBuf::from("output"),
+            regions: parse_regions("eu=/mnt/eu, ch=/mnt/ch, default=/x, bad"),
+            tenants: RwLock::new(HashMap::from([
+                ("acme".to_string(), "ch".to_string()),
+                ("ghost".to_string(), "us".to_string()),
+            ])),
+        };
+        assert_eq!(residency.regions.len(), 2);
+
+        let (data, output) = (Path::new("data"), Path::new("output"));
+        assert_eq!(residency.route(data, "acme"), Path::new("/mnt/ch/data"));
+        assert_eq!(residency.route(output, "acme"), Path::new("/mnt/ch/output"));
+        assert_eq!(residency.route(data, "independent"), data);
+        // Other bases (templates, temp dirs) are left alone.
+        assert_eq!(residency.route(Path::new("templates"), "acme"), Path::new("templates"));
+        // A tag for an unconfigured region never falls back to the default.
+        assert!(!residency.route(data, "ghost").starts_with("data"));
+
+        assert_eq!(residency.region_of_path(Path::new("/mnt/eu/data/x/y")), "eu");
+        assert_eq!(residency.region_of_path(Path::new("data/independent")), DEFAULT_REGION);
+        assert_eq!(residency.data_root("ch"), Path::new("/mnt/ch/data"));
+        assert_eq!(residency.data_root(DEFAULT_REGION), Path::new("data"));
+    }
+
+    #[test]
+    fn outputs_of_tagged_tenants_round_trip_through_their_url() {
+        let residency = Residency {
+            data_dir: PathBuf::from("data"),
+            output_dir: PathBuf::from("output"),
+            regions: parse_regions("ch=/mnt/ch"),
+            tenants: RwLock::new(HashMap::from([("acme".to_string(), "ch".to_string())])),
+        };
+        let tagged = residency
+            .route(Path::new("output"), "acme")
+            .join("acme/jane_at_acme_com/jane/jane_default_en.pdf");
+        assert_eq!(
+            tagged,
+            Path::new("/mnt/ch/output/acme/jane_at_acme_com/jane/jane_default_en.pdf")
+        );
+        let relative = residency.output_relative(&tagged);
+        assert_eq!(relative, Path::new("acme/jane_at_acme_com/jane/jane_default_en.pdf"));
+        assert_eq!(residency.output_file(relative), tagged);
+
+        let untagged = Path::new("output/independent/bob_example_com/bob/bob_default_en.pdf");
+        let relative = residency.output_relative(untagged);
+        assert_eq!(relative, Path::new("independent/bob_example_com/bob/bob_default_en.pdf"));
+        assert_eq!(residency.output_file(relative), untagged);
+    }
+}
@@ -6,9 +6,10 @@ use chrono::{DateTime, Utc};
 use graflog::app_log;
 use serde::{Deserialize, Serialize};
 use sqlx::SqlitePool;
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
-use crate::core::FsOps;
+use crate::core::{data_residency, owner, secrets, tenant_cache, FsOps};
 
 // ===== Core Database Connection Management =====
 
@@ -140,6 +141,9 @@ async fn run_migrations(pool: &SqlitePool) -> Result<()> {
     let _ = sqlx::query("ALTER TABLE tenants ADD COLUMN email_prefs TEXT DEFAULT '{}'")
         .execute(pool)
         .await;
+    let _ = sqlx::query("ALTER TABLE tenants ADD COLUMN archived_at TEXT")
+        .execute(pool)
+        .await;
 
     // ── Referrals table ──────────────────────────────────────────────────────
     sqlx::query(
@@ -231,10 +235,666 @@ async fn run_migrations(pool: &SqlitePool) -> Result<()> {
     .execute(pool)
     .await?;
 
+    // ── Email intake addresses ───────────────────────────────────────────
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS email_intake_addresses (
+            token       TEXT PRIMARY KEY,
+            user_email  TEXT NOT NULL UNIQUE,
+            created_at  TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    // ── Calendar feed tokens ─────────────────────────────────────────────
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS calendar_feeds (
+            token       TEXT PRIMARY KEY,
+            user_email  TEXT NOT NULL UNIQUE,
+            created_at  TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    // ── Conversation history ─────────────────────────────────────────────
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS conversations (
+            id                INTEGER PRIMARY KEY AUTOINCREMENT,
+            conversation_id   TEXT NOT NULL,
+            user_email        TEXT NOT NULL,
+            method            TEXT NOT NULL,
+            path              TEXT NOT NULL,
+            status            INTEGER NOT NULL,
+            request_summary   TEXT NOT NULL DEFAULT '',
+            response_summary  TEXT NOT NULL DEFAULT '',
+            created_at        TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS idx_conversations_user_conv ON conversations(user_email, conversation_id);",
+    )
+    .execute(pool)
+    .await?;
+
+    // ── cv-import service usage (LLM cost chargeback) ────────────────────
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS service_calls (
+            id              INTEGER PRIMARY KEY AUTOINCREMENT,
+            tenant_id       INTEGER NOT NULL,
+            user_email      TEXT NOT NULL,
+            endpoint        TEXT NOT NULL,
+            status          INTEGER NOT NULL,
+            duration_ms     INTEGER NOT NULL,
+            request_bytes   INTEGER NOT NULL DEFAULT 0,
+            response_bytes  INTEGER NOT NULL DEFAULT 0,
+            tokens          INTEGER,
+            created_at      TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+        "#,
+    )
+    .execute(pool)
+    .await?;
+    rekey_by_tenant_id(pool, "service_calls", Some("user_email"), true).await?;
+
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS idx_service_calls_created ON service_calls(created_at, tenant_id);",
+    )
+    .execute(pool)
+    .await?;
+
+    // ── Per-user roles within a domain tenant ────────────────────────────
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS member_roles (
+            email       TEXT PRIMARY KEY,
+            role        TEXT NOT NULL,
+            updated_at  TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    // ── Tenant activity for compliance reports ───────────────────────────
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS audit_log (
+            id          INTEGER PRIMARY KEY AUTOINCREMENT,
+            tenant_id   INTEGER NOT NULL,
+            user_email  TEXT NOT NULL,
+            action      TEXT NOT NULL,
+            subject     TEXT NOT NULL DEFAULT '',
+            created_at  TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+        "#,
+    )
+    .execute(pool)
+    .await?;
+    rekey_by_tenant_id(pool, "audit_log", Some("user_email"), true).await?;
+
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_audit_log_tenant ON audit_log(tenant_id, created_at);")
+        .execute(pool)
+        .await?;
+
+    // ── Autosaved editor drafts ──────────────────────────────────────────
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS file_drafts (
+            user_email  TEXT NOT NULL,
+            path        TEXT NOT NULL,
+            content     TEXT NOT NULL,
+            updated_at  TEXT NOT NULL DEFAULT (datetime('now')),
+            PRIMARY KEY (user_email, path)
+        );
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    // ── Per-tenant strictness of the editor's Typst linter ───────────────
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS typst_lint_levels (
+            tenant_id   INTEGER PRIMARY KEY,
+            level       TEXT NOT NULL,
+            updated_at  TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+        "#,
+    )
+    .execute(pool)
+    .await?;
+    rekey_by_tenant_id(pool, "typst_lint_levels", None, false).await?;
+
+    // ── Reviewer comments on profile files (core::profile_comments) ──────
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS profile_comments (
+            id           INTEGER PRIMARY KEY AUTOINCREMENT,
+            owner        TEXT NOT NULL,
+            profile      TEXT NOT NULL,
+            path         TEXT NOT NULL,
+            line_start   INTEGER,
+            line_end     INTEGER,
+            author       TEXT NOT NULL,
+            body         TEXT NOT NULL,
+            created_at   TEXT NOT NULL DEFAULT (datetime('now')),
+            resolved_at  TEXT,
+            resolved_by  TEXT
+        );
+        "#,
+    )
+    .execute(pool)
+    .await?;
+    rekey_by_account(pool, "profile_comments", Some("author")).await?;
+
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS idx_profile_comments_profile \
+         ON profile_comments(owner, profile, path);",
+    )
+    .execute(pool)
+    .await?;
+
+    // ── Watched profiles (core::profile_watch) ───────────────────────────
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS profile_watches (
+            owner           TEXT NOT NULL,
+            profile         TEXT NOT NULL,
+            email           TEXT NOT NULL,
+            notify_email    BOOLEAN NOT NULL DEFAULT TRUE,
+            webhook_url     TEXT,
+            webhook_secret  TEXT,
+            created_at      TEXT NOT NULL DEFAULT (datetime('now')),
+            PRIMARY KEY (owner, profile, email)
+        );
+        "#,
+    )
+    .execute(pool)
+    .await?;
+    rekey_by_account(pool, "profile_watches", Some("email")).await?;
+
+    // ── Tombstones of deleted generated CVs (core::generation_outputs) ───
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS deleted_generations (
+            path            TEXT PRIMARY KEY,
+            owner           TEXT NOT NULL,
+            generation_id   TEXT NOT NULL,
+            profile         TEXT NOT NULL,
+            deleted_by      TEXT NOT NULL,
+            deleted_at      TEXT NOT NULL
+        );
+        "#,
+    )
+    .execute(pool)
+    .await?;
+    rekey_by_account(pool, "deleted_generations", Some("deleted_by")).await?;
+
+    // ── Failed CV conversions kept for retrying (core::import_retry) ─────
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS failed_imports (
+            id              TEXT PRIMARY KEY,
+            owner           TEXT NOT NULL,
+            user_email      TEXT NOT NULL,
+            filename        TEXT NOT NULL,
+            stored_path     TEXT NOT NULL,
+            error           TEXT NOT NULL,
+            attempts        INTEGER NOT NULL DEFAULT 0,
+            status          TEXT NOT NULL,
+            next_attempt_at TEXT,
+            profile         TEXT,
+            created_at      TEXT NOT NULL,
+            updated_at      TEXT NOT NULL
+        );
+        "#,
+    )
+    .execute(pool)
+    .await?;
+    rekey_by_account(pool, "failed_imports", Some("user_email")).await?;
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_failed_imports_user ON failed_imports(user_email);")
+        .execute(pool)
+        .await?;
+
+    // ── Asynchronous generations (core::jobs) ───────────────────────────
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS generation_jobs (
+            id              TEXT PRIMARY KEY,
+            owner           TEXT NOT NULL,
+            user_email      TEXT NOT NULL,
+            profile         TEXT NOT NULL,
+            status          TEXT NOT NULL,
+            filename        TEXT,
+            error           TEXT,
+            created_at      TEXT NOT NULL,
+            updated_at      TEXT NOT NULL
+        );
+        "#,
+    )
+    .execute(pool)
+    .await?;
+    rekey_by_account(pool, "generation_jobs", Some("user_email")).await?;
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_generation_jobs_user ON generation_jobs(user_email);")
+        .execute(pool)
+        .await?;
+
+    // ── Tags and attributes of persons (core::person_tags) ───────────────
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS person_tags (
+            owner       TEXT NOT NULL,
+            profile     TEXT NOT NULL,
+            tag         TEXT NOT NULL,
+            PRIMARY KEY (owner, profile, tag)
+        );
+        "#,
+    )
+    .execute(pool)
+    .await?;
+    rekey_by_account(pool, "person_tags", None).await?;
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS person_attributes (
+            owner       TEXT NOT NULL,
+            profile     TEXT NOT NULL,
+            key         TEXT NOT NULL,
+            value       TEXT NOT NULL,
+            PRIMARY KEY (owner, profile, key)
+        );
+        "#,
+    )
+    .execute(pool)
+    .await?;
+    rekey_by_account(pool, "person_attributes", None).await?;
+
+    // ── Bulk re-generations after template updates (core::bulk_regeneration)
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS regeneration_runs (
+            id              TEXT PRIMARY KEY,
+            tenant          TEXT NOT NULL,
+            template        TEXT,
+            requested_by    TEXT NOT NULL,
+            created_at      TEXT NOT NULL,
+            finished_at     TEXT
+        );
+        "#,
+    )
+    .execute(pool)
+    .await?;
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_regeneration_runs_tenant ON regeneration_runs(tenant);")
+        .execute(pool)
+        .await?;
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS regeneration_items (
+            run_id          TEXT NOT NULL,
+            user_folder     TEXT NOT NULL,
+            profile         TEXT NOT NULL,
+            template        TEXT NOT NULL,
+            lang            TEXT NOT NULL,
+            status          TEXT NOT NULL,
+            error           TEXT,
+            updated_at      TEXT NOT NULL,
+            PRIMARY KEY (run_id, user_folder, profile)
+        );
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    // ── Saved person searches (core::smart_lists) ────────────────────────
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS smart_lists (
+            owner           TEXT NOT NULL,
+            name            TEXT NOT NULL,
+            criteria        TEXT NOT NULL,
+            created_by      TEXT NOT NULL,
+            updated_at      TEXT NOT NULL,
+            PRIMARY KEY (owner, name)
+        );
+        "#,
+    )
+    .execute(pool)
+    .await?;
+    rekey_by_account(pool, "smart_lists", Some("created_by")).await?;
+
+    // ── Monthly AI call budgets per tenant (core::ai_budget) ─────────────
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS ai_budgets (
+            tenant_id       INTEGER NOT NULL,
+            feature         TEXT NOT NULL,
+            monthly_calls   INTEGER NOT NULL,
+            updated_at      TEXT NOT NULL DEFAULT (datetime('now')),
+            PRIMARY KEY (tenant_id, feature)
+        );
+        "#,
+    )
+    .execute(pool)
+    .await?;
+    rekey_by_tenant_id(pool, "ai_budgets", None, false).await?;
+
+    // ── Per-tenant handling of unapproved profiles (core::profile_approval)
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS approval_policies (
+            tenant_id   INTEGER PRIMARY KEY,
+            policy      TEXT NOT NULL,
+            updated_at  TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+        "#,
+    )
+    .execute(pool)
+    .await?;
+    rekey_by_tenant_id(pool, "approval_policies", None, false).await?;
+
+    // ── Storage region of each tenant (see core::data_residency) ─────────
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS tenant_regions (
+            tenant      TEXT PRIMARY KEY,
+            region      TEXT NOT NULL,
+            updated_at  TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    // ── Scraped job postings (see core::job_postings) ───────────────────
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS job_postings (
+            url_key      TEXT PRIMARY KEY,
+            job_url      TEXT NOT NULL,
+            title        TEXT NOT NULL,
+            company      TEXT NOT NULL,
+            location     TEXT NOT NULL,
+            description  TEXT NOT NULL,
+            fetched_at   TEXT NOT NULL
+        );
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    // ── Per-tenant encrypted secrets (see core::secrets) ─────────────────
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS tenant_secrets (
+            tenant_id    INTEGER NOT NULL,
+            name         TEXT NOT NULL,
+            kind         TEXT NOT NULL,
+            value_nonce  BLOB NOT NULL,
+            value        BLOB NOT NULL,
+            key_nonce    BLOB NOT NULL,
+            wrapped_key  BLOB NOT NULL,
+            key_id       TEXT NOT NULL,
+            created_at   TEXT NOT NULL DEFAULT (datetime('now')),
+            updated_at   TEXT NOT NULL DEFAULT (datetime('now')),
+            PRIMARY KEY (tenant_id, name)
+        );
+        "#,
+    )
+    .execute(pool)
+    .await?;
+    secrets::rekey_by_tenant_id(pool).await?;
+    backfill_member_roles(pool).await?;
+
     app_log!(info, "Database migrations completed successfully");
     Ok(())
 }
 
+/// Whether `table` has `column`; lets one-off migrations run once.
+pub(crate) async fn has_column(pool: &SqlitePool, table: &str, column: &str) -> Result<bool> {
+    let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM pragma_table_info(?) WHERE name = ?")
+        .bind(table)
+        .bind(column)
+        .fetch_one(pool)
+        .await?;
+    Ok(count > 0)
+}
+
+/// Tables whose `column` names the user behind each row.
+const USER_EMAIL_COLUMNS: [(&str, &str); 8] = [
+    ("conversations", "user_email"),
+    ("audit_log", "user_email"),
+    ("service_calls", "user_email"),
+    ("file_drafts", "user_email"),
+    ("failed_imports", "user_email"),
+    ("email_intake_addresses", "user_email"),
+    ("calendar_feeds", "user_email"),
+    ("feedback", "email"),
+];
+
+/// One-off grant of `editor`, the access they had before roles existed, to
+/// the domain tenant users already known; their default is now `member`
+/// (see [`crate::core::profile_access::MemberRole::default_for`]). Grants
+/// are told apart from the admin's by their `source`.
+async fn backfill_member_roles(pool: &SqlitePool) -> Result<()> {
+    if has_column(pool, "member_roles", "source").await? {
+        return Ok(());
+    }
+    let users = USER_EMAIL_COLUMNS
+        .iter()
+        .map(|(table, column)| format!("SELECT lower({}) AS email FROM {}", column, table))
+        .collect::<Vec<_>>()
+        .join(" UNION ");
+    let mut tx = pool.begin().await?;
+    sqlx::query("ALTER TABLE member_roles ADD COLUMN source TEXT NOT NULL DEFAULT 'granted'")
+        .execute(&mut *tx)
+        .await?;
+    let granted = sqlx::query(&format!(
+        "INSERT OR IGNORE INTO member_roles (email, role, source) \
+         SELECT DISTINCT u.email, 'editor', 'backfill' FROM ({}) u \
+         JOIN tenants t ON lower(t.domain) = substr(u.email, instr(u.email, '@') + 1) \
+         WHERE t.is_active = TRUE AND instr(u.email, '@') > 0 \
+         AND NOT EXISTS (SELECT 1 FROM tenants e WHERE lower(e.email) = u.email)",
+        users
+    ))
+    .execute(&mut *tx)
+    .await?
+    .rows_affected();
+    tx.commit().await?;
+    if granted > 0 {
+        app_log!(info, "Granted editor to {} existing domain tenant users", granted);
+    }
+    Ok(())
+}
+
+/// Rows of `table` as (rowid, old tenant name, email in `email_column`).
+async fn legacy_rows(
+    conn: &mut sqlx::SqliteConnection,
+    table: &str,
+    key_column: &str,
+    email_column: Option<&str>,
+) -> Result<Vec<(i64, String, Option<String>)>> {
+    let rows = sqlx::query_as(&format!(
+        "SELECT rowid, {}, {} FROM {}",
+        key_column,
+        email_column.unwrap_or("NULL"),
+        table
+    ))
+    .fetch_all(conn)
+    .await?;
+    Ok(rows)
+}
+
+/// Email of the only tenant named `tenant_name`, when that is an email
+/// tenant.
+async fn sole_account(
+    conn: &mut sqlx::SqliteConnection,
+    tenant_name: &str,
+) -> Result<Option<String>> {
+    let emails: Vec<Option<String>> =
+        sqlx::query_scalar("SELECT email FROM tenants WHERE tenant_name = ?")
+            .bind(tenant_name)
+            .fetch_all(conn)
+            .await?;
+    Ok(match emails.as_slice() {
+        [Some(email)] => Some(email.clone()),
+        _ => None,
+    })
+}
+
+/// Id of the tenant that authorizes `email`, or else of the only tenant
+/// named `tenant_name`.
+pub(crate) async fn legacy_tenant_id(
+    conn: &mut sqlx::SqliteConnection,
+    tenant_name: &str,
+    email: Option<&str>,
+) -> Result<Option<i64>> {
+    if let Some(email) = email {
+        return Ok(sqlx::query_scalar(
+            "SELECT id FROM tenants WHERE email = ? OR domain = ? \
+             ORDER BY email NULLS LAST LIMIT 1",
+        )
+        .bind(email)
+        .bind(email.split('@').nth(1).unwrap_or(""))
+        .fetch_optional(conn)
+        .await?);
+    }
+    let ids: Vec<i64> = sqlx::query_scalar("SELECT id FROM tenants WHERE tenant_name = ?")
+        .bind(tenant_name)
+        .fetch_all(conn)
+        .await?;
+    Ok(match ids.as_slice() {
+        [id] => Some(*id),
+        _ => None,
+    })
+}
+
+/// One-off move of `table` from `tenant`, which held the tenant name, to
+/// `owner`, the account key of [`owner::account_key`]. Rows are attributed
+/// through `email_column`, or else to the only account with that tenant
+/// name; rows whose account can't be told apart are dropped.
+async fn rekey_by_account(
+    pool: &SqlitePool,
+    table: &str,
+    email_column: Option<&str>,
+) -> Result<()> {
+    if !has_column(pool, table, "tenant").await? {
+        return Ok(());
+    }
+    let mut tx = pool.begin().await?;
+    sqlx::query(&format!(
+        "ALTER TABLE {} RENAME COLUMN tenant TO owner",
+        table
+    ))
+    .execute(&mut *tx)
+    .await?;
+    let mut dropped = 0;
+    for (rowid, tenant_name, email) in legacy_rows(&mut tx, table, "owner", email_column).await? {
+        let email = match email {
+            Some(email) => Some(email),
+            None => sole_account(&mut tx, &tenant_name).await?,
+        };
+        if let Some(email) = email {
+            sqlx::query(&format!(
+                "UPDATE OR REPLACE {} SET owner = ? WHERE rowid = ?",
+                table
+            ))
+            .bind(owner::account_key(&email))
+            .bind(rowid)
+            .execute(&mut *tx)
+            .await?;
+        } else {
+            sqlx::query(&format!("DELETE FROM {} WHERE rowid = ?", table))
+                .bind(rowid)
+                .execute(&mut *tx)
+                .await?;
+            dropped += 1;
+        }
+    }
+    tx.commit().await?;
+    if dropped > 0 {
+        app_log!(
+            warn,
+            "Dropped {} rows of {} with no single owning account",
+            dropped,
+            table
+        );
+    }
+    Ok(())
+}
+
+/// Like [`rekey_by_account`] for tenant-wide tables: `tenant` becomes
+/// `tenant_id`. Rows of no known tenant are kept under id 0 when
+/// `keep_orphans` (history outlives accounts) and dropped otherwise.
+async fn rekey_by_tenant_id(
+    pool: &SqlitePool,
+    table: &str,
+    email_column: Option<&str>,
+    keep_orphans: bool,
+) -> Result<()> {
+    if !has_column(pool, table, "tenant").await? {
+        return Ok(());
+    }
+    let mut tx = pool.begin().await?;
+    sqlx::query(&format!(
+        "ALTER TABLE {} RENAME COLUMN tenant TO tenant_id",
+        table
+    ))
+    .execute(&mut *tx)
+    .await?;
+    let mut resolved: HashMap<(String, Option<String>), Option<i64>> = HashMap::new();
+    let mut orphans = 0;
+    for (rowid, tenant_name, email) in
+        legacy_rows(&mut tx, table, "tenant_id", email_column).await?
+    {
+        let id = match resolved.get(&(tenant_name.clone(), email.clone())) {
+            Some(id) => *id,
+            None => {
+                let id = legacy_tenant_id(&mut tx, &tenant_name, email.as_deref()).await?;
+                resolved.insert((tenant_name, email), id);
+                id
+            }
+        };
+        if id.is_none() {
+            orphans += 1;
+        }
+        if let Some(id) = id.or(keep_orphans.then_some(0)) {
+            sqlx::query(&format!(
+                "UPDATE OR REPLACE {} SET tenant_id = ? WHERE rowid = ?",
+                table
+            ))
+            .bind(id)
+            .bind(rowid)
+            .execute(&mut *tx)
+            .await?;
+        } else {
+            sqlx::query(&format!("DELETE FROM {} WHERE rowid = ?", table))
+                .bind(rowid)
+                .execute(&mut *tx)
+                .await?;
+        }
+    }
+    tx.commit().await?;
+    if orphans > 0 {
+        app_log!(
+            warn,
+            "{} rows of {} belong to no single tenant",
+            orphans,
+            table
+        );
+    }
+    Ok(())
+}
+
 // ===== Tenant Models =====
 
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
@@ -250,6 +910,11 @@ pub struct Tenant {
     pub referred_by_code: Option<String>,
     pub preferred_lang: Option<String>,
     pub email_prefs: Option<String>,
+    /// Set while the tenant's files are in cold storage (see
+    /// [`crate::core::tenant_archive`]).
+    #[sqlx(default)]
+    #[serde(default)]
+    pub archived_at: Option<DateTime<Utc>>,
 }
 
 impl Tenant {
@@ -273,7 +938,7 @@ impl Tenant {
 
 // ===== Legacy DatabaseConfig for backward compatibility =====
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct DatabaseConfig {
     pub database_path: PathBuf,
     pub pool: Option<SqlitePool>,
@@ -351,7 +1016,7 @@ impl<'a> TenantRepository<'a> {
 
         let tenant = sqlx::query_as::<_, Tenant>(
             r#"
-            SELECT id, email, domain, tenant_name, created_at, updated_at, is_active, last_seen_at, referred_by_code, preferred_lang, email_prefs
+            SELECT id, email, domain, tenant_name, created_at, updated_at, is_active, last_seen_at, referred_by_code, preferred_lang, email_prefs, archived_at
             FROM tenants
             WHERE is_active = TRUE AND (
                 email = ? OR domain = ?
@@ -368,6 +1033,27 @@ impl<'a> TenantRepository<'a> {
         Ok(tenant)
     }
 
+    /// Tenant an admin names by `address`: the one serving that email
+    /// address, or the domain tenant of a bare domain.
+    pub async fn find_by_address(&self, address: &str) -> Result<Option<Tenant>> {
+        let address = address.trim().to_lowercase();
+        if address.contains('@') {
+            return self.find_by_email_or_domain(&address).await;
+        }
+        let tenant = sqlx::query_as::<_, Tenant>(
+            r#"
+            SELECT id, email, domain, tenant_name, created_at, updated_at, is_active, last_seen_at, referred_by_code, preferred_lang, email_prefs, archived_at
+            FROM tenants
+            WHERE is_active = TRUE AND domain = ?
+            "#,
+        )
+        .bind(&address)
+        .fetch_optional(self.pool)
+        .await?;
+
+        Ok(tenant)
+    }
+
     /// Create tenant with specific email
     pub async fn create_email_tenant(&self, email: &str, tenant_name: &str) -> Result<Tenant> {
         let now = Utc::now();
@@ -399,6 +1085,7 @@ impl<'a> TenantRepository<'a> {
             referred_by_code: None,
             preferred_lang: Some("en".to_string()),
             email_prefs: Some("{}".to_string()),
+            archived_at: None,
         };
 
         app_log!(
@@ -441,8 +1128,11 @@ impl<'a> TenantRepository<'a> {
             referred_by_code: None,
             preferred_lang: Some("en".to_string()),
             email_prefs: Some("{}".to_string()),
+            archived_at: None,
         };
 
+        // Users of the domain may have been resolved to another tenant.
+        tenant_cache::invalidate_all();
         app_log!(
             info,
             "Created domain tenant: {} for domain: {}",
@@ -456,7 +1146,7 @@ impl<'a> TenantRepository<'a> {
     pub async fn list_active(&self) -> Result<Vec<Tenant>> {
         let tenants = sqlx::query_as::<_, Tenant>(
             r#"
-            SELECT id, email, domain, tenant_name, created_at, updated_at, is_active, last_seen_at, referred_by_code, preferred_lang, email_prefs
+            SELECT id, email, domain, tenant_name, created_at, updated_at, is_active, last_seen_at, referred_by_code, preferred_lang, email_prefs, archived_at
             FROM tenants
             WHERE is_active = TRUE
             ORDER BY tenant_name ASC, email ASC, domain ASC
@@ -475,6 +1165,7 @@ impl<'a> TenantRepository<'a> {
             .bind(email)
             .execute(self.pool)
             .await?;
+        tenant_cache::invalidate(email);
         Ok(())
     }
 
@@ -490,6 +1181,29 @@ impl<'a> TenantRepository<'a> {
         Ok(prefs.unwrap_or_else(|| "{}".to_string()))
     }
 
+    /// Role explicitly granted to `email`, if any (see
+    /// [`crate::core::profile_access::MemberRole`]).
+    pub async fn get_member_role(&self, email: &str) -> Result<Option<String>> {
+        let role = sqlx::query_scalar("SELECT role FROM member_roles WHERE email = ?")
+            .bind(email.to_lowercase())
+            .fetch_optional(self.pool)
+            .await?;
+        Ok(role)
+    }
+
+    pub async fn set_member_role(&self, email: &str, role: &str) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO member_roles (email, role) VALUES (?, ?) \
+             ON CONFLICT(email) DO UPDATE SET role = excluded.role, updated_at = datetime('now')",
+        )
+        .bind(email.to_lowercase())
+        .bind(role)
+        .execute(self.pool)
+        .await?;
+        tenant_cache::invalidate(email);
+        Ok(())
+    }
+
     /// Update email preferences JSON for a tenant.
     pub async fn update_email_prefs(&self, email: &str, prefs_json: &str) -> Result<()> {
         sqlx::query("UPDATE tenants SET email_prefs = ? WHERE email = ?")
@@ -497,6 +1211,7 @@ impl<'a> TenantRepository<'a> {
             .bind(email)
             .execute(self.pool)
             .await?;
+        tenant_cache::invalidate(email);
         Ok(())
     }
 
@@ -516,11 +1231,12 @@ impl<'a> TenantRepository<'a> {
         let cutoff = Utc::now() - chrono::Duration::days(days);
         let tenants = sqlx::query_as::<_, Tenant>(
             r#"
-            SELECT id, email, domain, tenant_name, created_at, updated_at, is_active, last_seen_at, referred_by_code, preferred_lang, email_prefs
+            SELECT id, email, domain, tenant_name, created_at, updated_at, is_active, last_seen_at, referred_by_code, preferred_lang, email_prefs, archived_at
             FROM tenants
             WHERE is_active = TRUE
               AND email IS NOT NULL
               AND domain IS NULL
+              AND archived_at IS NULL
               AND COALESCE(last_seen_at, created_at) < ?
             "#,
         )
@@ -554,6 +1270,7 @@ impl<'a> TenantRepository<'a> {
             WHERE is_active = TRUE
               AND email IS NOT NULL
               AND domain IS NULL
+              AND archived_at IS NULL
               AND created_at < ?
               AND first_cv_at IS NULL
               AND nudge_sent_at IS NULL
@@ -584,6 +1301,7 @@ impl<'a> TenantRepository<'a> {
             WHERE is_active = TRUE
               AND email IS NOT NULL
               AND domain IS NULL
+              AND archived_at IS NULL
               AND COALESCE(last_seen_at, created_at) < ?
               AND winback_sent_at IS NULL
             "#,
@@ -609,7 +1327,7 @@ impl<'a> TenantRepository<'a> {
             r#"
             SELECT id, email, tenant_name
             FROM tenants
-            WHERE is_active = TRUE AND email IS NOT NULL AND domain IS NULL
+            WHERE is_active = TRUE AND email IS NOT NULL AND domain IS NULL AND archived_at IS NULL
             "#,
         )
         .fetch_all(self.pool)
@@ -633,6 +1351,7 @@ impl<'a> TenantRepository<'a> {
 
         let updated = result.rows_affected() > 0;
         if updated {
+            tenant_cache::invalidate(email);
             app_log!(info, "Deactivated tenant for email: {}", email);
         }
 
@@ -648,6 +1367,7 @@ impl<'a> TenantRepository<'a> {
 
         let deleted = result.rows_affected() > 0;
         if deleted {
+            tenant_cache::invalidate(email);
             app_log!(info, "Hard-deleted tenant record for email: {}", email);
         }
         Ok(deleted)
@@ -668,6 +1388,7 @@ impl<'a> TenantRepository<'a> {
 
         let updated = result.rows_affected() > 0;
         if updated {
+            tenant_cache::invalidate_all();
             app_log!(info, "Deactivated tenant for domain: {}", domain);
         }
 
@@ -852,6 +1573,17 @@ pub fn get_tenant_folder_path(
     let tenant = get_tenant_for_email(email);
     let user_folder = email_to_folder_name(email);
 
-    tenant_data_path.join(tenant).join(user_folder)
+    data_residency::route(tenant_data_path, &tenant)
+        .join(tenant)
+        .join(user_folder)
+}
+
+/// Generated files mirror the data layout (`<output>/<tenant>/<user>`) so
+/// persons with the same name in different accounts never share a PDF path.
+pub fn get_tenant_output_path(email: &str, output_path: &std::path::Path) -> std::path::PathBuf {
+    let tenant = get_tenant_for_email(email);
+    data_residency::route(output_path, &tenant)
+        .join(tenant)
+        .join(email_to_folder_name(email))
 }
 
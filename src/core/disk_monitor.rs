@@ -0,0 +1,155 @@
+//! Free-space and inode checks for the data and output volumes.
+//!
+//! A full volume otherwise surfaces as a cryptic I/O error halfway through a
+//! Typst compile or an upload. Handlers call [`check`] before accepting work
+//! and turn [`StorageFull`] into a `STORAGE_FULL` response; the health
+//! endpoint reports [`usage`] for each volume.
+//!
+//! Thresholds: `DISK_MIN_FREE_MB` (default 500) and `DISK_MIN_FREE_INODES`
+//! (default 10000). On platforms without `statvfs` the checks always pass.
+
+use serde::Serialize;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+const DEFAULT_MIN_FREE_MB: u64 = 500;
+const DEFAULT_MIN_FREE_INODES: u64 = 10_000;
+
+fn min_free_bytes() -> u64 {
+    std::env::var("DISK_MIN_FREE_MB")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(DEFAULT_MIN_FREE_MB)
+        * 1024
+        * 1024
+}
+
+fn min_free_inodes() -> u64 {
+    std::env::var("DISK_MIN_FREE_INODES")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_MIN_FREE_INODES)
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct VolumeUsage {
+    pub path: PathBuf,
+    pub total_bytes: u64,
+    /// Space available to the (unprivileged) server process.
+    pub free_bytes: u64,
+    pub total_inodes: u64,
+    pub free_inodes: u64,
+    /// False when below either threshold.
+    pub ok: bool,
+}
+
+impl VolumeUsage {
+    fn is_ok(&self) -> bool {
+        self.free_bytes >= min_free_bytes()
+            // Filesystems without a fixed inode table (btrfs, some overlays) report 0.
+            && (self.total_inodes == 0 || self.free_inodes >= min_free_inodes())
+    }
+}
+
+/// Returned when a volume is below its free-space or free-inode threshold.
+#[derive(Debug)]
+pub struct StorageFull {
+    pub usage: VolumeUsage,
+}
+
+impl fmt::Display for StorageFull {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Storage almost full at {} ({} MB, {} inodes free)",
+            self.usage.path.display(),
+            self.usage.free_bytes / (1024 * 1024),
+            self.usage.free_inodes
+        )
+    }
+}
+
+impl std::error::Error for StorageFull {}
+
+#[cfg(unix)]
+fn statvfs(path: &Path) -> Option<(u64, u64, u64, u64)> {
+    use std::os::unix::ffi::OsStrExt;
+
+    let c_path = std::ffi::CString::new(path.as_os_str().as_bytes()).ok()?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    // SAFETY: c_path is NUL-terminated and stat is a valid out-pointer.
+    if unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return None;
+    }
+    let block = stat.f_frsize as u64;
+    Some((
+        stat.f_blocks as u64 * block,
+        stat.f_bavail as u64 * block,
+        stat.f_files as u64,
+        stat.f_favail as u64,
+    ))
+}
+
+#[cfg(not(unix))]
+fn statvfs(_path: &Path) -> Option<(u64, u64, u64, u64)> {
+    None
+}
+
+/// Capacity of the volume holding `path`, or `None` if it can't be queried.
+pub fn usage(path: &Path) -> Option<VolumeUsage> {
+    let (total_bytes, free_bytes, total_inodes, free_inodes) = statvfs(path)?;
+    let mut usage = VolumeUsage {
+        path: path.to_path_buf(),
+        total_bytes,
+        free_bytes,
+        total_inodes,
+        free_inodes,
+        ok: true,
+    };
+    usage.ok = usage.is_ok();
+    Some(usage)
+}
+
+/// Fail with [`StorageFull`] if any of `paths` is below a threshold. Volumes
+/// that can't be queried are not treated as full.
+pub fn check(paths: &[&Path]) -> Result<(), StorageFull> {
+    for path in paths {
+        if let Some(usage) = usage(path) {
+            if !usage.ok {
+                return Err(StorageFull { usage });
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inode_less_filesystems_are_not_full() {
+        let usage = VolumeUsage {
+            path: PathBuf::from("/data"),
+            total_bytes: 100 << 30,
+            free_bytes: 50 << 30,
+            total_inodes: 0,
+            free_inodes: 0,
+            ok: true,
+        };
+        assert!(usage.is_ok());
+
+        let exhausted = VolumeUsage {
+            total_inodes: 1_000_000,
+            free_inodes: 5,
+            ..usage.clone()
+        };
+        assert!(!exhausted.is_ok());
+
+        let no_space = VolumeUsage {
+            free_bytes: 1 << 20,
+            ..usage
+        };
+        assert!(!no_space.is_ok());
+    }
+}
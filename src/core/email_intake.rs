@@ -0,0 +1,142 @@
+// src/core/email_intake.rs
+//! Email-in CV intake addresses.
+//!
+//! Every user gets a private address `cvs+<token>@<INBOUND_EMAIL_DOMAIN>`.
+//! The mail provider (Postmark inbound, or anything posting the same JSON)
+//! forwards messages to `POST /intake/email`; the token in the recipient maps
+//! back to the user whose profiles receive the imported CVs. The token is the
+//! only thing tying a message to an account, so it is random and never derived
+//! from the email or tenant name.
+
+use anyhow::Result;
+use sqlx::SqlitePool;
+
+const DEFAULT_INTAKE_DOMAIN: &str = "cvenom.com";
+const INTAKE_MAILBOX: &str = "cvs";
+
+/// Attachments larger than this are skipped (same limit as `/upload-cv`).
+pub const MAX_ATTACHMENT_BYTES: usize = 10 * 1024 * 1024;
+
+/// Attachments processed per message; the rest are ignored.
+pub const MAX_ATTACHMENTS_PER_MESSAGE: usize = 5;
+
+/// Largest inbound message body: as many full-size attachments as are
+/// processed, base64-encoded, and 1 MiB for the rest of the message.
+pub const MAX_MESSAGE_BYTES: usize =
+    MAX_ATTACHMENTS_PER_MESSAGE * MAX_ATTACHMENT_BYTES.div_ceil(3) * 4 + 1024 * 1024;
+
+pub fn intake_domain() -> String {
+    std::env::var("INBOUND_EMAIL_DOMAIN").unwrap_or_else(|_| DEFAULT_INTAKE_DOMAIN.to_string())
+}
+
+/// Shared secret the provider sends in `X-Inbound-Secret`. Intake is disabled
+/// when unset.
+pub fn inbound_secret() -> Option<String> {
+    std::env::var("INBOUND_EMAIL_SECRET")
+        .ok()
+        .filter(|v| !v.trim().is_empty())
+}
+
+pub fn intake_address(token: &str) -> String {
+    format!("{}+{}@{}", INTAKE_MAILBOX, token, intake_domain())
+}
+
+/// Extract the token from a recipient such as `"CVs <cvs+ab12cd@cvenom.com>"`.
+/// Returns `None` for any other mailbox or domain.
+pub fn parse_intake_token(recipient: &str, domain: &str) -> Option<String> {
+    let address = match (recipient.find('<'), recipient.rfind('>')) {
+        (Some(start), Some(end)) if start < end => &recipient[start + 1..end],
+        _ => recipient,
+    };
+    let (local, host) = address.trim().rsplit_once('@')?;
+    if !host.eq_ignore_ascii_case(domain) {
+        return None;
+    }
+    let (mailbox, token) = local.split_once('+')?;
+    if !mailbox.eq_ignore_ascii_case(INTAKE_MAILBOX) {
+        return None;
+    }
+    let token = token.to_lowercase();
+    if token.is_empty() || !token.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return None;
+    }
+    Some(token)
+}
+
+/// Whether the converter accepts this attachment (PDF or DOCX).
+pub fn is_cv_attachment(file_name: &str) -> bool {
+    let lower = file_name.to_lowercase();
+    lower.ends_with(".pdf") || lower.ends_with(".docx")
+}
+
+/// The user's intake token, created on first request.
+pub async fn get_or_create_token(pool: &SqlitePool, user_email: &str) -> Result<String> {
+    if let Some(token) = sqlx::query_scalar::<_, String>(
+        "SELECT token FROM email_intake_addresses WHERE user_email = ?",
+    )
+    .bind(user_email)
+    .fetch_optional(pool)
+    .await?
+    {
+        return Ok(token);
+    }
+
+    let token = uuid::Uuid::new_v4().simple().to_string()[..16].to_string();
+    sqlx::query("INSERT OR IGNORE INTO email_intake_addresses (token, user_email) VALUES (?, ?)")
+        .bind(&token)
+        .bind(user_email)
+        .execute(pool)
+        .await?;
+
+    // A concurrent request may have won the insert — return whatever is stored.
+    let stored = sqlx::query_scalar::<_, String>(
+        "SELECT token FROM email_intake_addresses WHERE user_email = ?",
+    )
+    .bind(user_email)
+    .fetch_one(pool)
+    .await?;
+    Ok(stored)
+}
+
+/// Replace the user's token, invalidating the old address.
+pub async fn rotate_token(pool: &SqlitePool, user_email: &str) -> Result<String> {
+    sqlx::query("DELETE FROM email_intake_addresses WHERE user_email = ?")
+        .bind(user_email)
+        .execute(pool)
+        .await?;
+    get_or_create_token(pool, user_email).await
+}
+
+pub async fn find_user_by_token(pool: &SqlitePool, token: &str) -> Result<Option<String>> {
+    let email = sqlx::query_scalar::<_, String>(
+        "SELECT user_email FROM email_intake_addresses WHERE token = ?",
+    )
+    .bind(token)
+    .fetch_optional(pool)
+    .await?;
+    Ok(email)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_token_from_recipient() {
+        assert_eq!(
+            parse_intake_token("CVs <cvs+Ab12cd@cvenom.com>", "cvenom.com"),
+            Some("ab12cd".to_string())
+        );
+        assert_eq!(
+            parse_intake_token("cvs+ab12cd@CVENOM.com", "cvenom.com"),
+            Some("ab12cd".to_string())
+        );
+        assert_eq!(parse_intake_token("cvs@cvenom.com", "cvenom.com"), None);
+        assert_eq!(parse_intake_token("hr+ab12@cvenom.com", "cvenom.com"), None);
+        assert_eq!(parse_intake_token("cvs+ab12@evil.com", "cvenom.com"), None);
+        assert_eq!(
+            parse_intake_token("cvs+../x@cvenom.com", "cvenom.com"),
+            None
+        );
+    }
+}
@@ -0,0 +1,332 @@
+//! Structured event export for the analytics warehouse.
+//!
+//! Handlers call [`emit`] (fire-and-forget) for business events — CV
+//! generation, CV import, job analysis. Each event is appended to
+//! `<EVENT_EXPORT_DIR>/spool.ndjson` and fsync'd before anything else happens
+//! to it, so a crash between two flushes loses nothing. A background task calls
+//! [`flush`] periodically:
+//!
+//! * the spool is rotated into `outbox/events-<ts>-<uuid>.ndjson`;
+//! * without a push sink, outbox batches move straight to `exported/`, where
+//!   the warehouse loader picks them up;
+//! * with the `event-push` feature and `EVENT_EXPORT_PUSH_URL` set, each batch
+//!   is POSTed to the endpoint (Kafka REST proxy or a NATS HTTP bridge) and
+//!   only moves to `exported/` after a 2xx. Failed batches stay in the outbox
+//!   and are retried on the next tick — delivery is at-least-once, consumers
+//!   dedupe on `event_id`.
+//!
+//! Export is disabled entirely when `EVENT_EXPORT_DIR` is unset.
+
+use anyhow::{Context, Result};
+use graflog::app_log;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+
+const SPOOL_FILE: &str = "spool.ndjson";
+const OUTBOX_DIR: &str = "outbox";
+const EXPORTED_DIR: &str = "exported";
+const DEFAULT_FLUSH_INTERVAL_SECS: u64 = 60;
+
+/// Serialises spool appends against rotation so a batch never captures a
+/// half-written line.
+static SPOOL_LOCK: Mutex<()> = Mutex::const_new(());
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    CvGenerated,
+    CvImported,
+    JobAnalyzed,
+}
+
+impl EventKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::CvGenerated => "cv_generated",
+            Self::CvImported => "cv_imported",
+            Self::JobAnalyzed => "job_analyzed",
+        }
+    }
+}
+
+/// One line of an exported NDJSON batch.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportEvent {
+    /// Unique per event — the dedup key for at-least-once consumers.
+    pub event_id: String,
+    pub kind: EventKind,
+    pub tenant: String,
+    pub user_email: String,
+    /// RFC 3339, UTC.
+    pub occurred_at: String,
+    #[serde(default)]
+    pub properties: serde_json::Value,
+}
+
+impl ExportEvent {
+    pub fn new(
+        kind: EventKind,
+        tenant: &str,
+        user_email: &str,
+        properties: serde_json::Value,
+    ) -> Self {
+        Self {
+            event_id: uuid::Uuid::new_v4().to_string(),
+            kind,
+            tenant: tenant.to_string(),
+            user_email: user_email.to_lowercase(),
+            occurred_at: chrono::Utc::now().to_rfc3339(),
+            properties,
+        }
+    }
+}
+
+/// Export root from `EVENT_EXPORT_DIR`; `None` disables export.
+pub fn export_dir() -> Option<PathBuf> {
+    std::env::var("EVENT_EXPORT_DIR")
+        .ok()
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from)
+}
+
+/// Flush period from `EVENT_EXPORT_FLUSH_SECS` (default 60).
+pub fn flush_interval() -> Duration {
+    let secs = std::env::var("EVENT_EXPORT_FLUSH_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .filter(|v| *v > 0)
+        .unwrap_or(DEFAULT_FLUSH_INTERVAL_SECS);
+    Duration::from_secs(secs)
+}
+
+/// Fire-and-forget: spool an event on a background task.
+/// No-op when export is not configured.
+pub fn emit(kind: EventKind, tenant: &str, user_email: &str, properties: serde_json::Value) {
+    let Some(dir) = export_dir() else {
+        return;
+    };
+    let event = ExportEvent::new(kind, tenant, user_email, properties);
+    tokio::spawn(async move {
+        if let Err(e) = append_event(&dir, &event).await {
+            app_log!(
+                error,
+                "[events] Failed to spool {} event: {}",
+                event.kind.name(),
+                e
+            );
+        }
+    });
+}
+
+/// Append one event to the spool and sync it to disk.
+pub async fn append_event(dir: &Path, event: &ExportEvent) -> Result<()> {
+    let mut line = serde_json::to_string(event).context("Failed to serialize event")?;
+    line.push('\n');
+
+    let _guard = SPOOL_LOCK.lock().await;
+    tokio::fs::create_dir_all(dir)
+        .await
+        .with_context(|| format!("Failed to create {}", dir.display()))?;
+    let mut file = tokio::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(dir.join(SPOOL_FILE))
+        .await
+        .context("Failed to open event spool")?;
+    file.write_all(line.as_bytes())
+        .await
+        .context("Failed to write event spool")?;
+    file.sync_data()
+        .await
+        .context("Failed to sync event spool")?;
+    Ok(())
+}
+
+/// Move the current spool into the outbox as a new batch.
+/// Returns the batch path, or `None` when nothing was pending.
+pub async fn rotate_spool(dir: &Path) -> Result<Option<PathBuf>> {
+    let _guard = SPOOL_LOCK.lock().await;
+    let spool = dir.join(SPOOL_FILE);
+    match tokio::fs::metadata(&spool).await {
+        Ok(meta) if meta.len() > 0 => {}
+        _ => return Ok(None),
+    }
+
+    let outbox = dir.join(OUTBOX_DIR);
+    tokio::fs::create_dir_all(&outbox)
+        .await
+        .context("Failed to create event outbox")?;
+    let batch = outbox.join(format!(
+        "events-{}-{}.ndjson",
+        chrono::Utc::now().format("%Y%m%dT%H%M%SZ"),
+        uuid::Uuid::new_v4()
+    ));
+    tokio::fs::rename(&spool, &batch)
+        .await
+        .context("Failed to rotate event spool")?;
+    Ok(Some(batch))
+}
+
+/// Rotate the spool and deliver every pending outbox batch, oldest first.
+/// Stops at the first delivery failure so batches stay ordered; returns the
+/// number of batches moved to `exported/`.
+pub async fn flush(dir: &Path) -> Result<usize> {
+    rotate_spool(dir).await?;
+
+    let outbox = dir.join(OUTBOX_DIR);
+    if !outbox.exists() {
+        return Ok(0);
+    }
+    let exported = dir.join(EXPORTED_DIR);
+    tokio::fs::create_dir_all(&exported)
+        .await
+        .context("Failed to create exported dir")?;
+
+    let mut batches = Vec::new();
+    let mut entries = tokio::fs::read_dir(&outbox)
+        .await
+        .context("Failed to read event outbox")?;
+    while let Some(entry) = entries.next_entry().await? {
+        let path = entry.path();
+        if path.extension().and_then(|e| e.to_str()) == Some("ndjson") {
+            batches.push(path);
+        }
+    }
+    // File names start with a UTC timestamp, so lexical order is chronological.
+    batches.sort();
+
+    let mut delivered = 0;
+    for batch in batches {
+        if let Err(e) = deliver(&batch).await {
+            app_log!(
+                warn,
+                "[events] Batch {} not delivered, will retry: {}",
+                batch.display(),
+                e
+            );
+            break;
+        }
+        let Some(name) = batch.file_name() else {
+            continue;
+        };
+        tokio::fs::rename(&batch, exported.join(name))
+            .await
+            .with_context(|| format!("Failed to archive {}", batch.display()))?;
+        delivered += 1;
+    }
+    Ok(delivered)
+}
+
+#[cfg(not(feature = "event-push"))]
+async fn deliver(_batch: &Path) -> Result<()> {
+    Ok(())
+}
+
+/// POST a batch to `EVENT_EXPORT_PUSH_URL`. `EVENT_EXPORT_PUSH_FORMAT=kafka`
+/// wraps lines in a Kafka REST proxy v2 `records` envelope (keyed by tenant);
+/// anything else sends the raw NDJSON body. Unset URL = file export only.
+#[cfg(feature = "event-push")]
+async fn deliver(batch: &Path) -> Result<()> {
+    let Ok(url) = std::env::var("EVENT_EXPORT_PUSH_URL") else {
+        return Ok(());
+    };
+    let body = tokio::fs::read_to_string(batch)
+        .await
+        .with_context(|| format!("Failed to read {}", batch.display()))?;
+
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(30))
+        .build()
+        .context("Failed to build HTTP client")?;
+
+    let format = std::env::var("EVENT_EXPORT_PUSH_FORMAT").unwrap_or_default();
+    let mut request = if format.eq_ignore_ascii_case("kafka") {
+        let records: Vec<serde_json::Value> = body
+            .lines()
+            .filter(|l| !l.trim().is_empty())
+            .filter_map(|l| serde_json::from_str::<serde_json::Value>(l).ok())
+            .map(|event| {
+                serde_json::json!({
+                    "key": event.get("tenant").cloned().unwrap_or_default(),
+                    "value": event,
+                })
+            })
+            .collect();
+        client
+            .post(&url)
+            .header("Content-Type", "application/vnd.kafka.json.v2+json")
+            .body(serde_json::json!({ "records": records }).to_string())
+    } else {
+        client
+            .post(&url)
+            .header("Content-Type", "application/x-ndjson")
+            .body(body)
+    };
+    if let Ok(token) = std::env::var("EVENT_EXPORT_PUSH_TOKEN") {
+        request = request.bearer_auth(token);
+    }
+
+    let response = request.send().await.context("Event push request failed")?;
+    let status = response.status();
+    if !status.is_success() {
+        anyhow::bail!("Event push endpoint returned {}", status);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn sample(kind: EventKind) -> ExportEvent {
+        ExportEvent::new(
+            kind,
+            "independent",
+            "User@Example.com",
+            serde_json::json!({ "profile": "john-doe" }),
+        )
+    }
+
+    #[tokio::test]
+    async fn flush_without_events_is_noop() {
+        let tmp = TempDir::new().unwrap();
+        assert_eq!(flush(tmp.path()).await.unwrap(), 0);
+        assert!(!tmp.path().join(EXPORTED_DIR).exists());
+    }
+
+    #[tokio::test]
+    async fn spooled_events_land_in_exported_batch() {
+        let tmp = TempDir::new().unwrap();
+        append_event(tmp.path(), &sample(EventKind::CvGenerated))
+            .await
+            .unwrap();
+        append_event(tmp.path(), &sample(EventKind::CvImported))
+            .await
+            .unwrap();
+
+        assert_eq!(flush(tmp.path()).await.unwrap(), 1);
+        assert!(!tmp.path().join(SPOOL_FILE).exists());
+
+        let exported: Vec<_> = std::fs::read_dir(tmp.path().join(EXPORTED_DIR))
+            .unwrap()
+            .map(|e| e.unwrap().path())
+            .collect();
+        assert_eq!(exported.len(), 1);
+
+        let body = std::fs::read_to_string(&exported[0]).unwrap();
+        let events: Vec<ExportEvent> = body
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].kind, EventKind::CvGenerated);
+        assert_eq!(events[1].kind, EventKind::CvImported);
+        assert_eq!(events[0].user_email, "user@example.com");
+        assert_ne!(events[0].event_id, events[1].event_id);
+    }
+}
@@ -0,0 +1,134 @@
+// src/core/experiences_fallback.rs
+//! What a generation compiles when the profile has no experiences in the
+//! requested language.
+//!
+//! `EXPERIENCES_FALLBACK` lists what to use instead, in order: a language
+//! code for the profile's experiences in that language, `template` for the
+//! placeholder experiences new profiles start with. The default is
+//! `en,template`; `off` makes a missing file an error again. The profile is
+//! left as it is and the generation reports which fallback it used.
+
+use std::path::Path;
+
+const ENV_VAR: &str = "EXPERIENCES_FALLBACK";
+const DEFAULT_CHAIN: &str = "en,template";
+const TEMPLATE_STEP: &str = "template";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Fallback {
+    /// The profile's experiences in this language.
+    Language(String),
+    /// The template's placeholder experiences.
+    Template,
+}
+
+impl Fallback {
+    /// Warning returned with a CV generated in `lang` through this fallback.
+    pub fn warning(&self, lang: &str) -> String {
+        match self {
+            Self::Language(other) => format!(
+                "No experiences_{}.typ: the CV was generated with the '{}' experiences",
+                lang, other
+            ),
+            Self::Template => format!(
+                "No experiences_{}.typ: the CV was generated with placeholder experiences",
+                lang
+            ),
+        }
+    }
+}
+
+/// The profile has no experiences in `lang` and no fallback applies.
+#[derive(Debug)]
+pub struct MissingExperiences {
+    pub lang: String,
+}
+
+impl std::fmt::Display for MissingExperiences {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "The profile has no experiences_{}.typ", self.lang)
+    }
+}
+
+impl std::error::Error for MissingExperiences {}
+
+pub fn experiences_file(lang: &str) -> String {
+    format!("experiences_{}.typ", lang)
+}
+
+fn parse(spec: &str) -> Vec<Fallback> {
+    spec.split(',')
+        .map(|step| step.trim().to_lowercase())
+        .filter(|step| !step.is_empty() && step != "off")
+        .filter(|step| step.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'))
+        .map(|step| match step.as_str() {
+            TEMPLATE_STEP => Fallback::Template,
+            _ => Fallback::Language(step),
+        })
+        .collect()
+}
+
+/// The configured chain, after the requested language.
+pub fn chain() -> Vec<Fallback> {
+    parse(&std::env::var(ENV_VAR).unwrap_or_else(|_| DEFAULT_CHAIN.to_string()))
+}
+
+fn resolve_with(
+    profile_dir: &Path,
+    lang: &str,
+    chain: &[Fallback],
+) -> Result<Option<Fallback>, MissingExperiences> {
+    if profile_dir.join(experiences_file(lang)).exists() {
+        return Ok(None);
+    }
+    chain
+        .iter()
+        .find(|fallback| match fallback {
+            Fallback::Language(other) => {
+                other != lang && profile_dir.join(experiences_file(other)).exists()
+            }
+            Fallback::Template => true,
+        })
+        .cloned()
+        .map(Some)
+        .ok_or_else(|| MissingExperiences {
+            lang: lang.to_string(),
+        })
+}
+
+/// `None` when the profile has experiences in `lang`, otherwise the first
+/// fallback of the chain that applies.
+pub fn resolve(profile_dir: &Path, lang: &str) -> Result<Option<Fallback>, MissingExperiences> {
+    resolve_with(profile_dir, lang, &chain())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_first_available_fallback_is_used() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("experiences_en.typ"), "").unwrap();
+        let chain = parse("de, EN ,template,../x");
+        assert_eq!(
+            chain,
+            vec![
+                Fallback::Language("de".to_string()),
+                Fallback::Language("en".to_string()),
+                Fallback::Template,
+            ]
+        );
+
+        assert_eq!(resolve_with(dir.path(), "en", &chain).unwrap(), None);
+        assert_eq!(
+            resolve_with(dir.path(), "fr", &chain).unwrap(),
+            Some(Fallback::Language("en".to_string()))
+        );
+        assert_eq!(
+            resolve_with(dir.path(), "fr", &parse("de,template")).unwrap(),
+            Some(Fallback::Template)
+        );
+        assert!(resolve_with(dir.path(), "fr", &parse("off")).is_err());
+    }
+}
@@ -0,0 +1,103 @@
+// src/core/file_drafts.rs
+//! Unsaved editor content, kept per user and file.
+//!
+//! The studio autosaves what is in the editor to `file_drafts` so a closed
+//! tab doesn't lose work. Drafts never touch the profile files: saving the
+//! file through `/files/save` discards the draft, and drafts older than
+//! `FILE_DRAFT_TTL_DAYS` (default 7) are ignored and purged on the next save.
+
+use anyhow::{bail, Result};
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use sqlx::SqlitePool;
+
+const DEFAULT_TTL_DAYS: i64 = 7;
+/// Editor files are small sources; anything bigger is not an autosave.
+pub const MAX_DRAFT_BYTES: usize = 512 * 1024;
+
+fn ttl() -> Duration {
+    let days = std::env::var("FILE_DRAFT_TTL_DAYS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .filter(|d| *d > 0)
+        .unwrap_or(DEFAULT_TTL_DAYS);
+    Duration::days(days)
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct FileDraft {
+    pub path: String,
+    pub content: String,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DraftView {
+    #[serde(flatten)]
+    pub draft: FileDraft,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl From<FileDraft> for DraftView {
+    fn from(draft: FileDraft) -> Self {
+        let expires_at = draft.updated_at + ttl();
+        Self { draft, expires_at }
+    }
+}
+
+/// Store (or replace) the draft of `path` for `user_email`.
+pub async fn save(
+    pool: &SqlitePool,
+    user_email: &str,
+    path: &str,
+    content: &str,
+) -> Result<FileDraft> {
+    if content.len() > MAX_DRAFT_BYTES {
+        bail!("Draft is larger than {} bytes", MAX_DRAFT_BYTES);
+    }
+    let now = Utc::now();
+    sqlx::query("DELETE FROM file_drafts WHERE updated_at < ?")
+        .bind(now - ttl())
+        .execute(pool)
+        .await?;
+    sqlx::query(
+        "INSERT INTO file_drafts (user_email, path, content, updated_at) VALUES (?, ?, ?, ?) \
+         ON CONFLICT(user_email, path) DO UPDATE SET content = excluded.content, \
+         updated_at = excluded.updated_at",
+    )
+    .bind(user_email.to_lowercase())
+    .bind(path)
+    .bind(content)
+    .bind(now)
+    .execute(pool)
+    .await?;
+    Ok(FileDraft {
+        path: path.to_string(),
+        content: content.to_string(),
+        updated_at: now,
+    })
+}
+
+/// The unexpired draft of `path`, if any.
+pub async fn load(pool: &SqlitePool, user_email: &str, path: &str) -> Result<Option<FileDraft>> {
+    let draft = sqlx::query_as::<_, FileDraft>(
+        "SELECT path, content, updated_at FROM file_drafts \
+         WHERE user_email = ? AND path = ? AND updated_at >= ?",
+    )
+    .bind(user_email.to_lowercase())
+    .bind(path)
+    .bind(Utc::now() - ttl())
+    .fetch_optional(pool)
+    .await?;
+    Ok(draft)
+}
+
+/// Drop the draft of `path`; true if there was one.
+pub async fn discard(pool: &SqlitePool, user_email: &str, path: &str) -> Result<bool> {
+    let result = sqlx::query("DELETE FROM file_drafts WHERE user_email = ? AND path = ?")
+        .bind(user_email.to_lowercase())
+        .bind(path)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
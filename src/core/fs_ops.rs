@@ -6,6 +6,8 @@ use std::path::{Path, PathBuf};
 use tokio::fs;
 use graflog::app_log;
 
+use crate::core::data_encryption;
+
 pub struct FsOps;
 
 impl FsOps {
@@ -20,23 +22,46 @@ impl FsOps {
         Ok(())
     }
 
-    /// Read file safely - replaces all duplicate read_file_safe functions
+    /// Read file safely - replaces all duplicate read_file_safe functions.
+    /// Encrypted CV content is decrypted (see [`data_encryption`]).
     pub async fn read_file_safe(path: &Path) -> Result<String> {
-        fs::read_to_string(path)
+        let bytes = fs::read(path)
             .await
-            .with_context(|| format!("Failed to read file: {}", path.display()))
+            .with_context(|| format!("Failed to read file: {}", path.display()))?;
+        let bytes = data_encryption::open(path, bytes)
+            .with_context(|| format!("Failed to decrypt file: {}", path.display()))?;
+        String::from_utf8(bytes).with_context(|| format!("Failed to read file: {}", path.display()))
     }
 
     /// Write file safely - replaces all duplicate write_file_safe functions
     pub async fn write_file_safe(path: &Path, content: &str) -> Result<()> {
+        Self::write_bytes_safe(path, content.as_bytes()).await
+    }
+
+    /// Write via a hidden sibling temp file and rename it into place, so a
+    /// concurrent reader sees the old or the new content, never a partial file.
+    /// CV content of tenants with at-rest encryption is encrypted.
+    pub async fn write_bytes_safe(path: &Path, content: &[u8]) -> Result<()> {
+        let content = data_encryption::seal_for(path, content)
+            .with_context(|| format!("Failed to encrypt file: {}", path.display()))?;
+
         // Ensure parent directory exists
         if let Some(parent) = path.parent() {
             Self::ensure_dir_exists(parent).await?;
         }
 
-        fs::write(path, content)
+        let file_name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let tmp = path.with_file_name(format!(".{}.{}.tmp", file_name, uuid::Uuid::new_v4()));
+        fs::write(&tmp, &*content)
             .await
             .with_context(|| format!("Failed to write file: {}", path.display()))?;
+        if let Err(e) = fs::rename(&tmp, path).await {
+            let _ = fs::remove_file(&tmp).await;
+            return Err(e).with_context(|| format!("Failed to write file: {}", path.display()));
+        }
 
         app_log!(info, "Written file: {}", path.display());
         Ok(())
@@ -93,7 +118,7 @@ impl FsOps {
             }
         }
 
-        profiles.sort();
+        profiles.sort_by(|a, b| crate::utils::collate_names(a, b));
         Ok(profiles)
     }
 
@@ -0,0 +1,225 @@
+//! Coalescing of identical in-flight generations.
+//!
+//! The studio sometimes fires the same generate request twice (double-clicks).
+//! Rather than compiling twice — or making the second caller wait on the
+//! generation lock and compile again — requests with the same
+//! [`generation_key`] (tenant, person, template, lang and a hash of the
+//! options and profile files) share one compilation. The first caller becomes
+//! the [`FlightLeader`] and runs the work; later callers get a
+//! [`Flight::Follower`] future resolving to the same result.
+//!
+//! The work is spawned, so a leader whose client disconnects still delivers
+//! the PDF to its followers. The key is released as soon as the compilation
+//! finishes: a request arriving afterwards starts a fresh one.
+
+use futures::future::{BoxFuture, FutureExt, Shared};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::future::Future;
+use std::hash::{Hash, Hasher};
+use std::path::Path;
+use std::sync::{Arc, LazyLock, Mutex};
+use tokio::sync::oneshot;
+
+use crate::config::CvConfig;
+use crate::generator::GeneratedCv;
+
+/// Result shared by every caller of one compilation.
+pub type GenerationOutcome = Result<GeneratedCv, Arc<anyhow::Error>>;
+
+type SharedOutcome = Shared<BoxFuture<'static, GenerationOutcome>>;
+
+static IN_FLIGHT: LazyLock<Mutex<HashMap<String, SharedOutcome>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+fn in_flight() -> std::sync::MutexGuard<'static, HashMap<String, SharedOutcome>> {
+    IN_FLIGHT
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Hash of everything besides the key fields that changes the output: the
+/// generation options of `config` (watermark, draft, PDF metadata and
+/// template inputs included), the brand and the name, size and mtime of each
+/// profile file.
+pub fn request_hash(profile_dir: &Path, config: &CvConfig, brand_slug: Option<&str>) -> String {
+    let mut hasher = DefaultHasher::new();
+    profile_dir.hash(&mut hasher);
+    config.use_custom_colors.hash(&mut hasher);
+    brand_slug.map(str::trim).hash(&mut hasher);
+    config.features.as_deref().hash(&mut hasher);
+    config.strict_assets.hash(&mut hasher);
+    config.strict.hash(&mut hasher);
+    config.format.hash(&mut hasher);
+    config.pdf_a.hash(&mut hasher);
+    config.watermark.hash(&mut hasher);
+    config.draft_generated_at.hash(&mut hasher);
+    config.inputs.hash(&mut hasher);
+    // Not the creation date, which differs between two identical clicks.
+    let metadata = &config.pdf_metadata;
+    (&metadata.title, &metadata.author, &metadata.subject).hash(&mut hasher);
+    metadata.keywords.hash(&mut hasher);
+
+    let mut files: Vec<(String, u64, Option<std::time::SystemTime>)> =
+        std::fs::read_dir(profile_dir)
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .filter_map(|entry| {
+                        let meta = entry.metadata().ok()?;
+                        meta.is_file().then(|| {
+                            (
+                                entry.file_name().to_string_lossy().to_string(),
+                                meta.len(),
+                                meta.modified().ok(),
+                            )
+                        })
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+    files.sort();
+    files.hash(&mut hasher);
+
+    format!("{:016x}", hasher.finish())
+}
+
+pub fn generation_key(
+    tenant: &str,
+    person: &str,
+    template: &str,
+    lang: &str,
+    hash: &str,
+) -> String {
+    format!("{}/{}/{}/{}/{}", tenant, person, template, lang, hash)
+}
+
+pub enum Flight {
+    /// No identical generation is running: the caller must run it.
+    Leader(FlightLeader),
+    /// An identical generation is running: await its result.
+    Follower(SharedOutcome),
+}
+
+/// Owns an in-flight key. Dropping it without calling [`FlightLeader::run`]
+/// (e.g. the leader ran out of credits) releases the key and fails followers.
+pub struct FlightLeader {
+    key: String,
+    tx: Option<oneshot::Sender<GenerationOutcome>>,
+}
+
+impl Drop for FlightLeader {
+    fn drop(&mut self) {
+        in_flight().remove(&self.key);
+    }
+}
+
+impl FlightLeader {
+    /// Run the compilation to completion (even if the caller goes away) and
+    /// hand its result to every follower.
+    pub async fn run<F>(mut self, work: F) -> GenerationOutcome
+    where
+        F: Future<Output = anyhow::Result<GeneratedCv>> + Send + 'static,
+    {
+        let tx = self.tx.take();
+        let handle = tokio::spawn(async move {
+            let outcome = work.await.map_err(Arc::new);
+            // Release the key before waking followers so a retry after a
+            // failure starts a new compilation.
+            drop(self);
+            if let Some(tx) = tx {
+                let _ = tx.send(outcome.clone());
+            }
+            outcome
+        });
+        handle
+            .await
+            .unwrap_or_else(|e| Err(Arc::new(anyhow::anyhow!("Generation task failed: {}", e))))
+    }
+}
+
+/// Join the in-flight generation for `key`, or become its leader.
+pub fn join(key: &str) -> Flight {
+    let mut map = in_flight();
+    if let Some(shared) = map.get(key) {
+        return Flight::Follower(shared.clone());
+    }
+
+    let (tx, rx) = oneshot::channel::<GenerationOutcome>();
+    let shared = async move {
+        rx.await.unwrap_or_else(|_| {
+            Err(Arc::new(anyhow::anyhow!(
+                "The identical generation this request joined was abandoned"
+            )))
+        })
+    }
+    .boxed()
+    .shared();
+    map.insert(key.to_string(), shared);
+
+    Flight::Leader(FlightLeader {
+        key: key.to_string(),
+        tx: Some(tx),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[tokio::test]
+    async fn identical_requests_share_one_compilation() {
+        let key = generation_key("tenant-1", "jane_doe", "default", "en", "abc");
+        let runs = Arc::new(AtomicUsize::new(0));
+
+        let Flight::Leader(leader) = join(&key) else {
+            panic!("first caller must lead");
+        };
+        let Flight::Follower(follower) = join(&key) else {
+            panic!("second caller must follow");
+        };
+
+        let counter = runs.clone();
+        let led = leader.run(async move {
+            counter.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+            Ok(GeneratedCv {
+                output_path: PathBuf::from("out/jane_doe_default_en.pdf"),
+                skipped_assets: Vec::new(),
+                cached: false,
+            })
+        });
+        let (led, followed) = tokio::join!(led, follower);
+
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+        assert_eq!(led.unwrap(), followed.unwrap());
+        // Finished flights are released.
+        assert!(matches!(join(&key), Flight::Leader(_)));
+    }
+
+    #[test]
+    fn drafts_do_not_join_published_generations() {
+        let tmp = tempfile::tempdir().unwrap();
+        let config = || CvConfig::new("jane_doe", "en");
+        let published = request_hash(tmp.path(), &config(), None);
+        let draft = request_hash(tmp.path(), &config().with_draft("now".to_string()), None);
+        assert_ne!(published, draft);
+        assert_eq!(published, request_hash(tmp.path(), &config(), None));
+
+        let tagged = config().with_inputs(vec![("tags".to_string(), "bench".to_string())]);
+        assert_ne!(published, request_hash(tmp.path(), &tagged, None));
+    }
+
+    #[tokio::test]
+    async fn abandoned_leader_fails_followers() {
+        let key = generation_key("tenant-1", "john_doe", "default", "fr", "abc");
+        let leader = join(&key);
+        let Flight::Follower(follower) = join(&key) else {
+            panic!("second caller must follow");
+        };
+        drop(leader);
+        assert!(follower.await.is_err());
+    }
+}
@@ -0,0 +1,124 @@
+//! Per-person generation locks.
+//!
+//! Two generations for the same person write the same output path
+//! (`{profile}_{template}_{lang}.pdf`) and share workspace files, so they must
+//! not overlap. [`acquire`] hands out an async mutex keyed by the person's
+//! data directory (which already encodes tenant + person). A second request
+//! waits up to `GENERATION_LOCK_WAIT_SECS` (default 60, `0` = fail fast) and
+//! then gives up with [`GenerationBusy`], which handlers surface as a busy
+//! response instead of a generic failure.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::OwnedMutexGuard;
+
+const DEFAULT_WAIT_SECS: u64 = 60;
+
+type LockMap = HashMap<PathBuf, Arc<tokio::sync::Mutex<()>>>;
+
+static LOCKS: LazyLock<Mutex<LockMap>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Returned when the lock could not be obtained within the wait budget.
+#[derive(Debug)]
+pub struct GenerationBusy {
+    pub person: String,
+    pub waited: Duration,
+}
+
+impl fmt::Display for GenerationBusy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Another generation for '{}' is still running (waited {}ms)",
+            self.person,
+            self.waited.as_millis()
+        )
+    }
+}
+
+impl std::error::Error for GenerationBusy {}
+
+/// Held for the duration of a generation; releases the lock on drop.
+pub struct GenerationGuard {
+    _guard: OwnedMutexGuard<()>,
+    /// Time spent waiting for the lock — logged by callers.
+    pub waited: Duration,
+}
+
+fn wait_budget() -> Duration {
+    let secs = std::env::var("GENERATION_LOCK_WAIT_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(DEFAULT_WAIT_SECS);
+    Duration::from_secs(secs)
+}
+
+fn lock_for(person_dir: &Path) -> Arc<tokio::sync::Mutex<()>> {
+    let mut map = LOCKS
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    // Drop entries nobody holds or waits on so the map doesn't grow forever.
+    map.retain(|_, m| Arc::strong_count(m) > 1);
+    map.entry(person_dir.to_path_buf())
+        .or_insert_with(|| Arc::new(tokio::sync::Mutex::new(())))
+        .clone()
+}
+
+/// Acquire the generation lock for `person_dir`, waiting up to the configured budget.
+pub async fn acquire(person_dir: &Path) -> Result<GenerationGuard, GenerationBusy> {
+    acquire_with_budget(person_dir, wait_budget()).await
+}
+
+pub async fn acquire_with_budget(
+    person_dir: &Path,
+    budget: Duration,
+) -> Result<GenerationGuard, GenerationBusy> {
+    let mutex = lock_for(person_dir);
+    let started = Instant::now();
+
+    let guard = if budget.is_zero() {
+        mutex.try_lock_owned().ok()
+    } else {
+        tokio::time::timeout(budget, mutex.lock_owned()).await.ok()
+    };
+
+    match guard {
+        Some(guard) => Ok(GenerationGuard {
+            _guard: guard,
+            waited: started.elapsed(),
+        }),
+        None => Err(GenerationBusy {
+            person: person_dir
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            waited: started.elapsed(),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn second_caller_is_busy_while_first_holds_lock() {
+        let dir = PathBuf::from("/tmp/cvenom-lock-test/tenant/jane");
+        let first = acquire_with_budget(&dir, Duration::from_secs(1))
+            .await
+            .unwrap();
+
+        let busy = acquire_with_budget(&dir, Duration::ZERO).await;
+        assert!(busy.is_err());
+
+        // Different person is unaffected.
+        let other = PathBuf::from("/tmp/cvenom-lock-test/tenant/john");
+        assert!(acquire_with_budget(&other, Duration::ZERO).await.is_ok());
+
+        drop(first);
+        assert!(acquire_with_budget(&dir, Duration::ZERO).await.is_ok());
+    }
+}
@@ -0,0 +1,218 @@
+// src/core/generation_outputs.rs
+//! Deleting generated CVs.
+//!
+//! Generated PDFs sit in the person's output folder and are identified by
+//! [`generation_id`]. Deleting one removes the file, records it in the audit
+//! log and leaves a tombstone keyed by its `/outputs` path: the
+//! `/outputs/...` link handed out for it then answers that the CV was deleted
+//! instead of a bare 404. A later generation written to the same path is
+//! served again, since the link names the path, not the generation.
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::SqlitePool;
+use std::path::{Path, PathBuf};
+
+use crate::core::audit_log::{self, AuditAction};
+use crate::core::{data_residency, owner};
+use crate::utils::generation_id;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DeletedGeneration {
+    pub generation_id: String,
+    pub profile: String,
+    pub filename: String,
+    pub size: u64,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct Tombstone {
+    pub generation_id: String,
+    pub profile: String,
+    pub deleted_at: DateTime<Utc>,
+}
+
+/// A generated PDF on disk.
+struct Output {
+    profile: String,
+    path: PathBuf,
+    modified: Option<DateTime<Utc>>,
+}
+
+/// Tombstone key of a file at `relative` under its output root, as `/outputs`
+/// receives it.
+pub fn share_key(relative: &Path) -> String {
+    relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().to_string())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Account key of the file at `relative`: its `<tenant>/<user>` folders.
+fn owner_of(relative: &Path) -> String {
+    let mut folders = relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().to_string());
+    let tenant = folders.next().unwrap_or_default();
+    let user = folders.next().unwrap_or_default();
+    owner::account_key_of(&tenant, &user)
+}
+
+/// PDFs of one person folder, or of every person of `user_dir`.
+async fn outputs(user_dir: &Path, profile: Option<&str>) -> Vec<Output> {
+    let mut dirs = Vec::new();
+    match profile {
+        Some(profile) => dirs.push((profile.to_string(), user_dir.join(profile))),
+        None => {
+            if let Ok(mut persons) = tokio::fs::read_dir(user_dir).await {
+                while let Ok(Some(person)) = persons.next_entry().await {
+                    let name = person.file_name().to_string_lossy().to_string();
+                    dirs.push((name, person.path()));
+                }
+            }
+        }
+    }
+    let mut found = Vec::new();
+    for (profile, dir) in dirs {
+        let Ok(mut entries) = tokio::fs::read_dir(&dir).await else {
+            continue;
+        };
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            if !entry.file_name().to_string_lossy().ends_with(".pdf") {
+                continue;
+            }
+            let modified = entry
+                .metadata()
+                .await
+                .and_then(|meta| meta.modified())
+                .ok()
+                .map(DateTime::<Utc>::from);
+            found.push(Output {
+                profile: profile.clone(),
+                path: entry.path(),
+                modified,
+            });
+        }
+    }
+    found
+}
+
+/// Profile and path of generation `id` among the outputs of `user_dir`.
+pub async fn find(user_dir: &Path, id: &str) -> Option<(String, PathBuf)> {
+    outputs(user_dir, None)
+        .await
+        .into_iter()
+        .find(|output| generation_id(&output.path).as_deref() == Some(id))
+        .map(|output| (output.profile, output.path))
+}
+
+/// Delete one generated file, e.g. found by [`find`], on behalf of `email`.
+/// The deletion is logged for tenant `tenant_id` when it is known.
+pub async fn delete(
+    pool: &SqlitePool,
+    output_dir: &Path,
+    tenant_id: Option<i64>,
+    email: &str,
+    profile: &str,
+    path: &Path,
+) -> Result<DeletedGeneration> {
+    let id = generation_id(path).unwrap_or_default();
+    let size = tokio::fs::metadata(path).await?.len();
+    tokio::fs::remove_file(path).await?;
+    let filename = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let relative = data_residency::output_relative(output_dir, path);
+    let key = share_key(relative);
+    sqlx::query(
+        "INSERT OR REPLACE INTO deleted_generations \
+         (path, owner, generation_id, profile, deleted_by, deleted_at) \
+         VALUES (?, ?, ?, ?, ?, ?)",
+    )
+    .bind(&key)
+    .bind(owner_of(relative))
+    .bind(&id)
+    .bind(profile)
+    .bind(email.to_lowercase())
+    .bind(Utc::now())
+    .execute(pool)
+    .await?;
+    if let Some(tenant_id) = tenant_id {
+        audit_log::record(
+            pool,
+            tenant_id,
+            email,
+            AuditAction::GenerationDeleted,
+            &format!("{}/{}", profile, filename),
+        );
+    }
+    Ok(DeletedGeneration {
+        generation_id: id,
+        profile: profile.to_string(),
+        filename,
+        size,
+    })
+}
+
+/// Delete the generated files of `profile` in `user_dir`, only those written
+/// before `before` when given.
+pub async fn purge(
+    pool: &SqlitePool,
+    output_dir: &Path,
+    user_dir: &Path,
+    tenant_id: Option<i64>,
+    email: &str,
+    profile: &str,
+    before: Option<DateTime<Utc>>,
+) -> Result<Vec<DeletedGeneration>> {
+    let mut deleted = Vec::new();
+    for output in outputs(user_dir, Some(profile)).await {
+        let old_enough = match (before, output.modified) {
+            (Some(before), Some(modified)) => modified < before,
+            (Some(_), None) => false,
+            (None, _) => true,
+        };
+        if old_enough {
+            deleted.push(delete(pool, output_dir, tenant_id, email, profile, &output.path).await?);
+        }
+    }
+    Ok(deleted)
+}
+
+/// The tombstone left at `key` (see [`share_key`]), if its file was deleted.
+pub async fn tombstone(pool: &SqlitePool, key: &str) -> Result<Option<Tombstone>> {
+    let tombstone = sqlx::query_as::<_, Tombstone>(
+        "SELECT generation_id, profile, deleted_at FROM deleted_generations WHERE path = ?",
+    )
+    .bind(key)
+    .fetch_optional(pool)
+    .await?;
+    Ok(tombstone)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn generations_are_found_by_id() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().join("jane");
+        std::fs::create_dir(&dir).unwrap();
+        std::fs::write(dir.join("jane_default_en.pdf"), b"%PDF").unwrap();
+        std::fs::write(dir.join("notes.txt"), b"not an output").unwrap();
+
+        let id = generation_id(&dir.join("jane_default_en.pdf")).unwrap();
+        let (profile, path) = find(tmp.path(), &id).await.unwrap();
+        assert_eq!(profile, "jane");
+        assert_eq!(path, dir.join("jane_default_en.pdf"));
+        assert!(find(tmp.path(), "0000000000000000").await.is_none());
+        assert_eq!(
+            share_key(Path::new("acme.com/jane_at_acme_com/jane/jane_default_en.pdf")),
+            "acme.com/jane_at_acme_com/jane/jane_default_en.pdf"
+        );
+    }
+}
@@ -0,0 +1,277 @@
+//! Fair admission control for PDF generation across tenants.
+//!
+//! Typst compilation is the expensive part of a request, so it runs behind a
+//! global slot limit (`GENERATION_MAX_CONCURRENCY`, default 4). Every tenant
+//! also has a concurrency cap derived from its plan; requests beyond the cap
+//! — or beyond the global limit — wait in a per-tenant FIFO queue. When a
+//! slot frees up, the scheduler round-robins across tenants with waiters, so a
+//! tenant submitting 100 generations gets one slot at a time alongside
+//! everyone else instead of filling the whole pool. Callers take the
+//! person's generation lock first ([`crate::CvGenerator::lock`]), so a
+//! request waiting for a busy person holds no slot.
+//!
+//! Plans:
+//! * `GENERATION_TENANT_PLANS="acme=enterprise,cgi=team"` pins a tenant (by
+//!   tenant name) to a plan;
+//! * otherwise domain tenants are `team` and email tenants are `individual`;
+//! * `GENERATION_PLAN_CAPS="individual=1,team=3,enterprise=6"` overrides the
+//!   per-plan caps (these are also the defaults).
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::oneshot;
+
+use crate::core::database::Tenant;
+
+const DEFAULT_MAX_CONCURRENCY: usize = 4;
+const DEFAULT_PLAN_CAPS: &[(&str, usize)] = &[("individual", 1), ("team", 3), ("enterprise", 6)];
+
+struct SchedulerState {
+    max_concurrency: usize,
+    running_total: usize,
+    running: HashMap<String, usize>,
+    caps: HashMap<String, usize>,
+    queues: HashMap<String, VecDeque<oneshot::Sender<GenerationPermit>>>,
+    /// Tenants with queued waiters, in round-robin order.
+    rotation: VecDeque<String>,
+}
+
+static STATE: LazyLock<Mutex<SchedulerState>> = LazyLock::new(|| {
+    let max_concurrency = std::env::var("GENERATION_MAX_CONCURRENCY")
+        .ok()
+        .and_then(|v| v.parse::<usize>().ok())
+        .filter(|v| *v > 0)
+        .unwrap_or(DEFAULT_MAX_CONCURRENCY);
+    Mutex::new(SchedulerState {
+        max_concurrency,
+        running_total: 0,
+        running: HashMap::new(),
+        caps: HashMap::new(),
+        queues: HashMap::new(),
+        rotation: VecDeque::new(),
+    })
+});
+
+fn state() -> std::sync::MutexGuard<'static, SchedulerState> {
+    STATE
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+fn parse_pairs(raw: &str) -> Vec<(String, String)> {
+    raw.split(',')
+        .filter_map(|pair| {
+            let (k, v) = pair.split_once('=')?;
+            let (k, v) = (k.trim(), v.trim());
+            (!k.is_empty() && !v.is_empty()).then(|| (k.to_lowercase(), v.to_lowercase()))
+        })
+        .collect()
+}
+
+/// Resolve the plan name used for scheduling decisions.
+pub fn plan_for_tenant(tenant: &Tenant) -> String {
+    let pinned = std::env::var("GENERATION_TENANT_PLANS")
+        .ok()
+        .and_then(|raw| {
+            let name = tenant.tenant_name.to_lowercase();
+            parse_pairs(&raw)
+                .into_iter()
+                .find(|(k, _)| *k == name)
+                .map(|(_, plan)| plan)
+        });
+    pinned.unwrap_or_else(|| {
+        if tenant.domain.is_some() {
+            "team".to_string()
+        } else {
+            "individual".to_string()
+        }
+    })
+}
+
+/// Per-tenant concurrency cap for a plan. Unknown plans get the smallest cap.
+pub fn cap_for_plan(plan: &str) -> usize {
+    let plan = plan.to_lowercase();
+    if let Ok(raw) = std::env::var("GENERATION_PLAN_CAPS") {
+        if let Some(cap) = parse_pairs(&raw)
+            .into_iter()
+            .find(|(k, _)| *k == plan)
+            .and_then(|(_, v)| v.parse::<usize>().ok())
+        {
+            return cap.max(1);
+        }
+    }
+    DEFAULT_PLAN_CAPS
+        .iter()
+        .find(|(name, _)| *name == plan)
+        .map(|(_, cap)| *cap)
+        .unwrap_or(1)
+}
+
+/// A granted generation slot; frees it (and admits the next waiter) on drop.
+pub struct GenerationPermit {
+    tenant: String,
+    armed: bool,
+    /// Time spent queued before the slot was granted.
+    pub waited: Duration,
+}
+
+impl Drop for GenerationPermit {
+    fn drop(&mut self) {
+        if !self.armed {
+            return;
+        }
+        let mut st = state();
+        st.running_total = st.running_total.saturating_sub(1);
+        if let Some(n) = st.running.get_mut(&self.tenant) {
+            *n = n.saturating_sub(1);
+            if *n == 0 {
+                st.running.remove(&self.tenant);
+            }
+        }
+        dispatch(&mut st);
+    }
+}
+
+/// Hand free slots to queued waiters, one tenant at a time in rotation.
+fn dispatch(st: &mut SchedulerState) {
+    let mut idle_passes = 0;
+    while st.running_total < st.max_concurrency && !st.rotation.is_empty() {
+        if idle_passes >= st.rotation.len() {
+            break; // every waiting tenant is at its cap
+        }
+        let Some(tenant) = st.rotation.pop_front() else {
+            break;
+        };
+        let cap = st.caps.get(&tenant).copied().unwrap_or(1);
+        let running = st.running.get(&tenant).copied().unwrap_or(0);
+
+        if running >= cap {
+            st.rotation.push_back(tenant);
+            idle_passes += 1;
+            continue;
+        }
+
+        let Some(tx) = st.queues.get_mut(&tenant).and_then(|q| q.pop_front()) else {
+            st.queues.remove(&tenant);
+            continue;
+        };
+
+        st.running_total += 1;
+        *st.running.entry(tenant.clone()).or_insert(0) += 1;
+        let permit = GenerationPermit {
+            tenant: tenant.clone(),
+            armed: true,
+            waited: Duration::ZERO,
+        };
+        if let Err(mut unsent) = tx.send(permit) {
+            // Waiter gave up (client disconnected) — take the slot back
+            // without re-entering the lock through Drop.
+            unsent.armed = false;
+            st.running_total -= 1;
+            if let Some(n) = st.running.get_mut(&tenant) {
+                *n -= 1;
+            }
+        } else {
+            idle_passes = 0;
+        }
+
+        if st.queues.get(&tenant).is_some_and(|q| !q.is_empty()) {
+            st.rotation.push_back(tenant);
+        } else {
+            st.queues.remove(&tenant);
+        }
+    }
+}
+
+/// Wait for a generation slot for `tenant` under the given per-tenant cap.
+pub async fn acquire(tenant: &str, cap: usize) -> GenerationPermit {
+    let started = Instant::now();
+    let rx = {
+        let mut st = state();
+        st.caps.insert(tenant.to_string(), cap.max(1));
+        let running = st.running.get(tenant).copied().unwrap_or(0);
+        let has_waiters = st.queues.get(tenant).is_some_and(|q| !q.is_empty());
+
+        if st.running_total < st.max_concurrency && running < cap.max(1) && !has_waiters {
+            st.running_total += 1;
+            *st.running.entry(tenant.to_string()).or_insert(0) += 1;
+            return GenerationPermit {
+                tenant: tenant.to_string(),
+                armed: true,
+                waited: Duration::ZERO,
+            };
+        }
+
+        let (tx, rx) = oneshot::channel();
+        st.queues
+            .entry(tenant.to_string())
+            .or_default()
+            .push_back(tx);
+        if !st.rotation.iter().any(|t| t == tenant) {
+            st.rotation.push_back(tenant.to_string());
+        }
+        rx
+    };
+
+    match rx.await {
+        Ok(mut permit) => {
+            permit.waited = started.elapsed();
+            permit
+        }
+        // Sender only disappears if the scheduler state was torn down;
+        // fall back to an unaccounted permit rather than blocking forever.
+        Err(_) => GenerationPermit {
+            tenant: tenant.to_string(),
+            armed: false,
+            waited: started.elapsed(),
+        },
+    }
+}
+
+/// Wait for a slot using the tenant's plan cap. Keyed by tenant id — email
+/// tenant names are derived from the local part and can collide.
+pub async fn acquire_for(tenant: &Tenant) -> GenerationPermit {
+    let plan = plan_for_tenant(tenant);
+    acquire(&format!("tenant-{}", tenant.id), cap_for_plan(&plan)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_plan_caps() {
+        assert_eq!(cap_for_plan("individual"), 1);
+        assert_eq!(cap_for_plan("Enterprise"), 6);
+        assert_eq!(cap_for_plan("unknown"), 1);
+    }
+
+    #[test]
+    fn parse_pairs_ignores_garbage() {
+        let pairs = parse_pairs("Acme=Enterprise, bad, =x, cgi = team");
+        assert_eq!(
+            pairs,
+            vec![
+                ("acme".to_string(), "enterprise".to_string()),
+                ("cgi".to_string(), "team".to_string()),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn tenant_at_cap_does_not_block_others() {
+        let first = acquire("sched-test-a", 1).await;
+        // Tenant A is at its cap; B still gets a slot immediately.
+        let b = tokio::time::timeout(Duration::from_millis(200), acquire("sched-test-b", 1)).await;
+        assert!(b.is_ok());
+
+        // A's second request waits until the first finishes.
+        let waiting = tokio::spawn(acquire("sched-test-a", 1));
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!waiting.is_finished());
+        drop(first);
+        let second = tokio::time::timeout(Duration::from_secs(1), waiting).await;
+        assert!(second.is_ok());
+    }
+}
@@ -0,0 +1,86 @@
+// src/core/html_export.rs
+//! Self-contained HTML from Typst's HTML export.
+//!
+//! `format: "html"` compiles the template with `--features html --format
+//! html`. The result links its pictures by workspace path and has no styling,
+//! so before it is delivered [`make_self_contained`] embeds every local
+//! `src="…"` as a data URL and inlines the template's `html.css`, if it ships
+//! one, in a `<style>` element. Only templates listing `"html"` in their
+//! manifest `formats` are offered it.
+
+use base64::Engine;
+
+/// Stylesheet a template ships for its HTML output.
+pub const STYLESHEET: &str = "html.css";
+
+fn mime_type(path: &str) -> Option<&'static str> {
+    let ext = path.rsplit('.').next()?.to_lowercase();
+    Some(match ext.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "svg" => "image/svg+xml",
+        "webp" => "image/webp",
+        _ => return None,
+    })
+}
+
+/// `html` with `css` inlined and each local image read by `read` embedded.
+/// Sources that are already URLs, or that `read` can't find, are kept.
+pub fn make_self_contained(
+    html: &str,
+    css: Option<&str>,
+    read: impl Fn(&str) -> Option<Vec<u8>>,
+) -> String {
+    let mut out = String::with_capacity(html.len());
+    let mut rest = html;
+    while let Some(start) = rest.find("src=\"") {
+        let value_start = start + "src=\"".len();
+        let Some(len) = rest[value_start..].find('"') else {
+            break;
+        };
+        let source = &rest[value_start..value_start + len];
+        out.push_str(&rest[..value_start]);
+        let embedded = (!source.contains(':'))
+            .then(|| Some((mime_type(source)?, read(source)?)))
+            .flatten();
+        match embedded {
+            Some((mime, bytes)) => {
+                out.push_str("data:");
+                out.push_str(mime);
+                out.push_str(";base64,");
+                out.push_str(&base64::engine::general_purpose::STANDARD.encode(bytes));
+            }
+            None => out.push_str(source),
+        }
+        rest = &rest[value_start + len..];
+    }
+    out.push_str(rest);
+
+    let Some(css) = css.filter(|css| !css.trim().is_empty()) else {
+        return out;
+    };
+    let style = format!("<style>\n{}\n</style>\n", css.replace("</style", "<\\/style"));
+    match out.find("</head>") {
+        Some(at) => out.insert_str(at, &style),
+        None => out.insert_str(0, &style),
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn local_images_and_stylesheet_are_inlined() {
+        let html = r#"<html><head><title>CV</title></head><body><img src="profile.png"><img src="https://x.io/a.png"><img src="missing.jpg"></body></html>"#;
+        let out = make_self_contained(html, Some("h1 { color: red; }"), |path| {
+            (path == "profile.png").then(|| vec![1, 2, 3])
+        });
+        assert!(out.contains(r#"src="data:image/png;base64,AQID""#));
+        assert!(out.contains(r#"src="https://x.io/a.png""#));
+        assert!(out.contains(r#"src="missing.jpg""#));
+        assert!(out.contains("<style>\nh1 { color: red; }\n</style>\n</head>"));
+    }
+}
@@ -0,0 +1,131 @@
+// src/core/image_slots.rs
+//! Named image slots declared by templates.
+//!
+//! Besides `profile.png` and `company_logo.png`, a template can ask for more
+//! pictures — a header logo, a signature — in its manifest:
+//!
+//! ```toml
+//! [[image_slots]]
+//! name = "signature"
+//! file = "signature.png"
+//! description = "Handwritten signature under the closing line"
+//! ```
+//!
+//! Uploads are stored as PNG in `<person>/assets/<slot>.png`. When a CV is
+//! compiled, each slot of the chosen template that the person filled is copied
+//! to the slot's `file` in the workspace and announced to Typst as
+//! `--input image.<slot>=<file>`.
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::core::template_engine::TemplateManifest;
+use crate::core::TemplateEngine;
+
+pub const ASSETS_DIR: &str = "assets";
+const PNG_SIGNATURE: &[u8] = &[0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A];
+const JPEG_SIGNATURE: &[u8] = &[0xFF, 0xD8, 0xFF];
+/// Files the workspace already uses for other purposes.
+const RESERVED_FILES: &[&str] = &[
+    "main.typ",
+    "template.typ",
+    "experiences.typ",
+    "cv_params.toml",
+    "profile.png",
+    "profile.jpg",
+    "company_logo.png",
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImageSlot {
+    pub name: String,
+    /// File name the template loads, e.g. `signature.png`.
+    pub file: String,
+    pub description: Option<String>,
+}
+
+impl ImageSlot {
+    /// Whether the slot can be used: a plain name and a plain PNG file name
+    /// that doesn't shadow a workspace file.
+    pub fn is_valid(&self) -> bool {
+        valid_name(&self.name)
+            && self.file.ends_with(".png")
+            && !self.file.contains(['/', '\\'])
+            && !self.file.starts_with('.')
+            && !RESERVED_FILES.contains(&self.file.as_str())
+    }
+}
+
+/// Usable slots declared by `manifest`; invalid entries are skipped.
+pub fn declared(manifest: &TemplateManifest) -> Vec<&ImageSlot> {
+    manifest
+        .image_slots
+        .iter()
+        .flatten()
+        .filter(|slot| slot.is_valid())
+        .collect()
+}
+
+/// Whether any installed template declares `slot`.
+pub fn is_known(engine: &TemplateEngine, slot: &str) -> bool {
+    engine.list_templates().iter().any(|id| {
+        engine
+            .get_template(id)
+            .is_some_and(|t| declared(&t.manifest).iter().any(|s| s.name == slot))
+    })
+}
+
+/// Slot names: lowercase letters, digits and `_`.
+pub fn valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= 32
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+}
+
+/// Where the person's image for `slot` is stored.
+pub fn asset_path(person_dir: &Path, slot: &str) -> PathBuf {
+    person_dir.join(ASSETS_DIR).join(format!("{}.png", slot))
+}
+
+pub fn is_png(bytes: &[u8]) -> bool {
+    bytes.starts_with(PNG_SIGNATURE)
+}
+
+/// `bytes` as PNG: PNG is kept, JPEG is transcoded, anything else rejected.
+pub async fn to_png(bytes: Vec<u8>) -> Result<Vec<u8>> {
+    if is_png(&bytes) {
+        return Ok(bytes);
+    }
+    if !bytes.starts_with(JPEG_SIGNATURE) {
+        bail!("Images must be PNG or JPEG");
+    }
+    tokio::task::spawn_blocking(move || -> Result<Vec<u8>> {
+        let img = image::load_from_memory_with_format(&bytes, image::ImageFormat::Jpeg)?;
+        let mut out = Vec::new();
+        img.write_to(&mut std::io::Cursor::new(&mut out), image::ImageFormat::Png)?;
+        Ok(out)
+    })
+    .await?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_slots_that_escape_or_shadow_workspace_files() {
+        let slot = |name: &str, file: &str| ImageSlot {
+            name: name.to_string(),
+            file: file.to_string(),
+            description: None,
+        };
+        assert!(slot("signature", "signature.png").is_valid());
+        assert!(!slot("signature", "../signature.png").is_valid());
+        assert!(!slot("photo", "profile.png").is_valid());
+        assert!(!slot("Header Logo", "header.png").is_valid());
+        assert!(!slot("header", "header.svg").is_valid());
+    }
+}
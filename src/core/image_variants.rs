@@ -0,0 +1,252 @@
+//! Resized / re-encoded picture variants with an on-disk cache.
+//!
+//! Profile pictures are stored as uploaded (often multi-megabyte PNGs). The
+//! picture endpoint asks for a variant — target width plus output format — and
+//! gets back the bytes of a cached file under
+//! `<data_dir>/.picture_cache/<key>.<ext>`. The cache key covers the source path,
+//! its size and mtime, so re-uploading a picture naturally misses the cache.
+//! The cache lives outside tenant folders so it never shows up in file trees.
+//! Replaced pictures leave their variants behind; [`prune`] (run by
+//! `cvenom admin prune-caches`) deletes the old ones and caps the size.
+
+use anyhow::{Context, Result};
+use image::imageops::FilterType;
+use std::collections::hash_map::DefaultHasher;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+pub const CACHE_DIR: &str = ".picture_cache";
+pub const MIN_WIDTH: u32 = 16;
+pub const MAX_WIDTH: u32 = 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum VariantFormat {
+    Png,
+    Jpeg,
+    Webp,
+}
+
+impl VariantFormat {
+    /// Parse the `format` query param. Unknown values yield `None`.
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "png" => Some(Self::Png),
+            "jpg" | "jpeg" => Some(Self::Jpeg),
+            "webp" => Some(Self::Webp),
+            _ => None,
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Png => "png",
+            Self::Jpeg => "jpg",
+            Self::Webp => "webp",
+        }
+    }
+
+    pub fn mime(&self) -> (&'static str, &'static str) {
+        match self {
+            Self::Png => ("image", "png"),
+            Self::Jpeg => ("image", "jpeg"),
+            Self::Webp => ("image", "webp"),
+        }
+    }
+
+    fn image_format(&self) -> image::ImageFormat {
+        match self {
+            Self::Png => image::ImageFormat::Png,
+            Self::Jpeg => image::ImageFormat::Jpeg,
+            Self::Webp => image::ImageFormat::WebP,
+        }
+    }
+}
+
+/// A variant ready to serve: encoded bytes plus a strong ETag (the cache key).
+pub struct PictureVariant {
+    pub bytes: Vec<u8>,
+    pub format: VariantFormat,
+    pub etag: String,
+}
+
+/// Clamp a requested width into `[MIN_WIDTH, MAX_WIDTH]`.
+pub fn clamp_width(width: u32) -> u32 {
+    width.clamp(MIN_WIDTH, MAX_WIDTH)
+}
+
+fn cache_key(source: &Path, width: Option<u32>, format: VariantFormat) -> Result<String> {
+    let meta =
+        fs::metadata(source).with_context(|| format!("Failed to stat {}", source.display()))?;
+    let mtime = meta
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_nanos())
+        .unwrap_or(0);
+
+    let mut hasher = DefaultHasher::new();
+    source.hash(&mut hasher);
+    meta.len().hash(&mut hasher);
+    mtime.hash(&mut hasher);
+    width.hash(&mut hasher);
+    format.hash(&mut hasher);
+    Ok(format!("{:016x}", hasher.finish()))
+}
+
+/// Return the requested variant of `source`, generating and caching it on a
+/// miss. Never upscales: a width larger than the original keeps the original
+/// dimensions. CPU-bound — call from `spawn_blocking`.
+pub fn get_or_create(
+    data_dir: &Path,
+    source: &Path,
+    width: Option<u32>,
+    format: VariantFormat,
+) -> Result<PictureVariant> {
+    let width = width.map(clamp_width);
+    let key = cache_key(source, width, format)?;
+    let cache_dir = data_dir.join(CACHE_DIR);
+    let cached: PathBuf = cache_dir.join(format!("{}.{}", key, format.extension()));
+
+    if let Ok(bytes) = fs::read(&cached) {
+        return Ok(PictureVariant {
+            bytes,
+            format,
+            etag: key,
+        });
+    }
+
+    let raw = fs::read(source).with_context(|| format!("Failed to read {}", source.display()))?;
+    // Guess from content, not extension — uploads are not always what they claim.
+    let mut img = image::load_from_memory(&raw).context("Failed to decode picture")?;
+    if let Some(w) = width {
+        if w < img.width() {
+            let h = ((img.height() as u64 * w as u64) / img.width() as u64).max(1) as u32;
+            img = img.resize_exact(w, h, FilterType::Lanczos3);
+        }
+    }
+    // JPEG has no alpha channel.
+    if format == VariantFormat::Jpeg {
+        img = image::DynamicImage::ImageRgb8(img.to_rgb8());
+    }
+
+    let mut bytes = Vec::new();
+    img.write_to(&mut std::io::Cursor::new(&mut bytes), format.image_format())
+        .context("Failed to encode picture variant")?;
+
+    fs::create_dir_all(&cache_dir)
+        .with_context(|| format!("Failed to create {}", cache_dir.display()))?;
+    // Write-then-rename so a concurrent reader never sees a partial file.
+    let tmp = cache_dir.join(format!("{}.{}.tmp", key, uuid::Uuid::new_v4()));
+    fs::write(&tmp, &bytes).context("Failed to write picture variant")?;
+    fs::rename(&tmp, &cached).context("Failed to store picture variant")?;
+
+    Ok(PictureVariant {
+        bytes,
+        format,
+        etag: key,
+    })
+}
+
+/// Delete the variants cached under `data_dir` that were written more than
+/// `max_age` ago, then the oldest ones until the rest fit in `max_bytes`.
+/// A pruned variant is rebuilt on its next request. Returns how many files
+/// and bytes went; without `execute`, only counts them.
+pub fn prune(data_dir: &Path, max_age: Duration, max_bytes: u64, execute: bool) -> Result<(usize, u64)> {
+    let Ok(entries) = fs::read_dir(data_dir.join(CACHE_DIR)) else {
+        return Ok((0, 0));
+    };
+    let mut variants = Vec::new();
+    for entry in entries {
+        let entry = entry?;
+        let meta = entry.metadata()?;
+        if meta.is_file() {
+            let modified = meta.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+            variants.push((modified, meta.len(), entry.path()));
+        }
+    }
+    // Newest first: what is past the age or the size budget goes.
+    variants.sort_by(|a, b| b.0.cmp(&a.0));
+    let cutoff = SystemTime::now().checked_sub(max_age).unwrap_or(SystemTime::UNIX_EPOCH);
+    let (mut kept, mut deleted, mut freed) = (0u64, 0usize, 0u64);
+    for (modified, len, path) in variants {
+        if modified >= cutoff && kept + len <= max_bytes {
+            kept += len;
+            continue;
+        }
+        if execute {
+            fs::remove_file(&path).with_context(|| format!("Failed to delete {}", path.display()))?;
+        }
+        deleted += 1;
+        freed += len;
+    }
+    Ok((deleted, freed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write_png(path: &Path, w: u32, h: u32) {
+        image::RgbaImage::from_pixel(w, h, image::Rgba([200, 10, 10, 255]))
+            .save_with_format(path, image::ImageFormat::Png)
+            .unwrap();
+    }
+
+    #[test]
+    fn parse_formats() {
+        assert_eq!(VariantFormat::parse("WEBP"), Some(VariantFormat::Webp));
+        assert_eq!(VariantFormat::parse("jpg"), Some(VariantFormat::Jpeg));
+        assert_eq!(VariantFormat::parse("gif"), None);
+    }
+
+    #[test]
+    fn resizes_and_caches() {
+        let tmp = TempDir::new().unwrap();
+        let src = tmp.path().join("profile.png");
+        write_png(&src, 400, 200);
+
+        let v = get_or_create(tmp.path(), &src, Some(100), VariantFormat::Png).unwrap();
+        let img = image::load_from_memory(&v.bytes).unwrap();
+        assert_eq!((img.width(), img.height()), (100, 50));
+
+        let cached = tmp.path().join(CACHE_DIR).join(format!("{}.png", v.etag));
+        assert!(cached.exists());
+
+        let again = get_or_create(tmp.path(), &src, Some(100), VariantFormat::Png).unwrap();
+        assert_eq!(again.etag, v.etag);
+    }
+
+    #[test]
+    fn never_upscales() {
+        let tmp = TempDir::new().unwrap();
+        let src = tmp.path().join("profile.png");
+        write_png(&src, 40, 40);
+
+        let v = get_or_create(tmp.path(), &src, Some(800), VariantFormat::Jpeg).unwrap();
+        let img = image::load_from_memory(&v.bytes).unwrap();
+        assert_eq!(img.width(), 40);
+    }
+
+    #[test]
+    fn prune_keeps_the_newest_variants_within_budget() {
+        let tmp = TempDir::new().unwrap();
+        let src = tmp.path().join("profile.png");
+        write_png(&src, 400, 200);
+        let small = get_or_create(tmp.path(), &src, Some(50), VariantFormat::Png).unwrap();
+        std::thread::sleep(Duration::from_millis(20));
+        let large = get_or_create(tmp.path(), &src, Some(300), VariantFormat::Png).unwrap();
+        let day = Duration::from_secs(24 * 60 * 60);
+        let cached = |v: &PictureVariant| tmp.path().join(CACHE_DIR).join(format!("{}.png", v.etag));
+
+        assert_eq!(prune(tmp.path(), day, u64::MAX, true).unwrap().0, 0);
+        let budget = large.bytes.len() as u64;
+        assert_eq!(prune(tmp.path(), day, budget, false).unwrap().0, 1);
+        assert!(cached(&small).exists());
+        assert_eq!(prune(tmp.path(), day, budget, true).unwrap().0, 1);
+        assert!(!cached(&small).exists());
+        assert!(cached(&large).exists());
+    }
+}
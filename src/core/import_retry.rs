@@ -0,0 +1,309 @@
+// src/core/import_retry.rs
+//! Failed CV conversions, kept for retrying.
+//!
+//! When cv-import fails to convert an upload, the file is kept in
+//! `failed_imports/` and a job is recorded here. Failures that look
+//! temporary (the service unreachable, overloaded or timing out, see
+//! [`is_transient`]) are retried in the background up to
+//! `IMPORT_RETRY_ATTEMPTS` times (default 3), waiting
+//! `IMPORT_RETRY_INTERVAL_SECS` (default 300) and twice as long after each
+//! further failure. Any job can also be retried by its owner. A job is
+//! `retrying` while an automatic retry is scheduled, `running` during an
+//! attempt, `failed` once it is left to the user and `imported` after it
+//! succeeded, when its file is removed. `failed` and `imported` jobs are
+//! deleted, with their file, `IMPORT_RETRY_KEEP_DAYS` (default 30) after
+//! their last attempt, and with the account that owns them.
+
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use graflog::app_log;
+use serde::Serialize;
+use sqlx::SqlitePool;
+use std::path::{Path, PathBuf};
+
+const DEFAULT_ATTEMPTS: i64 = 3;
+const DEFAULT_INTERVAL_SECS: i64 = 300;
+const DEFAULT_KEEP_DAYS: i64 = 30;
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct FailedImport {
+    pub id: String,
+    pub filename: String,
+    /// Why the last attempt failed, as shown to the user.
+    pub error: String,
+    /// Retries made so far.
+    pub attempts: i64,
+    pub status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_attempt_at: Option<DateTime<Utc>>,
+    /// Profile created by the successful retry.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A job claimed for an attempt.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct RetryJob {
+    pub id: String,
+    pub owner: String,
+    pub user_email: String,
+    pub filename: String,
+    pub stored_path: String,
+    pub attempts: i64,
+}
+
+impl RetryJob {
+    pub fn stored_path(&self) -> PathBuf {
+        PathBuf::from(&self.stored_path)
+    }
+}
+
+fn env_i64(name: &str, default: i64) -> i64 {
+    std::env::var(name)
+        .ok()
+        .and_then(|v| v.trim().parse().ok())
+        .unwrap_or(default)
+}
+
+/// Automatic retries per job; `0` turns them off.
+pub fn max_attempts() -> i64 {
+    env_i64("IMPORT_RETRY_ATTEMPTS", DEFAULT_ATTEMPTS).max(0)
+}
+
+/// How often the background task looks for due retries.
+pub fn poll_interval() -> std::time::Duration {
+    let interval = env_i64("IMPORT_RETRY_INTERVAL_SECS", DEFAULT_INTERVAL_SECS);
+    std::time::Duration::from_secs((interval / 5).clamp(10, 60) as u64)
+}
+
+/// Days a job left to the user, or imported, is kept.
+fn keep_days() -> i64 {
+    env_i64("IMPORT_RETRY_KEEP_DAYS", DEFAULT_KEEP_DAYS).max(1)
+}
+
+/// Wait before the next automatic retry, after `attempts` retries; `None`
+/// when the job is left to the user.
+fn retry_delay(attempts: i64, transient: bool) -> Option<Duration> {
+    if !transient || attempts >= max_attempts() {
+        return None;
+    }
+    let base = env_i64("IMPORT_RETRY_INTERVAL_SECS", DEFAULT_INTERVAL_SECS).max(1);
+    Some(Duration::seconds(base << attempts.min(10)))
+}
+
+/// Whether a conversion error is worth retrying unchanged: the service was
+/// unreachable, overloaded or too slow, rather than unable to read the file.
+pub fn is_transient(error: &str) -> bool {
+    let error = error.to_lowercase();
+    [
+        "connection refused",
+        "os error 111",
+        "http request failed",
+        "timed out",
+        "timeout",
+        "overloaded",
+        "rate limit",
+        "(http 429",
+        "(http 502",
+        "(http 503",
+        "(http 504",
+    ]
+    .iter()
+    .any(|marker| error.contains(marker))
+}
+
+const COLUMNS: &str =
+    "id, filename, error, attempts, status, next_attempt_at, profile, created_at, updated_at";
+
+/// Record a failed conversion of `filename`, kept at `stored_path`.
+pub async fn record(
+    pool: &SqlitePool,
+    owner: &str,
+    email: &str,
+    filename: &str,
+    stored_path: &Path,
+    error: &str,
+    transient: bool,
+) -> Result<FailedImport> {
+    let now = Utc::now();
+    let next = retry_delay(0, transient).map(|delay| now + delay);
+    let job = sqlx::query_as::<_, FailedImport>(&format!(
+        "INSERT INTO failed_imports (id, owner, user_email, filename, stored_path, error, \
+         attempts, status, next_attempt_at, created_at, updated_at) \
+         VALUES (?, ?, ?, ?, ?, ?, 0, ?, ?, ?, ?) RETURNING {}",
+        COLUMNS
+    ))
+    .bind(uuid::Uuid::new_v4().to_string())
+    .bind(owner)
+    .bind(email.to_lowercase())
+    .bind(filename)
+    .bind(stored_path.to_string_lossy().to_string())
+    .bind(error)
+    .bind(if next.is_some() { "retrying" } else { "failed" })
+    .bind(next)
+    .bind(now)
+    .bind(now)
+    .fetch_one(pool)
+    .await?;
+    Ok(job)
+}
+
+/// Jobs of `email` that have not been imported, newest first.
+pub async fn list_for(pool: &SqlitePool, email: &str) -> Result<Vec<FailedImport>> {
+    let jobs = sqlx::query_as::<_, FailedImport>(&format!(
+        "SELECT {} FROM failed_imports WHERE user_email = ? AND status != 'imported' \
+         ORDER BY created_at DESC",
+        COLUMNS
+    ))
+    .bind(email.to_lowercase())
+    .fetch_all(pool)
+    .await?;
+    Ok(jobs)
+}
+
+const CLAIMED: &str = "id, owner, user_email, filename, stored_path, attempts";
+
+/// Claim `email`'s job `id` for a retry; `None` when there is no such job
+/// or it is running or imported.
+pub async fn claim(pool: &SqlitePool, email: &str, id: &str) -> Result<Option<RetryJob>> {
+    let job = sqlx::query_as::<_, RetryJob>(&format!(
+        "UPDATE failed_imports SET status = 'running', updated_at = ? \
+         WHERE id = ? AND user_email = ? AND status IN ('retrying', 'failed') RETURNING {}",
+        CLAIMED
+    ))
+    .bind(Utc::now())
+    .bind(id)
+    .bind(email.to_lowercase())
+    .fetch_optional(pool)
+    .await?;
+    Ok(job)
+}
+
+/// Claim every job whose automatic retry is due.
+pub async fn claim_due(pool: &SqlitePool) -> Result<Vec<RetryJob>> {
+    let now = Utc::now();
+    let jobs = sqlx::query_as::<_, RetryJob>(&format!(
+        "UPDATE failed_imports SET status = 'running', updated_at = ? \
+         WHERE status = 'retrying' AND next_attempt_at <= ? RETURNING {}",
+        CLAIMED
+    ))
+    .bind(now)
+    .bind(now)
+    .fetch_all(pool)
+    .await?;
+    Ok(jobs)
+}
+
+/// Jobs left `running` by a previous run are retried again.
+pub async fn release_running(pool: &SqlitePool) -> Result<u64> {
+    let released = sqlx::query(
+        "UPDATE failed_imports SET status = 'retrying', next_attempt_at = ? \
+         WHERE status = 'running'",
+    )
+    .bind(Utc::now())
+    .execute(pool)
+    .await?
+    .rows_affected();
+    Ok(released)
+}
+
+/// The retry of `job` created `profile`.
+pub async fn succeeded(pool: &SqlitePool, job: &RetryJob, profile: &str) -> Result<()> {
+    sqlx::query(
+        "UPDATE failed_imports SET status = 'imported', profile = ?, attempts = attempts + 1, \
+         next_attempt_at = NULL, updated_at = ? WHERE id = ?",
+    )
+    .bind(profile)
+    .bind(Utc::now())
+    .bind(&job.id)
+    .execute(pool)
+    .await?;
+    if let Err(e) = tokio::fs::remove_file(job.stored_path()).await {
+        app_log!(warn, "Failed to remove retried upload {}: {}", job.stored_path, e);
+    }
+    Ok(())
+}
+
+/// The retry of `job` failed with `error`; schedules the next one if any.
+pub async fn failed(
+    pool: &SqlitePool,
+    job: &RetryJob,
+    error: &str,
+    transient: bool,
+) -> Result<FailedImport> {
+    let attempts = job.attempts + 1;
+    let now = Utc::now();
+    let next = retry_delay(attempts, transient).map(|delay| now + delay);
+    let updated = sqlx::query_as::<_, FailedImport>(&format!(
+        "UPDATE failed_imports SET status = ?, error = ?, attempts = ?, next_attempt_at = ?, \
+         updated_at = ? WHERE id = ? RETURNING {}",
+        COLUMNS
+    ))
+    .bind(if next.is_some() { "retrying" } else { "failed" })
+    .bind(error)
+    .bind(attempts)
+    .bind(next)
+    .bind(now)
+    .bind(&job.id)
+    .fetch_one(pool)
+    .await?;
+    Ok(updated)
+}
+
+/// Remove the kept uploads at `paths`; a file already gone is fine.
+async fn remove_files(paths: Vec<String>) {
+    for path in paths {
+        match tokio::fs::remove_file(&path).await {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => app_log!(warn, "Failed to remove kept upload {}: {}", path, e),
+        }
+    }
+}
+
+/// Delete the jobs of the account `owner`, and their files.
+pub async fn delete_for(pool: &SqlitePool, owner: &str) -> Result<u64> {
+    let paths: Vec<String> =
+        sqlx::query_scalar("DELETE FROM failed_imports WHERE owner = ? RETURNING stored_path")
+            .bind(owner)
+            .fetch_all(pool)
+            .await?;
+    let deleted = paths.len() as u64;
+    remove_files(paths).await;
+    Ok(deleted)
+}
+
+/// Delete the `failed` and `imported` jobs untouched for
+/// `IMPORT_RETRY_KEEP_DAYS`, and their files.
+pub async fn prune_expired(pool: &SqlitePool) -> Result<u64> {
+    let paths: Vec<String> = sqlx::query_scalar(
+        "DELETE FROM failed_imports WHERE status IN ('failed', 'imported') AND updated_at < ? \
+         RETURNING stored_path",
+    )
+    .bind(Utc::now() - Duration::days(keep_days()))
+    .fetch_all(pool)
+    .await?;
+    let deleted = paths.len() as u64;
+    remove_files(paths).await;
+    Ok(deleted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_temporary_failures_are_retried_with_backoff() {
+        assert!(is_transient("HTTP request failed: error sending request (os error 111)"));
+        assert!(is_transient("CV service error (HTTP 503 Service Unavailable): busy"));
+        assert!(!is_transient("CV service error (HTTP 422 Unprocessable Entity): bad file"));
+        assert!(!is_transient("No readable text in the uploaded PDF"));
+
+        assert_eq!(retry_delay(0, true), Some(Duration::seconds(300)));
+        assert_eq!(retry_delay(2, true), Some(Duration::seconds(1200)));
+        assert_eq!(retry_delay(3, true), None);
+        assert_eq!(retry_delay(0, false), None);
+    }
+}
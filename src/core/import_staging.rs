@@ -0,0 +1,250 @@
+// src/core/import_staging.rs
+//! Staged CV imports.
+//!
+//! An import used to write straight into the person's directory, so a crash
+//! mid-conversion left the upload in the temp dir and a half-written person
+//! behind. Imports now work in `<data_dir>/.import_staging/<id>/`: the upload
+//! lands there, the person's files are written to `profile/` (seeded with a
+//! copy of the existing person on re-import), and [`StagedImport::commit`]
+//! swaps the result into place with renames. A staged import that is dropped
+//! without committing is removed.
+//!
+//! The swap moves an existing person to `previous/` before renaming `profile/`
+//! into place. [`recover`] runs at startup: it restores a `previous/` whose
+//! person is missing (a crash between the two renames) and deletes every
+//! other leftover, so interrupted imports never show up as persons.
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use graflog::app_log;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::core::{data_encryption, data_residency, profile_lock};
+
+pub const STAGING_DIR: &str = ".import_staging";
+const JOURNAL: &str = "import.json";
+const PROFILE: &str = "profile";
+const PREVIOUS: &str = "previous";
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Journal {
+    target: PathBuf,
+    /// Whether the person existed when the import started.
+    existed: bool,
+    started_at: DateTime<Utc>,
+}
+
+pub struct StagedImport {
+    dir: PathBuf,
+    target: PathBuf,
+    existed: bool,
+    done: bool,
+}
+
+impl StagedImport {
+    /// Start an import that will end up in `target` (a person directory),
+    /// staged in the data root of `target`'s region.
+    pub async fn begin(data_dir: &Path, target: &Path) -> Result<Self> {
+        let dir = data_residency::data_root_of(data_dir, target)
+            .join(STAGING_DIR)
+            .join(uuid::Uuid::new_v4().to_string());
+        tokio::fs::create_dir_all(&dir)
+            .await
+            .context("Failed to create import staging directory")?;
+        let journal = Journal {
+            target: target.to_path_buf(),
+            existed: target.is_dir(),
+            started_at: Utc::now(),
+        };
+        tokio::fs::write(dir.join(JOURNAL), serde_json::to_vec(&journal)?).await?;
+        Ok(Self {
+            dir,
+            target: journal.target,
+            existed: journal.existed,
+            done: false,
+        })
+    }
+
+    /// Scratch path for the uploaded document.
+    pub fn upload_path(&self, name: &str) -> PathBuf {
+        self.dir
+            .join(format!("upload-{}", name.replace(['/', '\\'], "_")))
+    }
+
+    /// Directory to write the person's files to, seeded with the current
+    /// files of an existing person so the import merges into them. Staged
+    /// files are plaintext: the staging dir belongs to no tenant.
+    pub async fn profile_dir(&self) -> Result<PathBuf> {
+        let staged = self.dir.join(PROFILE);
+        if self.target.is_dir() && !staged.exists() {
+            data_residency::ensure_same_region(&self.target, &staged)?;
+            let _snapshot = profile_lock::read(&self.target).await;
+            let (source, dest) = (self.target.clone(), staged.clone());
+            tokio::task::spawn_blocking(move || copy_tree(&source, &dest))
+                .await?
+                .context("Failed to copy the existing profile")?;
+        }
+        Ok(staged)
+    }
+
+    /// Move the staged person into place.
+    pub async fn commit(mut self) -> Result<()> {
+        let staged = self.dir.join(PROFILE);
+        if !staged.is_dir() {
+            bail!("Nothing was staged");
+        }
+        data_residency::ensure_same_region(&staged, &self.target)?;
+        let _lock = profile_lock::write(&self.target).await;
+        let exists = self.target.exists();
+        if exists && !self.existed {
+            bail!("'{}' was created during the import", self.target.display());
+        }
+
+        let previous = self.dir.join(PREVIOUS);
+        if exists {
+            tokio::fs::rename(&self.target, &previous).await?;
+        }
+        if let Err(e) = tokio::fs::rename(&staged, &self.target).await {
+            if exists {
+                if let Err(restore) = tokio::fs::rename(&previous, &self.target).await {
+                    // Keep the staging dir: `recover` restores it at startup.
+                    self.done = true;
+                    return Err(restore)
+                        .context("Failed to restore the profile after a failed import");
+                }
+            }
+            return Err(e).context("Failed to move the imported profile into place");
+        }
+
+        self.done = true;
+        // Staged files were written outside the tenant, so in plaintext.
+        if let Err(e) = data_encryption::protect_dir(&self.target).await {
+            app_log!(
+                warn,
+                "Failed to encrypt imported {}: {:#}",
+                self.target.display(),
+                e
+            );
+        }
+        if let Err(e) = tokio::fs::remove_dir_all(&self.dir).await {
+            app_log!(warn, "Failed to clean up {}: {}", self.dir.display(), e);
+        }
+        Ok(())
+    }
+}
+
+impl Drop for StagedImport {
+    fn drop(&mut self) {
+        if !self.done {
+            let _ = std::fs::remove_dir_all(&self.dir);
+        }
+    }
+}
+
+fn copy_tree(source: &Path, dest: &Path) -> Result<()> {
+    std::fs::create_dir_all(dest)?;
+    for entry in std::fs::read_dir(source)?.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            copy_tree(&path, &dest.join(entry.file_name()))?;
+        } else if data_encryption::is_protected(&path) {
+            data_encryption::copy_plain(&path, &dest.join(entry.file_name()))?;
+        } else {
+            std::fs::copy(&path, dest.join(entry.file_name()))?;
+        }
+    }
+    Ok(())
+}
+
+/// Roll back imports interrupted by a crash; returns how many were found.
+pub async fn recover(data_dir: &Path) -> usize {
+    let Ok(mut entries) = tokio::fs::read_dir(data_dir.join(STAGING_DIR)).await else {
+        return 0;
+    };
+    let mut recovered = 0;
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let dir = entry.path();
+        let journal = tokio::fs::read(dir.join(JOURNAL))
+            .await
+            .ok()
+            .and_then(|content| serde_json::from_slice::<Journal>(&content).ok());
+        let previous = dir.join(PREVIOUS);
+        if let Some(journal) = journal {
+            if previous.is_dir() && !journal.target.exists() {
+                match tokio::fs::rename(&previous, &journal.target).await {
+                    Ok(()) => app_log!(
+                        warn,
+                        "[imports] Restored {} after an interrupted import",
+                        journal.target.display()
+                    ),
+                    Err(e) => {
+                        app_log!(
+                            error,
+                            "[imports] Could not restore {} from {}: {}",
+                            journal.target.display(),
+                            previous.display(),
+                            e
+                        );
+                        continue;
+                    }
+                }
+            }
+        }
+        if let Err(e) = tokio::fs::remove_dir_all(&dir).await {
+            app_log!(warn, "[imports] Failed to remove {}: {}", dir.display(), e);
+            continue;
+        }
+        recovered += 1;
+    }
+    if recovered > 0 {
+        app_log!(
+            info,
+            "[imports] Rolled back {} interrupted imports",
+            recovered
+        );
+    }
+    recovered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn commit_replaces_and_recover_restores() {
+        let data = tempfile::tempdir().unwrap();
+        let target = data.path().join("acme/jane/john");
+        std::fs::create_dir_all(&target).unwrap();
+        std::fs::write(target.join("cv_params.toml"), "old").unwrap();
+
+        let staged = StagedImport::begin(data.path(), &target).await.unwrap();
+        let profile = staged.profile_dir().await.unwrap();
+        assert_eq!(
+            std::fs::read_to_string(profile.join("cv_params.toml")).unwrap(),
+            "old"
+        );
+        std::fs::write(profile.join("cv_params.toml"), "new").unwrap();
+        staged.commit().await.unwrap();
+        assert_eq!(
+            std::fs::read_to_string(target.join("cv_params.toml")).unwrap(),
+            "new"
+        );
+
+        // Crash between the two renames: the person sits in `previous/`.
+        let staged = StagedImport::begin(data.path(), &target).await.unwrap();
+        std::fs::rename(&target, staged.dir.join(PREVIOUS)).unwrap();
+        std::mem::forget(staged);
+        assert_eq!(recover(data.path()).await, 1);
+        assert_eq!(
+            std::fs::read_to_string(target.join("cv_params.toml")).unwrap(),
+            "new"
+        );
+        assert!(!data
+            .path()
+            .join(STAGING_DIR)
+            .read_dir()
+            .unwrap()
+            .any(|_| true));
+    }
+}
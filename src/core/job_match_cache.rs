@@ -0,0 +1,69 @@
+//! On-disk cache of job-match results.
+//!
+//! Ranking a whole bench against one job means one LLM call per person, so
+//! results are stored under `<data_dir>/.job_match_cache/<key>.json`. The key
+//! covers the job (URL and pasted text) and the person's full CV data, so
+//! editing a profile naturally misses the cache. Entries older than
+//! `JOB_MATCH_CACHE_DAYS` (default 7) are ignored — postings get edited too.
+
+use anyhow::{Context, Result};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+use crate::types::cv_data::CvJson;
+use crate::types::response::JobMatchResponse;
+
+pub const CACHE_DIR: &str = ".job_match_cache";
+const DEFAULT_TTL_DAYS: u64 = 7;
+
+fn ttl() -> Duration {
+    let days = std::env::var("JOB_MATCH_CACHE_DAYS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_TTL_DAYS);
+    Duration::from_secs(days * 24 * 60 * 60)
+}
+
+/// Stable identity of a job: trimmed URL plus trimmed pasted text.
+pub fn job_key(job_url: &str, job_description: Option<&str>) -> String {
+    let mut hasher = DefaultHasher::new();
+    job_url.trim().hash(&mut hasher);
+    job_description.map(str::trim).hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+pub fn cache_key(job_key: &str, cv_data: &CvJson) -> Result<String> {
+    let cv_json = serde_json::to_string(cv_data).context("Failed to serialize CV data")?;
+    let mut hasher = DefaultHasher::new();
+    job_key.hash(&mut hasher);
+    cv_json.hash(&mut hasher);
+    Ok(format!("{}-{:016x}", job_key, hasher.finish()))
+}
+
+pub async fn get(data_dir: &Path, key: &str) -> Option<JobMatchResponse> {
+    let path = data_dir.join(CACHE_DIR).join(format!("{}.json", key));
+    let modified = tokio::fs::metadata(&path).await.ok()?.modified().ok()?;
+    let age = SystemTime::now()
+        .duration_since(modified)
+        .unwrap_or_default();
+    if age > ttl() {
+        return None;
+    }
+    let content = tokio::fs::read(&path).await.ok()?;
+    serde_json::from_slice(&content).ok()
+}
+
+pub async fn put(data_dir: &Path, key: &str, response: &JobMatchResponse) -> Result<()> {
+    let dir = data_dir.join(CACHE_DIR);
+    tokio::fs::create_dir_all(&dir)
+        .await
+        .with_context(|| format!("Failed to create {}", dir.display()))?;
+    let content = serde_json::to_vec(response).context("Failed to serialize match result")?;
+    // Write-then-rename so a concurrent reader never sees a partial file.
+    let tmp = dir.join(format!("{}.{}.tmp", key, uuid::Uuid::new_v4()));
+    tokio::fs::write(&tmp, content).await?;
+    tokio::fs::rename(&tmp, dir.join(format!("{}.json", key))).await?;
+    Ok(())
+}
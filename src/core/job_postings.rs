@@ -0,0 +1,212 @@
+// src/core/job_postings.rs
+//! Cache of scraped job postings.
+//!
+//! Many users analyze the same posting, and every analysis used to make
+//! cv-import scrape it again (slow, and LinkedIn rate-limits us). Postings
+//! are now fetched once by this server, politely (see
+//! [`crate::linkedin_analysis::scraper`]), stored in `job_postings` under
+//! their [`normalize_url`] key, and sent to cv-import as the job description.
+//! Entries older than `JOB_POSTING_CACHE_HOURS` (default 24) are scraped
+//! again, as is any posting requested with `force_refresh`. When scraping
+//! fails, the analysis falls back to sending the bare URL, which cv-import
+//! scrapes itself.
+
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use graflog::app_log;
+use sqlx::SqlitePool;
+
+use crate::linkedin_analysis::{scraper, JobContent};
+
+const DEFAULT_TTL_HOURS: i64 = 24;
+
+/// Query parameters that only track where a click came from.
+const TRACKING_PARAMS: [&str; 8] = [
+    "trk",
+    "trackingid",
+    "refid",
+    "ref",
+    "source",
+    "src",
+    "lipi",
+    "originalsubdomain",
+];
+
+fn ttl() -> Duration {
+    let hours = std::env::var("JOB_POSTING_CACHE_HOURS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_TTL_HOURS);
+    Duration::hours(hours)
+}
+
+/// Cache key of a posting URL: scheme, `www.`, fragment, trailing slash and
+/// tracking parameters are dropped, and LinkedIn links (`/jobs/view/<slug>-<id>`
+/// or `?currentJobId=<id>`) reduce to the job id. `None` for non-HTTP URLs.
+pub fn normalize_url(job_url: &str) -> Option<String> {
+    let url = reqwest::Url::parse(job_url.trim()).ok()?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return None;
+    }
+    let host = url.host_str()?.trim_start_matches("www.").to_string();
+
+    if host == "linkedin.com" || host.ends_with(".linkedin.com") {
+        let from_path = url
+            .path()
+            .strip_prefix("/jobs/view/")
+            .map(|rest| rest.trim_end_matches('/'))
+            .and_then(|slug| slug.rsplit('-').next())
+            .filter(|id| !id.is_empty() && id.chars().all(|c| c.is_ascii_digit()))
+            .map(str::to_string);
+        let from_query = url
+            .query_pairs()
+            .find(|(k, _)| k == "currentJobId")
+            .map(|(_, v)| v.into_owned())
+            .filter(|id| !id.is_empty() && id.chars().all(|c| c.is_ascii_digit()));
+        if let Some(id) = from_path.or(from_query) {
+            return Some(format!("linkedin.com/jobs/view/{}", id));
+        }
+    }
+
+    let mut params: Vec<(String, String)> = url
+        .query_pairs()
+        .filter(|(k, _)| {
+            let k = k.to_lowercase();
+            !k.starts_with("utm_") && !TRACKING_PARAMS.contains(&k.as_str())
+        })
+        .map(|(k, v)| (k.into_owned(), v.into_owned()))
+        .collect();
+    params.sort();
+    let mut key = format!("{}{}", host, url.path().trim_end_matches('/'));
+    if !params.is_empty() {
+        let query: Vec<String> = params.iter().map(|(k, v)| format!("{}={}", k, v)).collect();
+        key = format!("{}?{}", key, query.join("&"));
+    }
+    Some(key)
+}
+
+/// Cached posting for `job_url`, if fresh.
+pub async fn get(pool: &SqlitePool, job_url: &str) -> Result<Option<JobContent>> {
+    let Some(key) = normalize_url(job_url) else {
+        return Ok(None);
+    };
+    let row: Option<(String, String, String, String, DateTime<Utc>)> = sqlx::query_as(
+        "SELECT title, company, location, description, fetched_at \
+         FROM job_postings WHERE url_key = ?",
+    )
+    .bind(&key)
+    .fetch_optional(pool)
+    .await?;
+    Ok(row
+        .filter(|(.., fetched_at)| Utc::now() - *fetched_at < ttl())
+        .map(|(title, company, location, description, _)| JobContent {
+            title,
+            company,
+            location,
+            description,
+        }))
+}
+
+pub async fn put(pool: &SqlitePool, job_url: &str, content: &JobContent) -> Result<()> {
+    let Some(key) = normalize_url(job_url) else {
+        return Ok(());
+    };
+    sqlx::query(
+        "INSERT INTO job_postings \
+         (url_key, job_url, title, company, location, description, fetched_at) \
+         VALUES (?, ?, ?, ?, ?, ?, ?) \
+         ON CONFLICT(url_key) DO UPDATE SET job_url = excluded.job_url, \
+         title = excluded.title, company = excluded.company, \
+         location = excluded.location, description = excluded.description, \
+         fetched_at = excluded.fetched_at",
+    )
+    .bind(&key)
+    .bind(job_url.trim())
+    .bind(&content.title)
+    .bind(&content.company)
+    .bind(&content.location)
+    .bind(&content.description)
+    .bind(Utc::now())
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// The posting at `job_url`, from the cache or freshly scraped; `None` when
+/// it can't be scraped, in which case cv-import gets the bare URL.
+pub async fn resolve(pool: &SqlitePool, job_url: &str, force_refresh: bool) -> Option<JobContent> {
+    if !force_refresh {
+        match get(pool, job_url).await {
+            Ok(Some(content)) => {
+                app_log!(info, "[job-postings] Cache hit for {}", job_url);
+                return Some(content);
+            }
+            Ok(None) => {}
+            Err(e) => app_log!(warn, "[job-postings] Cache lookup failed: {}", e),
+        }
+    }
+    normalize_url(job_url)?;
+
+    let content = match scraper::scraper().fetch_job(job_url).await {
+        Ok(content) if !content.description.trim().is_empty() => content,
+        Ok(_) => {
+            app_log!(warn, "[job-postings] Empty posting scraped from {}", job_url);
+            return None;
+        }
+        Err(e) => {
+            app_log!(warn, "[job-postings] Scraping {} failed: {}", job_url, e);
+            return None;
+        }
+    };
+    if let Err(e) = put(pool, job_url, &content).await {
+        app_log!(warn, "[job-postings] Failed to cache {}: {}", job_url, e);
+    }
+    Some(content)
+}
+
+/// Posting text sent to cv-import in place of the URL.
+pub fn describe(content: &JobContent) -> String {
+    let heading: Vec<&str> = [&content.title, &content.company, &content.location]
+        .into_iter()
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+        .collect();
+    if heading.is_empty() {
+        content.description.clone()
+    } else {
+        format!("{}\n\n{}", heading.join(" — "), content.description)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn urls_normalize_to_one_key_per_posting() {
+        let key = Some("linkedin.com/jobs/view/3912345678".to_string());
+        assert_eq!(
+            normalize_url("https://www.linkedin.com/jobs/view/3912345678/?trk=abc&refId=x"),
+            key
+        );
+        assert_eq!(
+            normalize_url("https://ch.linkedin.com/jobs/view/rust-engineer-at-acme-3912345678"),
+            key
+        );
+        assert_eq!(
+            normalize_url("https://www.linkedin.com/jobs/search/?currentJobId=3912345678&keywords=rust"),
+            key
+        );
+
+        assert_eq!(
+            normalize_url("https://jobs.example.com/offer/42/?utm_source=mail&b=2&a=1#apply"),
+            Some("jobs.example.com/offer/42?a=1&b=2".to_string())
+        );
+        assert_eq!(
+            normalize_url("http://jobs.example.com/offer/42"),
+            normalize_url("https://jobs.example.com/offer/42/")
+        );
+        assert_eq!(normalize_url("not a url"), None);
+        assert_eq!(normalize_url("ftp://example.com/job"), None);
+    }
+}
@@ -0,0 +1,189 @@
+// src/core/jobs.rs
+//! Asynchronous CV generations.
+//!
+//! A large tenant's generation can wait in the scheduler queue longer than a
+//! client or proxy keeps a request open. `POST /generate/async` records a job
+//! here and runs the usual generation in the background; the client polls
+//! the job and downloads the PDF once it is `done`. A job is `queued` until
+//! its task starts, `running` while it waits for a slot and compiles, then
+//! `done` with the generated `filename` or `failed` with an `error`. Jobs
+//! left unfinished by a restart are marked failed at startup.
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::SqlitePool;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Done,
+    Failed,
+}
+
+impl JobStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Queued => "queued",
+            Self::Running => "running",
+            Self::Done => "done",
+            Self::Failed => "failed",
+        }
+    }
+
+    fn parse(value: &str) -> Self {
+        match value {
+            "queued" => Self::Queued,
+            "running" => Self::Running,
+            "done" => Self::Done,
+            _ => Self::Failed,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GenerationJob {
+    pub id: String,
+    pub profile: String,
+    pub status: JobStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filename: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(sqlx::FromRow)]
+struct JobRow {
+    id: String,
+    profile: String,
+    status: String,
+    filename: Option<String>,
+    error: Option<String>,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+}
+
+impl From<JobRow> for GenerationJob {
+    fn from(row: JobRow) -> Self {
+        Self {
+            id: row.id,
+            profile: row.profile,
+            status: JobStatus::parse(&row.status),
+            filename: row.filename,
+            error: row.error,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        }
+    }
+}
+
+const COLUMNS: &str = "id, profile, status, filename, error, created_at, updated_at";
+
+/// Record a queued generation of `profile` for `email`.
+pub async fn create(
+    pool: &SqlitePool,
+    owner: &str,
+    email: &str,
+    profile: &str,
+) -> Result<GenerationJob> {
+    let now = Utc::now();
+    let row = sqlx::query_as::<_, JobRow>(&format!(
+        "INSERT INTO generation_jobs (id, owner, user_email, profile, status, created_at, \
+         updated_at) VALUES (?, ?, ?, ?, ?, ?, ?) RETURNING {}",
+        COLUMNS
+    ))
+    .bind(uuid::Uuid::new_v4().to_string())
+    .bind(owner)
+    .bind(email.to_lowercase())
+    .bind(profile)
+    .bind(JobStatus::Queued.as_str())
+    .bind(now)
+    .bind(now)
+    .fetch_one(pool)
+    .await?;
+    Ok(row.into())
+}
+
+/// Job `id` of `email`.
+pub async fn get(pool: &SqlitePool, email: &str, id: &str) -> Result<Option<GenerationJob>> {
+    let row = sqlx::query_as::<_, JobRow>(&format!(
+        "SELECT {} FROM generation_jobs WHERE id = ? AND user_email = ?",
+        COLUMNS
+    ))
+    .bind(id)
+    .bind(email.to_lowercase())
+    .fetch_optional(pool)
+    .await?;
+    Ok(row.map(Into::into))
+}
+
+async fn set(
+    pool: &SqlitePool,
+    id: &str,
+    status: JobStatus,
+    filename: Option<&str>,
+    error: Option<&str>,
+) -> Result<()> {
+    sqlx::query(
+        "UPDATE generation_jobs SET status = ?, filename = ?, error = ?, updated_at = ? \
+         WHERE id = ?",
+    )
+    .bind(status.as_str())
+    .bind(filename)
+    .bind(error)
+    .bind(Utc::now())
+    .bind(id)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn started(pool: &SqlitePool, id: &str) -> Result<()> {
+    set(pool, id, JobStatus::Running, None, None).await
+}
+
+/// The job generated `filename` for `profile`, as the generation resolved
+/// the requested name.
+pub async fn succeeded(pool: &SqlitePool, id: &str, profile: &str, filename: &str) -> Result<()> {
+    sqlx::query("UPDATE generation_jobs SET profile = ? WHERE id = ?")
+        .bind(profile)
+        .bind(id)
+        .execute(pool)
+        .await?;
+    set(pool, id, JobStatus::Done, Some(filename), None).await
+}
+
+pub async fn failed(pool: &SqlitePool, id: &str, error: &str) -> Result<()> {
+    set(pool, id, JobStatus::Failed, None, Some(error)).await
+}
+
+/// Fail the jobs a previous run left unfinished; returns how many.
+pub async fn fail_interrupted(pool: &SqlitePool) -> Result<u64> {
+    let failed = sqlx::query(
+        "UPDATE generation_jobs SET status = 'failed', \
+         error = 'The server restarted before the CV was generated', updated_at = ? \
+         WHERE status IN ('queued', 'running')",
+    )
+    .bind(Utc::now())
+    .execute(pool)
+    .await?
+    .rows_affected();
+    Ok(failed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn statuses_round_trip_and_unknown_ones_count_as_failed() {
+        for status in [JobStatus::Queued, JobStatus::Running, JobStatus::Done, JobStatus::Failed] {
+            assert_eq!(JobStatus::parse(status.as_str()), status);
+        }
+        assert_eq!(JobStatus::parse("cancelled"), JobStatus::Failed);
+    }
+}
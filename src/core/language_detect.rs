@@ -0,0 +1,182 @@
+// src/core/language_detect.rs
+//! Dominant language of imported CV content.
+//!
+//! Imported CVs used to land in `experiences_en.typ` whatever they were
+//! written in. [`cv_language`] counts common function words of each language
+//! in the CV's prose (summary, titles, descriptions, bullet points). A clear
+//! winner among the [`SUPPORTED`] languages decides; otherwise the supported
+//! language the import service reported in `metadata.language` is used, and
+//! English as a last resort. [`apply`]
+//! writes the result back to `metadata.language`, which names the profile's
+//! first `experiences_<lang>.typ`.
+
+use serde::Serialize;
+
+use crate::types::cv_data::CvJson;
+
+/// Languages CVs are generated in.
+const SUPPORTED: &[&str] = &["en", "fr", "de"];
+
+/// Function words per language; frequent in prose, rare in the others.
+/// Spanish is counted only so that Spanish prose, which shares many short
+/// words with French, is not taken for French; it is never the result.
+const STOPWORDS: &[(&str, &[&str])] = &[
+    (
+        "en",
+        &[
+            "the", "and", "of", "to", "in", "for", "with", "on", "as", "by", "from", "at", "is",
+            "was", "were", "are", "an", "this", "that", "my", "our", "which", "including",
+        ],
+    ),
+    (
+        "fr",
+        &[
+            "le", "la", "les", "des", "du", "de", "et", "en", "pour", "avec", "sur", "dans", "un",
+            "une", "au", "aux", "est", "par", "ainsi", "mise", "ses", "leur",
+        ],
+    ),
+    (
+        "de",
+        &[
+            "der", "die", "das", "und", "mit", "für", "von", "im", "zur", "zum", "bei", "ein",
+            "eine", "einer", "den", "dem", "des", "ist", "wurde", "sowie", "auf", "als",
+        ],
+    ),
+    (
+        "es",
+        &[
+            "el", "los", "las", "del", "y", "con", "para", "por", "una", "uno", "es", "como",
+            "entre", "sobre", "su", "sus", "al", "desde", "mediante", "lo",
+        ],
+    ),
+];
+
+/// Below this many function words the text is too short to judge.
+const MIN_HITS: usize = 8;
+/// The winner needs this many times the runner-up's hits.
+const MIN_MARGIN: f64 = 1.5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LanguageSource {
+    /// Detected from the CV's text.
+    Content,
+    /// Reported by the import service.
+    ServiceHint,
+    /// Neither was conclusive.
+    Default,
+}
+
+/// The language `text` is written in, if a supported one clearly dominates.
+pub fn detect(text: &str) -> Option<&'static str> {
+    let lowered = text.to_lowercase();
+    let words: Vec<&str> = lowered
+        .split(|c: char| !c.is_alphabetic())
+        .filter(|w| !w.is_empty())
+        .collect();
+
+    let mut scores: Vec<(&'static str, usize)> = STOPWORDS
+        .iter()
+        .map(|(lang, stopwords)| {
+            let hits = words.iter().filter(|w| stopwords.contains(w)).count();
+            (*lang, hits)
+        })
+        .collect();
+    scores.sort_by(|a, b| b.1.cmp(&a.1));
+
+    let (lang, best) = scores[0];
+    let runner_up = scores.get(1).map_or(0, |s| s.1);
+    let clear = best >= MIN_HITS && best as f64 >= runner_up as f64 * MIN_MARGIN;
+    (clear && SUPPORTED.contains(&lang)).then_some(lang)
+}
+
+/// The CV's free text, where its language shows.
+fn prose(cv: &CvJson) -> String {
+    let mut parts: Vec<&str> = Vec::new();
+    parts.extend(cv.personal_info.title.as_deref());
+    parts.extend(cv.personal_info.summary.as_deref());
+    for exp in &cv.work_experience {
+        parts.push(&exp.title);
+        parts.extend(exp.description.as_deref());
+        parts.extend(exp.responsibilities.iter().map(String::as_str));
+        parts.extend(exp.achievements.iter().flatten().map(String::as_str));
+    }
+    for edu in &cv.education {
+        parts.push(&edu.degree);
+        parts.extend(edu.field.as_deref());
+    }
+    for project in cv.projects.iter().flatten() {
+        parts.push(&project.description);
+    }
+    parts.join("\n")
+}
+
+/// Language to file the CV's experiences under, and how it was decided.
+pub fn cv_language(cv: &CvJson) -> (String, LanguageSource) {
+    if let Some(lang) = detect(&prose(cv)) {
+        return (lang.to_string(), LanguageSource::Content);
+    }
+    let hint = cv.metadata.language.trim().to_lowercase();
+    if SUPPORTED.contains(&hint.as_str()) {
+        return (hint, LanguageSource::ServiceHint);
+    }
+    ("en".to_string(), LanguageSource::Default)
+}
+
+/// Set `metadata.language` to [`cv_language`]; returns how it was decided.
+pub fn apply(cv: &mut CvJson) -> LanguageSource {
+    let (lang, source) = cv_language(cv);
+    cv.metadata.language = lang;
+    source
+}
+
+/// `metadata.language` if it names a supported language, else English.
+pub fn metadata_language(cv: &CvJson) -> &str {
+    let lang = cv.metadata.language.as_str();
+    if SUPPORTED.contains(&lang) {
+        lang
+    } else {
+        "en"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_clear_winners_only() {
+        assert_eq!(
+            detect(
+                "Responsable de la mise en place des pipelines de données pour les équipes \
+                 produit, avec un suivi des coûts et la formation des développeurs."
+            ),
+            Some("fr")
+        );
+        assert_eq!(
+            detect(
+                "Led the migration of the billing platform to Kubernetes and mentored a team \
+                 of five engineers in the design of the new services for our customers."
+            ),
+            Some("en")
+        );
+        assert_eq!(
+            detect(
+                "Verantwortlich für die Entwicklung der Plattform und die Betreuung der \
+                 Kunden im Bereich Logistik sowie die Einführung von einer neuen Lösung."
+            ),
+            Some("de")
+        );
+        // Spanish is recognized but not supported, and not taken for French.
+        assert_eq!(
+            detect(
+                "Responsable del desarrollo de la plataforma y de la gestión de los equipos \
+                 con los clientes para una empresa de logística en España, y de la \
+                 formación de los desarrolladores."
+            ),
+            None
+        );
+        // Keywords and names alone say nothing.
+        assert_eq!(detect("Rust, Kubernetes, PostgreSQL, AWS, Terraform"), None);
+    }
+}
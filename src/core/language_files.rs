@@ -0,0 +1,195 @@
+// src/core/language_files.rs
+//! Cleanup of experiences files that are not real translations.
+//!
+//! Profiles accumulate `experiences_<lang>.typ` files that add nothing: copies
+//! identical to another language (usually English) and untouched starter
+//! placeholders next to real content. Either makes a language look translated
+//! when it isn't. [`plan`] compares content hashes per profile and reports
+//! them; [`apply`] deletes the reported files. English is kept over its
+//! copies, and every profile keeps at least one experiences file.
+
+use anyhow::{Context, Result};
+use graflog::app_log;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::core::{profile_lock, translation_status, FsOps};
+use crate::utils::sha256_hex;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LanguageFileIssue {
+    /// Same content as another language's file.
+    Duplicate,
+    /// The unedited starter placeholder, while another language has content.
+    Starter,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LanguageFileFinding {
+    pub profile: String,
+    pub file: String,
+    pub lang: String,
+    pub issue: LanguageFileIssue,
+    /// Language whose file this one copies, for duplicates.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duplicate_of: Option<String>,
+}
+
+/// Report returned by the admin endpoint and printed by the CLI.
+#[derive(Debug, Clone, Serialize)]
+pub struct LanguageCleanupReport {
+    pub findings: Vec<LanguageFileFinding>,
+    pub profiles_scanned: usize,
+    /// False for a dry run.
+    pub applied: bool,
+}
+
+/// Hash ignoring line endings and trailing whitespace, so a copy saved from
+/// another editor still matches.
+pub(crate) fn content_hash(content: &str) -> String {
+    let normalized: Vec<&str> = content.lines().map(str::trim_end).collect();
+    sha256_hex(normalized.join("\n").trim().as_bytes())
+}
+
+/// Files to drop among `files` (`(lang, content)`), with the issue and the
+/// language duplicated. English comes first, then languages alphabetically.
+fn classify(
+    files: &[(String, String)],
+    starter: &str,
+) -> Vec<(String, LanguageFileIssue, Option<String>)> {
+    let mut ordered: Vec<&(String, String)> = files.iter().collect();
+    ordered.sort_by_key(|(lang, _)| (lang != "en", lang.clone()));
+
+    let starter = content_hash(starter);
+    let hashes: Vec<String> = ordered
+        .iter()
+        .map(|(_, content)| content_hash(content))
+        .collect();
+    let has_content = hashes.iter().any(|hash| *hash != starter);
+
+    let mut kept: HashMap<&str, &str> = HashMap::new();
+    let mut drops = Vec::new();
+    for ((lang, _), hash) in ordered.iter().zip(&hashes) {
+        if has_content && *hash == starter {
+            drops.push((lang.clone(), LanguageFileIssue::Starter, None));
+        } else if let Some(original) = kept.get(hash.as_str()) {
+            drops.push((
+                lang.clone(),
+                LanguageFileIssue::Duplicate,
+                Some(original.to_string()),
+            ));
+        } else {
+            kept.insert(hash, lang);
+        }
+    }
+    drops
+}
+
+async fn experiences_files(profile_dir: &Path) -> Result<Vec<(String, String)>> {
+    let mut files = Vec::new();
+    let mut entries = tokio::fs::read_dir(profile_dir)
+        .await
+        .with_context(|| format!("Failed to read {}", profile_dir.display()))?;
+    while let Some(entry) = entries.next_entry().await? {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let Some(lang) = name
+            .strip_prefix("experiences_")
+            .and_then(|rest| rest.strip_suffix(".typ"))
+            .filter(|lang| !lang.is_empty())
+        else {
+            continue;
+        };
+        let content = FsOps::read_file_safe(&entry.path()).await?;
+        files.push((lang.to_string(), content));
+    }
+    Ok(files)
+}
+
+/// Redundant language files of the profiles in `tenant_dir`; nothing is
+/// changed. `starter` is the placeholder new profiles are created with.
+pub async fn plan(tenant_dir: &Path, starter: &str) -> Result<LanguageCleanupReport> {
+    let profiles = FsOps::list_profiles(tenant_dir).await?;
+    let mut findings = Vec::new();
+    for profile in &profiles {
+        let files = experiences_files(&tenant_dir.join(profile)).await?;
+        for (lang, issue, duplicate_of) in classify(&files, starter) {
+            findings.push(LanguageFileFinding {
+                profile: profile.clone(),
+                file: format!("experiences_{}.typ", lang),
+                lang,
+                issue,
+                duplicate_of,
+            });
+        }
+    }
+    Ok(LanguageCleanupReport {
+        findings,
+        profiles_scanned: profiles.len(),
+        applied: false,
+    })
+}
+
+/// Delete the files in `report`.
+pub async fn apply(
+    tenant_dir: &Path,
+    mut report: LanguageCleanupReport,
+) -> Result<LanguageCleanupReport> {
+    for finding in &report.findings {
+        let profile_dir = tenant_dir.join(&finding.profile);
+        let _lock = profile_lock::write(&profile_dir).await;
+        tokio::fs::remove_file(profile_dir.join(&finding.file))
+            .await
+            .with_context(|| format!("Failed to delete {}/{}", finding.profile, finding.file))?;
+        translation_status::forget(&profile_dir, &finding.lang);
+    }
+    app_log!(
+        info,
+        "Removed {} redundant language files in {}",
+        report.findings.len(),
+        tenant_dir.display()
+    );
+    report.applied = true;
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_english_and_real_translations() {
+        let file = |lang: &str, content: &str| (lang.to_string(), content.to_string());
+        let files = vec![
+            file("fr", "= Experience\r\nAcme  \n"),
+            file("en", "= Experience\nAcme"),
+            file("de", "= Erfahrung\nAcme"),
+            file("es", "starter"),
+        ];
+        let mut drops = classify(&files, "starter");
+        drops.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(
+            drops,
+            vec![
+                ("es".to_string(), LanguageFileIssue::Starter, None),
+                (
+                    "fr".to_string(),
+                    LanguageFileIssue::Duplicate,
+                    Some("en".to_string())
+                ),
+            ]
+        );
+
+        // A profile with only placeholders keeps one of them.
+        let drops = classify(&[file("fr", "starter"), file("en", "starter")], "starter");
+        assert_eq!(
+            drops,
+            vec![(
+                "fr".to_string(),
+                LanguageFileIssue::Duplicate,
+                Some("en".to_string())
+            )]
+        );
+    }
+}
@@ -0,0 +1,245 @@
+// src/core/maintenance.rs
+//! Offline maintenance tasks, run by `cvenom admin` (see [`crate::admin_cli`]).
+//!
+//! Each task walks every user folder (`<root>/<tenant>/<user>`) of every
+//! region and returns a short report:
+//!
+//! * [`prune_outputs`] — delete generated PDFs older than a cutoff, leaving
+//!   the same tombstones as a user deletion ([`generation_outputs::delete`])
+//! * [`prune_caches`]  — delete old picture variants and cap their size,
+//!   and delete expired service answers
+//! * [`verify`]        — [`profile_integrity`] check of every person
+//! * [`reindex`]       — rebuild the profile name index of every user folder
+//! * [`vacuum`]        — `VACUUM` the SQLite database
+//! * [`rotate_log`]    — copy the log aside and truncate it once it is large;
+//!   the server keeps its file handle, so the file is never renamed
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::core::database::TenantRepository;
+use crate::core::{
+    data_residency, generation_outputs, image_variants, owner, profile_integrity, profile_lookup,
+    service_cache,
+};
+
+/// Recorded as the actor of pruned generations in the audit log.
+pub const ACTOR: &str = "cvenom-admin";
+
+/// A user folder and the tenant folder holding it.
+struct UserDir {
+    tenant: String,
+    path: PathBuf,
+}
+
+/// Every `<root>/<tenant>/<user>` directory, skipping hidden ones (staging,
+/// caches).
+async fn user_dirs(roots: &[PathBuf]) -> Vec<UserDir> {
+    let mut dirs = Vec::new();
+    for root in roots {
+        for (tenant, tenant_dir) in subdirs(root).await {
+            for (_, path) in subdirs(&tenant_dir).await {
+                dirs.push(UserDir {
+                    tenant: tenant.clone(),
+                    path,
+                });
+            }
+        }
+    }
+    dirs
+}
+
+async fn subdirs(dir: &Path) -> Vec<(String, PathBuf)> {
+    let mut found = Vec::new();
+    let Ok(mut entries) = tokio::fs::read_dir(dir).await else {
+        return found;
+    };
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if !name.starts_with('.') && entry.path().is_dir() {
+            found.push((name, entry.path()));
+        }
+    }
+    found.sort();
+    found
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PruneReport {
+    pub deleted: usize,
+    pub bytes: u64,
+}
+
+/// Delete the generated PDFs written before `before`. Without `execute`, only
+/// count them.
+pub async fn prune_outputs(
+    pool: &SqlitePool,
+    output_dir: &Path,
+    before: DateTime<Utc>,
+    execute: bool,
+) -> Result<PruneReport> {
+    let mut report = PruneReport::default();
+    let tenants = TenantRepository::new(pool).list_active().await?;
+    for user in user_dirs(&data_residency::output_roots(output_dir)).await {
+        // Logged for the tenant of the folder; none once it is deleted.
+        let user_folder = user.path.file_name().unwrap_or_default().to_string_lossy();
+        let tenant_id =
+            owner::tenant_of_account(&tenants, &user.tenant, &user_folder).map(|t| t.id);
+        for (profile, profile_dir) in subdirs(&user.path).await {
+            let mut entries = tokio::fs::read_dir(&profile_dir).await?;
+            while let Some(entry) = entries.next_entry().await? {
+                let path = entry.path();
+                if path.extension().and_then(|e| e.to_str()) != Some("pdf") {
+                    continue;
+                }
+                let meta = entry.metadata().await?;
+                let old = meta
+                    .modified()
+                    .map(|modified| DateTime::<Utc>::from(modified) < before)
+                    .unwrap_or(false);
+                if !old {
+                    continue;
+                }
+                if execute {
+                    generation_outputs::delete(
+                        pool,
+                        output_dir,
+                        tenant_id,
+                        ACTOR,
+                        &profile,
+                        &path,
+                    )
+                    .await?;
+                }
+                report.deleted += 1;
+                report.bytes += meta.len();
+            }
+        }
+    }
+    Ok(report)
+}
+
+/// Delete the cached picture variants of every data region older than
+/// `max_age` or beyond `max_bytes` per region, and the cached service
+/// answers past their TTL ([`service_cache::prune`]). Without `execute`,
+/// only count them.
+pub async fn prune_caches(
+    data_dir: &Path,
+    max_age: Duration,
+    max_bytes: u64,
+    execute: bool,
+) -> Result<PruneReport> {
+    let mut report = PruneReport::default();
+    for root in data_residency::data_roots(data_dir) {
+        let (pictures, answers) = tokio::task::spawn_blocking(move || {
+            Ok::<_, anyhow::Error>((
+                image_variants::prune(&root, max_age, max_bytes, execute)?,
+                service_cache::prune(&root, execute)?,
+            ))
+        })
+        .await??;
+        report.deleted += pictures.0 + answers.0;
+        report.bytes += pictures.1 + answers.1;
+    }
+    Ok(report)
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct VerifyReport {
+    pub checked: usize,
+    /// `<user folder>/<profile>` and its failed checks.
+    pub broken: Vec<(String, Vec<String>)>,
+}
+
+/// Check every person; `compile` adds the Typst check, which is slow.
+pub async fn verify(templates_dir: &Path, data_dir: &Path, compile: bool) -> Result<VerifyReport> {
+    let mut report = VerifyReport::default();
+    for user in user_dirs(&data_residency::data_roots(data_dir)).await {
+        let integrity = profile_integrity::check_all(templates_dir, &user.path, compile).await?;
+        report.checked += integrity.checked;
+        // Relative to its region's root, so every region reads the same.
+        let root = data_residency::data_root_of(data_dir, &user.path);
+        let folder = user
+            .path
+            .strip_prefix(&root)
+            .unwrap_or(&user.path)
+            .display()
+            .to_string();
+        for person in integrity.profiles.into_iter().filter(|p| !p.healthy) {
+            let failed = person
+                .checks
+                .iter()
+                .filter(|c| c.status == profile_integrity::CheckStatus::Failed)
+                .map(|c| c.id.to_string())
+                .collect();
+            report.broken.push((format!("{}/{}", folder, person.profile), failed));
+        }
+    }
+    Ok(report)
+}
+
+/// Rebuild the profile name index of every user folder; returns how many.
+pub async fn reindex(data_dir: &Path) -> Result<usize> {
+    let users = user_dirs(&data_residency::data_roots(data_dir)).await;
+    for user in &users {
+        profile_lookup::refresh(&user.path).await?;
+    }
+    Ok(users.len())
+}
+
+/// `VACUUM` the database at `database_path`; returns its size before and
+/// after.
+pub async fn vacuum(pool: &SqlitePool, database_path: &Path) -> Result<(u64, u64)> {
+    let size = || std::fs::metadata(database_path).map(|m| m.len()).unwrap_or(0);
+    let before = size();
+    sqlx::query("VACUUM").execute(pool).await?;
+    Ok((before, size()))
+}
+
+/// Rotate `log_path` once it exceeds `max_bytes`: `<log>.1` … `<log>.<keep>`
+/// shift by one and the log is copied to `<log>.1`, then truncated. Returns
+/// whether it was rotated.
+pub fn rotate_log(log_path: &Path, max_bytes: u64, keep: usize) -> Result<bool> {
+    let Ok(meta) = std::fs::metadata(log_path) else {
+        return Ok(false);
+    };
+    if meta.len() <= max_bytes || keep == 0 {
+        return Ok(false);
+    }
+    let numbered = |n: usize| PathBuf::from(format!("{}.{}", log_path.display(), n));
+    let _ = std::fs::remove_file(numbered(keep));
+    for n in (1..keep).rev() {
+        if numbered(n).exists() {
+            std::fs::rename(numbered(n), numbered(n + 1))?;
+        }
+    }
+    std::fs::copy(log_path, numbered(1))?;
+    std::fs::OpenOptions::new().write(true).open(log_path)?.set_len(0)?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn logs_rotate_only_when_large_and_keep_a_bounded_history() {
+        let tmp = tempfile::tempdir().unwrap();
+        let log = tmp.path().join("cvenom.log");
+        std::fs::write(&log, "small").unwrap();
+        assert!(!rotate_log(&log, 10, 2).unwrap());
+
+        for round in ["first rotation", "second rotation", "third rotation"] {
+            std::fs::write(&log, round).unwrap();
+            assert!(rotate_log(&log, 10, 2).unwrap());
+        }
+        assert_eq!(std::fs::read_to_string(&log).unwrap(), "");
+        let kept = |n| std::fs::read_to_string(tmp.path().join(format!("cvenom.log.{}", n)));
+        assert_eq!(kept(1).unwrap(), "third rotation");
+        assert_eq!(kept(2).unwrap(), "second rotation");
+        assert!(kept(3).is_err());
+    }
+}
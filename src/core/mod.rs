@@ -1,13 +1,81 @@
 // src/core/mod.rs
 //! Core services to eliminate redundancy while preserving API compatibility
 
+pub mod ai_budget;
+pub mod asset_check;
+pub mod asset_sync;
+pub mod audit_log;
 pub mod brand_store;
 pub mod branding;
+pub mod bulk_provisioning;
+pub mod bulk_regeneration;
+pub mod client_logos;
+pub mod compile_benchmark;
 pub mod config_manager;
+pub mod consistency;
+pub mod conversation_store;
+pub mod data_encryption;
+pub mod data_residency;
 pub mod database;
+pub mod disk_monitor;
+pub mod email_intake;
+pub mod event_export;
+pub mod experiences_fallback;
+pub mod file_drafts;
 pub mod fs_ops;
+pub mod generation_dedup;
+pub mod generation_lock;
+pub mod generation_outputs;
+pub mod generation_scheduler;
+pub mod html_export;
+pub mod image_slots;
+pub mod image_variants;
+pub mod import_retry;
+pub mod import_staging;
+pub mod job_match_cache;
+pub mod jobs;
+pub mod job_postings;
+pub mod language_detect;
+pub mod language_files;
+pub mod maintenance;
+pub mod owner;
+pub mod pdf_cache;
+pub mod person_tags;
+pub mod presigned_uploads;
+pub mod profile_access;
+pub mod profile_approval;
+pub mod profile_comments;
+pub mod profile_watch;
+pub mod profile_integrity;
+pub mod profile_lock;
+pub mod profile_lookup;
+pub mod profile_names;
+pub mod profile_schema;
+pub mod provisioning;
+pub mod request_cancel;
+pub mod review_calendar;
+pub mod secrets;
+pub mod service_cache;
 pub mod service_client;
+pub mod service_stream;
+pub mod service_usage;
+pub mod smart_lists;
 pub mod template_engine;
+pub mod template_features;
+pub mod template_health;
+pub mod template_registry;
+pub mod template_versions;
+pub mod tenant_archive;
+pub mod tenant_bundle;
+pub mod tenant_cache;
+pub mod tenant_rename;
+pub mod tenant_templates;
+pub mod toml_patch;
+pub mod translation_status;
+pub mod translations;
+pub mod typst_compiler;
+pub mod typst_lint;
+pub mod upload_sessions;
 
 pub use config_manager::ConfigManager;
 pub use database::Database;
@@ -0,0 +1,119 @@
+// src/core/owner.rs
+//! Who owns a database row.
+//!
+//! `tenant_name` is a display name, not a key: every `john@…` account gets
+//! "john", and an admin can rename it. Rows are keyed instead by
+//!
+//! - the account key, `<tenant folder>/<user folder>`, for records about a
+//!   person or profile (comments, watches, tags, jobs, failed imports…).
+//!   Profiles live in one account's folder, so this is exactly the folder
+//!   the record describes, and two members of a domain tenant with a person
+//!   of the same name never see each other's rows;
+//! - the tenant id, for what a tenant shares across its members: activity
+//!   reports, service usage, AI budgets and the admin's per-tenant settings.
+//!
+//! Neither key changes when a tenant is renamed.
+
+use crate::core::database::{email_to_folder_name, get_tenant_for_email, Tenant};
+
+/// Tables keyed by [`account_key`] in their `owner` column.
+pub const ACCOUNT_TABLES: [&str; 8] = [
+    "profile_comments",
+    "profile_watches",
+    "deleted_generations",
+    "failed_imports",
+    "generation_jobs",
+    "person_tags",
+    "person_attributes",
+    "smart_lists",
+];
+
+/// Key of the account of `email`: its user folder below the data dir.
+pub fn account_key(email: &str) -> String {
+    account_key_of(&get_tenant_for_email(email), &email_to_folder_name(email))
+}
+
+/// Key of the account whose files are in `<tenant_folder>/<user_folder>`.
+pub fn account_key_of(tenant_folder: &str, user_folder: &str) -> String {
+    format!("{}/{}", tenant_folder, user_folder)
+}
+
+/// The tenant, among `tenants`, of the account whose files are in
+/// `<tenant_folder>/<user_folder>`: the email tenant with that folder, or
+/// else the domain tenant whose domain the folder name ends with. Folder
+/// names are lossy (`@` and `.` become `-`), so the folder is compared with
+/// each tenant's rather than parsed back into an address.
+pub fn tenant_of_account<'a>(
+    tenants: &'a [Tenant],
+    tenant_folder: &str,
+    user_folder: &str,
+) -> Option<&'a Tenant> {
+    tenants
+        .iter()
+        .find(|t| {
+            t.email.as_deref().is_some_and(|email| {
+                get_tenant_for_email(email) == tenant_folder
+                    && email_to_folder_name(email) == user_folder
+            })
+        })
+        .or_else(|| {
+            tenants
+                .iter()
+                .filter_map(|t| Some((t, t.domain.as_deref()?)))
+                .filter(|(_, domain)| {
+                    let suffix = format!("-{}", domain.replace('.', "-"));
+                    get_tenant_for_email(&format!("member@{}", domain)) == tenant_folder
+                        && user_folder.ends_with(&suffix)
+                })
+                .max_by_key(|(_, domain)| domain.len())
+                .map(|(tenant, _)| tenant)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tenant(id: i64, email: Option<&str>, domain: Option<&str>) -> Tenant {
+        Tenant {
+            id,
+            email: email.map(str::to_string),
+            domain: domain.map(str::to_string),
+            tenant_name: "john".to_string(),
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+            is_active: true,
+            last_seen_at: None,
+            referred_by_code: None,
+            preferred_lang: None,
+            email_prefs: None,
+            archived_at: None,
+        }
+    }
+
+    #[test]
+    fn finds_the_tenant_of_a_user_folder() {
+        let tenants = [
+            tenant(1, Some("john@acme.com"), None),
+            tenant(2, None, Some("globex.com")),
+            tenant(3, None, Some("x.globex.com")),
+        ];
+        let folder = get_tenant_for_email("john@acme.com");
+        let id =
+            |user_folder: &str| tenant_of_account(&tenants, &folder, user_folder).map(|t| t.id);
+        assert_eq!(id("john-acme-com"), Some(1));
+        assert_eq!(id("john-globex-com"), Some(2));
+        assert_eq!(id("jane-x-globex-com"), Some(3));
+        assert_eq!(id("jane-acme-com"), None);
+    }
+
+    #[test]
+    fn accounts_sharing_a_tenant_name_have_distinct_keys() {
+        // Both accounts are tenant "john".
+        assert_ne!(account_key("john@acme.com"), account_key("john@globex.com"));
+        assert_eq!(
+            account_key("jane@acme.com"),
+            account_key_of(&get_tenant_for_email("jane@acme.com"), "jane-acme-com")
+        );
+    }
+}
@@ -0,0 +1,219 @@
+// src/core/pdf_cache.rs
+//! Reuse of a generated CV when nothing it is compiled from has changed.
+//!
+//! [`key`] hashes everything that goes into a compilation: the profile's
+//! files (`cv_params.toml`, the experiences of the requested language, the
+//! picture, logos…), the tenant's default photo, the files of the template
+//! version being used, the central text registry, the brand and the
+//! generation options. After a compilation, [`store`] records the key next to
+//! the generated file in the person's output directory (`.generation_cache.json`);
+//! [`lookup`] returns that file when the key matches and the file is still the
+//! one that was generated, so the caller can skip the compilation.
+//!
+//! Hidden files of the profile (approval, history and other metadata) and
+//! experiences of other languages, unless used as a fallback, are not part of
+//! the key.
+
+use graflog::app_log;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::config::CvConfig;
+use crate::core::experiences_fallback::{self, Fallback};
+use crate::core::{template_versions, TemplateEngine};
+use crate::generator::GeneratedCv;
+
+pub const CACHE_FILE: &str = ".generation_cache.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Entry {
+    filename: String,
+    /// [`crate::utils::generation_id`] of the file when it was generated.
+    file_id: String,
+    #[serde(default)]
+    skipped_assets: Vec<String>,
+}
+
+/// Cache entries of an output directory, by key.
+type Index = HashMap<String, Entry>;
+
+/// Length-prefixed, so consecutive fields can't run into each other.
+fn field(hasher: &mut Sha256, bytes: &[u8]) {
+    hasher.update((bytes.len() as u64).to_le_bytes());
+    hasher.update(bytes);
+}
+
+/// Names and contents of the files under `dir`, in name order. Hidden entries
+/// and top-level names rejected by `keep` are left out.
+fn hash_dir(hasher: &mut Sha256, dir: &Path, keep: &dyn Fn(&str) -> bool) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    let mut entries: Vec<(String, PathBuf)> = entries
+        .flatten()
+        .map(|e| (e.file_name().to_string_lossy().to_string(), e.path()))
+        .filter(|(name, _)| !name.starts_with('.') && keep(name))
+        .collect();
+    entries.sort();
+    for (name, path) in entries {
+        field(hasher, name.as_bytes());
+        if path.is_dir() {
+            hash_dir(hasher, &path, &|_| true);
+        } else if let Ok(content) = std::fs::read(&path) {
+            field(hasher, &content);
+        }
+    }
+}
+
+/// Hash of everything the output of `config` is compiled from.
+pub fn key(config: &CvConfig, engine: &TemplateEngine) -> String {
+    let mut hasher = Sha256::new();
+    field(&mut hasher, config.lang.as_bytes());
+    field(&mut hasher, config.template.as_bytes());
+    field(&mut hasher, config.template_version.as_deref().unwrap_or_default().as_bytes());
+    field(&mut hasher, config.format.extension().as_bytes());
+    field(&mut hasher, format!("{:?}", config.preview_ppi).as_bytes());
+    field(&mut hasher, config.watermark.as_deref().unwrap_or_default().as_bytes());
+    field(
+        &mut hasher,
+        &[
+            config.use_custom_colors as u8,
+            config.strict_assets as u8,
+            config.strict as u8,
+            config.pdf_a as u8,
+        ],
+    );
+    // Not the creation date: a cached PDF keeps the date it was made.
+    let metadata = &config.pdf_metadata;
+    for value in [&metadata.title, &metadata.author, &metadata.subject] {
+        field(&mut hasher, value.as_deref().unwrap_or_default().as_bytes());
+    }
+    field(&mut hasher, metadata.keywords.join(",").as_bytes());
+    field(&mut hasher, format!("{:?}", config.features).as_bytes());
+    for (name, value) in engine.text_inputs(&config.lang).iter().chain(&config.inputs) {
+        field(&mut hasher, name.as_bytes());
+        field(&mut hasher, value.as_bytes());
+    }
+    if let Some(brand) = &config.brand {
+        field(&mut hasher, &serde_json::to_vec(brand).unwrap_or_default());
+    }
+    if let Some(brand_dir) = &config.brand_dir {
+        hash_dir(&mut hasher, brand_dir, &|_| true);
+    }
+
+    let profile_dir = config.profile_data_dir();
+    let experiences = match experiences_fallback::resolve(&profile_dir, &config.lang) {
+        Ok(Some(Fallback::Language(other))) => experiences_fallback::experiences_file(&other),
+        Ok(Some(Fallback::Template)) => {
+            if let Ok(starter) = std::fs::read(config.templates_dir.join("experiences_template.typ")) {
+                field(&mut hasher, &starter);
+            }
+            String::new()
+        }
+        _ => experiences_fallback::experiences_file(&config.lang),
+    };
+    hash_dir(&mut hasher, &profile_dir, &|name| {
+        let other_language = name.starts_with("experiences_") && name != experiences;
+        !other_language && !name.ends_with(".bak")
+    });
+    if let Ok(photo) = std::fs::read(config.data_dir_absolute().join("default_photo.png")) {
+        field(&mut hasher, &photo);
+    }
+
+    if let Some(template) = engine.get_template(&config.template) {
+        let source = match config.template_version.as_deref() {
+            Some(version) => template_versions::version_dir(template, version)
+                .unwrap_or_else(|_| template.path.clone()),
+            None => template.path.clone(),
+        };
+        // The templates it extends are compiled in their current version.
+        for ancestor in engine.ancestors(&config.template).unwrap_or_default().into_iter().rev() {
+            let sample = ancestor.manifest.sample.as_deref().map(|s| s.trim_end_matches('/'));
+            hash_dir(&mut hasher, &ancestor.path, &|name| {
+                name != template_versions::VERSIONS_DIR && Some(name) != sample
+            });
+        }
+        // The sample person only feeds previews and compile checks.
+        let sample = template.manifest.sample.as_deref().map(|s| s.trim_end_matches('/'));
+        hash_dir(&mut hasher, &source, &|name| {
+            name != template_versions::VERSIONS_DIR && Some(name) != sample
+        });
+    }
+
+    format!("{:x}", hasher.finalize())
+}
+
+fn load(output_dir: &Path) -> Index {
+    std::fs::read_to_string(output_dir.join(CACHE_FILE))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// The file generated for `key` in `output_dir`, if it is still there unchanged.
+pub fn lookup(output_dir: &Path, key: &str) -> Option<GeneratedCv> {
+    let entry = load(output_dir).remove(key)?;
+    let path = output_dir.join(&entry.filename);
+    if crate::utils::generation_id(&path)? != entry.file_id {
+        return None;
+    }
+    Some(GeneratedCv {
+        output_path: path,
+        skipped_assets: entry.skipped_assets,
+        cached: true,
+    })
+}
+
+/// Remember that `generated` was compiled from `key`. Best effort: a failed
+/// write only costs a compilation next time.
+pub fn store(output_dir: &Path, key: &str, generated: &GeneratedCv) {
+    let Some(filename) = generated.output_path.file_name().and_then(|n| n.to_str()) else {
+        return;
+    };
+    let Some(file_id) = crate::utils::generation_id(&output_dir.join(filename)) else {
+        return;
+    };
+    let mut index = load(output_dir);
+    // A file is only ever the output of its latest key.
+    index.retain(|_, entry| entry.filename != filename);
+    index.insert(
+        key.to_string(),
+        Entry {
+            filename: filename.to_string(),
+            file_id,
+            skipped_assets: generated.skipped_assets.clone(),
+        },
+    );
+    let json = serde_json::to_string_pretty(&index).unwrap_or_default();
+    if let Err(e) = std::fs::write(output_dir.join(CACHE_FILE), json) {
+        app_log!(warn, "Failed to update the cache of {}: {}", output_dir.display(), e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn key_follows_the_profile_but_not_its_metadata() {
+        let dir = tempfile::tempdir().unwrap();
+        let profile = dir.path().join("jane");
+        std::fs::create_dir_all(&profile).unwrap();
+        std::fs::write(profile.join("cv_params.toml"), "name = \"Jane\"\n").unwrap();
+        std::fs::write(profile.join("experiences_en.typ"), "= Acme\n").unwrap();
+        let engine = TemplateEngine::new(dir.path().join("templates")).unwrap();
+        let config = CvConfig::new("jane", "en").with_data_dir(dir.path().to_path_buf());
+
+        let first = key(&config, &engine);
+        std::fs::write(profile.join(".template_versions.json"), "{}").unwrap();
+        std::fs::write(profile.join("experiences_fr.typ"), "= Acme SA\n").unwrap();
+        assert_eq!(key(&config, &engine), first);
+
+        std::fs::write(profile.join("experiences_en.typ"), "= Acme Corp\n").unwrap();
+        assert_ne!(key(&config, &engine), first);
+        let french = CvConfig::new("jane", "fr").with_data_dir(dir.path().to_path_buf());
+        assert_ne!(key(&french, &engine), key(&config, &engine));
+    }
+}
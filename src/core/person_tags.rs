@@ -0,0 +1,269 @@
+// src/core/person_tags.rs
+//! Tenant-defined tags and attributes of persons.
+//!
+//! A person (profile directory) can carry tags (`bench`, `senior`, `cloud`…)
+//! and free-form `key = value` attributes. The tenant picks its own
+//! vocabulary: tags and keys are only normalised to lowercase words. They are
+//! stored per owning account (see [`crate::core::owner`]) and profile, like
+//! comments; renaming a profile carries them over and deleting it removes
+//! them.
+//!
+//! `/persons` filters on them ([`PersonFilter`]) and generations pass them to
+//! the templates as `sys.inputs` ([`typst_inputs`]): `person.tags` holds the
+//! comma-separated tags and `person.attr.<key>` each attribute.
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+const MAX_TAGS: usize = 20;
+const MAX_ATTRIBUTES: usize = 30;
+const MAX_NAME_CHARS: usize = 40;
+const MAX_VALUE_CHARS: usize = 200;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PersonTags {
+    #[serde(default)]
+    pub tags: BTreeSet<String>,
+    #[serde(default)]
+    pub attributes: BTreeMap<String, String>,
+}
+
+/// Lowercase, with spaces as dashes; letters, digits, `-` and `_` only.
+pub(crate) fn normalize_name(name: &str, what: &str) -> Result<String> {
+    let name = name.trim().to_lowercase().replace(' ', "-");
+    if name.is_empty() || name.chars().count() > MAX_NAME_CHARS {
+        bail!("A {} must have 1 to {} characters", what, MAX_NAME_CHARS);
+    }
+    if !name.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_') {
+        bail!("'{}' is not a valid {}: use letters, digits, '-' and '_'", name, what);
+    }
+    Ok(name)
+}
+
+impl PersonTags {
+    /// Normalised tags and keys; blank attribute values are dropped.
+    pub fn normalized(&self) -> Result<Self> {
+        if self.tags.len() > MAX_TAGS {
+            bail!("A person has at most {} tags", MAX_TAGS);
+        }
+        if self.attributes.len() > MAX_ATTRIBUTES {
+            bail!("A person has at most {} attributes", MAX_ATTRIBUTES);
+        }
+        let tags = self
+            .tags
+            .iter()
+            .map(|tag| normalize_name(tag, "tag"))
+            .collect::<Result<_>>()?;
+        let mut attributes = BTreeMap::new();
+        for (key, value) in &self.attributes {
+            let value = value.trim();
+            if value.chars().count() > MAX_VALUE_CHARS {
+                bail!("Attribute values are limited to {} characters", MAX_VALUE_CHARS);
+            }
+            if !value.is_empty() {
+                attributes.insert(normalize_name(key, "attribute")?, value.to_string());
+            }
+        }
+        Ok(Self { tags, attributes })
+    }
+}
+
+/// `/persons` query: every tag, and every attribute with its value
+/// (case-insensitively), must be present.
+#[derive(Debug, Clone, Default)]
+pub struct PersonFilter {
+    tags: Vec<String>,
+    attributes: Vec<(String, String)>,
+}
+
+impl PersonFilter {
+    /// From `tags=bench,senior` and `attrs=level:senior,city:paris`.
+    pub fn parse(tags: Option<&str>, attrs: Option<&str>) -> Result<Self> {
+        let split = |list: Option<&str>| -> Vec<String> {
+            list.unwrap_or_default()
+                .split(',')
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(str::to_string)
+                .collect()
+        };
+        let tags = split(tags)
+            .iter()
+            .map(|tag| normalize_name(tag, "tag"))
+            .collect::<Result<_>>()?;
+        let attributes = split(attrs)
+            .iter()
+            .map(|pair| match pair.split_once(':') {
+                Some((key, value)) => {
+                    Ok((normalize_name(key, "attribute")?, value.trim().to_string()))
+                }
+                None => bail!("'{}' is not a key:value attribute filter", pair),
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { tags, attributes })
+    }
+
+    pub fn matches(&self, person: &PersonTags) -> bool {
+        self.tags.iter().all(|tag| person.tags.contains(tag))
+            && self.attributes.iter().all(|(key, value)| {
+                person
+                    .attributes
+                    .get(key)
+                    .is_some_and(|v| v.eq_ignore_ascii_case(value))
+            })
+    }
+}
+
+/// Tags and attributes of `profile`.
+pub async fn get(pool: &SqlitePool, owner: &str, profile: &str) -> Result<PersonTags> {
+    let mut all = for_profiles(pool, owner, Some(profile)).await?;
+    Ok(all.remove(profile).unwrap_or_default())
+}
+
+/// Tags and attributes of every person of account `owner` that has some.
+pub async fn all(pool: &SqlitePool, owner: &str) -> Result<HashMap<String, PersonTags>> {
+    for_profiles(pool, owner, None).await
+}
+
+async fn for_profiles(
+    pool: &SqlitePool,
+    owner: &str,
+    profile: Option<&str>,
+) -> Result<HashMap<String, PersonTags>> {
+    let mut persons: HashMap<String, PersonTags> = HashMap::new();
+    let tags: Vec<(String, String)> = sqlx::query_as(
+        "SELECT profile, tag FROM person_tags WHERE owner = ? AND (? IS NULL OR profile = ?)",
+    )
+    .bind(owner)
+    .bind(profile)
+    .bind(profile)
+    .fetch_all(pool)
+    .await?;
+    for (profile, tag) in tags {
+        persons.entry(profile).or_default().tags.insert(tag);
+    }
+    let attributes: Vec<(String, String, String)> = sqlx::query_as(
+        "SELECT profile, key, value FROM person_attributes \
+         WHERE owner = ? AND (? IS NULL OR profile = ?)",
+    )
+    .bind(owner)
+    .bind(profile)
+    .bind(profile)
+    .fetch_all(pool)
+    .await?;
+    for (profile, key, value) in attributes {
+        persons.entry(profile).or_default().attributes.insert(key, value);
+    }
+    Ok(persons)
+}
+
+/// Replace the tags and attributes of `profile`; returns them normalised.
+pub async fn set(
+    pool: &SqlitePool,
+    owner: &str,
+    profile: &str,
+    labels: &PersonTags,
+) -> Result<PersonTags> {
+    let labels = labels.normalized()?;
+    let mut tx = pool.begin().await?;
+    for table in ["person_tags", "person_attributes"] {
+        sqlx::query(&format!("DELETE FROM {} WHERE owner = ? AND profile = ?", table))
+            .bind(owner)
+            .bind(profile)
+            .execute(&mut *tx)
+            .await?;
+    }
+    for tag in &labels.tags {
+        sqlx::query("INSERT INTO person_tags (owner, profile, tag) VALUES (?, ?, ?)")
+            .bind(owner)
+            .bind(profile)
+            .bind(tag)
+            .execute(&mut *tx)
+            .await?;
+    }
+    for (key, value) in &labels.attributes {
+        sqlx::query(
+            "INSERT INTO person_attributes (owner, profile, key, value) VALUES (?, ?, ?, ?)",
+        )
+        .bind(owner)
+        .bind(profile)
+        .bind(key)
+        .bind(value)
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+    Ok(labels)
+}
+
+pub async fn rename_profile(pool: &SqlitePool, owner: &str, from: &str, to: &str) -> Result<()> {
+    for table in ["person_tags", "person_attributes"] {
+        sqlx::query(&format!(
+            "UPDATE {} SET profile = ? WHERE owner = ? AND profile = ?",
+            table
+        ))
+        .bind(to)
+        .bind(owner)
+        .bind(from)
+        .execute(pool)
+        .await?;
+    }
+    Ok(())
+}
+
+pub async fn delete_profile(pool: &SqlitePool, owner: &str, profile: &str) -> Result<()> {
+    for table in ["person_tags", "person_attributes"] {
+        sqlx::query(&format!("DELETE FROM {} WHERE owner = ? AND profile = ?", table))
+            .bind(owner)
+            .bind(profile)
+            .execute(pool)
+            .await?;
+    }
+    Ok(())
+}
+
+/// `sys.inputs` carrying `person`'s tags and attributes.
+pub fn typst_inputs(person: &PersonTags) -> Vec<(String, String)> {
+    let mut inputs = vec![(
+        "person.tags".to_string(),
+        person.tags.iter().cloned().collect::<Vec<_>>().join(","),
+    )];
+    inputs.extend(
+        person
+            .attributes
+            .iter()
+            .map(|(key, value)| (format!("person.attr.{}", key), value.clone())),
+    );
+    inputs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filters_match_normalised_tags_and_attributes() {
+        let person = PersonTags {
+            tags: ["Bench", "senior dev"].map(String::from).into(),
+            attributes: [("City".to_string(), " Paris ".to_string())].into(),
+        }
+        .normalized()
+        .unwrap();
+        assert!(person.tags.contains("senior-dev"));
+        assert_eq!(person.attributes["city"], "Paris");
+
+        let matching = PersonFilter::parse(Some("bench, senior-dev"), Some("city:paris")).unwrap();
+        assert!(matching.matches(&person));
+        let other = PersonFilter::parse(Some("bench"), Some("city:lyon")).unwrap();
+        assert!(!other.matches(&person));
+        assert!(PersonFilter::parse(None, Some("city")).is_err());
+        assert!(PersonTags {
+            tags: ["c++"].map(String::from).into(),
+            ..Default::default()
+        }
+        .normalized()
+        .is_err());
+    }
+}
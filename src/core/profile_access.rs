@@ -0,0 +1,100 @@
+// src/core/profile_access.rs
+//! Who may edit which profile.
+//!
+//! A profile is either the user's own ([`ProfileType::Own`], marked by
+//! [`SELF_PROFILE_MARKER`]) or a collaborator they manage. Members can always
+//! edit their own profile; creating or editing collaborators takes the
+//! editor role. Individual accounts own everything in them and are editors;
+//! users of a domain tenant are members unless granted editor in
+//! `member_roles` (the default is `DOMAIN_MEMBER_ROLE`, `member` if unset).
+
+use serde::Serialize;
+use std::path::Path;
+
+use crate::core::database::Tenant;
+use crate::core::provisioning::SELF_PROFILE_MARKER;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProfileType {
+    #[serde(rename = "self")]
+    Own,
+    Collaborator,
+}
+
+impl ProfileType {
+    pub fn of(profile_dir: &Path) -> Self {
+        if profile_dir.join(SELF_PROFILE_MARKER).is_file() {
+            Self::Own
+        } else {
+            Self::Collaborator
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Own => "self",
+            Self::Collaborator => "collaborator",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MemberRole {
+    Member,
+    Editor,
+}
+
+impl MemberRole {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "member" => Some(Self::Member),
+            "editor" => Some(Self::Editor),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Member => "member",
+            Self::Editor => "editor",
+        }
+    }
+
+    /// Role for a user with no `member_roles` row.
+    pub fn default_for(tenant: &Tenant) -> Self {
+        if tenant.email.is_some() {
+            return Self::Editor;
+        }
+        std::env::var("DOMAIN_MEMBER_ROLE")
+            .ok()
+            .and_then(|v| Self::parse(&v))
+            .unwrap_or(Self::Member)
+    }
+
+    pub fn can_edit(&self, profile_type: ProfileType) -> bool {
+        *self == Self::Editor || profile_type == ProfileType::Own
+    }
+
+    /// New profiles are collaborators; only the first-login one is created
+    /// on the user's behalf.
+    pub fn can_create(&self) -> bool {
+        *self == Self::Editor
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn members_edit_only_their_own_profile() {
+        assert!(MemberRole::Member.can_edit(ProfileType::Own));
+        assert!(!MemberRole::Member.can_edit(ProfileType::Collaborator));
+        assert!(MemberRole::Editor.can_edit(ProfileType::Collaborator));
+        assert!(!MemberRole::Member.can_create());
+        assert_eq!(MemberRole::parse(" Editor "), Some(MemberRole::Editor));
+        assert_eq!(MemberRole::parse("owner"), None);
+    }
+}
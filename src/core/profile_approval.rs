@@ -0,0 +1,365 @@
+// src/core/profile_approval.rs
+//! Review and approval of profiles before they are sent to clients.
+//!
+//! A profile is `draft` until its owner requests a review (`in_review`). A
+//! reviewer other than the requester then approves it (`approved`) or
+//! rejects it with a comment (back to `draft`): an editor of the same tenant,
+//! who finds it with [`in_review`], or the server admin. An individual
+//! account has no one else, so its owner decides their own reviews.
+//! Approval is tied to the content: the profile's files are hashed when it
+//! is approved, and any later change makes it a draft again. State and
+//! history live in the profile's `.approval.json`.
+//!
+//! What an unapproved profile means for generation is the tenant's policy:
+//! `off` (generate as usual), `watermark` (stamp every page with DRAFT) or
+//! `block` (refuse). It is set by the admin and defaults to `APPROVAL_POLICY`
+//! (default `off`).
+
+use anyhow::{bail, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::SqlitePool;
+use std::path::Path;
+
+use crate::core::FsOps;
+
+pub const PROFILE_FILE: &str = ".approval.json";
+const HISTORY_LIMIT: usize = 50;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalState {
+    #[default]
+    Draft,
+    InReview,
+    Approved,
+}
+
+impl ApprovalState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Draft => "draft",
+            Self::InReview => "in_review",
+            Self::Approved => "approved",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApprovalAction {
+    RequestReview,
+    Approve,
+    Reject,
+}
+
+impl ApprovalAction {
+    fn target(&self) -> ApprovalState {
+        match self {
+            Self::RequestReview => ApprovalState::InReview,
+            Self::Approve => ApprovalState::Approved,
+            Self::Reject => ApprovalState::Draft,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApprovalEvent {
+    pub from: ApprovalState,
+    pub to: ApprovalState,
+    pub by: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+    pub at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProfileApproval {
+    pub state: ApprovalState,
+    /// Who requested the pending review.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub requested_by: Option<String>,
+    /// Hash of the profile's files when it was approved.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub approved_hash: Option<String>,
+    /// Oldest first.
+    #[serde(default)]
+    pub history: Vec<ApprovalEvent>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApprovalPolicy {
+    Off,
+    Watermark,
+    Block,
+}
+
+impl ApprovalPolicy {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "off" => Some(Self::Off),
+            "watermark" => Some(Self::Watermark),
+            "block" => Some(Self::Block),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Off => "off",
+            Self::Watermark => "watermark",
+            Self::Block => "block",
+        }
+    }
+}
+
+impl Default for ApprovalPolicy {
+    /// `APPROVAL_POLICY`, or `off`.
+    fn default() -> Self {
+        std::env::var("APPROVAL_POLICY")
+            .ok()
+            .and_then(|v| Self::parse(&v))
+            .unwrap_or(Self::Off)
+    }
+}
+
+/// A profile waiting for a decision.
+#[derive(Debug, Clone, Serialize)]
+pub struct PendingReview {
+    pub profile: String,
+    pub requested_by: String,
+    pub requested_at: Option<DateTime<Utc>>,
+}
+
+/// Apply `action` by `email` to `approval`, whose files currently hash to
+/// `content_hash`. `self_review` lets the requester decide their own review.
+fn transition(
+    approval: &mut ProfileApproval,
+    action: ApprovalAction,
+    email: &str,
+    comment: Option<String>,
+    content_hash: &str,
+    self_review: bool,
+) -> Result<()> {
+    let from = approval.state;
+    match (action, from) {
+        (ApprovalAction::RequestReview, ApprovalState::Draft | ApprovalState::Approved) => {
+            approval.requested_by = Some(email.to_lowercase());
+        }
+        (ApprovalAction::Approve | ApprovalAction::Reject, ApprovalState::InReview) => {
+            if !self_review
+                && approval
+                    .requested_by
+                    .as_deref()
+                    .is_some_and(|requester| requester.eq_ignore_ascii_case(email))
+            {
+                bail!("A review must be decided by someone other than its requester");
+            }
+            if action == ApprovalAction::Reject && comment.is_none() {
+                bail!("Say what needs to change when rejecting");
+            }
+            approval.requested_by = None;
+        }
+        _ => bail!(
+            "A {} profile cannot move to {}",
+            from.as_str(),
+            action.target().as_str()
+        ),
+    }
+    approval.state = action.target();
+    approval.approved_hash = (approval.state == ApprovalState::Approved)
+        .then(|| content_hash.to_string());
+    approval.history.push(ApprovalEvent {
+        from,
+        to: approval.state,
+        by: email.to_lowercase(),
+        comment,
+        at: Utc::now(),
+    });
+    if approval.history.len() > HISTORY_LIMIT {
+        let excess = approval.history.len() - HISTORY_LIMIT;
+        approval.history.drain(..excess);
+    }
+    Ok(())
+}
+
+/// Hash of the profile's files, hidden ones (state such as this module's)
+/// excluded.
+pub fn content_hash(profile_dir: &Path) -> String {
+    let mut files = Vec::new();
+    let mut pending = vec![profile_dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if entry.file_name().to_string_lossy().starts_with('.') {
+                continue;
+            }
+            if path.is_dir() {
+                pending.push(path);
+            } else {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    let mut hasher = Sha256::new();
+    for file in files {
+        let relative = file.strip_prefix(profile_dir).unwrap_or(&file);
+        hasher.update(relative.to_string_lossy().as_bytes());
+        hasher.update([0]);
+        hasher.update(std::fs::read(&file).unwrap_or_default());
+        hasher.update([0]);
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+/// The profile's approval; an approved profile that changed since reads as
+/// a draft.
+pub fn load(profile_dir: &Path) -> ProfileApproval {
+    let mut approval: ProfileApproval = std::fs::read_to_string(profile_dir.join(PROFILE_FILE))
+        .ok()
+        .and_then(|raw| serde_json::from_str(&raw).ok())
+        .unwrap_or_default();
+    if approval.state == ApprovalState::Approved
+        && approval.approved_hash.as_deref() != Some(content_hash(profile_dir).as_str())
+    {
+        approval.state = ApprovalState::Draft;
+        approval.approved_hash = None;
+    }
+    approval
+}
+
+pub fn is_approved(profile_dir: &Path) -> bool {
+    load(profile_dir).state == ApprovalState::Approved
+}
+
+/// The profiles of the user folder `user_dir` that are in review.
+pub async fn in_review(user_dir: &Path) -> Vec<PendingReview> {
+    let profiles = FsOps::list_profiles(user_dir).await.unwrap_or_default();
+    profiles
+        .into_iter()
+        .filter_map(|profile| {
+            let approval = load(&user_dir.join(&profile));
+            if approval.state != ApprovalState::InReview {
+                return None;
+            }
+            Some(PendingReview {
+                profile,
+                requested_by: approval.requested_by.unwrap_or_default(),
+                requested_at: approval.history.last().map(|event| event.at),
+            })
+        })
+        .collect()
+}
+
+/// Apply `action` to the profile and save it; see [`transition`] for
+/// `self_review`.
+pub async fn apply(
+    profile_dir: &Path,
+    action: ApprovalAction,
+    email: &str,
+    comment: Option<String>,
+    self_review: bool,
+) -> Result<ProfileApproval> {
+    let _save = crate::core::profile_lock::write(profile_dir).await;
+    let mut approval = load(profile_dir);
+    let comment = comment
+        .map(|c| c.trim().to_string())
+        .filter(|c| !c.is_empty());
+    transition(
+        &mut approval,
+        action,
+        email,
+        comment,
+        &content_hash(profile_dir),
+        self_review,
+    )?;
+    FsOps::write_file_safe(
+        &profile_dir.join(PROFILE_FILE),
+        &serde_json::to_string_pretty(&approval)?,
+    )
+    .await?;
+    Ok(approval)
+}
+
+/// Approval policy of the tenant `tenant_id`, falling back to the server
+/// default.
+pub async fn policy_for(pool: &SqlitePool, tenant_id: i64) -> ApprovalPolicy {
+    let stored: Option<String> =
+        sqlx::query_scalar("SELECT policy FROM approval_policies WHERE tenant_id = ?")
+            .bind(tenant_id)
+            .fetch_optional(pool)
+            .await
+            .ok()
+            .flatten();
+    stored
+        .as_deref()
+        .and_then(ApprovalPolicy::parse)
+        .unwrap_or_default()
+}
+
+pub async fn set_policy(pool: &SqlitePool, tenant_id: i64, policy: ApprovalPolicy) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO approval_policies (tenant_id, policy, updated_at) VALUES (?, ?, ?) \
+         ON CONFLICT(tenant_id) DO UPDATE SET policy = excluded.policy, \
+         updated_at = excluded.updated_at",
+    )
+    .bind(tenant_id)
+    .bind(policy.as_str())
+    .bind(Utc::now())
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reviews_move_through_the_states() {
+        let mut approval = ProfileApproval::default();
+        let approve = |a: &mut ProfileApproval, by: &str| {
+            transition(a, ApprovalAction::Approve, by, None, "abc", false)
+        };
+        let request = |a: &mut ProfileApproval| {
+            transition(a, ApprovalAction::RequestReview, "dev@acme.com", None, "abc", false)
+        };
+
+        assert!(approve(&mut approval, "lead@acme.com").is_err());
+        request(&mut approval).unwrap();
+        assert_eq!(approval.state, ApprovalState::InReview);
+        assert!(approve(&mut approval, "Dev@acme.com").is_err());
+        let reject = transition(
+            &mut approval,
+            ApprovalAction::Reject,
+            "lead@acme.com",
+            None,
+            "abc",
+            false,
+        );
+        assert!(reject.is_err());
+
+        approve(&mut approval, "lead@acme.com").unwrap();
+        assert_eq!(approval.state, ApprovalState::Approved);
+        assert_eq!(approval.approved_hash.as_deref(), Some("abc"));
+        assert_eq!(approval.history.len(), 2);
+
+        // The owner of an individual account decides their own review.
+        let mut solo = ProfileApproval::default();
+        request(&mut solo).unwrap();
+        transition(&mut solo, ApprovalAction::Approve, "dev@acme.com", None, "abc", true).unwrap();
+        assert_eq!(solo.state, ApprovalState::Approved);
+
+        let tmp = tempfile::tempdir().unwrap();
+        std::fs::write(tmp.path().join("cv_params.toml"), "name = \"John\"").unwrap();
+        let before = content_hash(tmp.path());
+        std::fs::write(tmp.path().join(PROFILE_FILE), "{}").unwrap();
+        assert_eq!(content_hash(tmp.path()), before);
+        std::fs::write(tmp.path().join("cv_params.toml"), "name = \"Jon\"").unwrap();
+        assert_ne!(content_hash(tmp.path()), before);
+    }
+}
@@ -0,0 +1,180 @@
+// src/core/profile_comments.rs
+//! Reviewer comments on a profile's files.
+//!
+//! A comment points at a file of the profile (path relative to the profile
+//! directory) and optionally a line range, and stays open until someone
+//! resolves it. Comments are stored per owning account (see [`crate::core::owner`])
+//! and profile directory name; renaming a profile carries them over and deleting it removes them.
+
+use anyhow::{bail, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+
+const MAX_BODY_CHARS: usize = 4000;
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct ProfileComment {
+    pub id: i64,
+    pub profile: String,
+    pub path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line_start: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line_end: Option<i64>,
+    pub author: String,
+    pub body: String,
+    pub created_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resolved_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resolved_by: Option<String>,
+}
+
+/// A comment to add, as posted by the client.
+#[derive(Debug, Clone, Deserialize)]
+pub struct NewComment {
+    /// File the comment is about, relative to the profile directory.
+    pub path: String,
+    pub line_start: Option<i64>,
+    pub line_end: Option<i64>,
+    pub body: String,
+}
+
+/// A line range is 1-based and inclusive; a lone `end` is rejected, a lone
+/// `start` means that single line.
+fn validate_lines(start: Option<i64>, end: Option<i64>) -> Result<(Option<i64>, Option<i64>)> {
+    match (start, end) {
+        (None, None) => Ok((None, None)),
+        (None, Some(_)) => bail!("line_end needs a line_start"),
+        (Some(start), _) if start < 1 => bail!("Lines start at 1"),
+        (Some(start), None) => Ok((Some(start), Some(start))),
+        (Some(start), Some(end)) if end < start => bail!("line_end is before line_start"),
+        (Some(start), Some(end)) => Ok((Some(start), Some(end))),
+    }
+}
+
+pub async fn create(
+    pool: &SqlitePool,
+    owner: &str,
+    profile: &str,
+    author: &str,
+    comment: &NewComment,
+) -> Result<ProfileComment> {
+    let path = comment.path.trim().trim_start_matches("./");
+    let inside = std::path::Path::new(path)
+        .components()
+        .all(|c| matches!(c, std::path::Component::Normal(_)));
+    if path.is_empty() || !inside {
+        bail!("'{}' is not a path inside the profile", comment.path);
+    }
+    let body = comment.body.trim();
+    if body.is_empty() {
+        bail!("The comment is empty");
+    }
+    if body.chars().count() > MAX_BODY_CHARS {
+        bail!("Comments are limited to {} characters", MAX_BODY_CHARS);
+    }
+    let (line_start, line_end) = validate_lines(comment.line_start, comment.line_end)?;
+    let comment = sqlx::query_as::<_, ProfileComment>(
+        "INSERT INTO profile_comments \
+         (owner, profile, path, line_start, line_end, author, body, created_at) \
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?) \
+         RETURNING id, profile, path, line_start, line_end, author, body, created_at, \
+         resolved_at, resolved_by",
+    )
+    .bind(owner)
+    .bind(profile)
+    .bind(path)
+    .bind(line_start)
+    .bind(line_end)
+    .bind(author)
+    .bind(body)
+    .bind(Utc::now())
+    .fetch_one(pool)
+    .await?;
+    Ok(comment)
+}
+
+/// Comments on the profile, or one of its files, oldest first.
+pub async fn list(
+    pool: &SqlitePool,
+    owner: &str,
+    profile: &str,
+    path: Option<&str>,
+    include_resolved: bool,
+) -> Result<Vec<ProfileComment>> {
+    let comments = sqlx::query_as::<_, ProfileComment>(
+        "SELECT id, profile, path, line_start, line_end, author, body, created_at, \
+         resolved_at, resolved_by FROM profile_comments \
+         WHERE owner = ? AND profile = ? AND (? IS NULL OR path = ?) \
+         AND (? OR resolved_at IS NULL) ORDER BY created_at, id",
+    )
+    .bind(owner)
+    .bind(profile)
+    .bind(path)
+    .bind(path)
+    .bind(include_resolved)
+    .fetch_all(pool)
+    .await?;
+    Ok(comments)
+}
+
+/// Mark a comment resolved; `None` when it doesn't exist.
+pub async fn resolve(
+    pool: &SqlitePool,
+    owner: &str,
+    profile: &str,
+    id: i64,
+    by: &str,
+) -> Result<Option<ProfileComment>> {
+    let comment = sqlx::query_as::<_, ProfileComment>(
+        "UPDATE profile_comments SET resolved_at = COALESCE(resolved_at, ?), \
+         resolved_by = COALESCE(resolved_by, ?) \
+         WHERE id = ? AND owner = ? AND profile = ? \
+         RETURNING id, profile, path, line_start, line_end, author, body, created_at, \
+         resolved_at, resolved_by",
+    )
+    .bind(Utc::now())
+    .bind(by)
+    .bind(id)
+    .bind(owner)
+    .bind(profile)
+    .fetch_optional(pool)
+    .await?;
+    Ok(comment)
+}
+
+pub async fn rename_profile(pool: &SqlitePool, owner: &str, from: &str, to: &str) -> Result<()> {
+    sqlx::query("UPDATE profile_comments SET profile = ? WHERE owner = ? AND profile = ?")
+        .bind(to)
+        .bind(owner)
+        .bind(from)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+pub async fn delete_profile(pool: &SqlitePool, owner: &str, profile: &str) -> Result<()> {
+    sqlx::query("DELETE FROM profile_comments WHERE owner = ? AND profile = ?")
+        .bind(owner)
+        .bind(profile)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn line_ranges_are_validated() {
+        assert_eq!(validate_lines(None, None).unwrap(), (None, None));
+        assert_eq!(validate_lines(Some(4), None).unwrap(), (Some(4), Some(4)));
+        assert_eq!(validate_lines(Some(4), Some(9)).unwrap(), (Some(4), Some(9)));
+        assert!(validate_lines(None, Some(9)).is_err());
+        assert!(validate_lines(Some(0), None).is_err());
+        assert!(validate_lines(Some(9), Some(4)).is_err());
+    }
+}
@@ -0,0 +1,315 @@
+// src/core/profile_integrity.rs
+//! Integrity check of a person directory.
+//!
+//! A broken person — missing files, a `cv_params.toml` that no longer parses,
+//! experiences that don't compile, a corrupt picture — otherwise only shows up
+//! as a failed generation. [`check`] runs four checks and returns a report the
+//! dashboard keys on by check id:
+//!
+//! * `required_files` — `cv_params.toml` and at least one experiences file
+//! * `toml`           — `cv_params.toml` parses
+//! * `typst`          — each language's experiences compile with the `default`
+//!   template, in a scratch dir (nothing is written to the output dir)
+//! * `images`         — `profile.png`/`profile.jpg`/`company_logo.png` decode
+//!
+//! A check that cannot run because an earlier one failed is `skipped`.
+
+use chrono::{DateTime, Utc};
+use futures::stream::{self, StreamExt};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+use crate::core::template_engine::SHARED_FILES;
+use crate::core::{profile_lock, template_health, FsOps, TemplateEngine};
+
+const COMPILE_TEMPLATE: &str = "default";
+const IMAGES: &[&str] = &["profile.png", "profile.jpg", "company_logo.png"];
+/// Persons checked in parallel by [`check_all`].
+const BATCH_CONCURRENCY: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Passed,
+    Failed,
+    Skipped,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct IntegrityIssue {
+    pub file: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct IntegrityCheck {
+    /// `required_files`, `toml`, `typst` or `images`.
+    pub id: &'static str,
+    pub status: CheckStatus,
+    pub issues: Vec<IntegrityIssue>,
+}
+
+impl IntegrityCheck {
+    fn from_issues(id: &'static str, issues: Vec<IntegrityIssue>) -> Self {
+        let status = if issues.is_empty() {
+            CheckStatus::Passed
+        } else {
+            CheckStatus::Failed
+        };
+        Self { id, status, issues }
+    }
+
+    fn skipped(id: &'static str) -> Self {
+        Self {
+            id,
+            status: CheckStatus::Skipped,
+            issues: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct IntegrityReport {
+    pub profile: String,
+    /// True when no check failed.
+    pub healthy: bool,
+    pub checks: Vec<IntegrityCheck>,
+    pub checked_at: DateTime<Utc>,
+}
+
+fn issue(file: &str, message: impl Into<String>) -> IntegrityIssue {
+    IntegrityIssue {
+        file: file.to_string(),
+        message: message.into(),
+    }
+}
+
+/// Experiences files and the language each one compiles with; the legacy
+/// `experiences.typ` is English.
+fn experiences_files(profile_dir: &Path) -> Vec<(String, String)> {
+    let mut files: Vec<(String, String)> = std::fs::read_dir(profile_dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter_map(|entry| {
+                    let name = entry.file_name().to_string_lossy().to_string();
+                    let lang = name.strip_prefix("experiences_")?.strip_suffix(".typ")?;
+                    (!lang.is_empty()).then(|| (name.clone(), lang.to_string()))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    if files.is_empty() && profile_dir.join("experiences.typ").is_file() {
+        files.push(("experiences.typ".to_string(), "en".to_string()));
+    }
+    files.sort();
+    files
+}
+
+async fn check_toml(profile_dir: &Path) -> Vec<IntegrityIssue> {
+    match FsOps::read_file_safe(&profile_dir.join("cv_params.toml")).await {
+        Ok(content) => match toml::from_str::<toml::Table>(&content) {
+            Ok(_) => Vec::new(),
+            Err(e) => vec![issue("cv_params.toml", e.to_string())],
+        },
+        Err(e) => vec![issue("cv_params.toml", format!("Cannot read file: {}", e))],
+    }
+}
+
+async fn check_images(profile_dir: &Path) -> Vec<IntegrityIssue> {
+    let mut issues = Vec::new();
+    for name in IMAGES {
+        let path = profile_dir.join(name);
+        if !path.exists() {
+            continue;
+        }
+        if let Err(e) = FsOps::validate_image(&path).await {
+            issues.push(issue(name, e.to_string()));
+            continue;
+        }
+        // The magic bytes can be fine on a truncated file; decode it fully.
+        let decoded = tokio::task::spawn_blocking(move || {
+            image::ImageReader::open(&path)
+                .and_then(|reader| reader.with_guessed_format())
+                .map_err(|e| e.to_string())?
+                .decode()
+                .map(|_| ())
+                .map_err(|e| e.to_string())
+        })
+        .await;
+        match decoded {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => issues.push(issue(name, format!("Image does not decode: {}", e))),
+            Err(e) => issues.push(issue(name, e.to_string())),
+        }
+    }
+    issues
+}
+
+/// Copy the person's sources next to the template in `workspace`.
+async fn prepare_workspace(
+    templates_dir: &Path,
+    profile_dir: &Path,
+    workspace: &Path,
+) -> anyhow::Result<()> {
+    let engine = TemplateEngine::new(templates_dir.to_path_buf())?;
+    engine
+        .prepare_template_workspace(COMPILE_TEMPLATE, workspace)
+        .await?;
+    for shared in SHARED_FILES {
+        let src = templates_dir.join(shared);
+        if src.exists() {
+            tokio::fs::copy(&src, workspace.join(shared)).await?;
+        }
+    }
+    let cv_params = FsOps::read_file_safe(&profile_dir.join("cv_params.toml")).await?;
+    tokio::fs::write(workspace.join("cv_params.toml"), cv_params).await?;
+    Ok(())
+}
+
+async fn check_typst(templates_dir: &Path, profile_dir: &Path) -> Vec<IntegrityIssue> {
+    let workspace =
+        std::env::temp_dir().join(format!("cvenom-profile-check-{}", uuid::Uuid::new_v4()));
+    let mut issues = Vec::new();
+    match prepare_workspace(templates_dir, profile_dir, &workspace).await {
+        Err(e) => issues.push(issue("cv_params.toml", format!("{:#}", e))),
+        Ok(()) => {
+            for (file, lang) in experiences_files(profile_dir) {
+                let copied = match FsOps::read_file_safe(&profile_dir.join(&file)).await {
+                    Ok(content) => tokio::fs::write(workspace.join("experiences.typ"), content)
+                        .await
+                        .map_err(anyhow::Error::from),
+                    Err(e) => Err(e),
+                };
+                let result = match copied {
+                    Ok(_) => template_health::compile_workspace(&workspace, &lang).await,
+                    Err(e) => Err(format!("Cannot read file: {}", e)),
+                };
+                if let Err(e) = result {
+                    issues.push(issue(&file, e));
+                }
+            }
+        }
+    }
+    let _ = tokio::fs::remove_dir_all(&workspace).await;
+    issues
+}
+
+/// Check the person in `profile_dir`. `compile` runs the Typst check, which
+/// takes a compilation per language.
+pub async fn check(
+    templates_dir: &Path,
+    profile_dir: &Path,
+    profile: &str,
+    compile: bool,
+) -> IntegrityReport {
+    let _snapshot = profile_lock::read(profile_dir).await;
+
+    let has_params = profile_dir.join("cv_params.toml").is_file();
+    let mut missing = Vec::new();
+    if !has_params {
+        missing.push(issue("cv_params.toml", "Missing"));
+    }
+    if experiences_files(profile_dir).is_empty() {
+        missing.push(issue("experiences_<lang>.typ", "No experiences file"));
+    }
+    let files = IntegrityCheck::from_issues("required_files", missing);
+
+    let toml = if has_params {
+        IntegrityCheck::from_issues("toml", check_toml(profile_dir).await)
+    } else {
+        IntegrityCheck::skipped("toml")
+    };
+    let typst =
+        if compile && files.status == CheckStatus::Passed && toml.status == CheckStatus::Passed {
+            IntegrityCheck::from_issues("typst", check_typst(templates_dir, profile_dir).await)
+        } else {
+            IntegrityCheck::skipped("typst")
+        };
+    let images = IntegrityCheck::from_issues("images", check_images(profile_dir).await);
+
+    let checks = vec![files, toml, typst, images];
+    IntegrityReport {
+        profile: profile.to_string(),
+        healthy: checks.iter().all(|c| c.status != CheckStatus::Failed),
+        checks,
+        checked_at: Utc::now(),
+    }
+}
+
+/// Directories of every person in `tenant_dir`, including ones too broken to
+/// count as profiles elsewhere (no `cv_params.toml`).
+async fn person_dirs(tenant_dir: &Path) -> anyhow::Result<Vec<(String, PathBuf)>> {
+    let mut dirs = Vec::new();
+    let mut entries = tokio::fs::read_dir(tenant_dir).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let path = entry.path();
+        let looks_like_person =
+            path.join("cv_params.toml").exists() || !experiences_files(&path).is_empty();
+        if path.is_dir() && !name.starts_with('.') && looks_like_person {
+            dirs.push((name, path));
+        }
+    }
+    dirs.sort();
+    Ok(dirs)
+}
+
+/// Checks of every person in a tenant.
+#[derive(Debug, Clone, Serialize)]
+pub struct TenantIntegrity {
+    pub checked: usize,
+    pub broken: usize,
+    /// Broken persons first.
+    pub profiles: Vec<IntegrityReport>,
+}
+
+/// [`check`] every person in `tenant_dir`, a few at a time.
+pub async fn check_all(
+    templates_dir: &Path,
+    tenant_dir: &Path,
+    compile: bool,
+) -> anyhow::Result<TenantIntegrity> {
+    let persons = person_dirs(tenant_dir).await?;
+    let mut profiles: Vec<IntegrityReport> = stream::iter(persons)
+        .map(|(name, dir)| async move { check(templates_dir, &dir, &name, compile).await })
+        .buffer_unordered(BATCH_CONCURRENCY)
+        .collect()
+        .await;
+    profiles.sort_by(|a, b| a.healthy.cmp(&b.healthy).then(a.profile.cmp(&b.profile)));
+    Ok(TenantIntegrity {
+        checked: profiles.len(),
+        broken: profiles.iter().filter(|p| !p.healthy).count(),
+        profiles,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status(report: &IntegrityReport, id: &str) -> CheckStatus {
+        report.checks.iter().find(|c| c.id == id).unwrap().status
+    }
+
+    #[tokio::test]
+    async fn reports_broken_toml_and_picture() {
+        let data = tempfile::tempdir().unwrap();
+        let profile_dir = data.path().join("acme/jane/john");
+        std::fs::create_dir_all(&profile_dir).unwrap();
+        std::fs::write(profile_dir.join("cv_params.toml"), "name = ").unwrap();
+        std::fs::write(profile_dir.join("experiences_fr.typ"), "").unwrap();
+        std::fs::write(profile_dir.join("profile.png"), b"\x89PNG\r\n\x1a\nbroken").unwrap();
+
+        let report = check(data.path(), &profile_dir, "john", true).await;
+        assert!(!report.healthy);
+        assert_eq!(status(&report, "required_files"), CheckStatus::Passed);
+        assert_eq!(status(&report, "toml"), CheckStatus::Failed);
+        assert_eq!(status(&report, "typst"), CheckStatus::Skipped);
+        assert_eq!(status(&report, "images"), CheckStatus::Failed);
+
+        std::fs::remove_file(profile_dir.join("experiences_fr.typ")).unwrap();
+        let report = check(data.path(), &profile_dir, "john", false).await;
+        assert_eq!(status(&report, "required_files"), CheckStatus::Failed);
+    }
+}
@@ -0,0 +1,82 @@
+//! Per-person read/write locks over the profile files.
+//!
+//! A generation copies `cv_params.toml`, the `experiences_*.typ` files and the
+//! picture into its workspace; a save rewrites some of them. Without
+//! coordination a PDF can be compiled from a half-written TOML, or from the
+//! new TOML next to the old experiences. Generation holds [`read`] only while
+//! it takes that snapshot — compilation then runs on the copy, so a save
+//! arriving mid-compile waits for the copy, not for Typst. Saves hold
+//! [`write`] across every file they touch, including read-modify-write merges.
+//!
+//! Locks are not reentrant: take one per operation, never nest them.
+//! Individual files are additionally written atomically (see
+//! [`crate::core::FsOps::write_file_safe`]), so readers that don't lock still
+//! never see a truncated file.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock, Mutex};
+use tokio::sync::{OwnedRwLockReadGuard, OwnedRwLockWriteGuard, RwLock};
+
+type LockMap = HashMap<PathBuf, Arc<RwLock<()>>>;
+
+static LOCKS: LazyLock<Mutex<LockMap>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// `<tenant>/<user>/<person>` — the generator sees the profile dir as an
+/// absolute path while handlers may hold it relative to the data dir, so the
+/// full path can't be the key. There is only one data dir per server.
+fn key(profile_dir: &Path) -> PathBuf {
+    let mut tail: Vec<_> = profile_dir.components().rev().take(3).collect();
+    tail.reverse();
+    tail.into_iter().collect()
+}
+
+fn lock_for(profile_dir: &Path) -> Arc<RwLock<()>> {
+    let mut map = LOCKS
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    // Drop entries nobody holds or waits on so the map doesn't grow forever.
+    map.retain(|_, l| Arc::strong_count(l) > 1);
+    map.entry(key(profile_dir))
+        .or_insert_with(|| Arc::new(RwLock::new(())))
+        .clone()
+}
+
+/// Shared lock for taking a consistent snapshot of `profile_dir`.
+pub async fn read(profile_dir: &Path) -> OwnedRwLockReadGuard<()> {
+    lock_for(profile_dir).read_owned().await
+}
+
+/// Exclusive lock for saving files of `profile_dir`.
+pub async fn write(profile_dir: &Path) -> OwnedRwLockWriteGuard<()> {
+    lock_for(profile_dir).write_owned().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn save_waits_for_snapshot() {
+        let dir = PathBuf::from("/tmp/cvenom-profile-lock-test/acme/jane-acme-com/jane_doe");
+        let snapshot = read(&dir).await;
+
+        let blocked = tokio::time::timeout(Duration::from_millis(20), write(&dir)).await;
+        assert!(blocked.is_err());
+
+        // Other persons are unaffected.
+        let other = PathBuf::from("/tmp/cvenom-profile-lock-test/acme/jane-acme-com/john_doe");
+        let _other = write(&other).await;
+
+        // Same person seen through a relative data dir.
+        let relative = PathBuf::from("data/acme/jane-acme-com/jane_doe");
+        let blocked = tokio::time::timeout(Duration::from_millis(20), write(&relative)).await;
+        assert!(blocked.is_err());
+
+        drop(snapshot);
+        let _save = tokio::time::timeout(Duration::from_millis(200), write(&dir))
+            .await
+            .expect("write lock after snapshot is released");
+    }
+}
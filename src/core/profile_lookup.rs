@@ -0,0 +1,106 @@
+// src/core/profile_lookup.rs
+//! Accent- and case-insensitive resolution of profile names.
+//!
+//! Profile directories keep whatever accents they were created with, so a
+//! request for `elodie` must still find `élodie` (and the other way round).
+//! The tenant directory holds `.profile_names.json`, mapping each folded name
+//! ([`fold_name`]) to the directories that fold to it. A name that is not a
+//! directory as given is looked up there; the index is rebuilt whenever it
+//! misses or points at a directory that is gone, so profiles created or
+//! renamed by any path are picked up. Two directories folding to the same
+//! name are ambiguous and only match exactly.
+
+use anyhow::Result;
+use graflog::app_log;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::core::FsOps;
+use crate::utils::{fold_name, normalize_profile_name};
+
+pub const INDEX_FILE: &str = ".profile_names.json";
+
+type NameIndex = BTreeMap<String, Vec<String>>;
+
+fn build(profiles: Vec<String>) -> NameIndex {
+    let mut index = NameIndex::new();
+    for profile in profiles {
+        index.entry(fold_name(&profile)).or_default().push(profile);
+    }
+    index
+}
+
+/// The single directory `folded` stands for, if it still exists.
+fn lookup(index: &NameIndex, tenant_dir: &Path, folded: &str) -> Option<String> {
+    match index.get(folded).map(Vec::as_slice) {
+        Some([profile]) if tenant_dir.join(profile).is_dir() => Some(profile.clone()),
+        _ => None,
+    }
+}
+
+fn load(tenant_dir: &Path) -> NameIndex {
+    std::fs::read_to_string(tenant_dir.join(INDEX_FILE))
+        .ok()
+        .and_then(|raw| serde_json::from_str(&raw).ok())
+        .unwrap_or_default()
+}
+
+/// Rebuild the tenant's index from its profile directories.
+pub async fn refresh(tenant_dir: &Path) -> Result<()> {
+    let index = build(FsOps::list_profiles(tenant_dir).await?);
+    FsOps::write_file_safe(
+        &tenant_dir.join(INDEX_FILE),
+        &serde_json::to_string_pretty(&index)?,
+    )
+    .await
+}
+
+/// Directory name of the profile `name` refers to in `tenant_dir`.
+pub async fn resolve(tenant_dir: &Path, name: &str) -> Option<String> {
+    let normalized = normalize_profile_name(name);
+    if normalized.is_empty() {
+        return None;
+    }
+    if tenant_dir.join(&normalized).is_dir() {
+        return Some(normalized);
+    }
+    let folded = fold_name(&normalized);
+    if let Some(profile) = lookup(&load(tenant_dir), tenant_dir, &folded) {
+        return Some(profile);
+    }
+    if let Err(e) = refresh(tenant_dir).await {
+        app_log!(warn, "Failed to index profiles of {}: {}", tenant_dir.display(), e);
+        return None;
+    }
+    lookup(&load(tenant_dir), tenant_dir, &folded)
+}
+
+/// An existing profile other than `except` that `name` would be confused
+/// with.
+pub async fn conflict(tenant_dir: &Path, name: &str, except: &str) -> Option<String> {
+    let folded = fold_name(&normalize_profile_name(name));
+    FsOps::list_profiles(tenant_dir)
+        .await
+        .ok()?
+        .into_iter()
+        .find(|profile| profile != except && fold_name(profile) == folded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn folded_names_resolve_only_when_unambiguous() {
+        let tmp = tempfile::tempdir().unwrap();
+        for dir in ["élodie", "jose", "josé"] {
+            std::fs::create_dir(tmp.path().join(dir)).unwrap();
+        }
+        let index = build(vec!["élodie".into(), "jose".into(), "josé".into()]);
+        assert_eq!(lookup(&index, tmp.path(), "elodie").as_deref(), Some("élodie"));
+        assert_eq!(lookup(&index, tmp.path(), "jose"), None);
+
+        std::fs::remove_dir(tmp.path().join("élodie")).unwrap();
+        assert_eq!(lookup(&index, tmp.path(), "elodie"), None);
+    }
+}
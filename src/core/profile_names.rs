@@ -0,0 +1,213 @@
+// src/core/profile_names.rs
+//! Bulk cleanup of profile directory names.
+//!
+//! Older profiles can live in directories with spaces, uppercase or special
+//! characters. [`plan`] proposes the [`normalize_profile_name`] a new profile
+//! would get, suffixing `_2`, `_3`… when it would collide with another entry,
+//! also accent- and case-insensitively, and [`apply`] performs the renames
+//! all-or-nothing: if one fails, the ones already done are rolled back. Each
+//! rename then goes through [`carry_over`], as a rename from the profile
+//! handlers does.
+
+use anyhow::{bail, Context, Result};
+use graflog::app_log;
+use serde::Serialize;
+use sqlx::SqlitePool;
+use std::collections::HashSet;
+use std::path::Path;
+
+use crate::core::{
+    person_tags, profile_comments, profile_lock, profile_lookup, profile_watch, FsOps,
+};
+use crate::utils::{fold_name, normalize_profile_name};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ProfileRename {
+    pub from: String,
+    pub to: String,
+}
+
+/// Mapping report returned by the admin endpoint and printed by the CLI.
+#[derive(Debug, Clone, Serialize)]
+pub struct RenameReport {
+    pub renames: Vec<ProfileRename>,
+    /// Profiles whose name is already clean.
+    pub unchanged: usize,
+    /// False for a dry run.
+    pub applied: bool,
+}
+
+/// Normalized names for `profiles`, avoiding every name in `taken` other
+/// than the profile's own, accents and case aside.
+fn propose(profiles: &[String], taken: &mut HashSet<String>) -> Vec<ProfileRename> {
+    let mut renames = Vec::new();
+    for from in profiles {
+        let base = normalize_profile_name(from);
+        if base == *from {
+            continue;
+        }
+        let clashes =
+            |to: &str| taken.iter().any(|t| t != from && fold_name(t) == fold_name(to));
+        let mut to = base.clone();
+        let mut n = 2;
+        while clashes(&to) {
+            to = format!("{}_{}", base, n);
+            n += 1;
+        }
+        taken.insert(to.clone());
+        renames.push(ProfileRename {
+            from: from.clone(),
+            to,
+        });
+    }
+    renames
+}
+
+/// Proposed renames for the profiles in `tenant_dir`; nothing is changed.
+pub async fn plan(tenant_dir: &Path) -> Result<RenameReport> {
+    let profiles = FsOps::list_profiles(tenant_dir).await?;
+    let mut taken = HashSet::new();
+    let mut entries = tokio::fs::read_dir(tenant_dir)
+        .await
+        .with_context(|| format!("Failed to read {}", tenant_dir.display()))?;
+    while let Some(entry) = entries.next_entry().await? {
+        taken.insert(entry.file_name().to_string_lossy().to_string());
+    }
+
+    let renames = propose(&profiles, &mut taken);
+    Ok(RenameReport {
+        unchanged: profiles.len() - renames.len(),
+        renames,
+        applied: false,
+    })
+}
+
+async fn rename_locked(tenant_dir: &Path, from: &str, to: &str) -> Result<()> {
+    let source = tenant_dir.join(from);
+    let _lock = profile_lock::write(&source).await;
+    let target = tenant_dir.join(to);
+    if target.exists() {
+        bail!("'{}' already exists", to);
+    }
+    tokio::fs::rename(&source, &target)
+        .await
+        .with_context(|| format!("Failed to rename '{}' to '{}'", from, to))
+}
+
+/// Move what is keyed by profile `from`'s name to `to`, once its directory
+/// in `tenant_dir` was renamed: the comments, watches and tags of account
+/// `owner`, and the name index. Generated PDFs under `output_dir` carry the
+/// old name, so they are dropped rather than left as orphans. Failures are
+/// logged; the rename itself stands.
+pub async fn carry_over(
+    pool: Option<&SqlitePool>,
+    owner: &str,
+    tenant_dir: &Path,
+    output_dir: &Path,
+    from: &str,
+    to: &str,
+) {
+    if let Some(pool) = pool {
+        if let Err(e) = profile_comments::rename_profile(pool, owner, from, to).await {
+            app_log!(warn, "Failed to move comments of {}: {}", from, e);
+        }
+        if let Err(e) = profile_watch::rename_profile(pool, owner, from, to).await {
+            app_log!(warn, "Failed to move watches of {}: {}", from, e);
+        }
+        if let Err(e) = person_tags::rename_profile(pool, owner, from, to).await {
+            app_log!(warn, "Failed to move tags of {}: {}", from, e);
+        }
+    }
+    if let Err(e) = profile_lookup::refresh(tenant_dir).await {
+        app_log!(warn, "Failed to index profiles after renaming {}: {}", from, e);
+    }
+
+    let old_output = output_dir.join(from);
+    if old_output.exists() {
+        if let Err(e) = FsOps::remove_dir_all(&old_output).await {
+            app_log!(warn, "Failed to delete generated files of {}: {}", from, e);
+        }
+    }
+
+    // Touch profile.toml so its mtime reflects the rename — the frontend
+    // sorts profiles by most-recently-modified, and a directory rename alone
+    // does not update any file's mtime on Linux.
+    let profile_toml = tenant_dir.join(to).join("profile.toml");
+    if profile_toml.exists() {
+        if let Ok(content) = tokio::fs::read(&profile_toml).await {
+            let _ = tokio::fs::write(&profile_toml, content).await;
+        }
+    }
+}
+
+/// Apply `report`'s renames in `tenant_dir`, the folder of account `owner`,
+/// then [`carry_over`] each once every rename succeeded.
+pub async fn apply(
+    pool: Option<&SqlitePool>,
+    owner: &str,
+    tenant_dir: &Path,
+    output_dir: &Path,
+    mut report: RenameReport,
+) -> Result<RenameReport> {
+    for (done, rename) in report.renames.iter().enumerate() {
+        if let Err(e) = rename_locked(tenant_dir, &rename.from, &rename.to).await {
+            for undo in report.renames[..done].iter().rev() {
+                if let Err(undo_err) = rename_locked(tenant_dir, &undo.to, &undo.from).await {
+                    app_log!(
+                        error,
+                        "Rollback of '{}' -> '{}' failed: {}",
+                        undo.from,
+                        undo.to,
+                        undo_err
+                    );
+                }
+            }
+            return Err(e.context("Renames rolled back"));
+        }
+    }
+
+    for rename in &report.renames {
+        carry_over(pool, owner, tenant_dir, output_dir, &rename.from, &rename.to).await;
+    }
+
+    app_log!(
+        info,
+        "Sanitized {} profile names in {}",
+        report.renames.len(),
+        tenant_dir.display()
+    );
+    report.applied = true;
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn proposals_avoid_collisions() {
+        let profiles: Vec<String> = [
+            "John Doe", "jane", "john_doe", "John-Doe", "JOHN DOE", "Élodie", "elodie",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+        let mut taken: HashSet<String> = profiles.iter().cloned().collect();
+        taken.insert("brands".to_string());
+
+        let renames = propose(&profiles, &mut taken);
+        let mapping: Vec<(&str, &str)> = renames
+            .iter()
+            .map(|r| (r.from.as_str(), r.to.as_str()))
+            .collect();
+        assert_eq!(
+            mapping,
+            vec![
+                ("John Doe", "john_doe_2"),
+                ("John-Doe", "john-doe"),
+                ("JOHN DOE", "john_doe_3"),
+                ("Élodie", "élodie_2"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,245 @@
+// src/core/profile_schema.rs
+//! Upgrade of legacy `cv_params.toml` layouts to the current schema.
+//!
+//! The current schema keeps the personal fields (`name`, `title`, `email`,
+//! `phonenumber`, `address`, `summary`…) as top-level keys, which is what the
+//! templates read as `details.name`, and the sections as lowercase tables
+//! (`[skills]`, `[languages]`, `[[education]]`…). Older profiles were written
+//! with the personal fields in a `[personal]`, `[Personal]` or
+//! `[personal_info]` table, with `job_title` instead of `title`, or with
+//! capitalised section names.
+//!
+//! [`upgrade`] rewrites such a document with `toml_edit`, keeping comments and
+//! the other keys: a legacy table's fields move to the top level (a filled-in
+//! value in the table wins over the top-level one, as the form editor always
+//! read it that way), `job_title` becomes `title` and sections are renamed to
+//! lowercase. Readers go through [`canonicalize`], so they only know the
+//! current layout. [`migrate`] rewrites a profile's file on disk, keeping the
+//! original as `cv_params.toml.bak`; [`plan`] and [`apply`] do it for every
+//! profile of a user. With `PROFILE_SCHEMA_AUTO_UPGRADE=1` a profile is also
+//! migrated the first time its CV data is read.
+
+use anyhow::{Context, Result};
+use graflog::app_log;
+use serde::Serialize;
+use std::borrow::Cow;
+use std::path::Path;
+use toml_edit::{DocumentMut, Item};
+
+use crate::core::{profile_lock, FsOps};
+
+pub const PARAMS_FILE: &str = "cv_params.toml";
+/// The original of a migrated `cv_params.toml`.
+pub const BACKUP_FILE: &str = "cv_params.toml.bak";
+
+/// Tables older profiles kept the personal fields in.
+const LEGACY_PERSONAL_TABLES: [&str; 2] = ["personal", "personal_info"];
+/// Sections of the current schema, by their canonical (lowercase) name.
+const SECTIONS: [&str; 6] = ["skills", "languages", "education", "links", "styling", "projects"];
+
+/// Whether profiles are migrated when their CV data is read.
+pub fn auto_upgrade() -> bool {
+    std::env::var("PROFILE_SCHEMA_AUTO_UPGRADE")
+        .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+        .unwrap_or(false)
+}
+
+fn is_blank(item: &Item) -> bool {
+    item.as_str().is_some_and(|s| s.trim().is_empty())
+}
+
+/// Set `key` at the top level, keeping the comments above an existing one.
+fn set(doc: &mut DocumentMut, key: &str, item: Item) {
+    match doc.get_mut(key) {
+        Some(existing) => *existing = item,
+        None => {
+            doc.insert(key, item);
+        }
+    }
+}
+
+/// Bring `doc` to the current schema; false when it already was.
+pub fn upgrade(doc: &mut DocumentMut) -> bool {
+    let mut changed = false;
+
+    let legacy: Vec<String> = doc
+        .iter()
+        .map(|(key, _)| key.to_string())
+        .filter(|key| LEGACY_PERSONAL_TABLES.contains(&key.to_lowercase().as_str()))
+        .collect();
+    for name in legacy {
+        let Some(table) = doc.get(&name).and_then(Item::as_table_like) else {
+            continue;
+        };
+        let fields: Vec<(String, Item)> = table
+            .iter()
+            .map(|(key, item)| (key.to_string(), item.clone()))
+            .collect();
+        for (key, item) in fields {
+            let keep_top_level = is_blank(&item) && doc.contains_key(&key);
+            if !keep_top_level {
+                set(doc, &key, item);
+            }
+        }
+        doc.remove(&name);
+        changed = true;
+    }
+
+    if let Some(job_title) = doc.remove("job_title") {
+        if doc.get("title").is_none_or(is_blank) {
+            set(doc, "title", job_title);
+        }
+        changed = true;
+    }
+
+    let miscased: Vec<String> = doc
+        .iter()
+        .map(|(key, _)| key.to_string())
+        .filter(|key| {
+            let lower = key.to_lowercase();
+            *key != lower && SECTIONS.contains(&lower.as_str())
+        })
+        .collect();
+    for name in miscased {
+        let lower = name.to_lowercase();
+        if doc.contains_key(&lower) {
+            continue;
+        }
+        if let Some(item) = doc.remove(&name) {
+            doc.insert(&lower, item);
+            changed = true;
+        }
+    }
+
+    changed
+}
+
+/// `content` in the current schema. Invalid TOML is returned as-is.
+pub fn canonicalize(content: &str) -> Cow<'_, str> {
+    let Ok(mut doc) = content.parse::<DocumentMut>() else {
+        return Cow::Borrowed(content);
+    };
+    if upgrade(&mut doc) {
+        Cow::Owned(doc.to_string())
+    } else {
+        Cow::Borrowed(content)
+    }
+}
+
+/// Rewrite the `cv_params.toml` of `profile_dir` in the current schema,
+/// backing up the original first; false when there was nothing to upgrade.
+/// An existing backup is kept, so it always holds the oldest layout.
+pub async fn migrate(profile_dir: &Path) -> Result<bool> {
+    let path = profile_dir.join(PARAMS_FILE);
+    if !path.exists() {
+        return Ok(false);
+    }
+    let _lock = profile_lock::write(profile_dir).await;
+    let content = FsOps::read_file_safe(&path).await?;
+    let Cow::Owned(upgraded) = canonicalize(&content) else {
+        return Ok(false);
+    };
+
+    let backup = profile_dir.join(BACKUP_FILE);
+    if !backup.exists() {
+        FsOps::write_file_safe(&backup, &content)
+            .await
+            .with_context(|| format!("Failed to back up {}", path.display()))?;
+    }
+    FsOps::write_file_safe(&path, &upgraded)
+        .await
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    app_log!(info, "Upgraded {} to the current schema", path.display());
+    Ok(true)
+}
+
+/// [`migrate`] when `PROFILE_SCHEMA_AUTO_UPGRADE` is on; failures are logged
+/// and the profile is read as it is.
+pub async fn upgrade_on_read(profile_dir: &Path) {
+    if !auto_upgrade() {
+        return;
+    }
+    if let Err(e) = migrate(profile_dir).await {
+        app_log!(warn, "Schema upgrade of {} failed: {:#}", profile_dir.display(), e);
+    }
+}
+
+/// Report returned by the admin endpoint and printed by the CLI.
+#[derive(Debug, Clone, Serialize)]
+pub struct SchemaUpgradeReport {
+    /// Profiles whose `cv_params.toml` uses a legacy layout.
+    pub legacy: Vec<String>,
+    pub profiles_scanned: usize,
+    /// False for a dry run.
+    pub applied: bool,
+}
+
+/// Profiles of `tenant_dir` with a legacy `cv_params.toml`; nothing is changed.
+pub async fn plan(tenant_dir: &Path) -> Result<SchemaUpgradeReport> {
+    let profiles = FsOps::list_profiles(tenant_dir).await?;
+    let mut legacy = Vec::new();
+    for profile in &profiles {
+        let path = tenant_dir.join(profile).join(PARAMS_FILE);
+        let Ok(content) = FsOps::read_file_safe(&path).await else {
+            continue;
+        };
+        if matches!(canonicalize(&content), Cow::Owned(_)) {
+            legacy.push(profile.clone());
+        }
+    }
+    Ok(SchemaUpgradeReport {
+        legacy,
+        profiles_scanned: profiles.len(),
+        applied: false,
+    })
+}
+
+/// Migrate the profiles in `report`.
+pub async fn apply(
+    tenant_dir: &Path,
+    mut report: SchemaUpgradeReport,
+) -> Result<SchemaUpgradeReport> {
+    for profile in &report.legacy {
+        migrate(&tenant_dir.join(profile))
+            .await
+            .with_context(|| format!("Failed to upgrade {}", profile))?;
+    }
+    report.applied = true;
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn legacy_layouts_are_flattened() {
+        let legacy = r#"# Jane's CV
+email = ""
+
+[Personal]
+name = "Jane Doe"
+email = "jane@example.com"
+address = ""
+
+[personal_info]
+job_title = "Engineer"
+
+[Skills]
+technical = ["Rust"]
+"#;
+        let upgraded = canonicalize(legacy);
+        let table: toml::Table = toml::from_str(&upgraded).unwrap();
+        assert_eq!(table["name"].as_str(), Some("Jane Doe"));
+        assert_eq!(table["email"].as_str(), Some("jane@example.com"));
+        assert_eq!(table["title"].as_str(), Some("Engineer"));
+        assert_eq!(table["address"].as_str(), Some(""));
+        assert!(table.get("job_title").is_none());
+        assert!(table.get("Personal").is_none() && table.get("personal_info").is_none());
+        assert_eq!(table["skills"]["technical"][0].as_str(), Some("Rust"));
+        assert!(upgraded.starts_with("# Jane's CV"));
+
+        let current = "name = \"Jane\"\ntitle = \"Engineer\"\n\n[skills]\n";
+        assert!(matches!(canonicalize(current), Cow::Borrowed(_)));
+    }
+}
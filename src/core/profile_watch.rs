@@ -0,0 +1,360 @@
+// src/core/profile_watch.rs
+//! Watching collaborators' profiles for changes.
+//!
+//! A user of a tenant can watch any of its profiles and is told when one of
+//! its files is saved or a CV is generated from it. Changes made by the
+//! watcher themselves are not reported. Each watch notifies:
+//!
+//! - the live event stream (`/watches/events`), always;
+//! - by email, unless turned off, at most once per profile every
+//!   `WATCH_EMAIL_INTERVAL_MINS` (default 15) minutes;
+//! - a webhook, if one is set: the notice is POSTed as JSON, signed with the
+//!   watch's secret in `X-Cvenom-Signature: sha256=<hex hmac>`.
+//!
+//! Watches are stored per owning account (see [`crate::core::owner`]) and
+//! profile directory name; renaming a profile carries them over and deleting it removes them.
+
+use anyhow::{bail, Result};
+use chrono::{DateTime, Utc};
+use graflog::app_log;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast;
+
+use crate::core::database::TenantRepository;
+use crate::email::{send_email_with_prefs, EmailKind};
+use crate::utils::hmac_sha256_hex;
+
+pub const SIGNATURE_HEADER: &str = "X-Cvenom-Signature";
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+const EVENT_BUFFER: usize = 256;
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct ProfileWatch {
+    pub profile: String,
+    pub email: String,
+    pub notify_email: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub webhook_url: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// How a watch should notify, as posted by the client.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct WatchOptions {
+    /// Email on changes; defaults to on.
+    pub email: Option<bool>,
+    /// `https://` URL to POST notices to.
+    pub webhook_url: Option<String>,
+    /// Key the webhook payload is signed with; required with a webhook.
+    pub webhook_secret: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum WatchChange {
+    FileSaved { path: String },
+    CvGenerated { filename: String },
+}
+
+impl WatchChange {
+    fn describe(&self) -> String {
+        match self {
+            Self::FileSaved { path } => format!("saved {}", path),
+            Self::CvGenerated { filename } => format!("generated {}", filename),
+        }
+    }
+}
+
+/// What watchers are sent.
+#[derive(Debug, Clone, Serialize)]
+pub struct WatchNotice {
+    pub profile: String,
+    pub actor: String,
+    pub change: WatchChange,
+    pub at: DateTime<Utc>,
+    /// Watcher the notice is for; only used to route the live stream.
+    #[serde(skip)]
+    pub recipient: String,
+    #[serde(skip)]
+    pub owner: String,
+}
+
+static EVENTS: LazyLock<broadcast::Sender<WatchNotice>> =
+    LazyLock::new(|| broadcast::channel(EVENT_BUFFER).0);
+
+static LAST_EMAIL: LazyLock<Mutex<EmailThrottle>> =
+    LazyLock::new(|| Mutex::new(EmailThrottle::from_env()));
+
+/// When each watcher was last emailed about each profile.
+struct EmailThrottle {
+    interval: Duration,
+    sent: HashMap<(String, String, String), Instant>,
+}
+
+impl EmailThrottle {
+    fn from_env() -> Self {
+        let minutes = std::env::var("WATCH_EMAIL_INTERVAL_MINS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(15);
+        Self {
+            interval: Duration::from_secs(minutes * 60),
+            sent: HashMap::new(),
+        }
+    }
+
+    /// Whether an email for `key` may go out at `now`; records it if so.
+    fn allow(&mut self, key: (String, String, String), now: Instant) -> bool {
+        let interval = self.interval;
+        self.sent
+            .retain(|_, at| now.saturating_duration_since(*at) < interval);
+        if self.sent.contains_key(&key) {
+            return false;
+        }
+        self.sent.insert(key, now);
+        true
+    }
+}
+
+/// `sha256=<hex>` signature of a webhook body.
+fn signature(secret: &str, body: &[u8]) -> String {
+    format!("sha256={}", hmac_sha256_hex(secret.as_bytes(), body))
+}
+
+/// Receive every notice sent from now on; filter on `recipient`.
+pub fn subscribe() -> broadcast::Receiver<WatchNotice> {
+    EVENTS.subscribe()
+}
+
+/// Start watching `profile` as `email`, or change how an existing watch
+/// notifies.
+pub async fn watch(
+    pool: &SqlitePool,
+    owner: &str,
+    profile: &str,
+    email: &str,
+    options: &WatchOptions,
+) -> Result<ProfileWatch> {
+    let webhook_url = options
+        .webhook_url
+        .as_deref()
+        .map(str::trim)
+        .filter(|u| !u.is_empty());
+    let webhook_secret = options
+        .webhook_secret
+        .as_deref()
+        .map(str::trim)
+        .filter(|s| !s.is_empty());
+    if let Some(url) = webhook_url {
+        if !url.starts_with("https://") || reqwest::Url::parse(url).is_err() {
+            bail!("Webhook URLs must be valid https:// URLs");
+        }
+        if webhook_secret.is_none() {
+            bail!("A webhook needs a secret to sign its payloads");
+        }
+    }
+    let watch = sqlx::query_as::<_, ProfileWatch>(
+        "INSERT INTO profile_watches \
+         (owner, profile, email, notify_email, webhook_url, webhook_secret, created_at) \
+         VALUES (?, ?, ?, ?, ?, ?, ?) \
+         ON CONFLICT(owner, profile, email) DO UPDATE SET \
+         notify_email = excluded.notify_email, webhook_url = excluded.webhook_url, \
+         webhook_secret = excluded.webhook_secret \
+         RETURNING profile, email, notify_email, webhook_url, created_at",
+    )
+    .bind(owner)
+    .bind(profile)
+    .bind(email.to_lowercase())
+    .bind(options.email.unwrap_or(true))
+    .bind(webhook_url)
+    .bind(webhook_url.and(webhook_secret))
+    .bind(Utc::now())
+    .fetch_one(pool)
+    .await?;
+    Ok(watch)
+}
+
+/// Stop watching; `false` when `email` wasn't watching `profile`.
+pub async fn unwatch(pool: &SqlitePool, owner: &str, profile: &str, email: &str) -> Result<bool> {
+    let result =
+        sqlx::query("DELETE FROM profile_watches WHERE owner = ? AND profile = ? AND email = ?")
+            .bind(owner)
+            .bind(profile)
+            .bind(email.to_lowercase())
+            .execute(pool)
+            .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Profiles `email` watches, by name.
+pub async fn list_for(pool: &SqlitePool, owner: &str, email: &str) -> Result<Vec<ProfileWatch>> {
+    let watches = sqlx::query_as::<_, ProfileWatch>(
+        "SELECT profile, email, notify_email, webhook_url, created_at FROM profile_watches \
+         WHERE owner = ? AND email = ? ORDER BY profile",
+    )
+    .bind(owner)
+    .bind(email.to_lowercase())
+    .fetch_all(pool)
+    .await?;
+    Ok(watches)
+}
+
+#[derive(sqlx::FromRow)]
+struct Watcher {
+    email: String,
+    notify_email: bool,
+    webhook_url: Option<String>,
+    webhook_secret: Option<String>,
+}
+
+/// Tell everyone watching `profile`, except `actor`, about `change`. Returns
+/// at once; delivery happens in the background.
+pub fn notify(pool: &SqlitePool, owner: &str, profile: &str, actor: &str, change: WatchChange) {
+    let pool = pool.clone();
+    let owner = owner.to_string();
+    let profile = profile.to_string();
+    let actor = actor.to_lowercase();
+    tokio::spawn(async move {
+        let watchers = sqlx::query_as::<_, Watcher>(
+            "SELECT email, notify_email, webhook_url, webhook_secret FROM profile_watches \
+             WHERE owner = ? AND profile = ? AND email != ?",
+        )
+        .bind(&owner)
+        .bind(&profile)
+        .bind(&actor)
+        .fetch_all(&pool)
+        .await;
+        let watchers = match watchers {
+            Ok(watchers) => watchers,
+            Err(e) => {
+                app_log!(error, "Failed to load watchers of {}/{}: {}", owner, profile, e);
+                return;
+            }
+        };
+        for watcher in watchers {
+            let notice = WatchNotice {
+                profile: profile.clone(),
+                actor: actor.clone(),
+                change: change.clone(),
+                at: Utc::now(),
+                recipient: watcher.email.clone(),
+                owner: owner.clone(),
+            };
+            // No receivers is fine: nobody has the stream open.
+            let _ = EVENTS.send(notice.clone());
+            if watcher.notify_email {
+                email_watcher(&pool, &watcher.email, &notice).await;
+            }
+            if let (Some(url), Some(secret)) = (&watcher.webhook_url, &watcher.webhook_secret) {
+                post_webhook(url, secret, &notice).await;
+            }
+        }
+    });
+}
+
+async fn email_watcher(pool: &SqlitePool, email: &str, notice: &WatchNotice) {
+    let key = (
+        notice.owner.clone(),
+        notice.profile.clone(),
+        email.to_string(),
+    );
+    let allowed = LAST_EMAIL
+        .lock()
+        .map(|mut throttle| throttle.allow(key, Instant::now()))
+        .unwrap_or(false);
+    if !allowed {
+        return;
+    }
+    let account = TenantRepository::new(pool)
+        .find_by_email_or_domain(email)
+        .await
+        .ok()
+        .flatten();
+    let lang = account
+        .as_ref()
+        .and_then(|t| t.preferred_lang.clone())
+        .unwrap_or_else(|| "en".to_string());
+    send_email_with_prefs(
+        email,
+        EmailKind::WatchedProfileChanged {
+            profile: notice.profile.clone(),
+            actor: notice.actor.clone(),
+            change: notice.change.describe(),
+        },
+        &lang,
+        account.as_ref().and_then(|t| t.email_prefs.as_deref()),
+    );
+}
+
+async fn post_webhook(url: &str, secret: &str, notice: &WatchNotice) {
+    let body = match serde_json::to_vec(notice) {
+        Ok(body) => body,
+        Err(e) => {
+            app_log!(error, "Failed to encode watch notice: {}", e);
+            return;
+        }
+    };
+    let result = reqwest::Client::new()
+        .post(url)
+        .timeout(WEBHOOK_TIMEOUT)
+        .header("Content-Type", "application/json")
+        .header(SIGNATURE_HEADER, signature(secret, &body))
+        .body(body)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status());
+    if let Err(e) = result {
+        app_log!(
+            warn,
+            "Watch webhook for {} on {} failed: {}",
+            notice.recipient,
+            notice.profile,
+            e
+        );
+    }
+}
+
+pub async fn rename_profile(pool: &SqlitePool, owner: &str, from: &str, to: &str) -> Result<()> {
+    sqlx::query("UPDATE profile_watches SET profile = ? WHERE owner = ? AND profile = ?")
+        .bind(to)
+        .bind(owner)
+        .bind(from)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+pub async fn delete_profile(pool: &SqlitePool, owner: &str, profile: &str) -> Result<()> {
+    sqlx::query("DELETE FROM profile_watches WHERE owner = ? AND profile = ?")
+        .bind(owner)
+        .bind(profile)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn emails_are_throttled_per_watcher_and_profile() {
+        let mut throttle = EmailThrottle {
+            interval: Duration::from_secs(900),
+            sent: HashMap::new(),
+        };
+        let key = |profile: &str, email: &str| {
+            ("acme".to_string(), profile.to_string(), email.to_string())
+        };
+        let start = Instant::now();
+        assert!(throttle.allow(key("john", "lead@acme.com"), start));
+        assert!(!throttle.allow(key("john", "lead@acme.com"), start + Duration::from_secs(60)));
+        assert!(throttle.allow(key("jane", "lead@acme.com"), start));
+        assert!(throttle.allow(key("john", "dev@acme.com"), start));
+        assert!(throttle.allow(key("john", "lead@acme.com"), start + Duration::from_secs(901)));
+    }
+}
@@ -0,0 +1,147 @@
+// src/core/provisioning.rs
+//! First-login provisioning of a user's own profile.
+//!
+//! A tenant directory with no profiles gets one created from the templates,
+//! named after the email's local part, and marked as the user's own with a
+//! [`SELF_PROFILE_MARKER`]. The marker also drives `needs_onboarding`: it
+//! stays true until the user edits the profile's content files, so the
+//! frontend can keep routing to onboarding until then. Tenants that already
+//! had profiles before this existed are left alone.
+
+use anyhow::Result;
+use chrono::Utc;
+use graflog::app_log;
+use serde::Serialize;
+use std::path::Path;
+
+use crate::core::database::get_tenant_folder_path;
+use crate::core::{review_calendar, FsOps, TemplateEngine};
+use crate::utils::normalize_profile_name;
+
+/// Written into the profile directory of the user's own profile; holds the
+/// provisioning timestamp.
+pub const SELF_PROFILE_MARKER: &str = ".self_profile";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SelfProfile {
+    pub profile: String,
+    /// Created by this call.
+    pub created: bool,
+    /// Content files untouched since provisioning.
+    pub needs_onboarding: bool,
+}
+
+/// The profile in `tenant_dir` marked as the user's own, if any.
+pub async fn find_self_profile(tenant_dir: &Path) -> Result<Option<String>> {
+    for profile in FsOps::list_profiles(tenant_dir).await? {
+        if tenant_dir
+            .join(&profile)
+            .join(SELF_PROFILE_MARKER)
+            .is_file()
+        {
+            return Ok(Some(profile));
+        }
+    }
+    Ok(None)
+}
+
+/// Make `profile` the user's own profile, or unmark it (`own == false`).
+/// Only one profile is the user's own: marking one unmarks the others.
+pub async fn set_self_profile(tenant_dir: &Path, profile: &str, own: bool) -> Result<()> {
+    let marker = tenant_dir.join(profile).join(SELF_PROFILE_MARKER);
+    if !own {
+        if marker.is_file() {
+            tokio::fs::remove_file(&marker).await?;
+        }
+        return Ok(());
+    }
+    if let Some(previous) = find_self_profile(tenant_dir).await? {
+        if previous == profile {
+            return Ok(());
+        }
+        tokio::fs::remove_file(tenant_dir.join(&previous).join(SELF_PROFILE_MARKER)).await?;
+    }
+    tokio::fs::write(&marker, Utc::now().to_rfc3339()).await?;
+    Ok(())
+}
+
+async fn untouched_since_marker(profile_dir: &Path) -> bool {
+    let Ok(marked_at) = tokio::fs::metadata(profile_dir.join(SELF_PROFILE_MARKER))
+        .await
+        .and_then(|m| m.modified())
+    else {
+        return false;
+    };
+    review_calendar::content_modified(profile_dir)
+        .await
+        .map_or(true, |modified| modified <= marked_at)
+}
+
+/// The user's own profile if it was provisioned, without creating it.
+pub async fn self_profile(email: &str, data_dir: &Path) -> Result<Option<SelfProfile>> {
+    let tenant_dir = get_tenant_folder_path(email, data_dir);
+    let Some(profile) = find_self_profile(&tenant_dir).await? else {
+        return Ok(None);
+    };
+    let needs_onboarding = untouched_since_marker(&tenant_dir.join(&profile)).await;
+    Ok(Some(SelfProfile {
+        profile,
+        created: false,
+        needs_onboarding,
+    }))
+}
+
+/// Return the user's own profile, creating it when their tenant directory
+/// has no profiles yet. `None` for accounts that predate provisioning.
+pub async fn ensure_self_profile(
+    email: &str,
+    display_name: Option<&str>,
+    data_dir: &Path,
+    templates_dir: &Path,
+) -> Result<Option<SelfProfile>> {
+    if let Some(own) = self_profile(email, data_dir).await? {
+        return Ok(Some(own));
+    }
+    let tenant_dir = get_tenant_folder_path(email, data_dir);
+    if !FsOps::list_profiles(&tenant_dir).await?.is_empty() {
+        return Ok(None);
+    }
+
+    let local_part = email.split('@').next().unwrap_or("user");
+    let profile = match normalize_profile_name(local_part) {
+        name if name.is_empty() => "me".to_string(),
+        name => name,
+    };
+    let profile_dir = tenant_dir.join(&profile);
+
+    FsOps::ensure_dir_exists(&tenant_dir).await?;
+    // Claims the name: a concurrent first request loses here instead of
+    // writing the templates twice.
+    if let Err(e) = tokio::fs::create_dir(&profile_dir).await {
+        if e.kind() == std::io::ErrorKind::AlreadyExists {
+            return Ok(None);
+        }
+        return Err(e.into());
+    }
+
+    let engine = TemplateEngine::new(templates_dir.to_path_buf())?;
+    engine
+        .create_profile_from_templates_async(
+            &profile,
+            &tenant_dir,
+            Some(display_name.unwrap_or(local_part)),
+        )
+        .await?;
+    tokio::fs::write(
+        profile_dir.join(SELF_PROFILE_MARKER),
+        Utc::now().to_rfc3339(),
+    )
+    .await?;
+
+    app_log!(info, "Provisioned own profile '{}' for {}", profile, email);
+    Ok(Some(SelfProfile {
+        profile,
+        created: true,
+        needs_onboarding: true,
+    }))
+}
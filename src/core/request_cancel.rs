@@ -0,0 +1,180 @@
+// src/core/request_cancel.rs
+//! Cancellation of in-flight requests the client gave up on.
+//!
+//! Rocket 0.5 runs each handler in its own task and only notices a closed
+//! connection when it writes the response, so an aborted fetch leaves the
+//! handler waiting out the 400s cv-import timeout or a generation queue slot.
+//! The studio therefore tags long requests with an `X-Request-Id` header and,
+//! when its `AbortSignal` fires, calls `POST /requests/<id>/cancel`. Handlers
+//! [`register`] the id for the duration of the request; cancelling trips the
+//! [`CancelToken`], which the [`ServiceClient`](crate::core::ServiceClient)
+//! and queue waits select on. Ids are scoped to the user that sent them.
+//!
+//! Disconnects themselves can't be detected for ordinary responses, for the
+//! reason above. A streamed response is dropped at its first write after
+//! the client left, so streaming handlers keep their work, registration
+//! and charge inside the stream. Handlers that charged credits refund them
+//! when the request is cancelled
+//! ([`refund_credits`](crate::web::handlers::payment_handlers::refund_credits)).
+
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+use tokio::sync::watch;
+
+pub const REQUEST_ID_HEADER: &str = "X-Request-Id";
+const MAX_ID_LEN: usize = 64;
+
+/// (user email, request id) → cancel switch.
+static ACTIVE: LazyLock<Mutex<HashMap<(String, String), watch::Sender<bool>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+fn active() -> std::sync::MutexGuard<'static, HashMap<(String, String), watch::Sender<bool>>> {
+    ACTIVE
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// The error a cancelled call fails with; find it with `downcast_ref`.
+#[derive(Debug, Clone, Copy)]
+pub struct Cancelled;
+
+impl std::fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Request cancelled by the client")
+    }
+}
+
+impl std::error::Error for Cancelled {}
+
+/// Whether `error` (or anything it wraps) is a [`Cancelled`].
+pub fn is_cancelled(error: &anyhow::Error) -> bool {
+    error.downcast_ref::<Cancelled>().is_some()
+}
+
+#[derive(Clone)]
+pub struct CancelToken {
+    rx: Option<watch::Receiver<bool>>,
+}
+
+impl CancelToken {
+    /// A token that is never cancelled, for requests without an id.
+    pub fn never() -> Self {
+        Self { rx: None }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.rx.as_ref().is_some_and(|rx| *rx.borrow())
+    }
+
+    /// Resolves once the request is cancelled; pends forever otherwise.
+    pub async fn cancelled(&self) {
+        match &self.rx {
+            Some(rx) => {
+                let mut rx = rx.clone();
+                let closed = rx.wait_for(|cancelled| *cancelled).await.is_err();
+                if closed {
+                    std::future::pending::<()>().await;
+                }
+            }
+            None => std::future::pending().await,
+        }
+    }
+
+    /// Run `fut` unless the request is cancelled first.
+    pub async fn run<F: std::future::Future>(&self, fut: F) -> Result<F::Output, Cancelled> {
+        tokio::select! {
+            output = fut => Ok(output),
+            _ = self.cancelled() => Err(Cancelled),
+        }
+    }
+}
+
+/// Keeps a request id cancellable until dropped.
+pub struct Registration {
+    key: Option<(String, String)>,
+    token: CancelToken,
+}
+
+impl Registration {
+    pub fn token(&self) -> CancelToken {
+        self.token.clone()
+    }
+}
+
+impl Drop for Registration {
+    fn drop(&mut self) {
+        let (Some(key), Some(rx)) = (self.key.take(), &self.token.rx) else {
+            return;
+        };
+        // A reused id may have been registered again since; leave that one.
+        let mut active = active();
+        if active
+            .get(&key)
+            .is_some_and(|tx| tx.subscribe().same_channel(rx))
+        {
+            active.remove(&key);
+        }
+    }
+}
+
+/// A valid client-chosen request id: short, URL-safe.
+pub fn valid_id(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= MAX_ID_LEN
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Make `request_id` of `email` cancellable. Without an id (or with a
+/// malformed one) the token never fires.
+pub fn register(email: &str, request_id: Option<&str>) -> Registration {
+    let Some(id) = request_id.filter(|id| valid_id(id)) else {
+        return Registration {
+            key: None,
+            token: CancelToken::never(),
+        };
+    };
+    let key = (email.to_lowercase(), id.to_string());
+    let (tx, rx) = watch::channel(false);
+    active().insert(key.clone(), tx);
+    Registration {
+        key: Some(key),
+        token: CancelToken { rx: Some(rx) },
+    }
+}
+
+/// Cancel `request_id` of `email`; false if no such request is running.
+pub fn cancel(email: &str, request_id: &str) -> bool {
+    let key = (email.to_lowercase(), request_id.to_string());
+    match active().get(&key) {
+        Some(tx) => {
+            tx.send_replace(true);
+            true
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn cancel_trips_only_the_owners_request() {
+        let registration = register("Jane@Acme.com", Some("req-1"));
+        let token = registration.token();
+        assert!(!cancel("john@acme.com", "req-1"));
+        assert!(!token.is_cancelled());
+
+        assert!(cancel("jane@acme.com", "req-1"));
+        let result = token.run(std::future::pending::<()>()).await;
+        assert!(result.is_err());
+
+        drop(registration);
+        assert!(!cancel("jane@acme.com", "req-1"));
+        assert!(!register("jane@acme.com", Some("../x"))
+            .token()
+            .is_cancelled());
+    }
+}
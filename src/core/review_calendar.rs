@@ -0,0 +1,293 @@
+// src/core/review_calendar.rs
+//! Profile review tasks and their iCal rendering.
+//!
+//! Two kinds of task, both derived from the tenant directory on each request:
+//! * **Refresh** — due `PROFILE_REFRESH_DAYS` (default 180) after a profile's
+//!   content files were last modified.
+//! * **Import review** — a profile created by an import (upload, text, notes,
+//!   email) that nobody has edited since. The importer drops an
+//!   [`IMPORT_REVIEW_MARKER`]; any later edit to the content files clears the
+//!   task.
+//!
+//! Feeds are read by calendar clients that can't send a bearer token, so each
+//! user gets an unguessable feed token instead.
+
+use anyhow::Result;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use sqlx::SqlitePool;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::path::Path;
+use std::time::SystemTime;
+
+use crate::core::FsOps;
+
+/// Written into a profile directory by imports; holds the import timestamp.
+pub const IMPORT_REVIEW_MARKER: &str = ".import_review";
+
+const DEFAULT_REFRESH_DAYS: i64 = 180;
+
+pub fn refresh_interval_days() -> i64 {
+    std::env::var("PROFILE_REFRESH_DAYS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .filter(|d: &i64| *d > 0)
+        .unwrap_or(DEFAULT_REFRESH_DAYS)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskKind {
+    Refresh,
+    ImportReview,
+}
+
+impl TaskKind {
+    fn uid_prefix(self) -> &'static str {
+        match self {
+            TaskKind::Refresh => "refresh",
+            TaskKind::ImportReview => "import-review",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CalendarTask {
+    pub kind: TaskKind,
+    pub profile: String,
+    pub due: NaiveDate,
+    pub last_updated: DateTime<Utc>,
+}
+
+impl CalendarTask {
+    fn summary(&self) -> String {
+        match self.kind {
+            TaskKind::Refresh => format!("Refresh CV profile: {}", self.profile),
+            TaskKind::ImportReview => format!("Review imported profile: {}", self.profile),
+        }
+    }
+
+    fn description(&self) -> String {
+        match self.kind {
+            TaskKind::Refresh => format!(
+                "Last updated {}. Check roles, skills and availability are still current.",
+                self.last_updated.format("%Y-%m-%d")
+            ),
+            TaskKind::ImportReview => format!(
+                "Imported {} and not edited since. Check the converted data before sending it out.",
+                self.last_updated.format("%Y-%m-%d")
+            ),
+        }
+    }
+}
+
+/// Mark a freshly imported profile as awaiting review.
+pub async fn mark_imported(profile_dir: &Path) -> Result<()> {
+    tokio::fs::write(
+        profile_dir.join(IMPORT_REVIEW_MARKER),
+        Utc::now().to_rfc3339(),
+    )
+    .await?;
+    Ok(())
+}
+
+/// Latest mtime of the files a user edits (`cv_params.toml`, `experiences_*.typ`).
+pub async fn content_modified(profile_dir: &Path) -> Option<SystemTime> {
+    let mut latest: Option<SystemTime> = None;
+    let mut entries = tokio::fs::read_dir(profile_dir).await.ok()?;
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let is_content = name == "cv_params.toml"
+            || (name.starts_with("experiences_") && name.ends_with(".typ"));
+        if !is_content {
+            continue;
+        }
+        if let Ok(modified) = entry.metadata().await.and_then(|m| m.modified()) {
+            latest = Some(latest.map_or(modified, |l| l.max(modified)));
+        }
+    }
+    latest
+}
+
+/// True while an imported profile hasn't been edited since the import.
+pub async fn awaiting_import_review(profile_dir: &Path) -> bool {
+    let marker = profile_dir.join(IMPORT_REVIEW_MARKER);
+    let Ok(marked_at) = tokio::fs::metadata(&marker).await.and_then(|m| m.modified()) else {
+        return false;
+    };
+    content_modified(profile_dir)
+        .await
+        .is_some_and(|modified| modified <= marked_at)
+}
+
+/// All refresh and import-review tasks for the profiles in `tenant_dir`.
+pub async fn collect_tasks(tenant_dir: &Path) -> Result<Vec<CalendarTask>> {
+    let refresh_days = refresh_interval_days();
+    let mut tasks = Vec::new();
+
+    for profile in FsOps::list_profiles(tenant_dir).await? {
+        let profile_dir = tenant_dir.join(&profile);
+        let Some(modified) = content_modified(&profile_dir).await else {
+            continue;
+        };
+        let last_updated = DateTime::<Utc>::from(modified);
+
+        tasks.push(CalendarTask {
+            kind: TaskKind::Refresh,
+            profile: profile.clone(),
+            due: (last_updated + Duration::days(refresh_days)).date_naive(),
+            last_updated,
+        });
+
+        // The importer writes the marker after the content files, so an
+        // untouched import has content older than (or equal to) the marker.
+        let marker = profile_dir.join(IMPORT_REVIEW_MARKER);
+        if let Ok(marked_at) = tokio::fs::metadata(&marker)
+            .await
+            .and_then(|m| m.modified())
+        {
+            if modified <= marked_at {
+                let imported = DateTime::<Utc>::from(marked_at);
+                tasks.push(CalendarTask {
+                    kind: TaskKind::ImportReview,
+                    profile,
+                    due: imported.date_naive(),
+                    last_updated: imported,
+                });
+            }
+        }
+    }
+
+    tasks.sort_by(|a, b| a.due.cmp(&b.due).then_with(|| a.profile.cmp(&b.profile)));
+    Ok(tasks)
+}
+
+/// Escape a TEXT value (RFC 5545 §3.3.11).
+fn escape_text(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+        .replace('\r', "")
+}
+
+/// Fold a content line at 75 octets without splitting UTF-8 sequences.
+fn fold_line(line: &str) -> String {
+    let mut out = String::with_capacity(line.len() + 8);
+    let mut width = 0;
+    for ch in line.chars() {
+        let len = ch.len_utf8();
+        if width + len > 75 {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(ch);
+        width += len;
+    }
+    out.push_str("\r\n");
+    out
+}
+
+/// Render `tasks` as an all-day-event calendar. `owner` only seeds the UIDs so
+/// they are stable per profile and unique across users.
+pub fn render_ics(tasks: &[CalendarTask], owner: &str, now: DateTime<Utc>) -> String {
+    let mut hasher = DefaultHasher::new();
+    owner.hash(&mut hasher);
+    let owner_id = format!("{:016x}", hasher.finish());
+    let stamp = now.format("%Y%m%dT%H%M%SZ").to_string();
+
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//cvenom//Profile tasks//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+        "METHOD:PUBLISH".to_string(),
+        "X-WR-CALNAME:cvenom profile tasks".to_string(),
+    ];
+    for task in tasks {
+        lines.extend([
+            "BEGIN:VEVENT".to_string(),
+            format!(
+                "UID:{}-{}-{}@cvenom.com",
+                task.kind.uid_prefix(),
+                task.profile,
+                owner_id
+            ),
+            format!("DTSTAMP:{}", stamp),
+            format!("DTSTART;VALUE=DATE:{}", task.due.format("%Y%m%d")),
+            format!(
+                "DTEND;VALUE=DATE:{}",
+                (task.due + Duration::days(1)).format("%Y%m%d")
+            ),
+            format!("SUMMARY:{}", escape_text(&task.summary())),
+            format!("DESCRIPTION:{}", escape_text(&task.description())),
+            "TRANSP:TRANSPARENT".to_string(),
+            "END:VEVENT".to_string(),
+        ]);
+    }
+    lines.push("END:VCALENDAR".to_string());
+
+    lines.iter().map(|l| fold_line(l)).collect()
+}
+
+/// The user's feed token, created on first request.
+pub async fn get_or_create_feed_token(pool: &SqlitePool, user_email: &str) -> Result<String> {
+    if let Some(token) =
+        sqlx::query_scalar::<_, String>("SELECT token FROM calendar_feeds WHERE user_email = ?")
+            .bind(user_email)
+            .fetch_optional(pool)
+            .await?
+    {
+        return Ok(token);
+    }
+
+    let token = uuid::Uuid::new_v4().simple().to_string();
+    sqlx::query("INSERT OR IGNORE INTO calendar_feeds (token, user_email) VALUES (?, ?)")
+        .bind(&token)
+        .bind(user_email)
+        .execute(pool)
+        .await?;
+
+    let stored =
+        sqlx::query_scalar::<_, String>("SELECT token FROM calendar_feeds WHERE user_email = ?")
+            .bind(user_email)
+            .fetch_one(pool)
+            .await?;
+    Ok(stored)
+}
+
+pub async fn find_user_by_feed_token(pool: &SqlitePool, token: &str) -> Result<Option<String>> {
+    let email =
+        sqlx::query_scalar::<_, String>("SELECT user_email FROM calendar_feeds WHERE token = ?")
+            .bind(token)
+            .fetch_optional(pool)
+            .await?;
+    Ok(email)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn renders_escaped_folded_all_day_events() {
+        let task = CalendarTask {
+            kind: TaskKind::ImportReview,
+            profile: "john_doe".to_string(),
+            due: NaiveDate::from_ymd_opt(2026, 10, 16).unwrap(),
+            last_updated: Utc.with_ymd_and_hms(2026, 10, 16, 9, 0, 0).unwrap(),
+        };
+        let now = Utc.with_ymd_and_hms(2026, 10, 16, 12, 0, 0).unwrap();
+        let ics = render_ics(&[task], "jane@acme.com", now);
+
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\n"));
+        assert!(ics.contains("DTSTART;VALUE=DATE:20261016\r\n"));
+        assert!(ics.contains("DTEND;VALUE=DATE:20261017\r\n"));
+        assert!(ics.contains("SUMMARY:Review imported profile: john_doe\r\n"));
+        assert!(ics.contains("not edited since. Check"));
+        assert!(ics.lines().all(|l| l.len() <= 76));
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+        assert_eq!(escape_text("a,b;c\nd"), "a\\,b\\;c\\nd");
+    }
+}
@@ -0,0 +1,413 @@
+// src/core/secrets.rs
+//! Per-tenant secrets: SMTP credentials, webhook secrets, signing keys and
+//! API tokens other features need.
+//!
+//! Secrets are keyed by tenant id (see [`crate::core::owner`]), so a rename
+//! leaves them in place and two email tenants sharing a name never share
+//! secrets. Values are envelope-encrypted with AES-256-GCM: each secret gets
+//! a fresh data key that encrypts the value, and the data key is itself
+//! encrypted ("wrapped") under the server's master key. Both use
+//! `<tenant id>/<name>` as associated data, so a row copied to another
+//! tenant or name fails to open.
+//! The master key is 32 bytes, base64, from `SECRETS_MASTER_KEY` or the file
+//! named by `SECRETS_MASTER_KEY_FILE` (where a KMS agent or orchestrator
+//! mounts it); without one the store is disabled. Rows remember the id of the
+//! master key that sealed them, so a key change is reported rather than read
+//! as garbage.
+//!
+//! Values are write-only over the API: [`list`] returns names and dates, and
+//! subsystems read values with [`SecretStore::get`].
+
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+use aes_gcm::{Aes256Gcm, Nonce};
+use anyhow::{anyhow, bail, Context, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::{DateTime, Utc};
+use graflog::app_log;
+use serde::Serialize;
+use sqlx::SqlitePool;
+
+use crate::core::database::{has_column, legacy_tenant_id};
+use crate::utils::sha256_hex;
+
+pub(crate) const KEY_LEN: usize = 32;
+pub(crate) const NONCE_LEN: usize = 12;
+pub const MAX_VALUE_BYTES: usize = 64 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SecretKind {
+    SmtpCredential,
+    WebhookSecret,
+    SigningKey,
+    ApiToken,
+    Other,
+}
+
+impl SecretKind {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "smtp_credential" => Some(Self::SmtpCredential),
+            "webhook_secret" => Some(Self::WebhookSecret),
+            "signing_key" => Some(Self::SigningKey),
+            "api_token" => Some(Self::ApiToken),
+            "other" => Some(Self::Other),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::SmtpCredential => "smtp_credential",
+            Self::WebhookSecret => "webhook_secret",
+            Self::SigningKey => "signing_key",
+            Self::ApiToken => "api_token",
+            Self::Other => "other",
+        }
+    }
+}
+
+/// What [`list`] shows of a secret; never the value.
+#[derive(Debug, Clone, Serialize)]
+pub struct SecretInfo {
+    pub name: String,
+    pub kind: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A value sealed by [`seal`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Sealed {
+    value_nonce: Vec<u8>,
+    value: Vec<u8>,
+    key_nonce: Vec<u8>,
+    wrapped_key: Vec<u8>,
+}
+
+/// Secret names: lowercase letters, digits, `_` and `-`.
+pub fn valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= 64
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-')
+}
+
+fn cipher(key: &[u8]) -> Result<Aes256Gcm> {
+    Aes256Gcm::new_from_slice(key).map_err(|_| anyhow!("AES-256 keys are {} bytes", KEY_LEN))
+}
+
+pub(crate) fn encrypt(key: &[u8], aad: &[u8], msg: &[u8]) -> Result<(Vec<u8>, Vec<u8>)> {
+    let nonce: [u8; NONCE_LEN] = rand::random();
+    let sealed = cipher(key)?
+        .encrypt(Nonce::from_slice(&nonce), Payload { msg, aad })
+        .map_err(|_| anyhow!("Encryption failed"))?;
+    Ok((nonce.to_vec(), sealed))
+}
+
+pub(crate) fn decrypt(key: &[u8], aad: &[u8], nonce: &[u8], sealed: &[u8]) -> Result<Vec<u8>> {
+    if nonce.len() != NONCE_LEN {
+        bail!("Corrupt nonce");
+    }
+    cipher(key)?
+        .decrypt(Nonce::from_slice(nonce), Payload { msg: sealed, aad })
+        .map_err(|_| anyhow!("Decryption failed: wrong key or tampered data"))
+}
+
+fn seal(master: &[u8], aad: &[u8], value: &[u8]) -> Result<Sealed> {
+    let data_key: [u8; KEY_LEN] = rand::random();
+    let (value_nonce, value) = encrypt(&data_key, aad, value)?;
+    let (key_nonce, wrapped_key) = encrypt(master, aad, &data_key)?;
+    Ok(Sealed {
+        value_nonce,
+        value,
+        key_nonce,
+        wrapped_key,
+    })
+}
+
+fn open(master: &[u8], aad: &[u8], sealed: &Sealed) -> Result<Vec<u8>> {
+    let data_key = decrypt(master, aad, &sealed.key_nonce, &sealed.wrapped_key)?;
+    decrypt(&data_key, aad, &sealed.value_nonce, &sealed.value)
+}
+
+fn aad(owner: &str, name: &str) -> Vec<u8> {
+    format!("{}/{}", owner, name).into_bytes()
+}
+
+/// The master key from the environment. A malformed key is logged and
+/// treated as absent.
+pub(crate) fn master_key_from_env() -> Option<Vec<u8>> {
+    let encoded = match std::env::var("SECRETS_MASTER_KEY_FILE") {
+        Ok(path) => match std::fs::read_to_string(&path) {
+            Ok(key) => Some(key),
+            Err(e) => {
+                app_log!(error, "Failed to read SECRETS_MASTER_KEY_FILE {}: {}", path, e);
+                None
+            }
+        },
+        Err(_) => std::env::var("SECRETS_MASTER_KEY").ok(),
+    };
+    encoded.filter(|k| !k.trim().is_empty()).and_then(|k| {
+        match STANDARD.decode(k.trim()) {
+            Ok(key) if key.len() == KEY_LEN => Some(key),
+            _ => {
+                app_log!(
+                    error,
+                    "Secrets master key must be {} bytes of base64; ignoring it",
+                    KEY_LEN
+                );
+                None
+            }
+        }
+    })
+}
+
+/// The master key; managed as Rocket state.
+pub struct SecretStore {
+    master: Option<Vec<u8>>,
+}
+
+impl SecretStore {
+    /// Load the master key from the environment; without one the store is
+    /// disabled.
+    pub fn from_env() -> Self {
+        Self {
+            master: master_key_from_env(),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.master.is_some()
+    }
+
+    fn master(&self) -> Result<&[u8]> {
+        self.master
+            .as_deref()
+            .context("Secrets store is disabled: SECRETS_MASTER_KEY is not set")
+    }
+
+    /// Short id of the master key, stored with each row.
+    fn key_id(master: &[u8]) -> String {
+        sha256_hex(master)[..16].to_string()
+    }
+
+    /// Create or replace `name` for the tenant `tenant_id`.
+    pub async fn put(
+        &self,
+        pool: &SqlitePool,
+        tenant_id: i64,
+        name: &str,
+        kind: SecretKind,
+        value: &str,
+    ) -> Result<()> {
+        if !valid_name(name) {
+            bail!("Invalid secret name '{}'", name);
+        }
+        if value.len() > MAX_VALUE_BYTES {
+            bail!("Secret values are limited to {} bytes", MAX_VALUE_BYTES);
+        }
+        let master = self.master()?;
+        let sealed = seal(master, &aad(&tenant_id.to_string(), name), value.as_bytes())?;
+        let now = Utc::now();
+        sqlx::query(
+            "INSERT INTO tenant_secrets \
+             (tenant_id, name, kind, value_nonce, value, key_nonce, wrapped_key, key_id, created_at, updated_at) \
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?) \
+             ON CONFLICT(tenant_id, name) DO UPDATE SET kind = excluded.kind, \
+             value_nonce = excluded.value_nonce, value = excluded.value, \
+             key_nonce = excluded.key_nonce, wrapped_key = excluded.wrapped_key, \
+             key_id = excluded.key_id, updated_at = excluded.updated_at",
+        )
+        .bind(tenant_id)
+        .bind(name)
+        .bind(kind.as_str())
+        .bind(sealed.value_nonce)
+        .bind(sealed.value)
+        .bind(sealed.key_nonce)
+        .bind(sealed.wrapped_key)
+        .bind(Self::key_id(master))
+        .bind(now)
+        .bind(now)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// The value of `name` for the tenant `tenant_id`, if stored.
+    pub async fn get(
+        &self,
+        pool: &SqlitePool,
+        tenant_id: i64,
+        name: &str,
+    ) -> Result<Option<String>> {
+        let master = self.master()?;
+        let row: Option<(Vec<u8>, Vec<u8>, Vec<u8>, Vec<u8>, String)> = sqlx::query_as(
+            "SELECT value_nonce, value, key_nonce, wrapped_key, key_id FROM tenant_secrets \
+             WHERE tenant_id = ? AND name = ?",
+        )
+        .bind(tenant_id)
+        .bind(name)
+        .fetch_optional(pool)
+        .await?;
+        let Some((value_nonce, value, key_nonce, wrapped_key, key_id)) = row else {
+            return Ok(None);
+        };
+        if key_id != Self::key_id(master) {
+            bail!(
+                "Secret '{}' of tenant {} was sealed under another master key ({})",
+                name,
+                tenant_id,
+                key_id
+            );
+        }
+        let sealed = Sealed {
+            value_nonce,
+            value,
+            key_nonce,
+            wrapped_key,
+        };
+        let plain = open(master, &aad(&tenant_id.to_string(), name), &sealed)
+            .with_context(|| format!("Failed to open secret '{}' of tenant {}", name, tenant_id))?;
+        Ok(Some(String::from_utf8(plain).context("Secret is not UTF-8")?))
+    }
+
+    /// Like [`get`](Self::get) for optional integrations: a disabled store,
+    /// a missing secret or a failure to open it all give `None` (failures are
+    /// logged), and the caller falls back to its server-wide setting.
+    pub async fn lookup(&self, pool: &SqlitePool, tenant_id: i64, name: &str) -> Option<String> {
+        if !self.enabled() {
+            return None;
+        }
+        match self.get(pool, tenant_id, name).await {
+            Ok(value) => value,
+            Err(e) => {
+                app_log!(warn, "Ignoring secret '{}' of tenant {}: {:#}", name, tenant_id, e);
+                None
+            }
+        }
+    }
+}
+
+/// Secrets of the tenant `tenant_id`, by name.
+pub async fn list(pool: &SqlitePool, tenant_id: i64) -> Result<Vec<SecretInfo>> {
+    let rows: Vec<(String, String, DateTime<Utc>, DateTime<Utc>)> = sqlx::query_as(
+        "SELECT name, kind, created_at, updated_at FROM tenant_secrets \
+         WHERE tenant_id = ? ORDER BY name",
+    )
+    .bind(tenant_id)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows
+        .into_iter()
+        .map(|(name, kind, created_at, updated_at)| SecretInfo {
+            name,
+            kind,
+            created_at,
+            updated_at,
+        })
+        .collect())
+}
+
+/// Remove `name`; false if the tenant had no such secret.
+pub async fn delete(pool: &SqlitePool, tenant_id: i64, name: &str) -> Result<bool> {
+    let result = sqlx::query("DELETE FROM tenant_secrets WHERE tenant_id = ? AND name = ?")
+        .bind(tenant_id)
+        .bind(name)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+type LegacyRow = (i64, String, String, Vec<u8>, Vec<u8>, Vec<u8>, Vec<u8>, String);
+
+/// One-off move of `tenant_secrets` from the tenant name to the tenant id.
+/// The owner is bound into each value, so every row is re-sealed, which
+/// needs the master key that sealed them: without it the server refuses to
+/// start rather than lose them. Rows whose name matched no single tenant are dropped: they
+/// were readable by every tenant of that name.
+pub(crate) async fn rekey_by_tenant_id(pool: &SqlitePool) -> Result<()> {
+    if !has_column(pool, "tenant_secrets", "tenant").await? {
+        return Ok(());
+    }
+    let mut tx = pool.begin().await?;
+    sqlx::query("ALTER TABLE tenant_secrets RENAME COLUMN tenant TO tenant_id")
+        .execute(&mut *tx)
+        .await?;
+    let rows: Vec<LegacyRow> = sqlx::query_as(
+        "SELECT rowid, tenant_id, name, value_nonce, value, key_nonce, wrapped_key, key_id \
+         FROM tenant_secrets",
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+    if rows.is_empty() {
+        tx.commit().await?;
+        return Ok(());
+    }
+    let master = master_key_from_env().context(
+        "tenant_secrets must be re-keyed by tenant id: start once with the secrets master key",
+    )?;
+    let mut dropped = 0;
+    for (rowid, tenant_name, name, value_nonce, value, key_nonce, wrapped_key, key_id) in rows {
+        let Some(tenant_id) = legacy_tenant_id(&mut tx, &tenant_name, None).await? else {
+            sqlx::query("DELETE FROM tenant_secrets WHERE rowid = ?")
+                .bind(rowid)
+                .execute(&mut *tx)
+                .await?;
+            dropped += 1;
+            continue;
+        };
+        if key_id != SecretStore::key_id(&master) {
+            bail!("Secret '{}' of {} was sealed under another master key", name, tenant_name);
+        }
+        let sealed = Sealed {
+            value_nonce,
+            value,
+            key_nonce,
+            wrapped_key,
+        };
+        let plain = open(&master, &aad(&tenant_name, &name), &sealed)
+            .with_context(|| format!("Failed to open secret '{}' of {}", name, tenant_name))?;
+        let resealed = seal(&master, &aad(&tenant_id.to_string(), &name), &plain)?;
+        sqlx::query(
+            "UPDATE tenant_secrets SET tenant_id = ?, value_nonce = ?, value = ?, \
+             key_nonce = ?, wrapped_key = ? WHERE rowid = ?",
+        )
+        .bind(tenant_id)
+        .bind(resealed.value_nonce)
+        .bind(resealed.value)
+        .bind(resealed.key_nonce)
+        .bind(resealed.wrapped_key)
+        .bind(rowid)
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+    if dropped > 0 {
+        app_log!(warn, "Dropped {} secrets shared by several tenants of one name", dropped);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sealed_values_only_open_with_their_key_and_owner() {
+        let master = [7u8; KEY_LEN];
+        let sealed = seal(&master, &aad("7", "smtp_password"), b"hunter2").unwrap();
+        assert_ne!(sealed.value, b"hunter2".to_vec());
+        assert_eq!(
+            open(&master, &aad("7", "smtp_password"), &sealed).unwrap(),
+            b"hunter2".to_vec()
+        );
+        // Another tenant's row, another key, or tampering all fail.
+        assert!(open(&master, &aad("8", "smtp_password"), &sealed).is_err());
+        assert!(open(&[8u8; KEY_LEN], &aad("7", "smtp_password"), &sealed).is_err());
+        let mut tampered = sealed.clone();
+        tampered.value[0] ^= 1;
+        assert!(open(&master, &aad("7", "smtp_password"), &tampered).is_err());
+    }
+}
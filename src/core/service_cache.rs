@@ -0,0 +1,217 @@
+// src/core/service_cache.rs
+//! Answers of cv-import calls reused while the request is unchanged.
+//!
+//! Job-fit analysis, optimization and translation are LLM calls that cost
+//! money and take seconds, and they are often repeated on a CV that did not
+//! change: the same job analyzed twice, an optimization retried after a
+//! failed save. A [`ResultCache`] given to a
+//! [`ServiceClient`](crate::core::ServiceClient) keeps each successful
+//! answer in the caller's user folder, `<tenant>/<user>/.service_cache/
+//! <key>.json`, the key hashing the endpoint and the request body — CV data,
+//! job URL and posting text, target language. Answers hold CV content, so
+//! they are sealed like it ([`data_encryption::seal_derived`]) and go with
+//! the account when it is deleted. The same call within
+//! `SERVICE_CACHE_TTL_HOURS` (default 24; `0` disables the cache) is
+//! answered from disk and not recorded as a service call; any edit to the CV
+//! changes the key. `force_refresh` skips the lookup and stores the fresh
+//! answer. Expired answers are deleted by [`prune`], daily by the server and
+//! by `cvenom admin prune-caches`.
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use crate::core::data_encryption;
+use crate::utils::sha256_hex;
+
+pub const CACHE_DIR: &str = ".service_cache";
+const DEFAULT_TTL_HOURS: u64 = 24;
+
+fn ttl() -> Duration {
+    let hours = std::env::var("SERVICE_CACHE_TTL_HOURS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_TTL_HOURS);
+    Duration::from_secs(hours * 60 * 60)
+}
+
+#[derive(Debug, Clone)]
+pub struct ResultCache {
+    dir: PathBuf,
+    bypass: bool,
+}
+
+impl ResultCache {
+    /// The cache of the account whose data folder is `user_dir`; `None` when
+    /// the cache is disabled.
+    pub fn new(user_dir: &Path, bypass: bool) -> Option<Self> {
+        if ttl().is_zero() {
+            return None;
+        }
+        Some(Self {
+            dir: user_dir.join(CACHE_DIR),
+            bypass,
+        })
+    }
+
+    /// Key of a call to `endpoint` with `payload`. Whether the answer is
+    /// streamed does not change it.
+    pub fn key<T: Serialize + ?Sized>(&self, endpoint: &str, payload: &T) -> Option<String> {
+        let mut payload = serde_json::to_value(payload).ok()?;
+        if let Some(fields) = payload.as_object_mut() {
+            fields.remove("stream");
+        }
+        let identity = format!("{}\n{}", endpoint, payload);
+        Some(sha256_hex(identity.as_bytes()))
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", key))
+    }
+
+    /// The body answered to the call `key`, unless bypassed or expired.
+    pub async fn get(&self, key: &str) -> Option<String> {
+        if self.bypass {
+            return None;
+        }
+        let path = self.path(key);
+        let modified = tokio::fs::metadata(&path).await.ok()?.modified().ok()?;
+        let age = SystemTime::now().duration_since(modified).unwrap_or_default();
+        if age > ttl() {
+            let _ = tokio::fs::remove_file(&path).await;
+            return None;
+        }
+        tokio::task::spawn_blocking(move || data_encryption::read_to_string(&path))
+            .await
+            .ok()?
+            .ok()
+    }
+
+    /// Keep `body`, the successful answer to the call `key`.
+    pub async fn put(&self, key: &str, body: &str) {
+        if tokio::fs::create_dir_all(&self.dir).await.is_err() {
+            return;
+        }
+        let path = self.path(key);
+        let Ok(sealed) = data_encryption::seal_derived(&path, body.as_bytes()) else {
+            return;
+        };
+        // Write-then-rename so a concurrent reader never sees a partial file.
+        let tmp = self.dir.join(format!("{}.{}.tmp", key, uuid::Uuid::new_v4()));
+        if tokio::fs::write(&tmp, &sealed).await.is_ok() {
+            let _ = tokio::fs::rename(&tmp, &path).await;
+        }
+    }
+}
+
+/// Delete the answers cached in the user folders of `data_root` that are
+/// past the TTL (all of them when the cache is disabled), and temp files
+/// left by an interrupted write. Returns how many files and bytes went;
+/// without `execute`, only counts them.
+pub fn prune(data_root: &Path, execute: bool) -> Result<(usize, u64)> {
+    // Answers once shared by a whole tenant at the data root, unsealed.
+    let legacy = data_root.join(CACHE_DIR);
+    let (mut deleted, mut freed) = prune_older_than(&legacy, Duration::ZERO, execute)?;
+    for user_dir in user_dirs(data_root) {
+        let (files, bytes) = prune_older_than(&user_dir.join(CACHE_DIR), ttl(), execute)?;
+        deleted += files;
+        freed += bytes;
+    }
+    Ok((deleted, freed))
+}
+
+/// `<data_root>/<tenant>/<user>` folders, skipping hidden ones.
+fn user_dirs(data_root: &Path) -> Vec<PathBuf> {
+    let subdirs = |dir: &Path| -> Vec<PathBuf> {
+        std::fs::read_dir(dir)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter(|e| !e.file_name().to_string_lossy().starts_with('.') && e.path().is_dir())
+            .map(|e| e.path())
+            .collect()
+    };
+    subdirs(data_root).iter().flat_map(|tenant| subdirs(tenant)).collect()
+}
+
+fn prune_older_than(dir: &Path, max_age: Duration, execute: bool) -> Result<(usize, u64)> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Ok((0, 0));
+    };
+    let cutoff = SystemTime::now().checked_sub(max_age).unwrap_or(SystemTime::UNIX_EPOCH);
+    let (mut deleted, mut freed) = (0usize, 0u64);
+    for entry in entries {
+        let entry = entry?;
+        let meta = entry.metadata()?;
+        let modified = meta.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+        if !meta.is_file() || modified >= cutoff {
+            continue;
+        }
+        let path = entry.path();
+        if execute {
+            std::fs::remove_file(&path)
+                .with_context(|| format!("Failed to delete {}", path.display()))?;
+        }
+        deleted += 1;
+        freed += meta.len();
+    }
+    Ok((deleted, freed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn answers_are_reused_for_the_same_request_only() {
+        let root = tempfile::tempdir().unwrap();
+        let user_dir = root.path().join("acme.com").join("ada_at_acme_com");
+        let cache = ResultCache::new(&user_dir, false).unwrap();
+        let payload = serde_json::json!({ "cv_data": { "name": "Ada" }, "job_url": "https://jobs/1" });
+        let key = cache.key("/optimize", &payload).unwrap();
+        assert!(cache.get(&key).await.is_none());
+        cache.put(&key, "{\"status\":\"success\"}").await;
+        assert_eq!(cache.get(&key).await.as_deref(), Some("{\"status\":\"success\"}"));
+        assert!(user_dir.join(CACHE_DIR).join(format!("{}.json", key)).exists());
+
+        let mut streamed = payload.clone();
+        streamed["stream"] = serde_json::Value::Bool(true);
+        assert_eq!(cache.key("/optimize", &streamed).unwrap(), key);
+        assert_ne!(cache.key("/jobs-match", &payload).unwrap(), key);
+        let edited = serde_json::json!({ "cv_data": { "name": "Ada L." }, "job_url": "https://jobs/1" });
+        assert_ne!(cache.key("/optimize", &edited).unwrap(), key);
+        // Another account of the same tenant has its own answers.
+        let colleague_dir = root.path().join("acme.com").join("bob_at_acme_com");
+        let colleague = ResultCache::new(&colleague_dir, false).unwrap();
+        assert!(colleague.get(&key).await.is_none());
+
+        let bypass = ResultCache::new(&user_dir, true).unwrap();
+        assert!(bypass.get(&key).await.is_none());
+    }
+
+    #[test]
+    fn prune_deletes_expired_answers_only() {
+        let root = tempfile::tempdir().unwrap();
+        let dir = root.path().join("acme.com").join("ada_at_acme_com").join(CACHE_DIR);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("fresh.json"), "{}").unwrap();
+        std::fs::write(dir.join("old.json"), "{}").unwrap();
+        std::fs::File::options()
+            .write(true)
+            .open(dir.join("old.json"))
+            .unwrap()
+            .set_modified(SystemTime::now() - Duration::from_secs(48 * 60 * 60))
+            .unwrap();
+
+        let legacy = root.path().join(CACHE_DIR);
+        std::fs::create_dir_all(&legacy).unwrap();
+        std::fs::write(legacy.join("shared.json"), "{}").unwrap();
+
+        assert_eq!(prune(root.path(), false).unwrap(), (2, 4));
+        assert!(dir.join("old.json").exists() && legacy.join("shared.json").exists());
+        assert_eq!(prune(root.path(), true).unwrap(), (2, 4));
+        assert!(!dir.join("old.json").exists() && dir.join("fresh.json").exists());
+        assert!(!legacy.join("shared.json").exists());
+    }
+}
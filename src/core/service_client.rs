@@ -4,7 +4,14 @@
 use anyhow::{Context, Result};
 use graflog::app_log;
 use reqwest::multipart::{Form, Part};
+use reqwest::StatusCode;
 use std::path::Path;
+use std::time::Instant;
+
+use crate::core::request_cancel::{CancelToken, Cancelled};
+use crate::core::service_cache::ResultCache;
+use crate::core::service_stream::{self, DeltaSender, SseParser};
+use crate::core::service_usage::{self, CallMetrics, UsageRecorder};
 
 use crate::types::{
     cv_data::CvJson,
@@ -25,6 +32,9 @@ const DEFAULT_TIMEOUT_SECS: u64 = 400;
 pub struct ServiceClient {
     client: reqwest::Client,
     base_url: String,
+    usage: Option<UsageRecorder>,
+    cancel: CancelToken,
+    cache: Option<ResultCache>,
 }
 
 impl ServiceClient {
@@ -35,7 +45,152 @@ impl ServiceClient {
             .build()
             .context("Failed to create HTTP client")?;
 
-        Ok(Self { client, base_url })
+        Ok(Self {
+            client,
+            base_url,
+            usage: None,
+            cancel: CancelToken::never(),
+            cache: None,
+        })
+    }
+
+    /// Record every call made by this client for usage accounting.
+    pub fn with_usage(mut self, usage: Option<UsageRecorder>) -> Self {
+        self.usage = usage;
+        self
+    }
+
+    /// Abandon calls as soon as `cancel` fires instead of waiting them out.
+    pub fn with_cancel(mut self, cancel: CancelToken) -> Self {
+        self.cancel = cancel;
+        self
+    }
+
+    /// Answer analysis, optimization and translation calls from `cache` when
+    /// the same request was answered recently (see `core::service_cache`).
+    pub fn with_cache(mut self, cache: Option<ResultCache>) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    /// [`stream_json`](Self::stream_json) through the result cache: the
+    /// cached body when there is one, otherwise the service's answer with the
+    /// key to [`remember`](Self::remember) it under once it proved good.
+    async fn cached_json<T: serde::Serialize + ?Sized>(
+        &self,
+        endpoint: &str,
+        payload: &T,
+        deltas: Option<&DeltaSender>,
+    ) -> Result<(StatusCode, String, Option<String>)> {
+        let key = self.cache.as_ref().and_then(|cache| cache.key(endpoint, payload));
+        if let (Some(cache), Some(key)) = (&self.cache, &key) {
+            if let Some(body) = cache.get(key).await {
+                app_log!(trace, "Answering {} from the result cache", endpoint);
+                return Ok((StatusCode::OK, body, None));
+            }
+        }
+        let (status, body) = self.stream_json(endpoint, payload, deltas).await?;
+        Ok((status, body, key))
+    }
+
+    async fn remember(&self, key: Option<String>, body: &str) {
+        if let (Some(cache), Some(key)) = (&self.cache, key) {
+            cache.put(&key, body).await;
+        }
+    }
+
+    /// Send a request and read the whole response body, recording the call
+    /// when usage accounting is enabled.
+    async fn send(
+        &self,
+        endpoint: &str,
+        request: reqwest::RequestBuilder,
+        request_bytes: usize,
+    ) -> Result<(StatusCode, String)> {
+        self.exchange(endpoint, request, request_bytes, None).await
+    }
+
+    /// [`send`](Self::send), asking for an event stream when `deltas` is set:
+    /// analysis text is forwarded to `deltas` as it arrives and the body
+    /// returned is the final `result` event.
+    async fn exchange(
+        &self,
+        endpoint: &str,
+        request: reqwest::RequestBuilder,
+        request_bytes: usize,
+        deltas: Option<&DeltaSender>,
+    ) -> Result<(StatusCode, String)> {
+        let started = Instant::now();
+        let exchange = async {
+            let request = match deltas {
+                Some(_) => request.header(reqwest::header::ACCEPT, "text/event-stream"),
+                None => request,
+            };
+            let response = request.send().await?;
+            let status = response.status();
+            let streamed = response
+                .headers()
+                .get(reqwest::header::CONTENT_TYPE)
+                .and_then(|v| v.to_str().ok())
+                .is_some_and(|v| v.starts_with("text/event-stream"));
+            match deltas {
+                Some(deltas) if streamed => read_event_stream(response, deltas)
+                    .await
+                    .map(|body| (status, body)),
+                _ => Ok((status, response.text().await?)),
+            }
+        };
+        let result = self.cancel.run(exchange).await;
+
+        if let Some(usage) = &self.usage {
+            let (status, body) = match &result {
+                Ok(Ok((status, body))) => (status.as_u16(), body.as_str()),
+                Ok(Err(e)) => (
+                    e.downcast_ref::<reqwest::Error>()
+                        .and_then(|e| e.status())
+                        .map_or(0, |s| s.as_u16()),
+                    "",
+                ),
+                // nginx's "client closed request".
+                Err(Cancelled) => (499, ""),
+            };
+            usage.record(CallMetrics {
+                endpoint: endpoint.to_string(),
+                status,
+                duration_ms: started.elapsed().as_millis() as i64,
+                request_bytes: request_bytes as i64,
+                response_bytes: body.len() as i64,
+                tokens: service_usage::reported_tokens(body),
+            });
+        }
+        Ok(result??)
+    }
+
+    /// POST a JSON payload to `endpoint`.
+    async fn send_json<T: serde::Serialize + ?Sized>(
+        &self,
+        endpoint: &str,
+        payload: &T,
+    ) -> Result<(StatusCode, String)> {
+        self.stream_json(endpoint, payload, None).await
+    }
+
+    /// POST a JSON payload to `endpoint`, streaming analysis text to `deltas`.
+    async fn stream_json<T: serde::Serialize + ?Sized>(
+        &self,
+        endpoint: &str,
+        payload: &T,
+        deltas: Option<&DeltaSender>,
+    ) -> Result<(StatusCode, String)> {
+        let url = format!("{}{}", self.base_url, endpoint);
+        let body = serde_json::to_vec(payload).context("Failed to serialize request")?;
+        let request_bytes = body.len();
+        let request = self
+            .client
+            .post(&url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(body);
+        self.exchange(endpoint, request, request_bytes, deltas).await
     }
 
     /// 1. CV Upload/Conversion - sends file, receives CvJson
@@ -47,6 +202,7 @@ impl ServiceClient {
             .await
             .with_context(|| format!("Failed to read file: {}", file_path.display()))?;
 
+        let request_bytes = file_content.len();
         let form = Form::new().part(
             "cv_file",
             Part::bytes(file_content)
@@ -57,23 +213,15 @@ impl ServiceClient {
 
         app_log!(info, "Calling CV conversion service: {}", url);
 
-        let response = self
-            .client
-            .post(&url)
-            .multipart(form)
-            .send()
+        let request = self.client.post(&url).multipart(form);
+        let (status, response_text) = self
+            .send(UPLOAD_CV_ENDPOINT, request, request_bytes)
             .await
             .context("HTTP request failed")?;
 
-        let status = response.status();
         app_log!(trace, "Response status: {}", status);
 
         if status.is_success() {
-            let response_text = response
-                .text()
-                .await
-                .context("Failed to read response text")?;
-
             app_log!(info, "Raw CV service response (first 500 chars): {}", &response_text[..response_text.len().min(500)]);
 
             // Parse as a generic JSON value first so we can check "status"
@@ -100,48 +248,63 @@ impl ServiceClient {
                 anyhow::bail!("{}", detail)
             }
         } else {
-            let error_text = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "Unknown error".to_string());
-
-            app_log!(error, "CV service HTTP error {}: {}", status, error_text);
-            anyhow::bail!("CV service error (HTTP {}): {}", status, error_text)
+            app_log!(error, "CV service HTTP error {}: {}", status, response_text);
+            anyhow::bail!("CV service error (HTTP {}): {}", status, response_text)
         }
     }
 
     /// 2. Job Matching - sends CvJson + job_url, receives analysis
     pub async fn match_job(&self, cv_data: &CvJson, job_url: &str) -> Result<JobMatchResponse> {
+        self.match_job_with_description(cv_data, job_url, None).await
+    }
+
+    /// Job matching with pasted job text, for postings that can't be scraped
+    pub async fn match_job_with_description(
+        &self,
+        cv_data: &CvJson,
+        job_url: &str,
+        job_description: Option<&str>,
+    ) -> Result<JobMatchResponse> {
+        self.match_job_streaming(cv_data, job_url, job_description, None)
+            .await
+    }
+
+    /// Job matching that streams the analysis text to `deltas` while it is
+    /// written, when cv-import supports it.
+    pub async fn match_job_streaming(
+        &self,
+        cv_data: &CvJson,
+        job_url: &str,
+        job_description: Option<&str>,
+        deltas: Option<&DeltaSender>,
+    ) -> Result<JobMatchResponse> {
         let url = format!("{}{}", self.base_url, JOBS_MATCH_ENDPOINT);
 
-        let payload = serde_json::json!({
+        let mut payload = serde_json::json!({
             "cv_data": cv_data,
             "job_url": job_url
         });
+        if let Some(desc) = job_description {
+            payload["job_description"] = serde_json::Value::String(desc.to_string());
+        }
+        if deltas.is_some() {
+            payload["stream"] = serde_json::Value::Bool(true);
+        }
 
         app_log!(trace, "Calling job matching service: {}", url);
 
-        let response = self
-            .client
-            .post(&url)
-            .json(&payload)
-            .send()
+        let (status, body, key) = self
+            .cached_json(JOBS_MATCH_ENDPOINT, &payload, deltas)
             .await
             .context("Failed to call job matching service")?;
 
-        let status = response.status();
         if status.is_success() {
-            let match_response: JobMatchResponse = response
-                .json()
-                .await
-                .context("Failed to parse job match response")?;
+            let match_response: JobMatchResponse =
+                serde_json::from_str(&body).context("Failed to parse job match response")?;
+            self.remember(key, &body).await;
             Ok(match_response)
         } else {
-            let error_text = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "Unknown error".to_string());
-            anyhow::bail!("Job matching failed with status {}: {}", status, error_text)
+            anyhow::bail!("Job matching failed with status {}: {}", status, body)
         }
     }
 
@@ -156,32 +319,23 @@ impl ServiceClient {
 
         app_log!(trace, "Calling CV translation service: {}", url);
 
-        let response = self
-            .client
-            .post(&url)
-            .json(&payload)
-            .send()
+        let (status, body, key) = self
+            .cached_json(TRANSLATE_ENDPOINT, &payload, None)
             .await
             .context("Failed to call translation service")?;
 
-        let status = response.status();
         if status.is_success() {
-            let translation_response: CvTranslationResponse = response
-                .json()
-                .await
-                .context("Failed to parse translation response")?;
+            let translation_response: CvTranslationResponse =
+                serde_json::from_str(&body).context("Failed to parse translation response")?;
 
             if translation_response.status == "success" {
+                self.remember(key, &body).await;
                 Ok(translation_response.translated_cv)
             } else {
                 anyhow::bail!("Translation failed: {}", translation_response.status)
             }
         } else {
-            let error_text = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "Unknown error".to_string());
-            anyhow::bail!("Translation failed with status {}: {}", status, error_text)
+            anyhow::bail!("Translation failed with status {}: {}", status, body)
         }
     }
 
@@ -191,6 +345,19 @@ impl ServiceClient {
         cv_data: &CvJson,
         job_url: &str,
         job_description: Option<&str>,
+    ) -> Result<CvOptimizationResponse> {
+        self.optimize_cv_streaming(cv_data, job_url, job_description, None)
+            .await
+    }
+
+    /// CV optimization that streams the analysis text to `deltas` while it
+    /// is written, when cv-import supports it.
+    pub async fn optimize_cv_streaming(
+        &self,
+        cv_data: &CvJson,
+        job_url: &str,
+        job_description: Option<&str>,
+        deltas: Option<&DeltaSender>,
     ) -> Result<CvOptimizationResponse> {
         let url = format!("{}{}", self.base_url, OPTIMIZE_ENDPOINT);
 
@@ -201,33 +368,27 @@ impl ServiceClient {
         if let Some(desc) = job_description {
             payload["job_description"] = serde_json::Value::String(desc.to_string());
         }
+        if deltas.is_some() {
+            payload["stream"] = serde_json::Value::Bool(true);
+        }
 
         app_log!(trace, "Calling CV optimization service: {}", url);
 
-        let response = self
-            .client
-            .post(&url)
-            .json(&payload)
-            .send()
+        let (status, body, key) = self
+            .cached_json(OPTIMIZE_ENDPOINT, &payload, deltas)
             .await
             .context("Failed to call optimization service")?;
 
-        let status = response.status();
         if status.is_success() {
-            let optimization_response: CvOptimizationResponse = response
-                .json()
-                .await
-                .context("Failed to parse optimization response")?;
+            let optimization_response: CvOptimizationResponse =
+                serde_json::from_str(&body).context("Failed to parse optimization response")?;
             if optimization_response.status.starts_with("error:") {
                 anyhow::bail!("{}", optimization_response.status.trim_start_matches("error: "));
             }
+            self.remember(key, &body).await;
             Ok(optimization_response)
         } else {
-            let error_text = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "Unknown error".to_string());
-            anyhow::bail!("Optimization failed with status {}: {}", status, error_text)
+            anyhow::bail!("Optimization failed with status {}: {}", status, body)
         }
     }
 
@@ -253,30 +414,20 @@ impl ServiceClient {
         let url = format!("{}{}", self.base_url, COVER_LETTER_ENDPOINT);
         app_log!(trace, "Calling cover letter service: {}", url);
 
-        let response = self
-            .client
-            .post(&url)
-            .json(&payload)
-            .send()
+        let (status, body) = self
+            .send_json(COVER_LETTER_ENDPOINT, &payload)
             .await
             .context("Failed to call cover letter service")?;
 
-        let status = response.status();
         if status.is_success() {
-            let resp: CoverLetterServiceResponse = response
-                .json()
-                .await
-                .context("Failed to parse cover letter response")?;
+            let resp: CoverLetterServiceResponse =
+                serde_json::from_str(&body).context("Failed to parse cover letter response")?;
             if resp.status.starts_with("error") {
                 anyhow::bail!("{}", resp.status);
             }
             Ok(resp.cover_letter)
         } else {
-            let error_text = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "Unknown error".to_string());
-            anyhow::bail!("Cover letter generation failed with status {}: {}", status, error_text)
+            anyhow::bail!("Cover letter generation failed with status {}: {}", status, body)
         }
     }
 
@@ -300,33 +451,23 @@ impl ServiceClient {
         let url = format!("{}{}", self.base_url, PORTFOLIO_ENDPOINT);
         app_log!(trace, "Calling portfolio generation service: {}", url);
 
-        let response = self
-            .client
-            .post(&url)
-            .json(&payload)
-            .send()
+        let (status, body) = self
+            .send_json(PORTFOLIO_ENDPOINT, &payload)
             .await
             .context("Failed to call portfolio service")?;
 
-        let status = response.status();
         if status.is_success() {
-            let resp: PortfolioServiceResponse = response
-                .json()
-                .await
-                .context("Failed to parse portfolio response")?;
+            let resp: PortfolioServiceResponse =
+                serde_json::from_str(&body).context("Failed to parse portfolio response")?;
             if resp.status.starts_with("error") {
                 anyhow::bail!("{}", resp.status);
             }
             Ok(resp.projects_toml)
         } else {
-            let error_text = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "Unknown error".to_string());
             anyhow::bail!(
                 "Portfolio generation failed with status {}: {}",
                 status,
-                error_text
+                body
             )
         }
     }
@@ -339,26 +480,15 @@ impl ServiceClient {
     {
         let url = format!("{}{}", self.base_url, endpoint);
 
-        let response = self
-            .client
-            .post(&url)
-            .json(payload)
-            .send()
+        let (status, body) = self
+            .send_json(endpoint, payload)
             .await
             .with_context(|| format!("Failed to POST to {}", url))?;
 
-        let status = response.status();
         if status.is_success() {
-            response
-                .json::<R>()
-                .await
-                .context("Failed to parse JSON response")
+            serde_json::from_str::<R>(&body).context("Failed to parse JSON response")
         } else {
-            let error_text = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "Unknown error".to_string());
-            anyhow::bail!("HTTP {} error: {}", status, error_text)
+            anyhow::bail!("HTTP {} error: {}", status, body)
         }
     }
 
@@ -369,25 +499,15 @@ impl ServiceClient {
     {
         let url = format!("{}{}", self.base_url, endpoint);
 
-        let response = self
-            .client
-            .get(&url)
-            .send()
+        let (status, body) = self
+            .send(endpoint, self.client.get(&url), 0)
             .await
             .with_context(|| format!("Failed to GET from {}", url))?;
 
-        let status = response.status();
         if status.is_success() {
-            response
-                .json::<R>()
-                .await
-                .context("Failed to parse JSON response")
+            serde_json::from_str::<R>(&body).context("Failed to parse JSON response")
         } else {
-            let error_text = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "Unknown error".to_string());
-            anyhow::bail!("HTTP {} error: {}", status, error_text)
+            anyhow::bail!("HTTP {} error: {}", status, body)
         }
     }
 
@@ -397,6 +517,7 @@ impl ServiceClient {
         let url = format!("{}{}", self.base_url, UPLOAD_CV_ENDPOINT);
 
         let file_name = format!("{}.txt", profile_name);
+        let request_bytes = cv_text.len();
         let form = Form::new().part(
             "cv_file",
             Part::bytes(cv_text.as_bytes().to_vec())
@@ -407,21 +528,13 @@ impl ServiceClient {
 
         app_log!(info, "Calling CV conversion service (text import): {}", url);
 
-        let response = self
-            .client
-            .post(&url)
-            .multipart(form)
-            .send()
+        let request = self.client.post(&url).multipart(form);
+        let (status, response_text) = self
+            .send(UPLOAD_CV_ENDPOINT, request, request_bytes)
             .await
             .context("HTTP request failed")?;
 
-        let status = response.status();
         if status.is_success() {
-            let response_text = response
-                .text()
-                .await
-                .context("Failed to read response text")?;
-
             let raw: serde_json::Value = serde_json::from_str(&response_text)
                 .with_context(|| format!("CV service returned non-JSON response: {}", response_text))?;
 
@@ -440,11 +553,7 @@ impl ServiceClient {
                 anyhow::bail!("{}", detail)
             }
         } else {
-            let error_text = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "Unknown error".to_string());
-            anyhow::bail!("CV service error (HTTP {}): {}", status, error_text)
+            anyhow::bail!("CV service error (HTTP {}): {}", status, response_text)
         }
     }
 
@@ -461,3 +570,23 @@ impl ServiceClient {
     }
 }
 
+/// Forward `delta` events to `deltas` and return the data of the `result`
+/// event.
+async fn read_event_stream(mut response: reqwest::Response, deltas: &DeltaSender) -> Result<String> {
+    let mut parser = SseParser::default();
+    while let Some(chunk) = response.chunk().await? {
+        for event in parser.feed(&chunk) {
+            match event.event.as_str() {
+                "delta" => {
+                    // The receiver is gone when the client disconnected; keep
+                    // reading so the call still completes for accounting.
+                    let _ = deltas.send(service_stream::delta_text(&event.data));
+                }
+                "result" => return Ok(event.data),
+                "error" => anyhow::bail!("{}", event.data),
+                _ => {}
+            }
+        }
+    }
+    anyhow::bail!("Service stream ended without a result")
+}
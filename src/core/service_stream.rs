@@ -0,0 +1,157 @@
+// src/core/service_stream.rs
+//! Streaming responses from cv-import.
+//!
+//! Job matching and optimization can take a minute, most of it spent writing
+//! the analysis. When asked with `Accept: text/event-stream` and
+//! `"stream": true`, cv-import answers with server-sent events:
+//!
+//! * `delta`  — the next piece of analysis text (raw, or `{"text": "…"}`)
+//! * `result` — the complete JSON response, as the non-streaming call returns it
+//! * `error`  — a message; the call failed
+//!
+//! A cv-import without streaming support ignores the flag and answers with
+//! plain JSON, which is used as the result with no deltas. [`relay`] merges
+//! the deltas of a running call with its outcome for the SSE endpoints.
+
+use futures::stream::{self, Stream};
+use std::future::Future;
+use tokio::sync::mpsc;
+
+pub type DeltaSender = mpsc::UnboundedSender<String>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SseEvent {
+    /// `message` when the event has no `event:` line.
+    pub event: String,
+    pub data: String,
+}
+
+/// Incremental parser for a `text/event-stream` body.
+#[derive(Default)]
+pub struct SseParser {
+    buffer: Vec<u8>,
+}
+
+impl SseParser {
+    /// Feed the next chunk; returns the events it completed. Chunks may split
+    /// lines and UTF-8 sequences anywhere.
+    pub fn feed(&mut self, chunk: &[u8]) -> Vec<SseEvent> {
+        self.buffer.extend_from_slice(chunk);
+        let mut events = Vec::new();
+        while let Some((end, separator)) = find_blank_line(&self.buffer) {
+            let block: Vec<u8> = self.buffer.drain(..end + separator).collect();
+            if let Some(event) = parse_block(&String::from_utf8_lossy(&block[..end])) {
+                events.push(event);
+            }
+        }
+        events
+    }
+}
+
+/// Offset and length of the first blank line (`\n\n` or `\r\n\r\n`).
+fn find_blank_line(buffer: &[u8]) -> Option<(usize, usize)> {
+    (0..buffer.len()).find_map(|i| {
+        if buffer[i..].starts_with(b"\r\n\r\n") {
+            Some((i, 4))
+        } else if buffer[i..].starts_with(b"\n\n") {
+            Some((i, 2))
+        } else {
+            None
+        }
+    })
+}
+
+fn parse_block(block: &str) -> Option<SseEvent> {
+    let mut event = None;
+    let mut data: Vec<&str> = Vec::new();
+    for line in block.lines() {
+        let (field, value) = line.split_once(':').unwrap_or((line, ""));
+        let value = value.strip_prefix(' ').unwrap_or(value);
+        match field {
+            "event" => event = Some(value.to_string()),
+            "data" => data.push(value),
+            _ => {} // comments (`: keep-alive`), `id`, `retry`
+        }
+    }
+    if event.is_none() && data.is_empty() {
+        return None;
+    }
+    Some(SseEvent {
+        event: event.unwrap_or_else(|| "message".to_string()),
+        data: data.join("\n"),
+    })
+}
+
+/// Text of a `delta` event.
+pub fn delta_text(data: &str) -> String {
+    serde_json::from_str::<serde_json::Value>(data)
+        .ok()
+        .and_then(|v| v.get("text").and_then(|t| t.as_str()).map(str::to_string))
+        .unwrap_or_else(|| data.to_string())
+}
+
+pub enum Relayed<T> {
+    Delta(String),
+    Done(T),
+}
+
+/// Deltas sent by `call` as they arrive, then its outcome. `call` must own
+/// the sender of `deltas` so the channel closes when it finishes.
+pub fn relay<T, F>(
+    call: F,
+    deltas: mpsc::UnboundedReceiver<String>,
+) -> impl Stream<Item = Relayed<T>>
+where
+    F: Future<Output = T>,
+{
+    let state = (Some(Box::pin(call)), deltas, None::<T>);
+    stream::unfold(Some(state), |state| async move {
+        let (mut call, mut deltas, mut output) = state?;
+        loop {
+            if let Some(running) = call.as_mut() {
+                let next = tokio::select! {
+                    biased;
+                    Some(text) = deltas.recv() => Relayed::Delta(text),
+                    finished = running => Relayed::Done(finished),
+                };
+                match next {
+                    Relayed::Delta(text) => {
+                        return Some((Relayed::Delta(text), Some((call, deltas, output))));
+                    }
+                    Relayed::Done(finished) => {
+                        call = None;
+                        output = Some(finished);
+                    }
+                }
+            } else {
+                // Drain what was sent before the call finished.
+                return match deltas.recv().await {
+                    Some(text) => Some((Relayed::Delta(text), Some((call, deltas, output)))),
+                    None => output.map(|output| (Relayed::Done(output), None)),
+                };
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_events_split_across_chunks() {
+        let mut parser = SseParser::default();
+        assert!(parser
+            .feed(b": keep-alive\n\nevent: delta\ndata: {\"text\":")
+            .is_empty());
+        assert!(parser.feed(b" \"Strong \xc3").is_empty());
+        let events = parser.feed(
+            b"\xa9quipe\"}\r\n\r\nevent: result\ndata: {\"score\": 80,\ndata: \"analysis\": \"ok\"}\n\n",
+        );
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].event, "delta");
+        assert_eq!(delta_text(&events[0].data), "Strong équipe");
+        assert_eq!(events[1].event, "result");
+        assert_eq!(events[1].data, "{\"score\": 80,\n\"analysis\": \"ok\"}");
+    }
+}
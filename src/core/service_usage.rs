@@ -0,0 +1,212 @@
+// src/core/service_usage.rs
+//! Per-tenant accounting of cv-import service calls.
+//!
+//! Every call a [`crate::core::ServiceClient`] makes on behalf of a user is
+//! recorded in `service_calls` (endpoint, duration, payload sizes, HTTP status
+//! and the LLM token count when the service reports one) so LLM costs can be
+//! charged back to tenants. Recording is fire-and-forget: a failed insert is
+//! logged and never fails the request it describes.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use graflog::app_log;
+use serde::Serialize;
+use sqlx::SqlitePool;
+
+/// Measurements of one service call.
+#[derive(Debug, Clone)]
+pub struct CallMetrics {
+    pub endpoint: String,
+    /// HTTP status, or 0 when no response was received.
+    pub status: u16,
+    pub duration_ms: i64,
+    pub request_bytes: i64,
+    pub response_bytes: i64,
+    pub tokens: Option<i64>,
+}
+
+/// Attributes calls to the tenant (by id) and user they were made for.
+#[derive(Clone)]
+pub struct UsageRecorder {
+    pool: SqlitePool,
+    tenant_id: i64,
+    user_email: String,
+}
+
+impl UsageRecorder {
+    pub fn new(pool: &SqlitePool, tenant_id: i64, user_email: &str) -> Self {
+        Self {
+            pool: pool.clone(),
+            tenant_id,
+            user_email: user_email.to_lowercase(),
+        }
+    }
+
+    pub fn record(&self, call: CallMetrics) {
+        let recorder = self.clone();
+        tokio::spawn(async move {
+            if let Err(e) = insert(&recorder, &call).await {
+                app_log!(
+                    warn,
+                    "Failed to record {} call of {}: {}",
+                    call.endpoint,
+                    recorder.user_email,
+                    e
+                );
+            }
+        });
+    }
+}
+
+async fn insert(recorder: &UsageRecorder, call: &CallMetrics) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO service_calls \
+         (tenant_id, user_email, endpoint, status, duration_ms, request_bytes, response_bytes, tokens, created_at) \
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+    )
+    .bind(recorder.tenant_id)
+    .bind(&recorder.user_email)
+    .bind(&call.endpoint)
+    .bind(call.status as i64)
+    .bind(call.duration_ms)
+    .bind(call.request_bytes)
+    .bind(call.response_bytes)
+    .bind(call.tokens)
+    .bind(Utc::now())
+    .execute(&recorder.pool)
+    .await?;
+    Ok(())
+}
+
+/// Token count reported in a service response body, if any: a top-level
+/// `tokens_used` / `total_tokens`, or a `usage` object with `total_tokens`
+/// or `input_tokens` + `output_tokens`.
+pub fn reported_tokens(body: &str) -> Option<i64> {
+    let value: serde_json::Value = serde_json::from_str(body).ok()?;
+    let int = |v: &serde_json::Value, key: &str| v.get(key).and_then(|t| t.as_i64());
+
+    if let Some(tokens) = int(&value, "tokens_used").or_else(|| int(&value, "total_tokens")) {
+        return Some(tokens);
+    }
+    let usage = value.get("usage")?;
+    int(usage, "total_tokens").or_else(|| {
+        match (int(usage, "input_tokens"), int(usage, "output_tokens")) {
+            (None, None) => None,
+            (input, output) => Some(input.unwrap_or(0) + output.unwrap_or(0)),
+        }
+    })
+}
+
+/// One recorded call, as exported for chargeback.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct ServiceCall {
+    pub created_at: DateTime<Utc>,
+    pub tenant_id: i64,
+    /// Current name of the tenant; empty once it is deleted.
+    pub tenant: String,
+    pub user_email: String,
+    pub endpoint: String,
+    pub status: i64,
+    pub duration_ms: i64,
+    pub request_bytes: i64,
+    pub response_bytes: i64,
+    pub tokens: Option<i64>,
+}
+
+/// Calls aggregated per tenant and endpoint.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct UsageSummary {
+    pub tenant_id: i64,
+    /// Current name of the tenant; empty once it is deleted.
+    pub tenant: String,
+    pub endpoint: String,
+    pub calls: i64,
+    /// Calls without a 2xx response.
+    pub failures: i64,
+    pub total_duration_ms: i64,
+    pub avg_duration_ms: f64,
+    pub request_bytes: i64,
+    pub response_bytes: i64,
+    /// Sum of reported tokens; calls without a count contribute nothing.
+    pub tokens: i64,
+}
+
+/// Totals per tenant and endpoint for calls in `[from, to)`.
+pub async fn summary(
+    pool: &SqlitePool,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    tenant_id: Option<i64>,
+) -> Result<Vec<UsageSummary>> {
+    let rows = sqlx::query_as::<_, UsageSummary>(
+        "SELECT CAST(s.tenant_id AS INTEGER) AS tenant_id, \
+                COALESCE(MAX(t.tenant_name), '') AS tenant, s.endpoint, COUNT(*) AS calls, \
+                SUM(CASE WHEN s.status BETWEEN 200 AND 299 THEN 0 ELSE 1 END) AS failures, \
+                SUM(s.duration_ms) AS total_duration_ms, \
+                AVG(s.duration_ms) AS avg_duration_ms, \
+                SUM(s.request_bytes) AS request_bytes, \
+                SUM(s.response_bytes) AS response_bytes, \
+                COALESCE(SUM(s.tokens), 0) AS tokens \
+         FROM service_calls s LEFT JOIN tenants t ON t.id = s.tenant_id \
+         WHERE s.created_at >= ? AND s.created_at < ? AND (? IS NULL OR s.tenant_id = ?) \
+         GROUP BY s.tenant_id, s.endpoint ORDER BY tenant, s.tenant_id, s.endpoint",
+    )
+    .bind(from)
+    .bind(to)
+    .bind(tenant_id)
+    .bind(tenant_id)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows)
+}
+
+/// Individual calls in `[from, to)`, oldest first.
+pub async fn calls(
+    pool: &SqlitePool,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    tenant_id: Option<i64>,
+) -> Result<Vec<ServiceCall>> {
+    let rows = sqlx::query_as::<_, ServiceCall>(
+        "SELECT s.created_at, CAST(s.tenant_id AS INTEGER) AS tenant_id, \
+                COALESCE(t.tenant_name, '') AS tenant, s.user_email, s.endpoint, s.status, \
+                s.duration_ms, s.request_bytes, s.response_bytes, s.tokens \
+         FROM service_calls s LEFT JOIN tenants t ON t.id = s.tenant_id \
+         WHERE s.created_at >= ? AND s.created_at < ? AND (? IS NULL OR s.tenant_id = ?) \
+         ORDER BY s.id",
+    )
+    .bind(from)
+    .bind(to)
+    .bind(tenant_id)
+    .bind(tenant_id)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows)
+}
+
+/// CSV with a header row, one line per call.
+pub fn to_csv(calls: &[ServiceCall]) -> Result<String> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    for call in calls {
+        writer.serialize(call).context("Failed to write CSV row")?;
+    }
+    let bytes = writer.into_inner().context("Failed to finish CSV")?;
+    String::from_utf8(bytes).context("CSV is not UTF-8")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_token_counts_in_known_shapes() {
+        assert_eq!(reported_tokens(r#"{"status":"success","tokens_used":812}"#), Some(812));
+        assert_eq!(
+            reported_tokens(r#"{"usage":{"input_tokens":700,"output_tokens":120}}"#),
+            Some(820)
+        );
+        assert_eq!(reported_tokens(r#"{"usage":{"total_tokens":42}}"#), Some(42));
+        assert_eq!(reported_tokens(r#"{"status":"success"}"#), None);
+        assert_eq!(reported_tokens("not json"), None);
+    }
+}
@@ -0,0 +1,258 @@
+// src/core/smart_lists.rs
+//! Saved person searches ("smart lists").
+//!
+//! A smart list is a named set of [`Criteria`] saved by an account (see
+//! [`crate::core::owner`]), over that account's persons: skills, tags and
+//! attributes (see [`crate::core::person_tags`]),
+//! availability, experience and onboarding completeness. Only the criteria
+//! are stored; the persons are found again each time the list is evaluated,
+//! so a list follows the profiles as they change.
+
+use anyhow::{bail, Result};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::collections::BTreeMap;
+
+use crate::core::person_tags::{normalize_name, PersonTags};
+
+const MAX_LISTS: i64 = 50;
+const MAX_SKILLS: usize = 20;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Criteria {
+    /// Skills the person must all have, case-insensitively.
+    #[serde(default)]
+    pub skills: Vec<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub attributes: BTreeMap<String, String>,
+    /// Whether the `availability` of `cv_params.toml` must say the person
+    /// is available (`true`) or not (`false`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub available: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_years_experience: Option<f32>,
+    /// Percentage of the onboarding checklist done, 0 to 100.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_completeness: Option<u8>,
+}
+
+/// What [`Criteria::matches`] looks at for one person.
+#[derive(Debug, Clone, Default)]
+pub struct Candidate {
+    pub tags: PersonTags,
+    pub skills: Vec<String>,
+    pub availability: Option<String>,
+    pub years_experience: f32,
+    pub completeness: u8,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SmartList {
+    pub name: String,
+    pub criteria: Criteria,
+    pub updated_at: String,
+}
+
+/// An availability text counts as available unless it starts with a
+/// negation: "no", "not before March", "unavailable", "none".
+pub fn is_available(availability: Option<&str>) -> bool {
+    let Some(text) = availability.map(|a| a.trim().to_lowercase()) else {
+        return false;
+    };
+    let first = text
+        .split(|c: char| !c.is_alphanumeric())
+        .next()
+        .unwrap_or_default();
+    !text.is_empty() && !["no", "not", "unavailable", "none"].contains(&first)
+}
+
+impl Criteria {
+    /// Normalised tags and keys, trimmed skills, bounded numbers.
+    pub fn normalized(&self) -> Result<Self> {
+        if self.skills.len() > MAX_SKILLS {
+            bail!("A smart list has at most {} skills", MAX_SKILLS);
+        }
+        if self.min_completeness.is_some_and(|c| c > 100) {
+            bail!("min_completeness is a percentage, from 0 to 100");
+        }
+        if self.min_years_experience.is_some_and(|y| !(0.0..=60.0).contains(&y)) {
+            bail!("min_years_experience must be between 0 and 60");
+        }
+        let labels = PersonTags {
+            tags: self.tags.iter().cloned().collect(),
+            attributes: self.attributes.clone(),
+        }
+        .normalized()?;
+        let mut skills: Vec<String> = Vec::new();
+        for skill in self.skills.iter().map(|s| s.trim()).filter(|s| !s.is_empty()) {
+            if !skills.iter().any(|s| s.eq_ignore_ascii_case(skill)) {
+                skills.push(skill.to_string());
+            }
+        }
+        Ok(Self {
+            skills,
+            tags: labels.tags.into_iter().collect(),
+            attributes: labels.attributes,
+            ..self.clone()
+        })
+    }
+
+    pub fn matches(&self, person: &Candidate) -> bool {
+        let has_skill = |wanted: &String| person.skills.iter().any(|s| s.eq_ignore_ascii_case(wanted));
+        self.skills.iter().all(has_skill)
+            && self.tags.iter().all(|tag| person.tags.tags.contains(tag))
+            && self.attributes.iter().all(|(key, value)| {
+                person
+                    .tags
+                    .attributes
+                    .get(key)
+                    .is_some_and(|v| v.eq_ignore_ascii_case(value))
+            })
+            && self
+                .available
+                .map_or(true, |a| is_available(person.availability.as_deref()) == a)
+            && self
+                .min_years_experience
+                .map_or(true, |y| person.years_experience >= y)
+            && self.min_completeness.map_or(true, |c| person.completeness >= c)
+    }
+
+    /// Whether matching needs the persons' CV data (skills, experience).
+    pub fn needs_cv_data(&self) -> bool {
+        !self.skills.is_empty() || self.min_years_experience.is_some()
+    }
+}
+
+/// A list name as it appears in URLs: lowercase, dashes for spaces.
+pub fn normalize_list_name(name: &str) -> Result<String> {
+    normalize_name(name, "list name")
+}
+
+type Row = (String, String, String);
+
+fn from_row((name, criteria, updated_at): Row) -> Result<SmartList> {
+    Ok(SmartList {
+        name,
+        criteria: serde_json::from_str(&criteria)?,
+        updated_at,
+    })
+}
+
+pub async fn list(pool: &SqlitePool, owner: &str) -> Result<Vec<SmartList>> {
+    let rows: Vec<Row> = sqlx::query_as(
+        "SELECT name, criteria, updated_at FROM smart_lists WHERE owner = ? ORDER BY name",
+    )
+    .bind(owner)
+    .fetch_all(pool)
+    .await?;
+    rows.into_iter().map(from_row).collect()
+}
+
+pub async fn get(pool: &SqlitePool, owner: &str, name: &str) -> Result<Option<SmartList>> {
+    let row: Option<Row> = sqlx::query_as(
+        "SELECT name, criteria, updated_at FROM smart_lists WHERE owner = ? AND name = ?",
+    )
+    .bind(owner)
+    .bind(name)
+    .fetch_optional(pool)
+    .await?;
+    row.map(from_row).transpose()
+}
+
+/// Create or replace the list `name`; `created_by` is the email saving it.
+pub async fn save(
+    pool: &SqlitePool,
+    owner: &str,
+    name: &str,
+    criteria: &Criteria,
+    created_by: &str,
+) -> Result<SmartList> {
+    let name = normalize_list_name(name)?;
+    let criteria = criteria.normalized()?;
+    let count: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM smart_lists WHERE owner = ? AND name != ?",
+    )
+    .bind(owner)
+    .bind(&name)
+    .fetch_one(pool)
+    .await?;
+    if count >= MAX_LISTS {
+        bail!("An account has at most {} smart lists", MAX_LISTS);
+    }
+    let updated_at = Utc::now().to_rfc3339();
+    sqlx::query(
+        "INSERT INTO smart_lists (owner, name, criteria, created_by, updated_at) \
+         VALUES (?, ?, ?, ?, ?) \
+         ON CONFLICT(owner, name) DO UPDATE SET criteria = excluded.criteria, \
+         created_by = excluded.created_by, updated_at = excluded.updated_at",
+    )
+    .bind(owner)
+    .bind(&name)
+    .bind(serde_json::to_string(&criteria)?)
+    .bind(created_by)
+    .bind(&updated_at)
+    .execute(pool)
+    .await?;
+    Ok(SmartList {
+        name,
+        criteria,
+        updated_at,
+    })
+}
+
+/// Whether a list was removed.
+pub async fn delete(pool: &SqlitePool, owner: &str, name: &str) -> Result<bool> {
+    let result = sqlx::query("DELETE FROM smart_lists WHERE owner = ? AND name = ?")
+        .bind(owner)
+        .bind(name)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn available_java_seniors() {
+        let criteria = Criteria {
+            skills: vec!["java".to_string(), " Java ".to_string()],
+            tags: vec!["Senior".to_string()],
+            available: Some(true),
+            min_completeness: Some(75),
+            ..Default::default()
+        }
+        .normalized()
+        .unwrap();
+        assert_eq!(criteria.skills, vec!["java"]);
+        assert_eq!(criteria.tags, vec!["senior"]);
+
+        let mut jane = Candidate {
+            tags: PersonTags {
+                tags: ["senior"].map(String::from).into(),
+                ..Default::default()
+            },
+            skills: vec!["Java".to_string(), "Kotlin".to_string()],
+            availability: Some("Immediately".to_string()),
+            years_experience: 9.5,
+            completeness: 75,
+        };
+        assert!(criteria.matches(&jane));
+        jane.availability = Some("Not before March".to_string());
+        assert!(!criteria.matches(&jane));
+        jane.availability = None;
+        assert!(!criteria.matches(&jane));
+
+        assert!(is_available(Some("now")));
+        assert!(Criteria {
+            min_completeness: Some(120),
+            ..Default::default()
+        }
+        .normalized()
+        .is_err());
+    }
+}
@@ -2,13 +2,58 @@
 //! Unified template processing engine - consolidates all template functionality
 
 use anyhow::{Context, Result};
+use std::collections::hash_map::DefaultHasher;
+use chrono::{DateTime, Utc};
 use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, LazyLock, Mutex, RwLock};
 
-use crate::core::FsOps;
+use crate::config::OutputFormat;
+use crate::core::image_slots::ImageSlot;
+use crate::core::{template_health, FsOps};
+use crate::types::cv_data::{CvConverter, CvJson};
 use crate::types::response::ConversionResponse;
 use graflog::app_log;
 
+/// Profile name of the person written by
+/// [`TemplateEngine::write_sample_profile`].
+pub const SAMPLE_PROFILE: &str = "sample";
+/// Typst files of the templates directory that every template imports.
+pub const SHARED_FILES: &[&str] = &["font_config.typ", "common.typ"];
+/// Longest chain of `extends` followed.
+const MAX_EXTENDS_DEPTH: usize = 5;
+/// Directory of the temp dir holding the previews of
+/// [`TemplateEngine::store_preview`].
+const PREVIEW_CACHE_DIR: &str = "cvenom-template-previews";
+
+/// Last discovery line logged for each templates directory and each template
+/// that failed to load. Handlers still build engines per request; the
+/// inventory and load failures are logged when they change, not each time.
+static LOGGED_DISCOVERY: LazyLock<Mutex<HashMap<PathBuf, String>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Whether `line` differs from the one last logged for `subject`.
+fn first_logged(subject: &Path, line: &str) -> bool {
+    let mut logged = LOGGED_DISCOVERY
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    if logged.get(subject).is_some_and(|last| last == line) {
+        return false;
+    }
+    logged.insert(subject.to_path_buf(), line.to_string());
+    true
+}
+
+/// Forget the line of `subject`, a template that loads again.
+fn forget_logged(subject: &Path) {
+    LOGGED_DISCOVERY
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .remove(subject);
+}
+
 // ===== Template Models =====
 
 #[derive(Debug, Clone)]
@@ -22,6 +67,9 @@ pub struct TemplateInfo {
 pub struct TemplateManifest {
     pub name: String,
     pub description: Option<String>,
+    /// Translations of `description` keyed by language, from a
+    /// `[descriptions]` table.
+    pub descriptions: Option<HashMap<String, String>>,
     pub author: Option<String>,
     pub version: Option<String>,
     pub main_file: Option<String>,
@@ -31,13 +79,142 @@ pub struct TemplateManifest {
     pub photo_recommended: Option<bool>,
     /// Whether this template renders `company_logo.png` (brand logo) in its layout.
     pub shows_logo: Option<bool>,
+    /// Extra pictures the template renders, uploaded per person.
+    pub image_slots: Option<Vec<ImageSlot>>,
+    /// Output formats besides PDF and DOCX the template renders well, e.g.
+    /// `["html"]`.
+    pub formats: Option<Vec<String>>,
+    /// Font families the template sets, checked by
+    /// `POST /templates/<id>/validate`.
+    pub fonts: Option<Vec<String>>,
+    /// Sample person of the template, relative to its directory: a directory
+    /// of `cv_params.toml` and `experiences_<lang>.typ` files, or a CV JSON
+    /// file. Compiled for previews, compile checks and benchmarks.
+    pub sample: Option<String>,
+    /// Template this one builds on: its files are laid down first and this
+    /// template's files replace them, so a variant only ships what differs.
+    pub extends: Option<String>,
+}
+
+/// A file listed in a template's `dependencies` is in neither the template
+/// nor the person's directory.
+#[derive(Debug)]
+pub struct MissingDependency {
+    pub template: String,
+    pub file: String,
+}
+
+impl std::fmt::Display for MissingDependency {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Template '{}' needs '{}', which is in neither the template nor the profile",
+            self.template, self.file
+        )
+    }
+}
+
+impl std::error::Error for MissingDependency {}
+
+impl TemplateManifest {
+    /// Description in `lang`, falling back to the untranslated one.
+    pub fn description_for(&self, lang: Option<&str>) -> Option<&str> {
+        lang.and_then(|lang| self.descriptions.as_ref()?.get(lang))
+            .or(self.description.as_ref())
+            .map(String::as_str)
+    }
+
+    /// Whether `/generate` offers `format` for this template. PDF and DOCX
+    /// always are; HTML must be listed in `formats`.
+    pub fn supports_format(&self, format: OutputFormat) -> bool {
+        match format {
+            OutputFormat::Pdf | OutputFormat::Docx => true,
+            OutputFormat::Html => self
+                .formats
+                .as_ref()
+                .is_some_and(|formats| formats.iter().any(|f| f.eq_ignore_ascii_case("html"))),
+        }
+    }
+
+    /// The formats [`supports_format`](Self::supports_format) accepts.
+    pub fn output_formats(&self) -> Vec<String> {
+        [OutputFormat::Pdf, OutputFormat::Docx, OutputFormat::Html]
+            .into_iter()
+            .filter(|format| self.supports_format(*format))
+            .map(|format| format.extension().to_string())
+            .collect()
+    }
+}
+
+// ===== Shared State =====
+
+/// Templates discovered once at startup and shared by request handlers,
+/// re-read when one is installed from the registry.
+pub struct SharedTemplateEngine {
+    engine: RwLock<Arc<TemplateEngine>>,
+    /// [`TEMPLATES_EPOCH`] when `engine` was discovered.
+    epoch: AtomicU64,
+}
+
+/// Bumped by [`templates_changed`].
+static TEMPLATES_EPOCH: AtomicU64 = AtomicU64::new(0);
+
+/// The templates directory was changed behind the shared engines' back (see
+/// [`crate::core::asset_sync`]): each re-reads it on its next use.
+pub fn templates_changed() {
+    TEMPLATES_EPOCH.fetch_add(1, Ordering::AcqRel);
+}
+
+impl SharedTemplateEngine {
+    /// Discover the templates in `templates_dir`. An unreadable directory is
+    /// logged and yields no templates rather than keeping the server down.
+    pub fn load(templates_dir: PathBuf) -> Self {
+        let engine = TemplateEngine::new(templates_dir.clone()).unwrap_or_else(|e| {
+            app_log!(error, "Template discovery failed: {}", e);
+            TemplateEngine {
+                templates_dir,
+                templates: Vec::new(),
+                discovered_at: Utc::now(),
+            }
+        });
+        Self {
+            engine: RwLock::new(Arc::new(engine)),
+            epoch: AtomicU64::new(TEMPLATES_EPOCH.load(Ordering::Acquire)),
+        }
+    }
+
+    pub fn current(&self) -> Arc<TemplateEngine> {
+        let epoch = TEMPLATES_EPOCH.load(Ordering::Acquire);
+        if self.epoch.swap(epoch, Ordering::AcqRel) != epoch {
+            if let Err(e) = self.reload() {
+                app_log!(warn, "Re-reading the changed templates failed: {}", e);
+            }
+        }
+        self.engine
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    /// Re-read the templates directory; the previous list stays on error.
+    pub fn reload(&self) -> Result<()> {
+        let templates_dir = self.current().templates_dir.clone();
+        let engine = TemplateEngine::new(templates_dir)?;
+        *self
+            .engine
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = Arc::new(engine);
+        Ok(())
+    }
 }
 
 // ===== Main Template Engine =====
 
+#[derive(Clone)]
 pub struct TemplateEngine {
     templates_dir: PathBuf,
     templates: Vec<TemplateInfo>,
+    discovered_at: DateTime<Utc>,
 }
 
 impl TemplateEngine {
@@ -46,6 +223,7 @@ impl TemplateEngine {
         let mut engine = Self {
             templates_dir,
             templates: Vec::new(),
+            discovered_at: Utc::now(),
         };
         engine.discover_templates()?;
         Ok(engine)
@@ -54,13 +232,16 @@ impl TemplateEngine {
     /// Discover and load all available templates
     fn discover_templates(&mut self) -> Result<()> {
         self.templates.clear();
+        self.discovered_at = Utc::now();
 
         if !self.templates_dir.exists() {
-            app_log!(
-                warn,
+            let line = format!(
                 "Templates directory does not exist: {}",
                 self.templates_dir.display()
             );
+            if first_logged(&self.templates_dir, &line) {
+                app_log!(warn, "{}", line);
+            }
             return Ok(());
         }
 
@@ -77,6 +258,10 @@ impl TemplateEngine {
 
             if path.is_dir() {
                 if let Some(template_name) = path.file_name().and_then(|n| n.to_str()) {
+                    // Registry installs are staged in a dot directory.
+                    if template_name.starts_with('.') {
+                        continue;
+                    }
                     match self.load_template_info(template_name, &path) {
                         Ok(template) => {
                             app_log!(
@@ -85,20 +270,64 @@ impl TemplateEngine {
                                 template.id,
                                 template.path.display()
                             );
+                            forget_logged(&path);
                             self.templates.push(template);
                         }
                         Err(e) => {
-                            app_log!(warn, "Failed to load template {}: {}", template_name, e)
+                            let line = format!("Failed to load template {}: {}", template_name, e);
+                            if first_logged(&path, &line) {
+                                app_log!(warn, "{}", line);
+                            }
                         }
                     }
                 }
             }
         }
 
-        app_log!(info, "Discovered {} templates", self.templates.len());
+        let line = format!(
+            "Discovered {} templates in {}: {}",
+            self.templates.len(),
+            self.templates_dir.display(),
+            self.list_templates().join(", ")
+        );
+        if first_logged(&self.templates_dir, &line) {
+            app_log!(info, "{}", line);
+        } else {
+            app_log!(trace, "{}", line);
+        }
         Ok(())
     }
 
+    /// Add the templates a tenant uploaded to `dir` (see
+    /// [`crate::core::tenant_templates`]), unless the deployment turned them
+    /// off. One named like a server template is left out.
+    pub fn with_tenant_templates(mut self, dir: &Path) -> Self {
+        if !crate::core::config_manager::features().tenant_templates {
+            return self;
+        }
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return self;
+        };
+        for path in entries.flatten().map(|e| e.path()).filter(|p| p.is_dir()) {
+            let Some(id) = path.file_name().and_then(|n| n.to_str()).map(str::to_string) else {
+                continue;
+            };
+            if id.starts_with('.') || self.template_exists(&id) {
+                continue;
+            }
+            match self.load_template_info(&id, &path) {
+                Ok(template) => self.templates.push(template),
+                Err(e) => {
+                    let line = format!("Failed to load tenant template {}: {}", path.display(), e);
+                    if first_logged(&line) {
+                        app_log!(warn, "{}", line);
+                    }
+                }
+            }
+        }
+        self
+    }
+
     /// Load template information from directory
     fn load_template_info(&self, template_id: &str, template_path: &Path) -> Result<TemplateInfo> {
         let manifest_path = template_path.join("manifest.toml");
@@ -112,6 +341,7 @@ impl TemplateEngine {
             TemplateManifest {
                 name: template_id.to_string(),
                 description: None,
+                descriptions: None,
                 author: None,
                 version: None,
                 main_file: None,
@@ -120,6 +350,11 @@ impl TemplateEngine {
                 languages: None,
                 photo_recommended: None,
                 shows_logo: None,
+                image_slots: None,
+                formats: None,
+                fonts: None,
+                sample: None,
+                extends: None,
             }
         };
 
@@ -145,11 +380,72 @@ impl TemplateEngine {
         self.get_template(template_id).is_some()
     }
 
+    /// Templates `template_id` extends, its parent first.
+    pub fn ancestors(&self, template_id: &str) -> Result<Vec<&TemplateInfo>> {
+        let mut ancestors: Vec<&TemplateInfo> = Vec::new();
+        let mut current = self
+            .get_template(template_id)
+            .ok_or_else(|| anyhow::anyhow!("Template '{}' not found", template_id))?;
+        while let Some(parent_id) = current.manifest.extends.as_deref() {
+            if parent_id == template_id
+                || ancestors.iter().any(|a| a.id == parent_id)
+                || ancestors.len() == MAX_EXTENDS_DEPTH
+            {
+                anyhow::bail!("Template '{}' extends itself through '{}'", template_id, parent_id);
+            }
+            current = self.get_template(parent_id).ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Template '{}' extends '{}', which is not installed",
+                    current.id,
+                    parent_id
+                )
+            })?;
+            ancestors.push(current);
+        }
+        Ok(ancestors)
+    }
+
+    /// Whether `file` is in the directory of `template_id` or of a template
+    /// it extends.
+    pub fn provides(&self, template_id: &str, file: &str) -> bool {
+        let Some(template) = self.get_template(template_id) else {
+            return false;
+        };
+        std::iter::once(template)
+            .chain(self.ancestors(template_id).unwrap_or_default())
+            .any(|t| t.path.join(file).is_file())
+    }
+
+    /// Fingerprint of the files `template_id` is compiled from, those of the
+    /// templates it extends included (see [`template_health::fingerprint`]).
+    pub fn fingerprint(&self, template_id: &str) -> Option<u64> {
+        let template = self.get_template(template_id)?;
+        let mut hasher = DefaultHasher::new();
+        for t in std::iter::once(template).chain(self.ancestors(template_id).unwrap_or_default()) {
+            template_health::fingerprint(&self.templates_dir, &t.path).hash(&mut hasher);
+        }
+        Some(hasher.finish())
+    }
+
     /// Get templates directory
     pub fn templates_dir(&self) -> &PathBuf {
         &self.templates_dir
     }
 
+    /// When the templates directory was last read.
+    pub fn discovered_at(&self) -> DateTime<Utc> {
+        self.discovered_at
+    }
+
+    /// Typst `--input` pairs carrying the central text registry for `lang`
+    /// (`text.work_experience=…`), read by `localized()` in common.typ.
+    pub fn text_inputs(&self, lang: &str) -> Vec<(String, String)> {
+        crate::core::translations::strings(lang)
+            .into_iter()
+            .map(|(key, value)| (format!("text.{}", key), value))
+            .collect()
+    }
+
     // ===== Variable Processing =====
 
     /// Process template variables in content (supports both {{var}} and ${var} syntax)
@@ -182,36 +478,22 @@ impl TemplateEngine {
         template_id: &str,
         workspace_dir: &Path,
     ) -> Result<()> {
-        app_log!(trace, "Looking for template: '{}'", template_id);
-        app_log!(
-            trace,
-            "Templates directory: {}",
-            self.templates_dir.display()
-        );
-        app_log!(trace, "Available templates: {:?}", self.list_templates());
-
-        let template = self.get_template(template_id).ok_or_else(|| {
-            anyhow::anyhow!(
-                "Template '{}' not found. Available templates: {:?}. Templates directory: {}",
-                template_id,
-                self.list_templates(),
-                self.templates_dir.display()
-            )
-        })?;
-
-        FsOps::ensure_dir_exists(workspace_dir).await?;
+        self.prepare_template_version_workspace(template_id, None, workspace_dir)
+            .await
+    }
 
-        // Copy all template files to workspace
+    /// Copy the files at the top of a template directory into the workspace.
+    async fn copy_template_files(source: &Path, workspace_dir: &Path) -> Result<()> {
         app_log!(
             trace,
             "Reading template files from: {}",
-            template.path.display()
+            source.display()
         );
 
-        let mut entries = tokio::fs::read_dir(&template.path).await.with_context(|| {
+        let mut entries = tokio::fs::read_dir(source).await.with_context(|| {
             format!(
                 "Failed to read template directory: {}. Check if directory exists and has proper permissions.",
-                template.path.display()
+                source.display()
             )
         })?;
 
@@ -226,6 +508,46 @@ impl TemplateEngine {
                 FsOps::copy_file(&src_path, &dest_path).await?;
             }
         }
+        Ok(())
+    }
+
+    /// Prepare template workspace from a specific (possibly archived) version
+    pub async fn prepare_template_version_workspace(
+        &self,
+        template_id: &str,
+        version: Option<&str>,
+        workspace_dir: &Path,
+    ) -> Result<()> {
+        app_log!(trace, "Looking for template: '{}'", template_id);
+        app_log!(
+            trace,
+            "Templates directory: {}",
+            self.templates_dir.display()
+        );
+        app_log!(trace, "Available templates: {:?}", self.list_templates());
+
+        let template = self.get_template(template_id).ok_or_else(|| {
+            anyhow::anyhow!(
+                "Template '{}' not found. Available templates: {:?}. Templates directory: {}",
+                template_id,
+                self.list_templates(),
+                self.templates_dir.display()
+            )
+        })?;
+
+        let source = match version {
+            Some(version) => crate::core::template_versions::version_dir(template, version)?,
+            None => template.path.clone(),
+        };
+
+        FsOps::ensure_dir_exists(workspace_dir).await?;
+
+        // The templates it extends first, the farthest first, in their
+        // current version; each one's files replace those before it.
+        for ancestor in self.ancestors(template_id)?.into_iter().rev() {
+            Self::copy_template_files(&ancestor.path, workspace_dir).await?;
+        }
+        Self::copy_template_files(&source, workspace_dir).await?;
 
         app_log!(
             trace,
@@ -236,6 +558,58 @@ impl TemplateEngine {
         Ok(())
     }
 
+    /// Make sure every dependency the template declares is in `workspace_dir`,
+    /// copying it from `person_dir` when the template does not ship it. Fails
+    /// with [`MissingDependency`] naming the first file found in neither.
+    pub async fn check_dependencies(
+        &self,
+        template_id: &str,
+        workspace_dir: &Path,
+        person_dir: &Path,
+    ) -> Result<()> {
+        // The workspace holds the manifest of the version being compiled.
+        let manifest = match tokio::fs::read_to_string(workspace_dir.join("manifest.toml")).await {
+            Ok(content) => toml::from_str::<TemplateManifest>(&content).ok(),
+            Err(_) => None,
+        }
+        .or_else(|| self.get_template(template_id).map(|t| t.manifest.clone()));
+        let dependencies = manifest
+            .and_then(|m| m.dependencies)
+            .unwrap_or_default();
+
+        for dependency in dependencies {
+            let missing = || MissingDependency {
+                template: template_id.to_string(),
+                file: dependency.clone(),
+            };
+            let relative = Path::new(&dependency);
+            let contained = relative
+                .components()
+                .all(|c| matches!(c, std::path::Component::Normal(_)));
+            if !contained {
+                return Err(missing().into());
+            }
+            if workspace_dir.join(relative).is_file() {
+                continue;
+            }
+            let from_person = person_dir.join(relative);
+            if !from_person.is_file() {
+                return Err(missing().into());
+            }
+            if let Some(parent) = workspace_dir.join(relative).parent() {
+                FsOps::ensure_dir_exists(parent).await?;
+            }
+            FsOps::copy_file(&from_person, &workspace_dir.join(relative)).await?;
+            app_log!(
+                trace,
+                "Copied dependency {} of {} from the profile",
+                dependency,
+                template_id
+            );
+        }
+        Ok(())
+    }
+
     // ===== Profile Creation Functions =====
 
     /// Create profile from templates (legacy compatibility)
@@ -266,9 +640,6 @@ impl TemplateEngine {
         // Create experiences files
         self.create_experiences_files(&profile_dir).await?;
 
-        // Create README
-        self.create_readme(&profile_dir, profile_name).await?;
-
         app_log!(
             info,
             "Successfully created profile from templates: {}",
@@ -340,11 +711,6 @@ impl TemplateEngine {
             )
         })?;
 
-        // Create README
-        self.create_readme(&profile_dir, profile_name)
-            .await
-            .with_context(|| format!("Failed to create README for profile '{}'", profile_name))?;
-
         app_log!(
             info,
             "Successfully created profile with CV import data: {}",
@@ -406,39 +772,130 @@ show_contact = true\n",
     /// Create experiences files (English and French)
     async fn create_experiences_files(&self, profile_dir: &Path) -> Result<()> {
         // Create experiences_en.typ
-        let experiences_template_path = self.templates_dir.join("experiences_template.typ");
-        if experiences_template_path.exists() {
-            let template_content = FsOps::read_file_safe(&experiences_template_path).await?;
-            FsOps::write_file_safe(&profile_dir.join("experiences_en.typ"), &template_content)
+        let starter = self.starter_experiences().await?;
+        FsOps::write_file_safe(&profile_dir.join("experiences_en.typ"), &starter).await?;
+
+        Ok(())
+    }
+
+    /// Write the sample person of `template_id` into `profile_dir`, with
+    /// experiences in `lang`: the manifest's `sample` fixture, or the starter
+    /// profile new users get when the template declares none.
+    pub async fn write_sample_profile(
+        &self,
+        template_id: &str,
+        lang: &str,
+        profile_dir: &Path,
+    ) -> Result<()> {
+        let template = self
+            .get_template(template_id)
+            .ok_or_else(|| anyhow::anyhow!("Template '{}' not found", template_id))?;
+        FsOps::ensure_dir_exists(profile_dir).await?;
+        let experiences = profile_dir.join(format!("experiences_{}.typ", lang));
+
+        // A template without a sample shows that of the nearest one it extends.
+        let template = std::iter::once(template)
+            .chain(self.ancestors(template_id).unwrap_or_default())
+            .find(|t| t.manifest.sample.is_some())
+            .unwrap_or(template);
+        let Some(sample) = template.manifest.sample.as_deref() else {
+            self.create_cv_params(profile_dir, SAMPLE_PROFILE, Some("Sample Candidate"))
                 .await?;
+            return FsOps::write_file_safe(&experiences, &self.starter_experiences().await?).await;
+        };
+        let relative = Path::new(sample);
+        if !relative
+            .components()
+            .all(|c| matches!(c, std::path::Component::Normal(_)))
+        {
+            anyhow::bail!("Sample '{}' of template '{}' is outside the template", sample, template_id);
+        }
+        let source = template.path.join(relative);
+
+        if source.is_dir() {
+            let mut entries = tokio::fs::read_dir(&source)
+                .await
+                .with_context(|| format!("Failed to read sample {}", source.display()))?;
+            while let Some(entry) = entries.next_entry().await? {
+                if entry.file_type().await?.is_file() {
+                    FsOps::copy_file(&entry.path(), &profile_dir.join(entry.file_name())).await?;
+                }
+            }
+            // Samples without this language show their English experiences.
+            let english = profile_dir.join("experiences_en.typ");
+            if !experiences.exists() && english.exists() {
+                FsOps::copy_file(&english, &experiences).await?;
+            }
         } else {
-            let default_experiences = self.get_default_experiences_content();
-            FsOps::write_file_safe(
-                &profile_dir.join("experiences_en.typ"),
-                &default_experiences,
-            )
-            .await?;
+            let content = FsOps::read_file_safe(&source).await?;
+            let cv: CvJson = serde_json::from_str(&content)
+                .with_context(|| format!("Invalid sample {}", source.display()))?;
+            FsOps::write_file_safe(&profile_dir.join("cv_params.toml"), &CvConverter::to_toml(&cv)?)
+                .await?;
+            FsOps::write_file_safe(&experiences, &CvConverter::to_typst(&cv, lang)?).await?;
+        }
+        if !profile_dir.join("cv_params.toml").exists() || !experiences.exists() {
+            anyhow::bail!("Sample of template '{}' has no cv_params.toml or experiences", template_id);
         }
-
         Ok(())
     }
 
-    /// Create README file
-    async fn create_readme(&self, profile_dir: &Path, profile_name: &str) -> Result<()> {
-        let readme_content = format!(
-            "# {} CV Data\n\n\
-            Add your profile image as `profile.png` in this directory.\n\
-            Add your company logo as `company_logo.png` (optional).\n\n\
-            Edit the following files:\n\
-            - `cv_params.toml` - Personal information, skills, and key insights\n\
-            - `experiences_*.typ` - Work experience for each language (en/fr)\n\n\
-            ## Available Languages\n\
-            - English: experiences_en.typ\n",
-            profile_name
-        );
+    /// Name prefix of the previews of `template_id` in `lang`, and the file
+    /// of the current one: it changes with the template's files, its sample
+    /// and the shared files (see [`template_health::fingerprint`]).
+    fn preview_file(&self, template_id: &str, lang: &str) -> Option<(String, PathBuf)> {
+        let template = self.get_template(template_id)?;
+        // Tenants may upload templates of the same id.
+        let mut location = DefaultHasher::new();
+        template.path.hash(&mut location);
+        let prefix = format!("{}-{:08x}_{}_", template_id, location.finish() as u32, lang);
+        let print = self.fingerprint(template_id)?;
+        let file = std::env::temp_dir()
+            .join(PREVIEW_CACHE_DIR)
+            .join(format!("{}{:016x}.png", prefix, print));
+        Some((prefix, file))
+    }
 
-        FsOps::write_file_safe(&profile_dir.join("README.md"), &readme_content).await?;
-        Ok(())
+    /// The preview of `template_id` in `lang` kept by
+    /// [`store_preview`](Self::store_preview), unless the template changed
+    /// since.
+    pub async fn cached_preview(&self, template_id: &str, lang: &str) -> Option<Vec<u8>> {
+        let (_, file) = self.preview_file(template_id, lang)?;
+        tokio::fs::read(file).await.ok()
+    }
+
+    /// Keep `png` as the preview of `template_id` in `lang`, replacing the
+    /// previews of its earlier versions.
+    pub async fn store_preview(&self, template_id: &str, lang: &str, png: &[u8]) {
+        let Some((prefix, file)) = self.preview_file(template_id, lang) else {
+            return;
+        };
+        let dir = std::env::temp_dir().join(PREVIEW_CACHE_DIR);
+        if let Ok(mut entries) = tokio::fs::read_dir(&dir).await {
+            while let Ok(Some(entry)) = entries.next_entry().await {
+                if entry.file_name().to_string_lossy().starts_with(&prefix) {
+                    let _ = tokio::fs::remove_file(entry.path()).await;
+                }
+            }
+        }
+        let stored = async {
+            FsOps::ensure_dir_exists(&dir).await?;
+            tokio::fs::write(&file, png).await?;
+            anyhow::Ok(())
+        };
+        if let Err(e) = stored.await {
+            app_log!(warn, "Failed to cache the preview of template {}: {}", template_id, e);
+        }
+    }
+
+    /// Placeholder experiences written into profiles created from templates
+    pub async fn starter_experiences(&self) -> Result<String> {
+        let experiences_template_path = self.templates_dir.join("experiences_template.typ");
+        if experiences_template_path.exists() {
+            FsOps::read_file_safe(&experiences_template_path).await
+        } else {
+            Ok(self.get_default_experiences_content())
+        }
     }
 
     /// Get default experiences content for English
@@ -548,6 +1005,16 @@ mod tests {
         }
     }
 
+    #[test]
+    fn descriptions_come_from_the_manifest_in_the_requested_language() {
+        let engine = TemplateEngine::new(templates_dir()).unwrap();
+        let t = engine.get_template("keyteo").unwrap();
+        let english = t.manifest.description_for(None).unwrap();
+        assert_eq!(t.manifest.description_for(Some("en")), Some(english));
+        assert_eq!(t.manifest.description_for(Some("es")), Some(english));
+        assert_ne!(t.manifest.description_for(Some("fr")), Some(english));
+    }
+
     // ── All templates: files on disk ─────────────────────────────────────────
 
     #[test]
@@ -569,6 +1036,18 @@ mod tests {
         assert!(failures.is_empty(), "Template file checks failed:\n{}", failures.join("\n"));
     }
 
+    #[tokio::test]
+    async fn all_templates_ship_a_sample() {
+        let engine = TemplateEngine::new(templates_dir()).unwrap();
+        let root = tempfile::tempdir().unwrap();
+        for t in &engine.templates {
+            assert!(t.manifest.sample.is_some(), "{}: no sample in manifest", t.id);
+            let profile = root.path().join(&t.id);
+            engine.write_sample_profile(&t.id, "fr", &profile).await.unwrap();
+            assert!(profile.join("experiences_fr.typ").is_file(), "{}", t.id);
+        }
+    }
+
     // ── Error handling ───────────────────────────────────────────────────────
 
     #[test]
@@ -589,6 +1068,88 @@ mod tests {
         assert!(result.unwrap_err().to_string().contains("nonexistent_xyz"));
     }
 
+    #[tokio::test]
+    async fn dependencies_come_from_template_or_person() {
+        let root = tempfile::tempdir().unwrap();
+        let template = root.path().join("templates/badge");
+        std::fs::create_dir_all(&template).unwrap();
+        std::fs::write(
+            template.join("manifest.toml"),
+            "name = \"badge\"\ndependencies = [\"template.typ\", \"badge.svg\"]",
+        )
+        .unwrap();
+        std::fs::write(template.join("template.typ"), "").unwrap();
+        let person = root.path().join("person");
+        std::fs::create_dir_all(&person).unwrap();
+        let workspace = root.path().join("workspace");
+
+        let engine = TemplateEngine::new(root.path().join("templates")).unwrap();
+        engine
+            .prepare_template_workspace("badge", &workspace)
+            .await
+            .unwrap();
+        let err = engine
+            .check_dependencies("badge", &workspace, &person)
+            .await
+            .unwrap_err();
+        let missing = err.downcast_ref::<MissingDependency>().unwrap();
+        assert_eq!(missing.file, "badge.svg");
+
+        std::fs::write(person.join("badge.svg"), "<svg/>").unwrap();
+        engine
+            .check_dependencies("badge", &workspace, &person)
+            .await
+            .unwrap();
+        assert!(workspace.join("badge.svg").is_file());
+    }
+
+    #[tokio::test]
+    async fn child_templates_override_the_files_of_their_parent() {
+        let root = tempfile::tempdir().unwrap();
+        let templates = root.path().join("templates");
+        for (id, manifest, files) in [
+            ("base", "name = \"base\"", &[("main.typ", "base"), ("colors.typ", "blue")][..]),
+            ("red", "name = \"red\"\nextends = \"base\"", &[("colors.typ", "red")][..]),
+            ("loop", "name = \"loop\"\nextends = \"loop\"", &[][..]),
+        ] {
+            std::fs::create_dir_all(templates.join(id)).unwrap();
+            std::fs::write(templates.join(id).join("manifest.toml"), manifest).unwrap();
+            for (name, content) in files {
+                std::fs::write(templates.join(id).join(name), content).unwrap();
+            }
+        }
+        let engine = TemplateEngine::new(templates.clone()).unwrap();
+        let workspace = root.path().join("workspace");
+        engine.prepare_template_workspace("red", &workspace).await.unwrap();
+        assert_eq!(std::fs::read_to_string(workspace.join("main.typ")).unwrap(), "base");
+        assert_eq!(std::fs::read_to_string(workspace.join("colors.typ")).unwrap(), "red");
+        assert!(engine.provides("red", "main.typ"));
+
+        let before = engine.fingerprint("red");
+        std::fs::write(templates.join("base/main.typ"), "base v2").unwrap();
+        assert_ne!(engine.fingerprint("red"), before);
+
+        assert!(engine.ancestors("loop").is_err());
+    }
+
+    #[tokio::test]
+    async fn previews_are_cached_until_the_template_changes() {
+        let root = tempfile::tempdir().unwrap();
+        let template = root.path().join("templates/card");
+        std::fs::create_dir_all(&template).unwrap();
+        std::fs::write(template.join("manifest.toml"), "name = \"card\"").unwrap();
+        std::fs::write(template.join("main.typ"), "= Card").unwrap();
+        let engine = TemplateEngine::new(root.path().join("templates")).unwrap();
+
+        assert!(engine.cached_preview("card", "en").await.is_none());
+        engine.store_preview("card", "en", b"png").await;
+        assert_eq!(engine.cached_preview("card", "en").await.unwrap(), b"png");
+        assert!(engine.cached_preview("card", "fr").await.is_none());
+
+        std::fs::write(template.join("main.typ"), "= Card v2").unwrap();
+        assert!(engine.cached_preview("card", "en").await.is_none());
+    }
+
     // ── Variable substitution ────────────────────────────────────────────────
 
     #[test]
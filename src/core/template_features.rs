@@ -0,0 +1,110 @@
+// src/core/template_features.rs
+//! Generation features a template can support.
+//!
+//! Manifests list free-form `features` for the catalog; the ones in
+//! [`VOCABULARY`] also mean something at generation time. A generate request
+//! may ask for some of them: those the chosen template does not support are
+//! left out with a warning. Every vocabulary feature reaches Typst as
+//! `--input feature.<name>=true|false`, read in templates with
+//! `sys.inputs.at("feature.qr_code", default: "false") == "true"`.
+//!
+//! Older manifests say `photo_recommended` / `shows_logo` instead of listing
+//! `photo` / `logo`; both spellings count.
+
+use crate::core::template_engine::TemplateManifest;
+
+pub const VOCABULARY: &[&str] = &[
+    "photo",
+    "logo",
+    "projects",
+    "skill_matrix",
+    "qr_code",
+    "client_logos",
+];
+
+/// Vocabulary features `manifest` supports, in vocabulary order.
+pub fn supported(manifest: &TemplateManifest) -> Vec<&'static str> {
+    let listed = manifest.features.as_deref().unwrap_or_default();
+    VOCABULARY
+        .iter()
+        .copied()
+        .filter(|feature| {
+            listed.iter().any(|f| f == feature)
+                || (*feature == "photo" && manifest.photo_recommended == Some(true))
+                || (*feature == "logo" && manifest.shows_logo == Some(true))
+        })
+        .collect()
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct FeatureSelection {
+    pub enabled: Vec<String>,
+    /// One message per requested feature that was left out.
+    pub warnings: Vec<String>,
+}
+
+/// Features to enable when `requested` are asked of `template_id`.
+pub fn select(
+    template_id: &str,
+    manifest: &TemplateManifest,
+    requested: &[String],
+) -> FeatureSelection {
+    let supported = supported(manifest);
+    let mut selection = FeatureSelection::default();
+    for feature in requested {
+        let feature = feature.trim().to_lowercase();
+        if selection.enabled.contains(&feature) {
+            continue;
+        }
+        if !VOCABULARY.contains(&feature.as_str()) {
+            selection.warnings.push(format!(
+                "Unknown feature '{}' (known: {})",
+                feature,
+                VOCABULARY.join(", ")
+            ));
+        } else if supported.contains(&feature.as_str()) {
+            selection.enabled.push(feature);
+        } else {
+            selection.warnings.push(format!(
+                "Template '{}' does not support '{}'; it was left out",
+                template_id, feature
+            ));
+        }
+    }
+    selection
+}
+
+/// `feature.<name>` Typst inputs for every vocabulary feature.
+pub fn typst_inputs<S: AsRef<str>>(enabled: &[S]) -> Vec<(String, String)> {
+    VOCABULARY
+        .iter()
+        .map(|feature| {
+            let on = enabled.iter().any(|f| f.as_ref() == *feature);
+            (format!("feature.{}", feature), on.to_string())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drops_unsupported_features_with_a_warning() {
+        let manifest: TemplateManifest = toml::from_str(
+            "name = \"tech\"\nfeatures = [\"two_column\", \"projects\"]\nphoto_recommended = true",
+        )
+        .unwrap();
+        assert_eq!(supported(&manifest), vec!["photo", "projects"]);
+
+        let requested = ["Projects", "qr_code", "sparkles", "photo"].map(String::from);
+        let selection = select("tech", &manifest, &requested);
+        assert_eq!(selection.enabled, vec!["projects", "photo"]);
+        assert_eq!(selection.warnings.len(), 2);
+        assert!(selection.warnings[0].contains("'qr_code'"));
+
+        let inputs = typst_inputs(&selection.enabled);
+        assert!(inputs.contains(&("feature.photo".to_string(), "true".to_string())));
+        assert!(inputs.contains(&("feature.logo".to_string(), "false".to_string())));
+    }
+}
@@ -0,0 +1,402 @@
+//! Background compile check for templates.
+//!
+//! A template that no longer compiles would otherwise only surface when a
+//! tenant hits "generate". The watcher in `start_web_server` fingerprints every
+//! template directory (plus the shared Typst files) on an interval; whenever a
+//! fingerprint changes — including the first pass after startup — the template
+//! is compiled against its sample person (the manifest's `sample`, or else the
+//! profile new users get: `profile_template.toml` + `experiences_template.typ`)
+//! in a scratch dir.
+//!
+//! Failing templates are recorded as unhealthy: they are hidden from the
+//! template listing, generation requests for them fall back to `default`, and
+//! the admin gets one alert per healthy → unhealthy transition.
+//!
+//! `POST /templates/<id>/validate` runs the same compile on demand, for a
+//! tenant's uploaded templates too, and reports its diagnostics instead of
+//! recording health.
+//!
+//! * `TEMPLATE_HEALTH_INTERVAL_SECS` — poll interval (default 60, `0` disables)
+//! * `TEMPLATE_HEALTH_TIMEOUT_SECS`  — per-compile timeout (default 60)
+
+use anyhow::{Context, Result};
+use graflog::app_log;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::path::Path;
+use std::sync::{LazyLock, RwLock};
+use std::time::Duration;
+
+use serde::Serialize;
+
+use crate::core::template_engine::{SAMPLE_PROFILE, SHARED_FILES};
+use crate::core::typst_compiler::{Diagnostic, DiagnosticSeverity};
+use crate::core::{template_versions, typst_compiler, TemplateEngine};
+
+const DEFAULT_INTERVAL_SECS: u64 = 60;
+const DEFAULT_TIMEOUT_SECS: u64 = 60;
+
+/// Template id → last compile error.
+static UNHEALTHY: LazyLock<RwLock<HashMap<String, String>>> =
+    LazyLock::new(|| RwLock::new(HashMap::new()));
+
+/// Poll interval, or `None` when the check is disabled.
+pub fn check_interval() -> Option<Duration> {
+    let secs = std::env::var("TEMPLATE_HEALTH_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(DEFAULT_INTERVAL_SECS);
+    (secs > 0).then(|| Duration::from_secs(secs))
+}
+
+fn compile_timeout() -> Duration {
+    let secs = std::env::var("TEMPLATE_HEALTH_TIMEOUT_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(DEFAULT_TIMEOUT_SECS);
+    Duration::from_secs(secs.max(1))
+}
+
+/// Whether `template_id` passed its last compile check (unchecked = healthy).
+pub fn is_healthy(template_id: &str) -> bool {
+    !UNHEALTHY
+        .read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .contains_key(template_id)
+}
+
+/// Snapshot of unhealthy templates and their last error.
+pub fn unhealthy() -> HashMap<String, String> {
+    UNHEALTHY
+        .read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .clone()
+}
+
+/// Record a check result. Returns `true` when the template just turned unhealthy.
+fn record(template_id: &str, result: &Result<(), String>) -> bool {
+    let mut map = UNHEALTHY
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    match result {
+        Ok(()) => {
+            map.remove(template_id);
+            false
+        }
+        Err(e) => map.insert(template_id.to_string(), e.clone()).is_none(),
+    }
+}
+
+fn hash_file(hasher: &mut DefaultHasher, path: &Path) {
+    if let Ok(meta) = std::fs::metadata(path) {
+        path.file_name().hash(hasher);
+        meta.len().hash(hasher);
+        meta.modified()
+            .ok()
+            .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+            .map(|d| d.as_nanos())
+            .hash(hasher);
+    }
+}
+
+fn hash_files(hasher: &mut DefaultHasher, dir: &Path) {
+    let mut files: Vec<_> = std::fs::read_dir(dir)
+        .map(|rd| {
+            rd.flatten()
+                .map(|e| e.path())
+                .filter(|p| p.is_file())
+                .collect()
+        })
+        .unwrap_or_default();
+    files.sort();
+    for file in &files {
+        hash_file(hasher, file);
+    }
+}
+
+/// Fingerprint of a template's files, those of its sample directory and the
+/// shared files it is compiled with.
+pub fn fingerprint(templates_dir: &Path, template_dir: &Path) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    hash_files(&mut hasher, template_dir);
+    let mut subdirs: Vec<_> = std::fs::read_dir(template_dir)
+        .map(|rd| {
+            rd.flatten()
+                .map(|e| e.path())
+                .filter(|p| p.is_dir() && !p.ends_with(template_versions::VERSIONS_DIR))
+                .collect()
+        })
+        .unwrap_or_default();
+    subdirs.sort();
+    for dir in &subdirs {
+        hash_files(&mut hasher, dir);
+    }
+    for shared in SHARED_FILES {
+        hash_file(&mut hasher, &templates_dir.join(shared));
+    }
+    hasher.finish()
+}
+
+/// Compile `template_id` against the sample profile in a scratch directory.
+/// Runs typst with an explicit working dir, so it never touches the shared
+/// `tmp_workspace` used by live generations.
+pub async fn check_template(templates_dir: &Path, template_id: &str) -> Result<(), String> {
+    let scratch =
+        std::env::temp_dir().join(format!("cvenom-template-check-{}", uuid::Uuid::new_v4()));
+    let result = compile_in(templates_dir, template_id, &scratch).await;
+    let _ = tokio::fs::remove_dir_all(&scratch).await;
+    result
+}
+
+async fn compile_in(templates_dir: &Path, template_id: &str, scratch: &Path) -> Result<(), String> {
+    let engine = TemplateEngine::new(templates_dir.to_path_buf()).map_err(|e| format!("{:#}", e))?;
+    prepare_sample_workspace(&engine, template_id, "en", scratch)
+        .await
+        .map_err(|e| format!("{:#}", e))?;
+    compile_workspace(scratch, "en").await
+}
+
+/// Compile a prepared workspace's `main.typ`; the PDF is discarded.
+pub async fn compile_workspace(workspace: &Path, lang: &str) -> Result<(), String> {
+    typst_compiler::compile_with_timeout(
+        workspace,
+        "main.typ",
+        vec![("lang".to_string(), lang.to_string())],
+        typst_compiler::Output::Pdf,
+        compile_timeout(),
+    )
+    .await
+    .map(|_| ())
+    .map_err(|e| e.to_string())
+}
+
+async fn prepare_sample_workspace(
+    engine: &TemplateEngine,
+    template_id: &str,
+    lang: &str,
+    workspace: &Path,
+) -> Result<()> {
+    engine
+        .prepare_template_workspace(template_id, workspace)
+        .await?;
+
+    for shared in SHARED_FILES {
+        let src = engine.templates_dir().join(shared);
+        if src.exists() {
+            tokio::fs::copy(&src, workspace.join(shared)).await?;
+        }
+    }
+
+    let sample_dir = workspace.join(SAMPLE_PROFILE);
+    engine
+        .write_sample_profile(template_id, lang, &sample_dir)
+        .await
+        .context("Failed to create sample profile")?;
+    tokio::fs::copy(
+        sample_dir.join("cv_params.toml"),
+        workspace.join("cv_params.toml"),
+    )
+    .await?;
+    tokio::fs::copy(
+        sample_dir.join(format!("experiences_{}.typ", lang)),
+        workspace.join("experiences.typ"),
+    )
+    .await?;
+    Ok(())
+}
+
+/// Outcome of [`validate`].
+#[derive(Debug, Clone, Serialize)]
+pub struct TemplateValidation {
+    pub template: String,
+    pub lang: String,
+    /// The sample compiled without errors.
+    pub valid: bool,
+    /// Typst errors and warnings of the compilation.
+    pub diagnostics: Vec<Diagnostic>,
+    /// Variables the template uses but nothing defines.
+    pub missing_variables: Vec<String>,
+    /// Font families of the manifest's `fonts`, or that Typst looked for,
+    /// not installed on the server (Typst falls back to another font).
+    pub missing_fonts: Vec<String>,
+    /// What kept the sample from compiling at all: a missing dependency, a
+    /// broken sample, a timeout.
+    pub problems: Vec<String>,
+}
+
+/// The names Typst reported with `prefix` (e.g. "unknown variable: x").
+fn reported(diagnostics: &[Diagnostic], prefix: &str) -> Vec<String> {
+    let mut names: Vec<String> = diagnostics
+        .iter()
+        .filter_map(|d| d.message.strip_prefix(prefix))
+        .map(|name| name.trim().to_string())
+        .collect();
+    names.sort();
+    names.dedup();
+    names
+}
+
+/// Dry-compile `template_id` of `engine` against its sample person in
+/// `lang`, in a scratch directory. Health is left alone.
+pub async fn validate(engine: &TemplateEngine, template_id: &str, lang: &str) -> TemplateValidation {
+    let scratch =
+        std::env::temp_dir().join(format!("cvenom-template-validate-{}", uuid::Uuid::new_v4()));
+    let mut problems = Vec::new();
+    let mut diagnostics = Vec::new();
+    let prepared = async {
+        prepare_sample_workspace(engine, template_id, lang, &scratch).await?;
+        engine
+            .check_dependencies(template_id, &scratch, &scratch.join(SAMPLE_PROFILE))
+            .await
+    }
+    .await;
+    match prepared {
+        Err(e) => problems.push(format!("{:#}", e)),
+        Ok(()) => match typst_compiler::compile_with_timeout(
+            &scratch,
+            "main.typ",
+            vec![("lang".to_string(), lang.to_string())],
+            typst_compiler::Output::Pdf,
+            compile_timeout(),
+        )
+        .await
+        {
+            Ok(compiled) => diagnostics = compiled.warnings,
+            Err(e) => match e.downcast::<typst_compiler::CompileFailed>() {
+                Ok(failed) => diagnostics = failed.diagnostics,
+                Err(e) => problems.push(format!("{:#}", e)),
+            },
+        },
+    }
+    let _ = tokio::fs::remove_dir_all(&scratch).await;
+
+    let declared = engine
+        .get_template(template_id)
+        .and_then(|t| t.manifest.fonts.clone())
+        .unwrap_or_default();
+    let mut missing_fonts = typst_compiler::missing_fonts(&declared);
+    missing_fonts.extend(reported(&diagnostics, "unknown font family:"));
+    missing_fonts.sort();
+    missing_fonts.dedup();
+    TemplateValidation {
+        template: template_id.to_string(),
+        lang: lang.to_string(),
+        valid: problems.is_empty()
+            && !diagnostics.iter().any(|d| d.severity == DiagnosticSeverity::Error),
+        missing_variables: reported(&diagnostics, "unknown variable:"),
+        missing_fonts,
+        diagnostics,
+        problems,
+    }
+}
+
+/// One watcher pass: recompile templates whose fingerprint changed since the
+/// last pass and update health. `seen` carries fingerprints between passes.
+pub async fn run_pass(templates_dir: &Path, seen: &mut HashMap<String, u64>) {
+    let engine = match TemplateEngine::new(templates_dir.to_path_buf()) {
+        Ok(engine) => engine,
+        Err(e) => {
+            app_log!(
+                error,
+                "[templates] Health check could not list templates: {}",
+                e
+            );
+            return;
+        }
+    };
+
+    let ids = engine.list_templates();
+    seen.retain(|id, _| ids.contains(id));
+    UNHEALTHY
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .retain(|id, _| ids.contains(id));
+
+    for id in ids {
+        let Some(info) = engine.get_template(&id) else {
+            continue;
+        };
+        let print = engine.fingerprint(&id).unwrap_or_else(|| fingerprint(templates_dir, &info.path));
+        if seen.get(&id) == Some(&print) {
+            continue;
+        }
+        seen.insert(id.clone(), print);
+
+        let result = check_template(templates_dir, &id).await;
+        let newly_unhealthy = record(&id, &result);
+        match &result {
+            Ok(()) => {
+                app_log!(info, "[templates] '{}' compiled against sample data", id);
+                if let Err(e) = template_versions::archive(info, templates_dir) {
+                    app_log!(warn, "[templates] Failed to archive '{}': {}", id, e);
+                }
+            }
+            Err(e) => app_log!(
+                error,
+                "[templates] '{}' failed its compile check: {}",
+                id,
+                e
+            ),
+        }
+        if newly_unhealthy {
+            crate::email::notify_admin(crate::email::EmailKind::AdminActivity {
+                user_email: "system".to_string(),
+                action: "Template unhealthy".to_string(),
+                detail: format!(
+                    "template={} is hidden until it compiles again: {}",
+                    id,
+                    result.err().unwrap_or_default()
+                ),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn record_alerts_only_on_transition() {
+        let id = "health-test-template";
+        assert!(is_healthy(id));
+        assert!(record(id, &Err("boom".to_string())));
+        assert!(!is_healthy(id));
+        // Still failing — no second alert.
+        assert!(!record(id, &Err("boom again".to_string())));
+        assert!(!record(id, &Ok(())));
+        assert!(is_healthy(id));
+    }
+
+    #[test]
+    fn fingerprint_changes_when_a_file_changes() {
+        let tmp = tempfile::tempdir().unwrap();
+        let tpl = tmp.path().join("demo");
+        std::fs::create_dir_all(&tpl).unwrap();
+        std::fs::write(tpl.join("main.typ"), "= Hello").unwrap();
+        let before = fingerprint(tmp.path(), &tpl);
+
+        std::fs::write(tpl.join("main.typ"), "= Hello, world").unwrap();
+        assert_ne!(before, fingerprint(tmp.path(), &tpl));
+    }
+
+    #[test]
+    fn reported_names_are_read_from_diagnostics() {
+        let diagnostic = |severity, message: &str| Diagnostic {
+            severity,
+            message: message.to_string(),
+            file: Some("main.typ".to_string()),
+            line: Some(3),
+            hints: Vec::new(),
+        };
+        let diagnostics = [
+            diagnostic(DiagnosticSeverity::Error, "unknown variable: headline"),
+            diagnostic(DiagnosticSeverity::Error, "unknown variable: headline"),
+            diagnostic(DiagnosticSeverity::Warning, "unknown font family: inter"),
+            diagnostic(DiagnosticSeverity::Error, "expected expression"),
+        ];
+        assert_eq!(reported(&diagnostics, "unknown variable:"), vec!["headline"]);
+        assert_eq!(reported(&diagnostics, "unknown font family:"), vec!["inter"]);
+    }
+}
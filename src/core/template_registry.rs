@@ -0,0 +1,362 @@
+// src/core/template_registry.rs
+//! Curated templates from a remote registry.
+//!
+//! The registry at `TEMPLATE_REGISTRY_URL` serves `catalog.json`, signed with
+//! HMAC-SHA256 under `TEMPLATE_REGISTRY_KEY` in an `X-Registry-Signature`
+//! header. Each catalog entry points at a bundle — JSON mapping file names to
+//! base64 contents — and carries its SHA-256. Installing re-fetches the
+//! catalog, checks both the catalog signature and the bundle hash, validates
+//! the file list, and only then writes the template: into a staging
+//! directory first, renamed over the installed version once complete.
+//! Downloads stop at [`MAX_BUNDLE_BYTES`]. The template health watcher picks
+//! the template up from there.
+//!
+//! Both variables must be set; an unsigned catalog is never trusted.
+
+use anyhow::{anyhow, bail, Context, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use graflog::app_log;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::core::template_versions::VERSIONS_DIR;
+use crate::core::FsOps;
+use crate::utils::{constant_time_eq, get_file_extension, hmac_sha256_hex, sha256_hex};
+
+const SIGNATURE_HEADER: &str = "X-Registry-Signature";
+const ALLOWED_EXTENSIONS: &[&str] = &["typ", "toml", "png", "jpg", "svg"];
+/// Shared Typst files every template relies on; a bundle must not replace them.
+const RESERVED_IDS: &[&str] = &["common", "font_config"];
+const MAX_BUNDLE_BYTES: usize = 5 * 1024 * 1024;
+/// Below the templates directory, so installs are renamed within one file
+/// system; dot-prefixed, so template discovery skips it.
+const STAGING_DIR: &str = ".registry_staging";
+/// Written into templates installed from the registry: the catalog entry
+/// they came from. Templates without it ship with the server and are never
+/// overwritten.
+pub const INSTALLED_MARKER: &str = ".registry.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CatalogEntry {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub version: Option<String>,
+    #[serde(default)]
+    pub languages: Vec<String>,
+    /// Absolute, or relative to the registry URL.
+    pub bundle_url: String,
+    /// Hex SHA-256 of the bundle bytes.
+    pub sha256: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Bundle {
+    files: BTreeMap<String, String>,
+}
+
+pub struct Registry {
+    base_url: String,
+    key: Vec<u8>,
+    client: reqwest::Client,
+}
+
+impl Registry {
+    /// `None` unless both `TEMPLATE_REGISTRY_URL` and `TEMPLATE_REGISTRY_KEY`
+    /// are set.
+    pub fn from_env() -> Option<Self> {
+        let base_url = std::env::var("TEMPLATE_REGISTRY_URL")
+            .ok()
+            .map(|v| v.trim().trim_end_matches('/').to_string())
+            .filter(|v| !v.is_empty())?;
+        let key = std::env::var("TEMPLATE_REGISTRY_KEY")
+            .ok()
+            .filter(|v| !v.trim().is_empty())?;
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()
+            .ok()?;
+        Some(Self {
+            base_url,
+            key: key.into_bytes(),
+            client,
+        })
+    }
+
+    fn resolve(&self, url: &str) -> String {
+        if url.starts_with("https://") || url.starts_with("http://") {
+            url.to_string()
+        } else {
+            format!("{}/{}", self.base_url, url.trim_start_matches('/'))
+        }
+    }
+
+    async fn fetch(&self, url: &str) -> Result<reqwest::Response> {
+        let response = self
+            .client
+            .get(url)
+            .send()
+            .await
+            .with_context(|| format!("Registry unreachable: {}", url))?;
+        if !response.status().is_success() {
+            bail!("Registry returned {} for {}", response.status(), url);
+        }
+        Ok(response)
+    }
+
+    /// The body of `response`, refused as soon as it passes
+    /// [`MAX_BUNDLE_BYTES`] rather than after the whole download.
+    async fn read_capped(mut response: reqwest::Response) -> Result<Vec<u8>> {
+        let too_large = || anyhow!("Registry answer is larger than {} bytes", MAX_BUNDLE_BYTES);
+        if response
+            .content_length()
+            .is_some_and(|len| len > MAX_BUNDLE_BYTES as u64)
+        {
+            return Err(too_large());
+        }
+        let mut body = Vec::new();
+        while let Some(chunk) = response.chunk().await? {
+            if body.len() + chunk.len() > MAX_BUNDLE_BYTES {
+                return Err(too_large());
+            }
+            body.extend_from_slice(&chunk);
+        }
+        Ok(body)
+    }
+
+    /// The catalog, after checking its signature.
+    pub async fn catalog(&self) -> Result<Vec<CatalogEntry>> {
+        let response = self.fetch(&self.resolve("catalog.json")).await?;
+        let signature = response
+            .headers()
+            .get(SIGNATURE_HEADER)
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.trim().to_lowercase())
+            .ok_or_else(|| anyhow!("Catalog is not signed"))?;
+        let body = Self::read_capped(response).await?;
+        if !constant_time_eq(hmac_sha256_hex(&self.key, &body).as_bytes(), signature.as_bytes()) {
+            bail!("Catalog signature does not match");
+        }
+        serde_json::from_slice(&body).context("Catalog is not valid JSON")
+    }
+
+    /// Download, verify and write template `id` into `templates_dir`,
+    /// replacing an installed version. Returns the installed entry.
+    pub async fn install(&self, id: &str, templates_dir: &Path) -> Result<CatalogEntry> {
+        validate_id(id)?;
+        let entry = self
+            .catalog()
+            .await?
+            .into_iter()
+            .find(|e| e.id == id)
+            .ok_or_else(|| anyhow!("Template '{}' is not in the catalog", id))?;
+
+        let response = self.fetch(&self.resolve(&entry.bundle_url)).await?;
+        let bytes = Self::read_capped(response).await?;
+        if sha256_hex(&bytes) != entry.sha256.trim().to_lowercase() {
+            bail!("Bundle hash does not match the catalog");
+        }
+        let files = decode_bundle(&bytes)?;
+        write_template(templates_dir, &entry, &files).await?;
+
+        app_log!(
+            info,
+            "[templates] Installed '{}' {} from registry ({} files)",
+            id,
+            entry.version.as_deref().unwrap_or("(unversioned)"),
+            files.len()
+        );
+        Ok(entry)
+    }
+}
+
+/// Write an installed template, replacing a previously installed version.
+/// Templates that ship with the server are never replaced. The files are
+/// staged in full first, so a failed write leaves the installed version as
+/// it was.
+pub(crate) async fn write_template(
+    templates_dir: &Path,
+    entry: &CatalogEntry,
+    files: &[(String, Vec<u8>)],
+) -> Result<()> {
+    validate_id(&entry.id)?;
+    for (name, _) in files {
+        check_file_name(name)?;
+    }
+    let target = templates_dir.join(&entry.id);
+    if target.exists() && installed(templates_dir, &entry.id).is_none() {
+        bail!(
+            "Template '{}' ships with the server and can't be replaced",
+            entry.id
+        );
+    }
+
+    let staging = templates_dir.join(STAGING_DIR);
+    let staged = staging.join(&entry.id);
+    let previous = staging.join(format!("{}.previous", entry.id));
+    for leftover in [&staged, &previous] {
+        FsOps::remove_dir_all(leftover).await?;
+    }
+    FsOps::ensure_dir_exists(&staged).await?;
+    let written = async {
+        for (name, content) in files {
+            tokio::fs::write(staged.join(name), content)
+                .await
+                .with_context(|| format!("Failed to write {}", name))?;
+        }
+        tokio::fs::write(staged.join(INSTALLED_MARKER), serde_json::to_vec(entry)?).await?;
+        anyhow::Ok(())
+    }
+    .await;
+    if let Err(e) = written {
+        let _ = FsOps::remove_dir_all(&staged).await;
+        return Err(e);
+    }
+    swap_in(&staged, &target, &previous).await
+}
+
+/// Rename `staged` to `target`, moving a version installed there aside to
+/// `previous` and its `versions/` into the new one: profiles may be pinned
+/// to an archived version. On failure the installed version is put back.
+async fn swap_in(staged: &Path, target: &Path, previous: &Path) -> Result<()> {
+    let replacing = target.exists();
+    if replacing {
+        tokio::fs::rename(target, previous)
+            .await
+            .with_context(|| format!("Failed to move {} aside", target.display()))?;
+    }
+    let moved = async {
+        if previous.join(VERSIONS_DIR).is_dir() {
+            tokio::fs::rename(previous.join(VERSIONS_DIR), staged.join(VERSIONS_DIR)).await?;
+        }
+        tokio::fs::rename(staged, target).await
+    }
+    .await;
+    if let Err(e) = moved {
+        if staged.join(VERSIONS_DIR).is_dir() {
+            let _ = tokio::fs::rename(staged.join(VERSIONS_DIR), previous.join(VERSIONS_DIR)).await;
+        }
+        if replacing {
+            let _ = tokio::fs::rename(previous, target).await;
+        }
+        let _ = FsOps::remove_dir_all(staged).await;
+        return Err(anyhow!("Failed to install {}: {}", target.display(), e));
+    }
+    if replacing {
+        if let Err(e) = FsOps::remove_dir_all(previous).await {
+            app_log!(warn, "[templates] Failed to remove {}: {}", previous.display(), e);
+        }
+    }
+    Ok(())
+}
+
+/// Files of installed template `id` (without `versions/` and the marker),
+/// sorted by name.
+pub(crate) async fn read_template(templates_dir: &Path, id: &str) -> Result<Vec<(String, Vec<u8>)>> {
+    let dir = templates_dir.join(id);
+    let mut files = Vec::new();
+    let mut entries = tokio::fs::read_dir(&dir)
+        .await
+        .with_context(|| format!("Failed to read {}", dir.display()))?;
+    while let Some(entry) = entries.next_entry().await? {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if entry.file_type().await?.is_file() && name != INSTALLED_MARKER {
+            files.push((name, tokio::fs::read(entry.path()).await?));
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Ids of the templates installed from the registry.
+pub(crate) fn installed_ids(templates_dir: &Path) -> Vec<String> {
+    let mut ids: Vec<String> = std::fs::read_dir(templates_dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|e| e.file_name().to_string_lossy().to_string())
+                .filter(|id| installed(templates_dir, id).is_some())
+                .collect()
+        })
+        .unwrap_or_default();
+    ids.sort();
+    ids
+}
+
+/// The catalog entry template `id` was installed from, if it came from the
+/// registry.
+pub fn installed(templates_dir: &Path, id: &str) -> Option<CatalogEntry> {
+    let content = std::fs::read(templates_dir.join(id).join(INSTALLED_MARKER)).ok()?;
+    serde_json::from_slice(&content).ok()
+}
+
+pub(crate) fn validate_id(id: &str) -> Result<()> {
+    let well_formed = !id.is_empty()
+        && id
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-');
+    if !well_formed || RESERVED_IDS.contains(&id) {
+        bail!("Invalid template id '{}'", id);
+    }
+    Ok(())
+}
+
+/// Template files are flat, visible, and of an allowed type.
+pub(crate) fn check_file_name(name: &str) -> Result<()> {
+    let safe = !name.is_empty()
+        && !name.starts_with('.')
+        && !name.contains(['/', '\\'])
+        && get_file_extension(name).is_some_and(|ext| ALLOWED_EXTENSIONS.contains(&ext.as_str()));
+    if !safe {
+        bail!("Bundle file '{}' is not allowed", name);
+    }
+    Ok(())
+}
+
+/// Flat file list with allowed extensions and a manifest.
+fn decode_bundle(bytes: &[u8]) -> Result<Vec<(String, Vec<u8>)>> {
+    let bundle: Bundle = serde_json::from_slice(bytes).context("Bundle is not valid JSON")?;
+    if !bundle.files.contains_key("manifest.toml") {
+        bail!("Bundle has no manifest.toml");
+    }
+    bundle
+        .files
+        .into_iter()
+        .map(|(name, encoded)| {
+            check_file_name(&name)?;
+            let content = STANDARD
+                .decode(encoded.trim())
+                .with_context(|| format!("Bundle file '{}' is not base64", name))?;
+            Ok((name, content))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_unsafe_bundles() {
+        let bundle = |files: &str| format!(r#"{{"files":{{{}}}}}"#, files);
+        let ok = bundle(r#""manifest.toml":"bmFtZSA9ICJ4Ig==","main.typ":"""#);
+        assert_eq!(decode_bundle(ok.as_bytes()).unwrap().len(), 2);
+
+        assert!(decode_bundle(bundle(r#""main.typ":"""#).as_bytes()).is_err());
+        for name in ["../x.typ", "a/b.typ", ".hidden.typ", "run.sh"] {
+            let files = format!(r#""manifest.toml":"","{}":"""#, name);
+            assert!(
+                decode_bundle(bundle(&files).as_bytes()).is_err(),
+                "{}",
+                name
+            );
+        }
+        assert!(validate_id("common").is_err());
+        assert!(validate_id("../tech").is_err());
+        assert!(validate_id("modern_minimal").is_ok());
+    }
+}
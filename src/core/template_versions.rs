@@ -0,0 +1,267 @@
+// src/core/template_versions.rs
+//! Template versions and per-profile pins.
+//!
+//! A template's version is the `version` in its manifest. The first time a
+//! version is generated with (or passes its health check) its files are
+//! archived to `templates/<id>/versions/<version>/`, with the shared Typst
+//! files it imports, so the layout stays available and compiles the same
+//! after the template or the shared files are edited and its version bumped. A profile
+//! can pin a template to an archived version; every generation records the
+//! version it used. Pins and history live in the profile's
+//! `.template_versions.json`.
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use graflog::app_log;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::core::template_engine::{TemplateInfo, SHARED_FILES};
+
+pub const VERSIONS_DIR: &str = "versions";
+pub const PROFILE_FILE: &str = ".template_versions.json";
+/// Version of templates whose manifest does not declare one.
+const UNVERSIONED: &str = "0.0.0";
+const HISTORY_LIMIT: usize = 50;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GenerationRecord {
+    pub template: String,
+    pub version: String,
+    pub lang: String,
+    pub generated_at: DateTime<Utc>,
+    /// Images left out of this generation, with the reason.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub skipped_assets: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProfileTemplateVersions {
+    /// Template id → pinned version.
+    #[serde(default)]
+    pub pins: BTreeMap<String, String>,
+    /// Most recent generations, oldest first.
+    #[serde(default)]
+    pub generations: Vec<GenerationRecord>,
+}
+
+pub fn current_version(template: &TemplateInfo) -> String {
+    template
+        .manifest
+        .version
+        .as_deref()
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .unwrap_or(UNVERSIONED)
+        .to_string()
+}
+
+/// Versions are directory names: no separators, no leading dot.
+fn validate_version(version: &str) -> Result<()> {
+    let safe = !version.is_empty()
+        && !version.starts_with('.')
+        && version
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_' | '+'));
+    if !safe {
+        bail!("Invalid template version '{}'", version);
+    }
+    Ok(())
+}
+
+/// Archive the current files of `template` under its version, unless that
+/// version is already archived, with the [`SHARED_FILES`] of `templates_dir`
+/// (the template's own files of the same name win, as in a workspace). The
+/// copy is staged and renamed into place so a half-written archive is never
+/// used.
+pub fn archive(template: &TemplateInfo, templates_dir: &Path) -> Result<()> {
+    let version = current_version(template);
+    validate_version(&version)?;
+    let versions_dir = template.path.join(VERSIONS_DIR);
+    let target = versions_dir.join(&version);
+    if target.is_dir() {
+        return Ok(());
+    }
+
+    let staging = versions_dir.join(format!(".{}-{}", version, uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&staging)?;
+    let copied = (|| -> Result<()> {
+        for shared in SHARED_FILES {
+            let path = templates_dir.join(shared);
+            if path.is_file() {
+                std::fs::copy(&path, staging.join(shared))
+                    .with_context(|| format!("Failed to archive {}", path.display()))?;
+            }
+        }
+        for entry in std::fs::read_dir(&template.path)?.flatten() {
+            let path = entry.path();
+            if path.is_file() {
+                std::fs::copy(&path, staging.join(entry.file_name()))
+                    .with_context(|| format!("Failed to archive {}", path.display()))?;
+            }
+        }
+        Ok(())
+    })();
+    let placed = copied.and_then(|_| std::fs::rename(&staging, &target).map_err(Into::into));
+    if let Err(e) = placed {
+        let _ = std::fs::remove_dir_all(&staging);
+        // Another generation archived the same version first.
+        if target.is_dir() {
+            return Ok(());
+        }
+        return Err(e);
+    }
+
+    app_log!(
+        info,
+        "[templates] Archived '{}' version {}",
+        template.id,
+        version
+    );
+    Ok(())
+}
+
+/// Sort key comparing dotted numeric parts as numbers (`1.10.0` > `1.9.2`).
+fn version_key(version: &str) -> Vec<(u64, String)> {
+    version
+        .split(|c| c == '.' || c == '-' || c == '+')
+        .map(|part| (part.parse().unwrap_or(0), part.to_string()))
+        .collect()
+}
+
+/// Archived versions plus the current one, oldest first.
+pub fn versions(template: &TemplateInfo) -> Vec<String> {
+    let mut versions: Vec<String> = std::fs::read_dir(template.path.join(VERSIONS_DIR))
+        .map(|rd| {
+            rd.flatten()
+                .filter(|e| e.path().is_dir())
+                .map(|e| e.file_name().to_string_lossy().to_string())
+                .filter(|v| !v.starts_with('.'))
+                .collect()
+        })
+        .unwrap_or_default();
+    let current = current_version(template);
+    if !versions.contains(&current) {
+        versions.push(current);
+    }
+    versions.sort_by_key(|v| version_key(v));
+    versions
+}
+
+/// Directory holding `version` of `template`: the template itself for the
+/// current version, its archive otherwise.
+pub fn version_dir(template: &TemplateInfo, version: &str) -> Result<PathBuf> {
+    validate_version(version)?;
+    if version == current_version(template) {
+        return Ok(template.path.clone());
+    }
+    let archived = template.path.join(VERSIONS_DIR).join(version);
+    if !archived.is_dir() {
+        bail!(
+            "Template '{}' has no version {} (available: {})",
+            template.id,
+            version,
+            versions(template).join(", ")
+        );
+    }
+    Ok(archived)
+}
+
+pub fn load(profile_dir: &Path) -> ProfileTemplateVersions {
+    std::fs::read(profile_dir.join(PROFILE_FILE))
+        .ok()
+        .and_then(|content| serde_json::from_slice(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save(profile_dir: &Path, data: &ProfileTemplateVersions) -> Result<()> {
+    std::fs::write(
+        profile_dir.join(PROFILE_FILE),
+        serde_json::to_vec_pretty(data)?,
+    )
+    .context("Failed to save template versions")
+}
+
+/// Version `template` is pinned to for this profile, if any.
+pub fn pinned(profile_dir: &Path, template: &str) -> Option<String> {
+    load(profile_dir).pins.remove(template)
+}
+
+/// Pin `template` to `version`, or unpin it with `None`.
+pub fn set_pin(
+    profile_dir: &Path,
+    template: &str,
+    version: Option<&str>,
+) -> Result<ProfileTemplateVersions> {
+    let mut data = load(profile_dir);
+    match version {
+        Some(version) => {
+            validate_version(version)?;
+            data.pins.insert(template.to_string(), version.to_string());
+        }
+        None => {
+            data.pins.remove(template);
+        }
+    }
+    save(profile_dir, &data)?;
+    Ok(data)
+}
+
+/// Best effort: a failed write is logged and never fails the generation.
+pub fn record_generation(
+    profile_dir: &Path,
+    template: &str,
+    version: &str,
+    lang: &str,
+    skipped_assets: &[String],
+) {
+    let mut data = load(profile_dir);
+    data.generations.push(GenerationRecord {
+        template: template.to_string(),
+        version: version.to_string(),
+        lang: lang.to_string(),
+        generated_at: Utc::now(),
+        skipped_assets: skipped_assets.to_vec(),
+    });
+    let overflow = data.generations.len().saturating_sub(HISTORY_LIMIT);
+    data.generations.drain(..overflow);
+    if let Err(e) = save(profile_dir, &data) {
+        app_log!(
+            warn,
+            "Failed to record template version for {}: {}",
+            profile_dir.display(),
+            e
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn archived_versions_survive_a_bump() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tech");
+        std::fs::create_dir_all(&path).unwrap();
+        std::fs::write(path.join("main.typ"), "v1").unwrap();
+        std::fs::write(dir.path().join("common.typ"), "common v1").unwrap();
+        let mut template = TemplateInfo {
+            id: "tech".to_string(),
+            path: path.clone(),
+            manifest: toml::from_str("name = \"tech\"\nversion = \"1.9.0\"").unwrap(),
+        };
+        archive(&template, dir.path()).unwrap();
+
+        std::fs::write(path.join("main.typ"), "v2").unwrap();
+        std::fs::write(dir.path().join("common.typ"), "common v2").unwrap();
+        template.manifest.version = Some("1.10.0".to_string());
+        assert_eq!(versions(&template), vec!["1.9.0", "1.10.0"]);
+        let old = version_dir(&template, "1.9.0").unwrap();
+        assert_eq!(std::fs::read_to_string(old.join("main.typ")).unwrap(), "v1");
+        assert_eq!(std::fs::read_to_string(old.join("common.typ")).unwrap(), "common v1");
+        assert_eq!(version_dir(&template, "1.10.0").unwrap(), path);
+        assert!(version_dir(&template, "../1.9.0").is_err());
+    }
+}
@@ -0,0 +1,330 @@
+// src/core/tenant_archive.rs
+//! Cold storage for inactive accounts.
+//!
+//! [`archive`] marks an email tenant archived, so the auth guard rejects it
+//! with `TENANT_ARCHIVED`. It then zips the user's data and output folders
+//! into `<data root>/.archives/<user>.zip` and removes the folders. The data
+//! root is the one of the tenant's region (see
+//! [`crate::core::data_residency`]). Mount cold storage, such as an S3 bucket
+//! through a gateway, at `.archives`. [`restore`] unpacks the archive into
+//! staging folders next to the originals, moves them into place and lifts
+//! the mark. Files encrypted at rest stay encrypted inside the archive.
+//!
+//! Domain tenants are not archivable: their users' folders share a tenant
+//! folder with other accounts.
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use graflog::app_log;
+use serde::Serialize;
+use sqlx::SqlitePool;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::core::data_residency;
+use crate::core::database::{
+    email_to_folder_name, get_tenant_folder_path, get_tenant_output_path, Tenant,
+    TenantRepository,
+};
+use crate::core::tenant_cache;
+
+pub const ARCHIVE_DIR: &str = ".archives";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ArchiveReport {
+    pub email: String,
+    pub archive: PathBuf,
+    pub files: usize,
+    pub bytes: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub archived_at: Option<DateTime<Utc>>,
+}
+
+struct Locations {
+    data: PathBuf,
+    output: PathBuf,
+    archive: PathBuf,
+}
+
+fn locations(email: &str, data_dir: &Path, output_dir: &Path) -> Locations {
+    let data = get_tenant_folder_path(email, &data_dir.to_path_buf());
+    let archive = data_residency::data_root_of(data_dir, &data)
+        .join(ARCHIVE_DIR)
+        .join(format!("{}.zip", email_to_folder_name(email)));
+    Locations {
+        output: get_tenant_output_path(email, output_dir),
+        data,
+        archive,
+    }
+}
+
+async fn email_tenant(pool: &SqlitePool, email: &str) -> Result<Tenant> {
+    match TenantRepository::new(pool).find_by_email_or_domain(email).await? {
+        Some(tenant) if tenant.email.as_deref() == Some(email) => Ok(tenant),
+        Some(_) => bail!("{} belongs to a domain tenant, which cannot be archived", email),
+        None => bail!("No active account for {}", email),
+    }
+}
+
+async fn set_archived(pool: &SqlitePool, email: &str, at: Option<DateTime<Utc>>) -> Result<()> {
+    sqlx::query("UPDATE tenants SET archived_at = ?, updated_at = ? WHERE email = ?")
+        .bind(at)
+        .bind(Utc::now())
+        .bind(email)
+        .execute(pool)
+        .await?;
+    tenant_cache::invalidate(email);
+    Ok(())
+}
+
+/// Archive the account of `email`; see the module docs.
+pub async fn archive(
+    pool: &SqlitePool,
+    email: &str,
+    data_dir: &Path,
+    output_dir: &Path,
+) -> Result<ArchiveReport> {
+    let tenant = email_tenant(pool, email).await?;
+    if tenant.archived_at.is_some() {
+        bail!("{} is already archived", email);
+    }
+    let at = locations(email, data_dir, output_dir);
+    if at.archive.exists() {
+        bail!("{} already exists", at.archive.display());
+    }
+
+    // Reject the user first, so nothing is written while the files are packed.
+    let archived_at = Utc::now();
+    set_archived(pool, email, Some(archived_at)).await?;
+    let packed = {
+        let (data, output, archive) = (at.data.clone(), at.output.clone(), at.archive.clone());
+        tokio::task::spawn_blocking(move || {
+            write_archive(&archive, &[("data", &data), ("output", &output)])
+        })
+        .await
+        .map_err(anyhow::Error::from)
+        .and_then(|packed| packed)
+    };
+    let files = match packed {
+        Ok(files) => files,
+        Err(e) => {
+            set_archived(pool, email, None).await?;
+            return Err(e.context("Failed to write the archive"));
+        }
+    };
+
+    for dir in [&at.data, &at.output] {
+        if dir.exists() {
+            tokio::fs::remove_dir_all(dir)
+                .await
+                .with_context(|| format!("Archived, but failed to remove {}", dir.display()))?;
+        }
+    }
+    let bytes = tokio::fs::metadata(&at.archive).await?.len();
+    app_log!(
+        info,
+        "[archive] Archived {} ({} files, {} bytes) to {}",
+        email,
+        files,
+        bytes,
+        at.archive.display()
+    );
+    Ok(ArchiveReport {
+        email: email.to_string(),
+        archive: at.archive,
+        files,
+        bytes,
+        archived_at: Some(archived_at),
+    })
+}
+
+/// Bring an archived account back; see the module docs.
+pub async fn restore(
+    pool: &SqlitePool,
+    email: &str,
+    data_dir: &Path,
+    output_dir: &Path,
+) -> Result<ArchiveReport> {
+    let tenant = email_tenant(pool, email).await?;
+    if tenant.archived_at.is_none() {
+        bail!("{} is not archived", email);
+    }
+    let at = locations(email, data_dir, output_dir);
+    if !at.archive.is_file() {
+        bail!("Archive {} is missing", at.archive.display());
+    }
+    for dir in [&at.data, &at.output] {
+        if dir.exists() {
+            bail!("{} exists; move it aside before restoring", dir.display());
+        }
+    }
+
+    let staged = |dir: &Path| dir.with_extension("restoring");
+    let (data_staged, output_staged) = (staged(&at.data), staged(&at.output));
+    let unpacked = {
+        let (archive, data, output) = (
+            at.archive.clone(),
+            data_staged.clone(),
+            output_staged.clone(),
+        );
+        tokio::task::spawn_blocking(move || {
+            extract_archive(&archive, &[("data", &data), ("output", &output)])
+        })
+        .await
+        .map_err(anyhow::Error::from)
+        .and_then(|unpacked| unpacked)
+    };
+    let moved = match unpacked {
+        Ok(files) => move_into_place(&[(&data_staged, &at.data), (&output_staged, &at.output)])
+            .await
+            .map(|()| files),
+        Err(e) => Err(e),
+    };
+    let files = match moved {
+        Ok(files) => files,
+        Err(e) => {
+            for dir in [&data_staged, &output_staged] {
+                let _ = tokio::fs::remove_dir_all(dir).await;
+            }
+            return Err(e.context("Failed to restore the archive"));
+        }
+    };
+
+    set_archived(pool, email, None).await?;
+    let bytes = tokio::fs::metadata(&at.archive).await?.len();
+    tokio::fs::remove_file(&at.archive).await?;
+    app_log!(info, "[archive] Restored {} ({} files)", email, files);
+    Ok(ArchiveReport {
+        email: email.to_string(),
+        archive: at.archive,
+        files,
+        bytes,
+        archived_at: None,
+    })
+}
+
+async fn move_into_place(moves: &[(&PathBuf, &PathBuf)]) -> Result<()> {
+    for (from, to) in moves {
+        if !from.exists() {
+            continue;
+        }
+        if let Some(parent) = to.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        tokio::fs::rename(from, to)
+            .await
+            .with_context(|| format!("Failed to move {} into place", to.display()))?;
+    }
+    Ok(())
+}
+
+/// Zip each `(prefix, dir)` under `prefix/`; returns the number of files.
+fn write_archive(dest: &Path, roots: &[(&str, &Path)]) -> Result<usize> {
+    if let Some(parent) = dest.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let tmp = dest.with_extension("zip.tmp");
+    let mut zip = zip::ZipWriter::new(std::fs::File::create(&tmp)?);
+    let options =
+        zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+    let mut files = 0;
+    let written = (|| -> Result<()> {
+        for (prefix, root) in roots {
+            if !root.is_dir() {
+                continue;
+            }
+            let mut pending = vec![root.to_path_buf()];
+            while let Some(dir) = pending.pop() {
+                for entry in std::fs::read_dir(&dir)? {
+                    let path = entry?.path();
+                    let relative = path.strip_prefix(root)?.to_string_lossy().replace('\\', "/");
+                    let name = format!("{}/{}", prefix, relative);
+                    if path.is_dir() {
+                        zip.add_directory(name, options)?;
+                        pending.push(path);
+                    } else {
+                        zip.start_file(name, options)?;
+                        std::io::copy(&mut std::fs::File::open(&path)?, &mut zip)?;
+                        files += 1;
+                    }
+                }
+            }
+        }
+        let mut file = zip.finish()?;
+        file.flush()?;
+        file.sync_all()?;
+        Ok(())
+    })();
+    if let Err(e) = written {
+        let _ = std::fs::remove_file(&tmp);
+        return Err(e);
+    }
+    std::fs::rename(&tmp, dest)?;
+    Ok(files)
+}
+
+/// Unpack an archive written by [`write_archive`] into the given dirs.
+fn extract_archive(archive: &Path, roots: &[(&str, &Path)]) -> Result<usize> {
+    let mut zip = zip::ZipArchive::new(std::fs::File::open(archive)?)?;
+    let mut files = 0;
+    for i in 0..zip.len() {
+        let mut entry = zip.by_index(i)?;
+        let name = entry
+            .enclosed_name()
+            .map(Path::to_path_buf)
+            .with_context(|| format!("Unsafe path '{}' in archive", entry.name()))?;
+        let mut parts = name.components();
+        let prefix = parts
+            .next()
+            .and_then(|c| c.as_os_str().to_str())
+            .unwrap_or_default()
+            .to_string();
+        let Some((_, root)) = roots.iter().find(|(p, _)| *p == prefix) else {
+            bail!("Unexpected entry '{}' in archive", entry.name());
+        };
+        let dest = root.join(parts.as_path());
+        if entry.is_dir() {
+            std::fs::create_dir_all(&dest)?;
+            continue;
+        }
+        if let Some(parent) = dest.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::io::copy(&mut entry, &mut std::fs::File::create(&dest)?)?;
+        files += 1;
+    }
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn archives_round_trip() {
+        let tmp = tempfile::tempdir().unwrap();
+        let data = tmp.path().join("data/jane");
+        std::fs::create_dir_all(data.join("john/assets")).unwrap();
+        std::fs::write(data.join("john/cv_params.toml"), "name = \"John\"").unwrap();
+        std::fs::write(data.join("john/assets/logo.png"), [0u8, 1, 2]).unwrap();
+        let output = tmp.path().join("output/jane");
+        let archive = tmp.path().join(".archives/jane.zip");
+
+        let files = write_archive(&archive, &[("data", &data), ("output", &output)]).unwrap();
+        assert_eq!(files, 2);
+
+        let (data_back, output_back) = (tmp.path().join("back/data"), tmp.path().join("back/out"));
+        let files =
+            extract_archive(&archive, &[("data", &data_back), ("output", &output_back)]).unwrap();
+        assert_eq!(files, 2);
+        assert_eq!(
+            std::fs::read_to_string(data_back.join("john/cv_params.toml")).unwrap(),
+            "name = \"John\""
+        );
+        assert_eq!(
+            std::fs::read(data_back.join("john/assets/logo.png")).unwrap(),
+            vec![0u8, 1, 2]
+        );
+        assert!(!output_back.exists());
+    }
+}
@@ -0,0 +1,472 @@
+// src/core/tenant_bundle.rs
+//! Tenant configuration as code.
+//!
+//! [`export`] writes a tenant's accounts (email or domain, preferred
+//! language, email preferences), its Typst lint level, its member roles and
+//! the templates installed from the registry into one YAML document.
+//! [`apply`] brings another environment in line with such a bundle. It only
+//! writes what differs, so applying the same bundle twice changes nothing.
+//! It never deletes: roles or templates missing from the bundle are reported
+//! as warnings. Database changes happen in one transaction; templates are
+//! written after it commits.
+//!
+//! Secrets, data regions and user files are environment-specific and are not
+//! part of a bundle.
+
+use anyhow::{bail, Context, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::core::profile_access::MemberRole;
+use crate::core::template_registry::{self, CatalogEntry};
+use crate::core::tenant_cache;
+use crate::core::tenant_rename::valid_tenant_name;
+use crate::core::typst_lint::LintLevel;
+
+pub const BUNDLE_VERSION: u32 = 1;
+/// Upper bound of an uploaded bundle; templates are inlined as base64.
+pub const MAX_BUNDLE_BYTES: usize = 20 * 1024 * 1024;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TenantBundle {
+    pub version: u32,
+    pub tenant: String,
+    #[serde(default)]
+    pub accounts: Vec<BundleAccount>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub typst_lint: Option<String>,
+    #[serde(default)]
+    pub members: Vec<BundleMember>,
+    #[serde(default)]
+    pub templates: Vec<BundleTemplate>,
+}
+
+/// One tenant row: either `email` or `domain`. Unset preferences are left
+/// alone when applied.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleAccount {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub domain: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preferred_lang: Option<String>,
+    /// JSON, as stored.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email_prefs: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleMember {
+    pub email: String,
+    pub role: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleTemplate {
+    /// The registry entry the template was installed from.
+    pub entry: CatalogEntry,
+    /// File name to base64 content.
+    pub files: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BundleApplyReport {
+    pub tenant: String,
+    pub dry_run: bool,
+    /// What was (or, in a dry run, would be) changed.
+    pub changes: Vec<String>,
+    /// Items already matching the bundle.
+    pub unchanged: usize,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+}
+
+type AccountRow = (Option<String>, Option<String>, Option<String>, Option<String>);
+
+async fn accounts_of(pool: &SqlitePool, tenant: &str) -> Result<Vec<AccountRow>> {
+    Ok(sqlx::query_as(
+        "SELECT email, domain, preferred_lang, email_prefs FROM tenants \
+         WHERE tenant_name = ? AND is_active = TRUE ORDER BY email NULLS LAST, domain",
+    )
+    .bind(tenant)
+    .fetch_all(pool)
+    .await?)
+}
+
+/// Member roles of the accounts: the account's own email, or any address of
+/// a domain account.
+async fn roles_of(pool: &SqlitePool, accounts: &[AccountRow]) -> Result<Vec<BundleMember>> {
+    let rows: Vec<(String, String)> =
+        sqlx::query_as("SELECT email, role FROM member_roles ORDER BY email")
+            .fetch_all(pool)
+            .await?;
+    Ok(rows
+        .into_iter()
+        .filter(|(email, _)| {
+            accounts.iter().any(|(account, domain, ..)| {
+                account.as_deref() == Some(email.as_str())
+                    || domain
+                        .as_deref()
+                        .is_some_and(|d| email.ends_with(&format!("@{}", d)))
+            })
+        })
+        .map(|(email, role)| BundleMember { email, role })
+        .collect())
+}
+
+/// Bundle of `tenant`; see the module docs.
+pub async fn export(
+    pool: &SqlitePool,
+    templates_dir: &Path,
+    tenant: &str,
+    include_templates: bool,
+) -> Result<TenantBundle> {
+    let accounts = accounts_of(pool, tenant).await?;
+    if accounts.is_empty() {
+        bail!("No active tenant named '{}'", tenant);
+    }
+    let typst_lint: Option<String> = sqlx::query_scalar(
+        "SELECT l.level FROM typst_lint_levels l JOIN tenants t ON t.id = l.tenant_id \
+         WHERE t.tenant_name = ? AND t.is_active = TRUE ORDER BY t.id LIMIT 1",
+    )
+    .bind(tenant)
+    .fetch_optional(pool)
+    .await?;
+    let members = roles_of(pool, &accounts).await?;
+
+    let mut templates = Vec::new();
+    if include_templates {
+        for id in template_registry::installed_ids(templates_dir) {
+            let Some(entry) = template_registry::installed(templates_dir, &id) else {
+                continue;
+            };
+            let files = template_registry::read_template(templates_dir, &id)
+                .await?
+                .into_iter()
+                .map(|(name, content)| (name, STANDARD.encode(content)))
+                .collect();
+            templates.push(BundleTemplate { entry, files });
+        }
+    }
+
+    Ok(TenantBundle {
+        version: BUNDLE_VERSION,
+        tenant: tenant.to_string(),
+        accounts: accounts
+            .into_iter()
+            .map(|(email, domain, preferred_lang, email_prefs)| BundleAccount {
+                email,
+                domain,
+                preferred_lang,
+                email_prefs,
+            })
+            .collect(),
+        typst_lint,
+        members,
+        templates,
+    })
+}
+
+/// Reject malformed bundles before anything is written.
+pub fn validate(bundle: &TenantBundle) -> Result<()> {
+    if bundle.version != BUNDLE_VERSION {
+        bail!(
+            "Unsupported bundle version {} (expected {})",
+            bundle.version,
+            BUNDLE_VERSION
+        );
+    }
+    if !valid_tenant_name(&bundle.tenant) {
+        bail!("Invalid tenant name '{}'", bundle.tenant);
+    }
+    if bundle.accounts.is_empty() {
+        bail!("The bundle has no accounts");
+    }
+    for account in &bundle.accounts {
+        match (&account.email, &account.domain) {
+            (Some(email), None) if email.contains('@') => {}
+            (None, Some(domain)) if !domain.is_empty() && !domain.contains('@') => {}
+            _ => bail!("Each account needs either an email or a domain"),
+        }
+        if let Some(prefs) = &account.email_prefs {
+            serde_json::from_str::<serde_json::Value>(prefs)
+                .context("email_prefs must be JSON")?;
+        }
+    }
+    if let Some(level) = &bundle.typst_lint {
+        if LintLevel::parse(level).is_none() {
+            bail!("Unknown Typst lint level '{}'", level);
+        }
+    }
+    for member in &bundle.members {
+        if !member.email.contains('@') || MemberRole::parse(&member.role).is_none() {
+            bail!("Invalid member role {} = '{}'", member.email, member.role);
+        }
+    }
+    for template in &bundle.templates {
+        template_registry::validate_id(&template.entry.id)?;
+        for (name, content) in &template.files {
+            STANDARD
+                .decode(content.trim())
+                .with_context(|| format!("{}/{} is not base64", template.entry.id, name))?;
+        }
+    }
+    Ok(())
+}
+
+/// Bring this environment in line with `bundle`; see the module docs.
+pub async fn apply(
+    pool: &SqlitePool,
+    templates_dir: &Path,
+    bundle: &TenantBundle,
+    dry_run: bool,
+) -> Result<BundleApplyReport> {
+    validate(bundle)?;
+    let mut report = BundleApplyReport {
+        tenant: bundle.tenant.clone(),
+        dry_run,
+        ..Default::default()
+    };
+
+    let mut tx = pool.begin().await?;
+    for account in &bundle.accounts {
+        let (column, key) = match (&account.email, &account.domain) {
+            (Some(email), _) => ("email", email.to_lowercase()),
+            (_, Some(domain)) => ("domain", domain.to_lowercase()),
+            _ => unreachable!("validated"),
+        };
+        let existing: Option<(String, Option<String>, Option<String>)> = sqlx::query_as(&format!(
+            "SELECT tenant_name, preferred_lang, email_prefs FROM tenants \
+             WHERE {} = ? AND is_active = TRUE",
+            column
+        ))
+        .bind(&key)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let Some((tenant_name, lang, prefs)) = existing else {
+            report.changes.push(format!("Create {} account {}", column, key));
+            if !dry_run {
+                let now = chrono::Utc::now();
+                sqlx::query(&format!(
+                    "INSERT INTO tenants ({}, tenant_name, preferred_lang, email_prefs, \
+                     created_at, updated_at, is_active) VALUES (?, ?, ?, ?, ?, ?, TRUE)",
+                    column
+                ))
+                .bind(&key)
+                .bind(&bundle.tenant)
+                .bind(&account.preferred_lang)
+                .bind(&account.email_prefs)
+                .bind(now)
+                .bind(now)
+                .execute(&mut *tx)
+                .await?;
+            }
+            continue;
+        };
+        if tenant_name != bundle.tenant {
+            bail!(
+                "{} belongs to tenant '{}' here; rename it first",
+                key,
+                tenant_name
+            );
+        }
+        let mut updated = false;
+        for (field, current, wanted) in [
+            ("preferred_lang", &lang, &account.preferred_lang),
+            ("email_prefs", &prefs, &account.email_prefs),
+        ] {
+            let Some(wanted) = wanted else {
+                continue;
+            };
+            if current.as_ref() == Some(wanted) {
+                continue;
+            }
+            updated = true;
+            report.changes.push(format!("Set {} of {} to {}", field, key, wanted));
+            if !dry_run {
+                sqlx::query(&format!(
+                    "UPDATE tenants SET {} = ?, updated_at = ? WHERE {} = ? AND is_active = TRUE",
+                    field, column
+                ))
+                .bind(wanted)
+                .bind(chrono::Utc::now())
+                .bind(&key)
+                .execute(&mut *tx)
+                .await?;
+            }
+        }
+        if !updated {
+            report.unchanged += 1;
+        }
+    }
+
+    if let Some(level) = bundle.typst_lint.as_deref().and_then(LintLevel::parse) {
+        // The level is kept per account; the accounts created above are
+        // included, except in a dry run.
+        let levels: Vec<(i64, Option<String>)> = sqlx::query_as(
+            "SELECT t.id, l.level FROM tenants t \
+             LEFT JOIN typst_lint_levels l ON l.tenant_id = t.id \
+             WHERE t.tenant_name = ? AND t.is_active = TRUE",
+        )
+        .bind(&bundle.tenant)
+        .fetch_all(&mut *tx)
+        .await?;
+        let stale: Vec<i64> = levels
+            .iter()
+            .filter(|(_, current)| current.as_deref() != Some(level.as_str()))
+            .map(|(id, _)| *id)
+            .collect();
+        if stale.is_empty() && !levels.is_empty() {
+            report.unchanged += 1;
+        } else {
+            report.changes.push(format!("Set Typst lint level to {}", level.as_str()));
+            if !dry_run {
+                for tenant_id in stale {
+                    sqlx::query(
+                        "INSERT INTO typst_lint_levels (tenant_id, level, updated_at) \
+                         VALUES (?, ?, ?) ON CONFLICT(tenant_id) DO UPDATE SET \
+                         level = excluded.level, updated_at = excluded.updated_at",
+                    )
+                    .bind(tenant_id)
+                    .bind(level.as_str())
+                    .bind(chrono::Utc::now())
+                    .execute(&mut *tx)
+                    .await?;
+                }
+            }
+        }
+    }
+
+    for member in &bundle.members {
+        let email = member.email.trim().to_lowercase();
+        let role = MemberRole::parse(&member.role).expect("validated");
+        let current: Option<String> =
+            sqlx::query_scalar("SELECT role FROM member_roles WHERE email = ?")
+                .bind(&email)
+                .fetch_optional(&mut *tx)
+                .await?;
+        if current.as_deref() == Some(role.as_str()) {
+            report.unchanged += 1;
+            continue;
+        }
+        report.changes.push(format!("Grant {} to {}", role.as_str(), email));
+        if !dry_run {
+            sqlx::query(
+                "INSERT INTO member_roles (email, role) VALUES (?, ?) \
+                 ON CONFLICT(email) DO UPDATE SET role = excluded.role, \
+                 updated_at = datetime('now')",
+            )
+            .bind(&email)
+            .bind(role.as_str())
+            .execute(&mut *tx)
+            .await?;
+        }
+    }
+
+    if dry_run {
+        tx.rollback().await?;
+    } else {
+        tx.commit().await?;
+        tenant_cache::invalidate_all();
+    }
+
+    let here: Vec<AccountRow> = bundle
+        .accounts
+        .iter()
+        .map(|a| (a.email.clone(), a.domain.clone(), None, None))
+        .collect();
+    for extra in roles_of(pool, &here).await? {
+        if !bundle.members.iter().any(|m| m.email.eq_ignore_ascii_case(&extra.email)) {
+            report.warnings.push(format!(
+                "{} has role {} here but not in the bundle; left as is",
+                extra.email, extra.role
+            ));
+        }
+    }
+
+    for template in &bundle.templates {
+        apply_template(templates_dir, template, dry_run, &mut report).await?;
+    }
+    Ok(report)
+}
+
+async fn apply_template(
+    templates_dir: &Path,
+    template: &BundleTemplate,
+    dry_run: bool,
+    report: &mut BundleApplyReport,
+) -> Result<()> {
+    let id = &template.entry.id;
+    let files: Vec<(String, Vec<u8>)> = template
+        .files
+        .iter()
+        .map(|(name, content)| Ok((name.clone(), STANDARD.decode(content.trim())?)))
+        .collect::<Result<_>>()?;
+
+    if templates_dir.join(id).exists() {
+        if template_registry::installed(templates_dir, id).is_none() {
+            report
+                .warnings
+                .push(format!("Template '{}' ships with this server; skipped", id));
+            return Ok(());
+        }
+        if template_registry::read_template(templates_dir, id).await? == files {
+            report.unchanged += 1;
+            return Ok(());
+        }
+    }
+    report.changes.push(format!(
+        "Install template '{}' {}",
+        id,
+        template.entry.version.as_deref().unwrap_or("(unversioned)")
+    ));
+    if !dry_run {
+        template_registry::write_template(templates_dir, &template.entry, &files).await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bundles_round_trip_and_are_validated() {
+        let yaml = r#"
+version: 1
+tenant: acme
+accounts:
+  - domain: acme.ch
+    preferred_lang: fr
+  - email: boss@gmail.com
+typst_lint: block
+members:
+  - email: jane@acme.ch
+    role: editor
+"#;
+        let bundle: TenantBundle = serde_yaml::from_str(yaml).unwrap();
+        validate(&bundle).unwrap();
+        assert!(bundle.templates.is_empty());
+        let again: TenantBundle =
+            serde_yaml::from_str(&serde_yaml::to_string(&bundle).unwrap()).unwrap();
+        assert_eq!(again.accounts[0].domain.as_deref(), Some("acme.ch"));
+        assert_eq!(again.members[0].role, "editor");
+
+        let broken = |edit: fn(&mut TenantBundle)| {
+            let mut b = bundle.clone();
+            edit(&mut b);
+            validate(&b).is_err()
+        };
+        assert!(broken(|b| b.version = 2));
+        assert!(broken(|b| b.tenant = "../x".to_string()));
+        assert!(broken(|b| b.accounts[1].domain = Some("gmail.com".to_string())));
+        assert!(broken(|b| b.typst_lint = Some("strict".to_string())));
+        assert!(broken(|b| b.members[0].role = "owner".to_string()));
+        assert!(broken(|b| b.accounts[0].email_prefs = Some("{oops".to_string())));
+    }
+}
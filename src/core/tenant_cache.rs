@@ -0,0 +1,172 @@
+// src/core/tenant_cache.rs
+//! In-memory cache of the auth guard's per-email lookups (tenant and granted
+//! role), so authenticated requests stop hitting SQLite every time.
+//!
+//! Entries live for `TENANT_CACHE_TTL_SECS` (default 60, `0` disables the
+//! cache). [`TenantRepository`](crate::core::database::TenantRepository)
+//! invalidates an email whenever it changes that user's tenant row or role;
+//! changes to a domain tenant, shared by many emails, clear everything. With
+//! several server instances, a change made through another instance shows up
+//! here within the TTL.
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::core::database::Tenant;
+
+const DEFAULT_TTL_SECS: u64 = 60;
+const MAX_ENTRIES: usize = 10_000;
+
+static CACHE: LazyLock<TenantCache> = LazyLock::new(|| {
+    let ttl = std::env::var("TENANT_CACHE_TTL_SECS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_TTL_SECS);
+    TenantCache::new(Duration::from_secs(ttl))
+});
+
+#[derive(Debug, Clone)]
+pub struct CachedIdentity {
+    pub tenant: Tenant,
+    /// Role granted in `member_roles`, if any.
+    pub granted_role: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CacheStats {
+    pub ttl_secs: u64,
+    pub entries: usize,
+    pub hits: u64,
+    pub misses: u64,
+}
+
+struct TenantCache {
+    ttl: Duration,
+    entries: Mutex<HashMap<String, (Instant, CachedIdentity)>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl TenantCache {
+    fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    fn entries(&self) -> std::sync::MutexGuard<'_, HashMap<String, (Instant, CachedIdentity)>> {
+        self.entries
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn get_at(&self, email: &str, now: Instant) -> Option<CachedIdentity> {
+        let found = self
+            .entries()
+            .get(email)
+            .filter(|(stored, _)| now.duration_since(*stored) < self.ttl)
+            .map(|(_, identity)| identity.clone());
+        let counter = if found.is_some() {
+            &self.hits
+        } else {
+            &self.misses
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        found
+    }
+
+    fn put_at(&self, email: &str, identity: CachedIdentity, now: Instant) {
+        if self.ttl.is_zero() {
+            return;
+        }
+        let mut entries = self.entries();
+        if entries.len() >= MAX_ENTRIES {
+            entries.retain(|_, (stored, _)| now.duration_since(*stored) < self.ttl);
+            if entries.len() >= MAX_ENTRIES {
+                entries.clear();
+            }
+        }
+        entries.insert(email.to_string(), (now, identity));
+    }
+}
+
+/// Cached lookups of `email`, if still fresh.
+pub fn get(email: &str) -> Option<CachedIdentity> {
+    CACHE.get_at(&email.to_lowercase(), Instant::now())
+}
+
+pub fn put(email: &str, identity: CachedIdentity) {
+    CACHE.put_at(&email.to_lowercase(), identity, Instant::now());
+}
+
+/// Forget `email` after its tenant row or role changed.
+pub fn invalidate(email: &str) {
+    CACHE.entries().remove(&email.to_lowercase());
+}
+
+/// Forget everyone, e.g. after a domain tenant changed.
+pub fn invalidate_all() {
+    CACHE.entries().clear();
+}
+
+pub fn stats() -> CacheStats {
+    CacheStats {
+        ttl_secs: CACHE.ttl.as_secs(),
+        entries: CACHE.entries().len(),
+        hits: CACHE.hits.load(Ordering::Relaxed),
+        misses: CACHE.misses.load(Ordering::Relaxed),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    #[test]
+    fn entries_expire_and_can_be_invalidated() {
+        let identity = CachedIdentity {
+            tenant: Tenant {
+                id: 1,
+                email: Some("jane@example.com".to_string()),
+                domain: None,
+                tenant_name: "jane".to_string(),
+                created_at: Utc::now(),
+                updated_at: Utc::now(),
+                is_active: true,
+                last_seen_at: None,
+                referred_by_code: None,
+                preferred_lang: None,
+                email_prefs: None,
+                archived_at: None,
+            },
+            granted_role: Some("editor".to_string()),
+        };
+        let cache = TenantCache::new(Duration::from_secs(60));
+        let start = Instant::now();
+
+        assert!(cache.get_at("jane@example.com", start).is_none());
+        cache.put_at("jane@example.com", identity.clone(), start);
+        let hit = cache.get_at("jane@example.com", start + Duration::from_secs(59));
+        assert_eq!(hit.unwrap().granted_role.as_deref(), Some("editor"));
+        assert!(cache
+            .get_at("jane@example.com", start + Duration::from_secs(60))
+            .is_none());
+
+        cache.put_at("jane@example.com", identity.clone(), start);
+        cache.entries().remove("jane@example.com");
+        assert!(cache.get_at("jane@example.com", start).is_none());
+        assert_eq!(cache.hits.load(Ordering::Relaxed), 1);
+        assert_eq!(cache.misses.load(Ordering::Relaxed), 3);
+
+        let disabled = TenantCache::new(Duration::ZERO);
+        disabled.put_at("jane@example.com", identity, start);
+        assert!(disabled.get_at("jane@example.com", start).is_none());
+    }
+}
@@ -0,0 +1,99 @@
+// src/core/tenant_rename.rs
+//! Renaming a tenant.
+//!
+//! Only the tenant's own row changes, by id: every record is keyed by tenant
+//! id or account (see [`crate::core::owner`]) and stays in place, and other
+//! tenants that happen to share the old name are left alone. A name another
+//! tenant uses is refused, so admin requests naming a tenant stay
+//! unambiguous.
+//!
+//! Data folders are named after the email domain (see
+//! [`get_tenant_for_email`](crate::core::database::get_tenant_for_email)),
+//! not after the tenant name, so no files move and none are orphaned.
+//! Referral links carry the tenant name and stop working after a rename.
+
+use anyhow::{bail, Result};
+use chrono::Utc;
+use serde::Serialize;
+use sqlx::SqlitePool;
+
+use crate::core::tenant_cache;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TenantRenameReport {
+    pub tenant_id: i64,
+    pub from: String,
+    pub to: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+}
+
+pub fn valid_tenant_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= 64
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        && !name.starts_with('.')
+}
+
+/// Rename the tenant `tenant_id` to `to`; see the module docs.
+pub async fn rename(pool: &SqlitePool, tenant_id: i64, to: &str) -> Result<TenantRenameReport> {
+    if !valid_tenant_name(to) {
+        bail!("Invalid tenant name '{}'", to);
+    }
+
+    let mut tx = pool.begin().await?;
+    let from: Option<String> = sqlx::query_scalar("SELECT tenant_name FROM tenants WHERE id = ?")
+        .bind(tenant_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+    let Some(from) = from else {
+        bail!("No tenant with id {}", tenant_id);
+    };
+    if from == to {
+        bail!("The tenant is already named '{}'", to);
+    }
+    let used: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM tenants WHERE tenant_name = ? AND id != ?")
+            .bind(to)
+            .bind(tenant_id)
+            .fetch_one(&mut *tx)
+            .await?;
+    if used > 0 {
+        bail!("The name '{}' is used by another tenant", to);
+    }
+
+    sqlx::query("UPDATE tenants SET tenant_name = ?, updated_at = ? WHERE id = ?")
+        .bind(to)
+        .bind(Utc::now())
+        .bind(tenant_id)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+    tenant_cache::invalidate_all();
+
+    Ok(TenantRenameReport {
+        tenant_id,
+        warnings: vec![format!("Referral links using '{}' no longer work", from)],
+        from,
+        to: to.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tenant_names_are_plain_identifiers() {
+        assert!(valid_tenant_name("acme"));
+        assert!(valid_tenant_name("john.doe"));
+        assert!(valid_tenant_name("acme_ch-2"));
+        assert!(!valid_tenant_name(""));
+        assert!(!valid_tenant_name(".hidden"));
+        assert!(!valid_tenant_name("acme/../x"));
+        assert!(!valid_tenant_name("acme corp"));
+        assert!(!valid_tenant_name(&"a".repeat(65)));
+    }
+}
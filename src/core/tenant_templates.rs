@@ -0,0 +1,254 @@
+// src/core/tenant_templates.rs
+//! Templates uploaded by a tenant, offered only to its users.
+//!
+//! `POST /templates/upload` takes a ZIP of a template directory, its files at
+//! the root of the archive or in one top-level folder. Before anything is
+//! written the upload is validated: flat files of the types the registry
+//! accepts, a `manifest.toml` that parses, a `name` usable as the template
+//! id and not taken by a server template, and the main file and
+//! dependencies present, in the archive or in the server template it
+//! `extends`. The template then replaces any previous upload of
+//! the same id in `<tenant data>/.templates/<tenant id>/<id>/`, and
+//! [`TemplateEngine::with_tenant_templates`] adds it to the server's
+//! templates for the tenant's generations.
+
+use anyhow::{Context, Result};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use crate::core::data_residency;
+use crate::core::database::get_tenant_for_email;
+use crate::core::template_engine::{TemplateEngine, TemplateManifest};
+use crate::core::template_registry::{check_file_name, validate_id};
+use crate::core::FsOps;
+
+/// Directory of a tenant's templates, in its data folder.
+pub const TEMPLATES_DIR: &str = ".templates";
+pub const MAX_ARCHIVE_BYTES: usize = 5 * 1024 * 1024;
+const MAX_FILES: usize = 50;
+const MAX_UNPACKED_BYTES: u64 = 20 * 1024 * 1024;
+
+/// The upload is not a usable template; nothing was written.
+#[derive(Debug)]
+pub struct InvalidTemplate(pub String);
+
+impl std::fmt::Display for InvalidTemplate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for InvalidTemplate {}
+
+fn invalid(message: impl Into<String>) -> anyhow::Error {
+    InvalidTemplate(message.into()).into()
+}
+
+/// Templates directory of the tenant `tenant_id`, which `email` belongs to.
+/// A data folder such as `gmail-com` holds many unrelated email tenants, so
+/// the folder only places the directory (region tags apply) and the tenant
+/// id keys it; renaming the tenant keeps its templates.
+pub fn dir(data_dir: &Path, email: &str, tenant_id: i64) -> PathBuf {
+    let tenant = get_tenant_for_email(email);
+    data_residency::route(data_dir, &tenant)
+        .join(tenant)
+        .join(TEMPLATES_DIR)
+        .join(tenant_id.to_string())
+}
+
+/// Files of the archive by name, without the folder they may all share.
+/// Directory entries and hidden files (e.g. `__MACOSX/`, `.DS_Store`) are
+/// skipped.
+fn unpack(archive: &[u8]) -> Result<Vec<(String, Vec<u8>)>> {
+    let mut zip = zip::ZipArchive::new(std::io::Cursor::new(archive))
+        .map_err(|e| invalid(format!("The upload is not a ZIP archive: {}", e)))?;
+    let mut files = Vec::new();
+    let mut unpacked = 0u64;
+    for index in 0..zip.len() {
+        let mut entry = zip.by_index(index)?;
+        let Some(path) = entry.enclosed_name().map(Path::to_path_buf) else {
+            return Err(invalid(format!("'{}' points outside the archive", entry.name())));
+        };
+        let hidden = path.components().any(|c| {
+            let name = c.as_os_str().to_string_lossy();
+            name.starts_with('.') || name == "__MACOSX"
+        });
+        if entry.is_dir() || hidden {
+            continue;
+        }
+        unpacked += entry.size();
+        if files.len() == MAX_FILES || unpacked > MAX_UNPACKED_BYTES {
+            return Err(invalid(format!(
+                "A template has at most {} files and {} MiB",
+                MAX_FILES,
+                MAX_UNPACKED_BYTES / (1024 * 1024)
+            )));
+        }
+        let mut content = Vec::new();
+        entry.read_to_end(&mut content)?;
+        files.push((path.to_string_lossy().replace('\\', "/"), content));
+    }
+
+    // One top-level folder shared by every file is the template directory.
+    let prefix = files
+        .first()
+        .and_then(|(name, _)| name.split_once('/'))
+        .map(|(folder, _)| format!("{}/", folder));
+    if let Some(prefix) = prefix {
+        if files.iter().all(|(name, _)| name.starts_with(&prefix)) {
+            for (name, _) in &mut files {
+                *name = name[prefix.len()..].to_string();
+            }
+        }
+    }
+    Ok(files)
+}
+
+/// The template id and manifest of `files`, when they make a template.
+fn validate(files: &[(String, Vec<u8>)], server: &TemplateEngine) -> Result<(String, TemplateManifest)> {
+    for (name, _) in files {
+        check_file_name(name).map_err(|e| {
+            invalid(format!("{} (templates are flat .typ, .toml, .png, .jpg and .svg files)", e))
+        })?;
+    }
+    let file = |name: &str| files.iter().find(|(n, _)| n == name).map(|(_, c)| c);
+    let manifest = file("manifest.toml").ok_or_else(|| invalid("The archive has no manifest.toml"))?;
+    let manifest = std::str::from_utf8(manifest)
+        .map_err(|_| invalid("manifest.toml is not UTF-8"))?;
+    let manifest: TemplateManifest = toml::from_str(manifest)
+        .map_err(|e| invalid(format!("manifest.toml is invalid: {}", e)))?;
+
+    let id = manifest.name.trim().to_lowercase();
+    validate_id(&id).map_err(|_| {
+        invalid(format!(
+            "The manifest name '{}' must be lowercase letters, digits, '-' and '_'",
+            manifest.name
+        ))
+    })?;
+    if server.template_exists(&id) {
+        return Err(invalid(format!("'{}' is the name of a server template", id)));
+    }
+    let parent = manifest.extends.as_deref();
+    if let Some(parent) = parent {
+        server.ancestors(parent).map_err(|_| {
+            invalid(format!("'{}' extends '{}', which is not a usable server template", id, parent))
+        })?;
+    }
+    let main = manifest.main_file.as_deref().unwrap_or("main.typ");
+    let required = std::iter::once(main).chain(manifest.dependencies.iter().flatten().map(String::as_str));
+    for name in required {
+        if file(name).is_none() && !parent.is_some_and(|parent| server.provides(parent, name)) {
+            return Err(invalid(format!("'{}' is required by the manifest but not in the archive", name)));
+        }
+    }
+    Ok((id, manifest))
+}
+
+/// Validate `archive` and make it the tenant's template of its id.
+pub async fn install(archive: &[u8], tenant_dir: &Path, server: &TemplateEngine) -> Result<String> {
+    if archive.len() > MAX_ARCHIVE_BYTES {
+        return Err(invalid(format!(
+            "The archive is larger than {} MiB",
+            MAX_ARCHIVE_BYTES / (1024 * 1024)
+        )));
+    }
+    let files = unpack(archive)?;
+    let (id, _) = validate(&files, server)?;
+
+    // Written next to the template, then swapped in.
+    let staging = tenant_dir.join(format!(".{}.upload", id));
+    let target = tenant_dir.join(&id);
+    let previous = tenant_dir.join(format!(".{}.previous", id));
+    let _ = tokio::fs::remove_dir_all(&staging).await;
+    FsOps::ensure_dir_exists(&staging).await?;
+    for (name, content) in &files {
+        tokio::fs::write(staging.join(name), content)
+            .await
+            .with_context(|| format!("Failed to write {}", name))?;
+    }
+    let _ = tokio::fs::remove_dir_all(&previous).await;
+    if target.exists() {
+        tokio::fs::rename(&target, &previous).await?;
+    }
+    tokio::fs::rename(&staging, &target)
+        .await
+        .context("Failed to activate the template")?;
+    let _ = tokio::fs::remove_dir_all(&previous).await;
+    Ok(id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn archive(files: &[(&str, &str)]) -> Vec<u8> {
+        let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        for (name, content) in files {
+            zip.start_file(*name, zip::write::FileOptions::default()).unwrap();
+            zip.write_all(content.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap().into_inner()
+    }
+
+    #[tokio::test]
+    async fn uploads_are_validated_before_they_are_installed() {
+        let root = tempfile::tempdir().unwrap();
+        let server_dir = root.path().join("templates/default");
+        std::fs::create_dir_all(&server_dir).unwrap();
+        std::fs::write(server_dir.join("manifest.toml"), "name = \"default\"").unwrap();
+        let server = TemplateEngine::new(root.path().join("templates")).unwrap();
+        let tenant_dir = root.path().join("tenant/.templates");
+
+        let upload = archive(&[
+            ("acme/manifest.toml", "name = \"Acme\"\ndependencies = [\"template.typ\"]"),
+            ("acme/main.typ", "#include \"template.typ\""),
+            ("__MACOSX/acme/._main.typ", ""),
+        ]);
+        let err = install(&upload, &tenant_dir, &server).await.unwrap_err();
+        assert!(err.to_string().contains("template.typ"));
+        assert!(!tenant_dir.exists());
+
+        let clash = archive(&[("manifest.toml", "name = \"default\""), ("main.typ", "")]);
+        assert!(install(&clash, &tenant_dir, &server).await.is_err());
+
+        let upload = archive(&[
+            ("acme/manifest.toml", "name = \"Acme\"\ndependencies = [\"template.typ\"]"),
+            ("acme/main.typ", "#include \"template.typ\""),
+            ("acme/template.typ", ""),
+        ]);
+        assert_eq!(install(&upload, &tenant_dir, &server).await.unwrap(), "acme");
+        let engine = TemplateEngine::new(root.path().join("templates"))
+            .unwrap()
+            .with_tenant_templates(&tenant_dir);
+        assert!(engine.template_exists("acme"));
+        assert!(engine.template_exists("default"));
+    }
+
+    #[tokio::test]
+    async fn independent_accounts_of_one_domain_never_share_templates() {
+        let root = tempfile::tempdir().unwrap();
+        let server_dir = root.path().join("templates/default");
+        std::fs::create_dir_all(&server_dir).unwrap();
+        std::fs::write(server_dir.join("manifest.toml"), "name = \"default\"").unwrap();
+        let server = TemplateEngine::new(root.path().join("templates")).unwrap();
+        let data = root.path().join("data");
+
+        // Two email tenants that share the gmail-com data folder.
+        let jane = dir(&data, "jane@gmail.com", 1);
+        let john = dir(&data, "john@gmail.com", 2);
+        assert_ne!(jane, john);
+        assert_eq!(jane.parent(), john.parent());
+
+        let upload = archive(&[("manifest.toml", "name = \"mine\""), ("main.typ", "")]);
+        install(&upload, &jane, &server).await.unwrap();
+        let for_jane = TemplateEngine::new(root.path().join("templates"))
+            .unwrap()
+            .with_tenant_templates(&jane);
+        let for_john = TemplateEngine::new(root.path().join("templates"))
+            .unwrap()
+            .with_tenant_templates(&john);
+        assert!(for_jane.template_exists("mine"));
+        assert!(!for_john.template_exists("mine"));
+    }
+}
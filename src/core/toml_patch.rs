@@ -0,0 +1,263 @@
+//! Comment- and order-preserving rewrites of `cv_params.toml`.
+//!
+//! Write paths render a fresh TOML document from structured data (the form
+//! editor, section endpoints, imports and optimizations). Writing that
+//! string as-is would drop the user's comments and reorder their keys, so
+//! instead [`apply`] copies the fresh values for a set of owned keys into the
+//! existing document with `toml_edit`:
+//!
+//! * keys and tables already present keep their position, comments and
+//!   formatting — only values change;
+//! * keys missing from the fresh render are removed, new ones are appended;
+//! * `[[array]]` entries are updated in place, then truncated or extended;
+//! * keys outside the owned set are left untouched.
+
+use anyhow::{Context, Result};
+use toml_edit::{DocumentMut, Item, Table, Value};
+
+/// Parse a TOML document; empty input is an empty document.
+pub fn parse(content: &str) -> Result<DocumentMut> {
+    if content.trim().is_empty() {
+        return Ok(DocumentMut::new());
+    }
+    content.parse::<DocumentMut>().context("Invalid TOML")
+}
+
+/// Replace `keys` of `doc` with their values in `fresh`.
+pub fn apply(doc: &mut DocumentMut, fresh: &DocumentMut, keys: &[&str]) {
+    let mut next_position = max_position(doc.as_table()).map_or(0, |p| p + 1);
+    for key in keys {
+        match (doc.get_mut(key), fresh.get(key)) {
+            (Some(old), Some(new)) => update_item(old, new, &mut next_position),
+            (None, Some(new)) => {
+                let mut item = new.clone();
+                reposition(&mut item, &mut next_position);
+                doc.insert(key, item);
+            }
+            (_, None) => {
+                doc.remove(key);
+            }
+        }
+    }
+}
+
+/// [`apply`] on strings. `keys: None` means every top-level key of `fresh`.
+pub fn patch(existing: &str, fresh: &str, keys: Option<&[&str]>) -> Result<String> {
+    let mut doc = parse(existing)?;
+    let fresh = parse(fresh).context("Failed to parse generated TOML")?;
+    let all_keys: Vec<String>;
+    let keys = match keys {
+        Some(keys) => keys.to_vec(),
+        None => {
+            all_keys = fresh.iter().map(|(k, _)| k.to_string()).collect();
+            all_keys.iter().map(String::as_str).collect()
+        }
+    };
+    apply(&mut doc, &fresh, &keys);
+    Ok(doc.to_string())
+}
+
+/// Set `[table].key` to `value`, creating the table if needed. Returns
+/// `None` when the key already holds that value.
+pub fn set(
+    existing: &str,
+    table: &str,
+    key: &str,
+    value: impl Into<Value>,
+) -> Result<Option<String>> {
+    let mut doc = parse(existing)?;
+    let value = value.into();
+    let unchanged = doc
+        .get(table)
+        .and_then(|t| t.get(key))
+        .and_then(Item::as_value)
+        .is_some_and(|current| {
+            let mut bare = current.clone();
+            bare.decor_mut().clear();
+            bare.to_string() == value.to_string()
+        });
+    if unchanged {
+        return Ok(None);
+    }
+    if !doc.contains_key(table) {
+        let mut new_table = Table::new();
+        new_table.set_position(max_position(doc.as_table()).map_or(0, |p| p + 1));
+        doc.insert(table, Item::Table(new_table));
+    }
+    let target = doc
+        .get_mut(table)
+        .and_then(Item::as_table_like_mut)
+        .with_context(|| format!("`{}` is not a table", table))?;
+    match target.get_mut(key).and_then(Item::as_value_mut) {
+        Some(old) => {
+            let decor = old.decor().clone();
+            *old = value;
+            *old.decor_mut() = decor;
+        }
+        None => {
+            target.insert(key, Item::Value(value));
+        }
+    }
+    Ok(Some(doc.to_string()))
+}
+
+fn update_item(old: &mut Item, new: &Item, next_position: &mut usize) {
+    if let (Item::Table(old_table), Item::Table(new_table)) = (&mut *old, new) {
+        update_table(old_table, new_table, next_position);
+        return;
+    }
+    if let (Item::Value(old_value), Item::Value(new_value)) = (&mut *old, new) {
+        // Keep the trailing comment / spacing around the value.
+        let decor = old_value.decor().clone();
+        *old_value = new_value.clone();
+        *old_value.decor_mut() = decor;
+        return;
+    }
+    if let (Item::ArrayOfTables(old_array), Item::ArrayOfTables(new_array)) = (&mut *old, new) {
+        let last_position = old_array.iter().last().and_then(Table::position);
+        let common = old_array.len().min(new_array.len());
+        for (old_table, new_table) in old_array.iter_mut().zip(new_array.iter()) {
+            update_table(old_table, new_table, next_position);
+        }
+        while old_array.len() > new_array.len() {
+            old_array.remove(old_array.len() - 1);
+        }
+        for new_table in new_array.iter().skip(common) {
+            let mut table = new_table.clone();
+            // Sharing the last entry's position keeps new entries next to it.
+            match last_position {
+                Some(position) => table.set_position(position),
+                None => {
+                    table.set_position(*next_position);
+                    *next_position += 1;
+                }
+            }
+            old_array.push(table);
+        }
+        return;
+    }
+
+    // Shape changed (e.g. value → table): nothing to preserve.
+    let mut item = new.clone();
+    reposition(&mut item, next_position);
+    *old = item;
+}
+
+fn update_table(old: &mut Table, new: &Table, next_position: &mut usize) {
+    let stale: Vec<String> = old
+        .iter()
+        .map(|(k, _)| k.to_string())
+        .filter(|k| !new.contains_key(k))
+        .collect();
+    for key in stale {
+        old.remove(&key);
+    }
+    for (key, new_item) in new.iter() {
+        match old.get_mut(key) {
+            Some(old_item) => update_item(old_item, new_item, next_position),
+            None => {
+                let mut item = new_item.clone();
+                reposition(&mut item, next_position);
+                old.insert(key, item);
+            }
+        }
+    }
+}
+
+/// Items cloned from the fresh document carry its table positions; move
+/// them after everything already in the target document.
+fn reposition(item: &mut Item, next_position: &mut usize) {
+    match item {
+        Item::Table(table) => {
+            table.set_position(*next_position);
+            *next_position += 1;
+            for (_, child) in table.iter_mut() {
+                reposition(child, next_position);
+            }
+        }
+        Item::ArrayOfTables(array) => {
+            for table in array.iter_mut() {
+                table.set_position(*next_position);
+                *next_position += 1;
+                for (_, child) in table.iter_mut() {
+                    reposition(child, next_position);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn max_position(table: &Table) -> Option<usize> {
+    table
+        .iter()
+        .filter_map(|(_, item)| match item {
+            Item::Table(t) => t.position().max(max_position(t)),
+            Item::ArrayOfTables(a) => a
+                .iter()
+                .filter_map(|t| t.position().max(max_position(t)))
+                .max(),
+            _ => None,
+        })
+        .max()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_comments_order_and_unowned_keys() {
+        let existing = "\
+# Jane's CV — keep the title short
+title = \"Engineer\" # as on LinkedIn
+name = \"Jane\"
+custom = \"mine\"
+
+# Grouped by how I pitch myself
+[skills]
+tools = [\"git\"]
+legacy = [\"cobol\"]
+
+[[education]]
+title = \"MSc\" # thesis on compilers
+date = \"2010\"
+";
+        let fresh = "\
+name = \"Jane Doe\"
+title = \"Staff Engineer\"
+
+[skills]
+tools = [\"git\", \"nix\"]
+
+[[education]]
+title = \"MSc\"
+date = \"2010 - 2012\"
+
+[[education]]
+title = \"BSc\"
+date = \"2008\"
+
+[styling]
+show_photo = true
+";
+        let out = patch(
+            existing,
+            fresh,
+            Some(&["name", "title", "skills", "education", "styling"]),
+        )
+        .unwrap();
+
+        assert!(out.starts_with("# Jane's CV — keep the title short\ntitle = \"Staff Engineer\" # as on LinkedIn\nname = \"Jane Doe\"\n"));
+        assert!(out.contains("custom = \"mine\""));
+        assert!(out.contains("# Grouped by how I pitch myself\n[skills]"));
+        assert!(!out.contains("cobol"));
+        assert!(out.contains("title = \"MSc\" # thesis on compilers"));
+
+        let parsed: toml::Table = toml::from_str(&out).unwrap();
+        assert_eq!(parsed["education"].as_array().unwrap().len(), 2);
+        assert_eq!(parsed["education"][1]["title"].as_str(), Some("BSc"));
+        assert_eq!(parsed["styling"]["show_photo"].as_bool(), Some(true));
+        assert_eq!(parsed["skills"]["tools"][1].as_str(), Some("nix"));
+    }
+}
@@ -0,0 +1,247 @@
+// src/core/translation_status.rs
+//! Whether each language of a profile is a real translation.
+//!
+//! The profile's `.translations.json` records how every
+//! `experiences_<lang>.typ` was written — imported or saved in that language
+//! (`original`) or produced by the translation service (`translated`) — with
+//! the content hash at that moment. [`status`] compares it with the files on
+//! disk:
+//!
+//! * `copy`       — identical to another language's file (untranslated)
+//! * `original` / `translated` — unchanged since recorded
+//! * `edited`     — changed by hand since recorded
+//! * `unknown`    — written before provenance was tracked
+//!
+//! Recording is best effort: a failed write is logged and never fails the
+//! save that triggered it.
+
+use chrono::{DateTime, Utc};
+use graflog::app_log;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::core::data_encryption;
+use crate::core::language_files::content_hash;
+
+pub const PROFILE_FILE: &str = ".translations.json";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Provenance {
+    Original,
+    Translated,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranslationRecord {
+    pub provenance: Provenance,
+    /// Language translated from.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_lang: Option<String>,
+    pub content_hash: String,
+    pub recorded_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TranslationState {
+    Original,
+    Translated,
+    Edited,
+    Copy,
+    Unknown,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LanguageStatus {
+    pub lang: String,
+    pub status: TranslationState,
+    /// Language translated from, or copied for `copy`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_lang: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recorded_at: Option<DateTime<Utc>>,
+}
+
+type Records = BTreeMap<String, TranslationRecord>;
+
+fn load(profile_dir: &Path) -> Records {
+    std::fs::read(profile_dir.join(PROFILE_FILE))
+        .ok()
+        .and_then(|content| serde_json::from_slice(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save(profile_dir: &Path, records: &Records) {
+    let written = serde_json::to_vec_pretty(records)
+        .map_err(anyhow::Error::from)
+        .and_then(|json| Ok(std::fs::write(profile_dir.join(PROFILE_FILE), json)?));
+    if let Err(e) = written {
+        app_log!(
+            warn,
+            "Failed to save translation status for {}: {}",
+            profile_dir.display(),
+            e
+        );
+    }
+}
+
+fn experiences_path(profile_dir: &Path, lang: &str) -> std::path::PathBuf {
+    profile_dir.join(format!("experiences_{}.typ", lang))
+}
+
+/// Record how `lang`'s experiences file was just written.
+pub fn record(profile_dir: &Path, lang: &str, provenance: Provenance, source_lang: Option<&str>) {
+    let Ok(content) = data_encryption::read_to_string(&experiences_path(profile_dir, lang)) else {
+        return;
+    };
+    let mut records = load(profile_dir);
+    records.insert(
+        lang.to_string(),
+        TranslationRecord {
+            provenance,
+            source_lang: source_lang.map(str::to_string),
+            content_hash: content_hash(&content),
+            recorded_at: Utc::now(),
+        },
+    );
+    save(profile_dir, &records);
+}
+
+/// Drop the record of a deleted language file.
+pub fn forget(profile_dir: &Path, lang: &str) {
+    let mut records = load(profile_dir);
+    if records.remove(lang).is_some() {
+        save(profile_dir, &records);
+    }
+}
+
+/// Move the record of `from` to `to` after the file was relabeled.
+pub fn relabel(profile_dir: &Path, from: &str, to: &str) {
+    let mut records = load(profile_dir);
+    if let Some(record) = records.remove(from) {
+        records.insert(to.to_string(), record);
+        save(profile_dir, &records);
+    }
+}
+
+/// Status of every language in `files` (`(lang, content)`) given `records`.
+fn classify(files: &[(String, String)], records: &Records) -> Vec<LanguageStatus> {
+    let hashes: Vec<(&str, String)> = files
+        .iter()
+        .map(|(lang, content)| (lang.as_str(), content_hash(content)))
+        .collect();
+    // Of identical files, the recorded original wins, then English.
+    let rank = |lang: &str| {
+        let original = records
+            .get(lang)
+            .is_some_and(|r| r.provenance == Provenance::Original);
+        (!original, lang != "en", lang.to_string())
+    };
+
+    let mut statuses: Vec<LanguageStatus> = hashes
+        .iter()
+        .map(|(lang, hash)| {
+            let copied = hashes
+                .iter()
+                .filter(|(other, other_hash)| other != lang && other_hash == hash)
+                .map(|(other, _)| *other)
+                .min_by_key(|other| rank(other))
+                .filter(|other| rank(other) < rank(lang));
+            let record = records.get(*lang);
+            let (status, source_lang) = match (copied, record) {
+                (Some(original), _) => (TranslationState::Copy, Some(original.to_string())),
+                (None, Some(record)) if record.content_hash != *hash => {
+                    (TranslationState::Edited, record.source_lang.clone())
+                }
+                (None, Some(record)) => (
+                    match record.provenance {
+                        Provenance::Original => TranslationState::Original,
+                        Provenance::Translated => TranslationState::Translated,
+                    },
+                    record.source_lang.clone(),
+                ),
+                (None, None) => (TranslationState::Unknown, None),
+            };
+            LanguageStatus {
+                lang: lang.to_string(),
+                status,
+                source_lang,
+                recorded_at: record.map(|r| r.recorded_at),
+            }
+        })
+        .collect();
+    statuses.sort_by(|a, b| a.lang.cmp(&b.lang));
+    statuses
+}
+
+/// Status of each `experiences_<lang>.typ` of the profile.
+pub fn status(profile_dir: &Path) -> Vec<LanguageStatus> {
+    let files: Vec<(String, String)> = std::fs::read_dir(profile_dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter_map(|entry| {
+                    let name = entry.file_name().to_string_lossy().to_string();
+                    let lang = name.strip_prefix("experiences_")?.strip_suffix(".typ")?;
+                    if lang.is_empty() {
+                        return None;
+                    }
+                    let content = data_encryption::read_to_string(&entry.path()).ok()?;
+                    Some((lang.to_string(), content))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    classify(&files, &load(profile_dir))
+}
+
+/// The language `lang` is an untranslated copy of, if it is one.
+pub fn copied_from(profile_dir: &Path, lang: &str) -> Option<String> {
+    status(profile_dir)
+        .into_iter()
+        .find(|s| s.lang == lang && s.status == TranslationState::Copy)
+        .and_then(|s| s.source_lang)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn copies_edits_and_translations_are_told_apart() {
+        let file = |lang: &str, content: &str| (lang.to_string(), content.to_string());
+        let recorded = |provenance, content: &str| TranslationRecord {
+            provenance,
+            source_lang: (provenance == Provenance::Translated).then(|| "en".to_string()),
+            content_hash: content_hash(content),
+            recorded_at: Utc::now(),
+        };
+        let mut records = Records::new();
+        records.insert("fr".to_string(), recorded(Provenance::Original, "Bonjour"));
+        records.insert("de".to_string(), recorded(Provenance::Translated, "Hallo"));
+        records.insert("es".to_string(), recorded(Provenance::Translated, "Hola"));
+
+        let statuses = classify(
+            &[
+                file("de", "Hallo"),
+                file("en", "Bonjour"),
+                file("es", "Hola, editado"),
+                file("fr", "Bonjour"),
+                file("it", "Ciao"),
+            ],
+            &records,
+        );
+        let of = |lang: &str| {
+            let s = statuses.iter().find(|s| s.lang == lang).unwrap();
+            (s.status, s.source_lang.clone())
+        };
+        assert_eq!(of("de"), (TranslationState::Translated, Some("en".into())));
+        // The recorded original wins over English among identical files.
+        assert_eq!(of("en"), (TranslationState::Copy, Some("fr".into())));
+        assert_eq!(of("fr"), (TranslationState::Original, None));
+        assert_eq!(of("es"), (TranslationState::Edited, Some("en".into())));
+        assert_eq!(of("it"), (TranslationState::Unknown, None));
+    }
+}
@@ -0,0 +1,65 @@
+// src/core/translations.rs
+//! Central registry of template text blocks (section titles, labels).
+//!
+//! The strings live in `templates/translations.toml`, one table per language,
+//! and are compiled in. [`crate::core::TemplateEngine::text_inputs`] hands
+//! them to Typst as `text.<key>` inputs, which templates read through
+//! `localized()` in `common.typ`; the converter uses [`text`] for the few
+//! strings it writes itself. Adding a language means adding a table to the
+//! TOML file.
+
+use std::collections::BTreeMap;
+use std::sync::LazyLock;
+
+use graflog::app_log;
+
+pub const FALLBACK_LANG: &str = "en";
+
+type Table = BTreeMap<String, String>;
+
+static REGISTRY: LazyLock<BTreeMap<String, Table>> = LazyLock::new(|| {
+    toml::from_str(include_str!("../../templates/translations.toml")).unwrap_or_else(|e| {
+        app_log!(error, "templates/translations.toml is invalid: {}", e);
+        BTreeMap::new()
+    })
+});
+
+/// Languages with a table in the registry.
+pub fn languages() -> Vec<&'static str> {
+    REGISTRY.keys().map(String::as_str).collect()
+}
+
+/// Every string for `lang`, English filling in keys the language lacks.
+pub fn strings(lang: &str) -> Table {
+    let mut strings = REGISTRY.get(FALLBACK_LANG).cloned().unwrap_or_default();
+    if let Some(table) = REGISTRY.get(lang) {
+        strings.extend(table.clone());
+    }
+    strings
+}
+
+/// One string for `lang`, falling back to English, then to the key itself.
+pub fn text(lang: &str, key: &str) -> String {
+    REGISTRY
+        .get(lang)
+        .and_then(|t| t.get(key))
+        .or_else(|| REGISTRY.get(FALLBACK_LANG).and_then(|t| t.get(key)))
+        .cloned()
+        .unwrap_or_else(|| key.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn falls_back_to_english_then_key() {
+        for lang in ["en", "fr", "de"] {
+            assert!(languages().contains(&lang), "registry lacks [{}]", lang);
+        }
+        assert_eq!(text("de", "present"), "Heute");
+        assert_eq!(text("xx", "present"), "Present");
+        assert_eq!(text("fr", "no_such_key"), "no_such_key");
+        assert_eq!(strings("xx"), strings("en"));
+    }
+}
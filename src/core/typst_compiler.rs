@@ -0,0 +1,441 @@
+// src/core/typst_compiler.rs
+//! In-process Typst compilation.
+//!
+//! Generations used to run the `typst` binary. [`compile`] runs the `typst`
+//! crate instead, over a [`World`] rooted at the prepared workspace: files
+//! are read from the workspace (packages are not available), `sys.inputs`
+//! carries what used to be `--input` flags and fonts are the system fonts,
+//! the directories in `TYPST_FONT_PATHS`, the synced fonts of
+//! `ASSET_SYNC_FONTS_DIR` and Typst's embedded fonts. Fonts
+//! are discovered once per process (and again when asset sync brings new
+//! ones), and Typst's memoized layout results are kept between
+//! compilations, so regenerating a CV only redoes what changed.
+//!
+//! A failed compilation is a [`CompileFailed`] carrying its diagnostics with
+//! the file and line they point at. A PDF can be given [`PdfMetadata`] and be
+//! exported as PDF/A-2b for archiving.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock, Mutex, RwLock};
+use std::time::Duration;
+
+use chrono::{DateTime, Datelike, Local, TimeDelta, Timelike, Utc};
+use serde::Serialize;
+use typst::diag::{FileError, FileResult, Severity, SourceDiagnostic, Warned};
+use typst::foundations::{Bytes, Datetime, Dict, Str, Value};
+use typst::html::HtmlDocument;
+use typst::layout::PagedDocument;
+use typst::model::DocumentInfo;
+use typst::syntax::{FileId, Source, VirtualPath};
+use typst::text::{Font, FontBook};
+use typst::utils::LazyHash;
+use typst::{Feature, Features, Library, World};
+use typst_kit::fonts::{FontSearcher, FontSlot};
+use typst_pdf::{PdfOptions, PdfStandard, PdfStandards, Timestamp};
+
+/// Compilations a memoized result survives without being used.
+const CACHE_MAX_AGE: usize = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Output {
+    Pdf,
+    /// A PDF/A-2b document, for archiving.
+    PdfA,
+    Html,
+    /// The first page as a PNG at this resolution.
+    Png { ppi: f32 },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiagnosticSeverity {
+    Error,
+    Warning,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Diagnostic {
+    pub severity: DiagnosticSeverity,
+    pub message: String,
+    /// Workspace file the diagnostic points at.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    /// 1-based.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub hints: Vec<String>,
+}
+
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (&self.file, self.line) {
+            (Some(file), Some(line)) => write!(f, "{}:{}: {}", file, line, self.message),
+            (Some(file), None) => write!(f, "{}: {}", file, self.message),
+            _ => write!(f, "{}", self.message),
+        }
+    }
+}
+
+/// Document properties of a PDF, written over those the template sets.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PdfMetadata {
+    pub title: Option<String>,
+    pub author: Option<String>,
+    /// Written as the PDF's subject.
+    pub subject: Option<String>,
+    pub keywords: Vec<String>,
+    pub created: Option<DateTime<Utc>>,
+}
+
+impl PdfMetadata {
+    fn apply(&self, info: &mut DocumentInfo) {
+        if let Some(title) = &self.title {
+            info.title = Some(title.as_str().into());
+        }
+        if let Some(author) = &self.author {
+            info.author = vec![author.as_str().into()];
+        }
+        if let Some(subject) = &self.subject {
+            info.description = Some(subject.as_str().into());
+        }
+        info.keywords
+            .extend(self.keywords.iter().map(|k| k.as_str().into()));
+    }
+
+    fn options(&self, archival: bool) -> anyhow::Result<PdfOptions<'static>> {
+        let standards = match archival {
+            true => PdfStandards::new(&[PdfStandard::A_2b])
+                .map_err(|e| anyhow::anyhow!("PDF/A is not available: {}", e))?,
+            false => PdfStandards::default(),
+        };
+        let timestamp = self.created.and_then(|at| {
+            Datetime::from_ymd_hms(
+                at.year(),
+                at.month() as u8,
+                at.day() as u8,
+                at.hour() as u8,
+                at.minute() as u8,
+                at.second() as u8,
+            )
+            .map(Timestamp::new_utc)
+        });
+        Ok(PdfOptions {
+            timestamp,
+            standards,
+            ..Default::default()
+        })
+    }
+}
+
+/// Typst rejected the document.
+#[derive(Debug)]
+pub struct CompileFailed {
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl CompileFailed {
+    pub fn errors(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.diagnostics
+            .iter()
+            .filter(|d| d.severity == DiagnosticSeverity::Error)
+    }
+}
+
+impl std::fmt::Display for CompileFailed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let errors: Vec<String> = self.errors().map(ToString::to_string).collect();
+        write!(f, "Typst compilation failed: {}", errors.join("; "))
+    }
+}
+
+impl std::error::Error for CompileFailed {}
+
+pub struct Compiled {
+    pub bytes: Vec<u8>,
+    pub warnings: Vec<Diagnostic>,
+}
+
+struct Fonts {
+    book: LazyHash<FontBook>,
+    slots: Vec<FontSlot>,
+}
+
+fn search_fonts() -> Fonts {
+    let mut dirs: Vec<PathBuf> = std::env::var_os("TYPST_FONT_PATHS")
+        .map(|paths| std::env::split_paths(&paths).collect())
+        .unwrap_or_default();
+    dirs.extend(crate::core::asset_sync::fonts_dir());
+    let fonts = FontSearcher::new()
+        .include_system_fonts(true)
+        .search_with(dirs);
+    Fonts {
+        book: LazyHash::new(fonts.book),
+        slots: fonts.fonts,
+    }
+}
+
+static FONTS: LazyLock<RwLock<Arc<Fonts>>> = LazyLock::new(|| RwLock::new(Arc::new(search_fonts())));
+
+/// Families of `families` no installed font belongs to.
+pub fn missing_fonts(families: &[String]) -> Vec<String> {
+    let fonts = FONTS.read().unwrap_or_else(|p| p.into_inner()).clone();
+    families
+        .iter()
+        .filter(|family| !fonts.book.contains_family(&family.to_lowercase()))
+        .cloned()
+        .collect()
+}
+
+/// Discover the fonts again, e.g. after [`crate::core::asset_sync`] added
+/// some. Compilations already running keep the fonts they started with.
+pub fn reload_fonts() {
+    let fonts = Arc::new(search_fonts());
+    *FONTS.write().unwrap_or_else(|p| p.into_inner()) = fonts;
+}
+
+/// The workspace as Typst sees it: its files, the inputs and the fonts.
+struct WorkspaceWorld {
+    root: PathBuf,
+    main: FileId,
+    library: LazyHash<Library>,
+    fonts: Arc<Fonts>,
+    sources: Mutex<HashMap<FileId, Source>>,
+    files: Mutex<HashMap<FileId, Bytes>>,
+}
+
+impl WorkspaceWorld {
+    fn new(root: &Path, main: &str, inputs: &[(String, String)], html: bool) -> Self {
+        let inputs: Dict = inputs
+            .iter()
+            .map(|(key, value)| (Str::from(key.as_str()), Value::Str(value.as_str().into())))
+            .collect();
+        let mut library = Library::builder().with_inputs(inputs);
+        if html {
+            library = library.with_features(Features::from_iter([Feature::Html]));
+        }
+        Self {
+            root: root.to_path_buf(),
+            main: FileId::new(None, VirtualPath::new(main)),
+            library: LazyHash::new(library.build()),
+            fonts: FONTS.read().unwrap_or_else(|p| p.into_inner()).clone(),
+            sources: Mutex::new(HashMap::new()),
+            files: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn read(&self, id: FileId) -> FileResult<Bytes> {
+        if id.package().is_some() {
+            return Err(FileError::Other(Some(
+                "packages are not available to CV templates".into(),
+            )));
+        }
+        let path = id.vpath().resolve(&self.root).ok_or(FileError::AccessDenied)?;
+        std::fs::read(&path)
+            .map(Bytes::new)
+            .map_err(|e| FileError::from_io(e, &path))
+    }
+
+    fn diagnostic(&self, diagnostic: &SourceDiagnostic) -> Diagnostic {
+        let location = diagnostic.span.id().and_then(|id| {
+            let file = id.vpath().as_rootless_path().display().to_string();
+            let line = self.source(id).ok().and_then(|source| {
+                let range = source.range(diagnostic.span)?;
+                source.byte_to_line(range.start).map(|line| line + 1)
+            });
+            Some((file, line))
+        });
+        Diagnostic {
+            severity: match diagnostic.severity {
+                Severity::Error => DiagnosticSeverity::Error,
+                Severity::Warning => DiagnosticSeverity::Warning,
+            },
+            message: diagnostic.message.to_string(),
+            file: location.as_ref().map(|(file, _)| file.clone()),
+            line: location.and_then(|(_, line)| line),
+            hints: diagnostic.hints.iter().map(ToString::to_string).collect(),
+        }
+    }
+
+    fn diagnostics(&self, diagnostics: &[SourceDiagnostic]) -> Vec<Diagnostic> {
+        diagnostics.iter().map(|d| self.diagnostic(d)).collect()
+    }
+
+    fn failed(&self, errors: &[SourceDiagnostic], warnings: &[SourceDiagnostic]) -> CompileFailed {
+        let mut diagnostics = self.diagnostics(errors);
+        diagnostics.extend(self.diagnostics(warnings));
+        CompileFailed { diagnostics }
+    }
+}
+
+impl World for WorkspaceWorld {
+    fn library(&self) -> &LazyHash<Library> {
+        &self.library
+    }
+
+    fn book(&self) -> &LazyHash<FontBook> {
+        &self.fonts.book
+    }
+
+    fn main(&self) -> FileId {
+        self.main
+    }
+
+    fn source(&self, id: FileId) -> FileResult<Source> {
+        let mut sources = self.sources.lock().unwrap_or_else(|p| p.into_inner());
+        if let Some(source) = sources.get(&id) {
+            return Ok(source.clone());
+        }
+        let bytes = self.file(id)?;
+        let text = std::str::from_utf8(&bytes).map_err(|_| FileError::InvalidUtf8)?;
+        let source = Source::new(id, text.to_string());
+        sources.insert(id, source.clone());
+        Ok(source)
+    }
+
+    fn file(&self, id: FileId) -> FileResult<Bytes> {
+        let mut files = self.files.lock().unwrap_or_else(|p| p.into_inner());
+        if let Some(bytes) = files.get(&id) {
+            return Ok(bytes.clone());
+        }
+        let bytes = self.read(id)?;
+        files.insert(id, bytes.clone());
+        Ok(bytes)
+    }
+
+    fn font(&self, index: usize) -> Option<Font> {
+        self.fonts.slots.get(index)?.get()
+    }
+
+    fn today(&self, offset: Option<i64>) -> Option<Datetime> {
+        let date = match offset {
+            Some(hours) => (Utc::now() + TimeDelta::hours(hours)).date_naive(),
+            None => Local::now().date_naive(),
+        };
+        Datetime::from_ymd(date.year(), date.month() as u8, date.day() as u8)
+    }
+}
+
+/// Compile `main` (a path relative to `root`) with `inputs` as `sys.inputs`.
+pub fn compile(
+    root: &Path,
+    main: &str,
+    inputs: &[(String, String)],
+    output: Output,
+) -> anyhow::Result<Compiled> {
+    compile_with_metadata(root, main, inputs, output, &PdfMetadata::default())
+}
+
+/// [`compile`], writing `metadata` into a PDF.
+pub fn compile_with_metadata(
+    root: &Path,
+    main: &str,
+    inputs: &[(String, String)],
+    output: Output,
+    metadata: &PdfMetadata,
+) -> anyhow::Result<Compiled> {
+    let world = WorkspaceWorld::new(root, main, inputs, output == Output::Html);
+    let result = match output {
+        Output::Pdf | Output::PdfA | Output::Png { .. } => {
+            let Warned { output: document, warnings } = typst::compile::<PagedDocument>(&world);
+            let mut document = document.map_err(|errors| world.failed(&errors, &warnings))?;
+            metadata.apply(&mut document.info);
+            let bytes = match output {
+                Output::Png { ppi } => {
+                    let page = document
+                        .pages
+                        .first()
+                        .ok_or_else(|| anyhow::anyhow!("The document has no pages"))?;
+                    typst_render::render(page, ppi / 72.0)
+                        .encode_png()
+                        .map_err(|e| anyhow::anyhow!("Failed to encode the preview: {}", e))?
+                }
+                _ => typst_pdf::pdf(&document, &metadata.options(output == Output::PdfA)?)
+                    .map_err(|errors| world.failed(&errors, &warnings))?,
+            };
+            Compiled { bytes, warnings: world.diagnostics(&warnings) }
+        }
+        Output::Html => {
+            let Warned { output: document, warnings } = typst::compile::<HtmlDocument>(&world);
+            let document = document.map_err(|errors| world.failed(&errors, &warnings))?;
+            let html = typst_html::html(&document)
+                .map_err(|errors| world.failed(&errors, &warnings))?;
+            Compiled { bytes: html.into_bytes(), warnings: world.diagnostics(&warnings) }
+        }
+    };
+    comemo::evict(CACHE_MAX_AGE);
+    Ok(result)
+}
+
+/// [`compile`] on the blocking pool, giving up after `timeout`. A compilation
+/// can't be interrupted: one that times out finishes in the background.
+pub async fn compile_with_timeout(
+    root: &Path,
+    main: &str,
+    inputs: Vec<(String, String)>,
+    output: Output,
+    timeout: Duration,
+) -> anyhow::Result<Compiled> {
+    compile_with_metadata_timeout(root, main, inputs, output, PdfMetadata::default(), timeout)
+        .await
+}
+
+/// [`compile_with_timeout`], writing `metadata` into a PDF.
+pub async fn compile_with_metadata_timeout(
+    root: &Path,
+    main: &str,
+    inputs: Vec<(String, String)>,
+    output: Output,
+    metadata: PdfMetadata,
+    timeout: Duration,
+) -> anyhow::Result<Compiled> {
+    let root = root.to_path_buf();
+    let main = main.to_string();
+    let task = tokio::task::spawn_blocking(move || {
+        compile_with_metadata(&root, &main, &inputs, output, &metadata)
+    });
+    match tokio::time::timeout(timeout, task).await {
+        Err(_) => anyhow::bail!("compilation timed out after {}s", timeout.as_secs()),
+        Ok(joined) => joined?,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compiles_with_inputs_and_reports_error_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("main.typ"), "Hello #sys.inputs.at(\"name\")\n").unwrap();
+        let inputs = [("name".to_string(), "Jane".to_string())];
+        let pdf = compile(dir.path(), "main.typ", &inputs, Output::Pdf).unwrap();
+        assert!(pdf.bytes.starts_with(b"%PDF"));
+
+        std::fs::write(dir.path().join("main.typ"), "Hello\n#missing_function()\n").unwrap();
+        let err = compile(dir.path(), "main.typ", &[], Output::Pdf).unwrap_err();
+        let failed = err.downcast_ref::<CompileFailed>().unwrap();
+        let error = failed.errors().next().unwrap();
+        assert_eq!(error.file.as_deref(), Some("main.typ"));
+        assert_eq!(error.line, Some(2));
+    }
+
+    #[test]
+    fn archival_pdf_carries_the_metadata() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("main.typ"), "Hello\n").unwrap();
+        let metadata = PdfMetadata {
+            title: Some("Jane Doe CV".to_string()),
+            author: Some("Jane Doe".to_string()),
+            created: Some(Utc::now()),
+            ..Default::default()
+        };
+        let pdf = compile_with_metadata(dir.path(), "main.typ", &[], Output::PdfA, &metadata)
+            .unwrap()
+            .bytes;
+        let contains = |needle: &[u8]| pdf.windows(needle.len()).any(|w| w == needle);
+        assert!(contains(b"Jane Doe CV"));
+        assert!(contains(b"pdfaid"));
+    }
+}
@@ -0,0 +1,225 @@
+// src/core/typst_lint.rs
+//! Content linter for Typst saved from the editor.
+//!
+//! Typst compiles with the workspace as its root, so it already cannot reach
+//! files outside it; this catches the attempt at save time instead, with a
+//! message the user can act on. Flagged:
+//!
+//! * `file_read`      — `read()`, `json()`, `yaml()`, `toml()`, `csv()`,
+//!   `xml()`, `cbor()` and `plugin()` calls (a CV has no reason to load files)
+//! * `include`        — `#include`, or `include` of a string literal
+//! * `absolute_path`  — an absolute path given to `import`, `include`,
+//!   `image` or `bibliography`
+//! * `parent_path`    — such a path containing `..`
+//! * `package_import` — `import "@preview/…"`, which downloads at compile time
+//!
+//! The scan is deliberately naive: comments and strings are not exempt, so it
+//! errs on the side of flagging. What happens to findings depends on the
+//! tenant's level (`off`, `warn` or `block`), set by the admin and defaulting
+//! to `TYPST_LINT_LEVEL` (default `block`).
+
+use anyhow::Result;
+use chrono::Utc;
+use serde::Serialize;
+use sqlx::SqlitePool;
+
+const FILE_READERS: &[&str] = &[
+    "read", "json", "yaml", "toml", "csv", "xml", "cbor", "plugin",
+];
+const PATH_TAKERS: &[&str] = &["import", "include", "image", "bibliography"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LintLevel {
+    Off,
+    Warn,
+    Block,
+}
+
+impl LintLevel {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "off" => Some(Self::Off),
+            "warn" => Some(Self::Warn),
+            "block" => Some(Self::Block),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Off => "off",
+            Self::Warn => "warn",
+            Self::Block => "block",
+        }
+    }
+}
+
+impl Default for LintLevel {
+    /// `TYPST_LINT_LEVEL`, or `block`.
+    fn default() -> Self {
+        std::env::var("TYPST_LINT_LEVEL")
+            .ok()
+            .and_then(|v| Self::parse(&v))
+            .unwrap_or(Self::Block)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LintFinding {
+    pub rule: &'static str,
+    /// 1-based line of the offending call.
+    pub line: usize,
+    pub message: String,
+}
+
+impl std::fmt::Display for LintFinding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Line {}: {}", self.line, self.message)
+    }
+}
+
+pub(crate) fn is_ident_start(b: u8) -> bool {
+    b.is_ascii_alphabetic() || b == b'_'
+}
+
+pub(crate) fn is_ident_char(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b == b'_' || b == b'-'
+}
+
+/// The string literal starting at `i` (after optional `(` and whitespace).
+pub(crate) fn string_arg(source: &str, mut i: usize) -> Option<&str> {
+    let bytes = source.as_bytes();
+    while i < bytes.len() && (bytes[i].is_ascii_whitespace() || bytes[i] == b'(') {
+        i += 1;
+    }
+    if bytes.get(i) != Some(&b'"') {
+        return None;
+    }
+    let rest = &source[i + 1..];
+    rest.find('"').map(|end| &rest[..end])
+}
+
+fn path_problem(path: &str) -> Option<(&'static str, &'static str)> {
+    let bytes = path.as_bytes();
+    if path.starts_with('@') {
+        Some(("package_import", "imports a package"))
+    } else if path.starts_with(['/', '\\', '~']) || (bytes.len() > 1 && bytes[1] == b':') {
+        Some(("absolute_path", "uses an absolute path"))
+    } else if path.split(['/', '\\']).any(|segment| segment == "..") {
+        Some(("parent_path", "reaches outside the CV folder with '..'"))
+    } else {
+        None
+    }
+}
+
+/// Findings in `source`, in order of appearance.
+pub fn lint(source: &str) -> Vec<LintFinding> {
+    let bytes = source.as_bytes();
+    let line_of = |offset: usize| source[..offset].matches('\n').count() + 1;
+    let mut findings = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        if !is_ident_start(bytes[i]) || (i > 0 && is_ident_char(bytes[i - 1])) {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        while i < bytes.len() && is_ident_char(bytes[i]) {
+            i += 1;
+        }
+        let ident = &source[start..i];
+        let hashed = start > 0 && bytes[start - 1] == b'#';
+
+        if FILE_READERS.contains(&ident) && bytes.get(i) == Some(&b'(') {
+            findings.push(LintFinding {
+                rule: "file_read",
+                line: line_of(start),
+                message: format!("{}() reads files, which CV content may not do", ident),
+            });
+            continue;
+        }
+        if !PATH_TAKERS.contains(&ident) {
+            continue;
+        }
+        let path = string_arg(source, i);
+        if ident == "include" && (hashed || path.is_some()) {
+            findings.push(LintFinding {
+                rule: "include",
+                line: line_of(start),
+                message: "include pulls another file into the CV".to_string(),
+            });
+            continue;
+        }
+        if let Some((rule, problem)) = path.and_then(path_problem) {
+            findings.push(LintFinding {
+                rule,
+                line: line_of(start),
+                message: format!("{} \"{}\" {}", ident, path.unwrap_or_default(), problem),
+            });
+        }
+    }
+    findings
+}
+
+/// Lint level of tenant `tenant_id`, falling back to the server default.
+pub async fn level_for(pool: &SqlitePool, tenant_id: i64) -> LintLevel {
+    let stored: Option<String> =
+        sqlx::query_scalar("SELECT level FROM typst_lint_levels WHERE tenant_id = ?")
+            .bind(tenant_id)
+            .fetch_optional(pool)
+            .await
+            .ok()
+            .flatten();
+    stored
+        .as_deref()
+        .and_then(LintLevel::parse)
+        .unwrap_or_default()
+}
+
+pub async fn set_level(pool: &SqlitePool, tenant_id: i64, level: LintLevel) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO typst_lint_levels (tenant_id, level, updated_at) VALUES (?, ?, ?) \
+         ON CONFLICT(tenant_id) DO UPDATE SET level = excluded.level, \
+         updated_at = excluded.updated_at",
+    )
+    .bind(tenant_id)
+    .bind(level.as_str())
+    .bind(Utc::now())
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(source: &str) -> Vec<&'static str> {
+        lint(source).into_iter().map(|f| f.rule).collect()
+    }
+
+    #[test]
+    fn flags_file_access_but_not_template_imports() {
+        let clean = "#import \"template.typ\": conf, dated_experience\n\
+                     #image(\"profile.png\")\n\
+                     Responsibilities include thread(pool) tuning, already(done)";
+        assert!(lint(clean).is_empty());
+
+        let source = "#let secret = read(\"/etc/passwd\")\n\
+                      #include \"other.typ\"\n\
+                      #import \"../../jane/cv_params.toml\"\n\
+                      #image(\"C:\\\\photo.png\")\n\
+                      #import \"@preview/cetz:0.2.0\"";
+        assert_eq!(
+            rules(source),
+            vec![
+                "file_read",
+                "include",
+                "parent_path",
+                "absolute_path",
+                "package_import"
+            ]
+        );
+        assert_eq!(lint(source)[2].line, 3);
+    }
+}
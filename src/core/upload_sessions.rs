@@ -0,0 +1,345 @@
+// src/core/upload_sessions.rs
+//! Resumable uploads with server-side progress.
+//!
+//! A multipart upload is one opaque request: on a slow link the studio can't
+//! tell how far a 10 MB CV has got, and a dropped connection starts it over.
+//! Instead the client [`create`]s a session announcing the file's size, then
+//! sends the bytes in one or more chunks, each starting at the offset the
+//! server has so far (`Upload-Offset`). Bytes are counted as they are
+//! written, so [`progress`] and the [`subscribe`] stream show a chunk's
+//! progress while it is still arriving. After an interruption the client asks
+//! for the offset and sends the rest. A complete upload is [`take`]n by the
+//! endpoint that consumes it, e.g. the CV import.
+//!
+//! Sessions live in memory, belong to the user that created them and are
+//! dropped with their file after `SESSION_TTL` without activity.
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
+use tokio::sync::watch;
+
+pub const OFFSET_HEADER: &str = "Upload-Offset";
+pub const UPLOAD_DIR: &str = ".uploads";
+pub const MAX_UPLOAD_BYTES: u64 = 10 * 1024 * 1024;
+const SESSION_TTL: Duration = Duration::from_secs(60 * 60);
+const BUFFER_BYTES: usize = 64 * 1024;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UploadProgress {
+    pub id: String,
+    pub filename: String,
+    pub size: u64,
+    pub received: u64,
+    pub percent: u8,
+    pub complete: bool,
+}
+
+/// A finished upload, handed to the endpoint that consumes it.
+#[derive(Debug, Clone)]
+pub struct CompletedUpload {
+    pub path: PathBuf,
+    pub filename: String,
+    pub content_type: Option<String>,
+    pub size: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UploadError {
+    NotFound,
+    /// The chunk doesn't start where the upload stands.
+    OffsetMismatch { expected: u64 },
+    /// Another chunk is being written.
+    Busy,
+    TooLarge,
+    Incomplete { received: u64, size: u64 },
+    Invalid(String),
+    Io(String),
+}
+
+impl std::fmt::Display for UploadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotFound => write!(f, "Upload session not found or expired"),
+            Self::OffsetMismatch { expected } => {
+                write!(f, "The upload continues at byte {}", expected)
+            }
+            Self::Busy => write!(f, "A chunk of this upload is still being received"),
+            Self::TooLarge => write!(f, "The upload is larger than announced"),
+            Self::Incomplete { received, size } => {
+                write!(f, "Only {} of {} bytes were received", received, size)
+            }
+            Self::Invalid(message) => write!(f, "{}", message),
+            Self::Io(message) => write!(f, "Failed to store the upload: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for UploadError {}
+
+impl From<std::io::Error> for UploadError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e.to_string())
+    }
+}
+
+struct Session {
+    owner: String,
+    filename: String,
+    content_type: Option<String>,
+    size: u64,
+    path: PathBuf,
+    received: Arc<watch::Sender<u64>>,
+    writing: bool,
+    touched: Instant,
+}
+
+impl Session {
+    fn progress(&self, id: &str) -> UploadProgress {
+        progress_of(id, &self.filename, self.size, *self.received.borrow())
+    }
+}
+
+fn progress_of(id: &str, filename: &str, size: u64, received: u64) -> UploadProgress {
+    UploadProgress {
+        id: id.to_string(),
+        filename: filename.to_string(),
+        size,
+        received,
+        percent: (received * 100 / size.max(1)).min(100) as u8,
+        complete: received == size,
+    }
+}
+
+static SESSIONS: LazyLock<Mutex<HashMap<String, Session>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+fn sessions() -> MutexGuard<'static, HashMap<String, Session>> {
+    SESSIONS
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// `owner`'s session `id`.
+fn owned<'a>(
+    sessions: &'a mut HashMap<String, Session>,
+    owner: &str,
+    id: &str,
+) -> Result<&'a mut Session, UploadError> {
+    sessions
+        .get_mut(id)
+        .filter(|s| s.owner.eq_ignore_ascii_case(owner))
+        .ok_or(UploadError::NotFound)
+}
+
+/// Drop idle sessions and their files.
+fn sweep(sessions: &mut HashMap<String, Session>, now: Instant) {
+    sessions.retain(|_, s| {
+        let keep = s.writing || now.duration_since(s.touched) < SESSION_TTL;
+        if !keep {
+            let _ = std::fs::remove_file(&s.path);
+        }
+        keep
+    });
+}
+
+/// Open a session for a `size`-byte file, stored under `dir`.
+pub fn create(
+    owner: &str,
+    filename: &str,
+    content_type: Option<String>,
+    size: u64,
+    dir: &Path,
+) -> Result<UploadProgress, UploadError> {
+    let filename = Path::new(filename.trim())
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .filter(|n| !n.is_empty())
+        .ok_or_else(|| UploadError::Invalid("The upload needs a file name".to_string()))?;
+    if size == 0 || size > MAX_UPLOAD_BYTES {
+        return Err(UploadError::Invalid(format!(
+            "Uploads must be between 1 byte and {} MB",
+            MAX_UPLOAD_BYTES / (1024 * 1024)
+        )));
+    }
+    std::fs::create_dir_all(dir)?;
+    let id = uuid::Uuid::new_v4().to_string();
+    let session = Session {
+        owner: owner.to_lowercase(),
+        filename,
+        content_type,
+        size,
+        path: dir.join(format!("{}.part", id)),
+        received: Arc::new(watch::channel(0).0),
+        writing: false,
+        touched: Instant::now(),
+    };
+    let progress = session.progress(&id);
+    let mut sessions = sessions();
+    sweep(&mut sessions, Instant::now());
+    sessions.insert(id, session);
+    Ok(progress)
+}
+
+pub fn progress(owner: &str, id: &str) -> Result<UploadProgress, UploadError> {
+    let mut sessions = sessions();
+    Ok(owned(&mut sessions, owner, id)?.progress(id))
+}
+
+/// Current progress, and a receiver of the byte count as it changes. The
+/// receiver closes when the session is taken, cancelled or expires.
+pub fn subscribe(
+    owner: &str,
+    id: &str,
+) -> Result<(UploadProgress, watch::Receiver<u64>), UploadError> {
+    let mut sessions = sessions();
+    let session = owned(&mut sessions, owner, id)?;
+    Ok((session.progress(id), session.received.subscribe()))
+}
+
+/// Progress for a byte count reported by [`subscribe`].
+pub fn progress_at(first: &UploadProgress, received: u64) -> UploadProgress {
+    progress_of(&first.id, &first.filename, first.size, received)
+}
+
+/// Clears the session's `writing` mark however the write ends.
+struct Writing<'a>(&'a str);
+
+impl Drop for Writing<'_> {
+    fn drop(&mut self) {
+        if let Some(session) = sessions().get_mut(self.0) {
+            session.writing = false;
+            session.touched = Instant::now();
+        }
+    }
+}
+
+/// Write `body` at `offset`, which must be where the upload stands.
+pub async fn append<R: AsyncRead + Unpin>(
+    owner: &str,
+    id: &str,
+    offset: u64,
+    mut body: R,
+) -> Result<UploadProgress, UploadError> {
+    let (path, size, received, filename) = {
+        let mut sessions = sessions();
+        let session = owned(&mut sessions, owner, id)?;
+        if session.writing {
+            return Err(UploadError::Busy);
+        }
+        let expected = *session.received.borrow();
+        if offset != expected {
+            return Err(UploadError::OffsetMismatch { expected });
+        }
+        session.writing = true;
+        session.touched = Instant::now();
+        (
+            session.path.clone(),
+            session.size,
+            session.received.clone(),
+            session.filename.clone(),
+        )
+    };
+    let _writing = Writing(id);
+
+    let mut file = tokio::fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .truncate(false)
+        .open(&path)
+        .await?;
+    // Drop whatever an interrupted chunk wrote past the counted bytes.
+    file.set_len(offset).await?;
+    file.seek(std::io::SeekFrom::Start(offset)).await?;
+
+    let mut buffer = vec![0u8; BUFFER_BYTES];
+    let mut position = offset;
+    loop {
+        let read = body.read(&mut buffer).await?;
+        if read == 0 {
+            break;
+        }
+        if position + read as u64 > size {
+            return Err(UploadError::TooLarge);
+        }
+        file.write_all(&buffer[..read]).await?;
+        position += read as u64;
+        received.send_replace(position);
+    }
+    file.flush().await?;
+    Ok(progress_of(id, &filename, size, position))
+}
+
+/// Remove a complete upload from the sessions and hand over its file.
+pub fn take(owner: &str, id: &str) -> Result<CompletedUpload, UploadError> {
+    let mut sessions = sessions();
+    let session = owned(&mut sessions, owner, id)?;
+    let received = *session.received.borrow();
+    if session.writing {
+        return Err(UploadError::Busy);
+    }
+    if received != session.size {
+        return Err(UploadError::Incomplete {
+            received,
+            size: session.size,
+        });
+    }
+    let session = sessions.remove(id).ok_or(UploadError::NotFound)?;
+    Ok(CompletedUpload {
+        path: session.path,
+        filename: session.filename,
+        content_type: session.content_type,
+        size: session.size,
+    })
+}
+
+/// Abandon an upload; `false` when there was none.
+pub fn cancel(owner: &str, id: &str) -> bool {
+    let mut sessions = sessions();
+    if owned(&mut sessions, owner, id).is_err() {
+        return false;
+    }
+    if let Some(session) = sessions.remove(id) {
+        let _ = std::fs::remove_file(session.path);
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn chunks_resume_at_the_received_offset() {
+        let tmp = tempfile::tempdir().unwrap();
+        let upload = create("jane@acme.com", "../cv.pdf", None, 10, tmp.path()).unwrap();
+        assert_eq!(upload.filename, "cv.pdf");
+
+        let first = append("jane@acme.com", &upload.id, 0, &b"hello"[..]).await.unwrap();
+        assert_eq!((first.received, first.percent, first.complete), (5, 50, false));
+        assert_eq!(
+            append("jane@acme.com", &upload.id, 0, &b"hello"[..]).await,
+            Err(UploadError::OffsetMismatch { expected: 5 })
+        );
+        assert_eq!(
+            append("john@acme.com", &upload.id, 5, &b"world"[..]).await,
+            Err(UploadError::NotFound)
+        );
+        assert!(matches!(
+            take("jane@acme.com", &upload.id),
+            Err(UploadError::Incomplete { received: 5, size: 10 })
+        ));
+        assert_eq!(
+            append("jane@acme.com", &upload.id, 5, &b"world!"[..]).await,
+            Err(UploadError::TooLarge)
+        );
+
+        let done = append("jane@acme.com", &upload.id, 5, &b"world"[..]).await.unwrap();
+        assert!(done.complete);
+        let completed = take("jane@acme.com", &upload.id).unwrap();
+        assert_eq!(std::fs::read(completed.path).unwrap(), b"helloworld");
+    }
+}
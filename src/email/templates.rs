@@ -20,6 +20,7 @@ pub enum EmailKind {
     Nudge { name: String, credits: i64 },
     WinBack { name: String },
     NewTemplate { template_name: String },
+    WatchedProfileChanged { profile: String, actor: String, change: String },
     // ── Admin notifications ───────────────────────────────────────────────────
     AdminNewUser { user_email: String, credits_granted: i64 },
     AdminActivity { user_email: String, action: String, detail: String },
@@ -52,6 +53,7 @@ impl EmailKind {
             Self::Nudge { .. } => "nudge",
             Self::WinBack { .. } => "win_back",
             Self::NewTemplate { .. } => "new_template",
+            Self::WatchedProfileChanged { .. } => "watched_profile_changed",
             Self::AdminNewUser { .. } => "admin_new_user",
             Self::AdminActivity { .. } => "admin_activity",
             Self::AdminCvImportFailed { .. } => "admin_cv_import_failed",
@@ -72,6 +74,7 @@ impl EmailKind {
                 | Self::Nudge { .. }
                 | Self::WinBack { .. }
                 | Self::NewTemplate { .. }
+                | Self::WatchedProfileChanged { .. }
         )
     }
 
@@ -164,6 +167,11 @@ impl EmailKind {
                 "de" => format!("Neue Vorlage verfügbar: {}", template_name),
                 _ => format!("New template available: {}", template_name),
             },
+            Self::WatchedProfileChanged { profile, .. } => match lang {
+                "fr" => format!("Le profil {} a changé", profile),
+                "de" => format!("Das Profil {} wurde geändert", profile),
+                _ => format!("Profile {} changed", profile),
+            },
             // Admin emails — always English
             Self::AdminNewUser { user_email, .. } => format!("[CVenom] New user: {}", user_email),
             Self::AdminActivity { user_email, action, .. } => format!("[CVenom] {} — {}", action, user_email),
@@ -506,6 +514,30 @@ impl EmailKind {
 <p>{}</p>"#, btn("https://studio.cvenom.com", "Try It Now")),
             },
 
+            Self::WatchedProfileChanged { profile, actor, change } => {
+                let change = change
+                    .replace('&', "&amp;")
+                    .replace('<', "&lt;")
+                    .replace('>', "&gt;");
+                match lang {
+                    "fr" => format!(
+                        r#"<h1>Le profil {profile} a changé</h1>
+<p><strong>{actor}</strong> : {change}.</p>
+<p>Vous recevez cet e-mail car vous suivez ce profil. Vous ne recevrez pas d'autre e-mail à son sujet pendant un moment.</p>
+<p>{}</p>"#, open_editor()),
+                    "de" => format!(
+                        r#"<h1>Das Profil {profile} wurde geändert</h1>
+<p><strong>{actor}</strong>: {change}.</p>
+<p>Sie erhalten diese E-Mail, weil Sie dieses Profil beobachten. Für eine Weile erhalten Sie keine weitere E-Mail dazu.</p>
+<p>{}</p>"#, open_editor()),
+                    _ => format!(
+                        r#"<h1>Profile {profile} changed</h1>
+<p><strong>{actor}</strong> {change}.</p>
+<p>You are receiving this because you watch this profile. You won't get another email about it for a little while.</p>
+<p>{}</p>"#, open_editor()),
+                }
+            }
+
             // ── Admin notifications (always English) ─────────────────────────
             Self::AdminNewUser { user_email, credits_granted } => format!(
                 r#"<h2 style="color:#0F172A">🎉 New user signed up</h2>
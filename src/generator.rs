@@ -2,13 +2,19 @@
 use crate::config::CvConfig;
 use chrono::Utc;
 
-use crate::core::TemplateEngine;
+use crate::core::generation_lock::{self, GenerationBusy, GenerationGuard};
+use crate::core::{experiences_fallback, pdf_cache, profile_lock, template_versions, TemplateEngine};
 use crate::workspace::WorkspaceManager;
 use anyhow::{Context, Result};
 use graflog::app_log;
 use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
-use std::path::PathBuf;
-use std::{fs, process::Command};
+use std::path::{Path, PathBuf};
+use std::fs;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+/// How often [`CvGenerator::watch`] looks for changes.
+const WATCH_INTERVAL: Duration = Duration::from_millis(500);
 
 fn sanitize_filename(input: &str) -> String {
     utf8_percent_encode(input, NON_ALPHANUMERIC)
@@ -19,15 +25,45 @@ fn sanitize_filename(input: &str) -> String {
         .collect()
 }
 
+/// Latest modification time of the files under `dir`.
+fn last_modified(dir: &Path) -> Option<SystemTime> {
+    fs::read_dir(dir)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let meta = entry.metadata().ok()?;
+            if meta.is_dir() {
+                last_modified(&entry.path())
+            } else {
+                meta.modified().ok()
+            }
+        })
+        .max()
+}
+
+/// A compiled CV and the images that had to be left out of it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeneratedCv {
+    pub output_path: PathBuf,
+    pub skipped_assets: Vec<String>,
+    /// Served from [`pdf_cache`] instead of compiled.
+    pub cached: bool,
+}
+
 pub struct CvGenerator {
     pub config: CvConfig,
     template_manager: TemplateEngine,
+    /// Generation lock taken ahead with [`lock`](Self::lock).
+    held_lock: Mutex<Option<GenerationGuard>>,
 }
 
 impl CvGenerator {
     pub fn new(mut config: CvConfig) -> Result<Self> {
-        let template_manager = TemplateEngine::new(config.templates_dir.clone())
+        let mut template_manager = TemplateEngine::new(config.templates_dir.clone())
             .context("Failed to initialize template manager")?;
+        if let Some(dir) = &config.tenant_templates_dir {
+            template_manager = template_manager.with_tenant_templates(dir);
+        }
 
         // Validate and normalize template
         config.template = normalize_template_for_generator(&config.template, &template_manager);
@@ -41,26 +77,94 @@ impl CvGenerator {
             );
         }
 
-        // Validate experiences file exists
-        let experiences_path = config.profile_experiences_path();
-        if !experiences_path.exists() {
-            anyhow::bail!("Experiences file not found: {}", experiences_path.display());
+        // Experiences in the language, or a fallback (see `experiences_fallback`)
+        experiences_fallback::resolve(&profile_dir, &config.lang)?;
+
+        if config.template_version.is_none() {
+            config.template_version =
+                resolve_template_version(&template_manager, &config.template, &profile_dir);
         }
 
         Ok(Self {
             config,
             template_manager,
+            held_lock: Mutex::new(None),
         })
     }
 
+    /// Take the person's generation lock now, for the next generation to
+    /// use. Callers take it before waiting for a scheduler slot, so a slot
+    /// is never held while waiting for a busy person.
+    pub async fn lock(&self) -> Result<(), GenerationBusy> {
+        let guard = generation_lock::acquire(&self.config.profile_data_dir()).await?;
+        *self
+            .held_lock
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(guard);
+        Ok(())
+    }
+
+    /// The lock taken with [`lock`](Self::lock), or else a new one.
+    async fn person_lock(&self) -> Result<GenerationGuard, GenerationBusy> {
+        let held = self
+            .held_lock
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .take();
+        match held {
+            Some(guard) => Ok(guard),
+            None => generation_lock::acquire(&self.config.profile_data_dir()).await,
+        }
+    }
+
     pub async fn generate(&self) -> Result<PathBuf> {
+        Ok(self.generate_report().await?.output_path)
+    }
+
+    /// The file of the previous generation when nothing it was compiled from
+    /// has changed, checked under the profile read lock. Callers use it to
+    /// answer before queueing and charging; [`generate_report`](Self::generate_report)
+    /// checks again once it holds the generation lock.
+    pub async fn cached(&self) -> Option<GeneratedCv> {
+        let _snapshot = profile_lock::read(&self.config.profile_data_dir()).await;
+        let cache_key = pdf_cache::key(&self.config, &self.template_manager);
+        pdf_cache::lookup(&self.config.output_dir, &cache_key)
+    }
+
+    /// Like [`generate`](Self::generate), also reporting skipped images.
+    /// The file of the previous generation is returned as is when nothing it
+    /// was compiled from has changed.
+    pub async fn generate_report(&self) -> Result<GeneratedCv> {
+        let lock = self.person_lock().await?;
+        app_log!(
+            info,
+            "Generation lock acquired for {}, lock_wait_ms: {}",
+            self.config.profile_name,
+            lock.waited.as_millis()
+        );
+
         self.setup_output_dir()?;
 
         let workspace = WorkspaceManager::new(&self.config, &self.template_manager);
+        // Snapshot the profile files under the read lock; compilation then
+        // works on the copies, so saves only wait for the copy.
+        let snapshot = profile_lock::read(&self.config.profile_data_dir()).await;
+        let cache_key = pdf_cache::key(&self.config, &self.template_manager);
+        if let Some(cached) = pdf_cache::lookup(&self.config.output_dir, &cache_key) {
+            app_log!(
+                info,
+                "Nothing changed since {} was generated, serving it from the cache",
+                cached.output_path.display()
+            );
+            return Ok(cached);
+        }
         workspace.prepare_workspace().await?;
+        drop(snapshot);
 
-        let output_path = workspace.compile_cv()?;
+        let output_path = workspace.compile_cv().await?;
         workspace.cleanup_workspace()?;
+        let skipped_assets = workspace.skipped_assets();
+        self.record_template_version(&self.config.lang, &skipped_assets);
 
         app_log!(
             info,
@@ -71,7 +175,13 @@ impl CvGenerator {
             output_path.display()
         );
 
-        Ok(output_path)
+        let generated = GeneratedCv {
+            output_path,
+            skipped_assets,
+            cached: false,
+        };
+        pdf_cache::store(&self.config.output_dir, &cache_key, &generated);
+        Ok(generated)
     }
 
     pub async fn generate_pdf_data(&self) -> Result<(Vec<u8>, String)> {
@@ -82,42 +192,142 @@ impl CvGenerator {
             Utc::now().format("%Y")
         );
 
+        let lock = self.person_lock().await?;
+        app_log!(
+            info,
+            "Generation lock acquired for {}, lock_wait_ms: {}",
+            self.config.profile_name,
+            lock.waited.as_millis()
+        );
+
         self.setup_output_dir()?;
 
         let workspace = WorkspaceManager::new(&self.config, &self.template_manager);
+        // Snapshot the profile files under the read lock; compilation then
+        // works on the copies, so saves only wait for the copy.
+        let snapshot = profile_lock::read(&self.config.profile_data_dir()).await;
         workspace.prepare_workspace().await?;
+        drop(snapshot);
 
-        let output_path = workspace.compile_cv()?;
+        let output_path = workspace.compile_cv().await?;
         let pdf_data = fs::read(&output_path).context("Failed to read generated PDF")?;
 
         workspace.cleanup_workspace()?;
+        self.record_template_version(&self.config.lang, &workspace.skipped_assets());
 
         Ok((pdf_data, filename))
     }
 
-    pub async fn watch(&self) -> Result<()> {
+    /// The CV in each of `langs`, compiled from one workspace: the template
+    /// and images are prepared once, then only the experiences change between
+    /// compilations. A language that fails is reported next to the others.
+    pub async fn generate_languages(
+        &self,
+        langs: &[String],
+    ) -> Result<Vec<(String, Result<GeneratedCv>)>> {
+        let lock = self.person_lock().await?;
+        app_log!(
+            info,
+            "Generation lock acquired for {} in {} languages, lock_wait_ms: {}",
+            self.config.profile_name,
+            langs.len(),
+            lock.waited.as_millis()
+        );
+
         self.setup_output_dir()?;
 
         let workspace = WorkspaceManager::new(&self.config, &self.template_manager);
+        let snapshot = profile_lock::read(&self.config.profile_data_dir()).await;
         workspace.prepare_workspace().await?;
+        let staged = workspace.stage_languages(langs).await;
+        drop(snapshot);
+        if let Err(e) = staged {
+            workspace.cleanup_workspace()?;
+            return Err(e);
+        }
 
-        let output_path = self.config.output_dir.join(format!(
-            "{}_{}_{}.pdf",
-            self.config.profile_name, self.config.template, self.config.lang
-        ));
+        let skipped_assets = workspace.skipped_assets();
+        let mut results = Vec::new();
+        for lang in langs {
+            let result = workspace.compile_language(lang).await.map(|output_path| {
+                self.record_template_version(lang, &skipped_assets);
+                GeneratedCv {
+                    output_path,
+                    skipped_assets: skipped_assets.clone(),
+                    cached: false,
+                }
+            });
+            if let Err(e) = &result {
+                app_log!(warn, "{} CV of {} failed: {}", lang, self.config.profile_name, e);
+            }
+            results.push((lang.clone(), result));
+        }
+        workspace.cleanup_workspace()?;
+        Ok(results)
+    }
 
-        let status = Command::new("typst")
-            .arg("watch")
-            .arg("main.typ")
-            .arg(&output_path)
-            .status()
-            .context("Failed to execute typst watch command")?;
+    /// PNG of the first page; needs a config built [`with_preview`](CvConfig::with_preview).
+    /// The image is not kept in the output directory and the template version
+    /// is not recorded, since nothing was delivered.
+    pub async fn generate_preview(&self) -> Result<Vec<u8>> {
+        anyhow::ensure!(
+            self.config.preview_ppi.is_some(),
+            "Preview requested without a preview resolution"
+        );
+        let lock = self.person_lock().await?;
+        app_log!(
+            info,
+            "Generation lock acquired for {} preview, lock_wait_ms: {}",
+            self.config.profile_name,
+            lock.waited.as_millis()
+        );
 
-        if !status.success() {
-            anyhow::bail!("Typst watch failed");
-        }
+        self.setup_output_dir()?;
 
-        Ok(())
+        let workspace = WorkspaceManager::new(&self.config, &self.template_manager);
+        let snapshot = profile_lock::read(&self.config.profile_data_dir()).await;
+        workspace.prepare_workspace().await?;
+        drop(snapshot);
+
+        let output_path = workspace.compile_cv().await?;
+        let png = fs::read(&output_path).context("Failed to read generated preview");
+        let _ = fs::remove_file(&output_path);
+        workspace.cleanup_workspace()?;
+        png
+    }
+
+    /// Recompile the CV in-process whenever a file of the profile changes,
+    /// until the process is stopped. A failed compilation is logged and the
+    /// previous PDF is kept.
+    pub async fn watch(&self) -> Result<()> {
+        self.setup_output_dir()?;
+
+        let profile_dir = self.config.profile_data_dir();
+        let mut compiled_at = None;
+        loop {
+            let changed_at = last_modified(&profile_dir);
+            if changed_at != compiled_at {
+                compiled_at = changed_at;
+                let workspace = WorkspaceManager::new(&self.config, &self.template_manager);
+                // Snapshot the profile files under the read lock; compilation
+                // then works on the copies, so saves only wait for the copy.
+                let snapshot = profile_lock::read(&profile_dir).await;
+                workspace.prepare_workspace().await?;
+                drop(snapshot);
+
+                match workspace.compile_cv().await {
+                    Ok(output_path) => app_log!(info, "Compiled {}", output_path.display()),
+                    Err(e) => app_log!(
+                        warn,
+                        "Compilation of {} failed: {}",
+                        self.config.profile_name,
+                        e
+                    ),
+                }
+                workspace.cleanup_workspace()?;
+            }
+            tokio::time::sleep(WATCH_INTERVAL).await;
+        }
     }
 
     pub fn create_profile_unchecked(&self) -> Result<()> {
@@ -136,6 +346,18 @@ impl CvGenerator {
         Ok(())
     }
 
+    fn record_template_version(&self, lang: &str, skipped_assets: &[String]) {
+        if let Some(version) = &self.config.template_version {
+            template_versions::record_generation(
+                &self.config.profile_data_dir(),
+                &self.config.template,
+                version,
+                lang,
+                skipped_assets,
+            );
+        }
+    }
+
     fn setup_output_dir(&self) -> Result<()> {
         fs::create_dir_all(&self.config.output_dir).context("Failed to create output directory")?;
         fs::create_dir_all("tmp_workspace").context("Failed to create temporary workspace")?;
@@ -143,6 +365,29 @@ impl CvGenerator {
     }
 }
 
+/// The version `template` is pinned to for this profile, or its current one.
+/// The current version is archived on first use so later pins can target it.
+fn resolve_template_version(
+    template_manager: &TemplateEngine,
+    template: &str,
+    profile_dir: &Path,
+) -> Option<String> {
+    let info = template_manager.get_template(template)?;
+    if let Err(e) = template_versions::archive(info, template_manager.templates_dir()) {
+        app_log!(warn, "Failed to archive template '{}': {}", template, e);
+    }
+    match template_versions::pinned(profile_dir, template) {
+        Some(version) => match template_versions::version_dir(info, &version) {
+            Ok(_) => Some(version),
+            Err(e) => {
+                app_log!(warn, "Ignoring pin of {}: {}", profile_dir.display(), e);
+                Some(template_versions::current_version(info))
+            }
+        },
+        None => Some(template_versions::current_version(info)),
+    }
+}
+
 fn normalize_template_for_generator(template: &str, template_manager: &TemplateEngine) -> String {
     let requested = template.to_lowercase();
     for available_template in template_manager.list_templates() {
@@ -4,6 +4,7 @@ use std::path::PathBuf;
 
 pub use web::start_web_server;
 
+pub mod admin_cli;
 pub mod auth;
 pub mod config;
 pub mod core; // Unified core module
@@ -13,6 +14,7 @@ pub mod font_validator;
 pub mod generator;
 pub mod image_validator;
 pub mod linkedin_analysis;
+pub mod tenant_cli;
 pub mod types;
 pub mod utils;
 pub mod web;
@@ -22,7 +24,7 @@ pub mod workspace;
 pub use config::CvConfig;
 pub use core::{ConfigManager, Database, FsOps, TemplateEngine};
 pub use environment::EnvironmentConfig;
-pub use generator::CvGenerator;
+pub use generator::{CvGenerator, GeneratedCv};
 
 /// List all available profiles - now uses core FsOps
 pub fn list_profiles(data_dir: &PathBuf) -> Result<Vec<String>> {
@@ -1,11 +1,12 @@
 use super::{types::JobMatchApiRequest, JobAnalysisRequest, JobAnalysisResponse};
+use crate::linkedin_analysis::scraper;
 use crate::linkedin_analysis::JobContent;
 use crate::linkedin_analysis::JobMatchApiResponse;
+use crate::core::FsOps;
 use anyhow::{Context, Result};
 use graflog::app_log;
 use reqwest::Client;
 use std::path::PathBuf;
-use tokio::fs;
 
 pub struct JobAnalyzer {
     client: Client,
@@ -145,17 +146,10 @@ impl JobAnalyzer {
         }
     }
 
-    /// Extract job content from LinkedIn URL
+    /// Extract job content from LinkedIn URL (rate-limited, robots.txt-aware)
     async fn extract_job_content(&self, job_url: &str) -> Result<JobContent> {
         app_log!(info, "Extracting job content from URL: {}", job_url);
-
-        // Create a placeholder job content since we're not scraping anymore
-        Ok(JobContent {
-            title: "Job Position".to_string(),
-            company: "Company Name".to_string(),
-            description: format!("Job description from {}", job_url),
-            location: "Location".to_string(),
-        })
+        scraper::scraper().fetch_job(job_url).await
     }
 
     /// Read profile's experiences from files
@@ -163,7 +157,7 @@ impl JobAnalyzer {
         let experiences_en = profile_dir.join("experiences_en.typ");
 
         let work_experience = if experiences_en.exists() {
-            fs::read_to_string(&experiences_en).await?
+            FsOps::read_file_safe(&experiences_en).await?
         } else {
             return Err(anyhow::anyhow!("No experience files found"));
         };
@@ -175,7 +169,7 @@ impl JobAnalyzer {
     async fn create_cv_json(&self, profile_dir: &PathBuf, work_experience: &str) -> Result<String> {
         let cv_params_path = profile_dir.join("cv_params.toml");
         let cv_params = if cv_params_path.exists() {
-            fs::read_to_string(&cv_params_path).await?
+            FsOps::read_file_safe(&cv_params_path).await?
         } else {
             "# No CV params found".to_string()
         };
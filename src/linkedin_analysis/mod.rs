@@ -2,6 +2,7 @@
 use serde::{Deserialize, Serialize};
 
 pub mod job_analyzer;
+pub mod scraper;
 pub mod types;
 
 pub use job_analyzer::JobAnalyzer;
@@ -11,6 +12,10 @@ pub use job_analyzer::JobAnalyzer;
 pub struct JobAnalysisRequest {
     pub job_url: String,
     pub profile_name: String,
+    /// Scrape the posting again instead of using the cached copy, and ask
+    /// cv-import again instead of reusing a recent analysis.
+    #[serde(default)]
+    pub force_refresh: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
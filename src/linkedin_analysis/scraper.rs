@@ -0,0 +1,749 @@
+// src/linkedin_analysis/scraper.rs
+//! Polite fetching of job postings from this server.
+//!
+//! Every request goes through one shared client that:
+//! - identifies itself with `SCRAPER_USER_AGENT`;
+//! - waits `SCRAPER_MIN_INTERVAL_MS` (default 2000) between two requests to
+//!   the same host, or the host's `Crawl-delay` when longer;
+//! - checks the host's robots.txt first (cached for an hour), unless
+//!   `SCRAPER_RESPECT_ROBOTS=false`;
+//! - goes through the HTTP(S) proxy `SCRAPER_PROXY` when set;
+//! - only contacts hosts whose addresses are all public, checked again on
+//!   every redirect, so a job URL can't reach this server's network.
+//!
+//! Pages rendered by JavaScript come back nearly empty. When
+//! `HEADLESS_BROWSER_URL` points to a headless Chrome service (browserless
+//! or compatible `/content` API, token in `HEADLESS_BROWSER_TOKEN`), pages
+//! whose posting has fewer than `SCRAPER_MIN_TEXT_CHARS` (default 200)
+//! characters of description are rendered there and parsed again. The
+//! rendering counts as a request to the host for rate limiting.
+//!
+//! Postings are read from their JSON-LD `JobPosting` block, which job boards
+//! (LinkedIn included) publish for search engines, falling back to the page's
+//! meta tags.
+
+use anyhow::{bail, Context, Result};
+use graflog::app_log;
+use reqwest::{header, Client, Response, StatusCode, Url};
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr};
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::linkedin_analysis::JobContent;
+
+const DEFAULT_USER_AGENT: &str = concat!(
+    "cvenom-bot/",
+    env!("CARGO_PKG_VERSION"),
+    " (+https://cvenom.com/bot)"
+);
+const DEFAULT_MIN_INTERVAL_MS: u64 = 2000;
+const ROBOTS_TTL: Duration = Duration::from_secs(60 * 60);
+const MAX_CRAWL_DELAY: Duration = Duration::from_secs(60);
+const DEFAULT_MIN_TEXT_CHARS: usize = 200;
+const MAX_REDIRECTS: usize = 5;
+
+static SCRAPER: LazyLock<PoliteScraper> = LazyLock::new(PoliteScraper::from_env);
+
+/// The shared scraper.
+pub fn scraper() -> &'static PoliteScraper {
+    &SCRAPER
+}
+
+/// The host answered 429; retrying elsewhere would not help.
+#[derive(Debug)]
+struct RateLimited(String);
+
+impl std::fmt::Display for RateLimited {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} is rate-limiting us (HTTP 429)", self.0)
+    }
+}
+
+impl std::error::Error for RateLimited {}
+
+/// Headless Chrome service rendering JavaScript-heavy pages.
+struct Browser {
+    client: Client,
+    url: String,
+    token: Option<String>,
+}
+
+impl Browser {
+    fn from_env() -> Option<Self> {
+        let url = std::env::var("HEADLESS_BROWSER_URL")
+            .ok()
+            .map(|v| v.trim().trim_end_matches('/').to_string())
+            .filter(|v| !v.is_empty())?;
+        let client = Client::builder()
+            .timeout(Duration::from_secs(60))
+            .build()
+            .ok()?;
+        app_log!(info, "Headless browser fallback: {}", url);
+        Some(Self {
+            client,
+            url,
+            token: std::env::var("HEADLESS_BROWSER_TOKEN")
+                .ok()
+                .filter(|v| !v.trim().is_empty()),
+        })
+    }
+
+    /// HTML of `url` once its scripts have run.
+    async fn render(&self, url: &Url, user_agent: &str) -> Result<String> {
+        ensure_public(url).await?;
+        let mut request = self
+            .client
+            .post(format!("{}/content", self.url))
+            .json(&serde_json::json!({
+                "url": url.as_str(),
+                "userAgent": user_agent,
+                "gotoOptions": { "waitUntil": "networkidle2", "timeout": 45000 },
+            }));
+        if let Some(token) = &self.token {
+            request = request.query(&[("token", token)]);
+        }
+        let response = request
+            .send()
+            .await
+            .context("Failed to reach the headless browser")?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            bail!("Headless browser failed (HTTP {}): {}", status, body);
+        }
+        response
+            .text()
+            .await
+            .context("Failed to read the rendered page")
+    }
+}
+
+pub struct PoliteScraper {
+    client: Client,
+    user_agent: String,
+    min_interval: Duration,
+    respect_robots: bool,
+    browser: Option<Browser>,
+    /// Descriptions shorter than this are rendered by the browser.
+    min_text_chars: usize,
+    /// Earliest time of the next request to each host.
+    next_slot: Mutex<HashMap<String, Instant>>,
+    robots: Mutex<HashMap<String, (Instant, Robots)>>,
+}
+
+impl PoliteScraper {
+    fn from_env() -> Self {
+        let user_agent = std::env::var("SCRAPER_USER_AGENT")
+            .ok()
+            .filter(|v| !v.trim().is_empty())
+            .unwrap_or_else(|| DEFAULT_USER_AGENT.to_string());
+        let min_interval = std::env::var("SCRAPER_MIN_INTERVAL_MS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_MIN_INTERVAL_MS);
+        let respect_robots = std::env::var("SCRAPER_RESPECT_ROBOTS")
+            .map(|v| !matches!(v.to_lowercase().as_str(), "false" | "0" | "no"))
+            .unwrap_or(true);
+
+        let mut builder = Client::builder()
+            .user_agent(user_agent.clone())
+            .timeout(Duration::from_secs(30))
+            // Followed by `get_public`, which checks each target.
+            .redirect(reqwest::redirect::Policy::none());
+        if let Ok(proxy) = std::env::var("SCRAPER_PROXY") {
+            if !proxy.trim().is_empty() {
+                match reqwest::Proxy::all(proxy.trim()) {
+                    Ok(proxy) => builder = builder.proxy(proxy),
+                    Err(e) => app_log!(error, "Ignoring invalid SCRAPER_PROXY: {}", e),
+                }
+            }
+        }
+        let client = builder.build().unwrap_or_else(|e| {
+            app_log!(error, "Failed to build the scraping client: {}", e);
+            Client::new()
+        });
+
+        Self {
+            client,
+            user_agent,
+            min_interval: Duration::from_millis(min_interval),
+            respect_robots,
+            browser: Browser::from_env(),
+            min_text_chars: std::env::var("SCRAPER_MIN_TEXT_CHARS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(DEFAULT_MIN_TEXT_CHARS),
+            next_slot: Mutex::new(HashMap::new()),
+            robots: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Fetch and parse the posting at `job_url`.
+    pub async fn fetch_job(&self, job_url: &str) -> Result<JobContent> {
+        let url = Url::parse(job_url.trim()).context("Invalid job URL")?;
+        if !matches!(url.scheme(), "http" | "https") {
+            bail!("Only http(s) job URLs can be scraped");
+        }
+        let host = url.host_str().context("Job URL has no host")?.to_string();
+        ensure_public(&url).await?;
+
+        let robots = if self.respect_robots {
+            let robots = self.robots_for(&url, &host).await;
+            if !robots.allows(&path_and_query(&url)) {
+                bail!("robots.txt of {} disallows {}", host, url.path());
+            }
+            Some(robots)
+        } else {
+            None
+        };
+        let crawl_delay = robots.and_then(|r| r.crawl_delay);
+        self.wait_turn(&host, crawl_delay).await;
+        let plain = self.fetch_plain(&url, &host).await;
+
+        let Some(browser) = &self.browser else {
+            return plain?.1.with_context(|| format!("No job posting found at {}", url));
+        };
+        // The browser gets the page the checked redirects led to.
+        let (page, plain) = match plain {
+            Ok((_, Some(posting))) if !is_thin(&posting, self.min_text_chars) => {
+                return Ok(posting)
+            }
+            Err(e) if e.downcast_ref::<RateLimited>().is_some() => return Err(e),
+            Ok((page, posting)) => (page, posting),
+            Err(e) => {
+                app_log!(info, "[scraper] Plain fetch of {} failed: {:#}", url, e);
+                (url.clone(), None)
+            }
+        };
+
+        app_log!(info, "[scraper] Rendering {} in the headless browser", page);
+        self.wait_turn(&host, crawl_delay).await;
+        let rendered = match browser.render(&page, &self.user_agent).await {
+            Ok(html) => parse_job_page(&html),
+            Err(e) => {
+                app_log!(warn, "[scraper] {:#}", e);
+                None
+            }
+        };
+        // Keep whichever attempt found more text.
+        [plain, rendered]
+            .into_iter()
+            .flatten()
+            .max_by_key(|posting| posting.description.len())
+            .with_context(|| format!("No job posting found at {}", url))
+    }
+
+    /// The posting at `url` and the page redirects led to.
+    async fn fetch_plain(&self, url: &Url, host: &str) -> Result<(Url, Option<JobContent>)> {
+        let response = self.get_public(url.clone()).await?;
+        let page = response.url().clone();
+        let status = response.status();
+        if status == StatusCode::TOO_MANY_REQUESTS {
+            // Back off this host for longer before the next attempt.
+            self.push_back(host, Duration::from_secs(60));
+            return Err(RateLimited(host.to_string()).into());
+        }
+        if !status.is_success() {
+            bail!("Failed to fetch {} (HTTP {})", url, status);
+        }
+        let html = response.text().await.context("Failed to read the job page")?;
+        Ok((page, parse_job_page(&html)))
+    }
+
+    /// GET `url`, following up to [`MAX_REDIRECTS`] redirects to public
+    /// hosts only.
+    async fn get_public(&self, mut url: Url) -> Result<Response> {
+        for _ in 0..=MAX_REDIRECTS {
+            ensure_public(&url).await?;
+            let response = self
+                .client
+                .get(url.clone())
+                .send()
+                .await
+                .with_context(|| format!("Failed to fetch {}", url))?;
+            if !response.status().is_redirection() {
+                return Ok(response);
+            }
+            let Some(location) = response.headers().get(header::LOCATION) else {
+                return Ok(response);
+            };
+            let location = location.to_str().context("Invalid redirect location")?;
+            url = url.join(location).context("Invalid redirect location")?;
+            if !matches!(url.scheme(), "http" | "https") {
+                bail!("Redirected to a non-http(s) URL");
+            }
+        }
+        bail!("Too many redirects fetching {}", url)
+    }
+
+    /// Sleep until this host may be contacted again, and book the next slot.
+    async fn wait_turn(&self, host: &str, crawl_delay: Option<Duration>) {
+        let interval = crawl_delay
+            .map(|d| d.min(MAX_CRAWL_DELAY).max(self.min_interval))
+            .unwrap_or(self.min_interval);
+        let start = {
+            let mut slots = self
+                .next_slot
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            let now = Instant::now();
+            let start = slots.get(host).copied().unwrap_or(now).max(now);
+            slots.insert(host.to_string(), start + interval);
+            start
+        };
+        let wait = start.saturating_duration_since(Instant::now());
+        if !wait.is_zero() {
+            app_log!(trace, "[scraper] Waiting {:?} before contacting {}", wait, host);
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    fn push_back(&self, host: &str, delay: Duration) {
+        let mut slots = self
+            .next_slot
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let next = Instant::now() + delay;
+        let slot = slots.entry(host.to_string()).or_insert(next);
+        *slot = (*slot).max(next);
+    }
+
+    async fn robots_for(&self, url: &Url, host: &str) -> Robots {
+        let key = url.origin().ascii_serialization();
+        if let Some((fetched, robots)) = self
+            .robots
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .get(&key)
+        {
+            if fetched.elapsed() < ROBOTS_TTL {
+                return robots.clone();
+            }
+        }
+
+        self.wait_turn(host, None).await;
+        let robots_url = url.join("/robots.txt").expect("an absolute path joins");
+        let robots = match self.get_public(robots_url).await {
+            Ok(response) if response.status().is_success() => {
+                let body = response.text().await.unwrap_or_default();
+                Robots::parse(&body, &self.user_agent)
+            }
+            // RFC 9309: a missing robots.txt allows everything, an
+            // unreachable one disallows everything.
+            Ok(response) if response.status().is_client_error() => Robots::allow_all(),
+            Ok(response) => {
+                app_log!(warn, "[scraper] robots.txt of {}: HTTP {}", host, response.status());
+                Robots::disallow_all()
+            }
+            Err(e) => {
+                app_log!(warn, "[scraper] robots.txt of {} unreachable: {:#}", host, e);
+                Robots::disallow_all()
+            }
+        };
+        self.robots
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .insert(key, (Instant::now(), robots.clone()));
+        robots
+    }
+}
+
+/// Fail unless every address `url`'s host resolves to is public.
+async fn ensure_public(url: &Url) -> Result<()> {
+    let host = url.host_str().context("URL has no host")?;
+    let port = url.port_or_known_default().unwrap_or(443);
+    let literal = host.trim_start_matches('[').trim_end_matches(']');
+    let addresses: Vec<IpAddr> = match literal.parse() {
+        Ok(ip) => vec![ip],
+        Err(_) => tokio::net::lookup_host((host, port))
+            .await
+            .with_context(|| format!("Failed to resolve {}", host))?
+            .map(|address| address.ip())
+            .collect(),
+    };
+    if addresses.is_empty() {
+        bail!("{} does not resolve", host);
+    }
+    if let Some(ip) = addresses.iter().find(|ip| !is_public(**ip)) {
+        app_log!(warn, "[scraper] Refusing {}: {} is not a public address", url, ip);
+        bail!("{} is not a public host", host);
+    }
+    Ok(())
+}
+
+/// Not loopback, private, link-local (cloud metadata included), shared,
+/// reserved, multicast or unspecified.
+fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_public_v4(ip),
+        IpAddr::V6(ip) => {
+            if let Some(v4) = ip.to_ipv4_mapped() {
+                return is_public_v4(v4);
+            }
+            let s = ip.segments();
+            // NAT64 embeds an IPv4 address in the last 32 bits.
+            if s[..6] == [0x64, 0xff9b, 0, 0, 0, 0] {
+                return is_public_v4(Ipv4Addr::from(((s[6] as u32) << 16) | s[7] as u32));
+            }
+            !(ip.is_loopback()
+                || ip.is_unspecified()
+                || ip.is_multicast()
+                || (s[0] & 0xfe00) == 0xfc00 // unique local
+                || (s[0] & 0xffc0) == 0xfe80 // link-local
+                || (s[0] & 0xffc0) == 0xfec0 // site-local
+                || (s[0] == 0x2001 && s[1] == 0x0db8)) // documentation
+        }
+    }
+}
+
+fn is_public_v4(ip: Ipv4Addr) -> bool {
+    let [a, b, c, _] = ip.octets();
+    !(ip.is_private()
+        || ip.is_loopback()
+        || ip.is_link_local()
+        || ip.is_unspecified()
+        || ip.is_broadcast()
+        || ip.is_documentation()
+        || ip.is_multicast()
+        || a == 0
+        || (a == 100 && (64..128).contains(&b)) // shared address space
+        || (a == 192 && b == 0 && c == 0)
+        || (a == 198 && (18..20).contains(&b)) // benchmarking
+        || a >= 240)
+}
+
+fn path_and_query(url: &Url) -> String {
+    match url.query() {
+        Some(query) => format!("{}?{}", url.path(), query),
+        None => url.path().to_string(),
+    }
+}
+
+/// Rules of the robots.txt group that applies to us.
+#[derive(Debug, Clone, Default)]
+struct Robots {
+    /// `(allow, pattern)` pairs.
+    rules: Vec<(bool, String)>,
+    crawl_delay: Option<Duration>,
+}
+
+impl Robots {
+    fn allow_all() -> Self {
+        Self::default()
+    }
+
+    fn disallow_all() -> Self {
+        Self {
+            rules: vec![(false, "/".to_string())],
+            crawl_delay: None,
+        }
+    }
+
+    /// Keep the group naming our product token, else the `*` group.
+    fn parse(body: &str, user_agent: &str) -> Self {
+        let token = user_agent
+            .split(['/', ' '])
+            .next()
+            .unwrap_or_default()
+            .to_lowercase();
+        let (mut ours, mut wildcard) = (None::<Robots>, None::<Robots>);
+        let mut agents: Vec<String> = Vec::new();
+        let mut group = Robots::default();
+        let mut in_rules = false;
+
+        let mut close = |agents: &mut Vec<String>, group: &mut Robots| {
+            let taken = std::mem::take(group);
+            if agents.iter().any(|a| !token.is_empty() && a == &token) {
+                let target = ours.get_or_insert_with(Robots::default);
+                target.rules.extend(taken.rules);
+                target.crawl_delay = target.crawl_delay.or(taken.crawl_delay);
+            } else if agents.iter().any(|a| a == "*") {
+                let target = wildcard.get_or_insert_with(Robots::default);
+                target.rules.extend(taken.rules);
+                target.crawl_delay = target.crawl_delay.or(taken.crawl_delay);
+            }
+            agents.clear();
+        };
+
+        for line in body.lines() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            let Some((field, value)) = line.split_once(':') else {
+                continue;
+            };
+            let (field, value) = (field.trim().to_lowercase(), value.trim());
+            match field.as_str() {
+                "user-agent" => {
+                    if in_rules {
+                        close(&mut agents, &mut group);
+                        in_rules = false;
+                    }
+                    agents.push(value.to_lowercase());
+                }
+                "allow" | "disallow" => {
+                    in_rules = true;
+                    if !value.is_empty() {
+                        group.rules.push((field == "allow", value.to_string()));
+                    }
+                }
+                "crawl-delay" => {
+                    in_rules = true;
+                    group.crawl_delay = value
+                        .parse::<f64>()
+                        .ok()
+                        .filter(|secs| secs.is_finite() && *secs >= 0.0)
+                        .map(Duration::from_secs_f64);
+                }
+                _ => {}
+            }
+        }
+        close(&mut agents, &mut group);
+        ours.or(wildcard).unwrap_or_default()
+    }
+
+    /// The longest matching rule wins; `allow` wins ties.
+    fn allows(&self, path: &str) -> bool {
+        self.rules
+            .iter()
+            .filter(|(_, pattern)| rule_matches(pattern, path))
+            .max_by_key(|(allow, pattern)| (pattern.len(), *allow))
+            .map(|(allow, _)| *allow)
+            .unwrap_or(true)
+    }
+}
+
+/// robots.txt path pattern: a prefix, with `*` wildcards and an optional `$`
+/// end anchor.
+fn rule_matches(pattern: &str, path: &str) -> bool {
+    let (pattern, anchored) = match pattern.strip_suffix('$') {
+        Some(p) => (p, true),
+        None => (pattern, false),
+    };
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = path.strip_prefix(first) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    for (i, part) in parts.iter().enumerate() {
+        let last = i == parts.len() - 1;
+        let found = if last && anchored {
+            rest.ends_with(part).then(|| rest.len() - part.len())
+        } else {
+            rest.find(part)
+        };
+        match found {
+            Some(at) => rest = &rest[at + part.len()..],
+            None => return false,
+        }
+    }
+    !anchored || rest.is_empty()
+}
+
+/// Too little text to analyze, as served before scripts run.
+fn is_thin(posting: &JobContent, min_text_chars: usize) -> bool {
+    posting.description.trim().chars().count() < min_text_chars
+}
+
+/// Posting of a job page: its JSON-LD `JobPosting`, else its meta tags.
+fn parse_job_page(html: &str) -> Option<JobContent> {
+    json_ld_posting(html).or_else(|| {
+        let title = meta(html, "og:title").or_else(|| title_tag(html))?;
+        let description = meta(html, "og:description").or_else(|| meta(html, "description"))?;
+        Some(JobContent {
+            title,
+            company: meta(html, "og:site_name").unwrap_or_default(),
+            location: String::new(),
+            description,
+        })
+    })
+}
+
+fn json_ld_posting(html: &str) -> Option<JobContent> {
+    let mut rest = html;
+    while let Some(start) = rest.find("application/ld+json") {
+        rest = &rest[start..];
+        let open = rest.find('>')? + 1;
+        let close = rest[open..].find("</script>")? + open;
+        let block = &rest[open..close];
+        rest = &rest[close..];
+        let Ok(value) = serde_json::from_str::<serde_json::Value>(block.trim()) else {
+            continue;
+        };
+        let candidates = match &value {
+            serde_json::Value::Array(items) => items.clone(),
+            other => match other.get("@graph") {
+                Some(serde_json::Value::Array(items)) => items.clone(),
+                _ => vec![other.clone()],
+            },
+        };
+        for item in candidates {
+            if item.get("@type").and_then(|t| t.as_str()) != Some("JobPosting") {
+                continue;
+            }
+            let text = |v: Option<&serde_json::Value>| {
+                v.and_then(|v| v.as_str()).map(decode_entities).unwrap_or_default()
+            };
+            let location = item
+                .get("jobLocation")
+                .map(|l| if l.is_array() { &l[0] } else { l })
+                .and_then(|l| l.get("address"))
+                .map(|a| {
+                    ["addressLocality", "addressRegion", "addressCountry"]
+                        .iter()
+                        .filter_map(|k| a.get(*k).and_then(|v| v.as_str()))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                })
+                .unwrap_or_default();
+            return Some(JobContent {
+                title: text(item.get("title")),
+                company: text(item.get("hiringOrganization").and_then(|o| o.get("name"))),
+                location,
+                description: strip_tags(&text(item.get("description"))),
+            });
+        }
+    }
+    None
+}
+
+/// Content of `<meta property|name="key" content="...">`.
+fn meta(html: &str, key: &str) -> Option<String> {
+    let lower = html.to_ascii_lowercase();
+    for attr in ["property", "name"] {
+        for quote in ['"', '\''] {
+            let needle = format!("{}={}{}{}", attr, quote, key, quote);
+            let Some(at) = lower.find(&needle) else {
+                continue;
+            };
+            let tag_start = lower[..at].rfind('<')?;
+            let tag_end = lower[at..].find('>')? + at;
+            let tag = &html[tag_start..tag_end];
+            let content_at = tag.to_ascii_lowercase().find("content=")? + "content=".len();
+            let value = &tag[content_at..];
+            let value = match value.chars().next()? {
+                q @ ('"' | '\'') => value[1..].split(q).next()?,
+                _ => value.split(char::is_whitespace).next()?,
+            };
+            let value = decode_entities(value).trim().to_string();
+            if !value.is_empty() {
+                return Some(value);
+            }
+        }
+    }
+    None
+}
+
+fn title_tag(html: &str) -> Option<String> {
+    let lower = html.to_ascii_lowercase();
+    let start = lower.find("<title")?;
+    let open = lower[start..].find('>')? + start + 1;
+    let close = lower[open..].find("</title>")? + open;
+    Some(decode_entities(html[open..close].trim())).filter(|t| !t.is_empty())
+}
+
+fn strip_tags(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => {
+                in_tag = true;
+                text.push(' ');
+            }
+            '>' => in_tag = false,
+            _ if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    decode_entities(&text)
+        .lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn decode_entities(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&#x27;", "'")
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn robots_rules_and_postings_are_parsed() {
+        let robots = Robots::parse(
+            "User-agent: *\nDisallow: /\n\n\
+             User-agent: cvenom-bot\nUser-agent: other\nDisallow: /jobs/search\n\
+             Allow: /jobs/view/\nDisallow: /*.pdf$\nCrawl-delay: 5\n",
+            "cvenom-bot/0.1.0 (+https://cvenom.com/bot)",
+        );
+        assert!(robots.allows("/jobs/view/123"));
+        assert!(!robots.allows("/jobs/search?keywords=rust"));
+        assert!(!robots.allows("/files/offer.pdf"));
+        assert!(robots.allows("/files/offer.pdf.html"));
+        assert_eq!(robots.crawl_delay, Some(Duration::from_secs(5)));
+
+        let others = Robots::parse("User-agent: *\nDisallow: /private\n", "somebot/1.0");
+        assert!(!others.allows("/private/x"));
+        assert!(others.allows("/jobs/1"));
+        assert!(!Robots::disallow_all().allows("/"));
+
+        let html = r#"<html><head><title>Ignored</title>
+            <script type="application/ld+json">{"@context":"https://schema.org",
+            "@type":"JobPosting","title":"Rust Engineer",
+            "hiringOrganization":{"@type":"Organization","name":"Acme &amp; Co"},
+            "jobLocation":{"address":{"addressLocality":"Geneva","addressCountry":"CH"}},
+            "description":"<p>Build things.</p><ul><li>Rust</li></ul>"}</script></head></html>"#;
+        let posting = parse_job_page(html).unwrap();
+        assert_eq!(posting.title, "Rust Engineer");
+        assert_eq!(posting.company, "Acme & Co");
+        assert_eq!(posting.location, "Geneva, CH");
+        assert_eq!(posting.description, "Build things. Rust");
+
+        let meta_only = r#"<meta property="og:title" content="Data Analyst">
+            <meta name="description" content="Analyse data.">"#;
+        let posting = parse_job_page(meta_only).unwrap();
+        assert_eq!(posting.title, "Data Analyst");
+        assert_eq!(posting.description, "Analyse data.");
+        assert!(is_thin(&posting, DEFAULT_MIN_TEXT_CHARS));
+        assert!(!is_thin(&posting, 10));
+        // An unquoted value starting with a multibyte character.
+        assert_eq!(
+            meta(r#"<meta name="description" content=été>"#, "description").as_deref(),
+            Some("été")
+        );
+    }
+
+    #[test]
+    fn only_public_addresses_are_fetched() {
+        for private in [
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.0.0.0",
+            "::1",
+            "::",
+            "::ffff:127.0.0.1",
+            "64:ff9b::a9fe:a9fe",
+            "fd00:ec2::254",
+            "fe80::1",
+        ] {
+            assert!(!is_public(private.parse().unwrap()), "{}", private);
+        }
+        for public in ["93.184.216.34", "2606:2800:220:1:248:1893:25c8:1946"] {
+            assert!(is_public(public.parse().unwrap()), "{}", public);
+        }
+    }
+}
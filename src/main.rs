@@ -1,7 +1,13 @@
 use std::env;
 
 use anyhow::Result;
-use cv_generator::{core::ConfigManager, start_web_server};
+use clap::Parser;
+use cv_generator::admin_cli::{handle_admin_command, AdminCli};
+use cv_generator::tenant_cli::{handle_tenant_command, TenantCli};
+use cv_generator::{
+    core::{config_manager, ConfigManager},
+    start_web_server,
+};
 use graflog::app_log;
 use graflog::init_logging;
 use graflog::LogOption;
@@ -21,6 +27,14 @@ async fn main() -> Result<()> {
         LogOption::RocketOff
     ]);
 
+    // `cvenom tenant …` and `cvenom admin …` run one command and exit.
+    let args: Vec<String> = env::args().collect();
+    match args.get(1).map(String::as_str) {
+        Some("tenant") => return handle_tenant_command(TenantCli::parse_from(&args[1..])).await,
+        Some("admin") => return handle_admin_command(AdminCli::parse_from(&args[1..])).await,
+        _ => {}
+    }
+
     let port = std::env::var("ROCKET_PORT")
         .map_err(|_| anyhow::anyhow!("ROCKET_PORT environment variable not set"))?
         .parse::<u16>()
@@ -34,7 +48,23 @@ async fn main() -> Result<()> {
 
     // Load configuration using unified ConfigManager
     let config = ConfigManager::load()?;
+
+    // Validate everything up front so misconfiguration fails at boot with one
+    // readable report instead of at the first request.
+    let report = config.validate(port);
+    for warning in &report.warnings {
+        app_log!(warn, "Config: {}", warning);
+    }
+    if report.has_errors() {
+        for error in &report.errors {
+            app_log!(error, "Config: {}", error);
+        }
+        eprintln!("{}", report);
+        anyhow::bail!("Invalid configuration ({} error(s))", report.errors.len());
+    }
+
     config.ensure_directories().await?;
+    config_manager::set_features(config.features.clone());
 
     app_log!(info, "Starting Multi-tenant CV Generator API Server");
     app_log!(
@@ -62,6 +92,7 @@ async fn main() -> Result<()> {
         config.environment.database_path,
         port,
         cv_service_url,
+        config.tls,
     )
     .await
 }
@@ -1,19 +1,20 @@
 // src/tenant_cli.rs
-use crate::database::{DatabaseConfig, TenantRepository, TenantService, get_tenant_folder_path};
-use crate::core::FsOps;
+use crate::core::database::{DatabaseConfig, TenantRepository, TenantService, get_tenant_folder_path, get_tenant_output_path};
+use crate::core::{consistency, data_residency, language_files, owner, profile_names, profile_schema, tenant_bundle, FsOps, TemplateEngine};
 use anyhow::Result;
 use clap::{Parser, Subcommand};
-use std::path::PathBuf;
+use sqlx::SqlitePool;
+use std::path::{Path, PathBuf};
 use graflog::app_log;
 
 #[derive(Parser)]
-#[command(name = "tenant-manager")]
+#[command(name = "cvenom tenant")]
 #[command(about = "Manage tenants for the CV generator")]
 pub struct TenantCli {
     #[command(subcommand)]
     pub command: TenantCommand,
 
-    #[arg(long, default_value = "data/tenants.db")]
+    #[arg(long, env = "CVENOM_DATABASE_PATH", default_value = "data/tenants.db")]
     pub database_path: PathBuf,
 }
 
@@ -44,6 +45,69 @@ pub enum TenantCommand {
         #[arg(long, help = "Actually delete — omit to do a dry run")]
         execute: bool,
     },
+    /// Propose sanitized names for a user's profile directories. Dry-run by default.
+    SanitizeProfiles {
+        email: String,
+        #[arg(long, help = "Base data directory containing tenant folders")]
+        data_dir: PathBuf,
+        #[arg(long, help = "Base output directory containing generated PDFs")]
+        output_dir: PathBuf,
+        #[arg(long, help = "Apply the renames — omit to do a dry run")]
+        execute: bool,
+    },
+    /// Find database records naming profiles missing on disk, and a stale name index. Dry-run by default.
+    CheckConsistency {
+        email: String,
+        #[arg(long, help = "Base data directory containing tenant folders")]
+        data_dir: PathBuf,
+        #[arg(long, help = "Repair the mismatches — omit to do a dry run")]
+        execute: bool,
+    },
+    /// Find experiences files that copy another language or are unedited placeholders. Dry-run by default.
+    CleanupLanguages {
+        email: String,
+        #[arg(long, help = "Base data directory containing tenant folders")]
+        data_dir: PathBuf,
+        #[arg(long, help = "Templates directory (for the starter placeholder)")]
+        templates_dir: PathBuf,
+        #[arg(long, help = "Delete the files — omit to do a dry run")]
+        execute: bool,
+    },
+    /// Find profiles whose cv_params.toml uses a legacy layout and upgrade them. Dry-run by default.
+    UpgradeSchema {
+        email: String,
+        #[arg(long, help = "Base data directory containing tenant folders")]
+        data_dir: PathBuf,
+        #[arg(long, help = "Rewrite the files, keeping cv_params.toml.bak — omit to do a dry run")]
+        execute: bool,
+    },
+    /// Write a tenant's settings, member roles and installed templates as a YAML bundle.
+    ExportBundle {
+        tenant: String,
+        #[arg(long, help = "Templates directory")]
+        templates_dir: PathBuf,
+        #[arg(long, help = "Write to this file instead of stdout")]
+        output: Option<PathBuf>,
+        #[arg(long, help = "Leave installed templates out of the bundle")]
+        no_templates: bool,
+    },
+    /// Apply a YAML bundle idempotently. Dry-run by default.
+    ApplyBundle {
+        bundle: PathBuf,
+        #[arg(long, help = "Templates directory")]
+        templates_dir: PathBuf,
+        #[arg(long, help = "Apply the changes — omit to do a dry run")]
+        execute: bool,
+    },
+}
+
+/// Resolve tenant folders through their data regions, as the server does.
+/// Commands without `--output-dir` route generated files from the server's.
+async fn load_regions(pool: &SqlitePool, data_dir: &Path, output_dir: Option<&Path>) -> Result<()> {
+    let server_output = std::env::var("CVENOM_OUTPUT_PATH").unwrap_or_else(|_| "output".to_string());
+    data_residency::init(data_dir, output_dir.unwrap_or(Path::new(&server_output)));
+    data_residency::load(pool).await?;
+    Ok(())
 }
 
 pub async fn handle_tenant_command(cli: TenantCli) -> Result<()> {
@@ -269,6 +333,7 @@ pub async fn handle_tenant_command(cli: TenantCli) -> Result<()> {
         }
 
         TenantCommand::Cleanup { days, data_dir, execute } => {
+            load_regions(pool, &data_dir, None).await?;
             let stale = match tenant_repo.find_stale_email_tenants(days).await {
                 Ok(s) => s,
                 Err(e) => {
@@ -310,7 +375,177 @@ pub async fn handle_tenant_command(cli: TenantCli) -> Result<()> {
                 app_log!(info, "\nRe-run with --execute to actually delete these accounts.");
             }
         }
+
+        TenantCommand::SanitizeProfiles { email, data_dir, output_dir, execute } => {
+            load_regions(pool, &data_dir, Some(&output_dir)).await?;
+            let tenant_dir = get_tenant_folder_path(&email, &data_dir);
+            let mut report = profile_names::plan(&tenant_dir).await?;
+            if report.renames.is_empty() {
+                app_log!(info, "✅ All {} profile names are clean.", report.unchanged);
+                return Ok(());
+            }
+
+            if execute {
+                let output_dir = get_tenant_output_path(&email, &output_dir);
+                let owner = owner::account_key(&email);
+                let applied =
+                    profile_names::apply(Some(pool), &owner, &tenant_dir, &output_dir, report);
+                report = match applied.await {
+                    Ok(r) => r,
+                    Err(e) => {
+                        app_log!(error, "❌ {:#}", e);
+                        return Err(e);
+                    }
+                };
+            }
+
+            let mode = if report.applied { "RENAMED" } else { "DRY RUN" };
+            app_log!(info, "[{}] {} profile(s) for {}:", mode, report.renames.len(), email);
+            for rename in &report.renames {
+                app_log!(info, "  {} -> {}", rename.from, rename.to);
+            }
+
+            if !report.applied {
+                app_log!(info, "\nRe-run with --execute to apply these renames.");
+            }
+        }
+
+        TenantCommand::CheckConsistency { email, data_dir, execute } => {
+            if tenant_repo.find_by_email_or_domain(&email).await?.is_none() {
+                app_log!(info, "❌ No active tenant found for email: {}", email);
+                return Ok(());
+            }
+            load_regions(pool, &data_dir, None).await?;
+            let tenant_dir = get_tenant_folder_path(&email, &data_dir);
+            let mut report = consistency::plan(pool, &email, &tenant_dir).await?;
+            if report.mismatches.is_empty() {
+                app_log!(info, "✅ Records of {} match its {} profile(s).", email, report.profiles);
+                return Ok(());
+            }
+
+            if execute {
+                report = consistency::apply(pool, &tenant_dir, report).await?;
+            }
+
+            let mode = if report.applied { "REPAIRED" } else { "DRY RUN" };
+            app_log!(info, "[{}] {} mismatch(es) for {}:", mode, report.mismatches.len(), email);
+            for mismatch in &report.mismatches {
+                let fix = match &mismatch.fix {
+                    consistency::Fix::Move { to } => format!("move to {}", to),
+                    consistency::Fix::Delete => "delete".to_string(),
+                    consistency::Fix::Rebuild => "rebuild".to_string(),
+                };
+                app_log!(info, "  {:?} {} ({} record(s)): {}", mismatch.kind, mismatch.target, mismatch.records, fix);
+            }
+
+            if !report.applied {
+                app_log!(info, "\nRe-run with --execute to repair these records.");
+            }
+        }
+
+        TenantCommand::CleanupLanguages { email, data_dir, templates_dir, execute } => {
+            load_regions(pool, &data_dir, None).await?;
+            let tenant_dir = get_tenant_folder_path(&email, &data_dir);
+            let starter = TemplateEngine::new(templates_dir)?.starter_experiences().await?;
+            let mut report = language_files::plan(&tenant_dir, &starter).await?;
+            if report.findings.is_empty() {
+                app_log!(info, "✅ No redundant language files in {} profile(s).", report.profiles_scanned);
+                return Ok(());
+            }
+
+            if execute {
+                report = language_files::apply(&tenant_dir, report).await?;
+            }
+
+            let mode = if report.applied { "REMOVED" } else { "DRY RUN" };
+            app_log!(info, "[{}] {} language file(s) for {}:", mode, report.findings.len(), email);
+            for finding in &report.findings {
+                match &finding.duplicate_of {
+                    Some(original) => app_log!(info, "  {}/{} (copy of {})", finding.profile, finding.file, original),
+                    None => app_log!(info, "  {}/{} (unedited placeholder)", finding.profile, finding.file),
+                }
+            }
+
+            if !report.applied {
+                app_log!(info, "\nRe-run with --execute to delete these files.");
+            }
+        }
+
+        TenantCommand::UpgradeSchema { email, data_dir, execute } => {
+            load_regions(pool, &data_dir, None).await?;
+            let tenant_dir = get_tenant_folder_path(&email, &data_dir);
+            let mut report = profile_schema::plan(&tenant_dir).await?;
+            if report.legacy.is_empty() {
+                app_log!(info, "✅ All {} profile(s) use the current schema.", report.profiles_scanned);
+                return Ok(());
+            }
+
+            if execute {
+                report = profile_schema::apply(&tenant_dir, report).await?;
+            }
+
+            let mode = if report.applied { "UPGRADED" } else { "DRY RUN" };
+            app_log!(info, "[{}] {} legacy profile(s) for {}:", mode, report.legacy.len(), email);
+            for profile in &report.legacy {
+                app_log!(info, "  {}", profile);
+            }
+
+            if !report.applied {
+                app_log!(info, "\nRe-run with --execute to upgrade these profiles.");
+            }
+        }
+
+        TenantCommand::ExportBundle { tenant, templates_dir, output, no_templates } => {
+            let bundle = tenant_bundle::export(pool, &templates_dir, &tenant, !no_templates).await?;
+            let yaml = serde_yaml::to_string(&bundle)?;
+            match output {
+                Some(path) => {
+                    tokio::fs::write(&path, yaml).await?;
+                    app_log!(info, "✅ Bundle of {} written to {}", tenant, path.display());
+                }
+                None => println!("{}", yaml),
+            }
+        }
+
+        TenantCommand::ApplyBundle { bundle, templates_dir, execute } => {
+            let content = tokio::fs::read_to_string(&bundle).await?;
+            let bundle: tenant_bundle::TenantBundle = serde_yaml::from_str(&content)?;
+            let report = tenant_bundle::apply(pool, &templates_dir, &bundle, !execute).await?;
+
+            let mode = if execute { "APPLIED" } else { "DRY RUN" };
+            app_log!(info, "[{}] {} change(s) for tenant {}:", mode, report.changes.len(), report.tenant);
+            for change in &report.changes {
+                app_log!(info, "  {}", change);
+            }
+            for warning in &report.warnings {
+                app_log!(warn, "  ⚠️  {}", warning);
+            }
+            if !execute && !report.changes.is_empty() {
+                app_log!(info, "\nRe-run with --execute to apply these changes.");
+            }
+        }
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn tenant_arguments_parse_as_the_readme_documents() {
+        TenantCli::command().debug_assert();
+
+        let cli = TenantCli::try_parse_from([
+            "tenant", "check-consistency", "jane@acme.com", "--data-dir", "data", "--execute",
+        ])
+        .unwrap();
+        assert!(matches!(
+            cli.command,
+            TenantCommand::CheckConsistency { ref email, execute: true, .. } if email == "jane@acme.com"
+        ));
+        assert!(TenantCli::try_parse_from(["tenant", "upgrade-schema", "jane@acme.com"]).is_err());
+    }
+}
@@ -1,6 +1,7 @@
 // src/types/cv_data.rs
 //! Unified CV data structures for cv-import service interactions
 
+use crate::core::{data_encryption, profile_schema, toml_patch, translations};
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -211,31 +212,6 @@ pub struct CvMetadata {
     pub version: Option<String>,
 }
 
-// Helper function to get section case-insensitively
-fn get_section_ci<'a>(
-    toml_value: &'a toml::Value,
-    section_name: &str,
-) -> Option<&'a toml::value::Table> {
-    if let Some(table) = toml_value.as_table() {
-        for (key, value) in table {
-            if key.to_lowercase() == section_name.to_lowercase() {
-                // Handle both [section] (table) and [[section]] (array of tables - return first)
-                if let Some(table) = value.as_table() {
-                    return Some(table);
-                } else if let Some(array) = value.as_array() {
-                    // For [[section]], return the first table in the array
-                    if let Some(first_item) = array.first() {
-                        if let Some(first_table) = first_item.as_table() {
-                            return Some(first_table);
-                        }
-                    }
-                }
-            }
-        }
-    }
-    None
-}
-
 // ===== Local Conversion Logic =====
 
 /// Escape a string for embedding inside Typst double-quoted literals.
@@ -247,7 +223,42 @@ fn escape_typst(s: &str) -> String {
 
 pub struct CvConverter;
 
+/// Top-level cv_params.toml keys owned by [`CvConverter::to_toml`]. `styling`
+/// is left out: the converter only writes default colors for it.
+const CONVERTER_TOML_KEYS: &[&str] = &[
+    "name",
+    "title",
+    "email",
+    "phonenumber",
+    "address",
+    "summary",
+    "links",
+    "skills",
+    "education",
+    "languages",
+];
+
 impl CvConverter {
+    /// [`Self::to_toml`] applied to an existing cv_params.toml: the converter's
+    /// keys are updated in place while comments, key order, the user's
+    /// styling and unknown keys are kept. Unparseable input on either side
+    /// falls back to the plain conversion.
+    pub fn merge_into_toml(existing: &str, cv_data: &CvJson) -> Result<String> {
+        let fresh_toml = Self::to_toml(cv_data)?;
+        let (Ok(mut doc), Ok(fresh)) = (
+            toml_patch::parse(existing),
+            toml_patch::parse(&fresh_toml),
+        ) else {
+            return Ok(fresh_toml);
+        };
+        let mut keys = CONVERTER_TOML_KEYS.to_vec();
+        if !doc.contains_key("styling") {
+            keys.push("styling");
+        }
+        toml_patch::apply(&mut doc, &fresh, &keys);
+        Ok(doc.to_string())
+    }
+
     /// Convert CvJson to TOML configuration
     pub fn to_toml(cv_data: &CvJson) -> Result<String> {
         let mut toml_content = String::new();
@@ -375,11 +386,11 @@ impl CvConverter {
             let date_range = if let Some(end) = &exp.end_date {
                 format!("{} - {}", exp.start_date, end)
             } else {
-                match language {
-                    "fr" => format!("{} - Présent", exp.start_date),
-                    "de" => format!("{} - Heute", exp.start_date),
-                    _ => format!("{} - Present", exp.start_date),
-                }
+                format!(
+                    "{} - {}",
+                    exp.start_date,
+                    translations::text(language, "present")
+                )
             };
 
             typst_content.push_str(&format!("  == {}\n", exp.company));
@@ -438,31 +449,17 @@ impl CvConverter {
     ) -> Result<CvJson> {
         // Parse existing TOML file
         let toml_content =
-            std::fs::read_to_string(toml_path).context("Failed to read TOML file")?;
+            data_encryption::read_to_string(toml_path).context("Failed to read TOML file")?;
 
-        let toml_value: toml::Value =
-            toml::from_str(&toml_content).context("Failed to parse TOML")?;
+        let toml_value: toml::Value = toml::from_str(&profile_schema::canonicalize(&toml_content))
+            .context("Failed to parse TOML")?;
 
-        // Helper function to get field from either root level or personal section
         let get_personal_field = |field_name: &str| -> String {
-            // Try root level first (flat structure)
-            if let Some(value) = toml_value.get(field_name).and_then(|v| v.as_str()) {
-                return value.to_string();
-            }
-            // Try personal section (nested structure)
-            if let Some(personal_section) = get_section_ci(&toml_value, "personal") {
-                if let Some(value) = personal_section.get(field_name).and_then(|v| v.as_str()) {
-                    return value.to_string();
-                }
-            }
-            // Try personal_info section (alternative nested structure)
-            if let Some(personal_section) = get_section_ci(&toml_value, "personal_info") {
-                if let Some(value) = personal_section.get(field_name).and_then(|v| v.as_str()) {
-                    return value.to_string();
-                }
-            }
-            // Return empty string instead of None
-            String::new()
+            toml_value
+                .get(field_name)
+                .and_then(|v| v.as_str())
+                .unwrap_or_default()
+                .to_string()
         };
 
         let personal_info = PersonalInfo {
@@ -484,8 +481,7 @@ impl CvConverter {
             links: None, // TODO: Parse links if needed
         };
 
-        // Extract skills using case-insensitive lookup
-        let skills = if let Some(skills_section) = get_section_ci(&toml_value, "skills") {
+        let skills = if let Some(skills_section) = toml_value.get("skills").and_then(|v| v.as_table()) {
             Skills {
                 technical: skills_section
                     .get("technical")
@@ -533,8 +529,7 @@ impl CvConverter {
             }
         };
 
-        // Extract languages using case-insensitive lookup
-        let languages = if let Some(lang_section) = get_section_ci(&toml_value, "languages") {
+        let languages = if let Some(lang_section) = toml_value.get("languages").and_then(|v| v.as_table()) {
             Languages {
                 native: lang_section
                     .get("native")
@@ -578,10 +573,7 @@ impl CvConverter {
             }
         };
 
-        // Extract education using case-insensitive lookup
-        let education = if let Some(edu_array) = get_section_ci(&toml_value, "education")
-            .and_then(|_| toml_value.get("education"))
-            .and_then(|v| v.as_array())
+        let education = if let Some(edu_array) = toml_value.get("education").and_then(|v| v.as_array())
         {
             edu_array
                 .iter()
@@ -607,7 +599,7 @@ impl CvConverter {
         };
 
         // Parse work experience from the Typst file
-        let typst_content = std::fs::read_to_string(typst_path)
+        let typst_content = data_encryption::read_to_string(typst_path)
             .unwrap_or_default();
         let work_experience = parse_typst_experiences(&typst_content);
 
@@ -699,7 +691,10 @@ fn parse_typst_experiences(content: &str) -> Vec<Experience> {
                 exp.start_date = parts[0].trim().to_string();
                 if parts.len() > 1 {
                     let end = parts[1].trim().to_string();
-                    if end.is_empty() || end == "Present" || end == "Présent" {
+                    let ongoing = translations::languages()
+                        .into_iter()
+                        .any(|lang| end == translations::text(lang, "present"));
+                    if end.is_empty() || ongoing {
                         exp.end_date = None; // current position
                     } else {
                         exp.end_date = Some(end);
@@ -18,7 +18,7 @@ pub struct CvConversionResponse {
     pub message: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct JobMatchResponse {
     pub analysis: String,
     pub score: Option<f64>,
@@ -16,6 +16,45 @@ pub fn normalize_profile_name(name: &str) -> String {
         .collect()
 }
 
+/// ASCII spelling of a lowercase accented letter, if it has one.
+fn fold_accent(c: char) -> Option<&'static str> {
+    Some(match c {
+        'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' => "a",
+        'æ' => "ae",
+        'ç' => "c",
+        'è' | 'é' | 'ê' | 'ë' => "e",
+        'ì' | 'í' | 'î' | 'ï' => "i",
+        'ñ' => "n",
+        'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' => "o",
+        'œ' => "oe",
+        'ß' => "ss",
+        'ù' | 'ú' | 'û' | 'ü' => "u",
+        'ý' | 'ÿ' => "y",
+        _ => return None,
+    })
+}
+
+/// Case- and accent-insensitive form of a name: `Élodie` and `elodie` fold
+/// to the same key. Other characters are kept.
+pub fn fold_name(name: &str) -> String {
+    name.to_lowercase()
+        .chars()
+        .fold(String::new(), |mut folded, c| {
+            match fold_accent(c) {
+                Some(ascii) => folded.push_str(ascii),
+                None => folded.push(c),
+            }
+            folded
+        })
+}
+
+/// Order names as people read them: by their folded form, so `Élodie` sorts
+/// with the e's rather than after `zoe`, then by the raw name to keep the
+/// order stable.
+pub fn collate_names(a: &str, b: &str) -> std::cmp::Ordering {
+    fold_name(a).cmp(&fold_name(b)).then_with(|| a.cmp(b))
+}
+
 /// Normalize language code
 pub fn normalize_language(lang: Option<&str>) -> String {
     match lang.map(|s| s.to_lowercase()).as_deref() {
@@ -84,6 +123,99 @@ pub fn get_file_extension(filename: &str) -> Option<String> {
         .map(|ext| ext.to_lowercase())
 }
 
+/// File name of a generated CV: `{profile}_{template}_{lang}.pdf`.
+/// Shared by the compiler, the output listings and download headers so the
+/// three never drift apart.
+pub fn generated_pdf_filename(profile: &str, template: &str, lang: &str) -> String {
+    format!("{}_{}_{}.pdf", profile, template, lang)
+}
+
+/// File name of a DRAFT-marked CV, so it never replaces the published one:
+/// `{profile}_{template}_{lang}_draft.pdf`.
+pub fn draft_pdf_filename(profile: &str, template: &str, lang: &str) -> String {
+    format!("{}_{}_{}_draft.pdf", profile, template, lang)
+}
+
+/// Stable id for a generated file, derived from its name, size and mtime —
+/// the same file always reports the same id, a regeneration gets a new one.
+pub fn generation_id(path: &Path) -> Option<String> {
+    use std::hash::{Hash, Hasher};
+    let meta = std::fs::metadata(path).ok()?;
+    let mtime = meta
+        .modified()
+        .ok()?
+        .duration_since(std::time::UNIX_EPOCH)
+        .ok()?
+        .as_nanos();
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    path.file_name().hash(&mut hasher);
+    meta.len().hash(&mut hasher);
+    mtime.hash(&mut hasher);
+    Some(format!("{:016x}", hasher.finish()))
+}
+
+/// `Content-Disposition: attachment` value with an ASCII `filename` fallback
+/// and an RFC 5987 `filename*` carrying the exact UTF-8 name, so accented
+/// person names survive in every browser.
+pub fn content_disposition(filename: &str) -> String {
+    use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+    // RFC 5987 attr-char: ALPHA / DIGIT / "!#$&+-.^_`|~"
+    const ATTR_CHAR: &AsciiSet = &NON_ALPHANUMERIC
+        .remove(b'!')
+        .remove(b'#')
+        .remove(b'$')
+        .remove(b'&')
+        .remove(b'+')
+        .remove(b'-')
+        .remove(b'.')
+        .remove(b'^')
+        .remove(b'_')
+        .remove(b'`')
+        .remove(b'|')
+        .remove(b'~');
+
+    let fallback: String = filename
+        .chars()
+        .map(|c| {
+            if (c.is_ascii_graphic() && c != '"' && c != '\\') || c == ' ' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!(
+        "attachment; filename=\"{}\"; filename*=UTF-8''{}",
+        fallback,
+        utf8_percent_encode(filename, ATTR_CHAR)
+    )
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Hex SHA-256 of `data`.
+pub fn sha256_hex(data: &[u8]) -> String {
+    use sha2::{Digest, Sha256};
+    hex(&Sha256::digest(data))
+}
+
+/// Hex HMAC-SHA256 of `data` under `key`.
+pub fn hmac_sha256_hex(key: &[u8], data: &[u8]) -> String {
+    use hmac::{Hmac, Mac};
+    let mut mac =
+        Hmac::<sha2::Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(data);
+    hex(&mac.finalize().into_bytes())
+}
+
+/// Whether `a` and `b` are equal, in a time that depends on their length
+/// only; for comparing secrets and signatures.
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
 /// Validate file extension against allowed types
 pub fn validate_file_extension(filename: &str, allowed: &[&str]) -> Result<()> {
     let ext = get_file_extension(filename)
@@ -130,6 +262,42 @@ mod tests {
         assert_eq!(normalize_profile_name("Marie@Company"), "marie_company");
     }
 
+    #[test]
+    fn test_collate_names() {
+        assert_eq!(fold_name("Élodie_Müller"), "elodie_muller");
+        let mut names = vec!["zoe", "élodie", "Emma", "elodie", "adam"];
+        names.sort_by(|a, b| collate_names(a, b));
+        assert_eq!(names, vec!["adam", "elodie", "élodie", "Emma", "zoe"]);
+    }
+
+    #[test]
+    fn test_sha256_and_hmac() {
+        assert_eq!(
+            sha256_hex(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        // RFC 4231, test case 2.
+        assert_eq!(
+            hmac_sha256_hex(b"Jefe", b"what do ya want for nothing?"),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+        assert!(constant_time_eq(b"secret", b"secret"));
+        assert!(!constant_time_eq(b"secret", b"secreT"));
+        assert!(!constant_time_eq(b"secret", b"secret2"));
+    }
+
+    #[test]
+    fn test_content_disposition_encodes_accents() {
+        assert_eq!(
+            content_disposition("rené_default_fr.pdf"),
+            "attachment; filename=\"ren__default_fr.pdf\"; filename*=UTF-8''ren%C3%A9_default_fr.pdf"
+        );
+        assert_eq!(
+            content_disposition("a\"b.pdf"),
+            "attachment; filename=\"a_b.pdf\"; filename*=UTF-8''a%22b.pdf"
+        );
+    }
+
     #[test]
     fn test_normalize_language() {
         assert_eq!(normalize_language(Some("fr")), "fr");
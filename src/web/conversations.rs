@@ -0,0 +1,151 @@
+// src/web/conversations.rs
+//! Fairing that records conversation turns.
+//!
+//! Any authenticated request whose body or JSON response carries a
+//! `conversation_id` is stored as one turn in `core::conversation_store`.
+//! Handlers don't need to do anything: standard responses already echo the id.
+//! Sandbox requests (see [`crate::web::sandbox`]) are not recorded.
+
+use crate::auth::RequestUserEmail;
+use crate::core::conversation_store::{self, ConversationTurn};
+use crate::core::database::DatabaseConfig;
+use graflog::app_log;
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::http::Method;
+use rocket::{Data, Request, Response};
+use std::io::Cursor;
+
+/// Rocket only buffers up to 512 bytes for a peek; enough for the id and the
+/// start of the request.
+const PEEK_BYTES: usize = 512;
+
+/// JSON responses larger than this are not inspected (and not re-buffered).
+const MAX_INSPECTED_RESPONSE_BYTES: usize = 256 * 1024;
+
+const MAX_CONVERSATION_ID_LEN: usize = 128;
+
+pub struct ConversationRecorder;
+
+/// Start of the JSON request body, cached for `on_response`.
+struct RequestPeek(String);
+
+/// `"conversation_id": "<value>"` from a (possibly truncated) JSON prefix.
+fn conversation_id_from_peek(peek: &str) -> Option<String> {
+    let after_key = &peek[peek.find("\"conversation_id\"")? + "\"conversation_id\"".len()..];
+    let after_colon = after_key.trim_start().strip_prefix(':')?.trim_start();
+    let value = after_colon.strip_prefix('"')?;
+    Some(value[..value.find('"')?].to_string())
+}
+
+fn valid_conversation_id(id: &str) -> bool {
+    !id.is_empty() && id.len() <= MAX_CONVERSATION_ID_LEN && !id.contains(char::is_control)
+}
+
+#[rocket::async_trait]
+impl Fairing for ConversationRecorder {
+    fn info(&self) -> Info {
+        Info {
+            name: "Record conversation turns",
+            kind: Kind::Request | Kind::Response,
+        }
+    }
+
+    async fn on_request(&self, request: &mut Request<'_>, data: &mut Data<'_>) {
+        if !matches!(request.method(), Method::Post | Method::Put) {
+            return;
+        }
+        if !request.content_type().is_some_and(|ct| ct.is_json()) {
+            return;
+        }
+        let peek = String::from_utf8_lossy(data.peek(PEEK_BYTES).await).to_string();
+        request.local_cache(|| RequestPeek(peek));
+    }
+
+    async fn on_response<'r>(&self, request: &'r Request<'_>, response: &mut Response<'r>) {
+        if crate::web::sandbox::is_sandboxed(request) {
+            return;
+        }
+        let RequestUserEmail(Some(user_email)) = request.local_cache(|| RequestUserEmail(None))
+        else {
+            return;
+        };
+        let peek = &request.local_cache(|| RequestPeek(String::new())).0;
+
+        let inspectable = response.content_type().is_some_and(|ct| ct.is_json())
+            && response
+                .body()
+                .preset_size()
+                .is_some_and(|size| size <= MAX_INSPECTED_RESPONSE_BYTES);
+        let mut response_json = None;
+        if inspectable {
+            if let Ok(body) = response.body_mut().to_string().await {
+                response_json = serde_json::from_str::<serde_json::Value>(&body).ok();
+                response.set_sized_body(body.len(), Cursor::new(body));
+            }
+        }
+
+        let conversation_id = response_json
+            .as_ref()
+            .and_then(|body| body.get("conversation_id"))
+            .and_then(|v| v.as_str())
+            .map(str::to_string)
+            .or_else(|| conversation_id_from_peek(peek))
+            .filter(|id| valid_conversation_id(id));
+        let Some(conversation_id) = conversation_id else {
+            return;
+        };
+
+        let Some(pool) = request
+            .rocket()
+            .state::<DatabaseConfig>()
+            .and_then(|c| c.pool().ok())
+            .cloned()
+        else {
+            return;
+        };
+
+        let response_summary = match &response_json {
+            Some(body) => conversation_store::summarize_response(body),
+            None => format!(
+                "{} response",
+                response
+                    .content_type()
+                    .map(|ct| ct.to_string())
+                    .unwrap_or_else(|| "non-JSON".to_string())
+            ),
+        };
+        let turn = ConversationTurn {
+            conversation_id,
+            method: request.method().as_str().to_string(),
+            path: request.uri().path().to_string(),
+            status: response.status().code as i64,
+            request_summary: conversation_store::truncate_summary(peek),
+            response_summary,
+            created_at: chrono::Utc::now(),
+        };
+        let user_email = user_email.clone();
+        tokio::spawn(async move {
+            if let Err(e) = conversation_store::record(&pool, &user_email, &turn).await {
+                app_log!(warn, "Failed to record conversation turn: {}", e);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_conversation_id_in_truncated_body() {
+        assert_eq!(
+            conversation_id_from_peek(r#"{"profile":"jane","conversation_id" : "c-42","lang":"#),
+            Some("c-42".to_string())
+        );
+        assert_eq!(conversation_id_from_peek(r#"{"profile":"jane"}"#), None);
+        assert_eq!(
+            conversation_id_from_peek(r#"{"conversation_id":"unterminated"#),
+            None
+        );
+    }
+}
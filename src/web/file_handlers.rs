@@ -1,51 +1,44 @@
 // src/web/file_handlers.rs - Updated for new tenant structure
 
 use crate::auth::AuthenticatedUser;
-use crate::core::database::{get_tenant_folder_path, DatabaseConfig};
-use crate::core::FsOps;
+use crate::core::audit_log::{self, AuditAction};
+use crate::core::database::{get_tenant_folder_path, get_tenant_output_path, DatabaseConfig};
+use crate::core::file_drafts::{self, DraftView};
+use crate::core::profile_access::ProfileType;
+use crate::core::profile_watch::WatchChange;
+use crate::core::provisioning::{self, SelfProfile};
+use crate::core::typst_lint::{self, LintLevel};
+use crate::core::tenant_templates;
+use crate::core::{data_encryption, FsOps};
+use crate::web::handlers::cv_handlers::{
+    notify_watchers, require_create_access, require_edit_access,
+};
 use crate::web::types::{
-    ActionResponse, SaveFileRequest, StandardErrorResponse, StandardRequest, WithConversationId,
+    ActionResponse, DataResponse, SaveFileRequest, ServerConfig, StandardErrorResponse,
+    StandardRequest, WithConversationId,
 };
 use async_recursion::async_recursion;
 use graflog::app_log;
-use rocket::http::Status;
+use rocket::http::{ContentType, Status};
 use rocket::serde::json::Json;
 use rocket::State;
 use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
 
 impl AuthenticatedUser {
-    /// Ensure profile directory exists for this user
+    /// Provision this user's own profile on first login; see
+    /// [`crate::core::provisioning`].
     pub async fn ensure_profile_exists(
         &self,
-        config: &crate::web::types::ServerConfig,
-        _db_config: &DatabaseConfig,
-    ) -> Result<(), anyhow::Error> {
-        // Extract profile name from email (before @)
-        let profile_name = self.firebase_user.email.split('@').next().unwrap_or("user");
-        let normalized_profile = crate::utils::normalize_profile_name(profile_name);
-
-        // Use new tenant folder path
-        let tenant_data_dir = get_tenant_folder_path(&self.firebase_user.email, &config.data_dir);
-        let profile_dir = tenant_data_dir.join(&normalized_profile);
-
-        // Ensure directories exist
-        FsOps::ensure_dir_exists(&profile_dir).await?;
-
-        // Create default files if they don't exist
-        // let cv_params_path = profile_dir.join("cv_params.toml");
-        // if !cv_params_path.exists() {
-        //     // Use core TemplateEngine to create profile files
-        //     let template_engine = crate::core::TemplateEngine::new(config.templates_dir.clone())?;
-        //     template_engine
-        //         .create_profile_from_templates(
-        //             &normalized_profile,
-        //             &tenant_data_dir,
-        //             Some(profile_name),
-        //         )
-        //         .await?;
-        // }
-
-        Ok(())
+        config: &ServerConfig,
+    ) -> Result<Option<SelfProfile>, anyhow::Error> {
+        provisioning::ensure_self_profile(
+            &self.firebase_user.email,
+            self.firebase_user.name.as_deref(),
+            &config.data_dir,
+            &config.templates_dir,
+        )
+        .await
     }
 }
 
@@ -73,15 +66,14 @@ pub async fn get_tenant_file_content_handler(
 
     // Use new tenant folder path
     let tenant_data_dir = get_tenant_folder_path(&auth.user().email, &config.data_dir);
-    let file_path = tenant_data_dir.join(&path);
 
     // Security: Ensure the file is within tenant directory
-    if !file_path.starts_with(&tenant_data_dir) {
+    let Some(file_path) = resolve_tenant_path(&tenant_data_dir, &path) else {
         app_log!(warn, "Path traversal attempt: {}", path);
         return Err(Status::Forbidden);
-    }
+    };
 
-    match tokio::fs::read_to_string(&file_path).await {
+    match FsOps::read_file_safe(&file_path).await {
         Ok(content) => {
             app_log!(
                 info,
@@ -102,7 +94,7 @@ pub async fn save_tenant_file_content_handler(
     request: Json<StandardRequest<SaveFileRequest>>,
     auth: AuthenticatedUser,
     config: &State<crate::web::types::ServerConfig>,
-    _db_config: &State<DatabaseConfig>,
+    db_config: &State<DatabaseConfig>,
 ) -> Result<Json<ActionResponse>, Json<StandardErrorResponse>> {
     let tenant = auth.tenant();
     let conversation_id = request.conversation_id();
@@ -135,10 +127,9 @@ pub async fn save_tenant_file_content_handler(
 
     // Use new tenant folder path
     let tenant_data_dir = get_tenant_folder_path(&auth.user().email, &config.data_dir);
-    let file_path = tenant_data_dir.join(&request.data.path);
 
     // Security: Ensure the file is within tenant directory
-    if !file_path.starts_with(&tenant_data_dir) {
+    let Some(file_path) = resolve_tenant_path(&tenant_data_dir, &request.data.path) else {
         app_log!(warn, "Path traversal attempt: {}", request.data.path);
         return Err(Json(StandardErrorResponse::new(
             "Invalid file path".to_string(),
@@ -149,6 +140,52 @@ pub async fn save_tenant_file_content_handler(
             ],
             conversation_id,
         )));
+    };
+
+    // The first path component is the profile; anything else (a new
+    // profile, a tenant-level file) counts as creating one.
+    let profile_dir = Path::new(&request.data.path)
+        .components()
+        .next()
+        .map(|profile| tenant_data_dir.join(profile))
+        .filter(|dir| dir.is_dir() && file_path.starts_with(dir) && *dir != file_path);
+    match &profile_dir {
+        Some(dir) => require_edit_access(&auth, dir, conversation_id.clone())?,
+        None => require_create_access(&auth, conversation_id.clone())?,
+    }
+
+    // Editor Typst may not read files; see `typst_lint`.
+    let mut lint_warnings = Vec::new();
+    if request.data.path.ends_with(".typ") {
+        let findings = typst_lint::lint(&request.data.content);
+        if !findings.is_empty() {
+            let level = match db_config.pool() {
+                Ok(pool) => typst_lint::level_for(pool, tenant.id).await,
+                Err(_) => LintLevel::default(),
+            };
+            app_log!(
+                warn,
+                "Typst lint ({}) on {} by {}: {} findings",
+                level.as_str(),
+                request.data.path,
+                auth.email(),
+                findings.len()
+            );
+            match level {
+                LintLevel::Off => {}
+                LintLevel::Warn => {
+                    lint_warnings = findings.iter().map(|f| f.to_string()).collect();
+                }
+                LintLevel::Block => {
+                    return Err(Json(StandardErrorResponse::new(
+                        "The file reads other files or uses paths outside your CV".to_string(),
+                        "TYPST_CONTENT_BLOCKED".to_string(),
+                        findings.iter().map(|f| f.to_string()).collect(),
+                        conversation_id,
+                    )));
+                }
+            }
+        }
     }
 
     // Ensure parent directory exists
@@ -172,7 +209,7 @@ pub async fn save_tenant_file_content_handler(
         }
     }
 
-    match tokio::fs::write(&file_path, &request.data.content).await {
+    match FsOps::write_file_safe(&file_path, &request.data.content).await {
         Ok(_) => {
             app_log!(
                 info,
@@ -181,11 +218,30 @@ pub async fn save_tenant_file_content_handler(
                 tenant.tenant_name
             );
 
-            let next_actions = vec![
+            // The saved content supersedes any autosaved draft.
+            if let (Some(key), Ok(pool)) = (draft_key(&request.data.path), db_config.pool()) {
+                if let Err(e) = file_drafts::discard(pool, auth.email(), &key).await {
+                    app_log!(warn, "Failed to discard draft of {}: {}", key, e);
+                }
+            }
+            if let Some(dir) = &profile_dir {
+                let file = file_path.strip_prefix(dir).unwrap_or(&file_path);
+                notify_watchers(
+                    &auth,
+                    db_config,
+                    dir,
+                    WatchChange::FileSaved {
+                        path: file.to_string_lossy().replace('\\', "/"),
+                    },
+                );
+            }
+
+            let mut next_actions = lint_warnings;
+            next_actions.extend([
                 "Generate CV with updated content".to_string(),
                 "Preview changes in CV".to_string(),
                 "Save additional files if needed".to_string(),
-            ];
+            ]);
 
             let response = ActionResponse::success(
                 format!("File '{}' saved successfully", request.data.path),
@@ -212,23 +268,162 @@ pub async fn save_tenant_file_content_handler(
     }
 }
 
+/// Normalized draft key for an editable tenant-relative path.
+fn draft_key(path: &str) -> Option<String> {
+    if !path.ends_with(".typ") && !path.ends_with(".toml") {
+        return None;
+    }
+    let relative = resolve_tenant_path(Path::new(""), path)?;
+    let parts: Vec<_> = relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect();
+    Some(parts.join("/"))
+}
+
+fn draft_error(
+    message: &str,
+    code: &str,
+    suggestions: Vec<String>,
+    conversation_id: Option<String>,
+) -> Json<StandardErrorResponse> {
+    Json(StandardErrorResponse::new(
+        message.to_string(),
+        code.to_string(),
+        suggestions,
+        conversation_id,
+    ))
+}
+
+fn invalid_draft_path(conversation_id: Option<String>) -> Json<StandardErrorResponse> {
+    draft_error(
+        "Drafts are only kept for .typ and .toml files in your tenant directory",
+        "INVALID_PATH",
+        vec![],
+        conversation_id,
+    )
+}
+
+fn draft_db_error(
+    e: impl std::fmt::Display,
+    conversation_id: Option<String>,
+) -> Json<StandardErrorResponse> {
+    app_log!(error, "File draft storage failed: {}", e);
+    draft_error(
+        "Failed to access drafts",
+        "DB_ERROR",
+        vec!["Try again in a few moments".to_string()],
+        conversation_id,
+    )
+}
+
+/// POST /files/draft — autosave unsaved editor content
+pub async fn save_file_draft_handler(
+    request: Json<StandardRequest<SaveFileRequest>>,
+    auth: AuthenticatedUser,
+    db_config: &State<DatabaseConfig>,
+) -> Result<Json<DataResponse<DraftView>>, Json<StandardErrorResponse>> {
+    let conversation_id = request.conversation_id();
+    let key =
+        draft_key(&request.data.path).ok_or_else(|| invalid_draft_path(conversation_id.clone()))?;
+    if request.data.content.len() > file_drafts::MAX_DRAFT_BYTES {
+        return Err(draft_error(
+            "Draft is too large",
+            "DRAFT_TOO_LARGE",
+            vec!["Save the file instead".to_string()],
+            conversation_id,
+        ));
+    }
+
+    let pool = db_config
+        .pool()
+        .map_err(|e| draft_db_error(e, conversation_id.clone()))?;
+    let draft = file_drafts::save(pool, auth.email(), &key, &request.data.content)
+        .await
+        .map_err(|e| draft_db_error(e, conversation_id.clone()))?;
+
+    Ok(Json(DataResponse::success(
+        format!("Draft of '{}' saved", key),
+        draft.into(),
+        conversation_id,
+    )))
+}
+
+/// GET /files/draft?<path> — the autosaved draft of a file, if any
+pub async fn get_file_draft_handler(
+    path: String,
+    auth: AuthenticatedUser,
+    db_config: &State<DatabaseConfig>,
+) -> Result<Json<DataResponse<DraftView>>, Json<StandardErrorResponse>> {
+    let key = draft_key(&path).ok_or_else(|| invalid_draft_path(None))?;
+    let pool = db_config.pool().map_err(|e| draft_db_error(e, None))?;
+    match file_drafts::load(pool, auth.email(), &key).await {
+        Ok(Some(draft)) => Ok(Json(DataResponse::success(
+            format!("Draft of '{}' found", key),
+            draft.into(),
+            None,
+        ))),
+        Ok(None) => Err(draft_error(
+            &format!("No draft for '{}'", key),
+            "DRAFT_NOT_FOUND",
+            vec!["Load the saved file with /files/content".to_string()],
+            None,
+        )),
+        Err(e) => Err(draft_db_error(e, None)),
+    }
+}
+
+/// DELETE /files/draft?<path> — discard the autosaved draft of a file
+pub async fn discard_file_draft_handler(
+    path: String,
+    auth: AuthenticatedUser,
+    db_config: &State<DatabaseConfig>,
+) -> Result<Json<ActionResponse>, Json<StandardErrorResponse>> {
+    let key = draft_key(&path).ok_or_else(|| invalid_draft_path(None))?;
+    let pool = db_config.pool().map_err(|e| draft_db_error(e, None))?;
+    let existed = file_drafts::discard(pool, auth.email(), &key)
+        .await
+        .map_err(|e| draft_db_error(e, None))?;
+
+    let message = if existed {
+        format!("Draft of '{}' discarded", key)
+    } else {
+        format!("No draft for '{}'", key)
+    };
+    Ok(Json(ActionResponse::success(
+        message,
+        "DRAFT_DISCARDED".to_string(),
+        None,
+    )))
+}
+
 pub async fn get_tenant_files_handler(
+    include: Option<String>,
     auth: AuthenticatedUser,
     config: &State<crate::web::types::ServerConfig>,
     // db_config: &State<DatabaseConfig>,
 ) -> Result<Json<serde_json::Value>, Status> {
-    // Auto-create profile if doesn't exist
-    // if let Err(e) = auth.ensure_profile_exists(config, db_config).await {
-    //     app_log!(error, "Failed to ensure profile exists: {}", e);
-    // }
-
     let tenant_data_dir = get_tenant_folder_path(&auth.user().email, &config.data_dir);
+    let include = TreeInclude::parse(include.as_deref());
 
     // Check if a tenant-level default photo exists
     let has_default_photo = tenant_data_dir.join("default_photo.png").exists();
 
+    let outputs = if include.pdfs {
+        GeneratedOutputs::scan(config, &auth).await
+    } else {
+        GeneratedOutputs::default()
+    };
+
     // Build file tree for tenant's directory only if it exists
-    match build_file_tree(&tenant_data_dir, has_default_photo).await {
+    match build_file_tree(
+        &tenant_data_dir,
+        has_default_photo,
+        &include,
+        Some(&outputs),
+    )
+    .await
+    {
         Ok(tree) => {
             let tree_value = serde_json::to_value(tree).unwrap_or_default();
             Ok(Json(tree_value))
@@ -245,10 +440,255 @@ pub async fn get_tenant_files_handler(
     }
 }
 
+/// Serve an image or PDF stored in the tenant directory (the `downloadable`
+/// nodes of the file tree). Read through [`data_encryption`], so a file
+/// encrypted at rest goes out in plaintext.
+pub async fn download_tenant_file_handler(
+    path: String,
+    auth: AuthenticatedUser,
+    config: &State<crate::web::types::ServerConfig>,
+    db_config: &State<DatabaseConfig>,
+) -> Result<(ContentType, Vec<u8>), Status> {
+    if file_access(&path) != Some(FileAccess::Downloadable) {
+        app_log!(warn, "Unauthorized file download attempt: {}", path);
+        return Err(Status::Forbidden);
+    }
+
+    let tenant_data_dir = get_tenant_folder_path(&auth.user().email, &config.data_dir);
+
+    // Security: Ensure the file is within tenant directory
+    let Some(file_path) = resolve_tenant_path(&tenant_data_dir, &path) else {
+        app_log!(warn, "Path traversal attempt: {}", path);
+        return Err(Status::Forbidden);
+    };
+
+    let content_type = crate::utils::get_file_extension(&path)
+        .and_then(|ext| ContentType::from_extension(&ext))
+        .unwrap_or(ContentType::Binary);
+    let read_path = file_path.clone();
+    let bytes = tokio::task::spawn_blocking(move || data_encryption::read(&read_path))
+        .await
+        .map_err(|_| Status::InternalServerError)?
+        .map_err(|e| {
+            app_log!(error, "Failed to open file {}: {:#}", file_path.display(), e);
+            Status::NotFound
+        })?;
+    if let Ok(pool) = db_config.pool() {
+        audit_log::record(
+            pool,
+            auth.tenant_id(),
+            auth.email(),
+            AuditAction::DataExported,
+            &path,
+        );
+    }
+    Ok((content_type, bytes))
+}
+
+/// Join a client-supplied relative path onto the tenant directory. Rejects
+/// absolute paths and any `..` / root components, so the result can never
+/// leave `tenant_dir`.
+pub(crate) fn resolve_tenant_path(tenant_dir: &Path, relative: &str) -> Option<PathBuf> {
+    let relative = Path::new(relative);
+    let is_safe = relative.components().next().is_some()
+        && relative
+            .components()
+            .all(|c| matches!(c, Component::Normal(_)));
+    is_safe.then(|| tenant_dir.join(relative))
+}
+
+/// How a client may interact with a file tree node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileAccess {
+    /// Text sources served by `/files/content` and written by `/files/save`.
+    Editable,
+    /// Binary files served by `/files/download` (or `/outputs` for generated PDFs).
+    Downloadable,
+    /// Listed for context only.
+    Readonly,
+}
+
+impl FileAccess {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Editable => "editable",
+            Self::Downloadable => "downloadable",
+            Self::Readonly => "readonly",
+        }
+    }
+}
+
+const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "webp"];
+
+fn file_access(name: &str) -> Option<FileAccess> {
+    let ext = crate::utils::get_file_extension(name)?;
+    if ext == "typ" || ext == "toml" {
+        Some(FileAccess::Editable)
+    } else if ext == "pdf" || IMAGE_EXTENSIONS.contains(&ext.as_str()) {
+        Some(FileAccess::Downloadable)
+    } else {
+        None
+    }
+}
+
+/// Which optional node kinds `/files/tree?include=` asks for.
+/// Accepts a comma list of `images`, `pdfs` and `all` (which also lists
+/// any other file as `readonly`).
+#[derive(Debug, Default)]
+pub struct TreeInclude {
+    images: bool,
+    pdfs: bool,
+    other: bool,
+}
+
+impl TreeInclude {
+    pub fn parse(raw: Option<&str>) -> Self {
+        let mut include = Self::default();
+        for part in raw.unwrap_or_default().split(',') {
+            match part.trim().to_lowercase().as_str() {
+                "images" => include.images = true,
+                "pdfs" | "outputs" => include.pdfs = true,
+                "all" => {
+                    include.images = true;
+                    include.pdfs = true;
+                    include.other = true;
+                }
+                _ => {}
+            }
+        }
+        include
+    }
+
+    fn wants(&self, name: &str) -> Option<FileAccess> {
+        match file_access(name) {
+            Some(FileAccess::Editable) => Some(FileAccess::Editable),
+            Some(_) if name.to_lowercase().ends_with(".pdf") => {
+                self.pdfs.then_some(FileAccess::Downloadable)
+            }
+            Some(_) => self.images.then_some(FileAccess::Downloadable),
+            None => self.other.then_some(FileAccess::Readonly),
+        }
+    }
+}
+
+/// Generated PDFs of one account, stored per person under
+/// [`ServerConfig::person_output_dir`] and named by
+/// [`crate::utils::generated_pdf_filename`] (`{person}_{template}_{lang}.pdf`).
+/// DRAFT-marked files ([`crate::utils::draft_pdf_filename`]) are not listed.
+#[derive(Debug, Default)]
+pub struct GeneratedOutputs {
+    /// `<output_dir>/<tenant>/<user>`; one sub-folder per person.
+    dir: PathBuf,
+    /// `/outputs/...` path of `dir`.
+    url_prefix: String,
+    files: HashMap<String, Vec<(String, std::fs::Metadata)>>,
+    templates: Vec<String>,
+}
+
+impl GeneratedOutputs {
+    /// Templates known to `split_name` are the server's and those the
+    /// account's tenant uploaded.
+    pub async fn scan(config: &ServerConfig, auth: &AuthenticatedUser) -> Self {
+        let email = auth.email();
+        let tenant_templates = tenant_templates::dir(&config.data_dir, email, auth.tenant_id());
+        let templates = crate::core::TemplateEngine::new(config.templates_dir.clone())
+            .map(|engine| engine.with_tenant_templates(&tenant_templates).list_templates())
+            .unwrap_or_default();
+        let dir = get_tenant_output_path(email, &config.output_dir);
+        let mut files: HashMap<String, Vec<(String, std::fs::Metadata)>> = HashMap::new();
+        if let Ok(mut persons) = tokio::fs::read_dir(&dir).await {
+            while let Ok(Some(person)) = persons.next_entry().await {
+                let person_name = person.file_name().to_string_lossy().to_string();
+                let Ok(mut entries) = tokio::fs::read_dir(person.path()).await else {
+                    continue;
+                };
+                while let Ok(Some(entry)) = entries.next_entry().await {
+                    let name = entry.file_name().to_string_lossy().to_string();
+                    if !name.ends_with(".pdf") {
+                        continue;
+                    }
+                    if let Ok(meta) = entry.metadata().await {
+                        files
+                            .entry(person_name.clone())
+                            .or_default()
+                            .push((name, meta));
+                    }
+                }
+            }
+        }
+        Self {
+            url_prefix: config.output_url_path(&dir),
+            dir,
+            files,
+            templates,
+        }
+    }
+
+    /// Split `{person}_{template}_{lang}.pdf` into `(template, lang)`. The
+    /// template must be a known one, which skips renamed ATS exports.
+    fn split_name<'a>(&self, person: &str, name: &'a str) -> Option<(&'a str, &'a str)> {
+        name.strip_prefix(person)
+            .and_then(|rest| rest.strip_prefix('_'))
+            .and_then(|rest| rest.strip_suffix(".pdf"))
+            .and_then(|rest| rest.rsplit_once('_'))
+            .filter(|(template, lang)| {
+                !lang.is_empty() && self.templates.iter().any(|t| t == template)
+            })
+    }
+
+    /// Outputs generated for `person`.
+    fn for_person<'a>(
+        &'a self,
+        person: &'a str,
+    ) -> impl Iterator<Item = &'a (String, std::fs::Metadata)> {
+        self.files
+            .get(person)
+            .into_iter()
+            .flatten()
+            .filter(move |(name, _)| self.split_name(person, name).is_some())
+    }
+
+    /// Most recently written output for `person`, optionally narrowed to a
+    /// template and/or language.
+    pub fn latest_for_person(
+        &self,
+        person: &str,
+        template: Option<&str>,
+        lang: Option<&str>,
+    ) -> Option<&(String, std::fs::Metadata)> {
+        self.for_person(person)
+            .filter(|(name, _)| {
+                self.split_name(person, name).is_some_and(|(t, l)| {
+                    template.is_none_or(|want| want == t) && lang.is_none_or(|want| want == l)
+                })
+            })
+            .max_by_key(|(_, meta)| meta.modified().ok())
+    }
+
+    pub fn path(&self, person: &str, file: &str) -> PathBuf {
+        self.dir.join(person).join(file)
+    }
+
+    pub fn download_url(&self, person: &str, file: &str) -> String {
+        format!("{}/{}/{}", self.url_prefix, person, file)
+    }
+}
+
+fn modified_secs(metadata: &std::fs::Metadata) -> Option<u64> {
+    metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+}
+
+/// `outputs` is only passed for the tenant root, whose sub-folders are persons.
 #[async_recursion]
 async fn build_file_tree(
     dir_path: &std::path::Path,
     has_default_photo: bool,
+    include: &TreeInclude,
+    outputs: Option<&GeneratedOutputs>,
 ) -> Result<HashMap<String, serde_json::Value>, anyhow::Error> {
     use tokio::fs;
     let mut tree = HashMap::new();
@@ -259,9 +699,28 @@ async fn build_file_tree(
     while let Some(entry) = entries.next_entry().await? {
         let path = entry.path();
         let name = entry.file_name().to_string_lossy().to_string();
+        // Dotfiles are internal markers (e.g. the import-review flag).
+        if name.starts_with('.') {
+            continue;
+        }
         let metadata = entry.metadata().await?;
         if metadata.is_dir() {
-            let children = build_file_tree(&path, false).await?;
+            let mut children = build_file_tree(&path, false, include, None).await?;
+            if let Some(outputs) = outputs {
+                for (file, meta) in outputs.for_person(&name) {
+                    children.insert(
+                        file.clone(),
+                        serde_json::json!({
+                            "type": "file",
+                            "access": FileAccess::Downloadable.as_str(),
+                            "generated": true,
+                            "download_url": outputs.download_url(&name, file),
+                            "size": meta.len(),
+                            "modified": modified_secs(meta)
+                        }),
+                    );
+                }
+            }
             let has_own_photo = path.join("profile.png").exists()
                 || path.join("profile.jpg").exists()
                 || path.join("profile.jpeg").exists();
@@ -273,22 +732,35 @@ async fn build_file_tree(
                     "type": "folder",
                     "children": children,
                     "has_photo": has_photo,
-                    "has_own_photo": has_own_photo
+                    "has_own_photo": has_own_photo,
+                    "profile_type": ProfileType::of(&path).as_str()
                 }),
             );
-        } else if name.ends_with(".typ") || name.ends_with(".toml") {
+        } else if let Some(access) = include.wants(&name) {
             tree.insert(
                 name,
                 serde_json::json!({
                     "type": "file",
+                    "access": access.as_str(),
                     "size": metadata.len(),
-                    "modified": metadata.modified().ok()
-                        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
-                        .map(|d| d.as_secs())
+                    "modified": modified_secs(&metadata)
                 }),
             );
         }
     }
+    // JSON objects come out in byte order, which puts `élodie` after `zoe`;
+    // `position` gives folders their reading order.
+    let mut folders: Vec<String> = tree
+        .iter()
+        .filter(|(_, node)| node["type"] == "folder")
+        .map(|(name, _)| name.clone())
+        .collect();
+    folders.sort_by(|a, b| crate::utils::collate_names(a, b));
+    for (position, name) in folders.iter().enumerate() {
+        if let Some(node) = tree.get_mut(name).and_then(|node| node.as_object_mut()) {
+            node.insert("position".to_string(), position.into());
+        }
+    }
     Ok(tree)
 }
 
@@ -299,5 +771,57 @@ pub async fn get_tenant_file_tree(
 ) -> Result<HashMap<String, serde_json::Value>, anyhow::Error> {
     let tenant_path = get_tenant_folder_path(email, tenant_data_path);
     let has_default_photo = tenant_path.join("default_photo.png").exists();
-    build_file_tree(&tenant_path, has_default_photo).await
+    build_file_tree(
+        &tenant_path,
+        has_default_photo,
+        &TreeInclude::default(),
+        None,
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolve_tenant_path_rejects_escapes() {
+        let tenant = Path::new("/data/acme/jane");
+        assert_eq!(
+            resolve_tenant_path(tenant, "john/cv_params.toml"),
+            Some(tenant.join("john/cv_params.toml"))
+        );
+        assert!(resolve_tenant_path(tenant, "../other/cv_params.toml").is_none());
+        assert!(resolve_tenant_path(tenant, "john/../../x.toml").is_none());
+        assert!(resolve_tenant_path(tenant, "/etc/passwd").is_none());
+        assert!(resolve_tenant_path(tenant, "").is_none());
+    }
+
+    #[test]
+    fn tree_include_tags_access() {
+        let default = TreeInclude::parse(None);
+        assert_eq!(default.wants("cv_params.toml"), Some(FileAccess::Editable));
+        assert_eq!(default.wants("profile.png"), None);
+
+        let images = TreeInclude::parse(Some("images"));
+        assert_eq!(images.wants("profile.PNG"), Some(FileAccess::Downloadable));
+        assert_eq!(images.wants("old.pdf"), None);
+
+        let all = TreeInclude::parse(Some("all"));
+        assert_eq!(all.wants("README.md"), Some(FileAccess::Readonly));
+    }
+
+    #[test]
+    fn outputs_of_tenant_templates_are_recognised() {
+        let outputs = GeneratedOutputs {
+            templates: vec!["default".to_string(), "acme_brand".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(
+            outputs.split_name("jane", "jane_acme_brand_en.pdf"),
+            Some(("acme_brand", "en"))
+        );
+        assert_eq!(outputs.split_name("jane", "jane_default_fr.pdf"), Some(("default", "fr")));
+        assert_eq!(outputs.split_name("jane", "jane_other_en.pdf"), None);
+    }
 }
@@ -0,0 +1,302 @@
+// src/web/handlers/asset_handlers.rs
+//! Per-profile images for the named slots templates declare (header logo,
+//! signature, …) and client logos of experiences. See
+//! [`crate::core::image_slots`] and [`crate::core::client_logos`].
+
+use graflog::app_log;
+use rocket::form::Form;
+use rocket::serde::json::Json;
+use rocket::State;
+use std::path::PathBuf;
+
+use crate::auth::AuthenticatedUser;
+use crate::core::database::get_tenant_folder_path;
+use crate::core::template_engine::SharedTemplateEngine;
+use crate::core::{client_logos, image_slots, profile_lock, FsOps};
+use crate::web::handlers::common::error_response;
+use crate::web::handlers::cv_handlers::require_edit_access;
+use crate::web::types::{ActionResponse, AssetUploadForm, DataResponse, StandardErrorResponse};
+use crate::web::ServerConfig;
+
+/// Profile directory of `name`, if the slot is one some template declares
+/// and the user may edit the profile.
+fn slot_target(
+    name: &str,
+    slot: &str,
+    auth: &AuthenticatedUser,
+    config: &ServerConfig,
+    templates: &SharedTemplateEngine,
+) -> Result<PathBuf, Json<StandardErrorResponse>> {
+    if !image_slots::valid_name(slot) || !image_slots::is_known(&templates.current(), slot) {
+        return Err(error_response(
+            format!("No template has an image slot named '{}'", slot),
+            "UNKNOWN_IMAGE_SLOT",
+            vec!["GET /templates lists each template's image_slots".to_string()],
+        ));
+    }
+    editable_profile(name, auth, config)
+}
+
+/// Profile directory of `name`, if the user may edit it.
+fn editable_profile(
+    name: &str,
+    auth: &AuthenticatedUser,
+    config: &ServerConfig,
+) -> Result<PathBuf, Json<StandardErrorResponse>> {
+    let normalized = crate::utils::normalize_profile_name(name);
+    let profile_dir = get_tenant_folder_path(auth.email(), &config.data_dir).join(&normalized);
+    if !profile_dir.is_dir() {
+        return Err(error_response(
+            format!("Profile '{}' not found", name),
+            "NOT_FOUND",
+            vec!["Create the profile first".to_string()],
+        ));
+    }
+    require_edit_access(auth, &profile_dir, None)?;
+    Ok(profile_dir)
+}
+
+/// POST /profiles/<name>/assets/<slot> — store a PNG or JPEG for `slot`.
+pub async fn upload_asset_handler(
+    name: String,
+    slot: String,
+    upload: Form<AssetUploadForm<'_>>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    templates: &State<SharedTemplateEngine>,
+) -> Result<Json<ActionResponse>, Json<StandardErrorResponse>> {
+    crate::web::handlers::system_handlers::ensure_storage_available(config, None)?;
+    let profile_dir = slot_target(&name, &slot, &auth, config, templates)?;
+
+    let Some(file_path) = upload.file.path() else {
+        return Err(error_response(
+            "Invalid uploaded file".to_string(),
+            "UPLOAD_ERROR",
+            vec!["Please try uploading again".to_string()],
+        ));
+    };
+    let bytes = tokio::fs::read(file_path).await.map_err(|e| {
+        app_log!(error, "Failed to read uploaded asset: {}", e);
+        error_response(
+            "Failed to process uploaded file".to_string(),
+            "UPLOAD_ERROR",
+            vec!["Please try uploading again".to_string()],
+        )
+    })?;
+    let png = image_slots::to_png(bytes).await.map_err(|e| {
+        app_log!(
+            warn,
+            "Rejected image for slot '{}' of {}: {}",
+            slot,
+            name,
+            e
+        );
+        error_response(
+            format!("Invalid image: {}", e),
+            "INVALID_IMAGE",
+            vec!["Please upload a valid PNG or JPEG image".to_string()],
+        )
+    })?;
+
+    let asset_path = image_slots::asset_path(&profile_dir, &slot);
+    let _save = profile_lock::write(&profile_dir).await;
+    let saved = async {
+        FsOps::write_bytes_safe(&asset_path, &png).await?;
+        FsOps::validate_image(&asset_path).await
+    };
+    if let Err(e) = saved.await {
+        app_log!(
+            error,
+            "Failed to save image for slot '{}' of {}: {}",
+            slot,
+            name,
+            e
+        );
+        let _ = tokio::fs::remove_file(&asset_path).await;
+        return Err(error_response(
+            "Failed to save image".to_string(),
+            "SAVE_ERROR",
+            vec!["Please try again".to_string()],
+        ));
+    }
+
+    app_log!(
+        info,
+        "{} uploaded image slot '{}' for {}",
+        auth.email(),
+        slot,
+        name
+    );
+    Ok(Json(ActionResponse::success(
+        format!("Image '{}' uploaded for {}", slot, name),
+        "uploaded".to_string(),
+        None,
+    )))
+}
+
+/// DELETE /profiles/<name>/assets/<slot> — remove the image of `slot`.
+pub async fn delete_asset_handler(
+    name: String,
+    slot: String,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    templates: &State<SharedTemplateEngine>,
+) -> Result<Json<ActionResponse>, Json<StandardErrorResponse>> {
+    let profile_dir = slot_target(&name, &slot, &auth, config, templates)?;
+    let asset_path = image_slots::asset_path(&profile_dir, &slot);
+    if !asset_path.exists() {
+        return Err(error_response(
+            format!("No '{}' image for {}", slot, name),
+            "NOT_FOUND",
+            vec![],
+        ));
+    }
+
+    let _save = profile_lock::write(&profile_dir).await;
+    tokio::fs::remove_file(&asset_path).await.map_err(|e| {
+        app_log!(
+            error,
+            "Failed to delete image slot '{}' of {}: {}",
+            slot,
+            name,
+            e
+        );
+        error_response("Failed to delete image".to_string(), "DELETE_ERROR", vec![])
+    })?;
+
+    Ok(Json(ActionResponse::success(
+        format!("Image '{}' removed from {}", slot, name),
+        "deleted".to_string(),
+        None,
+    )))
+}
+
+/// Profile directory of `name` for the client logo `key`.
+fn logo_target(
+    name: &str,
+    key: &str,
+    auth: &AuthenticatedUser,
+    config: &ServerConfig,
+) -> Result<PathBuf, Json<StandardErrorResponse>> {
+    if !client_logos::valid_key(key) {
+        return Err(error_response(
+            format!("Invalid client logo key '{}'", key),
+            "INVALID_LOGO_KEY",
+            vec!["Use lowercase letters, digits and '_', e.g. 'acme'".to_string()],
+        ));
+    }
+    editable_profile(name, auth, config)
+}
+
+/// GET /profiles/<name>/client-logos — keys of the profile's client logos.
+pub async fn list_client_logos_handler(
+    name: String,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+) -> Result<Json<DataResponse<Vec<String>>>, Json<StandardErrorResponse>> {
+    let normalized = crate::utils::normalize_profile_name(&name);
+    let profile_dir = get_tenant_folder_path(auth.email(), &config.data_dir).join(&normalized);
+    if !profile_dir.is_dir() {
+        return Err(error_response(
+            format!("Profile '{}' not found", name),
+            "NOT_FOUND",
+            vec!["Create the profile first".to_string()],
+        ));
+    }
+    let keys = client_logos::list(&profile_dir);
+    Ok(Json(DataResponse::success(
+        format!("{} client logos", keys.len()),
+        keys,
+        None,
+    )))
+}
+
+/// POST /profiles/<name>/client-logos/<key> — store a PNG or JPEG logo,
+/// scaled down to a small PNG.
+pub async fn upload_client_logo_handler(
+    name: String,
+    key: String,
+    upload: Form<AssetUploadForm<'_>>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+) -> Result<Json<ActionResponse>, Json<StandardErrorResponse>> {
+    crate::web::handlers::system_handlers::ensure_storage_available(config, None)?;
+    let profile_dir = logo_target(&name, &key, &auth, config)?;
+
+    let Some(file_path) = upload.file.path() else {
+        return Err(error_response(
+            "Invalid uploaded file".to_string(),
+            "UPLOAD_ERROR",
+            vec!["Please try uploading again".to_string()],
+        ));
+    };
+    let bytes = tokio::fs::read(file_path).await.map_err(|e| {
+        app_log!(error, "Failed to read uploaded client logo: {}", e);
+        error_response(
+            "Failed to process uploaded file".to_string(),
+            "UPLOAD_ERROR",
+            vec!["Please try uploading again".to_string()],
+        )
+    })?;
+    let png = client_logos::to_logo_png(bytes).await.map_err(|e| {
+        app_log!(warn, "Rejected client logo '{}' of {}: {}", key, name, e);
+        error_response(
+            format!("Invalid image: {}", e),
+            "INVALID_IMAGE",
+            vec!["Please upload a PNG or JPEG logo under 2 MB".to_string()],
+        )
+    })?;
+
+    let logo_path = client_logos::logo_path(&profile_dir, &key);
+    let _save = profile_lock::write(&profile_dir).await;
+    let saved = async {
+        FsOps::write_bytes_safe(&logo_path, &png).await?;
+        FsOps::validate_image(&logo_path).await
+    };
+    if let Err(e) = saved.await {
+        app_log!(error, "Failed to save client logo '{}' of {}: {}", key, name, e);
+        let _ = tokio::fs::remove_file(&logo_path).await;
+        return Err(error_response(
+            "Failed to save image".to_string(),
+            "SAVE_ERROR",
+            vec!["Please try again".to_string()],
+        ));
+    }
+
+    app_log!(info, "{} uploaded client logo '{}' for {}", auth.email(), key, name);
+    Ok(Json(ActionResponse::success(
+        format!("Client logo '{}' uploaded for {}", key, name),
+        "uploaded".to_string(),
+        None,
+    )))
+}
+
+/// DELETE /profiles/<name>/client-logos/<key>
+pub async fn delete_client_logo_handler(
+    name: String,
+    key: String,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+) -> Result<Json<ActionResponse>, Json<StandardErrorResponse>> {
+    let profile_dir = logo_target(&name, &key, &auth, config)?;
+    let logo_path = client_logos::logo_path(&profile_dir, &key);
+    if !logo_path.exists() {
+        return Err(error_response(
+            format!("No client logo '{}' for {}", key, name),
+            "NOT_FOUND",
+            vec![],
+        ));
+    }
+
+    let _save = profile_lock::write(&profile_dir).await;
+    tokio::fs::remove_file(&logo_path).await.map_err(|e| {
+        app_log!(error, "Failed to delete client logo '{}' of {}: {}", key, name, e);
+        error_response("Failed to delete image".to_string(), "DELETE_ERROR", vec![])
+    })?;
+
+    Ok(Json(ActionResponse::success(
+        format!("Client logo '{}' removed from {}", key, name),
+        "deleted".to_string(),
+        None,
+    )))
+}
@@ -0,0 +1,96 @@
+// src/web/handlers/audit_handlers.rs
+//! Signed tenant activity report for customers' compliance teams.
+
+use graflog::app_log;
+use rocket::serde::json::Json;
+use rocket::State;
+
+use crate::auth::AuthenticatedUser;
+use crate::core::audit_log::{self, AuditReport};
+use crate::core::database::DatabaseConfig;
+use crate::web::handlers::common::{db_error, error_response, is_admin, pool, tenant_by_address};
+use crate::web::handlers::usage_handlers::date_range;
+use crate::web::types::{PdfResponse, StandardErrorResponse};
+use crate::web::ServerConfig;
+
+/// GET /audit/report — PDF of the caller's tenant activity (generations,
+/// exports, deletions) between `from` and `to`. Editors only; the admin may
+/// pass `tenant` (a member's email or the domain) to report on any tenant.
+/// Reports are always signed with the server key, so tenants cannot forge
+/// their own.
+pub async fn audit_report_handler(
+    from: Option<String>,
+    to: Option<String>,
+    tenant: Option<String>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    db_config: &State<DatabaseConfig>,
+) -> Result<PdfResponse, Json<StandardErrorResponse>> {
+    let admin = is_admin(&auth);
+    if !admin && !auth.role().can_create() {
+        return Err(error_response(
+            "Activity reports require the editor role",
+            "EDITOR_ROLE_REQUIRED",
+            vec!["Ask your account administrator for the editor role".to_string()],
+        ));
+    }
+    let (from, to) = date_range(from.as_deref(), to.as_deref())?;
+
+    let pool = pool(db_config)?;
+    let tenant = match tenant {
+        Some(address) if admin => tenant_by_address(pool, &address).await?,
+        _ => auth.tenant().clone(),
+    };
+    let Some(key) = audit_log::signing_key() else {
+        return Err(error_response(
+            "Activity reports are not enabled on this server",
+            "REPORTS_DISABLED",
+            vec!["Set AUDIT_SIGNING_KEY to enable signed reports".to_string()],
+        ));
+    };
+    let entries = audit_log::entries(pool, tenant.id, from, to)
+        .await
+        .map_err(db_error)?;
+
+    let report = AuditReport::new(&tenant.tenant_name, from, to, auth.email(), entries)
+        .sign(&key)
+        .map_err(|e| {
+            error_response(
+                &format!("Failed to sign report: {}", e),
+                "REPORT_ERROR",
+                vec![],
+            )
+        })?;
+    let pdf = audit_log::render_pdf(&config.templates_dir, &report)
+        .await
+        .map_err(|e| {
+            app_log!(
+                error,
+                "Failed to render activity report for {}: {:#}",
+                tenant.tenant_name,
+                e
+            );
+            error_response(
+                "Failed to render the activity report",
+                "REPORT_ERROR",
+                vec!["Try again or contact support".to_string()],
+            )
+        })?;
+
+    app_log!(
+        info,
+        "Activity report {} for {} ({} entries) requested by {}",
+        report.report_id,
+        tenant.tenant_name,
+        report.entries.len(),
+        auth.email()
+    );
+    let signature = audit_log::sign(&key, &pdf);
+    let filename = format!(
+        "activity-{}-{}-{}.pdf",
+        tenant.tenant_name,
+        from.format("%Y%m%d"),
+        to.format("%Y%m%d")
+    );
+    Ok(PdfResponse::with_filename(pdf, filename).with_signature(signature))
+}
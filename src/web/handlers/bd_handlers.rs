@@ -9,6 +9,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::auth::AuthenticatedUser;
 use crate::core::database::DatabaseConfig;
+use crate::web::handlers::common::admin_only;
 use crate::web::types::StandardErrorResponse;
 
 // ── Helpers ───────────────────────────────────────────────────────────────────
@@ -298,6 +299,7 @@ pub async fn attach_ref_handler(
     .execute(pool)
     .await
     .map_err(|e| pool_err(e))?;
+    crate::core::tenant_cache::invalidate_all();
 
     app_log!(
         info,
@@ -311,16 +313,6 @@ pub async fn attach_ref_handler(
 
 // ── Admin endpoints ───────────────────────────────────────────────────────────
 
-const ADMIN_EMAIL: &str = "mohamed.bennekrouf@gmail.com";
-
-fn admin_only(auth: &AuthenticatedUser) -> Result<(), Json<StandardErrorResponse>> {
-    if auth.email().to_lowercase() != ADMIN_EMAIL {
-        Err(make_error("Admin access required", "FORBIDDEN"))
-    } else {
-        Ok(())
-    }
-}
-
 #[derive(Serialize)]
 #[serde(crate = "rocket::serde")]
 pub struct AdminBdRow {
@@ -0,0 +1,333 @@
+// src/web/handlers/bench_handlers.rs
+//! Job-to-bench matching: rank every profile of the tenant against one job.
+//!
+//! The inverse of `/analyze-job-fit`. Each profile is matched by the
+//! cv-import service with bounded concurrency; results are cached per
+//! job + CV content (see `core::job_match_cache`) and only cache misses are
+//! charged.
+
+use futures::stream::{self, StreamExt};
+use graflog::app_log;
+use rocket::serde::json::Json;
+use rocket::State;
+use serde::{Deserialize, Serialize};
+
+use crate::auth::AuthenticatedUser;
+use crate::core::ai_budget::AiFeature;
+use crate::core::database::{get_tenant_folder_path, DatabaseConfig};
+use crate::core::{data_residency, job_match_cache, FsOps, ServiceClient};
+use crate::types::cv_data::CvJson;
+use crate::types::response::JobMatchResponse;
+use crate::utils::normalize_profile_name;
+use crate::web::handlers::cv_handlers::load_profile_cv_data;
+use crate::web::handlers::payment_handlers::{check_and_deduct_credits, refund_credits};
+use crate::web::handlers::usage_handlers::{check_ai_budget, usage_recorder};
+use crate::web::types::{DataResponse, ServerConfig, StandardErrorResponse, StandardRequest};
+
+/// Credits per profile that is not already cached.
+const COST_PER_MATCH: i64 = 2;
+const DEFAULT_CONCURRENCY: usize = 4;
+const DEFAULT_MAX_PROFILES: usize = 50;
+
+fn concurrency() -> usize {
+    std::env::var("BENCH_MATCH_CONCURRENCY")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .filter(|n: &usize| *n > 0)
+        .unwrap_or(DEFAULT_CONCURRENCY)
+}
+
+fn max_profiles() -> usize {
+    std::env::var("BENCH_MATCH_MAX_PROFILES")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .filter(|n: &usize| *n > 0)
+        .unwrap_or(DEFAULT_MAX_PROFILES)
+}
+
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct BenchMatchRequest {
+    #[serde(default)]
+    pub job_url: Option<String>,
+    /// Pasted job text, for postings behind a login.
+    #[serde(default)]
+    pub job_description: Option<String>,
+    /// Restrict to these profiles; all profiles when absent.
+    #[serde(default)]
+    pub profiles: Option<Vec<String>>,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct BenchMatchEntry {
+    pub profile: String,
+    pub name: String,
+    pub score: Option<f64>,
+    pub analysis: String,
+    pub recommendations: Vec<String>,
+    pub cached: bool,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct BenchMatchFailure {
+    pub profile: String,
+    pub error: String,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct BenchMatchResult {
+    /// Best score first; unscored results last.
+    pub ranked: Vec<BenchMatchEntry>,
+    pub failed: Vec<BenchMatchFailure>,
+    /// Net of the refund for failed matches.
+    pub credits_charged: i64,
+}
+
+struct Candidate {
+    profile: String,
+    cv_data: CvJson,
+    cache_key: String,
+    cached: Option<JobMatchResponse>,
+}
+
+/// POST /jobs/bench-match
+pub async fn bench_match_handler(
+    request: Json<StandardRequest<BenchMatchRequest>>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    cv_service_url: &State<String>,
+    db_config: &State<DatabaseConfig>,
+) -> Result<Json<DataResponse<BenchMatchResult>>, Json<StandardErrorResponse>> {
+    let user = auth.user();
+    let tenant = auth.tenant();
+    let conversation_id = request.conversation_id.clone();
+
+    let job_url = request
+        .data
+        .job_url
+        .as_deref()
+        .map(str::trim)
+        .unwrap_or("")
+        .to_string();
+    let job_description = request
+        .data
+        .job_description
+        .as_deref()
+        .map(str::trim)
+        .filter(|d| !d.is_empty())
+        .map(str::to_string);
+
+    if job_url.is_empty() && job_description.is_none() {
+        return Err(Json(StandardErrorResponse::new(
+            "Provide a job_url or a job_description".to_string(),
+            "INVALID_INPUT".to_string(),
+            vec!["Paste the job posting text if the URL is not public".to_string()],
+            conversation_id,
+        )));
+    }
+
+    let tenant_data_dir = get_tenant_folder_path(&user.email, &config.data_dir);
+    // Match results embed CV content: cache them in the tenant's region.
+    let cache_root = data_residency::data_root_of(&config.data_dir, &tenant_data_dir);
+    let available = FsOps::list_profiles(&tenant_data_dir)
+        .await
+        .unwrap_or_default();
+    let profiles: Vec<String> = match &request.data.profiles {
+        Some(selected) => {
+            let mut picked: Vec<String> = Vec::new();
+            for profile in selected.iter().map(|p| normalize_profile_name(p)) {
+                if available.contains(&profile) && !picked.contains(&profile) {
+                    picked.push(profile);
+                }
+            }
+            picked
+        }
+        None => available,
+    };
+
+    if profiles.is_empty() {
+        return Err(Json(StandardErrorResponse::new(
+            "No profiles to match".to_string(),
+            "PROFILE_NOT_FOUND".to_string(),
+            vec!["Create or import profiles first".to_string()],
+            conversation_id,
+        )));
+    }
+    if profiles.len() > max_profiles() {
+        return Err(Json(StandardErrorResponse::new(
+            format!(
+                "Too many profiles ({}); the limit is {} per request",
+                profiles.len(),
+                max_profiles()
+            ),
+            "TOO_MANY_PROFILES".to_string(),
+            vec!["Pass a selection in `profiles`".to_string()],
+            conversation_id,
+        )));
+    }
+
+    let job_key = job_match_cache::job_key(&job_url, job_description.as_deref());
+    let mut candidates = Vec::new();
+    let mut failed = Vec::new();
+    for profile in profiles {
+        match load_profile_cv_data(&profile, &tenant_data_dir).await {
+            Ok(cv_data) => {
+                let cache_key = match job_match_cache::cache_key(&job_key, &cv_data) {
+                    Ok(key) => key,
+                    Err(e) => {
+                        failed.push(BenchMatchFailure {
+                            profile,
+                            error: e.to_string(),
+                        });
+                        continue;
+                    }
+                };
+                let cached = job_match_cache::get(&cache_root, &cache_key).await;
+                candidates.push(Candidate {
+                    profile,
+                    cv_data,
+                    cache_key,
+                    cached,
+                });
+            }
+            Err(e) => failed.push(BenchMatchFailure {
+                profile,
+                error: e.to_string(),
+            }),
+        }
+    }
+
+    // Profiles that failed before matching; they were never charged.
+    let unreadable = failed.len() as i64;
+    let misses = candidates.iter().filter(|c| c.cached.is_none()).count() as i64;
+    let mut credits_charged = misses * COST_PER_MATCH;
+    if credits_charged > 0 {
+        check_ai_budget(db_config, &auth, AiFeature::Analysis, conversation_id.clone()).await?;
+        check_and_deduct_credits(
+            &user.email,
+            credits_charged,
+            conversation_id.clone(),
+            "bench_match",
+        )
+        .await?;
+    }
+
+    app_log!(
+        info,
+        "User {} (tenant: {}) bench-matching {} profiles ({} cached) against job {}",
+        user.email,
+        tenant.tenant_name,
+        candidates.len(),
+        candidates.len() as i64 - misses,
+        job_key
+    );
+
+    let service_client = match ServiceClient::new(cv_service_url.inner().clone(), 400) {
+        Ok(client) => client.with_usage(usage_recorder(db_config, &auth)),
+        Err(e) => {
+            app_log!(error, "Failed to initialize service client: {}", e);
+            if credits_charged > 0 {
+                refund_credits(&user.email, credits_charged, "bench_match");
+            }
+            return Err(Json(StandardErrorResponse::new(
+                "Service configuration error".to_string(),
+                "SERVICE_CONFIG_ERROR".to_string(),
+                vec!["Contact system administrator".to_string()],
+                conversation_id,
+            )));
+        }
+    };
+
+    let data_dir = cache_root.as_path();
+    let client = &service_client;
+    let job_url = job_url.as_str();
+    let job_description = job_description.as_deref();
+    let results: Vec<(Candidate, Result<(JobMatchResponse, bool), String>)> =
+        stream::iter(candidates)
+            .map(|candidate| async move {
+                if let Some(hit) = candidate.cached.clone() {
+                    return (candidate, Ok((hit, true)));
+                }
+                let result = client
+                    .match_job_with_description(&candidate.cv_data, job_url, job_description)
+                    .await;
+                match result {
+                    Ok(response) => {
+                        if let Err(e) =
+                            job_match_cache::put(data_dir, &candidate.cache_key, &response).await
+                        {
+                            app_log!(warn, "Failed to cache job match: {}", e);
+                        }
+                        (candidate, Ok((response, false)))
+                    }
+                    Err(e) => (candidate, Err(e.to_string())),
+                }
+            })
+            .buffer_unordered(concurrency())
+            .collect()
+            .await;
+
+    let mut ranked = Vec::new();
+    for (candidate, result) in results {
+        match result {
+            Ok((response, cached)) => ranked.push(BenchMatchEntry {
+                name: candidate.cv_data.personal_info.name.clone(),
+                profile: candidate.profile,
+                score: response.score,
+                analysis: response.analysis,
+                recommendations: response.recommendations.unwrap_or_default(),
+                cached,
+            }),
+            Err(error) => {
+                app_log!(
+                    warn,
+                    "Bench match failed for {}: {}",
+                    candidate.profile,
+                    error
+                );
+                failed.push(BenchMatchFailure {
+                    profile: candidate.profile,
+                    error,
+                });
+            }
+        }
+    }
+    // Only matches the service answered are paid for; cached ones never were.
+    let unanswered = failed.len() as i64 - unreadable;
+    if unanswered > 0 {
+        refund_credits(&user.email, unanswered * COST_PER_MATCH, "bench_match");
+        credits_charged -= unanswered * COST_PER_MATCH;
+    }
+    ranked.sort_by(|a, b| match (a.score, b.score) {
+        (Some(x), Some(y)) => y.total_cmp(&x),
+        (Some(_), None) => std::cmp::Ordering::Less,
+        (None, Some(_)) => std::cmp::Ordering::Greater,
+        (None, None) => a.profile.cmp(&b.profile),
+    });
+
+    crate::core::event_export::emit(
+        crate::core::event_export::EventKind::JobAnalyzed,
+        &tenant.tenant_name,
+        &user.email,
+        serde_json::json!({
+            "mode": "bench",
+            "job_url": job_url,
+            "profiles": ranked.len(),
+            "failed": failed.len(),
+            "top_score": ranked.first().and_then(|e| e.score),
+        }),
+    );
+
+    Ok(Json(DataResponse::success(
+        format!("Ranked {} profiles ({} failed)", ranked.len(), failed.len()),
+        BenchMatchResult {
+            ranked,
+            failed,
+            credits_charged,
+        },
+        conversation_id,
+    )))
+}
@@ -121,6 +121,8 @@ pub async fn upload_brand_logo_handler(
     auth: AuthenticatedUser,
     config: &State<ServerConfig>,
 ) -> Result<Json<serde_json::Value>, Json<StandardErrorResponse>> {
+    crate::web::handlers::system_handlers::ensure_storage_available(config, None)?;
+
     let dir = tenant_dir(&auth, config);
 
     // The brand must exist before a logo can be attached.
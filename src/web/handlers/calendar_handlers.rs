@@ -0,0 +1,89 @@
+// src/web/handlers/calendar_handlers.rs
+//! iCal feed of profile refresh reminders and pending import reviews.
+
+use graflog::app_log;
+use rocket::http::{ContentType, Status};
+use rocket::serde::json::Json;
+use rocket::State;
+use serde::Serialize;
+
+use crate::auth::AuthenticatedUser;
+use crate::core::database::{get_tenant_folder_path, DatabaseConfig};
+use crate::core::review_calendar;
+use crate::web::handlers::common::db_error;
+use crate::web::proxy::ProxyInfo;
+use crate::web::types::{DataResponse, ServerConfig, StandardErrorResponse};
+
+#[derive(Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct CalendarFeed {
+    /// Subscribe URL (`https://…/calendar/<token>/tasks.ics`).
+    pub url: String,
+    /// Same feed with the `webcal://` scheme, for one-click subscribe links.
+    pub webcal_url: String,
+    pub refresh_interval_days: i64,
+}
+
+// ── GET /calendar/feed ────────────────────────────────────────────────────────
+
+/// The caller's private calendar subscription URL, created on first use.
+pub async fn get_calendar_feed_handler(
+    auth: AuthenticatedUser,
+    db_config: &State<DatabaseConfig>,
+    proxy: ProxyInfo,
+) -> Result<Json<DataResponse<CalendarFeed>>, Json<StandardErrorResponse>> {
+    let pool = db_config.pool().map_err(db_error)?;
+    let token = review_calendar::get_or_create_feed_token(pool, auth.email())
+        .await
+        .map_err(db_error)?;
+
+    let url = format!("{}/calendar/{}/tasks.ics", proxy.public_base_url(), token);
+    let webcal_url = match url.split_once("://") {
+        Some((_, rest)) => format!("webcal://{}", rest),
+        None => url.clone(),
+    };
+
+    Ok(Json(DataResponse::success(
+        "Subscribe to this URL in Outlook or Google Calendar".to_string(),
+        CalendarFeed {
+            url,
+            webcal_url,
+            refresh_interval_days: review_calendar::refresh_interval_days(),
+        },
+        None,
+    )))
+}
+
+// ── GET /calendar/<token>/tasks.ics ───────────────────────────────────────────
+
+/// Public (token-authenticated) feed. Unknown tokens are a plain 404.
+pub async fn calendar_ics_handler(
+    token: String,
+    config: &State<ServerConfig>,
+    db_config: &State<DatabaseConfig>,
+) -> Result<(ContentType, String), Status> {
+    let pool = db_config.pool().map_err(|_| Status::ServiceUnavailable)?;
+    let user_email = review_calendar::find_user_by_feed_token(pool, &token)
+        .await
+        .map_err(|e| {
+            app_log!(error, "Calendar feed lookup failed: {}", e);
+            Status::InternalServerError
+        })?
+        .ok_or(Status::NotFound)?;
+
+    let tenant_dir = get_tenant_folder_path(&user_email, &config.data_dir);
+    let tasks = review_calendar::collect_tasks(&tenant_dir)
+        .await
+        .map_err(|e| {
+            app_log!(
+                error,
+                "Failed to collect calendar tasks for {}: {}",
+                user_email,
+                e
+            );
+            Status::InternalServerError
+        })?;
+
+    let ics = review_calendar::render_ics(&tasks, &user_email, chrono::Utc::now());
+    Ok((ContentType::new("text", "calendar"), ics))
+}
@@ -0,0 +1,163 @@
+// src/web/handlers/catalog_handlers.rs
+//! Remote template registry: browse the curated catalog and install from it
+//! (admin only — installs change the templates every tenant sees), and the
+//! inventory the server has loaded.
+
+use graflog::app_log;
+use rocket::serde::json::Json;
+use rocket::State;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::auth::AuthenticatedUser;
+use crate::core::template_engine::SharedTemplateEngine;
+use crate::core::template_registry::{self, CatalogEntry, Registry};
+use crate::core::asset_sync::{self, SyncStatus};
+use crate::core::{template_health, template_versions};
+use crate::web::handlers::common::admin_only;
+use crate::web::types::{ActionResponse, DataResponse, StandardErrorResponse};
+use crate::web::ServerConfig;
+
+fn registry() -> Result<Registry, Json<StandardErrorResponse>> {
+    Registry::from_env().ok_or_else(|| {
+        Json(StandardErrorResponse::new(
+            "No template registry is configured".to_string(),
+            "REGISTRY_DISABLED".to_string(),
+            vec!["Set TEMPLATE_REGISTRY_URL and TEMPLATE_REGISTRY_KEY".to_string()],
+            None,
+        ))
+    })
+}
+
+fn registry_error(e: anyhow::Error) -> Json<StandardErrorResponse> {
+    Json(StandardErrorResponse::new(
+        format!("{:#}", e),
+        "REGISTRY_ERROR".to_string(),
+        vec![],
+        None,
+    ))
+}
+
+#[derive(Debug, Serialize)]
+pub struct CatalogItem {
+    #[serde(flatten)]
+    pub entry: CatalogEntry,
+    /// Version installed from the registry, if any.
+    pub installed_version: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LoadedTemplate {
+    pub id: String,
+    pub name: String,
+    pub path: String,
+    pub version: String,
+    /// Archived versions a profile can be pinned to.
+    pub versions: Vec<String>,
+    /// Version installed from the registry, if any.
+    pub registry_version: Option<String>,
+    pub healthy: bool,
+    /// Last compile check error of an unhealthy template.
+    pub health_error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TemplateState {
+    pub templates_dir: String,
+    pub discovered_at: DateTime<Utc>,
+    pub templates: Vec<LoadedTemplate>,
+    /// Last sync with the other instances, when asset sync is on.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub asset_sync: Option<SyncStatus>,
+}
+
+/// GET /templates/catalog
+pub async fn catalog_handler(
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+) -> Result<Json<DataResponse<Vec<CatalogItem>>>, Json<StandardErrorResponse>> {
+    admin_only(&auth)?;
+    let catalog = registry()?.catalog().await.map_err(registry_error)?;
+    let items: Vec<CatalogItem> = catalog
+        .into_iter()
+        .map(|entry| CatalogItem {
+            installed_version: template_registry::installed(&config.templates_dir, &entry.id)
+                .map(|installed| installed.version.unwrap_or_default()),
+            entry,
+        })
+        .collect();
+
+    Ok(Json(DataResponse::success(
+        format!("{} templates in the registry", items.len()),
+        items,
+        None,
+    )))
+}
+
+/// POST /templates/install/<id>
+pub async fn install_template_handler(
+    id: String,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    templates: &State<SharedTemplateEngine>,
+) -> Result<Json<ActionResponse>, Json<StandardErrorResponse>> {
+    admin_only(&auth)?;
+    let entry = registry()?
+        .install(&id, &config.templates_dir)
+        .await
+        .map_err(|e| {
+            app_log!(error, "[templates] Installing '{}' failed: {:#}", id, e);
+            registry_error(e)
+        })?;
+    if let Err(e) = templates.reload() {
+        app_log!(warn, "[templates] Reload after installing '{}' failed: {}", id, e);
+    }
+    asset_sync::request_sync();
+
+    Ok(Json(ActionResponse::success(
+        format!("Template '{}' installed from the registry", entry.name),
+        "TEMPLATE_INSTALLED".to_string(),
+        None,
+    )))
+}
+
+/// GET /admin/templates/state — the templates shared by request handlers,
+/// as last discovered.
+pub async fn template_state_handler(
+    auth: AuthenticatedUser,
+    templates: &State<SharedTemplateEngine>,
+) -> Result<Json<DataResponse<TemplateState>>, Json<StandardErrorResponse>> {
+    admin_only(&auth)?;
+    let engine = templates.current();
+    let mut unhealthy = template_health::unhealthy();
+    let loaded: Vec<LoadedTemplate> = engine
+        .list_templates()
+        .into_iter()
+        .filter_map(|id| engine.get_template(&id).cloned())
+        .map(|info| {
+            let health_error = unhealthy.remove(&info.id);
+            LoadedTemplate {
+                name: info.manifest.name.clone(),
+                path: info.path.display().to_string(),
+                version: template_versions::current_version(&info),
+                versions: template_versions::versions(&info),
+                registry_version: template_registry::installed(engine.templates_dir(), &info.id)
+                    .and_then(|installed| installed.version),
+                healthy: health_error.is_none(),
+                health_error,
+                id: info.id,
+            }
+        })
+        .collect();
+
+    Ok(Json(DataResponse::success(
+        format!("{} templates loaded", loaded.len()),
+        TemplateState {
+            templates_dir: engine.templates_dir().display().to_string(),
+            discovered_at: engine.discovered_at(),
+            templates: loaded,
+            asset_sync: asset_sync::status(),
+        },
+        None,
+    )))
+}
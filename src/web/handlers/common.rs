@@ -0,0 +1,91 @@
+// src/web/handlers/common.rs
+//! What handler modules share: the admin check, error responses and the
+//! database pool.
+
+use graflog::app_log;
+use rocket::serde::json::Json;
+use sqlx::SqlitePool;
+
+use crate::auth::AuthenticatedUser;
+use crate::core::database::{DatabaseConfig, Tenant, TenantRepository};
+use crate::web::types::StandardErrorResponse;
+
+/// The operator's account; the only one allowed on `/admin/*`.
+pub const ADMIN_EMAIL: &str = "mohamed.bennekrouf@gmail.com";
+
+pub fn is_admin(auth: &AuthenticatedUser) -> bool {
+    auth.email().to_lowercase() == ADMIN_EMAIL
+}
+
+/// `FORBIDDEN` unless the caller is the admin.
+pub fn admin_only(auth: &AuthenticatedUser) -> Result<(), Json<StandardErrorResponse>> {
+    if is_admin(auth) {
+        Ok(())
+    } else {
+        Err(error_response("Admin access required", "FORBIDDEN", vec![]))
+    }
+}
+
+/// An error outside any conversation.
+pub fn error_response(
+    message: impl Into<String>,
+    code: &str,
+    suggestions: Vec<String>,
+) -> Json<StandardErrorResponse> {
+    Json(StandardErrorResponse::new(
+        message.into(),
+        code.to_string(),
+        suggestions,
+        None,
+    ))
+}
+
+/// `DB_ERROR` for a failed query; the cause is logged, not returned.
+pub fn db_error(e: impl std::fmt::Display) -> Json<StandardErrorResponse> {
+    app_log!(error, "Database error: {}", e);
+    error_response(
+        "Database error",
+        "DB_ERROR",
+        vec!["Try again in a few moments".to_string()],
+    )
+}
+
+/// `FEATURE_DISABLED` unless `enabled`; `feature` is the name turned off in
+/// `CVENOM_DISABLED_FEATURES`.
+pub fn require_feature(
+    enabled: bool,
+    feature: &str,
+    conversation_id: Option<String>,
+) -> Result<(), Json<StandardErrorResponse>> {
+    if enabled {
+        return Ok(());
+    }
+    Err(Json(StandardErrorResponse::new(
+        format!("'{}' is turned off on this deployment", feature),
+        "FEATURE_DISABLED".to_string(),
+        vec!["GET /capabilities lists what this deployment offers".to_string()],
+        conversation_id,
+    )))
+}
+
+pub fn pool(db_config: &DatabaseConfig) -> Result<&SqlitePool, Json<StandardErrorResponse>> {
+    db_config.pool().map_err(db_error)
+}
+
+/// The tenant an admin request names by email address or domain.
+pub async fn tenant_by_address(
+    pool: &SqlitePool,
+    address: &str,
+) -> Result<Tenant, Json<StandardErrorResponse>> {
+    TenantRepository::new(pool)
+        .find_by_address(address)
+        .await
+        .map_err(db_error)?
+        .ok_or_else(|| {
+            error_response(
+                format!("No active tenant for '{}'", address.trim()),
+                "TENANT_NOT_FOUND",
+                vec!["Give a member's email address or the tenant's domain".to_string()],
+            )
+        })
+}
@@ -0,0 +1,80 @@
+// src/web/handlers/conversation_handlers.rs
+//! Conversation history for restoring chat context.
+
+use rocket::serde::json::Json;
+use rocket::State;
+
+use crate::auth::AuthenticatedUser;
+use crate::core::conversation_store::{self, ConversationInfo, ConversationTurn};
+use crate::core::database::DatabaseConfig;
+use crate::web::handlers::common::db_error;
+use crate::web::types::{ActionResponse, DataResponse, StandardErrorResponse};
+
+const DEFAULT_LIMIT: i64 = 100;
+const MAX_LIMIT: i64 = 500;
+
+/// GET /conversations — the caller's conversations, most recent first.
+pub async fn list_conversations_handler(
+    limit: Option<i64>,
+    auth: AuthenticatedUser,
+    db_config: &State<DatabaseConfig>,
+) -> Result<Json<DataResponse<Vec<ConversationInfo>>>, Json<StandardErrorResponse>> {
+    let pool = db_config.pool().map_err(db_error)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+    let conversations = conversation_store::list(pool, auth.email(), limit)
+        .await
+        .map_err(db_error)?;
+
+    Ok(Json(DataResponse::success(
+        format!("{} conversations", conversations.len()),
+        conversations,
+        None,
+    )))
+}
+
+/// GET /conversations/<id> — turns of one conversation, oldest first.
+pub async fn get_conversation_handler(
+    conversation_id: String,
+    limit: Option<i64>,
+    auth: AuthenticatedUser,
+    db_config: &State<DatabaseConfig>,
+) -> Result<Json<DataResponse<Vec<ConversationTurn>>>, Json<StandardErrorResponse>> {
+    let pool = db_config.pool().map_err(db_error)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+    let turns = conversation_store::history(pool, auth.email(), &conversation_id, limit)
+        .await
+        .map_err(db_error)?;
+
+    if turns.is_empty() {
+        return Err(Json(StandardErrorResponse::new(
+            format!("Conversation '{}' not found", conversation_id),
+            "CONVERSATION_NOT_FOUND".to_string(),
+            vec!["Conversations are recorded once a request carries a conversation_id".to_string()],
+            Some(conversation_id),
+        )));
+    }
+
+    Ok(Json(DataResponse::success(
+        format!("{} turns", turns.len()),
+        turns,
+        Some(conversation_id),
+    )))
+}
+
+/// DELETE /conversations/<id>
+pub async fn delete_conversation_handler(
+    conversation_id: String,
+    auth: AuthenticatedUser,
+    db_config: &State<DatabaseConfig>,
+) -> Result<Json<ActionResponse>, Json<StandardErrorResponse>> {
+    let pool = db_config.pool().map_err(db_error)?;
+    let deleted = conversation_store::delete(pool, auth.email(), &conversation_id)
+        .await
+        .map_err(db_error)?;
+
+    Ok(Json(ActionResponse::success(
+        format!("Deleted {} turns", deleted),
+        "deleted".to_string(),
+        None,
+    )))
+}
@@ -0,0 +1,184 @@
+// src/web/handlers/cv_handlers/approval.rs
+//! Review and approval of a profile; see [`crate::core::profile_approval`].
+use crate::auth::AuthenticatedUser;
+use crate::core::config_manager;
+use crate::core::database::{get_tenant_folder_path, DatabaseConfig, TenantRepository};
+use crate::core::owner;
+use crate::core::profile_access::MemberRole;
+use crate::core::profile_approval::{self, ApprovalAction, PendingReview, ProfileApproval};
+use crate::utils::normalize_profile_name;
+use crate::web::handlers::common::{db_error, error_response, is_admin, pool, require_feature};
+use crate::web::types::{ApprovalRequest, DataResponse, ServerConfig, StandardErrorResponse};
+use graflog::app_log;
+use rocket::serde::json::Json;
+use rocket::State;
+use std::path::PathBuf;
+
+use super::helpers::require_edit_access;
+
+fn editor_role_required() -> Json<StandardErrorResponse> {
+    error_response(
+        "Deciding reviews requires the editor role".to_string(),
+        "EDITOR_ROLE_REQUIRED",
+        vec!["Ask your account administrator for the editor role".to_string()],
+    )
+}
+
+/// Editors review their tenant's profiles; the admin reviews any.
+fn is_reviewer(auth: &AuthenticatedUser) -> bool {
+    auth.role() == MemberRole::Editor || is_admin(auth)
+}
+
+/// The profile `name` of `owner`, the caller when `None`. Another member's
+/// profile is only reachable by a reviewer of the same tenant, or the admin.
+async fn profile_dir(
+    name: &str,
+    owner: Option<&str>,
+    auth: &AuthenticatedUser,
+    config: &ServerConfig,
+    db_config: &DatabaseConfig,
+) -> Result<PathBuf, Json<StandardErrorResponse>> {
+    let not_found = || {
+        error_response(
+            format!("Profile '{}' not found", name),
+            "PROFILE_NOT_FOUND",
+            vec!["Check the profile name spelling and its owner".to_string()],
+        )
+    };
+    let owner = owner
+        .map(str::trim)
+        .filter(|o| !o.is_empty() && !o.eq_ignore_ascii_case(auth.email()));
+    let account = match owner {
+        None => auth.email().to_string(),
+        Some(owner) => {
+            // Reviewing another member's profile is sharing.
+            require_feature(config_manager::features().sharing, "sharing", None)?;
+            let tenant = TenantRepository::new(pool(db_config)?)
+                .find_by_email_or_domain(owner)
+                .await
+                .map_err(db_error)?;
+            let same_tenant = tenant.is_some_and(|t| t.id == auth.tenant_id());
+            if !is_reviewer(auth) || !(same_tenant || is_admin(auth)) {
+                return Err(not_found());
+            }
+            owner.to_lowercase()
+        }
+    };
+    let profile = normalize_profile_name(name);
+    let profile_dir = get_tenant_folder_path(&account, &config.data_dir).join(&profile);
+    if profile.is_empty() || !profile_dir.is_dir() {
+        return Err(not_found());
+    }
+    Ok(profile_dir)
+}
+
+/// GET /profiles/<name>/approval?<owner>
+pub async fn approval_handler(
+    name: String,
+    owner: Option<String>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    db_config: &State<DatabaseConfig>,
+) -> Result<Json<DataResponse<ProfileApproval>>, Json<StandardErrorResponse>> {
+    let profile_dir = profile_dir(&name, owner.as_deref(), &auth, config, db_config).await?;
+    let data = profile_approval::load(&profile_dir);
+    Ok(Json(DataResponse::success(
+        format!("'{}' is {}", name, data.state.as_str()),
+        data,
+        None,
+    )))
+}
+
+/// POST /profiles/<name>/approval/{request-review,approve,reject}?<owner>
+pub async fn approval_action_handler(
+    name: String,
+    owner: Option<String>,
+    action: ApprovalAction,
+    request: Option<Json<ApprovalRequest>>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    db_config: &State<DatabaseConfig>,
+) -> Result<Json<DataResponse<ProfileApproval>>, Json<StandardErrorResponse>> {
+    let own = owner
+        .as_deref()
+        .map(str::trim)
+        .is_none_or(|o| o.is_empty() || o.eq_ignore_ascii_case(auth.email()));
+    if action == ApprovalAction::RequestReview && !own {
+        return Err(error_response(
+            "Only the profile's owner can request its review".to_string(),
+            "FORBIDDEN",
+            vec![],
+        ));
+    }
+    let profile_dir = profile_dir(&name, owner.as_deref(), &auth, config, db_config).await?;
+    if action == ApprovalAction::RequestReview {
+        require_edit_access(&auth, &profile_dir, None)?;
+    } else if !is_reviewer(&auth) {
+        return Err(editor_role_required());
+    }
+
+    // Nobody else can reach the profiles of an individual account.
+    let self_review = own && auth.tenant().email.is_some();
+    let comment = request.and_then(|r| r.into_inner().comment);
+    let data = profile_approval::apply(&profile_dir, action, auth.email(), comment, self_review)
+        .await
+        .map_err(|e| {
+            app_log!(warn, "Approval of {} by {} refused: {}", name, auth.email(), e);
+            error_response(
+                e.to_string(),
+                "INVALID_APPROVAL_TRANSITION",
+                vec!["GET /profiles/<name>/approval shows the current state".to_string()],
+            )
+        })?;
+    app_log!(
+        info,
+        "{} moved {} to {}",
+        auth.email(),
+        name,
+        data.state.as_str()
+    );
+    Ok(Json(DataResponse::success(
+        format!("'{}' is now {}", name, data.state.as_str()),
+        data,
+        None,
+    )))
+}
+
+/// GET /approvals/pending — profiles of the caller's tenant waiting for a
+/// decision, for its reviewers.
+pub async fn pending_reviews_handler(
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    db_config: &State<DatabaseConfig>,
+) -> Result<Json<DataResponse<Vec<PendingReview>>>, Json<StandardErrorResponse>> {
+    if !is_reviewer(&auth) {
+        return Err(editor_role_required());
+    }
+    require_feature(config_manager::features().sharing, "sharing", None)?;
+    let tenants = TenantRepository::new(pool(db_config)?)
+        .list_active()
+        .await
+        .map_err(db_error)?;
+    // Members of a tenant share its folder; other accounts may too.
+    let user_dir = get_tenant_folder_path(auth.email(), &config.data_dir);
+    let tenant_dir = user_dir.parent().unwrap_or(&user_dir);
+    let tenant_folder = tenant_dir.file_name().unwrap_or_default().to_string_lossy();
+
+    let mut pending = Vec::new();
+    if let Ok(mut members) = tokio::fs::read_dir(tenant_dir).await {
+        while let Ok(Some(member)) = members.next_entry().await {
+            let user_folder = member.file_name().to_string_lossy().to_string();
+            let tenant = owner::tenant_of_account(&tenants, &tenant_folder, &user_folder);
+            if user_folder.starts_with('.') || tenant.map(|t| t.id) != Some(auth.tenant_id()) {
+                continue;
+            }
+            pending.extend(profile_approval::in_review(&member.path()).await);
+        }
+    }
+    pending.sort_by_key(|review| review.requested_at);
+    Ok(Json(DataResponse::success(
+        format!("{} profile(s) waiting for review", pending.len()),
+        pending,
+        None,
+    )))
+}
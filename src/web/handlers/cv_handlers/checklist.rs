@@ -0,0 +1,173 @@
+// src/web/handlers/cv_handlers/checklist.rs
+//! Onboarding checklist for one person, computed from their files on each
+//! request so the studio can show a progress widget.
+use crate::auth::AuthenticatedUser;
+use crate::core::database::get_tenant_folder_path;
+use crate::core::{profile_schema, review_calendar, FsOps, TemplateEngine};
+use crate::utils::normalize_profile_name;
+use crate::web::types::{DataResponse, ServerConfig, StandardErrorResponse};
+use rocket::serde::json::Json;
+use rocket::State;
+use serde::Serialize;
+use std::path::Path;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ChecklistStep {
+    /// Stable id the frontend keys on: `personal_info`, `photo`,
+    /// `experiences`, `generated`.
+    pub id: &'static str,
+    pub label: &'static str,
+    pub done: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OnboardingChecklist {
+    pub profile: String,
+    pub steps: Vec<ChecklistStep>,
+    pub completed: usize,
+    pub total: usize,
+}
+
+/// Name, title and email are filled in.
+async fn has_personal_info(profile_dir: &Path) -> bool {
+    let Ok(content) = FsOps::read_file_safe(&profile_dir.join("cv_params.toml")).await else {
+        return false;
+    };
+    let Ok(table) = toml::from_str::<toml::Table>(&profile_schema::canonicalize(&content)) else {
+        return false;
+    };
+    let filled = |key: &str| {
+        table
+            .get(key)
+            .and_then(|v| v.as_str())
+            .is_some_and(|v| !v.trim().is_empty())
+    };
+    filled("name") && filled("email") && filled("title")
+}
+
+fn has_photo(profile_dir: &Path) -> bool {
+    ["profile.png", "profile.jpg"]
+        .iter()
+        .any(|name| profile_dir.join(name).is_file())
+}
+
+/// At least one experiences file moved past the starter placeholder, and an
+/// imported profile has been reviewed (edited since the import).
+async fn has_experiences(profile_dir: &Path, starter: Option<&str>) -> bool {
+    if review_calendar::awaiting_import_review(profile_dir).await {
+        return false;
+    }
+    let Ok(mut entries) = tokio::fs::read_dir(profile_dir).await else {
+        return false;
+    };
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if !(name.starts_with("experiences_") && name.ends_with(".typ")) {
+            continue;
+        }
+        if let Ok(content) = FsOps::read_file_safe(&entry.path()).await {
+            if !content.trim().is_empty() && starter.map_or(true, |s| content.trim() != s.trim()) {
+                return true;
+            }
+        }
+    }
+    false
+}
+
+fn has_generated(output_dir: &Path) -> bool {
+    std::fs::read_dir(output_dir)
+        .map(|entries| {
+            entries.flatten().any(|entry| {
+                entry
+                    .path()
+                    .extension()
+                    .is_some_and(|ext| ext.eq_ignore_ascii_case("pdf"))
+            })
+        })
+        .unwrap_or(false)
+}
+
+/// GET /profiles/<name>/checklist
+pub async fn checklist_handler(
+    name: String,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+) -> Result<Json<DataResponse<OnboardingChecklist>>, Json<StandardErrorResponse>> {
+    let profile = normalize_profile_name(&name);
+    let profile_dir = get_tenant_folder_path(auth.email(), &config.data_dir).join(&profile);
+
+    if profile.is_empty() || !profile_dir.is_dir() {
+        return Err(Json(StandardErrorResponse::new(
+            format!("Profile '{}' not found in your account", name),
+            "PROFILE_NOT_FOUND".to_string(),
+            vec!["Check the profile name spelling".to_string()],
+            None,
+        )));
+    }
+
+    let starter = starter_experiences(config).await;
+    let output_dir = config.person_output_dir(auth.email(), &profile);
+    let checklist = onboarding_checklist(profile, &profile_dir, starter.as_deref(), &output_dir).await;
+
+    Ok(Json(DataResponse::success(
+        format!(
+            "{} of {} onboarding steps done",
+            checklist.completed, checklist.total
+        ),
+        checklist,
+        None,
+    )))
+}
+
+/// The template's placeholder experiences, which don't count as filled in.
+pub(super) async fn starter_experiences(config: &ServerConfig) -> Option<String> {
+    match TemplateEngine::new(config.templates_dir.clone()) {
+        Ok(engine) => engine.starter_experiences().await.ok(),
+        Err(_) => None,
+    }
+}
+
+pub(super) async fn onboarding_checklist(
+    profile: String,
+    profile_dir: &Path,
+    starter: Option<&str>,
+    output_dir: &Path,
+) -> OnboardingChecklist {
+    let steps = vec![
+        ChecklistStep {
+            id: "personal_info",
+            label: "Add your name, title and email",
+            done: has_personal_info(profile_dir).await,
+        },
+        ChecklistStep {
+            id: "photo",
+            label: "Upload a photo",
+            done: has_photo(profile_dir),
+        },
+        ChecklistStep {
+            id: "experiences",
+            label: "Review your work experience",
+            done: has_experiences(profile_dir, starter).await,
+        },
+        ChecklistStep {
+            id: "generated",
+            label: "Generate your first CV",
+            done: has_generated(output_dir),
+        },
+    ];
+    let completed = steps.iter().filter(|s| s.done).count();
+    let total = steps.len();
+    OnboardingChecklist {
+        profile,
+        steps,
+        completed,
+        total,
+    }
+}
+
+impl OnboardingChecklist {
+    /// Share of the steps done, in percent.
+    pub fn completeness(&self) -> u8 {
+        (self.completed * 100 / self.total.max(1)) as u8
+    }
+}
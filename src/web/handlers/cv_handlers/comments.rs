@@ -0,0 +1,134 @@
+// src/web/handlers/cv_handlers/comments.rs
+//! Reviewer comments on a profile's files; see [`crate::core::profile_comments`].
+use crate::auth::AuthenticatedUser;
+use crate::core::config_manager;
+use crate::core::database::{get_tenant_folder_path, DatabaseConfig};
+use crate::core::profile_comments::{self, NewComment, ProfileComment};
+use crate::utils::normalize_profile_name;
+use crate::web::handlers::common::{db_error, error_response, pool, require_feature};
+use crate::web::types::{DataResponse, ServerConfig, StandardErrorResponse};
+use graflog::app_log;
+use rocket::serde::json::Json;
+use rocket::State;
+use sqlx::SqlitePool;
+use std::path::PathBuf;
+
+use super::helpers::require_edit_access;
+
+/// The profile's directory and name, and the database pool; comments are
+/// part of sharing.
+fn target<'a>(
+    name: &str,
+    auth: &AuthenticatedUser,
+    config: &ServerConfig,
+    db_config: &'a DatabaseConfig,
+) -> Result<(PathBuf, String, &'a SqlitePool), Json<StandardErrorResponse>> {
+    require_feature(config_manager::features().sharing, "sharing", None)?;
+    let profile = normalize_profile_name(name);
+    let profile_dir = get_tenant_folder_path(auth.email(), &config.data_dir).join(&profile);
+    if profile.is_empty() || !profile_dir.is_dir() {
+        return Err(error_response(
+            format!("Profile '{}' not found in your account", name),
+            "PROFILE_NOT_FOUND",
+            vec!["Check the profile name spelling".to_string()],
+        ));
+    }
+    Ok((profile_dir, profile, pool(db_config)?))
+}
+
+/// GET /profiles/<name>/comments?<path>&<resolved>
+pub async fn list_comments_handler(
+    name: String,
+    path: Option<String>,
+    resolved: Option<bool>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    db_config: &State<DatabaseConfig>,
+) -> Result<Json<DataResponse<Vec<ProfileComment>>>, Json<StandardErrorResponse>> {
+    let (_, profile, pool) = target(&name, &auth, config, db_config)?;
+    let comments = profile_comments::list(
+        pool,
+        &auth.account_key(),
+        &profile,
+        path.as_deref().map(str::trim).filter(|p| !p.is_empty()),
+        resolved.unwrap_or(false),
+    )
+    .await
+    .map_err(db_error)?;
+    Ok(Json(DataResponse::success(
+        format!("{} comments", comments.len()),
+        comments,
+        None,
+    )))
+}
+
+/// POST /profiles/<name>/comments
+pub async fn create_comment_handler(
+    name: String,
+    request: Json<NewComment>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    db_config: &State<DatabaseConfig>,
+) -> Result<Json<DataResponse<ProfileComment>>, Json<StandardErrorResponse>> {
+    let (_, profile, pool) = target(&name, &auth, config, db_config)?;
+    let owner = auth.account_key();
+    let comment = profile_comments::create(pool, &owner, &profile, auth.email(), &request)
+        .await
+        .map_err(|e| match e.downcast_ref::<sqlx::Error>() {
+            Some(_) => db_error(e),
+            None => error_response(
+                e.to_string(),
+                "INVALID_COMMENT",
+                vec!["Send a path inside the profile, a body and an optional line range".to_string()],
+            ),
+        })?;
+    app_log!(
+        info,
+        "{} commented on {}/{}",
+        auth.email(),
+        profile,
+        comment.path
+    );
+    Ok(Json(DataResponse::success(
+        "Comment added".to_string(),
+        comment,
+        None,
+    )))
+}
+
+/// POST /profiles/<name>/comments/<id>/resolve — by its author or anyone
+/// who may edit the profile.
+pub async fn resolve_comment_handler(
+    name: String,
+    id: i64,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    db_config: &State<DatabaseConfig>,
+) -> Result<Json<DataResponse<ProfileComment>>, Json<StandardErrorResponse>> {
+    let (profile_dir, profile, pool) = target(&name, &auth, config, db_config)?;
+    let existing = profile_comments::list(pool, &auth.account_key(), &profile, None, true)
+        .await
+        .map_err(db_error)?
+        .into_iter()
+        .find(|c| c.id == id);
+    let Some(existing) = existing else {
+        return Err(error_response(
+            format!("No comment {} on '{}'", id, name),
+            "NOT_FOUND",
+            vec![],
+        ));
+    };
+    if !existing.author.eq_ignore_ascii_case(auth.email()) {
+        require_edit_access(&auth, &profile_dir, None)?;
+    }
+
+    let comment = profile_comments::resolve(pool, &auth.account_key(), &profile, id, auth.email())
+        .await
+        .map_err(db_error)?
+        .unwrap_or(existing);
+    Ok(Json(DataResponse::success(
+        format!("Comment {} resolved", id),
+        comment,
+        None,
+    )))
+}
@@ -0,0 +1,360 @@
+// src/web/handlers/cv_handlers/compare.rs
+//! Side-by-side comparison of profiles for shortlisting
+use crate::auth::AuthenticatedUser;
+use crate::core::database::get_tenant_folder_path;
+use crate::core::FsOps;
+use crate::types::cv_data::{CvJson, Experience};
+use crate::utils::normalize_profile_name;
+use crate::web::types::{DataResponse, ServerConfig, StandardErrorResponse};
+use chrono::{Datelike, Utc};
+use graflog::app_log;
+use rocket::serde::json::Json;
+use rocket::State;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
+
+use super::helpers::load_profile_cv_data;
+
+const MIN_PROFILES: usize = 2;
+const MAX_PROFILES: usize = 10;
+
+#[derive(Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct LanguageLevel {
+    pub language: String,
+    pub level: String,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct ProfileSummary {
+    pub profile: String,
+    pub name: String,
+    pub title: Option<String>,
+    pub current_role: Option<String>,
+    /// Total professional experience, overlapping positions counted once.
+    pub years_experience: f32,
+    pub languages: Vec<LanguageLevel>,
+    pub skills: Vec<String>,
+    /// Free text from an optional `availability` key in `cv_params.toml`.
+    pub availability: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct SkillCoverage {
+    pub skill: String,
+    pub profiles: Vec<String>,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct ProfileComparison {
+    pub profiles: Vec<ProfileSummary>,
+    /// Skills every compared profile has.
+    pub common_skills: Vec<String>,
+    /// Every skill with the profiles that have it, most widely held first.
+    pub skill_matrix: Vec<SkillCoverage>,
+    /// Requested names with no readable profile.
+    pub not_found: Vec<String>,
+}
+
+/// `(year, month)` from CV date strings: `2015`, `2015-03`, `03/2015`,
+/// `Mar 2015`, `March 2015`. Year-only dates count from January.
+fn parse_year_month(raw: &str) -> Option<(i32, u32)> {
+    const MONTHS: [&str; 12] = [
+        "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+    ];
+    let lower = raw.trim().to_lowercase();
+    let tokens: Vec<&str> = lower
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|t| !t.is_empty())
+        .collect();
+
+    let year = tokens
+        .iter()
+        .filter(|t| t.len() == 4)
+        .find_map(|t| t.parse::<i32>().ok())
+        .filter(|y| (1950..=2100).contains(y))?;
+
+    let month = tokens
+        .iter()
+        .find_map(|t| {
+            if t.len() <= 2 {
+                t.parse::<u32>().ok().filter(|m| (1..=12).contains(m))
+            } else {
+                MONTHS
+                    .iter()
+                    .position(|m| t.starts_with(m))
+                    .map(|i| i as u32 + 1)
+            }
+        })
+        .unwrap_or(1);
+
+    Some((year, month))
+}
+
+fn is_ongoing(end: Option<&str>) -> bool {
+    match end.map(|e| e.trim().to_lowercase()) {
+        None => true,
+        Some(e) => {
+            e.is_empty()
+                || [
+                    "present", "current", "now", "ongoing", "today", "aujourd", "heute",
+                ]
+                .iter()
+                .any(|w| e.contains(w))
+        }
+    }
+}
+
+/// Years covered by the positions, merging overlaps. `today` is `(year, month)`.
+pub(super) fn years_of_experience(experiences: &[Experience], today: (i32, u32)) -> f32 {
+    let as_index = |(y, m): (i32, u32)| y * 12 + m as i32 - 1;
+    let now = as_index(today);
+
+    let mut spans: Vec<(i32, i32)> = experiences
+        .iter()
+        .filter_map(|exp| {
+            let start = as_index(parse_year_month(&exp.start_date)?);
+            let end = if is_ongoing(exp.end_date.as_deref()) {
+                now
+            } else {
+                as_index(parse_year_month(exp.end_date.as_deref()?)?)
+            };
+            (end >= start).then_some((start, end.min(now)))
+        })
+        .collect();
+    spans.sort();
+
+    let mut months = 0;
+    let mut current: Option<(i32, i32)> = None;
+    for (start, end) in spans {
+        current = match current {
+            Some((s, e)) if start <= e => Some((s, e.max(end))),
+            Some((s, e)) => {
+                months += e - s;
+                Some((start, end))
+            }
+            None => Some((start, end)),
+        };
+    }
+    if let Some((s, e)) = current {
+        months += e - s;
+    }
+
+    (months as f32 / 12.0 * 10.0).round() / 10.0
+}
+
+pub(super) fn collect_skills(cv: &CvJson) -> Vec<String> {
+    let declared = [
+        &cv.skills.technical,
+        &cv.skills.programming_languages,
+        &cv.skills.frameworks,
+        &cv.skills.tools,
+    ];
+    let other = cv.skills.other.iter().flat_map(|m| m.values());
+    let technologies = cv
+        .work_experience
+        .iter()
+        .filter_map(|e| e.technologies.as_ref());
+
+    let mut seen = HashSet::new();
+    let mut skills = Vec::new();
+    for skill in declared
+        .into_iter()
+        .flatten()
+        .chain(other)
+        .chain(technologies)
+        .flatten()
+    {
+        let trimmed = skill.trim();
+        if !trimmed.is_empty() && seen.insert(trimmed.to_lowercase()) {
+            skills.push(trimmed.to_string());
+        }
+    }
+    skills
+}
+
+fn collect_languages(cv: &CvJson) -> Vec<LanguageLevel> {
+    let levels = [
+        ("native", &cv.languages.native),
+        ("fluent", &cv.languages.fluent),
+        ("intermediate", &cv.languages.intermediate),
+        ("basic", &cv.languages.basic),
+    ];
+    levels
+        .into_iter()
+        .flat_map(|(level, langs)| {
+            langs.iter().flatten().map(move |language| LanguageLevel {
+                language: language.clone(),
+                level: level.to_string(),
+            })
+        })
+        .collect()
+}
+
+pub(super) async fn read_availability(profile_dir: &std::path::Path) -> Option<String> {
+    let content = FsOps::read_file_safe(&profile_dir.join("cv_params.toml"))
+        .await
+        .ok()?;
+    let value: toml::Value = toml::from_str(&content).ok()?;
+    value
+        .get("availability")
+        .and_then(|v| v.as_str())
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+}
+
+/// Skill → profiles, case-insensitive, ordered by coverage then name.
+fn skill_matrix(profiles: &[ProfileSummary]) -> Vec<SkillCoverage> {
+    let mut by_key: BTreeMap<String, SkillCoverage> = BTreeMap::new();
+    for summary in profiles {
+        for skill in &summary.skills {
+            let entry = by_key
+                .entry(skill.to_lowercase())
+                .or_insert_with(|| SkillCoverage {
+                    skill: skill.clone(),
+                    profiles: Vec::new(),
+                });
+            entry.profiles.push(summary.profile.clone());
+        }
+    }
+    let mut matrix: Vec<SkillCoverage> = by_key.into_values().collect();
+    matrix.sort_by_key(|row| std::cmp::Reverse(row.profiles.len()));
+    matrix
+}
+
+/// GET /profiles/compare?names=a,b,c
+pub async fn compare_profiles_handler(
+    names: String,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+) -> Result<Json<DataResponse<ProfileComparison>>, Json<StandardErrorResponse>> {
+    let mut requested: Vec<String> = Vec::new();
+    for name in names.split(',').map(normalize_profile_name) {
+        if !name.is_empty() && !requested.contains(&name) {
+            requested.push(name);
+        }
+    }
+
+    if requested.len() < MIN_PROFILES || requested.len() > MAX_PROFILES {
+        return Err(Json(StandardErrorResponse::new(
+            format!(
+                "Select between {} and {} profiles to compare",
+                MIN_PROFILES, MAX_PROFILES
+            ),
+            "INVALID_INPUT".to_string(),
+            vec!["Pass comma-separated profile names: ?names=alice,bob".to_string()],
+            None,
+        )));
+    }
+
+    let tenant_data_dir = get_tenant_folder_path(&auth.user().email, &config.data_dir);
+    let now = Utc::now();
+    let today = (now.year(), now.month());
+
+    let mut profiles = Vec::new();
+    let mut not_found = Vec::new();
+    for profile in requested {
+        let cv = match load_profile_cv_data(&profile, &tenant_data_dir).await {
+            Ok(cv) => cv,
+            Err(e) => {
+                app_log!(warn, "Compare: cannot load profile {}: {}", profile, e);
+                not_found.push(profile);
+                continue;
+            }
+        };
+        let current_role = cv
+            .work_experience
+            .iter()
+            .find(|e| is_ongoing(e.end_date.as_deref()))
+            .map(|e| format!("{} at {}", e.title, e.company));
+
+        profiles.push(ProfileSummary {
+            availability: read_availability(&tenant_data_dir.join(&profile)).await,
+            years_experience: years_of_experience(&cv.work_experience, today),
+            languages: collect_languages(&cv),
+            skills: collect_skills(&cv),
+            name: cv.personal_info.name.clone(),
+            title: cv.personal_info.title.clone(),
+            current_role,
+            profile,
+        });
+    }
+
+    if profiles.len() < MIN_PROFILES {
+        return Err(Json(StandardErrorResponse::new(
+            format!(
+                "Not enough profiles to compare (not found: {})",
+                not_found.join(", ")
+            ),
+            "PROFILE_NOT_FOUND".to_string(),
+            vec!["Check the profile name spelling".to_string()],
+            None,
+        )));
+    }
+
+    let skill_matrix = skill_matrix(&profiles);
+    let common_skills = skill_matrix
+        .iter()
+        .filter(|row| row.profiles.len() == profiles.len())
+        .map(|row| row.skill.clone())
+        .collect();
+
+    app_log!(
+        info,
+        "User {} compared {} profiles",
+        auth.user().email,
+        profiles.len()
+    );
+
+    Ok(Json(DataResponse::success(
+        format!("Compared {} profiles", profiles.len()),
+        ProfileComparison {
+            profiles,
+            common_skills,
+            skill_matrix,
+            not_found,
+        },
+        None,
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn exp(start: &str, end: Option<&str>) -> Experience {
+        Experience {
+            company: "UBS".to_string(),
+            title: "Engineer".to_string(),
+            start_date: start.to_string(),
+            end_date: end.map(str::to_string),
+            description: None,
+            responsibilities: Vec::new(),
+            achievements: None,
+            technologies: None,
+            location: None,
+        }
+    }
+
+    #[test]
+    fn parses_common_date_formats() {
+        assert_eq!(parse_year_month("2015"), Some((2015, 1)));
+        assert_eq!(parse_year_month("2015-03"), Some((2015, 3)));
+        assert_eq!(parse_year_month("03/2015"), Some((2015, 3)));
+        assert_eq!(parse_year_month("September 2019"), Some((2019, 9)));
+        assert_eq!(parse_year_month("Present"), None);
+    }
+
+    #[test]
+    fn overlapping_positions_count_once() {
+        let experiences = vec![
+            exp("2015", Some("2020")),
+            exp("2018-01", Some("2019-01")),
+            exp("Jan 2022", Some("Present")),
+        ];
+        assert_eq!(years_of_experience(&experiences, (2024, 1)), 7.0);
+    }
+}
@@ -8,10 +8,13 @@
 
 use crate::auth::AuthenticatedUser;
 use crate::core::database::{get_tenant_folder_path, DatabaseConfig};
-use crate::core::ServiceClient;
+use crate::core::{request_cancel, ServiceClient};
 use crate::types::cv_data::CvConverter;
-use crate::web::handlers::payment_handlers::check_and_deduct_credits;
-use crate::web::types::{DataResponse, StandardErrorResponse, StandardRequest, WithConversationId};
+use crate::web::handlers::payment_handlers::{check_and_deduct_credits, refund_credits};
+use crate::web::handlers::usage_handlers::usage_recorder;
+use crate::web::types::{
+    ClientRequestId, DataResponse, StandardErrorResponse, StandardRequest, WithConversationId,
+};
 use crate::web::ServerConfig;
 use graflog::app_log;
 use rocket::serde::{json::Json, Deserialize, Serialize};
@@ -46,6 +49,7 @@ pub async fn cover_letter_handler(
     config: &State<ServerConfig>,
     db_config: &State<DatabaseConfig>,
     cv_service_url: &State<String>,
+    request_id: ClientRequestId,
 ) -> Result<Json<DataResponse<CoverLetterResult>>, Json<StandardErrorResponse>> {
     let user = auth.user();
     let tenant = auth.tenant();
@@ -115,8 +119,11 @@ pub async fn cover_letter_handler(
     };
 
     // Initialise service client
+    let cancel = request_id.register(auth.email());
     let service_client = match ServiceClient::new(cv_service_url.inner().clone(), 60) {
-        Ok(c) => c,
+        Ok(c) => c
+            .with_usage(usage_recorder(db_config, &auth))
+            .with_cancel(cancel.token()),
         Err(e) => {
             return Err(Json(StandardErrorResponse::new(
                 format!("Service initialization failed: {}", e),
@@ -180,6 +187,10 @@ pub async fn cover_letter_handler(
                 conversation_id,
             )))
         }
+        Err(e) if request_cancel::is_cancelled(&e) => {
+            refund_credits(&user.email, 20, "cover_letter");
+            Err(Json(StandardErrorResponse::cancelled(conversation_id)))
+        }
         Err(e) => {
             app_log!(
                 error,
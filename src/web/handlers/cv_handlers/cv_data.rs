@@ -7,13 +7,19 @@
 //   PUT  /profiles/:name/cv-data  → accept CvFormData JSON, write cv_params.toml
 //                                    and regenerate experiences_en.typ (and
 //                                    experiences_fr.typ if it already exists).
+//   GET  /profiles/:name/cv-data/:section → one section of the above
+//   PUT  /profiles/:name/cv-data/:section → rewrite only that section; other
+//                                    cv_params.toml keys are left untouched.
 //
 // Security: The profile name is path-traversal-checked to ensure it stays
 // inside the authenticated user's tenant directory.
 
 use crate::auth::AuthenticatedUser;
-use crate::core::database::get_tenant_folder_path;
+use crate::core::database::{get_tenant_folder_path, DatabaseConfig};
+use crate::core::profile_watch::WatchChange;
+use crate::core::{profile_lock, profile_schema, toml_patch, FsOps};
 use crate::web::types::{StandardErrorResponse};
+use super::helpers::{notify_watchers, require_create_access, require_edit_access};
 use graflog::app_log;
 use rocket::serde::json::Json;
 use rocket::State;
@@ -149,38 +155,25 @@ fn resolve_profile_dir(
 // ── TOML parser ───────────────────────────────────────────────────────────────
 
 fn parse_toml_cv(content: &str) -> CvFormData {
-    let value: toml::Value = toml::from_str(content).unwrap_or(toml::Value::Table(Default::default()));
+    let content = profile_schema::canonicalize(content);
+    let value: toml::Value = toml::from_str(&content).unwrap_or(toml::Value::Table(Default::default()));
     let table = match value {
         toml::Value::Table(t) => t,
         _ => Default::default(),
     };
 
     // ── personal ──
-    // Support both [Personal]/[personal] section (form-editor format) and flat
-    // top-level keys (original AI-generated format used by old profiles).
-    // We collect the relevant personal fields once, preferring the section.
+    // Top-level keys; legacy layouts were flattened by `canonicalize`.
     let get_personal_str = |key: &str| -> String {
-        // Try [Personal] section first, then [personal], then top-level key.
-        let from_section = table.get("Personal")
-            .or_else(|| table.get("personal"))
-            .and_then(|v| v.as_table())
-            .and_then(|t| t.get(key))
-            .and_then(|v| v.as_str())
-            .unwrap_or("");
-        if !from_section.is_empty() {
-            return from_section.to_string();
-        }
         table.get(key)
             .and_then(|v| v.as_str())
             .unwrap_or("")
             .to_string()
     };
 
-    let title_raw = get_personal_str("title");
     let personal = PersonalData {
         name:    get_personal_str("name"),
-        // Fall back to "job_title" (used by some older keyteo profiles)
-        title:   if title_raw.is_empty() { get_personal_str("job_title") } else { title_raw },
+        title:   get_personal_str("title"),
         email:   get_personal_str("email"),
         phone:   get_personal_str("phonenumber"),
         address: get_personal_str("address"),
@@ -575,13 +568,14 @@ pub async fn get_cv_data_handler(
     };
 
     // Read cv_params.toml
+    profile_schema::upgrade_on_read(&profile_dir).await;
     let toml_path = profile_dir.join("cv_params.toml");
-    let toml_content = tokio::fs::read_to_string(&toml_path).await.unwrap_or_default();
+    let toml_content = FsOps::read_file_safe(&toml_path).await.unwrap_or_default();
     let mut cv_data = parse_toml_cv(&toml_content);
 
     // Read experiences_{lang}.typ (optional)
     let exp_path = profile_dir.join(format!("experiences_{}.typ", lang));
-    if let Ok(exp_content) = tokio::fs::read_to_string(&exp_path).await {
+    if let Ok(exp_content) = FsOps::read_file_safe(&exp_path).await {
         cv_data.work_experience = parse_experiences_typ(&exp_content);
     }
 
@@ -595,6 +589,7 @@ pub async fn put_cv_data_handler(
     request: Json<CvFormData>,
     auth: AuthenticatedUser,
     config: &State<crate::web::ServerConfig>,
+    db_config: &State<DatabaseConfig>,
 ) -> Result<Json<serde_json::Value>, Json<StandardErrorResponse>> {
     let email = auth.email();
     let lang = lang.as_deref().unwrap_or("en");
@@ -609,6 +604,12 @@ pub async fn put_cv_data_handler(
         }
     };
 
+    if profile_dir.is_dir() {
+        require_edit_access(&auth, &profile_dir, None)?;
+    } else {
+        require_create_access(&auth, None)?;
+    }
+
     // Ensure profile dir exists
     if let Err(e) = tokio::fs::create_dir_all(&profile_dir).await {
         return Err(Json(StandardErrorResponse::new(
@@ -617,10 +618,20 @@ pub async fn put_cv_data_handler(
         )));
     }
 
-    // Write cv_params.toml
-    let toml_content = generate_toml(&data);
+    // Both files are replaced under one lock so a generation never sees the
+    // new cv_params.toml next to the old experiences.
+    let _save = profile_lock::write(&profile_dir).await;
+
+    // Write cv_params.toml — patched in place so comments and key order
+    // survive; an unreadable file is replaced outright.
     let toml_path = profile_dir.join("cv_params.toml");
-    if let Err(e) = tokio::fs::write(&toml_path, &toml_content).await {
+    let existing = FsOps::read_file_safe(&toml_path).await.unwrap_or_default();
+    let toml_content = merge_toml_sections(&existing, &data, CvSection::FORM_SECTIONS)
+        .unwrap_or_else(|e| {
+            app_log!(warn, "Replacing cv_params.toml for {}: {}", profile_name, e);
+            generate_toml(&data)
+        });
+    if let Err(e) = FsOps::write_file_safe(&toml_path, &toml_content).await {
         app_log!(error, "Failed to write cv_params.toml: {}", e);
         return Err(Json(StandardErrorResponse::new(
             format!("Failed to save CV data: {}", e),
@@ -632,7 +643,7 @@ pub async fn put_cv_data_handler(
     let exp_typ = generate_experiences_typ(&data.work_experience);
     let exp_filename = format!("experiences_{}.typ", lang);
     let exp_path = profile_dir.join(&exp_filename);
-    if let Err(e) = tokio::fs::write(&exp_path, &exp_typ).await {
+    if let Err(e) = FsOps::write_file_safe(&exp_path, &exp_typ).await {
         app_log!(error, "Failed to write {}: {}", exp_filename, e);
         return Err(Json(StandardErrorResponse::new(
             format!("Failed to save experiences file: {}", e),
@@ -649,10 +660,304 @@ pub async fn put_cv_data_handler(
         data.work_experience.len(),
         data.education.len(),
     );
+    for path in ["cv_params.toml".to_string(), exp_filename] {
+        notify_watchers(&auth, db_config, &profile_dir, WatchChange::FileSaved { path });
+    }
 
     Ok(Json(serde_json::json!({ "success": true, "message": "CV data saved" })))
 }
 
+/// Rewrite a legacy cv_params.toml in the current schema, keeping a backup.
+pub async fn upgrade_cv_schema_handler(
+    profile_name: String,
+    auth: AuthenticatedUser,
+    config: &State<crate::web::ServerConfig>,
+    db_config: &State<DatabaseConfig>,
+) -> Result<Json<serde_json::Value>, Json<StandardErrorResponse>> {
+    let profile_dir = resolve_profile_dir(&profile_name, auth.email(), &config.data_dir)
+        .map_err(|e| Json(StandardErrorResponse::new(e, "INVALID_PROFILE".to_string(), vec![], None)))?;
+    if !profile_dir.is_dir() {
+        return Err(Json(StandardErrorResponse::new(
+            format!("Profile '{}' not found", profile_name),
+            "PROFILE_NOT_FOUND".to_string(),
+            vec!["Check the profile name spelling".to_string()],
+            None,
+        )));
+    }
+    require_edit_access(&auth, &profile_dir, None)?;
+
+    let upgraded = profile_schema::migrate(&profile_dir).await.map_err(|e| {
+        app_log!(error, "Schema upgrade of {} failed: {:#}", profile_name, e);
+        Json(StandardErrorResponse::new(
+            format!("Failed to upgrade cv_params.toml: {:#}", e),
+            "WRITE_ERROR".to_string(),
+            vec![],
+            None,
+        ))
+    })?;
+    if !upgraded {
+        return Ok(Json(serde_json::json!({
+            "success": true,
+            "upgraded": false,
+            "message": "cv_params.toml already uses the current schema",
+        })));
+    }
+
+    notify_watchers(
+        &auth,
+        db_config,
+        &profile_dir,
+        WatchChange::FileSaved { path: profile_schema::PARAMS_FILE.to_string() },
+    );
+    Ok(Json(serde_json::json!({
+        "success": true,
+        "upgraded": true,
+        "backup": profile_schema::BACKUP_FILE,
+        "message": "cv_params.toml upgraded to the current schema",
+    })))
+}
+
+// ── Section endpoints ─────────────────────────────────────────────────────────
+//
+// Granular read/write for single-purpose editors (e.g. a skills tag editor)
+// so saving one section cannot clobber edits made to another.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CvSection {
+    Personal,
+    Links,
+    Skills,
+    Education,
+    Languages,
+    Styling,
+    Experiences,
+}
+
+impl CvSection {
+    pub const ALL: &'static [&'static str] = &[
+        "personal", "links", "skills", "education", "languages", "styling", "experiences",
+    ];
+
+    /// Sections stored in cv_params.toml (everything but the experiences).
+    const FORM_SECTIONS: &'static [CvSection] = &[
+        Self::Personal,
+        Self::Links,
+        Self::Skills,
+        Self::Education,
+        Self::Languages,
+        Self::Styling,
+    ];
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "personal" => Some(Self::Personal),
+            "links" => Some(Self::Links),
+            "skills" => Some(Self::Skills),
+            "education" => Some(Self::Education),
+            "languages" => Some(Self::Languages),
+            "styling" => Some(Self::Styling),
+            "experiences" | "work_experience" => Some(Self::Experiences),
+            _ => None,
+        }
+    }
+
+    /// Top-level cv_params.toml keys owned by this section (as written by
+    /// `generate_toml`). Empty for sections stored outside the TOML.
+    fn toml_keys(&self) -> &'static [&'static str] {
+        match self {
+            Self::Personal => &["name", "title", "email", "phonenumber", "address", "summary"],
+            Self::Links => &["links"],
+            Self::Skills => &["skills"],
+            Self::Education => &["education"],
+            Self::Languages => &["languages"],
+            Self::Styling => &["styling"],
+            Self::Experiences => &[],
+        }
+    }
+}
+
+fn section_to_json(data: &CvFormData, section: CvSection) -> serde_json::Value {
+    let value = match section {
+        CvSection::Personal => serde_json::to_value(&data.personal),
+        CvSection::Links => serde_json::to_value(&data.links),
+        CvSection::Skills => serde_json::to_value(&data.skills),
+        CvSection::Education => serde_json::to_value(&data.education),
+        CvSection::Languages => serde_json::to_value(&data.languages),
+        CvSection::Styling => serde_json::to_value(&data.styling),
+        CvSection::Experiences => serde_json::to_value(&data.work_experience),
+    };
+    value.unwrap_or(serde_json::Value::Null)
+}
+
+fn apply_section_json(
+    data: &mut CvFormData,
+    section: CvSection,
+    body: serde_json::Value,
+) -> Result<(), serde_json::Error> {
+    match section {
+        CvSection::Personal => data.personal = serde_json::from_value(body)?,
+        CvSection::Links => data.links = serde_json::from_value(body)?,
+        CvSection::Skills => data.skills = serde_json::from_value(body)?,
+        CvSection::Education => data.education = serde_json::from_value(body)?,
+        CvSection::Languages => data.languages = serde_json::from_value(body)?,
+        CvSection::Styling => data.styling = serde_json::from_value(body)?,
+        CvSection::Experiences => data.work_experience = serde_json::from_value(body)?,
+    }
+    Ok(())
+}
+
+/// Rewrite only `section`'s keys in an existing cv_params.toml. Everything
+/// else — including keys the form editor doesn't know about, comments and
+/// key order — is kept.
+fn merge_toml_section(
+    existing: &str,
+    data: &CvFormData,
+    section: CvSection,
+) -> Result<String, String> {
+    merge_toml_sections(existing, data, &[section])
+}
+
+fn merge_toml_sections(
+    existing: &str,
+    data: &CvFormData,
+    sections: &[CvSection],
+) -> Result<String, String> {
+    let mut doc = toml_patch::parse(existing)
+        .map_err(|e| format!("cv_params.toml is not valid TOML: {}", e))?;
+    // A legacy layout would shadow the fresh values; saving upgrades it.
+    profile_schema::upgrade(&mut doc);
+    let fresh = toml_patch::parse(&generate_toml(data))
+        .map_err(|e| format!("Failed to render section: {}", e))?;
+
+    let keys: Vec<&str> = sections
+        .iter()
+        .flat_map(|s| s.toml_keys().iter().copied())
+        .collect();
+    toml_patch::apply(&mut doc, &fresh, &keys);
+
+    Ok(doc.to_string())
+}
+
+fn invalid_section(section: &str) -> Json<StandardErrorResponse> {
+    Json(StandardErrorResponse::new(
+        format!("Unknown CV section '{}'", section),
+        "INVALID_SECTION".to_string(),
+        vec![format!("Use one of: {}", CvSection::ALL.join(", "))],
+        None,
+    ))
+}
+
+pub async fn get_cv_section_handler(
+    profile_name: String,
+    section: String,
+    lang: Option<String>,
+    auth: AuthenticatedUser,
+    config: &State<crate::web::ServerConfig>,
+) -> Result<Json<serde_json::Value>, Json<StandardErrorResponse>> {
+    let email = auth.email();
+    let lang = lang.as_deref().unwrap_or("en");
+    let cv_section = CvSection::parse(&section).ok_or_else(|| invalid_section(&section))?;
+
+    let profile_dir = resolve_profile_dir(&profile_name, email, &config.data_dir).map_err(|e| {
+        Json(StandardErrorResponse::new(e, "INVALID_PROFILE".to_string(), vec![], None))
+    })?;
+
+    let mut cv_data = CvFormData::default();
+    if cv_section == CvSection::Experiences {
+        let exp_path = profile_dir.join(format!("experiences_{}.typ", lang));
+        if let Ok(exp_content) = FsOps::read_file_safe(&exp_path).await {
+            cv_data.work_experience = parse_experiences_typ(&exp_content);
+        }
+    } else {
+        let toml_content = FsOps::read_file_safe(&profile_dir.join("cv_params.toml"))
+            .await
+            .unwrap_or_default();
+        cv_data = parse_toml_cv(&toml_content);
+    }
+
+    app_log!(info, user = %email, profile = %profile_name, section = %section, "Loaded cv-data section");
+    Ok(Json(section_to_json(&cv_data, cv_section)))
+}
+
+pub async fn put_cv_section_handler(
+    profile_name: String,
+    section: String,
+    lang: Option<String>,
+    request: Json<serde_json::Value>,
+    auth: AuthenticatedUser,
+    config: &State<crate::web::ServerConfig>,
+    db_config: &State<DatabaseConfig>,
+) -> Result<Json<serde_json::Value>, Json<StandardErrorResponse>> {
+    let email = auth.email();
+    let lang = lang.as_deref().unwrap_or("en");
+    let cv_section = CvSection::parse(&section).ok_or_else(|| invalid_section(&section))?;
+
+    let profile_dir = resolve_profile_dir(&profile_name, email, &config.data_dir).map_err(|e| {
+        Json(StandardErrorResponse::new(e, "INVALID_PROFILE".to_string(), vec![], None))
+    })?;
+    if !profile_dir.exists() {
+        return Err(Json(StandardErrorResponse::new(
+            format!("Profile '{}' not found", profile_name),
+            "PROFILE_NOT_FOUND".to_string(),
+            vec!["Create the profile before editing its sections".to_string()],
+            None,
+        )));
+    }
+    require_edit_access(&auth, &profile_dir, None)?;
+
+    // Held across the read-merge-write so concurrent section saves don't
+    // overwrite each other.
+    let _save = profile_lock::write(&profile_dir).await;
+    let toml_path = profile_dir.join("cv_params.toml");
+    let toml_content = FsOps::read_file_safe(&toml_path).await.unwrap_or_default();
+    let mut cv_data = parse_toml_cv(&toml_content);
+
+    if let Err(e) = apply_section_json(&mut cv_data, cv_section, request.into_inner()) {
+        return Err(Json(StandardErrorResponse::new(
+            format!("Invalid '{}' payload: {}", section, e),
+            "INVALID_INPUT".to_string(),
+            vec!["Send the same shape returned by GET for this section".to_string()],
+            None,
+        )));
+    }
+
+    let (path, content) = if cv_section == CvSection::Experiences {
+        (
+            profile_dir.join(format!("experiences_{}.typ", lang)),
+            generate_experiences_typ(&cv_data.work_experience),
+        )
+    } else {
+        match merge_toml_section(&toml_content, &cv_data, cv_section) {
+            Ok(merged) => (toml_path, merged),
+            Err(e) => {
+                return Err(Json(StandardErrorResponse::new(
+                    e,
+                    "INVALID_TOML".to_string(),
+                    vec!["Fix cv_params.toml in the file editor, then retry".to_string()],
+                    None,
+                )));
+            }
+        }
+    };
+
+    if let Err(e) = FsOps::write_file_safe(&path, &content).await {
+        app_log!(error, "Failed to write {}: {}", path.display(), e);
+        return Err(Json(StandardErrorResponse::new(
+            format!("Failed to save section: {}", e),
+            "WRITE_ERROR".to_string(), vec![], None,
+        )));
+    }
+
+    app_log!(info, user = %email, profile = %profile_name, section = %section, lang = %lang, "Saved cv-data section");
+    notify_watchers(
+        &auth,
+        db_config,
+        &profile_dir,
+        WatchChange::FileSaved { path: file.to_string() },
+    );
+    Ok(Json(serde_json::json!({ "success": true, "message": format!("Section '{}' saved", section) })))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -697,4 +1002,19 @@ mod tests {
         assert_eq!(parsed[0].date, "2024 - Today");
         assert_eq!(parsed[0].responsibilities, vec!["did the thing".to_string()]);
     }
+
+    #[test]
+    fn merge_toml_section_only_touches_target_keys() {
+        let existing = "name = \"Jane\"\ncustom_key = \"keep me\"\n\n[skills]\nold = [\"x\"]\n\n[languages]\nnative = [\"French\"]\n";
+        let mut data = parse_toml_cv(existing);
+        data.skills = HashMap::from([("rust".to_string(), vec!["tokio".to_string()])]);
+
+        let merged = merge_toml_section(existing, &data, CvSection::Skills).unwrap();
+        let table: toml::Table = toml::from_str(&merged).unwrap();
+        assert_eq!(table["custom_key"].as_str(), Some("keep me"));
+        assert_eq!(table["name"].as_str(), Some("Jane"));
+        assert!(table["skills"].get("old").is_none());
+        assert_eq!(table["skills"]["rust"][0].as_str(), Some("tokio"));
+        assert_eq!(table["languages"]["native"][0].as_str(), Some("French"));
+    }
 }
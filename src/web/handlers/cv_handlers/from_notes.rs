@@ -0,0 +1,218 @@
+// src/web/handlers/cv_handlers/from_notes.rs
+//! Profile creation from free-form intake notes (dictated or pasted during a call)
+use crate::auth::AuthenticatedUser;
+use crate::core::database::{get_tenant_folder_path, DatabaseConfig};
+use crate::core::{language_detect, FsOps, ServiceClient};
+use crate::utils::normalize_profile_name;
+use crate::web::types::{DataResponse, ServerConfig, StandardErrorResponse, StandardRequest};
+use graflog::app_log;
+use rocket::serde::{json::Json, Deserialize, Serialize};
+use rocket::State;
+use std::path::Path;
+
+use super::helpers::{create_profile_from_cv_data, require_create_access, unique_profile_name};
+use crate::web::handlers::payment_handlers::check_and_deduct_credits;
+use crate::web::handlers::usage_handlers::usage_recorder;
+
+/// Notes are short by nature; anything longer is a CV and belongs on `/cv/import-text`.
+const MAX_NOTES_CHARS: usize = 20_000;
+
+/// Prepended to the notes so the import service reads them as loose facts
+/// rather than a formatted CV with sections.
+const NOTES_PREAMBLE: &str = "The following are informal intake notes about a candidate, \
+taken during a call or dictated. They are not a formatted CV: infer the name, roles, \
+employers, dates, skills and languages from the sentences. Do not invent facts that are \
+not stated.\n\n";
+
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct ProfileFromNotesRequest {
+    pub notes: String,
+    /// Profile name to use; derived from the name found in the notes when absent.
+    pub profile_name: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct ProfileFromNotesResult {
+    pub profile: String,
+    pub name: String,
+    pub title: Option<String>,
+    pub experiences: usize,
+    /// Language the experiences were filed under.
+    pub language: String,
+}
+
+fn profile_exists(tenant_dir: &Path, profile: &str) -> bool {
+    tenant_dir.join(profile).exists()
+}
+
+/// POST /profiles/from-notes
+/// Turn unstructured notes ("John, 10 years Java, worked at UBS 2015-2020 …")
+/// into a new profile. Never overwrites an existing profile — a numeric suffix
+/// is added instead.
+pub async fn profile_from_notes_handler(
+    request: Json<StandardRequest<ProfileFromNotesRequest>>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    cv_service_url: &State<String>,
+    db_config: &State<DatabaseConfig>,
+) -> Result<Json<DataResponse<ProfileFromNotesResult>>, Json<StandardErrorResponse>> {
+    let user = auth.user();
+    let tenant = auth.tenant();
+    let conversation_id = request.conversation_id.clone();
+
+    let notes = request.data.notes.trim().to_string();
+    if notes.is_empty() {
+        return Err(Json(StandardErrorResponse::new(
+            "notes must not be empty".to_string(),
+            "INVALID_INPUT".to_string(),
+            vec!["Paste or dictate what you know about the person".to_string()],
+            conversation_id,
+        )));
+    }
+
+    if notes.chars().count() > MAX_NOTES_CHARS {
+        return Err(Json(StandardErrorResponse::new(
+            format!(
+                "notes exceed maximum length of {} characters",
+                MAX_NOTES_CHARS
+            ),
+            "INPUT_TOO_LARGE".to_string(),
+            vec!["Use /cv/import-text for a full CV".to_string()],
+            conversation_id,
+        )));
+    }
+
+    require_create_access(&auth, conversation_id.clone())?;
+
+    // Same LLM conversion as a text import — 4 credits
+    check_and_deduct_credits(&user.email, 4, None, "cv_import_notes").await?;
+
+    app_log!(
+        info,
+        "User {} (tenant: {}) creating profile from notes ({} chars)",
+        user.email,
+        tenant.tenant_name,
+        notes.len()
+    );
+
+    let tenant_data_dir = get_tenant_folder_path(&user.email, &config.data_dir);
+    if let Err(e) = FsOps::ensure_dir_exists(&tenant_data_dir).await {
+        app_log!(error, "Failed to create tenant directory: {}", e);
+        return Err(Json(StandardErrorResponse::new(
+            "Failed to access tenant data directory".to_string(),
+            "TENANT_DIR_ERROR".to_string(),
+            vec!["Contact system administrator".to_string()],
+            conversation_id,
+        )));
+    }
+
+    let service_client = match ServiceClient::new(cv_service_url.inner().clone(), 400) {
+        Ok(c) => c.with_usage(usage_recorder(db_config, &auth)),
+        Err(e) => {
+            app_log!(error, "Failed to initialize service client: {}", e);
+            return Err(Json(StandardErrorResponse::new(
+                "Service configuration error".to_string(),
+                "SERVICE_CONFIG_ERROR".to_string(),
+                vec!["Contact system administrator".to_string()],
+                conversation_id,
+            )));
+        }
+    };
+
+    let document = format!("{}{}", NOTES_PREAMBLE, notes);
+    let mut cv_data = match service_client
+        .import_text_cv(&document, "intake-notes")
+        .await
+    {
+        Ok(data) => data,
+        Err(e) => {
+            let err_str = e.to_string();
+            app_log!(error, "Notes conversion failed: {}", err_str);
+
+            let (message, suggestions) =
+                if err_str.contains("Connection refused") || err_str.contains("os error 111") {
+                    (
+                        "CV import service is unavailable".to_string(),
+                        vec!["Contact the administrator".to_string()],
+                    )
+                } else {
+                    (
+                        format!("Could not build a profile from the notes: {}", err_str),
+                        vec![
+                            "Mention at least the person's name and one role".to_string(),
+                            "Add employers and years where you know them".to_string(),
+                        ],
+                    )
+                };
+
+            return Err(Json(StandardErrorResponse::new(
+                message,
+                "CONVERSION_ERROR".to_string(),
+                suggestions,
+                conversation_id,
+            )));
+        }
+    };
+
+    let requested = request
+        .data
+        .profile_name
+        .as_deref()
+        .map(normalize_profile_name)
+        .filter(|p| !p.is_empty());
+    let base_profile = requested
+        .or_else(|| Some(normalize_profile_name(&cv_data.personal_info.name)))
+        .filter(|p| !p.is_empty())
+        .unwrap_or_else(|| "new_profile".to_string());
+    let profile = unique_profile_name(&base_profile, |p| profile_exists(&tenant_data_dir, p));
+
+    language_detect::apply(&mut cv_data);
+    let profile_dir = tenant_data_dir.join(&profile);
+    if let Err(e) = create_profile_from_cv_data(&profile_dir, &cv_data, &profile).await {
+        app_log!(error, "Failed to create profile from notes: {}", e);
+        return Err(Json(StandardErrorResponse::new(
+            "Failed to create profile from notes".to_string(),
+            "PROFILE_CREATE_ERROR".to_string(),
+            vec!["Try again or contact support".to_string()],
+            conversation_id,
+        )));
+    }
+
+    app_log!(
+        info,
+        "Profile {} created from notes by {} (tenant: {})",
+        profile,
+        user.email,
+        tenant.tenant_name
+    );
+
+    crate::core::event_export::emit(
+        crate::core::event_export::EventKind::CvImported,
+        &tenant.tenant_name,
+        &user.email,
+        serde_json::json!({
+            "profile": profile,
+            "source": "notes",
+            "chars": notes.len(),
+        }),
+    );
+
+    let result = ProfileFromNotesResult {
+        profile: profile.clone(),
+        name: cv_data.personal_info.name.clone(),
+        title: cv_data.personal_info.title.clone(),
+        experiences: cv_data.work_experience.len(),
+        language: cv_data.metadata.language.clone(),
+    };
+
+    Ok(Json(DataResponse::success(
+        format!(
+            "Profile '{}' created from notes — review the details before generating a CV",
+            profile
+        ),
+        result,
+        conversation_id,
+    )))
+}
@@ -1,35 +1,82 @@
 // src/web/handlers/cv_handlers/generate.rs
 //! CV PDF generation handler
 use crate::auth::AuthenticatedUser;
+use crate::core::asset_check::MissingAssets;
+use crate::core::config_manager;
 use crate::core::database::{get_tenant_folder_path, DatabaseConfig};
+use crate::core::experiences_fallback;
+use crate::core::generation_dedup::{self, Flight};
+use crate::core::generation_lock::GenerationBusy;
+use crate::core::generation_scheduler;
+use crate::core::profile_lookup;
+use crate::core::profile_watch::WatchChange;
+use crate::core::template_engine::MissingDependency;
+use crate::core::template_features;
+use crate::core::tenant_templates;
+use crate::core::translation_status;
+use crate::core::typst_compiler::CompileFailed;
 use crate::core::{FsOps, TemplateEngine};
-use crate::web::handlers::payment_handlers::check_and_deduct_credits;
+use crate::web::handlers::payment_handlers::{check_and_deduct_credits, refund_credits};
+use crate::web::handlers::common::require_feature;
+use crate::web::handlers::system_handlers::ensure_storage_available;
 use crate::image_validator::ImageValidator;
 use crate::utils::{normalize_language, normalize_profile_name};
-use crate::web::types::WithConversationId;
+use crate::web::proxy::ProxyInfo;
+use crate::web::types::{ClientRequestId, WithConversationId};
 use crate::web::types::{
     GeneratePdfResponse, GenerateRequest, ResponseType, ServerConfig, StandardErrorResponse, StandardRequest,
 };
-use crate::{CvConfig, CvGenerator};
+use crate::config::OutputFormat;
+use crate::web::types::DocxResponse;
+use crate::workspace::{DocxUnavailable, InvalidAsset};
+use crate::{CvConfig, CvGenerator, GeneratedCv};
 use graflog::{app_log, app_span};
+use rocket::http::Header;
+use rocket::response::content::RawHtml;
 use rocket::serde::json::Json;
 use rocket::State;
-use std::env;
 
-use super::helpers::normalize_template;
+use super::generate_languages::LanguagesResponse;
+use super::helpers::{
+    approval_gate, draft_timestamp, normalize_template, notify_watchers, pdf_metadata,
+    person_inputs,
+};
+
+/// What `/generate` answers, depending on `format` and `langs`.
+#[derive(rocket::Responder)]
+pub enum GenerateResponse {
+    Link(Json<GeneratePdfResponse>, Header<'static>),
+    Docx(DocxResponse),
+    Html(RawHtml<String>),
+    Languages(LanguagesResponse),
+}
+
+impl GenerateResponse {
+    /// The link to a generated PDF, with a `Cache-Status` (RFC 9211) saying
+    /// whether it was recompiled.
+    pub fn link(response: Json<GeneratePdfResponse>) -> Self {
+        let status = if response.cache_hit {
+            "cvenom; hit"
+        } else {
+            "cvenom; fwd=miss"
+        };
+        Self::Link(response, Header::new("Cache-Status", status))
+    }
+}
 
 pub async fn generate_cv_handler(
     request: Json<StandardRequest<GenerateRequest>>,
     auth: AuthenticatedUser,
     config: &State<ServerConfig>,
     db_config: &State<DatabaseConfig>,
+    proxy: ProxyInfo,
+    request_id: ClientRequestId,
 ) -> Result<Json<GeneratePdfResponse>, Json<StandardErrorResponse>> {
     let user = auth.user();
     let tenant = auth.tenant();
     let conversation_id = request.conversation_id();
 
-    // PDF generation — 20 credits per generate
-    check_and_deduct_credits(&user.email, 20, conversation_id.clone(), "cv_generation").await?;
+    ensure_storage_available(config, conversation_id.clone())?;
 
     let generate_span = app_span!("cv_generation",
         user_email = %user.email,
@@ -40,7 +87,10 @@ pub async fn generate_cv_handler(
     );
     let _enter = generate_span.enter();
 
-    let template_manager = match TemplateEngine::new(config.templates_dir.clone()) {
+    let tenant_templates = tenant_templates::dir(&config.data_dir, auth.email(), auth.tenant_id());
+    let template_manager = match TemplateEngine::new(config.templates_dir.clone())
+        .map(|engine| engine.with_tenant_templates(&tenant_templates))
+    {
         Ok(manager) => {
             app_log!(
                 info,
@@ -69,8 +119,58 @@ pub async fn generate_cv_handler(
     };
 
     let lang = normalize_language(request.data.lang.as_deref());
-    let template_id = normalize_template(request.data.template.as_deref(), &template_manager);
+    let template_id = normalize_template(
+        request.data.template.as_deref(),
+        &template_manager,
+        conversation_id.clone(),
+    )?;
     let normalized_profile = normalize_profile_name(&request.data.profile);
+    let Some(format) = OutputFormat::parse(request.data.format.as_deref()) else {
+        return Err(Json(StandardErrorResponse::new(
+            format!(
+                "Unsupported format '{}'",
+                request.data.format.as_deref().unwrap_or_default()
+            ),
+            "INVALID_FORMAT".to_string(),
+            vec!["Use \"pdf\", \"docx\" or \"html\"".to_string()],
+            conversation_id,
+        )));
+    };
+    let draft = request.data.draft.unwrap_or(false);
+    if draft && format != OutputFormat::Pdf {
+        return Err(Json(StandardErrorResponse::new(
+            format!("A {} can't be marked as a draft", format.extension().to_uppercase()),
+            "INVALID_FORMAT".to_string(),
+            vec!["Generate a PDF, or remove 'draft'".to_string()],
+            conversation_id,
+        )));
+    }
+    let pdf_a = request.data.pdf_a.unwrap_or(false);
+    if pdf_a {
+        require_feature(config_manager::features().pdf_a, "pdf_a", conversation_id.clone())?;
+    }
+    if pdf_a && format != OutputFormat::Pdf {
+        return Err(Json(StandardErrorResponse::new(
+            format!("A {} can't be exported as PDF/A", format.extension().to_uppercase()),
+            "INVALID_FORMAT".to_string(),
+            vec!["Generate a PDF, or remove 'pdf_a'".to_string()],
+            conversation_id,
+        )));
+    }
+    let template_supports_format = template_manager
+        .get_template(&template_id)
+        .is_none_or(|t| t.manifest.supports_format(format));
+    if !template_supports_format {
+        return Err(Json(StandardErrorResponse::new(
+            format!("Template '{}' has no {} output", template_id, format.extension().to_uppercase()),
+            "FORMAT_NOT_SUPPORTED".to_string(),
+            vec![
+                "GET /templates lists the formats of each template".to_string(),
+                "Or generate a PDF".to_string(),
+            ],
+            conversation_id,
+        )));
+    }
 
     app_log!(
         info,
@@ -103,6 +203,10 @@ pub async fn generate_cv_handler(
         )));
     }
 
+    // `elodie` finds `élodie`; see `profile_lookup`.
+    let normalized_profile = profile_lookup::resolve(&tenant_data_dir, &normalized_profile)
+        .await
+        .unwrap_or(normalized_profile);
     let profile_dir = tenant_data_dir.join(&normalized_profile);
     app_log!(
         debug,
@@ -152,54 +256,195 @@ pub async fn generate_cv_handler(
         );
     }
 
+    let watermark = approval_gate(&auth, db_config, &profile_dir, conversation_id.clone()).await?;
+    // Only a PDF carries the DRAFT stamp, so only approved profiles get
+    // another format.
+    if watermark && format != OutputFormat::Pdf {
+        return Err(Json(StandardErrorResponse::new(
+            format!(
+                "'{}' must be approved before it can be exported as {}",
+                normalized_profile,
+                format.extension().to_uppercase()
+            ),
+            "APPROVAL_REQUIRED".to_string(),
+            vec![
+                format!(
+                    "Request a review with POST /profiles/{}/approval/request-review",
+                    normalized_profile
+                ),
+                "Or generate a PDF, marked DRAFT".to_string(),
+            ],
+            conversation_id,
+        )));
+    }
+
+    let output_dir = config.person_output_dir(&user.email, &normalized_profile);
     app_log!(info, "Creating CV configuration, profile: {}, lang: {}, template: {}, data_dir: {}, output_dir: {}, templates_dir: {}",
-        normalized_profile, lang, template_id, tenant_data_dir.display(), config.output_dir.display(), config.templates_dir.display()
+        normalized_profile, lang, template_id, tenant_data_dir.display(), output_dir.display(), config.templates_dir.display()
     );
 
-    let mut cv_config = CvConfig::new(&normalized_profile, &lang)
+    let cv_config = CvConfig::new(&normalized_profile, &lang)
         .with_template(template_id.to_string())
         .with_data_dir(tenant_data_dir.clone())
-        .with_output_dir(config.output_dir.clone())
+        .with_output_dir(output_dir.clone())
         .with_templates_dir(config.templates_dir.clone())
-        .with_custom_colors(request.data.use_custom_colors.unwrap_or(false));
-
-    // Optional brand selection: load it from the tenant brand library and
-    // attach. Unknown / empty slug = no brand (current behavior).
-    if let Some(slug) = request.data.brand_slug.as_deref() {
-        let slug = slug.trim();
-        if !slug.is_empty() && slug != "default" {
-            match crate::core::brand_store::load_brand(&tenant_data_dir, slug) {
-                Ok(brand) => {
-                    let brand_dir = tenant_data_dir.join("brands").join(slug);
-                    app_log!(info, "Applying brand '{}' for this generation", brand.name);
-                    cv_config = cv_config.with_brand(brand, brand_dir);
-                }
-                Err(e) => {
-                    app_log!(warn, "Requested brand '{}' not found: {}", slug, e);
-                    return Err(Json(StandardErrorResponse::new(
-                        format!("Brand '{}' not found", slug),
-                        "BRAND_NOT_FOUND".to_string(),
-                        vec!["Pick an existing brand or remove the selection".to_string()],
-                        conversation_id,
-                    )));
-                }
-            }
+        .with_tenant_templates(tenant_templates)
+        .with_custom_colors(request.data.use_custom_colors.unwrap_or(false))
+        .with_strict_assets(request.data.strict_assets.unwrap_or(false))
+        .with_strict(request.data.strict.unwrap_or(false))
+        .with_format(format)
+        .with_pdf_a(pdf_a)
+        .with_pdf_metadata(pdf_metadata(&auth, &profile_dir).await)
+        .with_inputs(person_inputs(&auth, db_config, &normalized_profile).await);
+
+    let (mut cv_config, mut warnings) = select_features(
+        cv_config,
+        &template_manager,
+        &template_id,
+        request.data.features.as_deref(),
+    );
+    if watermark {
+        cv_config = cv_config.with_watermark("DRAFT".to_string());
+        warnings.push("The profile is not approved, so the CV is marked DRAFT".to_string());
+    }
+    if draft {
+        cv_config = cv_config.with_draft(draft_timestamp());
+    }
+    if let Some(original) = translation_status::copied_from(&profile_dir, &lang) {
+        warnings.push(format!(
+            "experiences_{}.typ is an untranslated copy of experiences_{}.typ",
+            lang, original
+        ));
+    }
+    match experiences_fallback::resolve(&profile_dir, &lang) {
+        Ok(None) => {}
+        Ok(Some(fallback)) => warnings.push(fallback.warning(&lang)),
+        Err(missing) => {
+            return Err(Json(StandardErrorResponse::new(
+                missing.to_string(),
+                "EXPERIENCES_NOT_FOUND".to_string(),
+                vec![
+                    format!("Translate the experiences into '{}'", lang),
+                    "Or generate the CV in a language the profile has".to_string(),
+                ],
+                conversation_id,
+            )));
         }
     }
 
+    let cv_config = apply_brand(
+        cv_config,
+        &tenant_data_dir,
+        request.data.brand_slug.as_deref(),
+        conversation_id.clone(),
+    )?;
+
+    // Double-clicks from the studio: an identical generation already running
+    // is joined instead of compiled (and charged) a second time.
+    let request_hash = generation_dedup::request_hash(
+        &profile_dir,
+        &cv_config,
+        request.data.brand_slug.as_deref(),
+    );
+    let flight_key = generation_dedup::generation_key(
+        &format!("tenant-{}", tenant.id),
+        &normalized_profile,
+        &template_id,
+        &lang,
+        &request_hash,
+    );
+    let leader = match generation_dedup::join(&flight_key) {
+        Flight::Leader(leader) => leader,
+        Flight::Follower(flight) => {
+            app_log!(
+                info,
+                "Identical generation in flight, joining it, profile: {}, key: {}",
+                normalized_profile,
+                flight_key
+            );
+            return match flight.await {
+                Ok(generated) => Ok(Json(generated_response(
+                    &generated,
+                    &output_dir,
+                    config,
+                    &proxy,
+                    normalized_profile,
+                    warnings,
+                    conversation_id,
+                ))),
+                Err(e) => Err(generation_failed(&e, &normalized_profile, conversation_id)),
+            };
+        }
+    };
+
     let pdf_gen_span = app_span!("pdf_generation", profile = %normalized_profile);
     let _pdf_enter = pdf_gen_span.enter();
 
     match CvGenerator::new(cv_config) {
         Ok(generator) => {
             app_log!(info, "CV generator created successfully");
-            match generator.generate().await {
-                Ok(output_path) => {
-                    let filename = output_path
-                        .file_name()
-                        .and_then(|n| n.to_str())
-                        .unwrap_or("cv.pdf")
-                        .to_string();
+            // A CV that is still current is served without a slot and is
+            // not charged.
+            let outcome = match generator.cached().await {
+                Some(cached) => {
+                    app_log!(
+                        info,
+                        "Nothing changed since {} was generated, serving it free of charge",
+                        cached.output_path.display()
+                    );
+                    leader.run(async move { Ok(cached) }).await
+                }
+                None => {
+                    // PDF generation — 20 credits per generate
+                    check_and_deduct_credits(&user.email, 20, conversation_id.clone(), "cv_generation")
+                        .await?;
+                    // The person first, then a slot: waiting for a busy
+                    // person never holds one of the tenant's slots.
+                    if let Err(busy) = generator.lock().await {
+                        refund_credits(&user.email, 20, "cv_generation");
+                        return Err(generation_failed(&busy.into(), &normalized_profile, conversation_id));
+                    }
+                    // Only the wait for a slot is cancellable; a started
+                    // compilation runs to completion for any followers.
+                    let cancel = request_id.register(auth.email());
+                    let permit = match cancel
+                        .token()
+                        .run(generation_scheduler::acquire_for(tenant))
+                        .await
+                    {
+                        Ok(permit) => permit,
+                        Err(_) => {
+                            app_log!(info, "Generation cancelled while queued, profile: {}", normalized_profile);
+                            refund_credits(&user.email, 20, "cv_generation");
+                            return Err(Json(StandardErrorResponse::cancelled(conversation_id)));
+                        }
+                    };
+                    app_log!(
+                        info,
+                        "Generation slot granted, tenant: {}, queue_wait_ms: {}",
+                        tenant.tenant_name,
+                        permit.waited.as_millis()
+                    );
+                    leader
+                        .run(async move {
+                            let _permit = permit;
+                            generator.generate_report().await
+                        })
+                        .await
+                }
+            };
+            match outcome {
+                Ok(generated) => {
+                    let response = generated_response(
+                        &generated,
+                        &output_dir,
+                        config,
+                        &proxy,
+                        normalized_profile.clone(),
+                        warnings,
+                        conversation_id,
+                    );
+                    let filename = response.filename.clone();
 
                     app_log!(
                         info,
@@ -208,16 +453,12 @@ pub async fn generate_cv_handler(
                         filename
                     );
 
-                    let base_url = env::var("PUBLIC_BASE_URL")
-                        .unwrap_or_else(|_| "https://api.cvenom.com".to_string());
-                    let pdf_url = format!("{}/outputs/{}", base_url, filename);
-
                     crate::email::send_email_with_prefs(
                         &user.email,
                         crate::email::EmailKind::CvReady {
                             profile: normalized_profile.clone(),
                             filename: filename.clone(),
-                            download_url: pdf_url.clone(),
+                            download_url: response.download_url.clone(),
                         },
                         &lang,
                         auth.email_prefs(),
@@ -230,6 +471,34 @@ pub async fn generate_cv_handler(
                         },
                     );
 
+                    crate::core::event_export::emit(
+                        crate::core::event_export::EventKind::CvGenerated,
+                        &tenant.tenant_name,
+                        &user.email,
+                        serde_json::json!({
+                            "profile": normalized_profile,
+                            "template": template_id,
+                            "lang": lang,
+                            "filename": filename,
+                        }),
+                    );
+
+                    if let Ok(pool) = db_config.pool() {
+                        crate::core::audit_log::record(
+                            pool,
+                            tenant.id,
+                            &user.email,
+                            crate::core::audit_log::AuditAction::CvGenerated,
+                            &normalized_profile,
+                        );
+                    }
+                    notify_watchers(
+                        &auth,
+                        db_config,
+                        &profile_dir,
+                        WatchChange::CvGenerated { filename: filename.clone() },
+                    );
+
                     // Track first CV generation for the Tier-3 nudge scheduler
                     // and persist the user's preferred language.
                     if let Ok(pool) = db_config.pool() {
@@ -247,34 +516,9 @@ pub async fn generate_cv_handler(
                         });
                     }
 
-                    Ok(Json(GeneratePdfResponse {
-                        response_type: ResponseType::File,
-                        success: true,
-                        message: "CV generated successfully".to_string(),
-                        download_url: pdf_url,
-                        filename,
-                        profile: normalized_profile,
-                        conversation_id,
-                    }))
-                }
-                Err(e) => {
-                    app_log!(
-                        error,
-                        "CV generation failed, profile: {}, error: {}, error_debug: {:?}",
-                        normalized_profile,
-                        e,
-                        e
-                    );
-                    Err(Json(StandardErrorResponse::new(
-                        format!("CV generation failed: {}", e),
-                        "GENERATION_ERROR".to_string(),
-                        vec![
-                            "Check the error details above".to_string(),
-                            "Verify all required files exist".to_string(),
-                        ],
-                        conversation_id,
-                    )))
+                    Ok(Json(response))
                 }
+                Err(e) => Err(generation_failed(&e, &normalized_profile, conversation_id)),
             }
         }
         Err(e) => {
@@ -296,3 +540,237 @@ pub async fn generate_cv_handler(
         }
     }
 }
+
+/// `/generate` with `format: "docx"`: the same generation, answered with the
+/// document instead of a link to it.
+pub async fn generate_docx_handler(
+    request: Json<StandardRequest<GenerateRequest>>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    db_config: &State<DatabaseConfig>,
+    proxy: ProxyInfo,
+    request_id: ClientRequestId,
+) -> Result<DocxResponse, Json<StandardErrorResponse>> {
+    let (data, filename) =
+        generate_file(request, auth, config, db_config, proxy, request_id).await?;
+    Ok(DocxResponse::new(data, filename))
+}
+
+/// `/generate` with `format: "html"`: the self-contained page itself.
+pub async fn generate_html_handler(
+    request: Json<StandardRequest<GenerateRequest>>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    db_config: &State<DatabaseConfig>,
+    proxy: ProxyInfo,
+    request_id: ClientRequestId,
+) -> Result<RawHtml<String>, Json<StandardErrorResponse>> {
+    let (data, _) = generate_file(request, auth, config, db_config, proxy, request_id).await?;
+    Ok(RawHtml(String::from_utf8_lossy(&data).into_owned()))
+}
+
+/// Generate, then read the generated file back.
+async fn generate_file(
+    request: Json<StandardRequest<GenerateRequest>>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    db_config: &State<DatabaseConfig>,
+    proxy: ProxyInfo,
+    request_id: ClientRequestId,
+) -> Result<(Vec<u8>, String), Json<StandardErrorResponse>> {
+    let conversation_id = request.conversation_id();
+    let email = auth.email().to_string();
+    let generated = generate_cv_handler(request, auth, config, db_config, proxy, request_id)
+        .await?
+        .into_inner();
+    let path = config
+        .person_output_dir(&email, &generated.profile)
+        .join(&generated.filename);
+    match tokio::fs::read(&path).await {
+        Ok(data) => Ok((data, generated.filename)),
+        Err(e) => {
+            app_log!(error, "Failed to read {}: {}", path.display(), e);
+            Err(Json(StandardErrorResponse::new(
+                "Generated file could not be read".to_string(),
+                "FILE_ERROR".to_string(),
+                vec!["Generate the CV again".to_string()],
+                conversation_id,
+            )))
+        }
+    }
+}
+
+/// Requested features the template lacks are dropped, not fatal; they are
+/// returned as warnings.
+pub(super) fn select_features(
+    cv_config: CvConfig,
+    template_manager: &TemplateEngine,
+    template_id: &str,
+    requested: Option<&[String]>,
+) -> (CvConfig, Vec<String>) {
+    let Some(requested) = requested else {
+        return (cv_config, Vec::new());
+    };
+    let Some(template) = template_manager.get_template(template_id) else {
+        return (cv_config, Vec::new());
+    };
+    let selection = template_features::select(template_id, &template.manifest, requested);
+    for warning in &selection.warnings {
+        app_log!(info, "Feature warning for {}: {}", cv_config.profile_name, warning);
+    }
+    (cv_config.with_features(selection.enabled), selection.warnings)
+}
+
+/// Optional brand selection: load it from the tenant brand library and
+/// attach. Unknown / empty slug = no brand (current behavior).
+pub(super) fn apply_brand(
+    cv_config: CvConfig,
+    tenant_data_dir: &std::path::Path,
+    brand_slug: Option<&str>,
+    conversation_id: Option<String>,
+) -> Result<CvConfig, Json<StandardErrorResponse>> {
+    let slug = brand_slug.map(str::trim).unwrap_or_default();
+    if slug.is_empty() || slug == "default" {
+        return Ok(cv_config);
+    }
+    match crate::core::brand_store::load_brand(tenant_data_dir, slug) {
+        Ok(brand) => {
+            let brand_dir = tenant_data_dir.join("brands").join(slug);
+            app_log!(info, "Applying brand '{}' for this generation", brand.name);
+            Ok(cv_config.with_brand(brand, brand_dir))
+        }
+        Err(e) => {
+            app_log!(warn, "Requested brand '{}' not found: {}", slug, e);
+            Err(Json(StandardErrorResponse::new(
+                format!("Brand '{}' not found", slug),
+                "BRAND_NOT_FOUND".to_string(),
+                vec!["Pick an existing brand or remove the selection".to_string()],
+                conversation_id,
+            )))
+        }
+    }
+}
+
+/// Success response for a generated PDF — shared by the caller that compiled
+/// it and any identical requests that joined. `warnings` are extended with
+/// the images the generation left out.
+pub(super) fn generated_response(
+    generated: &GeneratedCv,
+    output_dir: &std::path::Path,
+    config: &ServerConfig,
+    proxy: &ProxyInfo,
+    profile: String,
+    mut warnings: Vec<String>,
+    conversation_id: Option<String>,
+) -> GeneratePdfResponse {
+    warnings.extend(generated.skipped_assets.iter().cloned());
+    let filename = generated
+        .output_path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("cv.pdf")
+        .to_string();
+    GeneratePdfResponse {
+        response_type: ResponseType::File,
+        success: true,
+        message: "CV generated successfully".to_string(),
+        download_url: format!(
+            "{}{}",
+            proxy.public_base_url(),
+            config.output_url_path(&output_dir.join(&filename))
+        ),
+        filename,
+        profile,
+        warnings,
+        conversation_id,
+        cache_hit: generated.cached,
+    }
+}
+
+pub(super) fn generation_failed(
+    e: &anyhow::Error,
+    profile: &str,
+    conversation_id: Option<String>,
+) -> Json<StandardErrorResponse> {
+    if e.downcast_ref::<GenerationBusy>().is_some() {
+        app_log!(warn, "CV generation rejected, profile busy: {}", e);
+        return Json(StandardErrorResponse::new(
+            format!("A CV for '{}' is already being generated", profile),
+            "GENERATION_BUSY".to_string(),
+            vec![
+                "Wait for the current generation to finish".to_string(),
+                "Try again in a few seconds".to_string(),
+            ],
+            conversation_id,
+        ));
+    }
+    if let Some(invalid) = e.downcast_ref::<InvalidAsset>() {
+        app_log!(warn, "CV generation failed, profile: {}: {}", profile, invalid);
+        return Json(StandardErrorResponse::new(
+            invalid.to_string(),
+            "INVALID_ASSET".to_string(),
+            vec![
+                format!("Upload a valid PNG or JPEG to replace the {}", invalid.asset.to_lowercase()),
+                "Or generate without strict_assets to leave it out".to_string(),
+            ],
+            conversation_id,
+        ));
+    }
+    if let Some(missing) = e.downcast_ref::<MissingAssets>() {
+        app_log!(warn, "CV generation failed, profile: {}: {}", profile, missing);
+        return Json(StandardErrorResponse::new(
+            missing.to_string(),
+            "MISSING_ASSETS".to_string(),
+            missing.problems.clone(),
+            conversation_id,
+        ));
+    }
+    if e.downcast_ref::<DocxUnavailable>().is_some() {
+        app_log!(error, "DOCX export failed, profile: {}: {}", profile, e);
+        return Json(StandardErrorResponse::new(
+            e.to_string(),
+            "DOCX_UNAVAILABLE".to_string(),
+            vec!["Generate a PDF instead".to_string()],
+            conversation_id,
+        ));
+    }
+    if let Some(missing) = e.downcast_ref::<MissingDependency>() {
+        app_log!(error, "CV generation failed, profile: {}: {}", profile, missing);
+        return Json(StandardErrorResponse::new(
+            missing.to_string(),
+            "MISSING_DEPENDENCY".to_string(),
+            vec![
+                format!("Add '{}' to the profile's files", missing.file),
+                format!("Or ask an admin to ship it with the '{}' template", missing.template),
+            ],
+            conversation_id,
+        ));
+    }
+    if let Some(failed) = e.downcast_ref::<CompileFailed>() {
+        app_log!(warn, "CV compilation failed, profile: {}: {}", profile, failed);
+        let mut suggestions: Vec<String> = failed.errors().map(ToString::to_string).collect();
+        suggestions.push("Fix the file named above, then generate again".to_string());
+        return Json(StandardErrorResponse::new(
+            format!("The CV of '{}' does not compile", profile),
+            "COMPILE_ERROR".to_string(),
+            suggestions,
+            conversation_id,
+        ));
+    }
+    app_log!(
+        error,
+        "CV generation failed, profile: {}, error: {}, error_debug: {:?}",
+        profile,
+        e,
+        e
+    );
+    Json(StandardErrorResponse::new(
+        format!("CV generation failed: {}", e),
+        "GENERATION_ERROR".to_string(),
+        vec![
+            "Check the error details above".to_string(),
+            "Verify all required files exist".to_string(),
+        ],
+        conversation_id,
+    ))
+}
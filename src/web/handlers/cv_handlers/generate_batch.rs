@@ -0,0 +1,262 @@
+// src/web/handlers/cv_handlers/generate_batch.rs
+//! `POST /generate/batch`: the CVs of several persons, all of the user's or
+//! those of a smart list, in one ZIP.
+//!
+//! Each person goes through `/generate` (approval, credits, the generation
+//! queue and its cache), `BATCH_CONCURRENCY` persons at a time. A person
+//! that fails does not fail the batch: the archive's `manifest.json` lists
+//! every person with the file generated for them or the error.
+
+use std::io::Write;
+
+use futures::stream::{self, StreamExt};
+use graflog::app_log;
+use rocket::serde::json::Json;
+use rocket::State;
+use serde::{Deserialize, Serialize};
+
+use crate::auth::AuthenticatedUser;
+use crate::core::database::{get_tenant_folder_path, DatabaseConfig};
+use crate::core::FsOps;
+use crate::utils::normalize_profile_name;
+use crate::web::handlers::system_handlers::ensure_storage_available;
+use crate::web::proxy::ProxyInfo;
+use crate::web::types::{
+    ClientRequestId, GenerateRequest, ServerConfig, StandardErrorResponse, StandardRequest,
+    WithConversationId, ZipResponse,
+};
+
+use super::generate::generate_cv_handler;
+use super::smart_lists::smart_list_persons;
+
+pub const MAX_BATCH_PERSONS: usize = 100;
+const BATCH_CONCURRENCY: usize = 3;
+const MANIFEST_FILE: &str = "manifest.json";
+
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct BatchGenerateRequest {
+    /// Persons to generate; ignored with `all` or `smart_list`.
+    #[serde(default)]
+    pub persons: Vec<String>,
+    /// The persons of this smart list (see `/smart-lists`).
+    pub smart_list: Option<String>,
+    /// Every person of the user.
+    #[serde(default)]
+    pub all: bool,
+    pub lang: Option<String>,
+    pub template: Option<String>,
+    pub use_custom_colors: Option<bool>,
+    pub brand_slug: Option<String>,
+    pub draft: Option<bool>,
+    pub pdf_a: Option<bool>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct BatchEntry {
+    pub person: String,
+    /// File of the archive holding the CV.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filename: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_code: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+}
+
+/// `manifest.json` of the archive.
+#[derive(Debug, Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct BatchManifest {
+    pub generated: usize,
+    pub failed: usize,
+    pub persons: Vec<BatchEntry>,
+}
+
+fn err(code: &str, msg: String, suggestions: Vec<String>, cid: Option<String>) -> Json<StandardErrorResponse> {
+    Json(StandardErrorResponse::new(msg, code.to_string(), suggestions, cid))
+}
+
+/// Requested persons, normalized, without repeats, in order.
+fn requested_persons(persons: &[String]) -> Vec<String> {
+    let mut normalized: Vec<String> = Vec::new();
+    for person in persons.iter().map(|p| normalize_profile_name(p.trim())) {
+        if !person.is_empty() && !normalized.contains(&person) {
+            normalized.push(person);
+        }
+    }
+    normalized
+}
+
+/// The generated files under their own names, then the manifest.
+fn zip_batch(files: &[(String, Vec<u8>)], manifest: &BatchManifest) -> anyhow::Result<Vec<u8>> {
+    let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    let options = zip::write::FileOptions::default();
+    for (filename, data) in files {
+        zip.start_file(filename.as_str(), options)?;
+        zip.write_all(data)?;
+    }
+    zip.start_file(MANIFEST_FILE, options)?;
+    zip.write_all(&serde_json::to_vec_pretty(manifest)?)?;
+    Ok(zip.finish()?.into_inner())
+}
+
+pub async fn generate_batch_handler(
+    request: Json<StandardRequest<BatchGenerateRequest>>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    db_config: &State<DatabaseConfig>,
+    proxy: ProxyInfo,
+) -> Result<ZipResponse, Json<StandardErrorResponse>> {
+    let conversation_id = request.conversation_id();
+    ensure_storage_available(config, conversation_id.clone())?;
+
+    let persons = if let Some(list) = &request.data.smart_list {
+        smart_list_persons(list, &auth, config, db_config).await?
+    } else if request.data.all {
+        let tenant_data_dir = get_tenant_folder_path(auth.email(), &config.data_dir);
+        FsOps::list_profiles(&tenant_data_dir).await.map_err(|e| {
+            app_log!(error, "Failed to list profiles: {}", e);
+            err(
+                "LIST_ERROR",
+                "Failed to list your persons".to_string(),
+                vec!["Try again or contact support".to_string()],
+                conversation_id.clone(),
+            )
+        })?
+    } else {
+        requested_persons(&request.data.persons)
+    };
+    if persons.is_empty() || persons.len() > MAX_BATCH_PERSONS {
+        return Err(err(
+            "INVALID_PERSONS",
+            format!("A batch has between 1 and {} persons", MAX_BATCH_PERSONS),
+            vec!["List the persons in 'persons', or send 'all': true or a 'smart_list'".to_string()],
+            conversation_id,
+        ));
+    }
+    app_log!(
+        info,
+        "{} requested a batch of {} CVs",
+        auth.email(),
+        persons.len()
+    );
+
+    let data = &request.data;
+    let outcomes: Vec<(String, Result<_, Json<StandardErrorResponse>>)> = stream::iter(persons)
+        .map(|person| {
+            let single = StandardRequest {
+                data: GenerateRequest {
+                    profile: person.clone(),
+                    lang: data.lang.clone(),
+                    template: data.template.clone(),
+                    use_custom_colors: data.use_custom_colors,
+                    brand_slug: data.brand_slug.clone(),
+                    features: None,
+                    strict_assets: None,
+                    strict: None,
+                    format: None,
+                    langs: None,
+                    archive: None,
+                    draft: data.draft,
+                    pdf_a: data.pdf_a,
+                },
+                conversation_id: None,
+            };
+            let (auth, proxy) = (auth.clone(), proxy.clone());
+            async move {
+                let outcome = generate_cv_handler(
+                    Json(single),
+                    auth,
+                    config,
+                    db_config,
+                    proxy,
+                    ClientRequestId(None),
+                )
+                .await;
+                (person, outcome)
+            }
+        })
+        .buffered(BATCH_CONCURRENCY)
+        .collect()
+        .await;
+
+    let mut files = Vec::new();
+    let mut entries = Vec::new();
+    for (person, outcome) in outcomes {
+        let generated = match outcome {
+            Ok(generated) => generated.into_inner(),
+            Err(e) => {
+                entries.push(BatchEntry {
+                    person,
+                    filename: None,
+                    error: Some(e.error.clone()),
+                    error_code: Some(e.error_code.clone()),
+                    warnings: Vec::new(),
+                });
+                continue;
+            }
+        };
+        let path = config
+            .person_output_dir(auth.email(), &generated.profile)
+            .join(&generated.filename);
+        match tokio::fs::read(&path).await {
+            Ok(pdf) => {
+                files.push((generated.filename.clone(), pdf));
+                entries.push(BatchEntry {
+                    person,
+                    filename: Some(generated.filename),
+                    error: None,
+                    error_code: None,
+                    warnings: generated.warnings,
+                });
+            }
+            Err(e) => {
+                app_log!(error, "Failed to read {}: {}", path.display(), e);
+                entries.push(BatchEntry {
+                    person,
+                    filename: None,
+                    error: Some("Generated PDF could not be read".to_string()),
+                    error_code: Some("FILE_ERROR".to_string()),
+                    warnings: Vec::new(),
+                });
+            }
+        }
+    }
+
+    let manifest = BatchManifest {
+        generated: files.len(),
+        failed: entries.len() - files.len(),
+        persons: entries,
+    };
+    app_log!(
+        info,
+        "Batch for {}: {} generated, {} failed",
+        auth.email(),
+        manifest.generated,
+        manifest.failed
+    );
+    let zip = zip_batch(&files, &manifest).map_err(|e| {
+        err(
+            "ZIP_ERROR",
+            format!("Failed to package the CVs: {}", e),
+            vec![],
+            conversation_id.clone(),
+        )
+    })?;
+    Ok(ZipResponse::new(zip, "cvs.zip".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn persons_are_normalized_once_in_order() {
+        let persons = ["John Doe", "", "jane_doe", "john doe"].map(String::from);
+        assert_eq!(requested_persons(&persons), vec!["john_doe", "jane_doe"]);
+    }
+}
@@ -0,0 +1,386 @@
+// src/web/handlers/cv_handlers/generate_languages.rs
+//! `/generate` with `langs`: one person's CV in several languages from a
+//! single request.
+//!
+//! The workspace (template, pictures, logos) is prepared once and compiled
+//! once per language, so a bilingual CV takes little more than one. Each PDF
+//! is kept in the output directory like any generation, so it shows in the
+//! history; `archive: true` also returns them together as a ZIP. A language
+//! that fails is reported next to the others. Identical requests are not
+//! joined as for a single language.
+
+use std::io::Write;
+
+use graflog::app_log;
+use rocket::serde::json::Json;
+use rocket::State;
+use serde::Serialize;
+
+use crate::auth::AuthenticatedUser;
+use crate::config::OutputFormat;
+use crate::core::database::{get_tenant_folder_path, DatabaseConfig};
+use crate::core::profile_watch::WatchChange;
+use crate::core::{
+    config_manager, experiences_fallback, generation_scheduler, profile_lookup, tenant_templates,
+    translation_status, TemplateEngine,
+};
+use crate::utils::{normalize_language, normalize_profile_name};
+use crate::web::handlers::payment_handlers::{check_and_deduct_credits, refund_credits};
+use crate::web::handlers::common::require_feature;
+use crate::web::handlers::system_handlers::ensure_storage_available;
+use crate::web::proxy::ProxyInfo;
+use crate::web::types::{
+    ClientRequestId, DataResponse, GenerateRequest, ServerConfig, StandardErrorResponse,
+    StandardRequest, WithConversationId, ZipResponse,
+};
+use crate::{CvConfig, CvGenerator, GeneratedCv};
+
+use super::generate::{apply_brand, generated_response, generation_failed, select_features};
+use super::helpers::{
+    approval_gate, draft_timestamp, normalize_template, notify_watchers, pdf_metadata,
+    person_inputs,
+};
+
+pub const MAX_LANGUAGES: usize = 4;
+const CREDITS_PER_LANGUAGE: i64 = 20;
+
+#[derive(Debug, Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct LanguageOutput {
+    pub lang: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filename: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub download_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct LanguageGeneration {
+    pub profile: String,
+    pub outputs: Vec<LanguageOutput>,
+    pub warnings: Vec<String>,
+}
+
+#[derive(rocket::Responder)]
+pub enum LanguagesResponse {
+    Json(Json<DataResponse<LanguageGeneration>>),
+    Zip(ZipResponse),
+}
+
+fn err(code: &str, msg: String, suggestions: Vec<String>, cid: Option<String>) -> Json<StandardErrorResponse> {
+    Json(StandardErrorResponse::new(msg, code.to_string(), suggestions, cid))
+}
+
+/// Requested languages, normalized, without repeats, in order.
+fn requested_languages(langs: &[String]) -> Vec<String> {
+    let mut normalized: Vec<String> = Vec::new();
+    for lang in langs.iter().map(|l| l.trim()).filter(|l| !l.is_empty()) {
+        let lang = normalize_language(Some(lang));
+        if !normalized.contains(&lang) {
+            normalized.push(lang);
+        }
+    }
+    normalized
+}
+
+/// The generated files, under their own names.
+fn zip_outputs(files: &[(String, Vec<u8>)]) -> anyhow::Result<Vec<u8>> {
+    let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    let options = zip::write::FileOptions::default();
+    for (filename, data) in files {
+        zip.start_file(filename.as_str(), options)?;
+        zip.write_all(data)?;
+    }
+    Ok(zip.finish()?.into_inner())
+}
+
+pub async fn generate_languages_handler(
+    request: Json<StandardRequest<GenerateRequest>>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    db_config: &State<DatabaseConfig>,
+    proxy: ProxyInfo,
+    request_id: ClientRequestId,
+) -> Result<LanguagesResponse, Json<StandardErrorResponse>> {
+    let user = auth.user();
+    let tenant = auth.tenant();
+    let conversation_id = request.conversation_id();
+
+    ensure_storage_available(config, conversation_id.clone())?;
+    if request.data.pdf_a.unwrap_or(false) {
+        require_feature(config_manager::features().pdf_a, "pdf_a", conversation_id.clone())?;
+    }
+
+    if OutputFormat::parse(request.data.format.as_deref()) != Some(OutputFormat::Pdf) {
+        return Err(err(
+            "INVALID_FORMAT",
+            "Several languages can only be generated as PDF".to_string(),
+            vec!["Remove 'format' or request one language at a time".to_string()],
+            conversation_id,
+        ));
+    }
+
+    let langs = requested_languages(request.data.langs.as_deref().unwrap_or_default());
+    if langs.is_empty() || langs.len() > MAX_LANGUAGES {
+        return Err(err(
+            "INVALID_LANGUAGES",
+            format!("Request between 1 and {} languages", MAX_LANGUAGES),
+            vec!["List the languages in 'langs', e.g. [\"en\", \"fr\"]".to_string()],
+            conversation_id,
+        ));
+    }
+
+    let tenant_templates = tenant_templates::dir(&config.data_dir, auth.email(), auth.tenant_id());
+    let template_manager = TemplateEngine::new(config.templates_dir.clone())
+        .map(|engine| engine.with_tenant_templates(&tenant_templates))
+        .map_err(|e| {
+            err(
+                "TEMPLATE_INIT_ERROR",
+                format!("Template system error: {}", e),
+                vec!["Contact system administrator".to_string()],
+                conversation_id.clone(),
+            )
+        })?;
+    let template_id = normalize_template(
+        request.data.template.as_deref(),
+        &template_manager,
+        conversation_id.clone(),
+    )?;
+
+    let tenant_data_dir = get_tenant_folder_path(&user.email, &config.data_dir);
+    let profile = normalize_profile_name(&request.data.profile);
+    let profile = profile_lookup::resolve(&tenant_data_dir, &profile)
+        .await
+        .unwrap_or(profile);
+    let profile_dir = tenant_data_dir.join(&profile);
+    if !profile_dir.exists() {
+        return Err(err(
+            "PROFILE_NOT_FOUND",
+            format!("Profile '{}' not found in your account", request.data.profile),
+            vec!["Check the profile name spelling".to_string()],
+            conversation_id,
+        ));
+    }
+
+    let watermark = approval_gate(&auth, db_config, &profile_dir, conversation_id.clone()).await?;
+
+    let output_dir = config.person_output_dir(&user.email, &profile);
+    let cv_config = CvConfig::new(&profile, &langs[0])
+        .with_template(template_id.clone())
+        .with_data_dir(tenant_data_dir.clone())
+        .with_output_dir(output_dir.clone())
+        .with_templates_dir(config.templates_dir.clone())
+        .with_tenant_templates(tenant_templates)
+        .with_custom_colors(request.data.use_custom_colors.unwrap_or(false))
+        .with_strict_assets(request.data.strict_assets.unwrap_or(false))
+        .with_strict(request.data.strict.unwrap_or(false))
+        .with_pdf_a(request.data.pdf_a.unwrap_or(false))
+        .with_pdf_metadata(pdf_metadata(&auth, &profile_dir).await)
+        .with_inputs(person_inputs(&auth, db_config, &profile).await);
+    let (mut cv_config, mut warnings) = select_features(
+        cv_config,
+        &template_manager,
+        &template_id,
+        request.data.features.as_deref(),
+    );
+    if watermark {
+        cv_config = cv_config.with_watermark("DRAFT".to_string());
+        warnings.push("The profile is not approved, so the CVs are marked DRAFT".to_string());
+    }
+    if request.data.draft.unwrap_or(false) {
+        cv_config = cv_config.with_draft(draft_timestamp());
+    }
+    for lang in &langs {
+        if let Some(original) = translation_status::copied_from(&profile_dir, lang) {
+            warnings.push(format!(
+                "experiences_{}.typ is an untranslated copy of experiences_{}.typ",
+                lang, original
+            ));
+        }
+        if let Ok(Some(fallback)) = experiences_fallback::resolve(&profile_dir, lang) {
+            warnings.push(fallback.warning(lang));
+        }
+    }
+    let cv_config = apply_brand(
+        cv_config,
+        &tenant_data_dir,
+        request.data.brand_slug.as_deref(),
+        conversation_id.clone(),
+    )?;
+
+    let generator = CvGenerator::new(cv_config).map_err(|e| {
+        err(
+            "CONFIG_ERROR",
+            format!("CV generator initialization failed: {}", e),
+            vec![format!("Check that the profile has experiences in '{}'", langs[0])],
+            conversation_id.clone(),
+        )
+    })?;
+
+    let cost = CREDITS_PER_LANGUAGE * langs.len() as i64;
+    check_and_deduct_credits(&user.email, cost, conversation_id.clone(), "cv_generation").await?;
+
+    // The person first, then a slot: waiting for a busy person never holds
+    // one of the tenant's slots. The languages share one workspace, so they
+    // take one slot.
+    if let Err(busy) = generator.lock().await {
+        refund_credits(&user.email, cost, "cv_generation");
+        return Err(generation_failed(&busy.into(), &profile, conversation_id));
+    }
+    let cancel = request_id.register(auth.email());
+    let Ok(_permit) = cancel
+        .token()
+        .run(generation_scheduler::acquire_for(tenant))
+        .await
+    else {
+        app_log!(info, "Generation cancelled while queued, profile: {}", profile);
+        refund_credits(&user.email, cost, "cv_generation");
+        return Err(Json(StandardErrorResponse::cancelled(conversation_id)));
+    };
+
+    let results = generator.generate_languages(&langs).await.map_err(|e| {
+        refund_credits(&user.email, cost, "cv_generation");
+        generation_failed(&e, &profile, conversation_id.clone())
+    })?;
+
+    let mut generated: Vec<(String, GeneratedCv)> = Vec::new();
+    let mut outputs = Vec::new();
+    for (lang, result) in results {
+        match result {
+            Ok(cv) => generated.push((lang, cv)),
+            Err(e) => outputs.push(LanguageOutput {
+                lang,
+                filename: None,
+                download_url: None,
+                error: Some(e.to_string()),
+            }),
+        }
+    }
+    // Only the languages delivered are paid for.
+    if !outputs.is_empty() {
+        let refund = CREDITS_PER_LANGUAGE * outputs.len() as i64;
+        refund_credits(&user.email, refund, "cv_generation");
+    }
+    if generated.is_empty() {
+        return Err(err(
+            "GENERATION_ERROR",
+            format!("No language of '{}' could be generated", profile),
+            outputs
+                .iter()
+                .filter_map(|o| o.error.as_ref().map(|e| format!("{}: {}", o.lang, e)))
+                .collect(),
+            conversation_id,
+        ));
+    }
+    if let Some((_, cv)) = generated.first() {
+        warnings.extend(cv.skipped_assets.iter().cloned());
+    }
+
+    for (lang, cv) in &generated {
+        let response = generated_response(
+            cv,
+            &output_dir,
+            config,
+            &proxy,
+            profile.clone(),
+            Vec::new(),
+            None,
+        );
+        crate::core::event_export::emit(
+            crate::core::event_export::EventKind::CvGenerated,
+            &tenant.tenant_name,
+            &user.email,
+            serde_json::json!({
+                "profile": profile,
+                "template": template_id,
+                "lang": lang,
+                "filename": response.filename,
+            }),
+        );
+        if let Ok(pool) = db_config.pool() {
+            crate::core::audit_log::record(
+                pool,
+                tenant.id,
+                &user.email,
+                crate::core::audit_log::AuditAction::CvGenerated,
+                &profile,
+            );
+        }
+        notify_watchers(
+            &auth,
+            db_config,
+            &profile_dir,
+            WatchChange::CvGenerated { filename: response.filename.clone() },
+        );
+        outputs.push(LanguageOutput {
+            lang: lang.clone(),
+            filename: Some(response.filename),
+            download_url: Some(response.download_url),
+            error: None,
+        });
+    }
+    outputs.sort_by_key(|o| langs.iter().position(|l| *l == o.lang));
+    app_log!(
+        info,
+        "Generated {} of {} languages for {}",
+        generated.len(),
+        langs.len(),
+        profile
+    );
+
+    if request.data.archive.unwrap_or(false) {
+        let mut files = Vec::new();
+        for (_, cv) in &generated {
+            let filename = cv
+                .output_path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let data = tokio::fs::read(&cv.output_path).await.map_err(|e| {
+                err(
+                    "FILE_ERROR",
+                    format!("Generated PDF could not be read: {}", e),
+                    vec!["Generate the CVs again".to_string()],
+                    conversation_id.clone(),
+                )
+            })?;
+            files.push((filename, data));
+        }
+        let data = zip_outputs(&files).map_err(|e| {
+            err(
+                "ZIP_ERROR",
+                format!("Failed to package the CVs: {}", e),
+                vec![],
+                conversation_id.clone(),
+            )
+        })?;
+        return Ok(LanguagesResponse::Zip(ZipResponse::new(
+            data,
+            format!("{}_{}.zip", profile, langs.join("_")),
+        )));
+    }
+
+    Ok(LanguagesResponse::Json(Json(DataResponse::success(
+        format!("Generated {} of {} languages", generated.len(), langs.len()),
+        LanguageGeneration {
+            profile,
+            outputs,
+            warnings,
+        },
+        conversation_id,
+    ))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn languages_are_normalized_and_deduplicated() {
+        let requested = ["EN", "french", " fr ", "", "en"].map(String::from);
+        assert_eq!(requested_languages(&requested), vec!["en", "fr"]);
+    }
+}
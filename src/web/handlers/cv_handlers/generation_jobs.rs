@@ -0,0 +1,162 @@
+// src/web/handlers/cv_handlers/generation_jobs.rs
+//! Asynchronous generations; see [`crate::core::jobs`].
+
+use graflog::app_log;
+use rocket::serde::json::Json;
+use rocket::State;
+
+use crate::auth::AuthenticatedUser;
+use crate::config::OutputFormat;
+use crate::core::database::DatabaseConfig;
+use crate::core::jobs::{self, GenerationJob, JobStatus};
+use crate::utils::normalize_profile_name;
+use crate::web::handlers::common::{db_error, error_response, pool};
+use crate::web::handlers::system_handlers::ensure_storage_available;
+use crate::web::proxy::ProxyInfo;
+use crate::web::types::{
+    ClientRequestId, DataResponse, GenerateRequest, PdfResponse, ServerConfig,
+    StandardErrorResponse, StandardRequest, WithConversationId,
+};
+
+use super::generate::generate_cv_handler;
+
+/// POST /generate/async
+pub async fn generate_async_handler(
+    request: Json<StandardRequest<GenerateRequest>>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    db_config: &State<DatabaseConfig>,
+    proxy: ProxyInfo,
+) -> Result<Json<DataResponse<GenerationJob>>, Json<StandardErrorResponse>> {
+    let conversation_id = request.conversation_id();
+    ensure_storage_available(config, conversation_id.clone())?;
+
+    let pdf = OutputFormat::parse(request.data.format.as_deref()) == Some(OutputFormat::Pdf);
+    if !pdf || request.data.langs.is_some() {
+        return Err(error_response(
+            "Asynchronous generation produces one PDF".to_string(),
+            "INVALID_FORMAT",
+            vec!["Remove 'format' and 'langs', or use POST /generate".to_string()],
+        ));
+    }
+
+    let pool = pool(db_config)?.clone();
+    let job = jobs::create(
+        &pool,
+        &auth.account_key(),
+        auth.email(),
+        &normalize_profile_name(&request.data.profile),
+    )
+    .await
+    .map_err(db_error)?;
+    app_log!(info, "{} queued generation job {} for {}", auth.email(), job.id, job.profile);
+
+    // The job outlives the request: it runs on copies of the managed state.
+    let id = job.id.clone();
+    let config = config.inner().clone();
+    let db_config = db_config.inner().clone();
+    let request = request.into_inner();
+    tokio::spawn(async move {
+        if let Err(e) = jobs::started(&pool, &id).await {
+            app_log!(warn, "Failed to mark job {} running: {}", id, e);
+        }
+        let outcome = generate_cv_handler(
+            Json(request),
+            auth,
+            (&config).into(),
+            (&db_config).into(),
+            proxy,
+            ClientRequestId(None),
+        )
+        .await;
+        let recorded = match outcome {
+            Ok(generated) => {
+                jobs::succeeded(&pool, &id, &generated.profile, &generated.filename).await
+            }
+            Err(e) => {
+                app_log!(info, "Generation job {} failed: {} ({})", id, e.error, e.error_code);
+                jobs::failed(&pool, &id, &e.error).await
+            }
+        };
+        if let Err(e) = recorded {
+            app_log!(error, "Failed to record the outcome of job {}: {}", id, e);
+        }
+    });
+
+    Ok(Json(DataResponse::success(
+        "Generation queued".to_string(),
+        job,
+        conversation_id,
+    )))
+}
+
+async fn find_job(
+    id: &str,
+    auth: &AuthenticatedUser,
+    db_config: &DatabaseConfig,
+) -> Result<GenerationJob, Json<StandardErrorResponse>> {
+    let pool = pool(db_config)?;
+    jobs::get(pool, auth.email(), id)
+        .await
+        .map_err(db_error)?
+        .ok_or_else(|| {
+            error_response(
+                format!("Generation job '{}' not found", id),
+                "JOB_NOT_FOUND",
+                vec!["Start a generation with POST /generate/async".to_string()],
+            )
+        })
+}
+
+/// GET /jobs/<id>
+pub async fn job_status_handler(
+    id: String,
+    auth: AuthenticatedUser,
+    db_config: &State<DatabaseConfig>,
+) -> Result<Json<DataResponse<GenerationJob>>, Json<StandardErrorResponse>> {
+    let job = find_job(&id, &auth, db_config).await?;
+    Ok(Json(DataResponse::success(
+        format!("Job is {}", job.status.as_str()),
+        job,
+        None,
+    )))
+}
+
+/// GET /jobs/<id>/result
+pub async fn job_result_handler(
+    id: String,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    db_config: &State<DatabaseConfig>,
+) -> Result<PdfResponse, Json<StandardErrorResponse>> {
+    let job = find_job(&id, &auth, db_config).await?;
+    let filename = match (job.status, job.filename) {
+        (JobStatus::Done, Some(filename)) => filename,
+        (JobStatus::Failed, _) => {
+            return Err(error_response(
+                job.error.unwrap_or_else(|| "The generation failed".to_string()),
+                "JOB_FAILED",
+                vec!["Start a new generation".to_string()],
+            ))
+        }
+        (status, _) => {
+            return Err(error_response(
+                format!("The CV is not ready yet (job is {})", status.as_str()),
+                "JOB_NOT_READY",
+                vec![format!("Poll GET /jobs/{} until its status is done", id)],
+            ))
+        }
+    };
+    let path = config
+        .person_output_dir(auth.email(), &job.profile)
+        .join(&filename);
+    match tokio::fs::read(&path).await {
+        Ok(data) => Ok(PdfResponse::with_filename(data, filename)
+            .with_generation_id(crate::utils::generation_id(&path))),
+        Err(_) => Err(error_response(
+            format!("'{}' is no longer available", filename),
+            "FILE_NOT_FOUND",
+            vec!["It may have been deleted; start a new generation".to_string()],
+        )),
+    }
+}
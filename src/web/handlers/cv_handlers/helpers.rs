@@ -2,14 +2,190 @@
 //! Shared utility functions for CV handlers
 
 use crate::{
-    core::{FsOps, TemplateEngine},
+    auth::AuthenticatedUser,
+    core::{
+        database::DatabaseConfig, import_staging::StagedImport, person_tags,
+        profile_approval::{self, ApprovalPolicy}, profile_watch::{self, WatchChange}, language_detect, profile_access::ProfileType, profile_lock,
+        review_calendar, template_health,
+        translation_status::{self, Provenance},
+        typst_compiler::PdfMetadata,
+        FsOps, TemplateEngine,
+    },
     types::cv_data::{CvConverter, CvJson},
+    web::types::StandardErrorResponse,
 };
 use anyhow::Context;
 use graflog::app_log;
-use std::path::Path;
+use rocket::serde::json::Json;
+use std::path::{Path, PathBuf};
 
-/// Create profile directory structure from CvJson data
+pub(super) fn editor_required(message: String, conversation_id: Option<String>) -> Json<StandardErrorResponse> {
+    Json(StandardErrorResponse::new(
+        message,
+        "EDITOR_ROLE_REQUIRED".to_string(),
+        vec!["Ask your account administrator for the editor role".to_string()],
+        conversation_id,
+    ))
+}
+
+/// Reject edits to `profile_dir` the caller's role doesn't allow: members
+/// may only edit their own profile. A missing directory passes — the
+/// handler reports that itself.
+pub fn require_edit_access(
+    auth: &AuthenticatedUser,
+    profile_dir: &Path,
+    conversation_id: Option<String>,
+) -> Result<(), Json<StandardErrorResponse>> {
+    let profile_type = ProfileType::of(profile_dir);
+    if !profile_dir.is_dir() || auth.role().can_edit(profile_type) {
+        return Ok(());
+    }
+    let profile = profile_dir.file_name().unwrap_or_default().to_string_lossy();
+    app_log!(
+        warn,
+        "{} ({}) denied edit of collaborator profile {}",
+        auth.email(),
+        auth.role().as_str(),
+        profile
+    );
+    Err(editor_required(
+        format!("Editing collaborator profile '{}' requires the editor role", profile),
+        conversation_id,
+    ))
+}
+
+/// Apply the tenant's approval policy to generating from `profile_dir`:
+/// `Ok(true)` when the CV must carry a DRAFT watermark.
+pub async fn approval_gate(
+    auth: &AuthenticatedUser,
+    db_config: &DatabaseConfig,
+    profile_dir: &Path,
+    conversation_id: Option<String>,
+) -> Result<bool, Json<StandardErrorResponse>> {
+    let approved = profile_approval::is_approved(profile_dir);
+    gate(auth, db_config, profile_dir, approved, conversation_id).await
+}
+
+/// [`approval_gate`] for a generation that first rewrites the profile (an
+/// optimization, generated projects): its result is never approved. Called
+/// before anything is charged.
+pub async fn unreviewed_gate(
+    auth: &AuthenticatedUser,
+    db_config: &DatabaseConfig,
+    profile_dir: &Path,
+    conversation_id: Option<String>,
+) -> Result<bool, Json<StandardErrorResponse>> {
+    gate(auth, db_config, profile_dir, false, conversation_id).await
+}
+
+async fn gate(
+    auth: &AuthenticatedUser,
+    db_config: &DatabaseConfig,
+    profile_dir: &Path,
+    approved: bool,
+    conversation_id: Option<String>,
+) -> Result<bool, Json<StandardErrorResponse>> {
+    let policy = match db_config.pool() {
+        Ok(pool) => profile_approval::policy_for(pool, auth.tenant_id()).await,
+        Err(_) => ApprovalPolicy::default(),
+    };
+    if policy == ApprovalPolicy::Off || approved {
+        return Ok(false);
+    }
+    if policy == ApprovalPolicy::Watermark {
+        return Ok(true);
+    }
+    let profile = profile_dir.file_name().unwrap_or_default().to_string_lossy();
+    Err(Json(StandardErrorResponse::new(
+        format!("Profile '{}' must be approved before generating", profile),
+        "APPROVAL_REQUIRED".to_string(),
+        vec![format!(
+            "Request a review with POST /profiles/{}/approval/request-review",
+            profile
+        )],
+        conversation_id,
+    )))
+}
+
+/// The person's tags and attributes as `sys.inputs`; none when they can't
+/// be read.
+pub async fn person_inputs(
+    auth: &AuthenticatedUser,
+    db_config: &DatabaseConfig,
+    profile: &str,
+) -> Vec<(String, String)> {
+    let Ok(pool) = db_config.pool() else {
+        return Vec::new();
+    };
+    match person_tags::get(pool, &auth.account_key(), profile).await {
+        Ok(tags) => person_tags::typst_inputs(&tags),
+        Err(e) => {
+            app_log!(warn, "Failed to load the tags of {}: {}", profile, e);
+            Vec::new()
+        }
+    }
+}
+
+/// Properties of a generated PDF: the person's name as author, the tenant as
+/// subject, and the generation time.
+pub async fn pdf_metadata(auth: &AuthenticatedUser, profile_dir: &Path) -> PdfMetadata {
+    let profile = profile_dir.file_name().unwrap_or_default().to_string_lossy();
+    let name = FsOps::read_file_safe(&profile_dir.join("cv_params.toml"))
+        .await
+        .ok()
+        .and_then(|content| toml::from_str::<toml::Value>(&content).ok())
+        .and_then(|params| params.get("name")?.as_str().map(|n| n.trim().to_string()))
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| profile.to_string());
+    PdfMetadata {
+        title: Some(format!("{} - CV", name)),
+        author: Some(name),
+        subject: Some(auth.tenant_name().to_string()),
+        keywords: Vec::new(),
+        created: Some(chrono::Utc::now()),
+    }
+}
+
+/// Generation time printed on a draft CV.
+pub fn draft_timestamp() -> String {
+    chrono::Utc::now().format("%Y-%m-%d %H:%M UTC").to_string()
+}
+
+/// Tell the watchers of `profile_dir` about a change made by `auth`.
+pub fn notify_watchers(
+    auth: &AuthenticatedUser,
+    db_config: &DatabaseConfig,
+    profile_dir: &Path,
+    change: WatchChange,
+) {
+    let (Ok(pool), Some(profile)) = (db_config.pool(), profile_dir.file_name()) else {
+        return;
+    };
+    profile_watch::notify(
+        pool,
+        &auth.account_key(),
+        &profile.to_string_lossy(),
+        auth.email(),
+        change,
+    );
+}
+
+/// Reject profile creation for members: every new profile is a collaborator.
+pub fn require_create_access(
+    auth: &AuthenticatedUser,
+    conversation_id: Option<String>,
+) -> Result<(), Json<StandardErrorResponse>> {
+    if auth.role().can_create() {
+        return Ok(());
+    }
+    Err(editor_required(
+        "Creating profiles requires the editor role".to_string(),
+        conversation_id,
+    ))
+}
+
+/// Create profile directory structure from CvJson data. The experiences are
+/// written in `metadata.language` — see [`language_detect::apply`].
 pub async fn create_profile_from_cv_data(
     profile_dir: &Path,
     cv_data: &CvJson,
@@ -19,45 +195,76 @@ pub async fn create_profile_from_cv_data(
     FsOps::ensure_dir_exists(profile_dir)
         .await
         .context("Failed to create profile directory")?;
+    let _save = profile_lock::write(profile_dir).await;
 
     // Convert CvJson to TOML
-    let toml_content =
-        CvConverter::to_toml(cv_data).context("Failed to convert CV data to TOML")?;
-
     let toml_path = profile_dir.join("cv_params.toml");
+    let existing = FsOps::read_file_safe(&toml_path).await.unwrap_or_default();
+    let toml_content = CvConverter::merge_into_toml(&existing, cv_data)
+        .context("Failed to convert CV data to TOML")?;
+
     FsOps::write_file_safe(&toml_path, &toml_content)
         .await
         .context("Failed to write cv_params.toml")?;
 
-    // Convert CvJson to Typst for both languages
-    let en_typst = CvConverter::to_typst(cv_data, "en")
-        .context("Failed to convert CV data to English Typst")?;
+    let lang = language_detect::metadata_language(cv_data);
+    let typst = CvConverter::to_typst(cv_data, lang)
+        .context("Failed to convert CV data to Typst")?;
 
-    let en_path = profile_dir.join("experiences_en.typ");
-    FsOps::write_file_safe(&en_path, &en_typst)
+    let typst_path = profile_dir.join(format!("experiences_{}.typ", lang));
+    FsOps::write_file_safe(&typst_path, &typst)
         .await
-        .context("Failed to write experiences_en.typ")?;
-
-    // Create README
-    let readme_content = format!(
-        "# {} CV Data\n\n\
-        Add your profile image as `profile.png` in this directory.\n\
-        Add your company logo as `company_logo.png` (optional).\n\n\
-        Edit the following files:\n\
-        - `cv_params.toml` - Personal information, skills, and key insights\n\
-        - `experiences_*.typ` - Work experience for each language\n",
-        profile_name
-    );
+        .with_context(|| format!("Failed to write experiences_{}.typ", lang))?;
+    translation_status::record(profile_dir, lang, Provenance::Original, None);
 
-    let readme_path = profile_dir.join("README.md");
-    FsOps::write_file_safe(&readme_path, &readme_content)
-        .await
-        .context("Failed to write README.md")?;
+    // Written last so the content files predate it — see review_calendar.
+    if let Err(e) = review_calendar::mark_imported(profile_dir).await {
+        app_log!(warn, "Failed to mark {} for import review: {}", profile_name, e);
+    }
 
     app_log!(info, "Created profile files from CV data: {}", profile_name);
     Ok(())
 }
 
+/// [`create_profile_from_cv_data`] through a staged import: the person only
+/// appears (or changes) once all of its files are written.
+pub async fn import_profile_from_cv_data(
+    staged: StagedImport,
+    cv_data: &CvJson,
+    profile_name: &str,
+) -> anyhow::Result<()> {
+    let staged_dir = staged.profile_dir().await?;
+    create_profile_from_cv_data(&staged_dir, cv_data, profile_name).await?;
+    staged.commit().await
+}
+
+/// The experiences file CV data is read from, with its language: English,
+/// then the legacy `experiences.typ`, then the first other language (profiles
+/// imported in another language have no English file).
+pub fn source_experiences(profile_dir: &Path) -> Option<(String, PathBuf)> {
+    let en = profile_dir.join("experiences_en.typ");
+    if en.exists() {
+        return Some(("en".to_string(), en));
+    }
+    let legacy = profile_dir.join("experiences.typ");
+    if legacy.exists() {
+        return Some(("en".to_string(), legacy));
+    }
+    let mut langs: Vec<String> = std::fs::read_dir(profile_dir)
+        .ok()?
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let lang = name.strip_prefix("experiences_")?.strip_suffix(".typ")?;
+            (!lang.is_empty()).then(|| lang.to_string())
+        })
+        .collect();
+    langs.sort();
+    let lang = langs.into_iter().next()?;
+    let path = profile_dir.join(format!("experiences_{}.typ", lang));
+    Some((lang, path))
+}
+
 /// Load profile CV data as CvJson (for job matching, etc.)
 pub async fn load_profile_cv_data(
     profile_name: &str,
@@ -65,38 +272,47 @@ pub async fn load_profile_cv_data(
 ) -> anyhow::Result<CvJson> {
     let profile_dir = tenant_data_dir.join(profile_name);
     let toml_path = profile_dir.join("cv_params.toml");
-    
-    // Support both language-specific and legacy filenames
-    let typst_path_en = profile_dir.join("experiences_en.typ");
-    let typst_path_legacy = profile_dir.join("experiences.typ");
-    
-    let active_typst_path = if typst_path_en.exists() {
-        Some(typst_path_en)
-    } else if typst_path_legacy.exists() {
-        Some(typst_path_legacy)
-    } else {
-        None
-    };
 
-    if !toml_path.exists() || active_typst_path.is_none() {
-        anyhow::bail!("CV files not found for profile: {} (checked experiences_en.typ and experiences.typ)", profile_name);
-    }
+    let source = source_experiences(&profile_dir);
+    let (true, Some((lang, typst_path))) = (toml_path.exists(), source) else {
+        anyhow::bail!("CV files not found for profile: {} (no cv_params.toml or experiences file)", profile_name);
+    };
 
-    CvConverter::from_files(&toml_path, &active_typst_path.unwrap())
-        .with_context(|| format!("Failed to load CV data for profile: {}", profile_name))
+    let mut cv_data = CvConverter::from_files(&toml_path, &typst_path)
+        .with_context(|| format!("Failed to load CV data for profile: {}", profile_name))?;
+    cv_data.metadata.language = lang;
+    Ok(cv_data)
 }
 
-/// Normalize template name against available templates
-pub fn normalize_template(template: Option<&str>, template_manager: &TemplateEngine) -> String {
+/// Normalize template name against available templates. An unknown one
+/// falls back to `default`; one that fails its health check is refused,
+/// since generating with another template would hand back a different CV.
+pub fn normalize_template(
+    template: Option<&str>,
+    template_manager: &TemplateEngine,
+    conversation_id: Option<String>,
+) -> Result<String, Json<StandardErrorResponse>> {
     let requested = template.unwrap_or("default").to_lowercase();
 
     for available_template in template_manager.list_templates() {
         if available_template.to_lowercase() == requested {
-            return available_template.to_lowercase();
+            if !template_health::is_healthy(&available_template) {
+                app_log!(warn, "Template '{}' is marked unhealthy, refusing it", available_template);
+                return Err(Json(StandardErrorResponse::new(
+                    format!("Template '{}' is temporarily unavailable", available_template),
+                    "TEMPLATE_UNAVAILABLE".to_string(),
+                    vec![
+                        "Pick another template".to_string(),
+                        "Try again once the template is fixed".to_string(),
+                    ],
+                    conversation_id,
+                )));
+            }
+            return Ok(available_template.to_lowercase());
         }
     }
 
-    "default".to_string()
+    Ok("default".to_string())
 }
 
 /// Save CvJson data to profile directory as TOML and Typst files
@@ -108,16 +324,19 @@ pub async fn save_profile_cv_data(
 ) -> anyhow::Result<()> {
     let profile_dir = tenant_data_dir.join(profile_name);
     FsOps::ensure_dir_exists(&profile_dir).await?;
+    let _save = profile_lock::write(&profile_dir).await;
 
-    // Convert and save TOML
-    let toml_content = CvConverter::to_toml(cv_data)?;
+    // Convert and save TOML, keeping the user's comments, order and styling
     let toml_path = profile_dir.join("cv_params.toml");
+    let existing = FsOps::read_file_safe(&toml_path).await.unwrap_or_default();
+    let toml_content = CvConverter::merge_into_toml(&existing, cv_data)?;
     FsOps::write_file_safe(&toml_path, &toml_content).await?;
 
     // Convert and save Typst
     let typst_content = CvConverter::to_typst(cv_data, language)?;
     let typst_path = profile_dir.join(&format!("experiences_{}.typ", language));
     FsOps::write_file_safe(&typst_path, &typst_content).await?;
+    translation_status::record(&profile_dir, language, Provenance::Original, None);
 
     app_log!(
         trace,
@@ -145,3 +364,29 @@ pub fn validate_cv_data(cv_data: &CvJson) -> anyhow::Result<()> {
 pub fn extract_profile_name_from_filename(filename: &str) -> String {
     filename.split('.').next().unwrap_or(filename).to_string()
 }
+
+/// First free profile name: `base`, then `base-2`, `base-3`, …
+pub fn unique_profile_name(base: &str, exists: impl Fn(&str) -> bool) -> String {
+    if !exists(base) {
+        return base.to_string();
+    }
+    (2..)
+        .map(|n| format!("{}-{}", base, n))
+        .find(|candidate| !exists(candidate))
+        .expect("unbounded range always yields a free name")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unique_profile_name_appends_suffix() {
+        let taken = ["john_doe", "john_doe-2"];
+        assert_eq!(unique_profile_name("jane", |p| taken.contains(&p)), "jane");
+        assert_eq!(
+            unique_profile_name("john_doe", |p| taken.contains(&p)),
+            "john_doe-3"
+        );
+    }
+}
@@ -0,0 +1,43 @@
+// src/web/handlers/cv_handlers/integrity.rs
+//! Integrity report for one person, for the dashboard's broken-profile flag.
+use crate::auth::AuthenticatedUser;
+use crate::core::database::get_tenant_folder_path;
+use crate::core::profile_integrity::{self, IntegrityReport};
+use crate::utils::normalize_profile_name;
+use crate::web::types::{DataResponse, ServerConfig, StandardErrorResponse};
+use rocket::serde::json::Json;
+use rocket::State;
+
+/// GET /profiles/<name>/check
+pub async fn integrity_handler(
+    name: String,
+    compile: Option<bool>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+) -> Result<Json<DataResponse<IntegrityReport>>, Json<StandardErrorResponse>> {
+    let profile = normalize_profile_name(&name);
+    let profile_dir = get_tenant_folder_path(auth.email(), &config.data_dir).join(&profile);
+
+    if profile.is_empty() || !profile_dir.is_dir() {
+        return Err(Json(StandardErrorResponse::new(
+            format!("Profile '{}' not found in your account", name),
+            "PROFILE_NOT_FOUND".to_string(),
+            vec!["Check the profile name spelling".to_string()],
+            None,
+        )));
+    }
+
+    let report = profile_integrity::check(
+        &config.templates_dir,
+        &profile_dir,
+        &profile,
+        compile.unwrap_or(true),
+    )
+    .await;
+    let message = if report.healthy {
+        format!("Profile '{}' passed the integrity check", profile)
+    } else {
+        format!("Profile '{}' has integrity problems", profile)
+    };
+    Ok(Json(DataResponse::success(message, report, None)))
+}
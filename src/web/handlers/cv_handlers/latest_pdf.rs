@@ -0,0 +1,142 @@
+// src/web/handlers/cv_handlers/latest_pdf.rs
+//! Direct download of a person's most recent generated PDF
+use crate::auth::AuthenticatedUser;
+use crate::core::audit_log::{self, AuditAction};
+use crate::core::database::{get_tenant_folder_path, DatabaseConfig};
+use crate::core::{tenant_templates, TemplateEngine};
+use crate::utils::{normalize_language, normalize_profile_name};
+use crate::web::file_handlers::GeneratedOutputs;
+use crate::web::proxy::ProxyInfo;
+use crate::web::types::{
+    ClientRequestId, GenerateRequest, PdfResponse, ServerConfig, StandardErrorResponse,
+    StandardRequest,
+};
+use graflog::app_log;
+use rocket::serde::json::Json;
+use rocket::State;
+
+use super::generate::generate_cv_handler;
+use super::helpers::normalize_template;
+
+/// `?lang=&template=` of `/profiles/<name>/latest-pdf`; unset means any.
+#[derive(Debug, Default, rocket::FromForm)]
+pub struct LatestPdfQuery {
+    pub lang: Option<String>,
+    pub template: Option<String>,
+}
+
+/// Serve the newest `{profile}_{template}_{lang}.pdf` from the output dir
+/// without recompiling. When nothing matches, generate once (same path and
+/// credit cost as `/generate`) and serve the result.
+pub async fn latest_pdf_handler(
+    name: String,
+    query: LatestPdfQuery,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    db_config: &State<DatabaseConfig>,
+    proxy: ProxyInfo,
+    request_id: ClientRequestId,
+) -> Result<PdfResponse, Json<StandardErrorResponse>> {
+    let LatestPdfQuery { lang, template } = query;
+    let profile = normalize_profile_name(&name);
+    let (tenant_id, email) = (auth.tenant_id(), auth.email().to_string());
+    let tenant_data_dir = get_tenant_folder_path(&auth.user().email, &config.data_dir);
+
+    if profile.is_empty() || !tenant_data_dir.join(&profile).is_dir() {
+        return Err(Json(StandardErrorResponse::new(
+            format!("Profile '{}' not found in your account", name),
+            "PROFILE_NOT_FOUND".to_string(),
+            vec!["Check the profile name spelling".to_string()],
+            None,
+        )));
+    }
+
+    // Only normalise filters the caller actually set — an absent filter means
+    // "any template / any language", not "default" / "en".
+    let lang = lang.map(|l| normalize_language(Some(&l)));
+    let template = match template {
+        Some(t) => {
+            let engine = TemplateEngine::new(config.templates_dir.clone())
+                .map(|engine| {
+                    let dir = tenant_templates::dir(&config.data_dir, &email, tenant_id);
+                    engine.with_tenant_templates(&dir)
+                })
+                .map_err(|e| {
+                    app_log!(error, "Failed to initialize template manager: {}", e);
+                    Json(StandardErrorResponse::new(
+                        "Template system initialization failed".to_string(),
+                        "TEMPLATE_INIT_ERROR".to_string(),
+                        vec!["Contact system administrator".to_string()],
+                        None,
+                    ))
+                })?;
+            Some(normalize_template(Some(&t), &engine, None)?)
+        }
+        None => None,
+    };
+
+    let outputs = GeneratedOutputs::scan(config, &auth).await;
+    let cached = outputs
+        .latest_for_person(&profile, template.as_deref(), lang.as_deref())
+        .map(|(file, _)| file.clone());
+
+    let cache_hit = cached.is_some();
+    let filename = match cached {
+        Some(file) => {
+            app_log!(info, "Serving cached PDF {} for profile {}", file, profile);
+            file
+        }
+        None => {
+            app_log!(
+                info,
+                "No generated PDF for profile {} (template: {:?}, lang: {:?}) — generating",
+                profile,
+                template,
+                lang
+            );
+            let request = StandardRequest {
+                data: GenerateRequest {
+                    profile: profile.clone(),
+                    lang: lang.clone(),
+                    template: template.clone(),
+                    use_custom_colors: None,
+                    brand_slug: None,
+                    features: None,
+                    strict_assets: None,
+                    strict: None,
+                    format: None,
+                    langs: None,
+                    archive: None,
+                    draft: None,
+                    pdf_a: None,
+                },
+                conversation_id: None,
+            };
+            generate_cv_handler(Json(request), auth, config, db_config, proxy, request_id)
+                .await?
+                .into_inner()
+                .filename
+        }
+    };
+
+    let path = outputs.path(&profile, &filename);
+    match tokio::fs::read(&path).await {
+        Ok(data) => {
+            if let Ok(pool) = db_config.pool() {
+                audit_log::record(pool, tenant_id, &email, AuditAction::DataExported, &filename);
+            }
+            Ok(PdfResponse::with_filename(data, filename)
+                .with_generation_id(crate::utils::generation_id(&path))
+                .with_cache_hit(cache_hit))
+        }
+        Err(e) => {
+            app_log!(error, "Failed to read PDF {}: {}", path.display(), e);
+            Err(Json(StandardErrorResponse::new(
+                "Generated PDF could not be read".to_string(),
+                "FILE_ERROR".to_string(),
+                vec!["Generate the CV again".to_string()],
+                None,
+            )))
+        }
+    }
+}
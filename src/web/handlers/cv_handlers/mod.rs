@@ -1,27 +1,80 @@
 // src/web/handlers/cv_handlers/mod.rs
 //! CV handlers module - refactored into separate files for better maintainability
 
+pub mod approval;
+pub mod checklist;
+pub mod comments;
+pub mod compare;
 pub mod cover_letter;
 pub mod cover_letter_export;
 pub mod cv_data;
+pub mod from_notes;
 pub mod generate;
+pub mod generate_batch;
+pub mod generate_languages;
+pub mod generation_jobs;
 pub mod helpers;
+pub mod integrity;
+pub mod latest_pdf;
 pub mod optimize;
+pub mod person_tags;
 pub mod portfolio;
+pub mod preview_set;
 pub mod save_optimized;
+pub mod smart_lists;
+pub mod template_pin;
 pub mod translate;
 pub mod upload_convert;
+pub mod watch;
 
 // Re-export all handler functions
+pub use approval::{approval_action_handler, approval_handler, pending_reviews_handler};
+pub use checklist::{checklist_handler, OnboardingChecklist};
+pub use comments::{create_comment_handler, list_comments_handler, resolve_comment_handler};
+pub use compare::{compare_profiles_handler, ProfileComparison};
 pub use cover_letter::{cover_letter_handler, CoverLetterRequest};
 pub use cover_letter_export::{cover_letter_export_handler, CoverLetterExportRequest};
-pub use cv_data::{get_cv_data_handler, put_cv_data_handler, CvFormData};
-pub use generate::generate_cv_handler;
+pub use cv_data::{
+    get_cv_data_handler, get_cv_section_handler, put_cv_data_handler, put_cv_section_handler,
+    upgrade_cv_schema_handler, CvFormData,
+};
+pub use from_notes::{profile_from_notes_handler, ProfileFromNotesRequest, ProfileFromNotesResult};
+pub use generate::{
+    generate_cv_handler, generate_docx_handler, generate_html_handler, GenerateResponse,
+};
+pub use generate_batch::{generate_batch_handler, BatchGenerateRequest};
+pub use generate_languages::{generate_languages_handler, LanguagesResponse};
+pub use generation_jobs::{generate_async_handler, job_result_handler, job_status_handler};
+pub use integrity::integrity_handler;
+pub use latest_pdf::{latest_pdf_handler, LatestPdfQuery};
+pub use person_tags::{
+    list_persons_handler, person_tags_handler, set_person_tags_handler, PersonSummary,
+};
 pub use portfolio::{generate_portfolio_handler, GeneratePortfolioRequest};
-pub use optimize::{optimize_and_generate_handler, optimize_cv_handler, OptimizeCvRequest};
+pub use preview_set::{preview_set_handler, PreviewSetRequest, PreviewSetResponse};
+pub use optimize::{
+    optimize_and_generate_handler, optimize_cv_handler, optimize_cv_stream_handler,
+    OptimizeCvRequest,
+};
 pub use save_optimized::{save_optimized_handler, SaveOptimizedRequest};
-pub use translate::translate_cv_handler;
-pub use upload_convert::{upload_and_convert_cv_handler, import_text_cv_handler, ImportTextRequest};
+pub use smart_lists::{
+    delete_smart_list_handler, evaluate_smart_list_handler, list_smart_lists_handler,
+    save_smart_list_handler, SaveSmartListRequest, SmartListResult,
+};
+pub use template_pin::{pin_template_handler, template_versions_handler};
+pub use translate::{translate_cv_handler, translation_status_handler, TranslateCvRequest};
+pub use upload_convert::{
+    convert_presigned_upload_handler, convert_upload_session_handler, import_text_cv_handler,
+    list_failed_imports_handler,
+    retry_failed_import, retry_failed_import_handler, upload_and_convert_cv_handler,
+    ImportTextRequest,
+};
+pub use watch::{
+    list_watches_handler, unwatch_profile_handler, watch_events_handler, watch_profile_handler,
+};
 
 // Re-export helper functions for use in other modules
-pub use helpers::{create_profile_from_cv_data, load_profile_cv_data, normalize_template};
+pub use helpers::{
+    create_profile_from_cv_data, import_profile_from_cv_data, load_profile_cv_data, normalize_template, require_create_access,
+    approval_gate, notify_watchers, require_edit_access, unreviewed_gate,
+};
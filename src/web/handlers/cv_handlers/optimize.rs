@@ -2,24 +2,40 @@
 //! CV optimization handler — optimizes via LLM then saves files + generates PDF.
 
 use crate::auth::AuthenticatedUser;
+use crate::core::ai_budget::AiFeature;
 use crate::core::database::{get_tenant_folder_path, DatabaseConfig};
+use crate::core::service_cache::ResultCache;
+use crate::core::service_usage::UsageRecorder;
+use crate::core::request_cancel::{self, CancelToken, Registration};
+use crate::core::service_stream::{self, Relayed};
 use crate::core::{FsOps, ServiceClient, TemplateEngine};
-use crate::web::handlers::payment_handlers::check_and_deduct_credits;
+use crate::web::handlers::system_handlers::ensure_storage_available;
+use crate::web::handlers::payment_handlers::{
+    check_and_deduct_credits, refund_credits, PendingCharge,
+};
+use crate::web::handlers::usage_handlers::{check_ai_budget, result_cache, usage_recorder};
 use crate::types::cv_data::{CvConverter, CvJson};
-use crate::types::response::OptimizeResponse;
+use crate::types::response::{CvOptimizationResponse, OptimizeResponse};
 use crate::utils::{normalize_language, normalize_profile_name};
-use crate::web::types::WithConversationId;
+use crate::web::proxy::ProxyInfo;
+use crate::web::types::{ClientRequestId, WithConversationId};
 use crate::web::types::{
     DataResponse, GeneratePdfResponse, ResponseType, ServerConfig, StandardErrorResponse, StandardRequest,
 };
-use crate::{CvConfig, CvGenerator};
+use crate::{CvConfig, CvGenerator, GeneratedCv};
+use futures::stream::{Stream, StreamExt};
 use graflog::app_log;
+use rocket::response::stream::{Event, EventStream};
 use rocket::serde::json::Json;
 use rocket::serde::Deserialize;
 use rocket::State;
-use std::env;
+use sqlx::SqlitePool;
 
-use super::helpers::{load_profile_cv_data, normalize_template, save_profile_cv_data};
+use super::generate::generation_failed;
+use super::helpers::{
+    load_profile_cv_data, normalize_template, pdf_metadata, require_edit_access,
+    save_profile_cv_data, unreviewed_gate,
+};
 
 /// Request body shared by both optimize endpoints.
 #[derive(Deserialize)]
@@ -38,11 +54,83 @@ pub struct OptimizeCvRequest {
     pub lang: Option<String>,
     /// CV template for PDF generation (defaults to "default").
     pub template: Option<String>,
+    /// Call cv-import even if the same CV and job were optimized recently.
+    #[serde(default)]
+    pub force_refresh: bool,
 }
 
 // ── Shared optimization pipeline ──────────────────────────────────────────────
 
-/// Runs the optimization pipeline:
+/// CV data to optimize: the request's `cv_json`, or the profile on disk.
+async fn request_cv_data(
+    data: &OptimizeCvRequest,
+    profile: &str,
+    tenant_data_dir: &std::path::Path,
+    conversation_id: Option<String>,
+) -> Result<CvJson, Json<StandardErrorResponse>> {
+    match &data.cv_json {
+        Some(json_str) => serde_json::from_str(json_str).map_err(|e| {
+            Json(StandardErrorResponse::new(
+                format!("Invalid CV JSON format: {}", e),
+                "INVALID_CV_JSON".to_string(),
+                vec!["Ensure CV data is in correct JSON format".to_string()],
+                conversation_id,
+            ))
+        }),
+        None => load_profile_cv_data(profile, tenant_data_dir).await.map_err(|e| {
+            Json(StandardErrorResponse::new(
+                format!("Failed to load CV data for profile '{}': {}", profile, e),
+                "PROFILE_LOAD_FAILED".to_string(),
+                vec![
+                    "Ensure the profile exists and has valid cv_params.toml and experiences_en.typ files".to_string(),
+                ],
+                conversation_id,
+            ))
+        }),
+    }
+}
+
+fn optimization_client(
+    cv_service_url: &str,
+    usage: Option<UsageRecorder>,
+    cancel: CancelToken,
+    cache: Option<ResultCache>,
+    conversation_id: Option<String>,
+) -> Result<ServiceClient, Json<StandardErrorResponse>> {
+    match ServiceClient::new(cv_service_url.to_string(), 30) {
+        Ok(c) => Ok(c.with_usage(usage).with_cancel(cancel).with_cache(cache)),
+        Err(e) => Err(Json(StandardErrorResponse::new(
+            format!("Service initialization failed: {}", e),
+            "SERVICE_INIT_FAILED".to_string(),
+            vec!["Contact system administrator".to_string()],
+            conversation_id,
+        ))),
+    }
+}
+
+/// Give back the optimization credits when the client cancelled the call.
+fn refund_if_cancelled(cancel: &Registration, email: &str) {
+    if cancel.token().is_cancelled() {
+        refund_credits(email, 5, "optimize");
+    }
+}
+
+fn optimization_failed(e: &anyhow::Error, conversation_id: Option<String>) -> StandardErrorResponse {
+    if request_cancel::is_cancelled(e) {
+        return StandardErrorResponse::cancelled(conversation_id);
+    }
+    StandardErrorResponse::new(
+        format!("CV optimization failed: {}", e),
+        "OPTIMIZATION_FAILED".to_string(),
+        vec![
+            "Verify the job URL is publicly accessible".to_string(),
+            "Ensure the CV data is valid JSON".to_string(),
+        ],
+        conversation_id,
+    )
+}
+
+/// Runs the optimization pipeline with a client from `optimization_client`:
 /// 1. Call cv-import service  (scrape job → keyword extraction → ATS rewrite)
 /// Returns the enriched `OptimizeResponse` and the resolved `CvJson`.
 /// Disk persistence is the caller's responsibility.
@@ -51,41 +139,28 @@ async fn run_optimization(
     lang: &str,
     job_url: &str,
     job_description: Option<&str>,
-    cv_service_url: &str,
+    service_client: &ServiceClient,
     conversation_id: Option<String>,
 ) -> Result<(OptimizeResponse, CvJson), Json<StandardErrorResponse>> {
-    // ── 1. Init service client ────────────────────────────────────────────────
-    let service_client = match ServiceClient::new(cv_service_url.to_string(), 30) {
-        Ok(c) => c,
-        Err(e) => {
-            return Err(Json(StandardErrorResponse::new(
-                format!("Service initialization failed: {}", e),
-                "SERVICE_INIT_FAILED".to_string(),
-                vec!["Contact system administrator".to_string()],
-                conversation_id,
-            )));
-        }
-    };
-
-    // ── 2. Call cv-import optimization service ────────────────────────────────
+    // ── 1. Call cv-import optimization service ────────────────────────────────
     let optimization_response = match service_client.optimize_cv(cv_data, job_url, job_description).await {
         Ok(r) => r,
-        Err(e) => {
-            return Err(Json(StandardErrorResponse::new(
-                format!("CV optimization failed: {}", e),
-                "OPTIMIZATION_FAILED".to_string(),
-                vec![
-                    "Verify the job URL is publicly accessible".to_string(),
-                    "Ensure the CV data is valid JSON".to_string(),
-                ],
-                conversation_id,
-            )));
-        }
+        Err(e) => return Err(Json(optimization_failed(&e, conversation_id))),
     };
 
+    optimization_result(optimization_response, lang, conversation_id)
+}
+
+/// The `OptimizeResponse` for what cv-import returned, and the optimized
+/// `CvJson`.
+fn optimization_result(
+    optimization_response: CvOptimizationResponse,
+    lang: &str,
+    conversation_id: Option<String>,
+) -> Result<(OptimizeResponse, CvJson), Json<StandardErrorResponse>> {
     let optimized_cv_json = optimization_response.optimized_cv.clone();
 
-    // ── 3. Convert optimized CvJson → Typst (for the response payload) ────────
+    // ── 2. Convert optimized CvJson → Typst (for the response payload) ────────
     let optimized_typst = match CvConverter::to_typst(&optimized_cv_json, lang) {
         Ok(t) => t,
         Err(e) => {
@@ -120,88 +195,178 @@ async fn run_optimization(
 
 // ── POST /optimize ─────────────────────────────────────────────────────────────
 
+/// Email the ATS results and remember the language, after a successful
+/// optimization.
+fn after_optimization(
+    auth: &AuthenticatedUser,
+    pool: Option<SqlitePool>,
+    profile: &str,
+    lang: &str,
+    response: &OptimizeResponse,
+) {
+    crate::email::send_email_with_prefs(
+        &auth.user().email,
+        crate::email::EmailKind::AtsResults {
+            profile: profile.to_string(),
+            job_title: response.job_title.clone(),
+            company: response.company_name.clone(),
+            before_score: response.before_score.map(|s| s as u8),
+            after_score: response.after_score.map(|s| s as u8),
+        },
+        lang,
+        auth.email_prefs(),
+    );
+
+    // Persist user's preferred language
+    if let Some(pool) = pool {
+        let email = auth.user().email.clone();
+        let preferred = lang.to_string();
+        tokio::spawn(async move {
+            let repo = crate::core::database::TenantRepository::new(&pool);
+            if let Err(e) = repo.update_preferred_lang(&email, &preferred).await {
+                graflog::app_log!(warn, "update_preferred_lang failed for {}: {}", email, e);
+            }
+        });
+    }
+}
+
+fn optimized_message(response: &OptimizeResponse) -> String {
+    format!(
+        "CV optimized for \"{}\" at {}",
+        response.job_title, response.company_name
+    )
+}
+
 pub async fn optimize_cv_handler(
     request: Json<StandardRequest<OptimizeCvRequest>>,
     auth: AuthenticatedUser,
     config: &State<ServerConfig>,
     db_config: &State<DatabaseConfig>,
     cv_service_url: &State<String>,
+    request_id: ClientRequestId,
 ) -> Result<Json<DataResponse<OptimizeResponse>>, Json<StandardErrorResponse>> {
     let conversation_id = request.conversation_id();
+    ensure_storage_available(config, conversation_id.clone())?;
     let lang = normalize_language(request.data.lang.as_deref());
     let profile = normalize_profile_name(&request.data.profile);
     let tenant_data_dir = get_tenant_folder_path(&auth.user().email, &config.data_dir);
 
-    let cv_data: CvJson = match &request.data.cv_json {
-        Some(json_str) => serde_json::from_str(json_str).map_err(|e| {
-            Json(StandardErrorResponse::new(
-                format!("Invalid CV JSON format: {}", e),
-                "INVALID_CV_JSON".to_string(),
-                vec!["Ensure CV data is in correct JSON format".to_string()],
-                conversation_id.clone(),
-            ))
-        })?,
-        None => load_profile_cv_data(&profile, &tenant_data_dir).await.map_err(|e| {
-            Json(StandardErrorResponse::new(
-                format!("Failed to load CV data for profile '{}': {}", profile, e),
-                "PROFILE_LOAD_FAILED".to_string(),
-                vec![
-                    "Ensure the profile exists and has valid cv_params.toml and experiences_en.typ files".to_string(),
-                ],
-                conversation_id.clone(),
-            ))
-        })?,
-    };
+    let cv_data =
+        request_cv_data(&request.data, &profile, &tenant_data_dir, conversation_id.clone()).await?;
 
+    check_ai_budget(db_config, &auth, AiFeature::Optimization, conversation_id.clone()).await?;
     // Optimization — 5 credits (¼ of a CV generation)
     check_and_deduct_credits(&auth.user().email, 5, conversation_id.clone(), "optimize").await?;
 
+    let cancel = request_id.register(auth.email());
+    let service_client = optimization_client(
+        cv_service_url.inner(),
+        usage_recorder(db_config, &auth),
+        cancel.token(),
+        result_cache(config, &auth, request.data.force_refresh),
+        conversation_id.clone(),
+    )?;
     let (response, _) = run_optimization(
         &cv_data,
         &lang,
         &request.data.job_url,
         request.data.job_description.as_deref(),
-        cv_service_url.inner(),
+        &service_client,
         conversation_id.clone(),
     )
-    .await?;
-
-    crate::email::send_email_with_prefs(
-        &auth.user().email,
-        crate::email::EmailKind::AtsResults {
-            profile: profile.clone(),
-            job_title: response.job_title.clone(),
-            company: response.company_name.clone(),
-            before_score: response.before_score.map(|s| s as u8),
-            after_score: response.after_score.map(|s| s as u8),
-        },
-        &lang,
-        auth.email_prefs(),
-    );
+    .await
+    .inspect_err(|_| refund_if_cancelled(&cancel, auth.email()))?;
 
-    // Persist user's preferred language
-    if let Ok(pool) = db_config.pool() {
-        let email = auth.user().email.clone();
-        let preferred = lang.clone();
-        let pool = pool.clone();
-        tokio::spawn(async move {
-            let repo = crate::core::database::TenantRepository::new(&pool);
-            if let Err(e) = repo.update_preferred_lang(&email, &preferred).await {
-                graflog::app_log!(warn, "update_preferred_lang failed for {}: {}", email, e);
-            }
-        });
-    }
+    after_optimization(&auth, db_config.pool().ok().cloned(), &profile, &lang, &response);
 
     Ok(Json(DataResponse::success(
-        format!(
-            "CV optimized for \"{}\" at {}",
-            response.job_title, response.company_name
-        ),
+        optimized_message(&response),
         response,
         conversation_id,
     )))
 }
 
+// ── POST /cv/optimize/stream ──────────────────────────────────────────────────
+
+/// Same as `optimize_cv_handler`, as server-sent events: `delta` events
+/// (`{"text": …}`) while cv-import writes its analysis, then a `result` event
+/// with the response of `/cv/optimize` or an `error` event.
+pub async fn optimize_cv_stream_handler(
+    request: Json<StandardRequest<OptimizeCvRequest>>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    db_config: &State<DatabaseConfig>,
+    cv_service_url: &State<String>,
+    request_id: ClientRequestId,
+) -> Result<EventStream<impl Stream<Item = Event>>, Json<StandardErrorResponse>> {
+    let conversation_id = request.conversation_id();
+    ensure_storage_available(config, conversation_id.clone())?;
+    let lang = normalize_language(request.data.lang.as_deref());
+    let profile = normalize_profile_name(&request.data.profile);
+    let tenant_data_dir = get_tenant_folder_path(&auth.user().email, &config.data_dir);
+
+    let cv_data =
+        request_cv_data(&request.data, &profile, &tenant_data_dir, conversation_id.clone()).await?;
+    let cancel = request_id.register(auth.email());
+    let service_client = optimization_client(
+        cv_service_url.inner(),
+        usage_recorder(db_config, &auth),
+        cancel.token(),
+        result_cache(config, &auth, request.data.force_refresh),
+        conversation_id.clone(),
+    )?;
+
+    check_ai_budget(db_config, &auth, AiFeature::Optimization, conversation_id.clone()).await?;
+    // Optimization — 5 credits (¼ of a CV generation)
+    check_and_deduct_credits(&auth.user().email, 5, conversation_id.clone(), "optimize").await?;
+
+    let request = request.into_inner().data;
+    let pool = db_config.pool().ok().cloned();
+    let (deltas, received) = tokio::sync::mpsc::unbounded_channel();
+    // Dropping the stream (client gone) drops the call and its registration,
+    // and refunds the unfinished charge.
+    let charge = PendingCharge::new(auth.email(), 5, "optimize");
+    let call = async move {
+        let _cancel = cancel;
+        let result = service_client
+            .optimize_cv_streaming(
+                &cv_data,
+                &request.job_url,
+                request.job_description.as_deref(),
+                Some(&deltas),
+            )
+            .await;
+        if !result.as_ref().is_err_and(request_cancel::is_cancelled) {
+            charge.finish();
+        }
+        result
+    };
+    let events = service_stream::relay(call, received).map(move |item| {
+        let outcome = match item {
+            Relayed::Delta(text) => {
+                return Event::json(&serde_json::json!({ "text": text })).event("delta");
+            }
+            Relayed::Done(Ok(optimization_response)) => {
+                optimization_result(optimization_response, &lang, conversation_id.clone())
+            }
+            Relayed::Done(Err(e)) => Err(Json(optimization_failed(&e, conversation_id.clone()))),
+        };
+        match outcome {
+            Ok((response, _)) => {
+                after_optimization(&auth, pool.clone(), &profile, &lang, &response);
+                Event::json(&DataResponse::success(
+                    optimized_message(&response),
+                    response,
+                    conversation_id.clone(),
+                ))
+                .event("result")
+            }
+            Err(error) => Event::json(&error.into_inner()).event("error"),
+        }
+    });
+    Ok(EventStream::from(events))
+}
+
 // ── POST /optimize-and-generate ────────────────────────────────────────────────
 
 pub async fn optimize_and_generate_handler(
@@ -210,46 +375,69 @@ pub async fn optimize_and_generate_handler(
     config: &State<ServerConfig>,
     db_config: &State<DatabaseConfig>,
     cv_service_url: &State<String>,
+    proxy: ProxyInfo,
+    request_id: ClientRequestId,
 ) -> Result<Json<GeneratePdfResponse>, Json<StandardErrorResponse>> {
     let conversation_id = request.conversation_id();
     let lang = normalize_language(request.data.lang.as_deref());
     let profile = normalize_profile_name(&request.data.profile);
     let tenant_data_dir = get_tenant_folder_path(&auth.user().email, &config.data_dir);
 
-    let cv_data: CvJson = match &request.data.cv_json {
-        Some(json_str) => serde_json::from_str(json_str).map_err(|e| {
-            Json(StandardErrorResponse::new(
-                format!("Invalid CV JSON format: {}", e),
-                "INVALID_CV_JSON".to_string(),
-                vec!["Ensure CV data is in correct JSON format".to_string()],
-                conversation_id.clone(),
-            ))
-        })?,
-        None => load_profile_cv_data(&profile, &tenant_data_dir).await.map_err(|e| {
-            Json(StandardErrorResponse::new(
-                format!("Failed to load CV data for profile '{}': {}", profile, e),
-                "PROFILE_LOAD_FAILED".to_string(),
-                vec![
-                    "Ensure the profile exists and has valid cv_params.toml and experiences_en.typ files".to_string(),
-                ],
+    let cv_data =
+        request_cv_data(&request.data, &profile, &tenant_data_dir, conversation_id.clone()).await?;
+    // The optimized files overwrite this profile (step 1b), so the PDF is of
+    // content no one reviewed yet.
+    require_edit_access(&auth, &tenant_data_dir.join(&profile), conversation_id.clone())?;
+    let watermark = unreviewed_gate(
+        &auth,
+        db_config,
+        &tenant_data_dir.join(&profile),
+        conversation_id.clone(),
+    )
+    .await?;
+
+    // The template is checked before anything is charged.
+    let template_manager = match TemplateEngine::new(config.templates_dir.clone()) {
+        Ok(m) => m,
+        Err(e) => {
+            return Err(Json(StandardErrorResponse::new(
+                format!("Template system error: {}", e),
+                "TEMPLATE_INIT_ERROR".to_string(),
+                vec!["Contact system administrator".to_string()],
                 conversation_id.clone(),
-            ))
-        })?,
+            )));
+        }
     };
 
+    let template_id = normalize_template(
+        request.data.template.as_deref(),
+        &template_manager,
+        conversation_id.clone(),
+    )?;
+
+    check_ai_budget(db_config, &auth, AiFeature::Optimization, conversation_id.clone()).await?;
     // Optimization — 5 credits (¼ of a CV generation)
     check_and_deduct_credits(&auth.user().email, 5, conversation_id.clone(), "optimize").await?;
 
     // ── Step 1: Optimize ─────────────────────────────────────────────────────
+    let cancel = request_id.register(auth.email());
+    let service_client = optimization_client(
+        cv_service_url.inner(),
+        usage_recorder(db_config, &auth),
+        cancel.token(),
+        result_cache(config, &auth, request.data.force_refresh),
+        conversation_id.clone(),
+    )?;
     let (optimize_resp, optimized_cv_data) = run_optimization(
         &cv_data,
         &lang,
         &request.data.job_url,
         request.data.job_description.as_deref(),
-        cv_service_url.inner(),
+        &service_client,
         conversation_id.clone(),
     )
-    .await?;
+    .await
+    .inspect_err(|_| refund_if_cancelled(&cancel, auth.email()))?;
 
     // ── Step 1b: Persist optimized files so the PDF generator can read them ──
     if let Err(e) = save_profile_cv_data(&profile, &tenant_data_dir, &optimized_cv_data, &lang).await {
@@ -264,20 +452,6 @@ pub async fn optimize_and_generate_handler(
     app_log!(info, "Optimized CV saved for PDF generation — profile: {}, lang: {}", profile, lang);
 
     // ── Step 2: Generate PDF from freshly-saved profile ───────────────────────
-    let template_manager = match TemplateEngine::new(config.templates_dir.clone()) {
-        Ok(m) => m,
-        Err(e) => {
-            return Err(Json(StandardErrorResponse::new(
-                format!("Template system error: {}", e),
-                "TEMPLATE_INIT_ERROR".to_string(),
-                vec!["Contact system administrator".to_string()],
-                conversation_id,
-            )));
-        }
-    };
-
-    let template_id = normalize_template(request.data.template.as_deref(), &template_manager);
-
     let profile_dir = tenant_data_dir.join(&profile);
     if !profile_dir.exists() {
         return Err(Json(StandardErrorResponse::new(
@@ -288,7 +462,8 @@ pub async fn optimize_and_generate_handler(
         )));
     }
 
-    if let Err(e) = FsOps::ensure_dir_exists(&config.output_dir).await {
+    let output_dir = config.person_output_dir(&auth.user().email, &profile);
+    if let Err(e) = FsOps::ensure_dir_exists(&output_dir).await {
         return Err(Json(StandardErrorResponse::new(
             format!("Output directory error: {}", e),
             "OUTPUT_DIR_ERROR".to_string(),
@@ -297,11 +472,16 @@ pub async fn optimize_and_generate_handler(
         )));
     }
 
-    let cv_config = CvConfig::new(&profile, &lang)
+    let metadata = pdf_metadata(&auth, &profile_dir).await;
+    let mut cv_config = CvConfig::new(&profile, &lang)
         .with_template(template_id)
         .with_data_dir(tenant_data_dir)
-        .with_output_dir(config.output_dir.clone())
-        .with_templates_dir(config.templates_dir.clone());
+        .with_output_dir(output_dir.clone())
+        .with_templates_dir(config.templates_dir.clone())
+        .with_pdf_metadata(metadata);
+    if watermark {
+        cv_config = cv_config.with_watermark("DRAFT".to_string());
+    }
 
     let generator = match CvGenerator::new(cv_config) {
         Ok(g) => g,
@@ -315,8 +495,32 @@ pub async fn optimize_and_generate_handler(
         }
     };
 
-    match generator.generate().await {
-        Ok(output_path) => {
+    // The person first, then a slot: waiting for a busy person never holds
+    // one of the tenant's slots.
+    if let Err(busy) = generator.lock().await {
+        return Err(generation_failed(&busy.into(), &profile, conversation_id));
+    }
+    let permit = match cancel
+        .token()
+        .run(crate::core::generation_scheduler::acquire_for(auth.tenant()))
+        .await
+    {
+        Ok(permit) => permit,
+        Err(_) => return Err(Json(StandardErrorResponse::cancelled(conversation_id))),
+    };
+    app_log!(
+        info,
+        "Generation slot granted, tenant: {}, queue_wait_ms: {}",
+        auth.tenant_name(),
+        permit.waited.as_millis()
+    );
+
+    match generator.generate_report().await {
+        Ok(GeneratedCv {
+            output_path,
+            skipped_assets,
+            ..
+        }) => {
             // Build a descriptive ATS filename: "{company}_{job-title}_{lang}.pdf"
             // Falls back gracefully when either field is missing.
             let slug = |s: &str| -> String {
@@ -341,7 +545,7 @@ pub async fn optimize_and_generate_handler(
             let ats_filename = format!("{}_{}.pdf", base, lang);
 
             // Rename the output file to the ATS filename in the output directory
-            let final_path = config.output_dir.join(&ats_filename);
+            let final_path = output_dir.join(&ats_filename);
             if let Err(e) = std::fs::rename(&output_path, &final_path) {
                 app_log!(warn, "Failed to rename optimized PDF to {}: {}", ats_filename, e);
             }
@@ -352,9 +556,8 @@ pub async fn optimize_and_generate_handler(
                 ats_filename
             );
 
-            let base_url = env::var("PUBLIC_BASE_URL")
-                .unwrap_or_else(|_| "https://api.cvenom.com".to_string());
-            let pdf_url = format!("{}/outputs/{}", base_url, ats_filename);
+            let base_url = proxy.public_base_url();
+            let pdf_url = format!("{}{}", base_url, config.output_url_path(&final_path));
 
             // Persist user's preferred language
             if let Ok(pool) = db_config.pool() {
@@ -376,17 +579,12 @@ pub async fn optimize_and_generate_handler(
                 download_url: pdf_url,
                 filename: ats_filename,
                 profile,
+                warnings: skipped_assets,
                 conversation_id,
+                // The ATS name is never the cached file's.
+                cache_hit: false,
             }))
         }
-        Err(e) => Err(Json(StandardErrorResponse::new(
-            format!("PDF generation failed: {}", e),
-            "GENERATION_ERROR".to_string(),
-            vec![
-                "Verify all required profile files exist".to_string(),
-                "Check the error details above".to_string(),
-            ],
-            conversation_id,
-        ))),
+        Err(e) => Err(generation_failed(&e, &profile, conversation_id)),
     }
 }
@@ -0,0 +1,136 @@
+// src/web/handlers/cv_handlers/person_tags.rs
+//! Tags and attributes of persons; see [`crate::core::person_tags`].
+use crate::auth::AuthenticatedUser;
+use crate::core::database::{get_tenant_folder_path, DatabaseConfig};
+use crate::core::person_tags::{self, PersonFilter, PersonTags};
+use crate::core::FsOps;
+use crate::utils::normalize_profile_name;
+use crate::web::handlers::common::{db_error, error_response, pool};
+use crate::web::types::{DataResponse, ServerConfig, StandardErrorResponse};
+use graflog::app_log;
+use rocket::serde::json::Json;
+use rocket::State;
+use serde::Serialize;
+
+use super::helpers::require_edit_access;
+
+/// A person of `/persons`.
+#[derive(Debug, Serialize)]
+pub struct PersonSummary {
+    pub name: String,
+    #[serde(flatten)]
+    pub tags: PersonTags,
+}
+
+/// GET /persons?<tags>&<attrs>
+pub async fn list_persons_handler(
+    tags: Option<String>,
+    attrs: Option<String>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    db_config: &State<DatabaseConfig>,
+) -> Result<Json<DataResponse<Vec<PersonSummary>>>, Json<StandardErrorResponse>> {
+    let filter = PersonFilter::parse(tags.as_deref(), attrs.as_deref()).map_err(|e| {
+        error_response(
+            e.to_string(),
+            "INVALID_FILTER",
+            vec!["Filter with tags=bench,senior and attrs=key:value,…".to_string()],
+        )
+    })?;
+    let tenant_data_dir = get_tenant_folder_path(auth.email(), &config.data_dir);
+    let profiles = FsOps::list_profiles(&tenant_data_dir).await.map_err(|e| {
+        app_log!(error, "Failed to list profiles: {}", e);
+        error_response(
+            "Failed to list persons".to_string(),
+            "LIST_ERROR",
+            vec!["Try again or contact support".to_string()],
+        )
+    })?;
+    let mut all = person_tags::all(pool(db_config)?, &auth.account_key())
+        .await
+        .map_err(db_error)?;
+
+    let persons: Vec<PersonSummary> = profiles
+        .into_iter()
+        .map(|name| PersonSummary {
+            tags: all.remove(&name).unwrap_or_default(),
+            name,
+        })
+        .filter(|person| filter.matches(&person.tags))
+        .collect();
+    Ok(Json(DataResponse::success(
+        format!("{} persons", persons.len()),
+        persons,
+        None,
+    )))
+}
+
+fn profile_dir_of(
+    name: &str,
+    auth: &AuthenticatedUser,
+    config: &ServerConfig,
+) -> Result<(std::path::PathBuf, String), Json<StandardErrorResponse>> {
+    let profile = normalize_profile_name(name);
+    let profile_dir = get_tenant_folder_path(auth.email(), &config.data_dir).join(&profile);
+    if profile.is_empty() || !profile_dir.is_dir() {
+        return Err(error_response(
+            format!("Profile '{}' not found in your account", name),
+            "PROFILE_NOT_FOUND",
+            vec!["Check the profile name spelling".to_string()],
+        ));
+    }
+    Ok((profile_dir, profile))
+}
+
+/// GET /profiles/<name>/tags
+pub async fn person_tags_handler(
+    name: String,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    db_config: &State<DatabaseConfig>,
+) -> Result<Json<DataResponse<PersonTags>>, Json<StandardErrorResponse>> {
+    let (_, profile) = profile_dir_of(&name, &auth, config)?;
+    let tags = person_tags::get(pool(db_config)?, &auth.account_key(), &profile)
+        .await
+        .map_err(db_error)?;
+    Ok(Json(DataResponse::success(
+        format!("{} tags, {} attributes", tags.tags.len(), tags.attributes.len()),
+        tags,
+        None,
+    )))
+}
+
+/// PUT /profiles/<name>/tags — replaces the person's tags and attributes.
+pub async fn set_person_tags_handler(
+    name: String,
+    request: Json<PersonTags>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    db_config: &State<DatabaseConfig>,
+) -> Result<Json<DataResponse<PersonTags>>, Json<StandardErrorResponse>> {
+    let (profile_dir, profile) = profile_dir_of(&name, &auth, config)?;
+    require_edit_access(&auth, &profile_dir, None)?;
+    let tags = person_tags::set(pool(db_config)?, &auth.account_key(), &profile, &request)
+        .await
+        .map_err(|e| match e.downcast_ref::<sqlx::Error>() {
+            Some(_) => db_error(e),
+            None => error_response(
+                e.to_string(),
+                "INVALID_TAGS",
+                vec!["Use short names of letters, digits, '-' and '_'".to_string()],
+            ),
+        })?;
+    app_log!(
+        info,
+        "{} set {} tags and {} attributes on {}",
+        auth.email(),
+        tags.tags.len(),
+        tags.attributes.len(),
+        profile
+    );
+    Ok(Json(DataResponse::success(
+        "Tags saved".to_string(),
+        tags,
+        None,
+    )))
+}
@@ -4,20 +4,25 @@
 
 use crate::auth::AuthenticatedUser;
 use crate::core::database::{get_tenant_folder_path, DatabaseConfig};
-use crate::core::{FsOps, ServiceClient, TemplateEngine};
+use crate::core::{data_encryption, toml_patch, FsOps, ServiceClient, TemplateEngine};
+use crate::web::handlers::cv_handlers::helpers::{
+    pdf_metadata, require_edit_access, unreviewed_gate,
+};
+use crate::web::handlers::system_handlers::ensure_storage_available;
 use crate::web::handlers::payment_handlers::check_and_deduct_credits;
+use crate::web::handlers::usage_handlers::usage_recorder;
 use crate::utils::{normalize_language, normalize_profile_name};
+use crate::web::proxy::ProxyInfo;
 use crate::web::types::WithConversationId;
 use crate::web::types::{
     GeneratePdfResponse, ResponseType, ServerConfig, StandardErrorResponse, StandardRequest,
 };
-use crate::{CvConfig, CvGenerator};
+use crate::{CvConfig, CvGenerator, GeneratedCv};
 use crate::types::cv_data::CvConverter;
 use graflog::{app_log, app_span};
 use rocket::serde::json::Json;
 use rocket::State;
 use serde::Deserialize;
-use std::env;
 
 #[derive(Deserialize)]
 #[serde(crate = "rocket::serde")]
@@ -37,11 +42,18 @@ pub async fn generate_portfolio_handler(
     config: &State<ServerConfig>,
     db_config: &State<DatabaseConfig>,
     cv_service_url: &State<String>,
+    proxy: ProxyInfo,
 ) -> Result<Json<GeneratePdfResponse>, Json<StandardErrorResponse>> {
     let user = auth.user();
     let tenant = auth.tenant();
     let conversation_id = request.conversation_id();
 
+    ensure_storage_available(config, conversation_id.clone())?;
+    // Generated projects are merged into the profile, so it must be editable.
+    let target_dir = get_tenant_folder_path(&user.email, &config.data_dir)
+        .join(normalize_profile_name(&request.data.profile));
+    require_edit_access(&auth, &target_dir, conversation_id.clone())?;
+    let watermark = unreviewed_gate(&auth, db_config, &target_dir, conversation_id.clone()).await?;
     check_and_deduct_credits(&user.email, 20, conversation_id.clone(), "portfolio_generation")
         .await?;
 
@@ -109,9 +121,11 @@ pub async fn generate_portfolio_handler(
     };
 
     // ── 2. Call AI service to generate [[projects]] TOML ─────────────────────
-    let service_client = ServiceClient::new(cv_service_url.inner().clone(), 120).map_err(|e| {
-        err("SERVICE_CLIENT_ERROR", format!("Failed to create service client: {}", e), conversation_id.clone())
-    })?;
+    let service_client = ServiceClient::new(cv_service_url.inner().clone(), 120)
+        .map_err(|e| {
+            err("SERVICE_CLIENT_ERROR", format!("Failed to create service client: {}", e), conversation_id.clone())
+        })?
+        .with_usage(usage_recorder(db_config, &auth));
 
     app_log!(info, "Calling AI service to generate portfolio projects for '{}'", normalized_profile);
 
@@ -130,17 +144,30 @@ pub async fn generate_portfolio_handler(
 
     // ── 3. Merge generated projects into the profile's cv_params.toml ────────
     if !projects_toml.trim().is_empty() {
-        let existing_toml = std::fs::read_to_string(&toml_path).unwrap_or_default();
+        let save = crate::core::profile_lock::write(&profile_dir).await;
+        let existing_toml = data_encryption::read_to_string(&toml_path).unwrap_or_default();
 
-        // Strip any existing [[projects]] blocks and everything after them
-        let base_toml = strip_projects_section(&existing_toml);
-        let updated_toml = format!(
-            "{}\n\n# Projects generated by AI — edit freely\n{}\n",
-            base_toml.trim_end(),
-            projects_toml
-        );
+        // Replace existing projects in place, keeping the user's comments and
+        // everything around them; otherwise append the generated block.
+        let patched = toml_patch::parse(&existing_toml)
+            .ok()
+            .filter(|doc| doc.contains_key("projects"))
+            .and_then(|_| {
+                toml_patch::patch(&existing_toml, &projects_toml, Some(&["projects"])).ok()
+            });
+        let updated_toml = patched.unwrap_or_else(|| {
+            // Strip any existing [[projects]] blocks and everything after them
+            let base_toml = strip_projects_section(&existing_toml);
+            format!(
+                "{}\n\n# Projects generated by AI — edit freely\n{}\n",
+                base_toml.trim_end(),
+                projects_toml
+            )
+        });
 
-        if let Err(e) = std::fs::write(&toml_path, &updated_toml) {
+        let written = FsOps::write_file_safe(&toml_path, &updated_toml).await;
+        drop(save);
+        if let Err(e) = written {
             app_log!(warn, "Could not save generated projects to cv_params.toml: {}", e);
             // Non-fatal: proceed with compilation using what's already in the file
         } else {
@@ -149,11 +176,16 @@ pub async fn generate_portfolio_handler(
     }
 
     // ── 4. Compile portfolio PDF ──────────────────────────────────────────────
+    let output_dir = config.person_output_dir(&auth.user().email, &normalized_profile);
     let mut cv_config = CvConfig::new(&normalized_profile, &lang)
         .with_template(template_id)
         .with_data_dir(tenant_data_dir.clone())
-        .with_output_dir(config.output_dir.clone())
-        .with_templates_dir(config.templates_dir.clone());
+        .with_output_dir(output_dir.clone())
+        .with_templates_dir(config.templates_dir.clone())
+        .with_pdf_metadata(pdf_metadata(&auth, &tenant_data_dir.join(&normalized_profile)).await);
+    if watermark {
+        cv_config = cv_config.with_watermark("DRAFT".to_string());
+    }
 
     // Optional brand selection — same shape as `/generate`. Unknown / empty /
     // "default" slug = no brand (current behavior).
@@ -178,68 +210,94 @@ pub async fn generate_portfolio_handler(
         }
     }
 
-    match CvGenerator::new(cv_config) {
-        Ok(generator) => match generator.generate().await {
-            Ok(output_path) => {
-                let filename = output_path
-                    .file_name()
-                    .and_then(|n| n.to_str())
-                    .unwrap_or("portfolio.pdf")
-                    .to_string();
-
-                let base_url = env::var("PUBLIC_BASE_URL")
-                    .unwrap_or_else(|_| "https://api.cvenom.com".to_string());
-
-                app_log!(info, "Portfolio generated: {}", filename);
-
-                let download_url = format!("{}/outputs/{}", base_url, filename);
-                crate::email::send_email_with_prefs(
-                    &auth.user().email,
-                    crate::email::EmailKind::PortfolioReady {
-                        profile: normalized_profile.clone(),
-                        filename: filename.clone(),
-                        download_url: download_url.clone(),
-                    },
-                    &lang,
-                    auth.email_prefs(),
-                );
-                crate::email::notify_admin(
-                    crate::email::EmailKind::AdminActivity {
-                        user_email: auth.user().email.clone(),
-                        action: "Portfolio generated".to_string(),
-                        detail: format!("profile={}", normalized_profile),
-                    },
-                );
-
-                // Persist user's preferred language
-                if let Ok(pool) = db_config.pool() {
-                    let email = auth.user().email.clone();
-                    let preferred = lang.clone();
-                    let pool = pool.clone();
-                    tokio::spawn(async move {
-                        let repo = crate::core::database::TenantRepository::new(&pool);
-                        if let Err(e) = repo.update_preferred_lang(&email, &preferred).await {
-                            graflog::app_log!(warn, "update_preferred_lang failed for {}: {}", email, e);
-                        }
-                    });
-                }
+    let generator = match CvGenerator::new(cv_config) {
+        Ok(generator) => generator,
+        Err(e) => {
+            let message = format!("Generator init failed: {}", e);
+            return Err(err("CONFIG_ERROR", message, conversation_id));
+        }
+    };
+    // The person first, then a slot: waiting for a busy person never holds
+    // one of the tenant's slots.
+    if let Err(busy) = generator.lock().await {
+        return Err(err("GENERATION_BUSY", busy.to_string(), conversation_id));
+    }
+    let permit = crate::core::generation_scheduler::acquire_for(tenant).await;
+    app_log!(
+        info,
+        "Generation slot granted, tenant: {}, queue_wait_ms: {}",
+        tenant.tenant_name,
+        permit.waited.as_millis()
+    );
 
-                Ok(Json(GeneratePdfResponse {
-                    response_type: ResponseType::File,
-                    success: true,
-                    message: "Portfolio generated successfully".to_string(),
-                    download_url,
-                    filename,
-                    profile: normalized_profile,
-                    conversation_id,
-                }))
-            }
-            Err(e) => {
-                app_log!(error, "Portfolio compilation failed: {}", e);
-                Err(err("GENERATION_ERROR", format!("Portfolio compilation failed: {}", e), conversation_id))
+    match generator.generate_report().await {
+        Ok(GeneratedCv {
+            output_path,
+            skipped_assets,
+            cached,
+        }) => {
+            let filename = output_path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or("portfolio.pdf")
+                .to_string();
+
+            let base_url = proxy.public_base_url();
+
+            app_log!(info, "Portfolio generated: {}", filename);
+
+            let download_url = format!(
+                "{}{}",
+                base_url,
+                config.output_url_path(&output_dir.join(&filename))
+            );
+            crate::email::send_email_with_prefs(
+                &auth.user().email,
+                crate::email::EmailKind::PortfolioReady {
+                    profile: normalized_profile.clone(),
+                    filename: filename.clone(),
+                    download_url: download_url.clone(),
+                },
+                &lang,
+                auth.email_prefs(),
+            );
+            crate::email::notify_admin(
+                crate::email::EmailKind::AdminActivity {
+                    user_email: auth.user().email.clone(),
+                    action: "Portfolio generated".to_string(),
+                    detail: format!("profile={}", normalized_profile),
+                },
+            );
+
+            // Persist user's preferred language
+            if let Ok(pool) = db_config.pool() {
+                let email = auth.user().email.clone();
+                let preferred = lang.clone();
+                let pool = pool.clone();
+                tokio::spawn(async move {
+                    let repo = crate::core::database::TenantRepository::new(&pool);
+                    if let Err(e) = repo.update_preferred_lang(&email, &preferred).await {
+                        graflog::app_log!(warn, "update_preferred_lang failed for {}: {}", email, e);
+                    }
+                });
             }
-        },
-        Err(e) => Err(err("CONFIG_ERROR", format!("Generator init failed: {}", e), conversation_id)),
+
+            Ok(Json(GeneratePdfResponse {
+                response_type: ResponseType::File,
+                success: true,
+                message: "Portfolio generated successfully".to_string(),
+                download_url,
+                filename,
+                profile: normalized_profile,
+                warnings: skipped_assets,
+                conversation_id,
+                cache_hit: cached,
+            }))
+        }
+        Err(e) => {
+            app_log!(error, "Portfolio compilation failed: {}", e);
+            Err(err("GENERATION_ERROR", format!("Portfolio compilation failed: {}", e), conversation_id))
+        }
     }
 }
 
@@ -0,0 +1,300 @@
+// src/web/handlers/cv_handlers/preview_set.rs
+//! Side-by-side template previews: one person compiled with several
+//! templates in a single request, returning a PNG of each first page.
+//!
+//! Every template is started at once. The person's generation lock makes
+//! them take turns, since they share the workspace, and each then waits for
+//! a slot from the generation scheduler like `/generate`. A template that
+//! fails is reported next to the others instead of failing the whole set.
+
+use std::collections::BTreeMap;
+use std::io::Write;
+
+use base64::Engine;
+use futures::future::join_all;
+use graflog::app_log;
+use rocket::serde::json::Json;
+use rocket::State;
+use serde::{Deserialize, Serialize};
+
+use crate::auth::AuthenticatedUser;
+use crate::core::database::{get_tenant_folder_path, DatabaseConfig};
+use crate::core::{generation_scheduler, tenant_templates, TemplateEngine};
+use crate::utils::{normalize_language, normalize_profile_name};
+use crate::web::handlers::payment_handlers::{check_and_deduct_credits, refund_credits};
+use crate::web::handlers::system_handlers::ensure_storage_available;
+use crate::web::types::{
+    DataResponse, ServerConfig, StandardErrorResponse, StandardRequest, WithConversationId,
+    ZipResponse,
+};
+use crate::{CvConfig, CvGenerator};
+
+use super::helpers::approval_gate;
+
+pub const MAX_PREVIEW_TEMPLATES: usize = 8;
+const PREVIEW_PPI: u32 = 72;
+const CREDITS_PER_PREVIEW: i64 = 5;
+
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct PreviewSetRequest {
+    pub profile: String,
+    pub lang: Option<String>,
+    pub templates: Vec<String>,
+    pub use_custom_colors: Option<bool>,
+    /// `json` (default): base64 PNGs keyed by template. `zip`: `<template>.png` files.
+    pub format: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct TemplatePreview {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub png_base64: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct PreviewSet {
+    pub profile: String,
+    pub lang: String,
+    pub previews: BTreeMap<String, TemplatePreview>,
+}
+
+#[derive(rocket::Responder)]
+pub enum PreviewSetResponse {
+    Json(Json<DataResponse<PreviewSet>>),
+    Zip(ZipResponse),
+}
+
+fn err(code: &str, msg: String, suggestions: Vec<String>, cid: Option<String>) -> Json<StandardErrorResponse> {
+    Json(StandardErrorResponse::new(msg, code.to_string(), suggestions, cid))
+}
+
+/// Requested template ids, lowercased, without blanks or repeats, in order.
+fn requested_templates(templates: &[String]) -> Vec<String> {
+    let mut ids: Vec<String> = Vec::new();
+    for id in templates {
+        let id = id.trim().to_lowercase();
+        if !id.is_empty() && !ids.contains(&id) {
+            ids.push(id);
+        }
+    }
+    ids
+}
+
+/// `<template>.png` for each preview that compiled.
+fn zip_previews(previews: &[(String, Vec<u8>)]) -> anyhow::Result<Vec<u8>> {
+    let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    // PNGs are already compressed.
+    let options =
+        zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Stored);
+    for (template, png) in previews {
+        zip.start_file(format!("{}.png", template), options)?;
+        zip.write_all(png)?;
+    }
+    Ok(zip.finish()?.into_inner())
+}
+
+pub async fn preview_set_handler(
+    request: Json<StandardRequest<PreviewSetRequest>>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    db_config: &State<DatabaseConfig>,
+) -> Result<PreviewSetResponse, Json<StandardErrorResponse>> {
+    let user = auth.user();
+    let tenant = auth.tenant();
+    let conversation_id = request.conversation_id();
+
+    ensure_storage_available(config, conversation_id.clone())?;
+
+    let as_zip = match request.data.format.as_deref().map(str::to_lowercase).as_deref() {
+        None | Some("json") => false,
+        Some("zip") => true,
+        Some(other) => {
+            return Err(err(
+                "INVALID_FORMAT",
+                format!("Unknown format '{}'", other),
+                vec!["Use 'json' or 'zip'".to_string()],
+                conversation_id,
+            ))
+        }
+    };
+
+    let templates = requested_templates(&request.data.templates);
+    if templates.is_empty() || templates.len() > MAX_PREVIEW_TEMPLATES {
+        return Err(err(
+            "INVALID_TEMPLATES",
+            format!("Request between 1 and {} templates", MAX_PREVIEW_TEMPLATES),
+            vec!["List the template ids to compare in 'templates'".to_string()],
+            conversation_id,
+        ));
+    }
+
+    let tenant_templates = tenant_templates::dir(&config.data_dir, auth.email(), auth.tenant_id());
+    let template_manager = TemplateEngine::new(config.templates_dir.clone())
+        .map(|engine| engine.with_tenant_templates(&tenant_templates))
+        .map_err(|e| {
+            err(
+                "TEMPLATE_INIT_ERROR",
+                format!("Template system error: {}", e),
+                vec!["Contact system administrator".to_string()],
+                conversation_id.clone(),
+            )
+        })?;
+
+    let lang = normalize_language(request.data.lang.as_deref());
+    let profile = normalize_profile_name(&request.data.profile);
+    let tenant_data_dir = get_tenant_folder_path(&user.email, &config.data_dir);
+    if !tenant_data_dir.join(&profile).exists() {
+        return Err(err(
+            "PROFILE_NOT_FOUND",
+            format!("Profile '{}' not found in your account", request.data.profile),
+            vec!["Check the profile name spelling".to_string()],
+            conversation_id,
+        ));
+    }
+
+    let watermark = approval_gate(
+        &auth,
+        db_config,
+        &tenant_data_dir.join(&profile),
+        conversation_id.clone(),
+    )
+    .await?;
+
+    let known: Vec<String> = template_manager
+        .list_templates()
+        .into_iter()
+        .map(|t| t.to_lowercase())
+        .collect();
+    let (available, unknown): (Vec<String>, Vec<String>) =
+        templates.into_iter().partition(|t| known.contains(t));
+
+    if !available.is_empty() {
+        check_and_deduct_credits(
+            &user.email,
+            CREDITS_PER_PREVIEW * available.len() as i64,
+            conversation_id.clone(),
+            "cv_preview_set",
+        )
+        .await?;
+    }
+
+    let output_dir = config.person_output_dir(&user.email, &profile);
+    let use_custom_colors = request.data.use_custom_colors.unwrap_or(false);
+    let compiled = join_all(available.iter().map(|template| {
+        let mut cv_config = CvConfig::new(&profile, &lang)
+            .with_template(template.clone())
+            .with_data_dir(tenant_data_dir.clone())
+            .with_output_dir(output_dir.clone())
+            .with_templates_dir(config.templates_dir.clone())
+            .with_tenant_templates(tenant_templates.clone())
+            .with_custom_colors(use_custom_colors)
+            .with_preview(PREVIEW_PPI);
+        if watermark {
+            cv_config = cv_config.with_watermark("DRAFT".to_string());
+        }
+        async move {
+            // The person first, then a slot (see `CvGenerator::lock`).
+            let generator = CvGenerator::new(cv_config)?;
+            generator.lock().await?;
+            let _permit = generation_scheduler::acquire_for(tenant).await;
+            generator.generate_preview().await
+        }
+    }))
+    .await;
+
+    let mut previews = BTreeMap::new();
+    let mut images = Vec::new();
+    for (template, result) in available.into_iter().zip(compiled) {
+        match result {
+            Ok(png) => images.push((template, png)),
+            Err(e) => {
+                app_log!(warn, "Preview of {} with '{}' failed: {}", profile, template, e);
+                previews.insert(
+                    template,
+                    TemplatePreview {
+                        png_base64: None,
+                        error: Some(e.to_string()),
+                    },
+                );
+            }
+        }
+    }
+    // Only delivered previews are paid for.
+    let failed = previews.len() as i64;
+    if failed > 0 {
+        refund_credits(&user.email, CREDITS_PER_PREVIEW * failed, "cv_preview_set");
+    }
+    for template in unknown {
+        previews.insert(
+            template.clone(),
+            TemplatePreview {
+                png_base64: None,
+                error: Some(format!("Template '{}' not found", template)),
+            },
+        );
+    }
+    app_log!(
+        info,
+        "Preview set for {}: {} compiled, {} failed",
+        profile,
+        images.len(),
+        previews.len()
+    );
+
+    if as_zip {
+        let data = zip_previews(&images).map_err(|e| {
+            let delivered = images.len() as i64;
+            refund_credits(&user.email, CREDITS_PER_PREVIEW * delivered, "cv_preview_set");
+            err(
+                "ZIP_ERROR",
+                format!("Failed to package previews: {}", e),
+                vec![],
+                conversation_id.clone(),
+            )
+        })?;
+        return Ok(PreviewSetResponse::Zip(ZipResponse::new(
+            data,
+            format!("{}_previews.zip", profile),
+        )));
+    }
+
+    for (template, png) in images {
+        previews.insert(
+            template,
+            TemplatePreview {
+                png_base64: Some(base64::engine::general_purpose::STANDARD.encode(png)),
+                error: None,
+            },
+        );
+    }
+    Ok(PreviewSetResponse::Json(Json(DataResponse::success(
+        format!("Previewed {} templates", previews.len()),
+        PreviewSet {
+            profile,
+            lang,
+            previews,
+        },
+        conversation_id,
+    ))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn templates_are_deduplicated_and_zipped_by_name() {
+        let requested = ["Modern", " default ", "modern", ""].map(String::from);
+        assert_eq!(requested_templates(&requested), vec!["modern", "default"]);
+
+        let data = zip_previews(&[("modern".to_string(), vec![0x89, b'P', b'N', b'G'])]).unwrap();
+        let mut archive = zip::ZipArchive::new(std::io::Cursor::new(data)).unwrap();
+        assert_eq!(archive.len(), 1);
+        assert_eq!(archive.by_index(0).unwrap().name(), "modern.png");
+    }
+}
@@ -5,7 +5,9 @@ use crate::auth::AuthenticatedUser;
 use crate::core::database::get_tenant_folder_path;
 use crate::types::cv_data::CvJson;
 use crate::utils::{normalize_language, normalize_profile_name};
-use crate::web::handlers::cv_handlers::helpers::save_profile_cv_data;
+use crate::web::handlers::cv_handlers::helpers::{
+    require_create_access, require_edit_access, save_profile_cv_data,
+};
 use crate::web::types::WithConversationId;
 use crate::web::types::{ActionResponse, ServerConfig, StandardErrorResponse, StandardRequest};
 use graflog::app_log;
@@ -33,6 +35,12 @@ pub async fn save_optimized_handler(
     let profile = normalize_profile_name(&request.data.profile_name);
     let lang = normalize_language(request.data.lang.as_deref());
     let tenant_data_dir = get_tenant_folder_path(&auth.user().email, &config.data_dir);
+    let profile_dir = tenant_data_dir.join(&profile);
+    if profile_dir.is_dir() {
+        require_edit_access(&auth, &profile_dir, conversation_id.clone())?;
+    } else {
+        require_create_access(&auth, conversation_id.clone())?;
+    }
 
     // Parse the serialised CvJson back into a strongly-typed struct
     let cv_data: CvJson = serde_json::from_str(&request.data.cv_json).map_err(|e| {
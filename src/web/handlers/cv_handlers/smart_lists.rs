@@ -0,0 +1,220 @@
+// src/web/handlers/cv_handlers/smart_lists.rs
+//! Saved person searches; see [`crate::core::smart_lists`].
+use crate::auth::AuthenticatedUser;
+use crate::core::database::{get_tenant_folder_path, DatabaseConfig};
+use crate::core::person_tags;
+use crate::core::smart_lists::{self, Candidate, Criteria, SmartList};
+use crate::core::FsOps;
+use crate::web::handlers::common::{db_error, error_response, pool};
+use crate::web::types::{DataResponse, ServerConfig, StandardErrorResponse};
+use chrono::{Datelike, Utc};
+use graflog::app_log;
+use rocket::serde::json::Json;
+use rocket::State;
+use serde::{Deserialize, Serialize};
+
+use super::checklist::{onboarding_checklist, starter_experiences};
+use super::compare::{collect_skills, read_availability, years_of_experience};
+use super::helpers::{editor_required, load_profile_cv_data};
+
+#[derive(Debug, Deserialize)]
+pub struct SaveSmartListRequest {
+    pub name: String,
+    #[serde(flatten)]
+    pub criteria: Criteria,
+}
+
+/// A person found by a smart list, with what it was matched on.
+#[derive(Debug, Serialize)]
+pub struct SmartListMember {
+    pub name: String,
+    pub availability: Option<String>,
+    pub years_experience: f32,
+    /// Onboarding checklist done, in percent.
+    pub completeness: u8,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SmartListResult {
+    pub list: SmartList,
+    pub persons: Vec<SmartListMember>,
+}
+
+fn require_list_access(auth: &AuthenticatedUser) -> Result<(), Json<StandardErrorResponse>> {
+    if auth.role().can_create() {
+        return Ok(());
+    }
+    Err(editor_required(
+        "Managing smart lists requires the editor role".to_string(),
+        None,
+    ))
+}
+
+async fn find_list(
+    name: &str,
+    auth: &AuthenticatedUser,
+    db_config: &DatabaseConfig,
+) -> Result<SmartList, Json<StandardErrorResponse>> {
+    let not_found = || {
+        error_response(
+            format!("Smart list '{}' not found", name),
+            "SMART_LIST_NOT_FOUND",
+            vec!["GET /smart-lists shows the saved lists".to_string()],
+        )
+    };
+    let name = smart_lists::normalize_list_name(name).map_err(|_| not_found())?;
+    smart_lists::get(pool(db_config)?, &auth.account_key(), &name)
+        .await
+        .map_err(db_error)?
+        .ok_or_else(not_found)
+}
+
+/// The user's persons matching `criteria`, in name order.
+async fn evaluate(
+    criteria: &Criteria,
+    auth: &AuthenticatedUser,
+    config: &ServerConfig,
+    db_config: &DatabaseConfig,
+) -> Result<Vec<SmartListMember>, Json<StandardErrorResponse>> {
+    let tenant_data_dir = get_tenant_folder_path(auth.email(), &config.data_dir);
+    let profiles = FsOps::list_profiles(&tenant_data_dir).await.map_err(|e| {
+        app_log!(error, "Failed to list profiles: {}", e);
+        error_response(
+            "Failed to list persons".to_string(),
+            "LIST_ERROR",
+            vec!["Try again or contact support".to_string()],
+        )
+    })?;
+    let mut tags = person_tags::all(pool(db_config)?, &auth.account_key())
+        .await
+        .map_err(db_error)?;
+    let starter = starter_experiences(config).await;
+    let now = Utc::now();
+    let today = (now.year(), now.month());
+
+    let mut members = Vec::new();
+    for profile in profiles {
+        let profile_dir = tenant_data_dir.join(&profile);
+        let mut candidate = Candidate {
+            tags: tags.remove(&profile).unwrap_or_default(),
+            availability: read_availability(&profile_dir).await,
+            ..Default::default()
+        };
+        if criteria.needs_cv_data() {
+            match load_profile_cv_data(&profile, &tenant_data_dir).await {
+                Ok(cv) => {
+                    candidate.skills = collect_skills(&cv);
+                    candidate.years_experience = years_of_experience(&cv.work_experience, today);
+                }
+                Err(e) => app_log!(warn, "Smart list: cannot load profile {}: {}", profile, e),
+            }
+        }
+        let output_dir = config.person_output_dir(auth.email(), &profile);
+        candidate.completeness =
+            onboarding_checklist(profile.clone(), &profile_dir, starter.as_deref(), &output_dir)
+                .await
+                .completeness();
+        if criteria.matches(&candidate) {
+            members.push(SmartListMember {
+                name: profile,
+                availability: candidate.availability,
+                years_experience: candidate.years_experience,
+                completeness: candidate.completeness,
+            });
+        }
+    }
+    Ok(members)
+}
+
+/// Persons of the smart list `name`, for endpoints taking a list of persons.
+pub(super) async fn smart_list_persons(
+    name: &str,
+    auth: &AuthenticatedUser,
+    config: &ServerConfig,
+    db_config: &DatabaseConfig,
+) -> Result<Vec<String>, Json<StandardErrorResponse>> {
+    let list = find_list(name, auth, db_config).await?;
+    let members = evaluate(&list.criteria, auth, config, db_config).await?;
+    Ok(members.into_iter().map(|m| m.name).collect())
+}
+
+/// GET /smart-lists
+pub async fn list_smart_lists_handler(
+    auth: AuthenticatedUser,
+    db_config: &State<DatabaseConfig>,
+) -> Result<Json<DataResponse<Vec<SmartList>>>, Json<StandardErrorResponse>> {
+    let lists = smart_lists::list(pool(db_config)?, &auth.account_key())
+        .await
+        .map_err(db_error)?;
+    Ok(Json(DataResponse::success(
+        format!("{} smart lists", lists.len()),
+        lists,
+        None,
+    )))
+}
+
+/// POST /smart-lists — creates the list, or replaces the one of that name.
+pub async fn save_smart_list_handler(
+    request: Json<SaveSmartListRequest>,
+    auth: AuthenticatedUser,
+    db_config: &State<DatabaseConfig>,
+) -> Result<Json<DataResponse<SmartList>>, Json<StandardErrorResponse>> {
+    require_list_access(&auth)?;
+    let list = smart_lists::save(
+        pool(db_config)?,
+        &auth.account_key(),
+        &request.name,
+        &request.criteria,
+        auth.email(),
+    )
+    .await
+    .map_err(|e| match e.downcast_ref::<sqlx::Error>() {
+        Some(_) => db_error(e),
+        None => error_response(
+            e.to_string(),
+            "INVALID_SMART_LIST",
+            vec!["Name the list with letters, digits, '-' and '_'".to_string()],
+        ),
+    })?;
+    app_log!(info, "{} saved smart list {}", auth.email(), list.name);
+    Ok(Json(DataResponse::success(
+        format!("Smart list '{}' saved", list.name),
+        list,
+        None,
+    )))
+}
+
+/// GET /smart-lists/<name>/persons
+pub async fn evaluate_smart_list_handler(
+    name: String,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    db_config: &State<DatabaseConfig>,
+) -> Result<Json<DataResponse<SmartListResult>>, Json<StandardErrorResponse>> {
+    let list = find_list(&name, &auth, db_config).await?;
+    let persons = evaluate(&list.criteria, &auth, config, db_config).await?;
+    Ok(Json(DataResponse::success(
+        format!("{} persons in '{}'", persons.len(), list.name),
+        SmartListResult { list, persons },
+        None,
+    )))
+}
+
+/// DELETE /smart-lists/<name>
+pub async fn delete_smart_list_handler(
+    name: String,
+    auth: AuthenticatedUser,
+    db_config: &State<DatabaseConfig>,
+) -> Result<Json<DataResponse<SmartList>>, Json<StandardErrorResponse>> {
+    require_list_access(&auth)?;
+    let list = find_list(&name, &auth, db_config).await?;
+    smart_lists::delete(pool(db_config)?, &auth.account_key(), &list.name)
+        .await
+        .map_err(db_error)?;
+    app_log!(info, "{} deleted smart list {}", auth.email(), list.name);
+    Ok(Json(DataResponse::success(
+        format!("Smart list '{}' deleted", list.name),
+        list,
+        None,
+    )))
+}
@@ -0,0 +1,113 @@
+// src/web/handlers/cv_handlers/template_pin.rs
+//! Per-profile template pins and the versions recent generations used.
+use crate::auth::AuthenticatedUser;
+use crate::core::database::get_tenant_folder_path;
+use crate::core::template_versions::{self, ProfileTemplateVersions};
+use crate::core::TemplateEngine;
+use crate::utils::normalize_profile_name;
+use crate::web::handlers::common::error_response;
+use crate::web::types::{DataResponse, PinTemplateRequest, ServerConfig, StandardErrorResponse};
+use graflog::app_log;
+use rocket::serde::json::Json;
+use rocket::State;
+use std::path::PathBuf;
+
+use super::helpers::require_edit_access;
+
+fn profile_dir(
+    name: &str,
+    auth: &AuthenticatedUser,
+    config: &ServerConfig,
+) -> Result<PathBuf, Json<StandardErrorResponse>> {
+    let profile = normalize_profile_name(name);
+    let profile_dir = get_tenant_folder_path(auth.email(), &config.data_dir).join(&profile);
+    if profile.is_empty() || !profile_dir.is_dir() {
+        return Err(error_response(
+            format!("Profile '{}' not found in your account", name),
+            "PROFILE_NOT_FOUND",
+            vec!["Check the profile name spelling".to_string()],
+        ));
+    }
+    Ok(profile_dir)
+}
+
+/// GET /profiles/<name>/template-versions
+pub async fn template_versions_handler(
+    name: String,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+) -> Result<Json<DataResponse<ProfileTemplateVersions>>, Json<StandardErrorResponse>> {
+    let profile_dir = profile_dir(&name, &auth, config)?;
+    let data = template_versions::load(&profile_dir);
+    Ok(Json(DataResponse::success(
+        format!("{} pinned templates", data.pins.len()),
+        data,
+        None,
+    )))
+}
+
+/// PUT /profiles/<name>/template-pin
+pub async fn pin_template_handler(
+    name: String,
+    request: Json<PinTemplateRequest>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+) -> Result<Json<DataResponse<ProfileTemplateVersions>>, Json<StandardErrorResponse>> {
+    let profile_dir = profile_dir(&name, &auth, config)?;
+    require_edit_access(&auth, &profile_dir, None)?;
+
+    let engine = TemplateEngine::new(config.templates_dir.clone()).map_err(|e| {
+        app_log!(error, "Failed to initialize template manager: {}", e);
+        error_response(
+            "Template system initialization failed".to_string(),
+            "TEMPLATE_INIT_ERROR",
+            vec!["Contact system administrator".to_string()],
+        )
+    })?;
+    let template_id = request.template.trim().to_lowercase();
+    let Some(template) = engine.get_template(&template_id) else {
+        return Err(error_response(
+            format!("Template '{}' not found", request.template),
+            "TEMPLATE_NOT_FOUND",
+            vec!["Pick a template from GET /templates".to_string()],
+        ));
+    };
+
+    let version = request
+        .version
+        .as_deref()
+        .map(str::trim)
+        .filter(|v| !v.is_empty());
+    if let Some(version) = version {
+        // The current version is archived now so the pin keeps working
+        // after the template changes.
+        if let Err(e) = template_versions::archive(template, engine.templates_dir()) {
+            app_log!(warn, "Failed to archive template '{}': {}", template_id, e);
+        }
+        if let Err(e) = template_versions::version_dir(template, version) {
+            return Err(error_response(
+                e.to_string(),
+                "TEMPLATE_VERSION_NOT_FOUND",
+                vec![format!(
+                    "Available versions: {}",
+                    template_versions::versions(template).join(", ")
+                )],
+            ));
+        }
+    }
+
+    let data = template_versions::set_pin(&profile_dir, &template_id, version).map_err(|e| {
+        app_log!(error, "Failed to pin template for {}: {}", name, e);
+        error_response(
+            "Failed to update template pin".to_string(),
+            "FS_ERROR",
+            vec!["Try again or contact support".to_string()],
+        )
+    })?;
+
+    let message = match version {
+        Some(version) => format!("Template '{}' pinned to version {}", template_id, version),
+        None => format!("Template '{}' follows its current version", template_id),
+    };
+    Ok(Json(DataResponse::success(message, data, None)))
+}
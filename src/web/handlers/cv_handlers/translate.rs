@@ -1,12 +1,20 @@
 // src/web/handlers/cv_handlers/translate.rs
 //! CV translation handler
 use crate::auth::AuthenticatedUser;
+use crate::core::ai_budget::AiFeature;
 use crate::core::database::{get_tenant_folder_path, DatabaseConfig};
-use crate::core::ServiceClient;
-use crate::web::handlers::payment_handlers::check_and_deduct_credits;
+use crate::core::translation_status::{
+    self, LanguageStatus, Provenance, TranslationState,
+};
+use crate::core::{request_cancel, FsOps, ServiceClient};
+use crate::web::handlers::cv_handlers::helpers::{require_edit_access, source_experiences};
+use crate::web::handlers::payment_handlers::{check_and_deduct_credits, refund_credits};
+use crate::web::handlers::usage_handlers::{check_ai_budget, result_cache, usage_recorder};
 use crate::types::cv_data::CvConverter;
 use crate::types::response::TranslateResponse;
-use crate::web::types::{DataResponse, StandardErrorResponse, StandardRequest, WithConversationId};
+use crate::web::types::{
+    ClientRequestId, DataResponse, StandardErrorResponse, StandardRequest, WithConversationId,
+};
 use crate::web::ServerConfig;
 use graflog::app_log;
 use rocket::serde::{json::Json, Deserialize};
@@ -16,6 +24,9 @@ use rocket::State;
 pub struct TranslateCvRequest {
     pub profile_name: String,
     pub target_lang: String,
+    /// Call cv-import even if the same CV was translated recently.
+    #[serde(default)]
+    pub force_refresh: bool,
 }
 
 pub async fn translate_cv_handler(
@@ -24,11 +35,18 @@ pub async fn translate_cv_handler(
     config: &State<ServerConfig>,
     db_config: &State<DatabaseConfig>,
     cv_service_url: &State<String>,
+    request_id: ClientRequestId,
 ) -> Result<Json<DataResponse<TranslateResponse>>, Json<StandardErrorResponse>> {
     let user = auth.user();
     let tenant = auth.tenant();
     let conversation_id = request.conversation_id();
 
+    // The translation is saved into the profile.
+    let profile_dir = get_tenant_folder_path(&user.email, &config.data_dir)
+        .join(&request.data.profile_name);
+    require_edit_access(&auth, &profile_dir, conversation_id.clone())?;
+
+    check_ai_budget(db_config, &auth, AiFeature::Translation, conversation_id.clone()).await?;
     // Translation — 5 credits (¼ of a CV generation)
     check_and_deduct_credits(&user.email, 5, conversation_id.clone(), "translate").await?;
 
@@ -42,8 +60,6 @@ pub async fn translate_cv_handler(
     );
 
     // Load CV data from profile
-    let tenant_data_dir = get_tenant_folder_path(&user.email, &config.data_dir);
-    let profile_dir = tenant_data_dir.join(&request.data.profile_name);
     let toml_path = profile_dir.join("cv_params.toml");
 
     // Verify profile exists
@@ -57,17 +73,10 @@ pub async fn translate_cv_handler(
     }
 
     // Load CV data from profile files
-    // Try both language-specific and legacy filenames
-    let typst_path_en = profile_dir.join("experiences_en.typ");
-    let typst_path_legacy = profile_dir.join("experiences.typ");
-    
-    let active_typst_path = if typst_path_en.exists() {
-        typst_path_en
-    } else {
-        typst_path_legacy
-    };
+    let (source_lang, active_typst_path) = source_experiences(&profile_dir)
+        .unwrap_or_else(|| ("en".to_string(), profile_dir.join("experiences.typ")));
 
-    let cv_data = match CvConverter::from_files(&toml_path, &active_typst_path) {
+    let mut cv_data = match CvConverter::from_files(&toml_path, &active_typst_path) {
         Ok(data) => data,
         Err(e) => {
             app_log!(
@@ -88,9 +97,14 @@ pub async fn translate_cv_handler(
             )));
         }
     };
+    cv_data.metadata.language = source_lang.clone();
 
+    let cancel = request_id.register(auth.email());
     let service_client = match ServiceClient::new(cv_service_url.inner().clone(), 30) {
-        Ok(client) => client,
+        Ok(client) => client
+            .with_usage(usage_recorder(db_config, &auth))
+            .with_cancel(cancel.token())
+            .with_cache(result_cache(config, &auth, request.data.force_refresh)),
         Err(e) => {
             return Err(Json(StandardErrorResponse::new(
                 format!("Service initialization failed: {}", e),
@@ -125,11 +139,17 @@ pub async fn translate_cv_handler(
             // AUTO-SAVE: Write the translated content to experiences_{lang}.typ
             let target_filename = format!("experiences_{}.typ", request.data.target_lang);
             let target_path = profile_dir.join(&target_filename);
-            if let Err(e) = tokio::fs::write(&target_path, &translated_typst).await {
+            if let Err(e) = FsOps::write_file_safe(&target_path, &translated_typst).await {
                 app_log!(error, "Failed to auto-save translated CV to {}: {}", target_filename, e);
                 // We don't fail the whole request, but log it
             } else {
                 app_log!(info, "Auto-saved translated CV to {}", target_filename);
+                translation_status::record(
+                    &profile_dir,
+                    &request.data.target_lang,
+                    Provenance::Translated,
+                    Some(&source_lang),
+                );
             }
 
             app_log!(
@@ -179,6 +199,10 @@ pub async fn translate_cv_handler(
                 conversation_id,
             )))
         }
+        Err(e) if request_cancel::is_cancelled(&e) => {
+            refund_credits(&user.email, 5, "translate");
+            Err(Json(StandardErrorResponse::cancelled(conversation_id)))
+        }
         Err(e) => {
             app_log!(
                 error,
@@ -200,3 +224,32 @@ pub async fn translate_cv_handler(
         }
     }
 }
+
+/// GET /profiles/<name>/translations
+pub async fn translation_status_handler(
+    name: String,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+) -> Result<Json<DataResponse<Vec<LanguageStatus>>>, Json<StandardErrorResponse>> {
+    let profile = crate::utils::normalize_profile_name(&name);
+    let profile_dir = get_tenant_folder_path(auth.email(), &config.data_dir).join(&profile);
+    if profile.is_empty() || !profile_dir.is_dir() {
+        return Err(Json(StandardErrorResponse::new(
+            format!("Profile '{}' not found in your account", name),
+            "PROFILE_NOT_FOUND".to_string(),
+            vec!["Check the profile name spelling".to_string()],
+            None,
+        )));
+    }
+
+    let statuses = translation_status::status(&profile_dir);
+    let copies = statuses
+        .iter()
+        .filter(|s| s.status == TranslationState::Copy)
+        .count();
+    Ok(Json(DataResponse::success(
+        format!("{} languages, {} untranslated copies", statuses.len(), copies),
+        statuses,
+        None,
+    )))
+}
@@ -2,17 +2,32 @@
 //! CV upload and conversion handler
 
 use crate::auth::AuthenticatedUser;
-use crate::core::database::get_tenant_folder_path;
-use crate::core::{FsOps, ServiceClient};
+use crate::core::database::{get_tenant_folder_path, DatabaseConfig, Tenant, TenantRepository};
+use crate::core::import_retry::{self, FailedImport, RetryJob};
+use crate::core::import_staging::StagedImport;
+use crate::core::service_usage::UsageRecorder;
+use crate::core::upload_sessions::{self, CompletedUpload};
+use crate::core::presigned_uploads;
+use crate::core::profile_access::MemberRole;
+use crate::core::{data_residency, language_detect, FsOps, ServiceClient};
+use crate::web::handlers::system_handlers::ensure_storage_available;
 use crate::utils::normalize_profile_name;
-use crate::web::types::{ActionResponse, CvUploadForm, StandardErrorResponse, StandardRequest};
+use crate::web::types::{
+    ActionResponse, CvUploadForm, DataResponse, StandardErrorResponse, StandardRequest,
+};
 use graflog::{app_log, app_span};
 use rocket::form::Form;
+use rocket::fs::TempFile;
+use rocket::http::ContentType;
 use rocket::serde::{json::Json, Deserialize};
 use rocket::State;
+use sqlx::SqlitePool;
+use std::path::Path;
 
 use crate::web::handlers::payment_handlers::check_and_deduct_credits;
-use super::helpers::create_profile_from_cv_data;
+use crate::web::handlers::upload_handlers::upload_error;
+use crate::web::handlers::usage_handlers::usage_recorder;
+use super::helpers::{import_profile_from_cv_data, require_create_access};
 
 #[derive(Deserialize)]
 #[serde(crate = "rocket::serde")]
@@ -21,15 +36,168 @@ pub struct ImportTextRequest {
     pub profile_name: Option<String>,
 }
 
+/// Settle the imported CV's language (see [`language_detect`]) and return it.
+fn detect_language(cv_data: &mut crate::types::cv_data::CvJson, profile: &str) -> String {
+    let source = language_detect::apply(cv_data);
+    app_log!(
+        info,
+        "Imported CV for {} is in '{}' ({:?})",
+        profile,
+        cv_data.metadata.language,
+        source
+    );
+    cv_data.metadata.language.clone()
+}
+
+/// User-facing message and suggestions for a failed conversion.
+fn conversion_failure(err_str: &str) -> (String, Vec<String>) {
+    // Detect specific error types for targeted messages
+    if err_str.contains("Connection refused")
+        || err_str.contains("connection refused")
+        || err_str.contains("os error 111")
+        || err_str.contains("HTTP request failed")
+    {
+        (
+            "CV import service is unavailable".to_string(),
+            vec![
+                "The cv-import service is not running — contact the administrator".to_string(),
+                "Try again in a few minutes".to_string(),
+            ],
+        )
+    } else if err_str.contains("not_found_error")
+        || err_str.contains("LLMError")
+        || err_str.contains("Claude API Error")
+        || err_str.contains("model:")
+    {
+        (
+            "AI model error — the configured LLM model is unavailable or misconfigured".to_string(),
+            vec![
+                "The AI model may be deprecated or misspelled — check CV_IMPORT_MODEL".to_string(),
+                "Verify the CLAUDE_API_KEY is valid and has access to the model".to_string(),
+                "Contact the administrator to update the model configuration".to_string(),
+            ],
+        )
+    } else if err_str.contains("API key") || err_str.contains("authentication") || err_str.contains("401") {
+        (
+            "AI service authentication failed".to_string(),
+            vec![
+                "The API key for the AI provider is invalid or expired".to_string(),
+                "Contact the administrator to renew the API key".to_string(),
+            ],
+        )
+    } else if err_str.contains("No readable text")
+        || err_str.contains("empty text")
+        || err_str.contains("No text extracted")
+        || err_str.contains("non-standard encoding")
+    {
+        (
+            "Could not extract text from this PDF".to_string(),
+            vec![
+                "If this is a cvenom-generated PDF, your profile already exists — no import needed".to_string(),
+                "Try converting the PDF to DOCX first (LibreOffice or Word)".to_string(),
+                "Upload a PDF with selectable/copyable text".to_string(),
+            ],
+        )
+    } else if err_str.contains("Failed to deserialize cv_data")
+        || err_str.contains("CV service returned non-JSON response")
+    {
+        (
+            "We couldn't read the CV structure returned by the import service".to_string(),
+            vec![
+                "The CV was parsed but its format didn't match what we expected".to_string(),
+                "Try uploading the CV as DOCX instead of PDF".to_string(),
+                "If the problem persists, contact support — our team has been notified".to_string(),
+            ],
+        )
+    } else {
+        // Generic fallback — do NOT leak raw error text (may contain full JSON payloads)
+        (
+            "CV conversion failed".to_string(),
+            vec![
+                "Ensure the CV has selectable text (not a scanned image)".to_string(),
+                "Try DOCX format — it works more reliably than PDF".to_string(),
+                "Contact support if the problem persists".to_string(),
+            ],
+        )
+    }
+}
+
+/// Where the CV being converted comes from.
+enum CvSource<'a, 'r> {
+    Form(&'a mut TempFile<'r>),
+    /// A complete resumable or pre-signed upload.
+    Session(CompletedUpload),
+}
+
 pub async fn upload_and_convert_cv_handler(
     mut upload: Form<CvUploadForm<'_>>,
     auth: AuthenticatedUser,
     config: &State<crate::web::types::ServerConfig>,
     cv_service_url: &State<String>,
+    db_config: &State<DatabaseConfig>,
+) -> Result<Json<ActionResponse>, Json<StandardErrorResponse>> {
+    convert_cv(CvSource::Form(&mut upload.cv_file), auth, config, cv_service_url, db_config).await
+}
+
+/// POST /cv/upload/<id> — convert a complete resumable upload.
+pub async fn convert_upload_session_handler(
+    id: String,
+    auth: AuthenticatedUser,
+    config: &State<crate::web::types::ServerConfig>,
+    cv_service_url: &State<String>,
+    db_config: &State<DatabaseConfig>,
+) -> Result<Json<ActionResponse>, Json<StandardErrorResponse>> {
+    let upload = upload_sessions::take(auth.email(), &id).map_err(upload_error)?;
+    let path = upload.path.clone();
+    let result = convert_cv(CvSource::Session(upload), auth, config, cv_service_url, db_config).await;
+    // Moved into the import on success; anything left is a rejected upload.
+    let _ = tokio::fs::remove_file(&path).await;
+    result
+}
+
+/// POST /cv/upload/presigned/<id> — fetch a file sent to object storage and
+/// convert it.
+pub async fn convert_presigned_upload_handler(
+    id: String,
+    auth: AuthenticatedUser,
+    config: &State<crate::web::types::ServerConfig>,
+    cv_service_url: &State<String>,
+    db_config: &State<DatabaseConfig>,
+) -> Result<Json<ActionResponse>, Json<StandardErrorResponse>> {
+    let Some(s3) = presigned_uploads::config() else {
+        return Err(upload_error(upload_sessions::UploadError::NotFound));
+    };
+    let announced = presigned_uploads::find(auth.email(), &id).map_err(upload_error)?;
+    let key = announced.key.clone();
+    let tenant_dir = get_tenant_folder_path(auth.email(), &config.data_dir);
+    let dir = data_residency::data_root_of(&config.data_dir, &tenant_dir)
+        .join(upload_sessions::UPLOAD_DIR);
+    let upload = presigned_uploads::fetch(s3, announced, &dir)
+        .await
+        .map_err(upload_error)?;
+    presigned_uploads::forget(&id);
+    if let Err(e) = presigned_uploads::remove(s3, &key).await {
+        app_log!(warn, "Failed to delete uploaded object {}: {}", key, e);
+    }
+    let path = upload.path.clone();
+    let result = convert_cv(CvSource::Session(upload), auth, config, cv_service_url, db_config).await;
+    let _ = tokio::fs::remove_file(&path).await;
+    result
+}
+
+async fn convert_cv(
+    source: CvSource<'_, '_>,
+    auth: AuthenticatedUser,
+    config: &State<crate::web::types::ServerConfig>,
+    cv_service_url: &State<String>,
+    db_config: &State<DatabaseConfig>,
 ) -> Result<Json<ActionResponse>, Json<StandardErrorResponse>> {
     let user = auth.user();
     let tenant = auth.tenant();
 
+    ensure_storage_available(config, None)?;
+    require_create_access(&auth, None)?;
+
     // CV import calls Claude Sonnet — 4 credits ($1.00 at $0.25/credit)
     check_and_deduct_credits(&user.email, 4, None, "cv_import").await?;
 
@@ -47,13 +215,22 @@ pub async fn upload_and_convert_cv_handler(
     );
 
     // Extract file information
-    let content_type = upload.cv_file.content_type();
-    let file_size = upload.cv_file.len();
+    let (content_type, file_size, raw_name) = match &source {
+        CvSource::Form(file) => (
+            file.content_type().cloned(),
+            file.len(),
+            file.raw_name().and_then(|n| n.as_str()).map(str::to_string),
+        ),
+        CvSource::Session(upload) => (
+            upload.content_type.as_deref().and_then(ContentType::parse_flexible),
+            upload.size,
+            Some(upload.filename.clone()),
+        ),
+    };
+    let content_type = content_type.as_ref();
 
-    let original_filename = upload
-        .cv_file
-        .raw_name()
-        .and_then(|n| n.as_str())
+    let original_filename = raw_name
+        .as_deref()
         .unwrap_or_else(|| {
             if content_type.map_or(false, |ct| ct.is_pdf()) {
                 "uploaded_cv.pdf"
@@ -127,9 +304,36 @@ pub async fn upload_and_convert_cv_handler(
         )));
     }
 
-    let temp_path = std::env::temp_dir().join(format!("cv_upload_{}", uuid::Uuid::new_v4()));
+    let profile_name = original_filename
+        .split('.')
+        .next()
+        .unwrap_or(&original_filename)
+        .to_string();
+    let normalized_profile = normalize_profile_name(&profile_name);
+    let profile_dir = tenant_data_dir.join(&normalized_profile);
+
+    // Everything until the person is in place happens in a staging dir, so an
+    // interrupted import leaves nothing half-written behind.
+    let staged = match StagedImport::begin(&config.data_dir, &profile_dir).await {
+        Ok(staged) => staged,
+        Err(e) => {
+            app_log!(error, "Failed to stage CV import: {}", e);
+            return Err(Json(StandardErrorResponse::new(
+                "Failed to process uploaded file".to_string(),
+                "FILE_SAVE_ERROR".to_string(),
+                vec!["Try uploading the file again".to_string()],
+                None,
+            )));
+        }
+    };
+    let temp_path = staged.upload_path(&filename_with_extension);
 
-    if let Err(e) = upload.cv_file.persist_to(&temp_path).await {
+    let persisted = match source {
+        CvSource::Form(file) => file.persist_to(&temp_path).await,
+        // Uploads and staging share the data root, so this is a rename.
+        CvSource::Session(upload) => tokio::fs::rename(&upload.path, &temp_path).await,
+    };
+    if let Err(e) = persisted {
         app_log!(error, "Failed to save uploaded file: {}", e);
         return Err(Json(StandardErrorResponse::new(
             "Failed to process uploaded file".to_string(),
@@ -141,10 +345,9 @@ pub async fn upload_and_convert_cv_handler(
 
     // Initialize service client for cv-import
     let service_client = match ServiceClient::new(cv_service_url.inner().clone(), 400) {
-        Ok(client) => client,
+        Ok(client) => client.with_usage(usage_recorder(db_config, &auth)),
         Err(e) => {
             app_log!(error, "Failed to initialize service client: {}", e);
-            let _ = tokio::fs::remove_file(&temp_path).await;
             return Err(Json(StandardErrorResponse::new(
                 "Service configuration error".to_string(),
                 "SERVICE_CONFIG_ERROR".to_string(),
@@ -158,7 +361,7 @@ pub async fn upload_and_convert_cv_handler(
     };
 
     // Get CvJson from cv-import service
-    let cv_data = match service_client
+    let mut cv_data = match service_client
         .upload_cv(&temp_path, &filename_with_extension)
         .await
     {
@@ -167,9 +370,11 @@ pub async fn upload_and_convert_cv_handler(
             let err_str = e.to_string();
             app_log!(error, "CV conversion failed: {}", err_str);
 
-            // Preserve the failed upload to a debug folder so the admin can retrieve it.
-            let failed_dir = config.data_dir.join("failed_imports");
-            let saved_path_str = match FsOps::ensure_dir_exists(&failed_dir).await {
+            // Preserve the failed upload so it can be retried (see
+            // `import_retry`) and the admin can retrieve it.
+            let failed_dir = data_residency::data_root_of(&config.data_dir, &temp_path)
+                .join("failed_imports");
+            let stored = match FsOps::ensure_dir_exists(&failed_dir).await {
                 Ok(_) => {
                     let stamp = chrono::Utc::now().format("%Y%m%d_%H%M%S");
                     let safe_name = original_filename.replace('/', "_").replace('\\', "_");
@@ -180,20 +385,22 @@ pub async fn upload_and_convert_cv_handler(
                         safe_name
                     ));
                     match tokio::fs::rename(&temp_path, &dest).await {
-                        Ok(_) => dest.display().to_string(),
+                        Ok(_) => Some(dest),
                         Err(rename_err) => {
                             app_log!(error, "Failed to preserve failed CV upload: {}", rename_err);
-                            let _ = tokio::fs::remove_file(&temp_path).await;
-                            "<not preserved>".to_string()
+                            None
                         }
                     }
                 }
                 Err(dir_err) => {
                     app_log!(error, "Failed to create failed_imports dir: {}", dir_err);
-                    let _ = tokio::fs::remove_file(&temp_path).await;
-                    "<not preserved>".to_string()
+                    None
                 }
             };
+            let saved_path_str = stored
+                .as_ref()
+                .map(|dest| dest.display().to_string())
+                .unwrap_or_else(|| "<not preserved>".to_string());
 
             crate::email::notify_admin(crate::email::EmailKind::AdminCvImportFailed {
                 user_email: user.email.clone(),
@@ -202,75 +409,30 @@ pub async fn upload_and_convert_cv_handler(
                 saved_path: saved_path_str,
             });
 
-            // Detect specific error types for targeted messages
-            let (message, suggestions) = if err_str.contains("Connection refused")
-                || err_str.contains("connection refused")
-                || err_str.contains("os error 111")
-                || err_str.contains("HTTP request failed")
-            {
-                (
-                    "CV import service is unavailable".to_string(),
-                    vec![
-                        "The cv-import service is not running — contact the administrator".to_string(),
-                        "Try again in a few minutes".to_string(),
-                    ],
-                )
-            } else if err_str.contains("not_found_error")
-                || err_str.contains("LLMError")
-                || err_str.contains("Claude API Error")
-                || err_str.contains("model:")
-            {
-                (
-                    "AI model error — the configured LLM model is unavailable or misconfigured".to_string(),
-                    vec![
-                        "The AI model may be deprecated or misspelled — check CV_IMPORT_MODEL".to_string(),
-                        "Verify the CLAUDE_API_KEY is valid and has access to the model".to_string(),
-                        "Contact the administrator to update the model configuration".to_string(),
-                    ],
-                )
-            } else if err_str.contains("API key") || err_str.contains("authentication") || err_str.contains("401") {
-                (
-                    "AI service authentication failed".to_string(),
-                    vec![
-                        "The API key for the AI provider is invalid or expired".to_string(),
-                        "Contact the administrator to renew the API key".to_string(),
-                    ],
-                )
-            } else if err_str.contains("No readable text")
-                || err_str.contains("empty text")
-                || err_str.contains("No text extracted")
-                || err_str.contains("non-standard encoding")
-            {
-                (
-                    "Could not extract text from this PDF".to_string(),
-                    vec![
-                        "If this is a cvenom-generated PDF, your profile already exists — no import needed".to_string(),
-                        "Try converting the PDF to DOCX first (LibreOffice or Word)".to_string(),
-                        "Upload a PDF with selectable/copyable text".to_string(),
-                    ],
+            let (message, mut suggestions) = conversion_failure(&err_str);
+            let transient = import_retry::is_transient(&format!("{:#}", e));
+            if let (Some(stored), Ok(pool)) = (&stored, db_config.pool()) {
+                match import_retry::record(
+                    pool,
+                    &auth.account_key(),
+                    &user.email,
+                    &filename_with_extension,
+                    stored,
+                    &message,
+                    transient,
                 )
-            } else if err_str.contains("Failed to deserialize cv_data")
-                || err_str.contains("CV service returned non-JSON response")
-            {
-                (
-                    "We couldn't read the CV structure returned by the import service".to_string(),
-                    vec![
-                        "The CV was parsed but its format didn't match what we expected".to_string(),
-                        "Try uploading the CV as DOCX instead of PDF".to_string(),
-                        "If the problem persists, contact support — our team has been notified".to_string(),
-                    ],
-                )
-            } else {
-                // Generic fallback — do NOT leak raw error text (may contain full JSON payloads)
-                (
-                    "CV conversion failed".to_string(),
-                    vec![
-                        "Ensure the CV has selectable text (not a scanned image)".to_string(),
-                        "Try DOCX format — it works more reliably than PDF".to_string(),
-                        "Contact support if the problem persists".to_string(),
-                    ],
-                )
-            };
+                .await
+                {
+                    Ok(job) if job.status == "retrying" => suggestions.insert(
+                        0,
+                        "We kept your file and will retry the import automatically".to_string(),
+                    ),
+                    Ok(_) => suggestions.push(
+                        "Your file was kept: retry it from your failed imports".to_string(),
+                    ),
+                    Err(e) => app_log!(error, "Failed to record failed import: {}", e),
+                }
+            }
 
             return Err(Json(StandardErrorResponse::new(
                 message,
@@ -281,18 +443,10 @@ pub async fn upload_and_convert_cv_handler(
         }
     };
 
-    let _ = tokio::fs::remove_file(&temp_path).await;
-
-    let profile_name = original_filename
-        .split('.')
-        .next()
-        .unwrap_or(&original_filename);
-
-    let normalized_profile = normalize_profile_name(profile_name);
-    let profile_dir = tenant_data_dir.join(&normalized_profile);
+    let lang = detect_language(&mut cv_data, &normalized_profile);
 
     // Convert CvJson to local file structure
-    match create_profile_from_cv_data(&profile_dir, &cv_data, &normalized_profile).await {
+    match import_profile_from_cv_data(staged, &cv_data, &normalized_profile).await {
         Ok(_) => {
             app_log!(
                 info,
@@ -302,6 +456,18 @@ pub async fn upload_and_convert_cv_handler(
                 tenant.tenant_name
             );
 
+            crate::core::event_export::emit(
+                crate::core::event_export::EventKind::CvImported,
+                &tenant.tenant_name,
+                &user.email,
+                serde_json::json!({
+                    "profile": normalized_profile,
+                    "source": "file",
+                    "file_size": file_size,
+                    "lang": lang,
+                }),
+            );
+
             let next_actions = vec![
                 format!("Upload profile picture for {}", profile_name),
                 format!("Edit CV parameters for {}", profile_name),
@@ -312,7 +478,7 @@ pub async fn upload_and_convert_cv_handler(
                 &user.email,
                 crate::email::EmailKind::CvImported {
                     profile: profile_name.to_string(),
-                    lang: "auto".into(),
+                    lang: lang.clone(),
                 },
                 auth.lang(),
                 auth.email_prefs(),
@@ -320,8 +486,8 @@ pub async fn upload_and_convert_cv_handler(
 
             let response = ActionResponse::success(
                 format!(
-                    "CV successfully converted and profile '{}' created",
-                    profile_name
+                    "CV successfully converted and profile '{}' created (language: {})",
+                    profile_name, lang
                 ),
                 "created".to_string(),
                 None,
@@ -353,6 +519,7 @@ pub async fn import_text_cv_handler(
     auth: AuthenticatedUser,
     config: &State<crate::web::types::ServerConfig>,
     cv_service_url: &State<String>,
+    db_config: &State<DatabaseConfig>,
 ) -> Result<Json<ActionResponse>, Json<StandardErrorResponse>> {
     let user = auth.user();
     let tenant = auth.tenant();
@@ -379,6 +546,8 @@ pub async fn import_text_cv_handler(
         )));
     }
 
+    require_create_access(&auth, None)?;
+
     // CV import calls an LLM — 4 credits
     check_and_deduct_credits(&user.email, 4, None, "cv_import_text").await?;
 
@@ -411,7 +580,7 @@ pub async fn import_text_cv_handler(
     }
 
     let service_client = match ServiceClient::new(cv_service_url.inner().clone(), 400) {
-        Ok(c) => c,
+        Ok(c) => c.with_usage(usage_recorder(db_config, &auth)),
         Err(e) => {
             app_log!(error, "Failed to initialize service client: {}", e);
             return Err(Json(StandardErrorResponse::new(
@@ -423,7 +592,7 @@ pub async fn import_text_cv_handler(
         }
     };
 
-    let cv_data = match service_client.import_text_cv(&cv_text, &normalized_profile).await {
+    let mut cv_data = match service_client.import_text_cv(&cv_text, &normalized_profile).await {
         Ok(data) => data,
         Err(e) => {
             let err_str = e.to_string();
@@ -455,9 +624,14 @@ pub async fn import_text_cv_handler(
         }
     };
 
+    let lang = detect_language(&mut cv_data, &normalized_profile);
     let profile_dir = tenant_data_dir.join(&normalized_profile);
+    let created = match StagedImport::begin(&config.data_dir, &profile_dir).await {
+        Ok(staged) => import_profile_from_cv_data(staged, &cv_data, &normalized_profile).await,
+        Err(e) => Err(e),
+    };
 
-    match create_profile_from_cv_data(&profile_dir, &cv_data, &normalized_profile).await {
+    match created {
         Ok(_) => {
             app_log!(
                 info,
@@ -467,6 +641,18 @@ pub async fn import_text_cv_handler(
                 tenant.tenant_name
             );
 
+            crate::core::event_export::emit(
+                crate::core::event_export::EventKind::CvImported,
+                &tenant.tenant_name,
+                &user.email,
+                serde_json::json!({
+                    "profile": normalized_profile,
+                    "source": "text",
+                    "chars": cv_text.len(),
+                    "lang": lang,
+                }),
+            );
+
             let next_actions = vec![
                 format!("Upload profile picture for {}", normalized_profile),
                 format!("Generate CV PDF for {}", normalized_profile),
@@ -477,7 +663,7 @@ pub async fn import_text_cv_handler(
                 &user.email,
                 crate::email::EmailKind::CvImported {
                     profile: normalized_profile.clone(),
-                    lang: "auto".into(),
+                    lang: lang.clone(),
                 },
                 auth.lang(),
                 auth.email_prefs(),
@@ -485,7 +671,10 @@ pub async fn import_text_cv_handler(
 
             Ok(Json(
                 ActionResponse::success(
-                    format!("Profile '{}' created from imported CV text", normalized_profile),
+                    format!(
+                        "Profile '{}' created from imported CV text (language: {})",
+                        normalized_profile, lang
+                    ),
                     "created".to_string(),
                     None,
                 )
@@ -503,3 +692,214 @@ pub async fn import_text_cv_handler(
         }
     }
 }
+
+// ── Failed import retries (core::import_retry) ───────────────────────────────
+
+/// Convert the stored file of `job` and import it, as `/cv/upload` would.
+/// Service calls are recorded for `account`, the job owner's tenant.
+async fn reconvert(
+    pool: &SqlitePool,
+    data_dir: &Path,
+    cv_service_url: &str,
+    job: &RetryJob,
+    account: &Tenant,
+) -> anyhow::Result<(String, String)> {
+    let tenant_data_dir = get_tenant_folder_path(&job.user_email, data_dir);
+    FsOps::ensure_dir_exists(&tenant_data_dir).await?;
+    let profile = normalize_profile_name(job.filename.split('.').next().unwrap_or(&job.filename));
+    let staged = StagedImport::begin(data_dir, &tenant_data_dir.join(&profile)).await?;
+    let upload_path = staged.upload_path(&job.filename);
+    tokio::fs::copy(job.stored_path(), &upload_path).await?;
+
+    let service_client = ServiceClient::new(cv_service_url.to_string(), 400)?
+        .with_usage(Some(UsageRecorder::new(pool, account.id, &job.user_email)));
+    let mut cv_data = service_client.upload_cv(&upload_path, &job.filename).await?;
+    let lang = detect_language(&mut cv_data, &profile);
+    import_profile_from_cv_data(staged, &cv_data, &profile).await?;
+    Ok((profile, lang))
+}
+
+/// The account of the job's owner, if it may still create profiles: it
+/// exists, is not archived and its role allows it, as checked for
+/// `/cv/upload`. Otherwise why not, and whether that may change by itself.
+async fn retry_account(pool: &SqlitePool, job: &RetryJob) -> Result<Tenant, (String, bool)> {
+    let unavailable = |e: anyhow::Error| {
+        app_log!(error, "Failed to check the account of import {}: {}", job.id, e);
+        ("Database unavailable".to_string(), true)
+    };
+    let repo = TenantRepository::new(pool);
+    let account = repo
+        .find_by_email_or_domain(&job.user_email)
+        .await
+        .map_err(unavailable)?
+        .ok_or_else(|| ("The account of this upload no longer exists".to_string(), false))?;
+    if account.archived_at.is_some() {
+        return Err(("This account is archived".to_string(), false));
+    }
+    let role = repo
+        .get_member_role(&job.user_email)
+        .await
+        .map_err(unavailable)?
+        .as_deref()
+        .and_then(MemberRole::parse)
+        .unwrap_or_else(|| MemberRole::default_for(&account));
+    if !role.can_create() {
+        return Err(("Creating profiles requires the editor role".to_string(), false));
+    }
+    Ok(account)
+}
+
+/// Retry a failed import claimed with [`import_retry::claim`] or
+/// [`import_retry::claim_due`], and record the outcome. Returns the profile
+/// and language, or the message for the user. Retries are not charged again.
+pub async fn retry_failed_import(
+    pool: &SqlitePool,
+    data_dir: &Path,
+    cv_service_url: &str,
+    job: RetryJob,
+) -> Result<(String, String), String> {
+    let account = match retry_account(pool, &job).await {
+        Ok(account) => account,
+        Err((message, transient)) => {
+            app_log!(warn, "Retry of import {} refused: {}", job.id, message);
+            if let Err(e) = import_retry::failed(pool, &job, &message, transient).await {
+                app_log!(error, "Failed to record retry of import {}: {}", job.id, e);
+            }
+            return Err(message);
+        }
+    };
+    match reconvert(pool, data_dir, cv_service_url, &job, &account).await {
+        Ok((profile, lang)) => {
+            if let Err(e) = import_retry::succeeded(pool, &job, &profile).await {
+                app_log!(error, "Failed to mark import {} as done: {}", job.id, e);
+            }
+            app_log!(
+                info,
+                "Retried import {} of {} created profile {}",
+                job.id,
+                job.user_email,
+                profile
+            );
+            crate::core::event_export::emit(
+                crate::core::event_export::EventKind::CvImported,
+                &account.tenant_name,
+                &job.user_email,
+                serde_json::json!({
+                    "profile": profile,
+                    "source": "retry",
+                    "attempts": job.attempts + 1,
+                    "lang": lang,
+                }),
+            );
+            crate::email::send_email_with_prefs(
+                &job.user_email,
+                crate::email::EmailKind::CvImported {
+                    profile: profile.clone(),
+                    lang: lang.clone(),
+                },
+                account.preferred_lang.as_deref().unwrap_or("en"),
+                account.email_prefs.as_deref(),
+            );
+            Ok((profile, lang))
+        }
+        Err(e) => {
+            app_log!(warn, "Retry of import {} failed: {:#}", job.id, e);
+            let (message, _) = conversion_failure(&e.to_string());
+            let transient = import_retry::is_transient(&format!("{:#}", e));
+            if let Err(e) = import_retry::failed(pool, &job, &message, transient).await {
+                app_log!(error, "Failed to record retry of import {}: {}", job.id, e);
+            }
+            Err(message)
+        }
+    }
+}
+
+/// GET /imports/failed
+pub async fn list_failed_imports_handler(
+    auth: AuthenticatedUser,
+    db_config: &State<DatabaseConfig>,
+) -> Result<Json<DataResponse<Vec<FailedImport>>>, Json<StandardErrorResponse>> {
+    let pool = db_config.pool().map_err(|e| {
+        app_log!(error, "Failed imports unavailable: {}", e);
+        Json(StandardErrorResponse::new(
+            "Database unavailable".to_string(),
+            "DB_ERROR".to_string(),
+            vec![],
+            None,
+        ))
+    })?;
+    let jobs = import_retry::list_for(pool, auth.email()).await.map_err(|e| {
+        app_log!(error, "Failed to list failed imports: {}", e);
+        Json(StandardErrorResponse::new(
+            "Failed to list failed imports".to_string(),
+            "DB_ERROR".to_string(),
+            vec![],
+            None,
+        ))
+    })?;
+    Ok(Json(DataResponse::success(
+        format!("{} failed imports", jobs.len()),
+        jobs,
+        None,
+    )))
+}
+
+/// POST /imports/failed/<id>/retry
+pub async fn retry_failed_import_handler(
+    id: String,
+    auth: AuthenticatedUser,
+    config: &State<crate::web::types::ServerConfig>,
+    cv_service_url: &State<String>,
+    db_config: &State<DatabaseConfig>,
+) -> Result<Json<ActionResponse>, Json<StandardErrorResponse>> {
+    ensure_storage_available(config, None)?;
+    require_create_access(&auth, None)?;
+    let db_error = |e: anyhow::Error| {
+        app_log!(error, "Failed to claim import {}: {}", id, e);
+        Json(StandardErrorResponse::new(
+            "Database unavailable".to_string(),
+            "DB_ERROR".to_string(),
+            vec![],
+            None,
+        ))
+    };
+    let pool = db_config.pool().map_err(db_error)?;
+    let Some(job) = import_retry::claim(pool, auth.email(), &id)
+        .await
+        .map_err(db_error)?
+    else {
+        return Err(Json(StandardErrorResponse::new(
+            format!("No failed import '{}' to retry", id),
+            "IMPORT_NOT_FOUND".to_string(),
+            vec![
+                "It may already be imported or being retried".to_string(),
+                "List your failed imports with GET /imports/failed".to_string(),
+            ],
+            None,
+        )));
+    };
+
+    match retry_failed_import(pool, &config.data_dir, cv_service_url.inner(), job).await {
+        Ok((profile, lang)) => Ok(Json(
+            ActionResponse::success(
+                format!(
+                    "CV successfully converted and profile '{}' created (language: {})",
+                    profile, lang
+                ),
+                "created".to_string(),
+                None,
+            )
+            .with_next_actions(vec![
+                format!("Upload profile picture for {}", profile),
+                format!("Edit CV parameters for {}", profile),
+                format!("Generate CV PDF for {}", profile),
+            ]),
+        )),
+        Err(message) => Err(Json(StandardErrorResponse::new(
+            message,
+            "CONVERSION_ERROR".to_string(),
+            vec!["Your file is still kept; you can retry it later".to_string()],
+            None,
+        ))),
+    }
+}
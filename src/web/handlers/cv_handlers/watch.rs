@@ -0,0 +1,125 @@
+// src/web/handlers/cv_handlers/watch.rs
+//! Watching profiles for changes; see [`crate::core::profile_watch`].
+use crate::auth::AuthenticatedUser;
+use crate::core::config_manager;
+use crate::core::database::{get_tenant_folder_path, DatabaseConfig};
+use crate::core::profile_watch::{self, ProfileWatch, WatchOptions};
+use crate::utils::normalize_profile_name;
+use crate::web::handlers::common::{db_error, error_response, pool, require_feature};
+use crate::web::types::{DataResponse, ServerConfig, StandardErrorResponse};
+use futures::Stream;
+use graflog::app_log;
+use rocket::response::stream::{Event, EventStream};
+use rocket::serde::json::Json;
+use rocket::State;
+use tokio::sync::broadcast::error::RecvError;
+
+/// The normalized name of an existing profile of the caller's account.
+fn existing_profile(
+    name: &str,
+    auth: &AuthenticatedUser,
+    config: &ServerConfig,
+) -> Result<String, Json<StandardErrorResponse>> {
+    let profile = normalize_profile_name(name);
+    let profile_dir = get_tenant_folder_path(auth.email(), &config.data_dir).join(&profile);
+    if profile.is_empty() || !profile_dir.is_dir() {
+        return Err(error_response(
+            format!("Profile '{}' not found in your account", name),
+            "PROFILE_NOT_FOUND",
+            vec!["Check the profile name spelling".to_string()],
+        ));
+    }
+    Ok(profile)
+}
+
+/// PUT /profiles/<name>/watch
+pub async fn watch_profile_handler(
+    name: String,
+    request: Option<Json<WatchOptions>>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    db_config: &State<DatabaseConfig>,
+) -> Result<Json<DataResponse<ProfileWatch>>, Json<StandardErrorResponse>> {
+    require_feature(config_manager::features().sharing, "sharing", None)?;
+    let profile = existing_profile(&name, &auth, config)?;
+    let pool = pool(db_config)?;
+    let options = request.map(Json::into_inner).unwrap_or_default();
+    let watch = profile_watch::watch(pool, &auth.account_key(), &profile, auth.email(), &options)
+        .await
+        .map_err(|e| match e.downcast_ref::<sqlx::Error>() {
+            Some(_) => db_error(e),
+            None => error_response(
+                e.to_string(),
+                "INVALID_WATCH",
+                vec!["Send an https:// webhook_url together with a webhook_secret".to_string()],
+            ),
+        })?;
+    app_log!(info, "{} watches {}", auth.email(), profile);
+    Ok(Json(DataResponse::success(
+        format!("Watching '{}'", profile),
+        watch,
+        None,
+    )))
+}
+
+/// DELETE /profiles/<name>/watch
+pub async fn unwatch_profile_handler(
+    name: String,
+    auth: AuthenticatedUser,
+    db_config: &State<DatabaseConfig>,
+) -> Result<Json<DataResponse<bool>>, Json<StandardErrorResponse>> {
+    // The profile may already be gone; the watch is removed either way.
+    let profile = normalize_profile_name(&name);
+    let pool = pool(db_config)?;
+    let removed = profile_watch::unwatch(pool, &auth.account_key(), &profile, auth.email())
+        .await
+        .map_err(db_error)?;
+    let message = if removed {
+        format!("Stopped watching '{}'", profile)
+    } else {
+        format!("You were not watching '{}'", profile)
+    };
+    Ok(Json(DataResponse::success(message, removed, None)))
+}
+
+/// GET /watches
+pub async fn list_watches_handler(
+    auth: AuthenticatedUser,
+    db_config: &State<DatabaseConfig>,
+) -> Result<Json<DataResponse<Vec<ProfileWatch>>>, Json<StandardErrorResponse>> {
+    let pool = pool(db_config)?;
+    let watches = profile_watch::list_for(pool, &auth.account_key(), auth.email())
+        .await
+        .map_err(db_error)?;
+    Ok(Json(DataResponse::success(
+        format!("Watching {} profiles", watches.len()),
+        watches,
+        None,
+    )))
+}
+
+/// GET /watches/events — `change` events for the caller's watches as they
+/// happen; `lagged` tells how many were dropped for a slow reader.
+pub fn watch_events_handler(auth: AuthenticatedUser) -> EventStream<impl Stream<Item = Event>> {
+    let owner = auth.account_key();
+    let email = auth.email().to_lowercase();
+    let events = futures::stream::unfold(profile_watch::subscribe(), move |mut receiver| {
+        let (owner, email) = (owner.clone(), email.clone());
+        async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(notice) if notice.owner == owner && notice.recipient == email => {
+                        return Some((Event::json(&notice).event("change"), receiver));
+                    }
+                    Ok(_) => continue,
+                    Err(RecvError::Lagged(missed)) => {
+                        let event = Event::json(&serde_json::json!({ "missed": missed }));
+                        return Some((event.event("lagged"), receiver));
+                    }
+                    Err(RecvError::Closed) => return None,
+                }
+            }
+        }
+    });
+    EventStream::from(events)
+}
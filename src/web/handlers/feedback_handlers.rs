@@ -6,6 +6,7 @@
 
 use crate::auth::AuthenticatedUser;
 use crate::core::database::DatabaseConfig;
+use crate::web::handlers::common::ADMIN_EMAIL;
 use crate::web::handlers::payment_handlers::api0_topup_credits;
 use crate::web::types::StandardErrorResponse;
 use graflog::app_log;
@@ -14,7 +15,6 @@ use serde::{Deserialize, Serialize};
 
 const CREDIT_REWARD: i64 = 10;
 const MIN_WORDS: usize = 10;
-const ADMIN_EMAIL: &str = "mohamed.bennekrouf@gmail.com";
 
 // ── Request / response types ─────────────────────────────────────────────────
 
@@ -0,0 +1,130 @@
+// src/web/handlers/generation_handlers.rs
+//! Deleting generated CVs; see [`crate::core::generation_outputs`].
+
+use graflog::app_log;
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rocket::State;
+use std::path::Path;
+
+use crate::auth::AuthenticatedUser;
+use crate::core::database::{get_tenant_folder_path, get_tenant_output_path, DatabaseConfig};
+use crate::core::generation_outputs::{self, DeletedGeneration};
+use crate::core::profile_lookup;
+use crate::web::handlers::common::{error_response, pool};
+use crate::web::types::{DataResponse, ServerConfig, StandardErrorResponse};
+
+fn delete_error(e: anyhow::Error) -> Json<StandardErrorResponse> {
+    app_log!(error, "Failed to delete generated CV: {}", e);
+    error_response(
+        "Failed to delete the generated CV".to_string(),
+        "GENERATION_DELETE_ERROR",
+        vec!["Try again in a few moments".to_string()],
+    )
+}
+
+/// DELETE /generations/<id>
+pub async fn delete_generation_handler(
+    id: String,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    db_config: &State<DatabaseConfig>,
+) -> Result<Json<DataResponse<DeletedGeneration>>, Json<StandardErrorResponse>> {
+    let pool = pool(db_config)?;
+    let user_dir = get_tenant_output_path(auth.email(), &config.output_dir);
+    let Some((profile, path)) = generation_outputs::find(&user_dir, &id).await else {
+        return Err(error_response(
+            format!("Generated CV '{}' not found", id),
+            "GENERATION_NOT_FOUND",
+            vec!["Use the X-Generation-Id of a CV you generated".to_string()],
+        ));
+    };
+    let deleted = generation_outputs::delete(
+        pool,
+        &config.output_dir,
+        Some(auth.tenant_id()),
+        auth.email(),
+        &profile,
+        &path,
+    )
+    .await
+    .map_err(delete_error)?;
+    app_log!(info, "{} deleted generated CV {}/{}", auth.email(), profile, deleted.filename);
+    Ok(Json(DataResponse::success(
+        format!("Deleted '{}'", deleted.filename),
+        deleted,
+        None,
+    )))
+}
+
+/// DELETE /profiles/<name>/generations?older_than_days=
+pub async fn purge_generations_handler(
+    name: String,
+    older_than_days: Option<u32>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    db_config: &State<DatabaseConfig>,
+) -> Result<Json<DataResponse<Vec<DeletedGeneration>>>, Json<StandardErrorResponse>> {
+    let tenant_dir = get_tenant_folder_path(auth.email(), &config.data_dir);
+    let Some(profile) = profile_lookup::resolve(&tenant_dir, &name).await else {
+        return Err(error_response(
+            format!("Profile '{}' not found in your account", name),
+            "PROFILE_NOT_FOUND",
+            vec!["Check the profile name spelling".to_string()],
+        ));
+    };
+    let pool = pool(db_config)?;
+    let before = older_than_days
+        .map(|days| chrono::Utc::now() - chrono::Duration::days(i64::from(days)));
+    let deleted = generation_outputs::purge(
+        pool,
+        &config.output_dir,
+        &get_tenant_output_path(auth.email(), &config.output_dir),
+        Some(auth.tenant_id()),
+        auth.email(),
+        &profile,
+        before,
+    )
+    .await
+    .map_err(delete_error)?;
+    app_log!(
+        info,
+        "{} purged {} generated CVs of {}",
+        auth.email(),
+        deleted.len(),
+        profile
+    );
+    Ok(Json(DataResponse::success(
+        format!("Deleted {} generated CVs of '{}'", deleted.len(), profile),
+        deleted,
+        None,
+    )))
+}
+
+/// The response for an `/outputs` file that is gone: 410 with the deletion
+/// date when it was deleted on purpose, `None` for a plain 404.
+pub async fn output_tombstone(
+    file: &Path,
+    db_config: &DatabaseConfig,
+) -> Option<(Status, Json<StandardErrorResponse>)> {
+    let pool = db_config.pool().ok()?;
+    let key = generation_outputs::share_key(file);
+    let tombstone = match generation_outputs::tombstone(pool, &key).await {
+        Ok(tombstone) => tombstone?,
+        Err(e) => {
+            app_log!(warn, "Failed to look up tombstone of {}: {}", key, e);
+            return None;
+        }
+    };
+    Some((
+        Status::Gone,
+        error_response(
+            format!(
+                "This CV was deleted by its owner on {}",
+                tombstone.deleted_at.format("%Y-%m-%d")
+            ),
+            "GENERATION_DELETED",
+            vec!["Ask the owner for a new link".to_string()],
+        ),
+    ))
+}
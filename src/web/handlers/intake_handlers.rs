@@ -0,0 +1,397 @@
+// src/web/handlers/intake_handlers.rs
+//! Email-in CV intake: per-user address and the inbound mail webhook.
+//!
+//! The webhook acknowledges immediately and converts attachments in the
+//! background — conversion can take minutes and mail providers retry on slow
+//! or failed deliveries. Each imported CV becomes a new profile (never
+//! overwriting one) and the owner gets the usual "CV imported" email.
+
+use base64::Engine;
+use graflog::app_log;
+use rocket::data::ToByteUnit;
+use rocket::http::Status;
+use rocket::request::{FromRequest, Outcome};
+use rocket::serde::json::Json;
+use rocket::{Data, Request, State};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::path::PathBuf;
+
+use crate::auth::AuthenticatedUser;
+use crate::core::database::{get_tenant_folder_path, DatabaseConfig, TenantRepository};
+use crate::core::email_intake::{
+    self, MAX_ATTACHMENTS_PER_MESSAGE, MAX_ATTACHMENT_BYTES, MAX_MESSAGE_BYTES,
+};
+use crate::core::service_usage::UsageRecorder;
+use crate::core::{disk_monitor, language_detect, FsOps, ServiceClient};
+use crate::utils::{constant_time_eq, normalize_profile_name};
+use crate::web::handlers::common::{db_error, error_response};
+use crate::web::handlers::cv_handlers::helpers::{
+    create_profile_from_cv_data, unique_profile_name,
+};
+use crate::web::handlers::payment_handlers::check_and_deduct_credits;
+use crate::web::types::{ActionResponse, DataResponse, ServerConfig, StandardErrorResponse};
+
+// ── Types ─────────────────────────────────────────────────────────────────────
+
+#[derive(Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct IntakeAddress {
+    pub address: String,
+    pub enabled: bool,
+}
+
+/// Postmark inbound JSON (the subset we use). Other providers can be pointed
+/// at the endpoint through a small transform that emits the same fields.
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde", rename_all = "PascalCase")]
+pub struct InboundEmail {
+    #[serde(default)]
+    pub from: String,
+    #[serde(default)]
+    pub to: String,
+    #[serde(default)]
+    pub original_recipient: Option<String>,
+    #[serde(default)]
+    pub subject: String,
+    #[serde(default)]
+    pub attachments: Vec<InboundAttachment>,
+}
+
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde", rename_all = "PascalCase")]
+pub struct InboundAttachment {
+    pub name: String,
+    /// Base64-encoded file content.
+    pub content: String,
+}
+
+/// Request guard checking `X-Inbound-Secret` against `INBOUND_EMAIL_SECRET`.
+pub struct InboundSecret;
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for InboundSecret {
+    type Error = ();
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let Some(expected) = email_intake::inbound_secret() else {
+            return Outcome::Error((Status::NotFound, ()));
+        };
+        match req.headers().get_one("X-Inbound-Secret") {
+            Some(given) if constant_time_eq(given.as_bytes(), expected.as_bytes()) => {
+                Outcome::Success(InboundSecret)
+            }
+            _ => Outcome::Error((Status::Unauthorized, ())),
+        }
+    }
+}
+
+// ── GET /intake/email-address ─────────────────────────────────────────────────
+
+/// The caller's private intake address, created on first use.
+pub async fn get_intake_address_handler(
+    auth: AuthenticatedUser,
+    db_config: &State<DatabaseConfig>,
+) -> Result<Json<DataResponse<IntakeAddress>>, Json<StandardErrorResponse>> {
+    let pool = db_config.pool().map_err(db_error)?;
+    let token = email_intake::get_or_create_token(pool, auth.email())
+        .await
+        .map_err(db_error)?;
+
+    Ok(Json(DataResponse::success(
+        "Email CVs (PDF or DOCX) to this address to import them".to_string(),
+        IntakeAddress {
+            address: email_intake::intake_address(&token),
+            enabled: email_intake::inbound_secret().is_some(),
+        },
+        None,
+    )))
+}
+
+// ── POST /intake/email-address/rotate ─────────────────────────────────────────
+
+/// Issue a new address; mail to the old one is ignored from now on.
+pub async fn rotate_intake_address_handler(
+    auth: AuthenticatedUser,
+    db_config: &State<DatabaseConfig>,
+) -> Result<Json<DataResponse<IntakeAddress>>, Json<StandardErrorResponse>> {
+    let pool = db_config.pool().map_err(db_error)?;
+    let token = email_intake::rotate_token(pool, auth.email())
+        .await
+        .map_err(db_error)?;
+
+    app_log!(info, "Intake address rotated for {}", auth.email());
+
+    Ok(Json(DataResponse::success(
+        "Intake address replaced — the previous address no longer works".to_string(),
+        IntakeAddress {
+            address: email_intake::intake_address(&token),
+            enabled: email_intake::inbound_secret().is_some(),
+        },
+        None,
+    )))
+}
+
+// ── POST /intake/email ────────────────────────────────────────────────────────
+
+/// The message posted by the provider. Its attachments are inline, so it is
+/// read up to [`MAX_MESSAGE_BYTES`] rather than the JSON limit.
+async fn read_message(data: Data<'_>) -> Result<InboundEmail, Json<StandardErrorResponse>> {
+    let body = data
+        .open(MAX_MESSAGE_BYTES.bytes())
+        .into_bytes()
+        .await
+        .map_err(|e| {
+            error_response(format!("Failed to read the message: {}", e), "INVALID_INPUT", vec![])
+        })?;
+    if !body.is_complete() {
+        return Err(error_response(
+            format!("The message is larger than {} MiB", MAX_MESSAGE_BYTES / (1024 * 1024)),
+            "PAYLOAD_TOO_LARGE",
+            vec![],
+        ));
+    }
+    serde_json::from_slice(&body).map_err(|e| {
+        error_response(format!("Invalid inbound message: {}", e), "INVALID_JSON", vec![])
+    })
+}
+
+/// Mail provider webhook. Unknown recipients and messages without CV
+/// attachments are acknowledged and dropped so the provider does not retry.
+pub async fn inbound_email_handler(
+    data: Data<'_>,
+    config: &State<ServerConfig>,
+    db_config: &State<DatabaseConfig>,
+    cv_service_url: &State<String>,
+) -> Result<Json<ActionResponse>, Json<StandardErrorResponse>> {
+    let email = read_message(data).await?;
+    let pool = db_config.pool().map_err(db_error)?.clone();
+    let domain = email_intake::intake_domain();
+
+    let token = email
+        .original_recipient
+        .iter()
+        .map(String::as_str)
+        .chain(email.to.split(','))
+        .find_map(|recipient| email_intake::parse_intake_token(recipient, &domain));
+
+    let user_email = match token {
+        Some(token) => email_intake::find_user_by_token(&pool, &token)
+            .await
+            .map_err(db_error)?,
+        None => None,
+    };
+    let Some(user_email) = user_email else {
+        app_log!(
+            warn,
+            "Inbound email to unknown intake address (to: {})",
+            email.to
+        );
+        return Ok(Json(ActionResponse::success(
+            "No intake address matched — message ignored".to_string(),
+            "ignored".to_string(),
+            None,
+        )));
+    };
+
+    let attachments: Vec<InboundAttachment> = email
+        .attachments
+        .into_iter()
+        .filter(|a| email_intake::is_cv_attachment(&a.name))
+        .take(MAX_ATTACHMENTS_PER_MESSAGE)
+        .collect();
+
+    app_log!(
+        info,
+        "Inbound email for {} from {} ({:?}): {} CV attachment(s)",
+        user_email,
+        email.from,
+        email.subject,
+        attachments.len()
+    );
+
+    if attachments.is_empty() {
+        return Ok(Json(ActionResponse::success(
+            "No PDF or DOCX attachment found — message ignored".to_string(),
+            "ignored".to_string(),
+            None,
+        )));
+    }
+
+    let count = attachments.len();
+    let data_dir = config.data_dir.clone();
+    let service_url = cv_service_url.inner().clone();
+    tokio::spawn(async move {
+        for attachment in attachments {
+            import_attachment(&pool, &data_dir, &service_url, &user_email, attachment).await;
+        }
+    });
+
+    Ok(Json(ActionResponse::success(
+        format!("{} attachment(s) queued for import", count),
+        "accepted".to_string(),
+        None,
+    )))
+}
+
+/// Convert one attachment into a new profile for `user_email`. Failures are
+/// logged and reported to the admin; the sender gets no bounce.
+async fn import_attachment(
+    pool: &SqlitePool,
+    data_dir: &PathBuf,
+    service_url: &str,
+    user_email: &str,
+    attachment: InboundAttachment,
+) {
+    if let Err(e) = disk_monitor::check(&[data_dir.as_path()]) {
+        app_log!(error, "Inbound attachment {} dropped: {}", attachment.name, e);
+        return;
+    }
+    let bytes = match base64::engine::general_purpose::STANDARD.decode(attachment.content.trim()) {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            app_log!(
+                warn,
+                "Inbound attachment {} is not valid base64: {}",
+                attachment.name,
+                e
+            );
+            return;
+        }
+    };
+    if bytes.len() > MAX_ATTACHMENT_BYTES {
+        app_log!(
+            warn,
+            "Inbound attachment {} skipped: {} bytes exceeds limit",
+            attachment.name,
+            bytes.len()
+        );
+        return;
+    }
+
+    if let Err(e) = check_and_deduct_credits(user_email, 4, None, "cv_import_email").await {
+        app_log!(
+            warn,
+            "Inbound attachment {} for {} skipped: {}",
+            attachment.name,
+            user_email,
+            e.error
+        );
+        return;
+    }
+
+    let fail = |error_summary: String| {
+        crate::email::notify_admin(crate::email::EmailKind::AdminCvImportFailed {
+            user_email: user_email.to_string(),
+            filename: attachment.name.clone(),
+            error_summary,
+            saved_path: "<email intake>".to_string(),
+        });
+    };
+
+    let temp_path = std::env::temp_dir().join(format!("cv_email_{}", uuid::Uuid::new_v4()));
+    if let Err(e) = tokio::fs::write(&temp_path, &bytes).await {
+        app_log!(error, "Failed to stage inbound attachment: {}", e);
+        return;
+    }
+
+    let usage = TenantRepository::new(pool)
+        .find_by_email_or_domain(user_email)
+        .await
+        .ok()
+        .flatten()
+        .map(|tenant| UsageRecorder::new(pool, tenant.id, user_email));
+    let converted = match ServiceClient::new(service_url.to_string(), 400) {
+        Ok(client) => {
+            client
+                .with_usage(usage)
+                .upload_cv(&temp_path, &attachment.name)
+                .await
+        }
+        Err(e) => Err(e),
+    };
+    let _ = tokio::fs::remove_file(&temp_path).await;
+
+    let mut cv_data = match converted {
+        Ok(data) => data,
+        Err(e) => {
+            app_log!(
+                error,
+                "Inbound CV {} conversion failed: {}",
+                attachment.name,
+                e
+            );
+            fail(e.to_string());
+            return;
+        }
+    };
+
+    let tenant_data_dir = get_tenant_folder_path(user_email, data_dir);
+    if let Err(e) = FsOps::ensure_dir_exists(&tenant_data_dir).await {
+        app_log!(error, "Failed to create tenant directory: {}", e);
+        return;
+    }
+
+    let base_profile = Some(normalize_profile_name(&cv_data.personal_info.name))
+        .filter(|p| !p.is_empty())
+        .unwrap_or_else(|| {
+            normalize_profile_name(
+                crate::web::handlers::cv_handlers::helpers::extract_profile_name_from_filename(
+                    &attachment.name,
+                )
+                .as_str(),
+            )
+        });
+    let profile = unique_profile_name(&base_profile, |p| tenant_data_dir.join(p).exists());
+
+    language_detect::apply(&mut cv_data);
+    if let Err(e) =
+        create_profile_from_cv_data(&tenant_data_dir.join(&profile), &cv_data, &profile).await
+    {
+        app_log!(error, "Failed to create profile from inbound CV: {}", e);
+        fail(e.to_string());
+        return;
+    }
+
+    app_log!(
+        info,
+        "Inbound CV {} imported as profile {} for {}",
+        attachment.name,
+        profile,
+        user_email
+    );
+
+    let tenant = TenantRepository::new(pool)
+        .find_by_email_or_domain(user_email)
+        .await
+        .ok()
+        .flatten();
+    let tenant_name = tenant
+        .as_ref()
+        .map(|t| t.tenant_name.clone())
+        .unwrap_or_default();
+
+    crate::core::event_export::emit(
+        crate::core::event_export::EventKind::CvImported,
+        &tenant_name,
+        user_email,
+        serde_json::json!({
+            "profile": profile,
+            "source": "email",
+            "filename": attachment.name,
+        }),
+    );
+
+    crate::email::send_email_with_prefs(
+        user_email,
+        crate::email::EmailKind::CvImported {
+            profile,
+            lang: "auto".into(),
+        },
+        tenant
+            .as_ref()
+            .and_then(|t| t.preferred_lang.as_deref())
+            .unwrap_or("en"),
+        tenant.as_ref().and_then(|t| t.email_prefs.as_deref()),
+    );
+}
@@ -2,35 +2,44 @@
 
 use crate::auth::AuthenticatedUser;
 use crate::core::database::{get_tenant_folder_path, DatabaseConfig};
+use crate::core::job_postings;
+use crate::core::request_cancel::{self, CancelToken};
+use crate::core::service_stream::{self, Relayed};
 use crate::core::{FsOps, ServiceClient};
 use crate::linkedin_analysis::JobAnalysisRequest;
 use crate::types::cv_data::{CvConverter, CvJson}; // Add CvJson imports
-use crate::web::types::{StandardErrorResponse, StandardRequest, TextResponse, WithConversationId};
+use crate::types::response::JobMatchResponse;
+use crate::web::types::{
+    ClientRequestId, StandardErrorResponse, StandardRequest, TextResponse, WithConversationId,
+};
+use crate::core::ai_budget::AiFeature;
+use crate::web::handlers::usage_handlers::{check_ai_budget, result_cache, usage_recorder};
 use crate::web::ServerConfig;
 use anyhow::Result;
+use futures::stream::{Stream, StreamExt};
 use graflog::app_log;
+use rocket::response::stream::{Event, EventStream};
 use rocket::serde::json::Json;
 use rocket::{post, State};
 
-#[post("/analyze-job-fit", data = "<request>")]
-pub async fn analyze_job_fit_handler(
-    request: Json<StandardRequest<JobAnalysisRequest>>,
-    auth: AuthenticatedUser,
-    config: &State<ServerConfig>,
-    cv_service_url: &State<String>,
-    _db_config: &State<DatabaseConfig>,
-) -> Result<Json<TextResponse>, Json<StandardErrorResponse>> {
-    let user = auth.user();
-    let tenant = auth.tenant();
-    let conversation_id = request.conversation_id();
-
+/// Service client and CV data for a job fit analysis.
+async fn prepare_analysis(
+    request: &JobAnalysisRequest,
+    auth: &AuthenticatedUser,
+    config: &ServerConfig,
+    cv_service_url: &str,
+    db_config: &DatabaseConfig,
+    cancel: CancelToken,
+    conversation_id: Option<String>,
+) -> Result<(ServiceClient, CvJson), Json<StandardErrorResponse>> {
     app_log!(
         info,
         "User {} (tenant: {}) requesting job fit analysis for {}",
-        user.email,
-        tenant.tenant_name,
-        request.data.profile_name
+        auth.email(),
+        auth.tenant_name(),
+        request.profile_name
     );
+    check_ai_budget(db_config, auth, AiFeature::Analysis, conversation_id.clone()).await?;
 
     // Use new tenant folder path
     let tenant_data_dir = get_tenant_folder_path(&auth.user().email, &config.data_dir);
@@ -47,8 +56,11 @@ pub async fn analyze_job_fit_handler(
     }
 
     // Initialize service client
-    let service_client = match ServiceClient::new(cv_service_url.inner().clone(), 400) {
-        Ok(client) => client,
+    let service_client = match ServiceClient::new(cv_service_url.to_string(), 400) {
+        Ok(client) => client
+            .with_usage(usage_recorder(db_config, auth))
+            .with_cancel(cancel)
+            .with_cache(result_cache(config, auth, request.force_refresh)),
         Err(e) => {
             app_log!(error, "Failed to initialize service client: {}", e);
             return Err(Json(StandardErrorResponse::new(
@@ -65,24 +77,24 @@ pub async fn analyze_job_fit_handler(
 
     // Load profile's CV data as CvJson (UPDATED)
     // In the load_profile_cv_data function error handling (around line 9565)
-    let cv_data = match load_profile_cv_data(&request.data.profile_name, &tenant_data_dir).await {
+    let cv_data = match load_profile_cv_data(&request.profile_name, &tenant_data_dir).await {
         Ok(data) => data,
         Err(e) => {
             app_log!(
                 error,
                 "Failed to load CV data for {}: {}",
-                request.data.profile_name,
+                request.profile_name,
                 e
             );
 
             let error_message = e.to_string();
             let (error_code, suggestions) =
-                categorize_cv_error(&error_message, &request.data.profile_name);
+                categorize_cv_error(&error_message, &request.profile_name);
 
             return Err(Json(StandardErrorResponse::new(
                 format!(
                     "Profile '{}' has invalid CV data: {}",
-                    request.data.profile_name, error_message
+                    request.profile_name, error_message
                 ),
                 error_code,
                 suggestions,
@@ -91,46 +103,161 @@ pub async fn analyze_job_fit_handler(
         }
     };
 
+    Ok((service_client, cv_data))
+}
+
+/// The posting as text, from the posting cache or scraped once; `None`
+/// lets cv-import scrape the URL itself.
+async fn posting_text(request: &JobAnalysisRequest, db_config: &DatabaseConfig) -> Option<String> {
+    let pool = db_config.pool().ok()?;
+    job_postings::resolve(pool, &request.job_url, request.force_refresh)
+        .await
+        .map(|content| job_postings::describe(&content))
+}
+
+fn analysis_succeeded(
+    request: &JobAnalysisRequest,
+    auth: &AuthenticatedUser,
+    match_response: &JobMatchResponse,
+) {
+    app_log!(
+        info,
+        "Successfully analyzed job fit for {} by {} (tenant: {})",
+        request.profile_name,
+        auth.email(),
+        auth.tenant_name()
+    );
+    crate::core::event_export::emit(
+        crate::core::event_export::EventKind::JobAnalyzed,
+        auth.tenant_name(),
+        auth.email(),
+        serde_json::json!({
+            "profile": request.profile_name,
+            "job_url": request.job_url,
+            "score": match_response.score,
+        }),
+    );
+}
+
+fn analysis_failed(
+    e: &anyhow::Error,
+    request: &JobAnalysisRequest,
+    auth: &AuthenticatedUser,
+    conversation_id: Option<String>,
+) -> StandardErrorResponse {
+    if request_cancel::is_cancelled(e) {
+        return StandardErrorResponse::cancelled(conversation_id);
+    }
+    let error_msg = format!("Job analysis failed: {}", e);
+    app_log!(
+        error,
+        "Job analysis failed for {} by {} (tenant: {}): {}",
+        request.profile_name,
+        auth.email(),
+        auth.tenant_name(),
+        error_msg
+    );
+
+    let (error_code, suggestions) = categorize_error(&error_msg, &request.profile_name);
+    StandardErrorResponse::new(error_msg, error_code, suggestions, conversation_id)
+}
+
+#[post("/analyze-job-fit", data = "<request>")]
+pub async fn analyze_job_fit_handler(
+    request: Json<StandardRequest<JobAnalysisRequest>>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    cv_service_url: &State<String>,
+    db_config: &State<DatabaseConfig>,
+    request_id: ClientRequestId,
+) -> Result<Json<TextResponse>, Json<StandardErrorResponse>> {
+    let conversation_id = request.conversation_id();
+    let cancel = request_id.register(auth.email());
+    let (service_client, cv_data) = prepare_analysis(
+        &request.data,
+        &auth,
+        config,
+        cv_service_url,
+        db_config,
+        cancel.token(),
+        conversation_id.clone(),
+    )
+    .await?;
+    let job_description = posting_text(&request.data, db_config).await;
+
     // Call cv_import service for job matching (UPDATED to use CvJson)
     match service_client
-        .match_job(&cv_data, &request.data.job_url)
+        .match_job_with_description(&cv_data, &request.data.job_url, job_description.as_deref())
         .await
     {
         Ok(match_response) => {
-            app_log!(
-                info,
-                "Successfully analyzed job fit for {} by {} (tenant: {})",
-                request.data.profile_name,
-                user.email,
-                tenant.tenant_name
-            );
+            analysis_succeeded(&request.data, &auth, &match_response);
             // Use the analysis field from JobMatchResponse
             Ok(Json(TextResponse::success(
                 match_response.analysis,
                 conversation_id,
             )))
         }
-        Err(e) => {
-            let error_msg = format!("Job analysis failed: {}", e);
-            app_log!(
-                error,
-                "Job analysis failed for {} by {} (tenant: {}): {}",
-                request.data.profile_name,
-                user.email,
-                tenant.tenant_name,
-                error_msg
-            );
+        Err(e) => Err(Json(analysis_failed(
+            &e,
+            &request.data,
+            &auth,
+            conversation_id,
+        ))),
+    }
+}
 
-            let (error_code, suggestions) =
-                categorize_error(&error_msg, &request.data.profile_name);
-            Err(Json(StandardErrorResponse::new(
-                error_msg,
-                error_code,
-                suggestions,
-                conversation_id,
-            )))
+/// POST /analyze-job-fit/stream — the analysis as server-sent events: `delta`
+/// events (`{"text": …}`) while cv-import writes it, then a `result` event
+/// with the response of `/analyze-job-fit` or an `error` event.
+pub async fn analyze_job_fit_stream_handler(
+    request: Json<StandardRequest<JobAnalysisRequest>>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    cv_service_url: &State<String>,
+    db_config: &State<DatabaseConfig>,
+    request_id: ClientRequestId,
+) -> Result<EventStream<impl Stream<Item = Event>>, Json<StandardErrorResponse>> {
+    let conversation_id = request.conversation_id();
+    let cancel = request_id.register(auth.email());
+    let (service_client, cv_data) = prepare_analysis(
+        &request.data,
+        &auth,
+        config,
+        cv_service_url,
+        db_config,
+        cancel.token(),
+        conversation_id.clone(),
+    )
+    .await?;
+    let job_description = posting_text(&request.data, db_config).await;
+
+    let request = request.into_inner().data;
+    let job_url = request.job_url.clone();
+    let (deltas, received) = tokio::sync::mpsc::unbounded_channel();
+    // Dropping the stream (client gone) drops the call and its registration.
+    let call = async move {
+        let _cancel = cancel;
+        service_client
+            .match_job_streaming(&cv_data, &job_url, job_description.as_deref(), Some(&deltas))
+            .await
+    };
+    let events = service_stream::relay(call, received).map(move |item| match item {
+        Relayed::Delta(text) => Event::json(&serde_json::json!({ "text": text })).event("delta"),
+        Relayed::Done(Ok(match_response)) => {
+            analysis_succeeded(&request, &auth, &match_response);
+            Event::json(&TextResponse::success(
+                match_response.analysis,
+                conversation_id.clone(),
+            ))
+            .event("result")
         }
-    }
+        Relayed::Done(Err(e)) => {
+            Event::json(&analysis_failed(&e, &request, &auth, conversation_id.clone()))
+                .event("error")
+        }
+    });
+    Ok(EventStream::from(events))
 }
 
 // UPDATED: Load profile CV data as CvJson instead of String
@@ -0,0 +1,723 @@
+// src/web/handlers/maintenance_handlers.rs
+//! Admin operations: one-off data cleanups, integrity sweeps, capacity
+//! benchmarks and per-tenant content policies.
+
+use graflog::app_log;
+use rocket::serde::json::Json;
+use rocket::State;
+
+use crate::auth::AuthenticatedUser;
+use crate::core::bulk_regeneration::{self, RegenerationReport};
+use crate::core::compile_benchmark::{self, BenchmarkReport};
+use crate::core::consistency::{self, ConsistencyReport};
+use crate::core::data_encryption::{self, KeyRotationReport};
+use crate::core::data_residency::{self, TenantRegion};
+use crate::core::database::{DatabaseConfig, TenantRepository};
+use crate::core::database::{get_tenant_folder_path, get_tenant_output_path};
+use crate::core::language_files::{self, LanguageCleanupReport};
+use crate::core::profile_approval::{self, ApprovalPolicy};
+use crate::core::profile_integrity::{self, TenantIntegrity};
+use crate::core::profile_names::{self, RenameReport};
+use crate::core::profile_schema::{self, SchemaUpgradeReport};
+use crate::core::tenant_archive::{self, ArchiveReport};
+use crate::core::typst_lint::{self, LintLevel};
+use crate::core::{owner, template_health, TemplateEngine};
+use crate::web::handlers::common::{admin_only, db_error, pool, tenant_by_address};
+use crate::web::types::{
+    ActionResponse, ApprovalPolicyRequest, BenchmarkRequest, ConsistencyCheckRequest, DataKeyRotationRequest, DataResponse, IntegrityCheckRequest,
+    LanguageFilesCleanupRequest, RegenerationRequest, SanitizeProfilesRequest, SchemaUpgradeRequest, StandardErrorResponse,
+    TenantArchiveRequest, TenantRegionRequest, TypstLintLevelRequest,
+};
+use crate::web::ServerConfig;
+
+const DEFAULT_BENCHMARK_RUNS: usize = 20;
+
+/// POST /admin/profiles/sanitize — propose (and with `execute`, apply)
+/// sanitized directory names for a user's profiles.
+pub async fn sanitize_profiles_handler(
+    request: Json<SanitizeProfilesRequest>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    db_config: &State<DatabaseConfig>,
+) -> Result<Json<DataResponse<RenameReport>>, Json<StandardErrorResponse>> {
+    admin_only(&auth)?;
+
+    let email = request.email.trim().to_lowercase();
+    let tenant_dir = get_tenant_folder_path(&email, &config.data_dir);
+    if !tenant_dir.is_dir() {
+        return Err(Json(StandardErrorResponse::new(
+            format!("No data directory for {}", email),
+            "TENANT_NOT_FOUND".to_string(),
+            vec!["Check the email address".to_string()],
+            None,
+        )));
+    }
+
+    let rename_error = |e: anyhow::Error| {
+        app_log!(error, "Profile name cleanup for {} failed: {:#}", email, e);
+        Json(StandardErrorResponse::new(
+            format!("{:#}", e),
+            "RENAME_ERROR".to_string(),
+            vec!["No profile was renamed; fix the cause and retry".to_string()],
+            None,
+        ))
+    };
+    let mut report = profile_names::plan(&tenant_dir)
+        .await
+        .map_err(rename_error)?;
+    if request.execute && !report.renames.is_empty() {
+        let output_dir = get_tenant_output_path(&email, &config.output_dir);
+        let owner = owner::account_key(&email);
+        let pool = pool(db_config)?;
+        report = profile_names::apply(Some(pool), &owner, &tenant_dir, &output_dir, report)
+            .await
+            .map_err(rename_error)?;
+    }
+
+    let message = if report.applied {
+        format!("Renamed {} profiles", report.renames.len())
+    } else {
+        format!("{} profiles would be renamed", report.renames.len())
+    };
+    Ok(Json(DataResponse::success(message, report, None)))
+}
+
+/// POST /admin/consistency — report (and with `execute`, repair) database
+/// records that name profiles missing from a user's folder.
+pub async fn consistency_handler(
+    request: Json<ConsistencyCheckRequest>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    db_config: &State<DatabaseConfig>,
+) -> Result<Json<DataResponse<ConsistencyReport>>, Json<StandardErrorResponse>> {
+    admin_only(&auth)?;
+
+    let email = request.email.trim().to_lowercase();
+    let db_error = |e: anyhow::Error| {
+        app_log!(error, "Consistency check for {} failed: {:#}", email, e);
+        Json(StandardErrorResponse::new(
+            format!("{:#}", e),
+            "CONSISTENCY_ERROR".to_string(),
+            vec!["Fixes already made are kept; fix the cause and retry".to_string()],
+            None,
+        ))
+    };
+    let pool = db_config.pool().map_err(db_error)?;
+    let tenant = TenantRepository::new(pool)
+        .find_by_email_or_domain(&email)
+        .await
+        .map_err(db_error)?;
+    let tenant_dir = get_tenant_folder_path(&email, &config.data_dir);
+    if tenant.is_none() || !tenant_dir.is_dir() {
+        return Err(Json(StandardErrorResponse::new(
+            format!("No account or data directory for {}", email),
+            "TENANT_NOT_FOUND".to_string(),
+            vec!["Check the email address".to_string()],
+            None,
+        )));
+    }
+
+    let mut report = consistency::plan(pool, &email, &tenant_dir)
+        .await
+        .map_err(db_error)?;
+    if request.execute && !report.mismatches.is_empty() {
+        report = consistency::apply(pool, &tenant_dir, report)
+            .await
+            .map_err(db_error)?;
+    }
+
+    let message = if report.applied {
+        format!("Repaired {} mismatches", report.mismatches.len())
+    } else {
+        format!("{} mismatches found", report.mismatches.len())
+    };
+    Ok(Json(DataResponse::success(message, report, None)))
+}
+
+/// POST /admin/profiles/language-files — report (and with `execute`, delete)
+/// experiences files that copy another language or are unedited placeholders.
+pub async fn cleanup_language_files_handler(
+    request: Json<LanguageFilesCleanupRequest>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+) -> Result<Json<DataResponse<LanguageCleanupReport>>, Json<StandardErrorResponse>> {
+    admin_only(&auth)?;
+
+    let email = request.email.trim().to_lowercase();
+    let tenant_dir = get_tenant_folder_path(&email, &config.data_dir);
+    if !tenant_dir.is_dir() {
+        return Err(Json(StandardErrorResponse::new(
+            format!("No data directory for {}", email),
+            "TENANT_NOT_FOUND".to_string(),
+            vec!["Check the email address".to_string()],
+            None,
+        )));
+    }
+
+    let cleanup_error = |e: anyhow::Error| {
+        app_log!(error, "Language file cleanup for {} failed: {:#}", email, e);
+        Json(StandardErrorResponse::new(
+            format!("{:#}", e),
+            "LANGUAGE_CLEANUP_ERROR".to_string(),
+            vec![],
+            None,
+        ))
+    };
+    let engine = TemplateEngine::new(config.templates_dir.clone())
+        .map_err(|e| cleanup_error(e.context("Template system initialization failed")))?;
+    let starter = engine.starter_experiences().await.map_err(cleanup_error)?;
+    let mut report = language_files::plan(&tenant_dir, &starter)
+        .await
+        .map_err(cleanup_error)?;
+    if request.execute && !report.findings.is_empty() {
+        report = language_files::apply(&tenant_dir, report)
+            .await
+            .map_err(cleanup_error)?;
+    }
+
+    let message = if report.applied {
+        format!("Removed {} language files", report.findings.len())
+    } else {
+        format!("{} language files would be removed", report.findings.len())
+    };
+    Ok(Json(DataResponse::success(message, report, None)))
+}
+
+/// POST /admin/profiles/schema — report (and with `execute`, upgrade) the
+/// profiles of a user whose cv_params.toml uses a legacy layout.
+pub async fn upgrade_profile_schema_handler(
+    request: Json<SchemaUpgradeRequest>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+) -> Result<Json<DataResponse<SchemaUpgradeReport>>, Json<StandardErrorResponse>> {
+    admin_only(&auth)?;
+
+    let email = request.email.trim().to_lowercase();
+    let tenant_dir = get_tenant_folder_path(&email, &config.data_dir);
+    if !tenant_dir.is_dir() {
+        return Err(Json(StandardErrorResponse::new(
+            format!("No data directory for {}", email),
+            "TENANT_NOT_FOUND".to_string(),
+            vec!["Check the email address".to_string()],
+            None,
+        )));
+    }
+
+    let upgrade_error = |e: anyhow::Error| {
+        app_log!(error, "Schema upgrade for {} failed: {:#}", email, e);
+        Json(StandardErrorResponse::new(
+            format!("{:#}", e),
+            "SCHEMA_UPGRADE_ERROR".to_string(),
+            vec!["Profiles already upgraded keep their backup; fix the cause and retry".to_string()],
+            None,
+        ))
+    };
+    let mut report = profile_schema::plan(&tenant_dir)
+        .await
+        .map_err(upgrade_error)?;
+    if request.execute && !report.legacy.is_empty() {
+        report = profile_schema::apply(&tenant_dir, report)
+            .await
+            .map_err(upgrade_error)?;
+    }
+
+    let message = if report.applied {
+        format!("Upgraded {} profiles", report.legacy.len())
+    } else {
+        format!("{} profiles would be upgraded", report.legacy.len())
+    };
+    Ok(Json(DataResponse::success(message, report, None)))
+}
+
+/// POST /admin/data-encryption/rotate — new current data key for a tenant;
+/// with `reencrypt`, rewrite the tenant's CV content with it (this is also
+/// how a newly listed tenant's plaintext files get encrypted).
+pub async fn rotate_data_key_handler(
+    request: Json<DataKeyRotationRequest>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+) -> Result<Json<DataResponse<KeyRotationReport>>, Json<StandardErrorResponse>> {
+    admin_only(&auth)?;
+
+    let tenant = request.tenant.trim().to_string();
+    let tenant_dir = data_residency::route(&config.data_dir, &tenant).join(&tenant);
+    if tenant.is_empty() || tenant.contains(['/', '\\', '.']) || !tenant_dir.is_dir() {
+        return Err(Json(StandardErrorResponse::new(
+            format!("No data directory for tenant '{}'", tenant),
+            "TENANT_NOT_FOUND".to_string(),
+            vec!["Pass the tenant folder name".to_string()],
+            None,
+        )));
+    }
+
+    let rotation_error = |e: anyhow::Error| {
+        app_log!(error, "Data key rotation for {} failed: {:#}", tenant, e);
+        Json(StandardErrorResponse::new(
+            format!("{:#}", e),
+            "KEY_ROTATION_ERROR".to_string(),
+            vec!["Check SECRETS_MASTER_KEY and DATA_ENCRYPTION_TENANTS".to_string()],
+            None,
+        ))
+    };
+    let key_version = data_encryption::rotate(&tenant).map_err(rotation_error)?;
+    let files_reencrypted = if request.reencrypt {
+        data_encryption::reencrypt(&tenant_dir)
+            .await
+            .map_err(rotation_error)?
+    } else {
+        0
+    };
+
+    app_log!(
+        info,
+        "{} rotated the data key of {} to v{} ({} files rewritten)",
+        auth.email(),
+        tenant,
+        key_version,
+        files_reencrypted
+    );
+    Ok(Json(DataResponse::success(
+        format!("Data key of {} is now v{}", tenant, key_version),
+        KeyRotationReport {
+            tenant,
+            key_version,
+            files_reencrypted,
+        },
+        None,
+    )))
+}
+
+/// GET /admin/tenant-regions — tenants stored outside the default region.
+pub async fn list_tenant_regions_handler(
+    auth: AuthenticatedUser,
+) -> Result<Json<DataResponse<Vec<TenantRegion>>>, Json<StandardErrorResponse>> {
+    admin_only(&auth)?;
+    let list = data_residency::list();
+    Ok(Json(DataResponse::success(
+        format!(
+            "{} tenants outside the default region (regions: {})",
+            list.len(),
+            data_residency::regions().join(", ")
+        ),
+        list,
+        None,
+    )))
+}
+
+/// PUT /admin/tenant-region — store a tenant's files in another region.
+/// Only tenants without files can move.
+pub async fn set_tenant_region_handler(
+    request: Json<TenantRegionRequest>,
+    auth: AuthenticatedUser,
+    db_config: &State<DatabaseConfig>,
+) -> Result<Json<DataResponse<TenantRegion>>, Json<StandardErrorResponse>> {
+    admin_only(&auth)?;
+
+    let tenant = request.tenant.trim();
+    let region = request.region.trim().to_lowercase();
+    if tenant.is_empty() || tenant.contains(['/', '\\', '.']) {
+        return Err(Json(StandardErrorResponse::new(
+            "Tenant is required".to_string(),
+            "INVALID_TENANT".to_string(),
+            vec!["Pass the tenant folder name".to_string()],
+            None,
+        )));
+    }
+
+    let pool = pool(db_config)?;
+    let placed = data_residency::set_region(pool, tenant, &region)
+        .await
+        .map_err(|e| {
+            app_log!(warn, "Cannot move {} to region {}: {:#}", tenant, region, e);
+            Json(StandardErrorResponse::new(
+                format!("{:#}", e),
+                "REGION_CHANGE_REFUSED".to_string(),
+                vec![format!(
+                    "Known regions: {}",
+                    data_residency::regions().join(", ")
+                )],
+                None,
+            ))
+        })?;
+
+    app_log!(
+        info,
+        "{} placed tenant {} in region {}",
+        auth.email(),
+        tenant,
+        placed.region
+    );
+    Ok(Json(DataResponse::success(
+        format!("{} is now stored in region {}", tenant, placed.region),
+        placed,
+        None,
+    )))
+}
+
+/// POST /admin/tenants/archive and /admin/tenants/restore.
+pub async fn tenant_archive_handler(
+    request: Json<TenantArchiveRequest>,
+    restore: bool,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    db_config: &State<DatabaseConfig>,
+) -> Result<Json<DataResponse<ArchiveReport>>, Json<StandardErrorResponse>> {
+    admin_only(&auth)?;
+
+    let email = request.email.trim().to_lowercase();
+    if !email.contains('@') {
+        return Err(Json(StandardErrorResponse::new(
+            "A valid email is required".to_string(),
+            "INVALID_EMAIL".to_string(),
+            vec![],
+            None,
+        )));
+    }
+    let pool = pool(db_config)?;
+
+    let result = if restore {
+        tenant_archive::restore(pool, &email, &config.data_dir, &config.output_dir).await
+    } else {
+        tenant_archive::archive(pool, &email, &config.data_dir, &config.output_dir).await
+    };
+    let action = if restore { "restore" } else { "archive" };
+    let report = result.map_err(|e| {
+        app_log!(error, "Failed to {} {}: {:#}", action, email, e);
+        Json(StandardErrorResponse::new(
+            format!("{:#}", e),
+            if restore { "RESTORE_FAILED" } else { "ARCHIVE_FAILED" }.to_string(),
+            vec!["Only personal (email) accounts can be archived".to_string()],
+            None,
+        ))
+    })?;
+
+    app_log!(info, "{} ran {} for {}", auth.email(), action, email);
+    let message = if restore {
+        format!("{} restored ({} files)", email, report.files)
+    } else {
+        format!("{} archived ({} files, {} bytes)", email, report.files, report.bytes)
+    };
+    Ok(Json(DataResponse::success(message, report, None)))
+}
+
+/// POST /admin/profiles/check — integrity report for every person of a user.
+pub async fn check_profiles_handler(
+    request: Json<IntegrityCheckRequest>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+) -> Result<Json<DataResponse<TenantIntegrity>>, Json<StandardErrorResponse>> {
+    admin_only(&auth)?;
+
+    let email = request.email.trim().to_lowercase();
+    let tenant_dir = get_tenant_folder_path(&email, &config.data_dir);
+    if !tenant_dir.is_dir() {
+        return Err(Json(StandardErrorResponse::new(
+            format!("No data directory for {}", email),
+            "TENANT_NOT_FOUND".to_string(),
+            vec!["Check the email address".to_string()],
+            None,
+        )));
+    }
+
+    let report = profile_integrity::check_all(
+        &config.templates_dir,
+        &tenant_dir,
+        request.compile.unwrap_or(true),
+    )
+    .await
+    .map_err(|e| {
+        app_log!(error, "Integrity check for {} failed: {}", email, e);
+        Json(StandardErrorResponse::new(
+            format!("Failed to list profiles: {}", e),
+            "INTEGRITY_CHECK_ERROR".to_string(),
+            vec![],
+            None,
+        ))
+    })?;
+    app_log!(
+        info,
+        "[integrity] {}: {} of {} profiles broken",
+        email,
+        report.broken,
+        report.checked
+    );
+
+    Ok(Json(DataResponse::success(
+        format!(
+            "{} of {} profiles have problems",
+            report.broken, report.checked
+        ),
+        report,
+        None,
+    )))
+}
+
+/// PUT /admin/typst-lint — how strictly a tenant's editor saves are linted.
+pub async fn set_typst_lint_level_handler(
+    request: Json<TypstLintLevelRequest>,
+    auth: AuthenticatedUser,
+    db_config: &State<DatabaseConfig>,
+) -> Result<Json<ActionResponse>, Json<StandardErrorResponse>> {
+    admin_only(&auth)?;
+
+    let tenant = request.tenant.trim();
+    let level = LintLevel::parse(&request.level).ok_or_else(|| {
+        Json(StandardErrorResponse::new(
+            format!("Unknown lint level '{}'", request.level),
+            "INVALID_LINT_LEVEL".to_string(),
+            vec!["Use 'off', 'warn' or 'block'".to_string()],
+            None,
+        ))
+    })?;
+    if tenant.is_empty() {
+        return Err(Json(StandardErrorResponse::new(
+            "Tenant is required".to_string(),
+            "INVALID_TENANT".to_string(),
+            vec![],
+            None,
+        )));
+    }
+
+    let pool = pool(db_config)?;
+    let tenant = tenant_by_address(pool, tenant).await?;
+    typst_lint::set_level(pool, tenant.id, level)
+        .await
+        .map_err(db_error)?;
+
+    app_log!(
+        info,
+        "Typst lint level of {} set to {}",
+        tenant.tenant_name,
+        level.as_str()
+    );
+    Ok(Json(ActionResponse::success(
+        format!(
+            "Typst lint level of {} is now {}",
+            tenant.tenant_name,
+            level.as_str()
+        ),
+        "LINT_LEVEL_UPDATED".to_string(),
+        None,
+    )))
+}
+
+/// PUT /admin/approval-policy — what generating an unapproved profile does.
+pub async fn set_approval_policy_handler(
+    request: Json<ApprovalPolicyRequest>,
+    auth: AuthenticatedUser,
+    db_config: &State<DatabaseConfig>,
+) -> Result<Json<ActionResponse>, Json<StandardErrorResponse>> {
+    admin_only(&auth)?;
+
+    let tenant = request.tenant.trim();
+    let policy = ApprovalPolicy::parse(&request.policy).ok_or_else(|| {
+        Json(StandardErrorResponse::new(
+            format!("Unknown approval policy '{}'", request.policy),
+            "INVALID_APPROVAL_POLICY".to_string(),
+            vec!["Use 'off', 'watermark' or 'block'".to_string()],
+            None,
+        ))
+    })?;
+    if tenant.is_empty() {
+        return Err(Json(StandardErrorResponse::new(
+            "Tenant is required".to_string(),
+            "INVALID_TENANT".to_string(),
+            vec![],
+            None,
+        )));
+    }
+
+    let pool = pool(db_config)?;
+    let tenant = tenant_by_address(pool, tenant).await?;
+    profile_approval::set_policy(pool, tenant.id, policy)
+        .await
+        .map_err(db_error)?;
+
+    app_log!(
+        info,
+        "Approval policy of {} set to {}",
+        tenant.tenant_name,
+        policy.as_str()
+    );
+    Ok(Json(ActionResponse::success(
+        format!(
+            "Approval policy of {} is now {}",
+            tenant.tenant_name,
+            policy.as_str()
+        ),
+        "APPROVAL_POLICY_UPDATED".to_string(),
+        None,
+    )))
+}
+
+fn benchmark_error(message: String, code: &str) -> Json<StandardErrorResponse> {
+    Json(StandardErrorResponse::new(
+        message,
+        code.to_string(),
+        vec![],
+        None,
+    ))
+}
+
+/// POST /admin/benchmark — compile the sample profile `runs` times,
+/// `concurrency` at a time, and report latency and resource usage.
+pub async fn benchmark_handler(
+    request: Json<BenchmarkRequest>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+) -> Result<Json<DataResponse<BenchmarkReport>>, Json<StandardErrorResponse>> {
+    admin_only(&auth)?;
+
+    let engine = TemplateEngine::new(config.templates_dir.clone()).map_err(|e| {
+        benchmark_error(
+            format!("Template system initialization failed: {}", e),
+            "TEMPLATE_INIT_ERROR",
+        )
+    })?;
+    let templates = match request.template.as_deref().map(str::trim) {
+        Some(template) if !template.is_empty() => {
+            if !engine.template_exists(template) {
+                return Err(benchmark_error(
+                    format!("Template '{}' not found", template),
+                    "TEMPLATE_NOT_FOUND",
+                ));
+            }
+            vec![template.to_string()]
+        }
+        _ => {
+            let mut templates: Vec<String> = engine
+                .list_templates()
+                .into_iter()
+                .filter(|t| template_health::is_healthy(t))
+                .collect();
+            templates.sort();
+            templates
+        }
+    };
+    let runs = request.runs.unwrap_or(DEFAULT_BENCHMARK_RUNS);
+    let concurrency = request.concurrency.unwrap_or_else(|| {
+        std::thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1)
+    });
+
+    app_log!(
+        info,
+        "[benchmark] {} requested {} runs at concurrency {} over {} templates",
+        auth.email(),
+        runs,
+        concurrency,
+        templates.len()
+    );
+    let report = compile_benchmark::run(&config.templates_dir, &templates, runs, concurrency)
+        .await
+        .map_err(|e| benchmark_error(e.to_string(), "BENCHMARK_UNAVAILABLE"))?;
+    app_log!(
+        info,
+        "[benchmark] {} runs: p50 {}ms, p95 {}ms, {:.1}/min, {} failed",
+        report.runs,
+        report.p50_ms,
+        report.p95_ms,
+        report.per_minute,
+        report.failed
+    );
+
+    Ok(Json(DataResponse::success(
+        format!(
+            "{} compilations, p50 {}ms, p95 {}ms",
+            report.runs, report.p50_ms, report.p95_ms
+        ),
+        report,
+        None,
+    )))
+}
+
+/// POST /admin/regenerations — regenerate every CV of a tenant (optionally
+/// only those of one template) in the background; poll the returned report.
+pub async fn start_regeneration_handler(
+    request: Json<RegenerationRequest>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    db_config: &State<DatabaseConfig>,
+) -> Result<Json<DataResponse<RegenerationReport>>, Json<StandardErrorResponse>> {
+    admin_only(&auth)?;
+
+    let tenant = request.tenant.trim().to_string();
+    let template = request
+        .template
+        .as_deref()
+        .map(str::trim)
+        .filter(|t| !t.is_empty());
+    let regeneration_error = |e: anyhow::Error| {
+        app_log!(error, "Regeneration of {} failed to start: {:#}", tenant, e);
+        Json(StandardErrorResponse::new(
+            format!("{:#}", e),
+            "REGENERATION_ERROR".to_string(),
+            vec!["Check the tenant name, or wait for its running regeneration".to_string()],
+            None,
+        ))
+    };
+    let pool = db_config.pool().map_err(regeneration_error)?.clone();
+    let dirs = bulk_regeneration::Directories {
+        data_dir: config.data_dir.clone(),
+        output_dir: config.output_dir.clone(),
+        templates_dir: config.templates_dir.clone(),
+    };
+    let report = bulk_regeneration::start(&pool, &dirs, &tenant, auth.email(), template)
+        .await
+        .map_err(regeneration_error)?;
+
+    let id = report.id.clone();
+    tokio::spawn(async move {
+        if let Err(e) = bulk_regeneration::run(pool, dirs, id.clone()).await {
+            app_log!(error, "Regeneration {} stopped: {:#}", id, e);
+        }
+    });
+
+    Ok(Json(DataResponse::success(
+        format!("Regenerating {} CVs of {}", report.total, report.tenant),
+        report,
+        None,
+    )))
+}
+
+/// GET /admin/regenerations/<id> — progress and outcome of a regeneration.
+pub async fn regeneration_report_handler(
+    id: String,
+    auth: AuthenticatedUser,
+    db_config: &State<DatabaseConfig>,
+) -> Result<Json<DataResponse<RegenerationReport>>, Json<StandardErrorResponse>> {
+    admin_only(&auth)?;
+
+    let db_error = |e: anyhow::Error| {
+        app_log!(error, "Failed to load regeneration {}: {}", id, e);
+        Json(StandardErrorResponse::new(
+            "Failed to load the regeneration".to_string(),
+            "DB_ERROR".to_string(),
+            vec![],
+            None,
+        ))
+    };
+    let pool = db_config.pool().map_err(db_error)?;
+    let report = bulk_regeneration::report(pool, &id)
+        .await
+        .map_err(db_error)?
+        .ok_or_else(|| {
+            Json(StandardErrorResponse::new(
+                format!("Regeneration '{}' not found", id),
+                "REGENERATION_NOT_FOUND".to_string(),
+                vec!["Start one with POST /admin/regenerations".to_string()],
+                None,
+            ))
+        })?;
+
+    let message = if report.finished {
+        format!(
+            "{} regenerated, {} failed, {} skipped",
+            report.succeeded, report.failed, report.skipped
+        )
+    } else {
+        format!("{} of {} CVs still pending", report.pending, report.total)
+    };
+    Ok(Json(DataResponse::success(message, report, None)))
+}
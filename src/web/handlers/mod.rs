@@ -1,14 +1,28 @@
 // src/web/handlers/mod.rs - Fixed to include upload_picture_handler
 
+pub mod asset_handlers;
+pub mod audit_handlers;
 pub mod bd_handlers;
+pub mod bench_handlers;
 pub mod brand_handlers;
+pub mod calendar_handlers;
+pub mod catalog_handlers;
+pub mod common;
+pub mod conversation_handlers;
+pub mod generation_handlers;
 pub mod model_handlers;
 pub mod cv_handlers;
+pub mod intake_handlers;
 pub mod linkedin_handlers;
+pub mod maintenance_handlers;
 pub mod payment_handlers;
 pub mod profile_handlers;
 pub mod referral_handlers;
+pub mod role_handlers;
+pub mod secret_handlers;
 pub mod system_handlers;
+pub mod upload_handlers;
+pub mod usage_handlers;
 pub mod feedback_handlers;
 
 pub use bd_handlers::*;
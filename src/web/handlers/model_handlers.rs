@@ -3,26 +3,12 @@
 //! and trigger a hot-reload (PM2 restart).
 
 use crate::auth::AuthenticatedUser;
+use crate::web::handlers::common::admin_only;
 use crate::web::types::StandardErrorResponse;
 use graflog::app_log;
 use rocket::serde::json::Json;
 use serde::{Deserialize, Serialize};
 
-const ADMIN_EMAIL: &str = "mohamed.bennekrouf@gmail.com";
-
-fn admin_only(auth: &AuthenticatedUser) -> Result<(), Json<StandardErrorResponse>> {
-    if auth.email().to_lowercase() != ADMIN_EMAIL {
-        Err(Json(StandardErrorResponse::new(
-            "Admin access required".to_string(),
-            "FORBIDDEN".to_string(),
-            vec![],
-            None,
-        )))
-    } else {
-        Ok(())
-    }
-}
-
 fn config_path() -> String {
     std::env::var("CV_IMPORT_CONFIG_PATH")
         .unwrap_or_else(|_| "../cv-import/config.yaml".to_string())
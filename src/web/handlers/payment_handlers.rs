@@ -23,6 +23,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::auth::AuthenticatedUser;
 use crate::core::database::{DatabaseConfig, TenantRepository};
+use crate::web::handlers::common::{admin_only, pool, ADMIN_EMAIL};
 use crate::web::types::StandardErrorResponse;
 
 // ── Request / Response types ──────────────────────────────────────────────────
@@ -312,6 +313,53 @@ pub async fn check_and_deduct_credits(
     Ok(())
 }
 
+/// Give back the `cost` credits [`check_and_deduct_credits`] took for work
+/// that was cancelled by the client or failed. Runs in the background; a
+/// failure is only logged.
+pub fn refund_credits(user_email: &str, cost: i64, action_type: &str) {
+    let (email, action_type) = (user_email.to_string(), format!("{}_refund", action_type));
+    tokio::spawn(async move {
+        match api0_topup_credits(&email, cost, &action_type, Some("Request cancelled or failed")).await {
+            Ok(_) => app_log!(info, "Refunded {} credits to {} ({})", cost, email, action_type),
+            Err(e) => app_log!(error, "Failed to refund {} credits to {}: {}", cost, email, e),
+        }
+    });
+}
+
+/// Credits taken for a streamed request. A streamed response notices its
+/// client is gone — the stream is dropped on the next write — so dropping
+/// the charge before [`PendingCharge::finish`] refunds it.
+pub struct PendingCharge {
+    email: String,
+    cost: i64,
+    action_type: &'static str,
+    finished: bool,
+}
+
+impl PendingCharge {
+    pub fn new(user_email: &str, cost: i64, action_type: &'static str) -> Self {
+        Self {
+            email: user_email.to_string(),
+            cost,
+            action_type,
+            finished: false,
+        }
+    }
+
+    /// The request ran to its end; the credits stay spent.
+    pub fn finish(mut self) {
+        self.finished = true;
+    }
+}
+
+impl Drop for PendingCharge {
+    fn drop(&mut self) {
+        if !self.finished {
+            refund_credits(&self.email, self.cost, self.action_type);
+        }
+    }
+}
+
 // ── Route handlers ────────────────────────────────────────────────────────────
 
 /// POST /payment/intent
@@ -688,8 +736,6 @@ pub async fn get_transactions_handler(
 //
 // Returns: { success, email, amount, new_balance, description }
 
-const ADMIN_EMAIL: &str = "mohamed.bennekrouf@gmail.com";
-
 #[derive(Deserialize)]
 #[serde(crate = "rocket::serde")]
 pub struct AdminCreditRequest {
@@ -847,23 +893,9 @@ pub async fn admin_credit_users_handler(
     auth: AuthenticatedUser,
     db_config: &rocket::State<crate::core::database::DatabaseConfig>,
 ) -> Result<Json<AdminCreditUsersResponse>, Json<StandardErrorResponse>> {
-    if auth.email().to_lowercase() != ADMIN_EMAIL {
-        return Err(Json(StandardErrorResponse::new(
-            "Admin access required".to_string(),
-            "FORBIDDEN".to_string(),
-            vec![],
-            None,
-        )));
-    }
+    admin_only(&auth)?;
 
-    let pool = db_config.pool().map_err(|e| {
-        Json(StandardErrorResponse::new(
-            format!("Database error: {}", e),
-            "DB_ERROR".to_string(),
-            vec![],
-            None,
-        ))
-    })?;
+    let pool = pool(db_config)?;
 
     // Fetch all active email-based tenants
     let rows: Vec<(String, String, String)> = sqlx::query_as(
@@ -926,14 +958,7 @@ pub async fn admin_user_transactions_handler(
     email: String,
     auth: AuthenticatedUser,
 ) -> Result<Json<AdminUserTransactionsResponse>, Json<StandardErrorResponse>> {
-    if auth.email().to_lowercase() != ADMIN_EMAIL {
-        return Err(Json(StandardErrorResponse::new(
-            "Admin access required".to_string(),
-            "FORBIDDEN".to_string(),
-            vec![],
-            None,
-        )));
-    }
+    admin_only(&auth)?;
 
     let decoded = percent_encoding::percent_decode_str(&email)
         .decode_utf8()
@@ -1,17 +1,24 @@
 // src/web/handlers/profile_handlers.rs - Updated with new tenant structure
 use crate::auth::AuthenticatedUser;
-use crate::core::database::{get_tenant_folder_path, DatabaseConfig};
-use crate::core::FsOps;
+use crate::core::audit_log::{self, AuditAction};
+use crate::core::database::{get_tenant_folder_path, get_tenant_output_path, DatabaseConfig};
+use crate::core::image_variants::{self, VariantFormat};
+use crate::core::profile_access::ProfileType;
+use crate::core::{
+    data_residency, person_tags, profile_comments, profile_lookup, profile_names, profile_watch, provisioning, toml_patch, translation_status, FsOps,
+};
 use crate::web::types::{
-    ActionResponse, CreateProfileRequest, DeleteProfileRequest, StandardErrorResponse,
-    StandardRequest, UploadForm, WithConversationId,
+    ActionResponse, CreateProfileRequest, DeleteProfileRequest, SetProfileTypeRequest,
+    StandardErrorResponse, StandardRequest, UploadForm, WithConversationId,
 };
+use crate::web::types::ImageResponse;
 use crate::web::types::ChangeLanguageRequest;
 use crate::web::RenameProfileRequest;
+use crate::web::handlers::cv_handlers::{require_create_access, require_edit_access};
 use crate::web::ServerConfig;
 use graflog::app_log;
 use rocket::form::Form;
-use rocket::fs::NamedFile;
+use rocket::http::{Accept, ContentType};
 use rocket::serde::json::Json;
 use rocket::State;
 
@@ -34,6 +41,8 @@ pub async fn create_profile_handler(
         conversation_id.clone().unwrap_or_default()
     );
 
+    require_create_access(&auth, conversation_id.clone())?;
+
     let tenant_data_dir = get_tenant_folder_path(&auth.user().email, &config.data_dir);
 
     // Ensure the directory exists
@@ -91,6 +100,7 @@ pub async fn rename_profile_handler(
     request: Json<StandardRequest<RenameProfileRequest>>,
     auth: AuthenticatedUser,
     config: &State<ServerConfig>,
+    db_config: &State<DatabaseConfig>,
 ) -> Result<Json<ActionResponse>, Json<StandardErrorResponse>> {
     let user = auth.user();
     let tenant = auth.tenant();
@@ -140,7 +150,15 @@ pub async fn rename_profile_handler(
         )));
     }
 
-    let old_profile_dir = tenant_data_dir.join(&old_name); // Use original old_name
+    // The URL name as-is first (legacy directories), then accent-insensitively.
+    let old_name = if tenant_data_dir.join(&old_name).is_dir() {
+        old_name
+    } else {
+        profile_lookup::resolve(&tenant_data_dir, &old_name)
+            .await
+            .unwrap_or(old_name)
+    };
+    let old_profile_dir = tenant_data_dir.join(&old_name);
     let new_profile_dir = tenant_data_dir.join(&normalized_new_name);
 
     if !old_profile_dir.exists() {
@@ -155,6 +173,8 @@ pub async fn rename_profile_handler(
         )));
     }
 
+    require_edit_access(&auth, &old_profile_dir, conversation_id.clone())?;
+
     // 3. Check if new name exists
     if new_profile_dir.exists() {
         return Err(Json(StandardErrorResponse::new(
@@ -167,6 +187,19 @@ pub async fn rename_profile_handler(
             conversation_id,
         )));
     }
+    if let Some(existing) =
+        profile_lookup::conflict(&tenant_data_dir, &normalized_new_name, &old_name).await
+    {
+        return Err(Json(StandardErrorResponse::new(
+            format!(
+                "Profile '{}' already exists as '{}'",
+                request.data.new_name, existing
+            ),
+            "PROFILE_ALREADY_EXISTS".to_string(),
+            vec!["Names differing only by accents or case refer to the same profile".to_string()],
+            conversation_id,
+        )));
+    }
 
     app_log!(
         info,
@@ -202,15 +235,15 @@ pub async fn rename_profile_handler(
         tenant.tenant_name
     );
 
-    // Touch profile.toml so its mtime reflects the rename — the frontend
-    // sorts profiles by most-recently-modified, and a directory rename alone
-    // does not update any file's mtime on Linux.
-    let profile_toml = new_profile_dir.join("profile.toml");
-    if profile_toml.exists() {
-        if let Ok(content) = tokio::fs::read(&profile_toml).await {
-            let _ = tokio::fs::write(&profile_toml, content).await;
-        }
-    }
+    profile_names::carry_over(
+        db_config.pool().ok(),
+        &auth.account_key(),
+        &tenant_data_dir,
+        &get_tenant_output_path(&user.email, &config.output_dir),
+        &old_name,
+        &normalized_new_name,
+    )
+    .await;
 
     Ok(Json(ActionResponse::success(
         format!(
@@ -243,11 +276,63 @@ pub async fn list_profiles_handler(
     }
 }
 
+/// PUT /profiles/<name>/type — mark a profile as the user's own (`self`) or
+/// a `collaborator`. Editors only: otherwise a member could claim any
+/// collaborator to edit it.
+pub async fn set_profile_type_handler(
+    profile_name: String,
+    request: Json<SetProfileTypeRequest>,
+    auth: AuthenticatedUser,
+    config: &State<crate::web::types::ServerConfig>,
+) -> Result<Json<ActionResponse>, Json<StandardErrorResponse>> {
+    let own = match request.profile_type.trim().to_lowercase().as_str() {
+        "self" => true,
+        "collaborator" => false,
+        other => {
+            return Err(Json(StandardErrorResponse::new(
+                format!("Unknown profile type '{}'", other),
+                "INVALID_PROFILE_TYPE".to_string(),
+                vec!["Use 'self' or 'collaborator'".to_string()],
+                None,
+            )));
+        }
+    };
+    require_create_access(&auth, None)?;
+
+    let profile = crate::utils::normalize_profile_name(&profile_name);
+    let tenant_data_dir = get_tenant_folder_path(&auth.user().email, &config.data_dir);
+    if profile.is_empty() || !tenant_data_dir.join(&profile).is_dir() {
+        return Err(Json(StandardErrorResponse::new(
+            format!("Profile '{}' not found", profile_name),
+            "PROFILE_NOT_FOUND".to_string(),
+            vec!["Check the profile name spelling".to_string()],
+            None,
+        )));
+    }
+
+    if let Err(e) = provisioning::set_self_profile(&tenant_data_dir, &profile, own).await {
+        app_log!(error, "Failed to set type of profile {}: {}", profile, e);
+        return Err(Json(StandardErrorResponse::new(
+            "Failed to update profile type".to_string(),
+            "FS_ERROR".to_string(),
+            vec!["Try again or contact support".to_string()],
+            None,
+        )));
+    }
+
+    let profile_type = ProfileType::of(&tenant_data_dir.join(&profile));
+    Ok(Json(ActionResponse::success(
+        format!("Profile '{}' is now {}", profile, profile_type.as_str()),
+        "PROFILE_TYPE_UPDATED".to_string(),
+        None,
+    )))
+}
+
 pub async fn delete_profile_handler(
     request: Json<StandardRequest<DeleteProfileRequest>>,
     auth: AuthenticatedUser,
     config: &State<crate::web::types::ServerConfig>,
-    _db_config: &State<DatabaseConfig>,
+    db_config: &State<DatabaseConfig>,
 ) -> Result<Json<ActionResponse>, Json<StandardErrorResponse>> {
     let profile_name = &request.data.profile; // Use raw name for delete
     let conversation_id = request.conversation_id();
@@ -270,6 +355,8 @@ pub async fn delete_profile_handler(
         )));
     }
 
+    require_edit_access(&auth, &profile_dir, conversation_id.clone())?;
+
     if let Err(e) = FsOps::remove_dir_all(&profile_dir).await {
         app_log!(error, "Failed to delete profile directory: {}", e);
         return Err(Json(StandardErrorResponse::new(
@@ -280,6 +367,32 @@ pub async fn delete_profile_handler(
         )));
     }
 
+    let output_dir = config.person_output_dir(&auth.user().email, profile_name);
+    if output_dir.exists() {
+        if let Err(e) = FsOps::remove_dir_all(&output_dir).await {
+            app_log!(warn, "Failed to delete generated files of {}: {}", profile_name, e);
+        }
+    }
+
+    if let Ok(pool) = db_config.pool() {
+        let owner = auth.account_key();
+        if let Err(e) = profile_comments::delete_profile(pool, &owner, profile_name).await {
+            app_log!(warn, "Failed to delete comments of {}: {}", profile_name, e);
+        }
+        if let Err(e) = profile_watch::delete_profile(pool, &owner, profile_name).await {
+            app_log!(warn, "Failed to delete watches of {}: {}", profile_name, e);
+        }
+        if let Err(e) = person_tags::delete_profile(pool, &owner, profile_name).await {
+            app_log!(warn, "Failed to delete tags of {}: {}", profile_name, e);
+        }
+        audit_log::record(
+            pool,
+            auth.tenant_id(),
+            auth.email(),
+            AuditAction::ProfileDeleted,
+            profile_name,
+        );
+    }
     app_log!(info, "Successfully deleted profile: {}", profile_name);
 
     Ok(Json(ActionResponse::success(
@@ -299,6 +412,8 @@ pub async fn upload_picture_handler(
     let tenant = auth.tenant();
     let normalized_profile = crate::utils::normalize_profile_name(&upload.profile);
 
+    crate::web::handlers::system_handlers::ensure_storage_available(config, None)?;
+
     app_log!(
         info,
         "User {} (tenant: {}) uploading picture for {} (normalized: {})",
@@ -319,6 +434,7 @@ pub async fn upload_picture_handler(
             None,
         )));
     }
+    require_edit_access(&auth, &profile_dir, None)?;
 
     // Handle Option<&Path> from TempFile::path()
     let file_path = match upload.file.path() {
@@ -349,8 +465,10 @@ pub async fn upload_picture_handler(
 
     let profile_path = profile_dir.join("profile.png");
 
-    // Write file using tokio fs
-    match tokio::fs::write(&profile_path, &file_bytes).await {
+    // Held until the picture is validated and cv_params.toml updated, so a
+    // generation never snapshots an unvalidated picture.
+    let _save = crate::core::profile_lock::write(&profile_dir).await;
+    match FsOps::write_bytes_safe(&profile_path, &file_bytes).await {
         Ok(_) => {
             // Validate the uploaded image
             if let Err(e) = FsOps::validate_image(&profile_path).await {
@@ -382,20 +500,16 @@ pub async fn upload_picture_handler(
             // Auto-enable show_photo in cv_params.toml so the photo renders in templates
             let cv_params_path = profile_dir.join("cv_params.toml");
             if cv_params_path.exists() {
-                if let Ok(content) = tokio::fs::read_to_string(&cv_params_path).await {
-                    if !content.contains("show_photo = true") {
-                        let updated = if content.contains("[styling]") {
-                            // Replace show_photo = false with true, or append after [styling]
-                            if content.contains("show_photo = false") {
-                                content.replace("show_photo = false", "show_photo = true")
-                            } else {
-                                content.replace("[styling]", "[styling]\nshow_photo = true")
-                            }
-                        } else {
-                            // Append [styling] section
-                            format!("{}\n[styling]\nshow_photo = true\n", content.trim_end())
-                        };
-                        if let Err(e) = tokio::fs::write(&cv_params_path, updated).await {
+                if let Ok(content) = FsOps::read_file_safe(&cv_params_path).await {
+                    let updated = match toml_patch::set(&content, "styling", "show_photo", true) {
+                        Ok(updated) => updated,
+                        Err(e) => {
+                            app_log!(warn, "Not enabling show_photo, cv_params.toml is invalid: {}", e);
+                            None
+                        }
+                    };
+                    if let Some(updated) = updated {
+                        if let Err(e) = FsOps::write_file_safe(&cv_params_path, &updated).await {
                             app_log!(warn, "Failed to auto-enable show_photo in cv_params.toml: {}", e);
                         } else {
                             app_log!(info, "Auto-enabled show_photo for profile: {}", normalized_profile);
@@ -425,12 +539,19 @@ pub async fn upload_picture_handler(
     }
 }
 
+/// Serve a profile picture, optionally resized (`w`) and re-encoded (`format`).
+/// Without an explicit `format`, WebP is chosen when the client's `Accept`
+/// header allows it. Variants are cached on disk by [`image_variants`].
+///
+/// [`image_variants`]: crate::core::image_variants
 pub async fn get_picture_handler(
     profile: String,
+    w: Option<u32>,
+    format: Option<String>,
+    accept: Option<&Accept>,
     auth: AuthenticatedUser,
     config: &State<crate::web::types::ServerConfig>,
-    _db_config: &State<DatabaseConfig>,
-) -> Result<NamedFile, Json<StandardErrorResponse>> {
+) -> Result<ImageResponse, Json<StandardErrorResponse>> {
     let normalized_profile = crate::utils::normalize_profile_name(&profile);
 
     let tenant_data_dir = get_tenant_folder_path(&auth.user().email, &config.data_dir);
@@ -455,10 +576,59 @@ pub async fn get_picture_handler(
         }
     };
 
-    match NamedFile::open(&photo_path).await {
-        Ok(file) => Ok(file),
+    let variant_format = match format.as_deref() {
+        Some(f) => match VariantFormat::parse(f) {
+            Some(vf) => vf,
+            None => {
+                return Err(Json(StandardErrorResponse::new(
+                    format!("Unsupported image format: '{}'", f),
+                    "INVALID_FORMAT".to_string(),
+                    vec!["Use one of: png, jpeg, webp".to_string()],
+                    None,
+                )));
+            }
+        },
+        None => {
+            let wants_webp = accept
+                .map(|a| a.media_types().any(|m| m.top() == "image" && m.sub() == "webp"))
+                .unwrap_or(false);
+            if wants_webp {
+                VariantFormat::Webp
+            } else {
+                VariantFormat::Png
+            }
+        }
+    };
+
+    let data_dir = data_residency::data_root_of(&config.data_dir, &photo_path);
+    let variant = tokio::task::spawn_blocking(move || {
+        image_variants::get_or_create(&data_dir, &photo_path, w, variant_format)
+    })
+    .await;
+
+    match variant {
+        Ok(Ok(v)) => {
+            let (top, sub) = v.format.mime();
+            Ok(ImageResponse {
+                data: v.bytes,
+                content_type: ContentType::new(top, sub),
+                etag: v.etag,
+            })
+        }
+        Ok(Err(e)) => {
+            app_log!(error, "Failed to build picture variant for {}: {}", normalized_profile, e);
+            Err(Json(StandardErrorResponse::new(
+                "Failed to serve profile picture".to_string(),
+                "IMAGE_PROCESSING_ERROR".to_string(),
+                vec![
+                    "The stored picture may be corrupted — try re-uploading it".to_string(),
+                    "Contact support if the problem persists".to_string(),
+                ],
+                None,
+            )))
+        }
         Err(e) => {
-            app_log!(error, "Failed to serve profile picture: {}", e);
+            app_log!(error, "Picture variant task failed: {}", e);
             Err(Json(StandardErrorResponse::new(
                 "Failed to serve profile picture".to_string(),
                 "FILE_ERROR".to_string(),
@@ -503,6 +673,7 @@ pub async fn change_profile_language_handler(
             conversation_id,
         )));
     }
+    require_edit_access(&auth, &profile_dir, conversation_id.clone())?;
 
     // Scan for experiences files in the profile directory.
     let mut experiences_files: Vec<(String, std::path::PathBuf)> = Vec::new();
@@ -607,6 +778,7 @@ pub async fn change_profile_language_handler(
             conversation_id,
         )));
     }
+    translation_status::relabel(&profile_dir, &current_lang, &new_lang);
 
     app_log!(
         info,
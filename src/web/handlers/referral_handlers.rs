@@ -15,6 +15,7 @@ use serde::Serialize;
 use sqlx::SqlitePool;
 
 use crate::auth::AuthenticatedUser;
+use crate::web::handlers::common::pool;
 use crate::web::types::StandardErrorResponse;
 
 // ── Response types ────────────────────────────────────────────────────────────
@@ -43,14 +44,7 @@ pub async fn get_referral_link_handler(
     auth: AuthenticatedUser,
     db_config: &rocket::State<crate::core::database::DatabaseConfig>,
 ) -> Result<Json<ReferralLinkResponse>, Json<StandardErrorResponse>> {
-    let pool = db_config.pool().map_err(|e| {
-        Json(StandardErrorResponse::new(
-            format!("Database error: {}", e),
-            "DB_ERROR".to_string(),
-            vec![],
-            None,
-        ))
-    })?;
+    let pool = pool(db_config)?;
 
     let email = auth.email();
     let referral_code = auth.tenant_name().to_string();
@@ -0,0 +1,310 @@
+// src/web/handlers/role_handlers.rs
+//! Member roles within domain tenants, bulk onboarding of a company, tenant
+//! renames and configuration bundles (admin only). See [`crate::core::profile_access`] for what each role may
+//! edit.
+
+use graflog::app_log;
+use rocket::data::{Data, ToByteUnit};
+use rocket::http::ContentType;
+use rocket::serde::json::Json;
+use rocket::State;
+
+use crate::auth::AuthenticatedUser;
+use crate::core::audit_log::{self, AuditAction};
+use crate::core::bulk_provisioning::{self, BulkProvisionReport, MAX_MEMBERS};
+use crate::core::database::{DatabaseConfig, TenantRepository};
+use crate::core::profile_access::MemberRole;
+use crate::core::tenant_bundle::{self, BundleApplyReport, TenantBundle, MAX_BUNDLE_BYTES};
+use crate::core::tenant_rename::{self, TenantRenameReport};
+use crate::web::handlers::common::{
+    admin_only, db_error, error_response, pool, tenant_by_address,
+};
+use crate::web::types::{
+    ActionResponse, BulkProvisionRequest, DataResponse, RenameTenantRequest,
+    SetMemberRoleRequest, StandardErrorResponse,
+};
+use crate::web::ServerConfig;
+
+/// PUT /admin/roles — grant `member` or `editor` to a user.
+pub async fn set_member_role_handler(
+    request: Json<SetMemberRoleRequest>,
+    auth: AuthenticatedUser,
+    db_config: &State<DatabaseConfig>,
+) -> Result<Json<ActionResponse>, Json<StandardErrorResponse>> {
+    admin_only(&auth)?;
+
+    let email = request.email.trim().to_lowercase();
+    let role = MemberRole::parse(&request.role).ok_or_else(|| {
+        Json(StandardErrorResponse::new(
+            format!("Unknown role '{}'", request.role),
+            "INVALID_ROLE".to_string(),
+            vec!["Use 'member' or 'editor'".to_string()],
+            None,
+        ))
+    })?;
+    if email.is_empty() {
+        return Err(Json(StandardErrorResponse::new(
+            "Email is required".to_string(),
+            "INVALID_EMAIL".to_string(),
+            vec![],
+            None,
+        )));
+    }
+
+    let pool = pool(db_config)?;
+    TenantRepository::new(pool)
+        .set_member_role(&email, role.as_str())
+        .await
+        .map_err(|e| {
+            app_log!(error, "Failed to set role for {}: {}", email, e);
+            Json(StandardErrorResponse::new(
+                "Failed to save role".to_string(),
+                "DB_ERROR".to_string(),
+                vec![],
+                None,
+            ))
+        })?;
+
+    app_log!(info, "Role of {} set to {}", email, role.as_str());
+    Ok(Json(ActionResponse::success(
+        format!("{} is now {}", email, role.as_str()),
+        "ROLE_UPDATED".to_string(),
+        None,
+    )))
+}
+
+/// POST /admin/tenants/bulk — create a company's domain tenant, its members'
+/// roles and optionally their profiles in one all-or-nothing batch.
+pub async fn bulk_provision_handler(
+    request: Json<BulkProvisionRequest>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    db_config: &State<DatabaseConfig>,
+) -> Result<Json<DataResponse<BulkProvisionReport>>, Json<StandardErrorResponse>> {
+    admin_only(&auth)?;
+
+    let request = request.into_inner();
+    let domain = request.domain.trim().trim_start_matches('@').to_lowercase();
+    let tenant_name = request.tenant_name.trim();
+    if domain.is_empty() || !domain.contains('.') || tenant_name.is_empty() {
+        return Err(error_response(
+            "A domain and a tenant name are required".to_string(),
+            "INVALID_TENANT",
+            vec![],
+        ));
+    }
+
+    let mut members = request.members;
+    if let Some(csv) = request.csv.as_deref() {
+        let parsed = bulk_provisioning::parse_csv(csv).map_err(|e| {
+            error_response(
+                format!("Invalid CSV: {:#}", e),
+                "INVALID_CSV",
+                vec!["Use the columns email,name,role,scaffold".to_string()],
+            )
+        })?;
+        members.extend(parsed);
+    }
+    if members.is_empty() || members.len() > MAX_MEMBERS {
+        return Err(error_response(
+            format!("A batch needs 1 to {} members", MAX_MEMBERS),
+            "INVALID_BATCH",
+            vec![],
+        ));
+    }
+    let invalid = bulk_provisioning::validate(&domain, &members);
+    if !invalid.is_empty() {
+        return Err(error_response(
+            format!("{} invalid members; nothing was provisioned", invalid.len()),
+            "INVALID_MEMBERS",
+            invalid
+                .iter()
+                .map(|i| format!("Row {} ({}): {}", i.row, i.email, i.error))
+                .collect(),
+        ));
+    }
+
+    let pool = db_config
+        .pool()
+        .map_err(db_error)?;
+    let report = bulk_provisioning::provision(
+        pool,
+        &config.data_dir,
+        &config.templates_dir,
+        &domain,
+        tenant_name,
+        &members,
+        request.dry_run,
+    )
+    .await
+    .map_err(|e| {
+        app_log!(error, "Bulk provisioning of {} failed: {:#}", domain, e);
+        error_response(
+            format!("{:#}", e),
+            "BULK_PROVISION_ERROR",
+            vec!["Nothing was provisioned; fix the cause and retry".to_string()],
+        )
+    })?;
+
+    app_log!(
+        info,
+        "{} {} {} members of {} ({} roles, {} profiles)",
+        auth.email(),
+        if report.dry_run { "checked" } else { "provisioned" },
+        report.members.len(),
+        domain,
+        report.roles_granted,
+        report.profiles_created
+    );
+    let message = if report.dry_run {
+        format!("{} members of {} would be provisioned", report.members.len(), domain)
+    } else {
+        format!("Provisioned {} members of {}", report.members.len(), domain)
+    };
+    Ok(Json(DataResponse::success(message, report, None)))
+}
+
+/// POST /admin/tenants/<tenant>/rename — rename the tenant of a member's
+/// email address or of a domain; nothing but its name changes.
+pub async fn rename_tenant_handler(
+    tenant: String,
+    request: Json<RenameTenantRequest>,
+    auth: AuthenticatedUser,
+    db_config: &State<DatabaseConfig>,
+) -> Result<Json<DataResponse<TenantRenameReport>>, Json<StandardErrorResponse>> {
+    admin_only(&auth)?;
+
+    let new_name = request.new_name.trim();
+    if !tenant_rename::valid_tenant_name(new_name) {
+        return Err(error_response(
+            format!("Invalid tenant name '{}'", new_name),
+            "INVALID_TENANT",
+            vec!["Use letters, digits, '.', '_' and '-' (up to 64)".to_string()],
+        ));
+    }
+
+    let pool = pool(db_config)?;
+    let tenant = tenant_by_address(pool, &tenant).await?;
+    let report = tenant_rename::rename(pool, tenant.id, new_name)
+        .await
+        .map_err(|e| {
+            app_log!(warn, "Renaming tenant {} to {} failed: {:#}", tenant.id, new_name, e);
+            error_response(
+                format!("{:#}", e),
+                "TENANT_RENAME_ERROR",
+                vec!["Nothing was renamed".to_string()],
+            )
+        })?;
+    audit_log::record(
+        pool,
+        tenant.id,
+        auth.email(),
+        AuditAction::TenantRenamed,
+        &format!("{} -> {}", report.from, report.to),
+    );
+
+    app_log!(
+        info,
+        "{} renamed tenant {} from {} to {}",
+        auth.email(),
+        tenant.id,
+        report.from,
+        report.to
+    );
+    Ok(Json(DataResponse::success(
+        format!("Tenant {} is now {}", report.from, report.to),
+        report,
+        None,
+    )))
+}
+
+/// GET /admin/tenants/<name>/bundle — the tenant's configuration as YAML.
+pub async fn export_bundle_handler(
+    name: String,
+    templates: Option<bool>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    db_config: &State<DatabaseConfig>,
+) -> Result<(ContentType, String), Json<StandardErrorResponse>> {
+    admin_only(&auth)?;
+
+    let pool = db_config
+        .pool()
+        .map_err(db_error)?;
+    let bundle = tenant_bundle::export(
+        pool,
+        &config.templates_dir,
+        &name,
+        templates.unwrap_or(true),
+    )
+    .await
+    .map_err(|e| error_response(format!("{:#}", e), "BUNDLE_EXPORT_ERROR", vec![]))?;
+    let yaml = serde_yaml::to_string(&bundle)
+        .map_err(|e| error_response(e.to_string(), "BUNDLE_EXPORT_ERROR", vec![]))?;
+
+    app_log!(info, "{} exported the bundle of tenant {}", auth.email(), name);
+    Ok((ContentType::new("application", "yaml"), yaml))
+}
+
+/// POST /admin/tenants/bundle — apply a YAML bundle (`?dry_run=true` to preview).
+pub async fn apply_bundle_handler(
+    data: Data<'_>,
+    dry_run: Option<bool>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    db_config: &State<DatabaseConfig>,
+) -> Result<Json<DataResponse<BundleApplyReport>>, Json<StandardErrorResponse>> {
+    admin_only(&auth)?;
+
+    let body = data
+        .open(MAX_BUNDLE_BYTES.bytes())
+        .into_string()
+        .await
+        .map_err(|e| error_response(e.to_string(), "INVALID_BUNDLE", vec![]))?;
+    if !body.is_complete() {
+        return Err(error_response(
+            format!("Bundles are limited to {} bytes", MAX_BUNDLE_BYTES),
+            "BUNDLE_TOO_LARGE",
+            vec!["Export with ?templates=false and install templates separately".to_string()],
+        ));
+    }
+    let bundle: TenantBundle = serde_yaml::from_str(&body).map_err(|e| {
+        error_response(
+            format!("Invalid bundle: {}", e),
+            "INVALID_BUNDLE",
+            vec!["Start from GET /admin/tenants/<name>/bundle".to_string()],
+        )
+    })?;
+
+    let pool = db_config
+        .pool()
+        .map_err(db_error)?;
+    let dry_run = dry_run.unwrap_or(false);
+    let report = tenant_bundle::apply(pool, &config.templates_dir, &bundle, dry_run)
+        .await
+        .map_err(|e| {
+            app_log!(warn, "Applying the bundle of {} failed: {:#}", bundle.tenant, e);
+            error_response(
+                format!("{:#}", e),
+                "BUNDLE_APPLY_ERROR",
+                vec!["Database changes were rolled back".to_string()],
+            )
+        })?;
+
+    app_log!(
+        info,
+        "{} applied the bundle of tenant {} ({} changes{})",
+        auth.email(),
+        bundle.tenant,
+        report.changes.len(),
+        if dry_run { ", dry run" } else { "" }
+    );
+    let message = if report.changes.is_empty() {
+        format!("Tenant {} already matches the bundle", bundle.tenant)
+    } else if dry_run {
+        format!("{} change(s) would be applied", report.changes.len())
+    } else {
+        format!("{} change(s) applied", report.changes.len())
+    };
+    Ok(Json(DataResponse::success(message, report, None)))
+}
@@ -0,0 +1,160 @@
+// src/web/handlers/secret_handlers.rs
+//! The caller's tenant secrets (editors only). Values go in and never come
+//! back out; see [`crate::core::secrets`].
+
+use graflog::app_log;
+use rocket::serde::json::Json;
+use rocket::State;
+use sqlx::SqlitePool;
+
+use crate::auth::AuthenticatedUser;
+use crate::core::audit_log::{self, AuditAction};
+use crate::core::database::DatabaseConfig;
+use crate::core::secrets::{self, SecretInfo, SecretKind, SecretStore, MAX_VALUE_BYTES};
+use crate::web::handlers::common::{db_error, error_response};
+use crate::web::types::{ActionResponse, DataResponse, PutSecretRequest, StandardErrorResponse};
+
+/// Editor role, an enabled store and the database.
+fn secrets_access<'a>(
+    auth: &AuthenticatedUser,
+    store: &SecretStore,
+    db_config: &'a DatabaseConfig,
+) -> Result<&'a SqlitePool, Json<StandardErrorResponse>> {
+    if !auth.role().can_create() {
+        return Err(error_response(
+            "Managing secrets requires the editor role".to_string(),
+            "EDITOR_ROLE_REQUIRED",
+            vec!["Ask your account administrator for the editor role".to_string()],
+        ));
+    }
+    if !store.enabled() {
+        return Err(error_response(
+            "The secrets store is not enabled on this server".to_string(),
+            "SECRETS_DISABLED",
+            vec!["Set SECRETS_MASTER_KEY or SECRETS_MASTER_KEY_FILE".to_string()],
+        ));
+    }
+    db_config
+        .pool()
+        .map_err(db_error)
+}
+
+fn checked_name(name: &str) -> Result<(), Json<StandardErrorResponse>> {
+    if secrets::valid_name(name) {
+        return Ok(());
+    }
+    Err(error_response(
+        format!("Invalid secret name '{}'", name),
+        "INVALID_SECRET_NAME",
+        vec!["Use lowercase letters, digits, '_' and '-' (up to 64)".to_string()],
+    ))
+}
+
+/// GET /secrets — names, kinds and dates of the tenant's secrets.
+pub async fn list_secrets_handler(
+    auth: AuthenticatedUser,
+    store: &State<SecretStore>,
+    db_config: &State<DatabaseConfig>,
+) -> Result<Json<DataResponse<Vec<SecretInfo>>>, Json<StandardErrorResponse>> {
+    let pool = secrets_access(&auth, store, db_config)?;
+    let list = secrets::list(pool, auth.tenant_id())
+        .await
+        .map_err(db_error)?;
+    Ok(Json(DataResponse::success(
+        format!("{} secrets", list.len()),
+        list,
+        None,
+    )))
+}
+
+/// PUT /secrets/<name> — create or replace a secret.
+pub async fn put_secret_handler(
+    name: String,
+    request: Json<PutSecretRequest>,
+    auth: AuthenticatedUser,
+    store: &State<SecretStore>,
+    db_config: &State<DatabaseConfig>,
+) -> Result<Json<ActionResponse>, Json<StandardErrorResponse>> {
+    let pool = secrets_access(&auth, store, db_config)?;
+    checked_name(&name)?;
+    let kind = SecretKind::parse(&request.kind).ok_or_else(|| {
+        error_response(
+            format!("Unknown secret kind '{}'", request.kind),
+            "INVALID_SECRET_KIND",
+            vec![
+                "Use smtp_credential, webhook_secret, signing_key, api_token or other".to_string(),
+            ],
+        )
+    })?;
+    if request.value.is_empty() || request.value.len() > MAX_VALUE_BYTES {
+        return Err(error_response(
+            format!("Secret values must be 1 to {} bytes", MAX_VALUE_BYTES),
+            "INVALID_SECRET_VALUE",
+            vec![],
+        ));
+    }
+
+    let tenant = auth.tenant_name();
+    store
+        .put(pool, auth.tenant_id(), &name, kind, &request.value)
+        .await
+        .map_err(|e| {
+            app_log!(error, "Failed to store secret '{}' of {}: {:#}", name, tenant, e);
+            error_response(
+                "Failed to store the secret".to_string(),
+                "SECRET_ERROR",
+                vec!["Try again or contact support".to_string()],
+            )
+        })?;
+    audit_log::record(
+        pool,
+        auth.tenant_id(),
+        auth.email(),
+        AuditAction::SecretChanged,
+        &format!("set {}", name),
+    );
+    app_log!(info, "{} stored secret '{}' for {}", auth.email(), name, tenant);
+
+    Ok(Json(ActionResponse::success(
+        format!("Secret '{}' saved", name),
+        "saved".to_string(),
+        None,
+    )))
+}
+
+/// DELETE /secrets/<name>
+pub async fn delete_secret_handler(
+    name: String,
+    auth: AuthenticatedUser,
+    store: &State<SecretStore>,
+    db_config: &State<DatabaseConfig>,
+) -> Result<Json<ActionResponse>, Json<StandardErrorResponse>> {
+    let pool = secrets_access(&auth, store, db_config)?;
+    checked_name(&name)?;
+
+    let tenant = auth.tenant_name();
+    let deleted = secrets::delete(pool, auth.tenant_id(), &name)
+        .await
+        .map_err(db_error)?;
+    if !deleted {
+        return Err(error_response(
+            format!("No secret named '{}'", name),
+            "NOT_FOUND",
+            vec![],
+        ));
+    }
+    audit_log::record(
+        pool,
+        auth.tenant_id(),
+        auth.email(),
+        AuditAction::SecretChanged,
+        &format!("deleted {}", name),
+    );
+    app_log!(info, "{} deleted secret '{}' of {}", auth.email(), name, tenant);
+
+    Ok(Json(ActionResponse::success(
+        format!("Secret '{}' deleted", name),
+        "deleted".to_string(),
+        None,
+    )))
+}
@@ -1,64 +1,323 @@
 // src/web/handlers/system_handlers.rs
 use crate::auth::{AuthenticatedUser, OptionalAuth};
-use crate::core::database::{DatabaseConfig, TenantRepository, get_tenant_folder_path};
-use crate::core::{FsOps, TemplateEngine};
+use crate::core::database::{
+    get_tenant_folder_path, get_tenant_output_path, DatabaseConfig, TenantRepository,
+};
+use crate::core::audit_log::{self, AuditAction};
+use crate::core::disk_monitor::{self, VolumeUsage};
+use crate::core::tenant_cache::{self, CacheStats};
+use crate::core::template_engine::{SharedTemplateEngine, SAMPLE_PROFILE};
+use crate::core::config_manager;
+use crate::core::secrets::SecretStore;
+use crate::core::template_registry::Registry;
+use crate::core::typst_compiler::DiagnosticSeverity;
+use crate::core::provisioning::{self, SelfProfile};
+use crate::core::{
+    asset_sync, email_intake, generation_scheduler, image_slots, import_retry, owner,
+    request_cancel, template_health, template_versions, tenant_templates, translations, FsOps,
+};
+use crate::web::handlers::common::require_feature;
+use crate::web::handlers::cv_handlers::generate_batch::MAX_BATCH_PERSONS;
+use crate::web::handlers::cv_handlers::generate_languages::MAX_LANGUAGES;
+use crate::web::handlers::cv_handlers::preview_set::MAX_PREVIEW_TEMPLATES;
+use crate::utils::normalize_language;
 use crate::web::types::{
-    ActionResponse, DataResponse, StandardErrorResponse, TemplateInfo, TextResponse, UserInfo,
+    ActionResponse, DataResponse, StandardErrorResponse, TemplateInfo, TemplateUploadForm,
+    UserInfo,
 };
-use crate::web::{ResponseType, ServerConfig};
+use crate::web::{ResponseType, ServerConfig, MAX_UPLOAD_MB};
+use crate::{CvConfig, CvGenerator};
 use graflog::app_log;
+use rocket::http::ContentType;
 use rocket::serde::json::Json;
 use rocket::State;
 
+/// Installed templates; `lang` picks the manifest's translated description.
+/// A signed-in user also gets the templates their tenant uploaded.
 pub async fn get_templates_handler(
-    config: &State<crate::web::types::ServerConfig>,
+    lang: Option<String>,
+    auth: OptionalAuth,
+    config: &State<ServerConfig>,
+    templates: &State<SharedTemplateEngine>,
 ) -> Json<DataResponse<Vec<TemplateInfo>>> {
-    match TemplateEngine::new(config.templates_dir.clone()) {
-        Ok(template_engine) => {
-            let templates: Vec<TemplateInfo> = template_engine
-                .list_templates()
-                .into_iter()
-                .map(|template_name| {
-                    let template_info = template_engine.get_template(&template_name);
-                    TemplateInfo {
-                        // id: template_name,
-                        name: template_info
-                            .map(|t| t.manifest.name.clone())
-                            .unwrap_or_default(),
-                        description: template_info
-                            .and_then(|t| t.manifest.description.clone())
-                            .unwrap_or_else(|| "No description available".to_string()),
-                        photo_recommended: template_info
-                            .and_then(|t| t.manifest.photo_recommended)
-                            .unwrap_or(false),
-                        shows_logo: template_info
-                            .and_then(|t| t.manifest.shows_logo)
-                            .unwrap_or(false),
-                    }
-                })
-                .collect();
-
-            Json(DataResponse {
-                success: true,
-                data: templates,
-                message: "Templates retrieved successfully".to_string(),
-                conversation_id: None,
-                display_format: None,
-                response_type: ResponseType::Data,
-            })
-        }
-        Err(e) => Json(DataResponse {
-            success: false,
-            data: Vec::new(),
-            message: format!("Failed to load templates: {}", e),
-            conversation_id: None,
-            display_format: None,
-            response_type: ResponseType::Error,
-        }),
+    let shared = templates.current();
+    let with_tenant = auth.user.as_ref().map(|user| {
+        let dir = tenant_templates::dir(&config.data_dir, user.email(), user.tenant_id());
+        (*shared).clone().with_tenant_templates(&dir)
+    });
+    let template_engine = with_tenant.as_ref().unwrap_or(shared.as_ref());
+    let templates: Vec<TemplateInfo> = template_engine
+        .list_templates()
+        .into_iter()
+        // Templates failing their background compile check stay hidden.
+        .filter(|template_name| template_health::is_healthy(template_name))
+        .map(|template_name| {
+            let template_info = template_engine.get_template(&template_name);
+            TemplateInfo {
+                // id: template_name,
+                name: template_info
+                    .map(|t| t.manifest.name.clone())
+                    .unwrap_or_default(),
+                description: template_info
+                    .and_then(|t| t.manifest.description_for(lang.as_deref()))
+                    .unwrap_or("No description available")
+                    .to_string(),
+                photo_recommended: template_info
+                    .and_then(|t| t.manifest.photo_recommended)
+                    .unwrap_or(false),
+                shows_logo: template_info
+                    .and_then(|t| t.manifest.shows_logo)
+                    .unwrap_or(false),
+                version: template_info
+                    .map(template_versions::current_version)
+                    .unwrap_or_default(),
+                versions: template_info
+                    .map(template_versions::versions)
+                    .unwrap_or_default(),
+                image_slots: template_info
+                    .map(|t| {
+                        image_slots::declared(&t.manifest)
+                            .into_iter()
+                            .cloned()
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+                formats: template_info
+                    .map(|t| t.manifest.output_formats())
+                    .unwrap_or_else(|| vec!["pdf".to_string()]),
+            }
+        })
+        .collect();
+
+    Json(DataResponse {
+        success: true,
+        data: templates,
+        message: "Templates retrieved successfully".to_string(),
+        conversation_id: None,
+        display_format: None,
+        response_type: ResponseType::Data,
+    })
+}
+
+const TEMPLATE_PREVIEW_PPI: u32 = 72;
+
+/// First page of a template's sample person, as a PNG. Rendered once per
+/// version of the template, then served from the engine's cache.
+pub async fn template_preview_handler(
+    id: String,
+    lang: Option<String>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    templates: &State<SharedTemplateEngine>,
+) -> Result<(ContentType, Vec<u8>), Json<StandardErrorResponse>> {
+    let tenant_templates = tenant_templates::dir(&config.data_dir, auth.email(), auth.tenant_id());
+    let engine = (*templates.current())
+        .clone()
+        .with_tenant_templates(&tenant_templates);
+    if engine.get_template(&id).is_none() || !template_health::is_healthy(&id) {
+        return Err(Json(StandardErrorResponse::new(
+            format!("Template '{}' not found", id),
+            "TEMPLATE_NOT_FOUND".to_string(),
+            vec!["GET /templates lists the available templates".to_string()],
+            None,
+        )));
+    }
+    let lang = normalize_language(lang.as_deref());
+    if let Some(png) = engine.cached_preview(&id, &lang).await {
+        return Ok((ContentType::PNG, png));
     }
+
+    // The sample is compiled from a scratch data directory, never a tenant's.
+    let scratch =
+        std::env::temp_dir().join(format!("cvenom-template-preview-{}", uuid::Uuid::new_v4()));
+    let data_dir = scratch.join("data");
+    let preview = async {
+        engine
+            .write_sample_profile(&id, &lang, &data_dir.join(SAMPLE_PROFILE))
+            .await?;
+        let cv_config = CvConfig::new(SAMPLE_PROFILE, &lang)
+            .with_template(id.clone())
+            .with_data_dir(data_dir.clone())
+            .with_output_dir(scratch.join("output"))
+            .with_templates_dir(config.templates_dir.clone())
+            .with_tenant_templates(tenant_templates.clone())
+            .with_preview(TEMPLATE_PREVIEW_PPI);
+        let _permit = generation_scheduler::acquire_for(auth.tenant()).await;
+        CvGenerator::new(cv_config)?.generate_preview().await
+    }
+    .await;
+    let _ = tokio::fs::remove_dir_all(&scratch).await;
+    if let Ok(png) = &preview {
+        engine.store_preview(&id, &lang, png).await;
+    }
+
+    preview.map(|png| (ContentType::PNG, png)).map_err(|e| {
+        app_log!(error, "Preview of template '{}' failed: {}", id, e);
+        Json(StandardErrorResponse::new(
+            format!("Failed to preview template '{}'", id),
+            "PREVIEW_ERROR".to_string(),
+            vec!["Try again later".to_string()],
+            None,
+        ))
+    })
 }
 
-pub async fn get_current_user_handler(auth: AuthenticatedUser) -> Json<DataResponse<UserInfo>> {
+/// Install a tenant template from a ZIP (editors only).
+pub async fn upload_template_handler(
+    upload: rocket::form::Form<TemplateUploadForm<'_>>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    templates: &State<SharedTemplateEngine>,
+) -> Result<Json<DataResponse<String>>, Json<StandardErrorResponse>> {
+    if !auth.role().can_create() {
+        return Err(Json(StandardErrorResponse::new(
+            "Uploading templates requires the editor role".to_string(),
+            "EDITOR_ROLE_REQUIRED".to_string(),
+            vec!["Ask your account administrator for the editor role".to_string()],
+            None,
+        )));
+    }
+    require_feature(config_manager::features().tenant_templates, "tenant_templates", None)?;
+    ensure_storage_available(config, None)?;
+
+    let upload_error = |message: &str| {
+        Json(StandardErrorResponse::new(
+            message.to_string(),
+            "UPLOAD_ERROR".to_string(),
+            vec![],
+            None,
+        ))
+    };
+    let path = upload
+        .file
+        .path()
+        .ok_or_else(|| upload_error("Uploaded file has no path"))?;
+    let archive = tokio::fs::read(path).await.map_err(|e| {
+        app_log!(error, "Reading uploaded template failed: {}", e);
+        upload_error("Failed to read uploaded file")
+    })?;
+
+    let dir = tenant_templates::dir(&config.data_dir, auth.email(), auth.tenant_id());
+    let id = tenant_templates::install(&archive, &dir, &templates.current())
+        .await
+        .map_err(|e| match e.downcast_ref::<tenant_templates::InvalidTemplate>() {
+            Some(invalid) => Json(StandardErrorResponse::new(
+                invalid.to_string(),
+                "INVALID_TEMPLATE".to_string(),
+                vec![
+                    "Zip a template directory with its manifest.toml and main file".to_string(),
+                ],
+                None,
+            )),
+            None => {
+                app_log!(error, "Installing uploaded template failed: {:#}", e);
+                upload_error("Failed to store the template")
+            }
+        })?;
+    app_log!(
+        info,
+        "{} uploaded template '{}' for tenant {}",
+        auth.email(),
+        id,
+        auth.tenant_name()
+    );
+
+    Ok(Json(DataResponse::success(
+        format!("Template '{}' is available to your tenant", id),
+        id,
+        None,
+    )))
+}
+
+/// Dry compile of a template against its sample person (editors only), to
+/// check an upload before the tenant's users pick it.
+pub async fn validate_template_handler(
+    id: String,
+    lang: Option<String>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    templates: &State<SharedTemplateEngine>,
+) -> Result<Json<DataResponse<template_health::TemplateValidation>>, Json<StandardErrorResponse>> {
+    if !auth.role().can_create() {
+        return Err(Json(StandardErrorResponse::new(
+            "Validating templates requires the editor role".to_string(),
+            "EDITOR_ROLE_REQUIRED".to_string(),
+            vec!["Ask your account administrator for the editor role".to_string()],
+            None,
+        )));
+    }
+    let tenant_templates = tenant_templates::dir(&config.data_dir, auth.email(), auth.tenant_id());
+    let engine = (*templates.current())
+        .clone()
+        .with_tenant_templates(&tenant_templates);
+    if engine.get_template(&id).is_none() {
+        return Err(Json(StandardErrorResponse::new(
+            format!("Template '{}' not found", id),
+            "TEMPLATE_NOT_FOUND".to_string(),
+            vec!["GET /templates lists the available templates".to_string()],
+            None,
+        )));
+    }
+    let lang = normalize_language(lang.as_deref());
+
+    let validation = {
+        let _permit = generation_scheduler::acquire_for(auth.tenant()).await;
+        template_health::validate(&engine, &id, &lang).await
+    };
+    let message = if validation.valid {
+        format!("Template '{}' compiles", id)
+    } else {
+        let errors = validation.problems.len()
+            + validation
+                .diagnostics
+                .iter()
+                .filter(|d| d.severity == DiagnosticSeverity::Error)
+                .count();
+        app_log!(
+            info,
+            "Validation of template '{}' for {} found {} error(s)",
+            id,
+            auth.email(),
+            errors
+        );
+        format!("Template '{}' has {} error(s)", id, errors)
+    };
+    Ok(Json(DataResponse::success(message, validation, None)))
+}
+
+/// The caller's own profile is reported, never created: a profile the user
+/// deleted stays deleted.
+pub async fn get_current_user_handler(
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+) -> Json<DataResponse<UserInfo>> {
+    let self_profile = provisioning::self_profile(auth.email(), &config.data_dir)
+        .await
+        .unwrap_or_else(|e| {
+            app_log!(warn, "Failed to look up own profile of {}: {}", auth.email(), e);
+            None
+        });
+    current_user_response(&auth, self_profile)
+}
+
+/// Provisions the caller's own profile when their tenant has none yet.
+pub async fn provision_current_user_handler(
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+) -> Json<DataResponse<UserInfo>> {
+    // Provisioning never blocks login; the frontend just skips onboarding.
+    let self_profile = auth.ensure_profile_exists(config).await.unwrap_or_else(|e| {
+        app_log!(error, "Failed to provision own profile for {}: {}", auth.email(), e);
+        None
+    });
+    current_user_response(&auth, self_profile)
+}
+
+/// The `/me` answer for `auth`, whose own profile is `self_profile`.
+fn current_user_response(
+    auth: &AuthenticatedUser,
+    self_profile: Option<SelfProfile>,
+) -> Json<DataResponse<UserInfo>> {
     let user = auth.user();
     let tenant = auth.tenant();
 
@@ -68,6 +327,9 @@ pub async fn get_current_user_handler(auth: AuthenticatedUser) -> Json<DataRespo
         name: user.name.clone(),
         picture: user.picture.clone(),
         tenant_name: tenant.tenant_name.clone(),
+        needs_onboarding: self_profile.as_ref().is_some_and(|p| p.needs_onboarding),
+        self_profile: self_profile.map(|p| p.profile),
+        role: auth.role().as_str(),
     };
 
     Json(DataResponse::success(
@@ -101,10 +363,13 @@ pub async fn delete_account_handler(
 
     // 1. Delete all files on disk
     let tenant_data_dir = get_tenant_folder_path(&email, &config.data_dir);
-    if tenant_data_dir.exists() {
-        if let Err(e) = FsOps::remove_dir_all(&tenant_data_dir).await {
-            app_log!(error, "Failed to delete tenant directory for {}: {}", email, e);
-            // Proceed anyway — DB record must still be removed
+    let tenant_output_dir = get_tenant_output_path(&email, &config.output_dir);
+    for dir in [&tenant_data_dir, &tenant_output_dir] {
+        if dir.exists() {
+            if let Err(e) = FsOps::remove_dir_all(dir).await {
+                app_log!(error, "Failed to delete {} for {}: {}", dir.display(), email, e);
+                // Proceed anyway — DB record must still be removed
+            }
         }
     }
 
@@ -132,6 +397,28 @@ pub async fn delete_account_handler(
         )));
     }
 
+    // 3. Rows of the account's own tables; kept uploads live outside its folder
+    let account = auth.account_key();
+    if let Err(e) = import_retry::delete_for(pool, &account).await {
+        app_log!(error, "Failed to delete failed imports of {}: {}", email, e);
+    }
+    for table in owner::ACCOUNT_TABLES {
+        let deleted = sqlx::query(&format!("DELETE FROM {} WHERE owner = ?", table))
+            .bind(&account)
+            .execute(pool)
+            .await;
+        if let Err(e) = deleted {
+            app_log!(error, "Failed to delete {} rows of {}: {}", table, email, e);
+        }
+    }
+
+    audit_log::record(
+        pool,
+        auth.tenant_id(),
+        &email,
+        AuditAction::AccountDeleted,
+        &email,
+    );
     app_log!(info, "Account fully deleted for: {}", email);
     crate::email::send_email(&email, crate::email::EmailKind::AccountDeleted, auth.lang());
     Ok(Json(ActionResponse::success(
@@ -141,12 +428,211 @@ pub async fn delete_account_handler(
     )))
 }
 
-pub async fn health_handler(auth: OptionalAuth) -> Json<TextResponse> {
-    let message = if auth.user.is_some() {
-        "System is healthy (authenticated user)".to_string()
-    } else {
-        "System is healthy".to_string()
+#[derive(serde::Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct HealthStatus {
+    pub storage_ok: bool,
+    /// Data and output volumes (one entry when they share a path); only for
+    /// authenticated callers, since it names the volume paths.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub volumes: Option<Vec<VolumeUsage>>,
+    /// Hit rate of the auth guard's tenant lookups; authenticated callers only.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tenant_cache: Option<CacheStats>,
+}
+
+pub async fn health_handler(
+    auth: OptionalAuth,
+    config: &State<ServerConfig>,
+) -> Json<DataResponse<HealthStatus>> {
+    let mut volumes = Vec::new();
+    for dir in [&config.data_dir, &config.output_dir] {
+        if volumes.iter().any(|v: &VolumeUsage| &v.path == dir) {
+            continue;
+        }
+        if let Some(usage) = disk_monitor::usage(dir) {
+            volumes.push(usage);
+        }
+    }
+    let storage_ok = volumes.iter().all(|v| v.ok);
+
+    let authenticated = auth.user.is_some();
+    let message = match (storage_ok, authenticated) {
+        (false, _) => "System is degraded: storage almost full".to_string(),
+        (true, true) => "System is healthy (authenticated user)".to_string(),
+        (true, false) => "System is healthy".to_string(),
     };
 
-    Json(TextResponse::success(message, None))
+    Json(DataResponse::success(
+        message,
+        HealthStatus {
+            storage_ok,
+            volumes: authenticated.then_some(volumes),
+            tenant_cache: authenticated.then(tenant_cache::stats),
+        },
+        None,
+    ))
+}
+
+/// Settled once when the server is built, from the configuration and
+/// [`config_manager::features`].
+#[derive(Clone, serde::Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct Subsystems {
+    /// Job-fit analysis, CV optimization, translation and cover letters all
+    /// go through the CV service.
+    pub analysis: bool,
+    pub optimization: bool,
+    pub translation: bool,
+    pub cover_letters: bool,
+    pub docx_export: bool,
+    pub html_export: bool,
+    pub pdf_a: bool,
+    /// Watching and reviewing the profiles of other members of the tenant.
+    pub sharing: bool,
+    pub tenant_templates: bool,
+    pub template_registry: bool,
+    /// Registry templates and fonts synced with the other instances.
+    pub asset_sync: bool,
+    pub payments: bool,
+    pub email_intake: bool,
+    pub secrets: bool,
+}
+
+impl Subsystems {
+    pub fn detect(cv_service_url: &str, secrets: &SecretStore) -> Self {
+        let set = |name: &str| std::env::var(name).is_ok_and(|v| !v.trim().is_empty());
+        let cv_service = !cv_service_url.trim().is_empty();
+        let features = config_manager::features();
+        Self {
+            analysis: cv_service,
+            optimization: cv_service,
+            translation: cv_service,
+            cover_letters: cv_service,
+            docx_export: features.docx_export,
+            html_export: true,
+            pdf_a: features.pdf_a,
+            sharing: features.sharing,
+            tenant_templates: features.tenant_templates,
+            template_registry: Registry::from_env().is_some(),
+            asset_sync: asset_sync::config().is_some(),
+            payments: set("STRIPE_SECRET_KEY"),
+            email_intake: email_intake::inbound_secret().is_some(),
+            secrets: secrets.enabled(),
+        }
+    }
+}
+
+#[derive(serde::Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct DeploymentLimits {
+    pub max_upload_bytes: u64,
+    pub max_template_archive_bytes: usize,
+    pub max_batch_persons: usize,
+    pub max_languages_per_generation: usize,
+    pub max_preview_templates: usize,
+    /// Languages the templates' labels are translated into.
+    pub languages: Vec<String>,
+}
+
+#[derive(serde::Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct TemplateCapability {
+    pub id: String,
+    pub name: String,
+    pub version: String,
+    pub formats: Vec<String>,
+}
+
+#[derive(serde::Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct Capabilities {
+    pub version: &'static str,
+    pub subsystems: Subsystems,
+    pub limits: DeploymentLimits,
+    /// Healthy server templates; `GET /templates` adds the tenant's own.
+    pub templates: Vec<TemplateCapability>,
+}
+
+/// What this deployment offers, so clients don't hard-code it.
+pub async fn capabilities_handler(
+    subsystems: &State<Subsystems>,
+    templates: &State<SharedTemplateEngine>,
+) -> Json<DataResponse<Capabilities>> {
+    let engine = templates.current();
+    let inventory = engine
+        .list_templates()
+        .into_iter()
+        .filter(|id| template_health::is_healthy(id))
+        .filter_map(|id| engine.get_template(&id))
+        .map(|template| TemplateCapability {
+            id: template.id.clone(),
+            name: template.manifest.name.clone(),
+            version: template_versions::current_version(template),
+            formats: template.manifest.output_formats(),
+        })
+        .collect();
+
+    Json(DataResponse::success(
+        "Capabilities retrieved".to_string(),
+        Capabilities {
+            version: env!("CARGO_PKG_VERSION"),
+            subsystems: subsystems.inner().clone(),
+            limits: DeploymentLimits {
+                max_upload_bytes: MAX_UPLOAD_MB * 1024 * 1024,
+                max_template_archive_bytes: tenant_templates::MAX_ARCHIVE_BYTES,
+                max_batch_persons: MAX_BATCH_PERSONS,
+                max_languages_per_generation: MAX_LANGUAGES,
+                max_preview_templates: MAX_PREVIEW_TEMPLATES,
+                languages: translations::languages()
+                    .into_iter()
+                    .map(str::to_string)
+                    .collect(),
+            },
+            templates: inventory,
+        },
+        None,
+    ))
+}
+
+/// Refuse new uploads/generations while the data or output volume is below
+/// its free-space or inode threshold.
+pub fn ensure_storage_available(
+    config: &ServerConfig,
+    conversation_id: Option<String>,
+) -> Result<(), Json<StandardErrorResponse>> {
+    disk_monitor::check(&[&config.data_dir, &config.output_dir]).map_err(|e| {
+        app_log!(error, "Rejecting request: {}", e);
+        Json(StandardErrorResponse::new(
+            "Storage is full; new files can't be saved right now".to_string(),
+            "STORAGE_FULL".to_string(),
+            vec![
+                "Try again later".to_string(),
+                "Contact the administrator if this persists".to_string(),
+            ],
+            conversation_id,
+        ))
+    })
+}
+
+/// POST /requests/<id>/cancel — abandon a running request the client sent
+/// with `X-Request-Id: <id>`.
+pub async fn cancel_request_handler(
+    id: String,
+    auth: AuthenticatedUser,
+) -> Result<Json<ActionResponse>, Json<StandardErrorResponse>> {
+    if !request_cancel::cancel(auth.email(), &id) {
+        return Err(Json(StandardErrorResponse::new(
+            format!("No running request with id '{}'", id),
+            "REQUEST_NOT_FOUND".to_string(),
+            vec!["The request may already have finished".to_string()],
+            None,
+        )));
+    }
+    app_log!(info, "Request {} cancelled by {}", id, auth.email());
+    Ok(Json(ActionResponse::success(
+        "Request cancelled".to_string(),
+        "REQUEST_CANCELLED".to_string(),
+        None,
+    )))
 }
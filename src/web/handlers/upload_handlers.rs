@@ -0,0 +1,218 @@
+// src/web/handlers/upload_handlers.rs
+//! Resumable uploads with progress; see [`crate::core::upload_sessions`].
+
+use futures::Stream;
+use graflog::app_log;
+use rocket::data::{Data, ToByteUnit};
+use rocket::response::stream::{Event, EventStream};
+use rocket::serde::json::Json;
+use rocket::State;
+
+use crate::auth::AuthenticatedUser;
+use crate::core::data_residency;
+use crate::core::database::get_tenant_folder_path;
+use crate::core::presigned_uploads::{self, PresignedUpload};
+use crate::core::upload_sessions::{self, UploadError, UploadProgress};
+use crate::web::handlers::system_handlers::ensure_storage_available;
+use crate::web::types::{
+    CreateUploadRequest, DataResponse, ServerConfig, StandardErrorResponse, UploadOffset,
+};
+
+pub fn upload_error(e: UploadError) -> Json<StandardErrorResponse> {
+    let (code, suggestions) = match &e {
+        UploadError::NotFound => (
+            "UPLOAD_NOT_FOUND",
+            vec!["Start a new upload with POST /uploads".to_string()],
+        ),
+        UploadError::OffsetMismatch { expected } => (
+            "OFFSET_MISMATCH",
+            vec![format!("Send the rest of the file with Upload-Offset: {}", expected)],
+        ),
+        UploadError::Busy => (
+            "UPLOAD_BUSY",
+            vec!["Wait for the current chunk to finish".to_string()],
+        ),
+        UploadError::TooLarge => (
+            "UPLOAD_TOO_LARGE",
+            vec!["Send exactly the announced number of bytes".to_string()],
+        ),
+        UploadError::Incomplete { received, .. } => (
+            "UPLOAD_INCOMPLETE",
+            vec![format!("Send the rest of the file with Upload-Offset: {}", received)],
+        ),
+        UploadError::Invalid(_) => ("INVALID_UPLOAD", vec![]),
+        UploadError::Io(_) => (
+            "UPLOAD_STORAGE_ERROR",
+            vec!["Try again in a few moments".to_string()],
+        ),
+    };
+    if let UploadError::Io(message) = &e {
+        app_log!(error, "Upload storage failed: {}", message);
+    }
+    Json(StandardErrorResponse::new(
+        e.to_string(),
+        code.to_string(),
+        suggestions,
+        None,
+    ))
+}
+
+/// POST /uploads
+pub async fn create_upload_handler(
+    request: Json<CreateUploadRequest>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+) -> Result<Json<DataResponse<UploadProgress>>, Json<StandardErrorResponse>> {
+    ensure_storage_available(config, None)?;
+    let tenant_dir = get_tenant_folder_path(auth.email(), &config.data_dir);
+    let dir = data_residency::data_root_of(&config.data_dir, &tenant_dir)
+        .join(upload_sessions::UPLOAD_DIR);
+    let upload = upload_sessions::create(
+        auth.email(),
+        &request.filename,
+        request.content_type.clone(),
+        request.size,
+        &dir,
+    )
+    .map_err(upload_error)?;
+    app_log!(
+        info,
+        "{} started upload {} ({}, {} bytes)",
+        auth.email(),
+        upload.id,
+        upload.filename,
+        upload.size
+    );
+    Ok(Json(DataResponse::success(
+        "Upload started".to_string(),
+        upload,
+        None,
+    )))
+}
+
+/// POST /uploads/presigned
+pub async fn presign_upload_handler(
+    request: Json<CreateUploadRequest>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+) -> Result<Json<DataResponse<PresignedUpload>>, Json<StandardErrorResponse>> {
+    ensure_storage_available(config, None)?;
+    let Some(s3) = presigned_uploads::config() else {
+        return Err(Json(StandardErrorResponse::new(
+            "Direct uploads to object storage are not enabled".to_string(),
+            "PRESIGNED_UPLOADS_DISABLED".to_string(),
+            vec!["Upload through POST /uploads instead".to_string()],
+            None,
+        )));
+    };
+    let upload = presigned_uploads::create(
+        s3,
+        auth.email(),
+        auth.tenant_name(),
+        &request.filename,
+        request.content_type.clone(),
+        request.size,
+    )
+    .map_err(upload_error)?;
+    app_log!(
+        info,
+        "{} got a pre-signed upload {} ({}, {} bytes)",
+        auth.email(),
+        upload.id,
+        upload.filename,
+        upload.size
+    );
+    Ok(Json(DataResponse::success(
+        "Send the file to the URL, then complete the upload".to_string(),
+        upload,
+        None,
+    )))
+}
+
+/// PATCH /uploads/<id>
+pub async fn upload_chunk_handler(
+    id: String,
+    data: Data<'_>,
+    offset: UploadOffset,
+    auth: AuthenticatedUser,
+) -> Result<Json<DataResponse<UploadProgress>>, Json<StandardErrorResponse>> {
+    let current = upload_sessions::progress(auth.email(), &id).map_err(upload_error)?;
+    let Some(offset) = offset.0 else {
+        return Err(upload_error(UploadError::OffsetMismatch {
+            expected: current.received,
+        }));
+    };
+    // One byte over what is left, so an oversized chunk is noticed.
+    let limit = current.size.saturating_sub(offset) + 1;
+    let progress = upload_sessions::append(auth.email(), &id, offset, data.open(limit.bytes()))
+        .await
+        .map_err(upload_error)?;
+    let message = if progress.complete {
+        "Upload complete".to_string()
+    } else {
+        format!("Received {} of {} bytes", progress.received, progress.size)
+    };
+    Ok(Json(DataResponse::success(message, progress, None)))
+}
+
+/// GET /uploads/<id>
+pub async fn upload_progress_handler(
+    id: String,
+    auth: AuthenticatedUser,
+) -> Result<Json<DataResponse<UploadProgress>>, Json<StandardErrorResponse>> {
+    let progress = upload_sessions::progress(auth.email(), &id).map_err(upload_error)?;
+    Ok(Json(DataResponse::success(
+        format!("{}% received", progress.percent),
+        progress,
+        None,
+    )))
+}
+
+/// GET /uploads/<id>/events — a `progress` event now and whenever more bytes
+/// arrive; the stream ends once the upload is complete or gone.
+pub async fn upload_events_handler(
+    id: String,
+    auth: AuthenticatedUser,
+) -> Result<EventStream<impl Stream<Item = Event>>, Json<StandardErrorResponse>> {
+    let (first, receiver) = upload_sessions::subscribe(auth.email(), &id).map_err(upload_error)?;
+    let events = futures::stream::unfold(
+        (Some(first.clone()), receiver, false),
+        move |(pending, mut receiver, done)| {
+            let first = first.clone();
+            async move {
+                if done {
+                    return None;
+                }
+                let progress = match pending {
+                    Some(progress) => progress,
+                    None => {
+                        receiver.changed().await.ok()?;
+                        let received = *receiver.borrow_and_update();
+                        upload_sessions::progress_at(&first, received)
+                    }
+                };
+                let complete = progress.complete;
+                Some((
+                    Event::json(&progress).event("progress"),
+                    (None, receiver, complete),
+                ))
+            }
+        },
+    );
+    Ok(EventStream::from(events))
+}
+
+/// DELETE /uploads/<id>
+pub async fn cancel_upload_handler(
+    id: String,
+    auth: AuthenticatedUser,
+) -> Result<Json<DataResponse<bool>>, Json<StandardErrorResponse>> {
+    if !upload_sessions::cancel(auth.email(), &id) {
+        return Err(upload_error(UploadError::NotFound));
+    }
+    Ok(Json(DataResponse::success(
+        "Upload cancelled".to_string(),
+        true,
+        None,
+    )))
+}
@@ -0,0 +1,246 @@
+// src/web/handlers/usage_handlers.rs
+//! cv-import service usage per tenant, for LLM cost chargeback (admin only),
+//! and the monthly AI budgets it is counted against.
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use graflog::app_log;
+use rocket::http::ContentType;
+use rocket::serde::json::Json;
+use rocket::State;
+use sqlx::SqlitePool;
+
+use crate::auth::AuthenticatedUser;
+use crate::core::ai_budget::{self, AiFeature, BudgetExceeded, BudgetStatus};
+use crate::core::database::{get_tenant_folder_path, DatabaseConfig};
+use crate::core::service_cache::ResultCache;
+use crate::core::service_usage::{self, UsageRecorder, UsageSummary};
+use crate::web::handlers::common::{admin_only, db_error, pool, tenant_by_address};
+use crate::web::types::{ActionResponse, AiBudgetRequest, DataResponse, StandardErrorResponse};
+use crate::web::ServerConfig;
+
+const DEFAULT_RANGE_DAYS: i64 = 30;
+
+/// Recorder attributing a handler's service calls to the caller's tenant.
+/// `None` (calls go unrecorded) when the database is unavailable.
+pub fn usage_recorder(
+    db_config: &DatabaseConfig,
+    auth: &AuthenticatedUser,
+) -> Option<UsageRecorder> {
+    let pool = db_config.pool().ok()?;
+    Some(UsageRecorder::new(pool, auth.tenant_id(), auth.email()))
+}
+
+/// Cache of the cv-import answers given to the caller's account (see
+/// `core::service_cache`); `force_refresh` asks the service again.
+pub fn result_cache(
+    config: &ServerConfig,
+    auth: &AuthenticatedUser,
+    force_refresh: bool,
+) -> Option<ResultCache> {
+    let user_dir = get_tenant_folder_path(auth.email(), &config.data_dir);
+    ResultCache::new(&user_dir, force_refresh)
+}
+
+/// Refuse an AI-backed request once the caller's tenant has used up its
+/// monthly `feature` budget. A database failure lets the request through.
+pub async fn check_ai_budget(
+    db_config: &DatabaseConfig,
+    auth: &AuthenticatedUser,
+    feature: AiFeature,
+    conversation_id: Option<String>,
+) -> Result<(), Json<StandardErrorResponse>> {
+    let Ok(pool) = db_config.pool() else {
+        return Ok(());
+    };
+    match ai_budget::check(pool, auth.tenant_id(), feature).await {
+        Ok(()) => Ok(()),
+        Err(e) => match e.downcast_ref::<BudgetExceeded>() {
+            Some(exceeded) => {
+                app_log!(
+                    warn,
+                    "{} budget of {} exhausted ({} calls)",
+                    feature.as_str(),
+                    auth.tenant_name(),
+                    exceeded.0.used
+                );
+                Err(Json(StandardErrorResponse::new(
+                    exceeded.to_string(),
+                    "BUDGET_EXCEEDED".to_string(),
+                    vec![
+                        format!("Try again after {}", exceeded.0.resets_at.format("%Y-%m-%d")),
+                        "Ask your administrator to raise the budget".to_string(),
+                    ],
+                    conversation_id,
+                )))
+            }
+            None => {
+                app_log!(warn, "Failed to check AI budget of {}: {}", auth.tenant_name(), e);
+                Ok(())
+            }
+        },
+    }
+}
+
+/// GET /usage — the caller's tenant's AI budgets and this month's use.
+pub async fn tenant_usage_handler(
+    auth: AuthenticatedUser,
+    db_config: &State<DatabaseConfig>,
+) -> Result<Json<DataResponse<Vec<BudgetStatus>>>, Json<StandardErrorResponse>> {
+    let pool = db_config.pool().map_err(db_error)?;
+    let budgets = ai_budget::statuses(pool, auth.tenant_id())
+        .await
+        .map_err(db_error)?;
+    Ok(Json(DataResponse::success(
+        format!("AI usage of {} this month", auth.tenant_name()),
+        budgets,
+        None,
+    )))
+}
+
+/// PUT /admin/ai-budget — monthly call budget of one AI feature for a tenant.
+pub async fn set_ai_budget_handler(
+    request: Json<AiBudgetRequest>,
+    auth: AuthenticatedUser,
+    db_config: &State<DatabaseConfig>,
+) -> Result<Json<ActionResponse>, Json<StandardErrorResponse>> {
+    admin_only(&auth)?;
+    let tenant = request.tenant.trim();
+    let feature = AiFeature::parse(&request.feature).ok_or_else(|| {
+        Json(StandardErrorResponse::new(
+            format!("Unknown AI feature '{}'", request.feature),
+            "INVALID_FEATURE".to_string(),
+            vec!["Use 'analysis', 'optimization' or 'translation'".to_string()],
+            None,
+        ))
+    })?;
+    if tenant.is_empty() {
+        return Err(Json(StandardErrorResponse::new(
+            "Tenant is required".to_string(),
+            "INVALID_TENANT".to_string(),
+            vec![],
+            None,
+        )));
+    }
+    let pool = pool(db_config)?;
+    let tenant = tenant_by_address(pool, tenant).await?;
+    ai_budget::set_budget(pool, tenant.id, feature, request.monthly_calls)
+        .await
+        .map_err(db_error)?;
+
+    let budget = match request.monthly_calls {
+        Some(calls) => format!("{} calls a month", calls.max(0)),
+        None => "the server default".to_string(),
+    };
+    app_log!(
+        info,
+        "{} budget of {} set to {}",
+        feature.as_str(),
+        tenant.tenant_name,
+        budget
+    );
+    Ok(Json(ActionResponse::success(
+        format!(
+            "The {} budget of {} is now {}",
+            feature.as_str(),
+            tenant.tenant_name,
+            budget
+        ),
+        "AI_BUDGET_UPDATED".to_string(),
+        None,
+    )))
+}
+
+/// `[from, to)` from inclusive `YYYY-MM-DD` dates; defaults to the last
+/// 30 days.
+pub(crate) fn date_range(
+    from: Option<&str>,
+    to: Option<&str>,
+) -> Result<(DateTime<Utc>, DateTime<Utc>), Json<StandardErrorResponse>> {
+    let parse = |value: &str| {
+        NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d").map_err(|_| {
+            Json(StandardErrorResponse::new(
+                format!("Invalid date '{}'", value),
+                "INVALID_DATE".to_string(),
+                vec!["Use YYYY-MM-DD, e.g. 2025-01-31".to_string()],
+                None,
+            ))
+        })
+    };
+    let start_of = |date: NaiveDate| date.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc();
+
+    let to = match to {
+        Some(value) => start_of(parse(value)? + Duration::days(1)),
+        None => Utc::now(),
+    };
+    let from = match from {
+        Some(value) => start_of(parse(value)?),
+        None => to - Duration::days(DEFAULT_RANGE_DAYS),
+    };
+    Ok((from, to))
+}
+
+/// Id of the tenant an admin filters on, by a member's email or the domain.
+async fn admin_tenant_id(
+    pool: &SqlitePool,
+    tenant: Option<&str>,
+) -> Result<Option<i64>, Json<StandardErrorResponse>> {
+    match tenant.map(str::trim).filter(|t| !t.is_empty()) {
+        Some(address) => Ok(Some(tenant_by_address(pool, address).await?.id)),
+        None => Ok(None),
+    }
+}
+
+/// GET /admin/service-usage — calls, failures, durations, bytes and tokens
+/// per tenant and endpoint.
+pub async fn service_usage_handler(
+    from: Option<String>,
+    to: Option<String>,
+    tenant: Option<String>,
+    auth: AuthenticatedUser,
+    db_config: &State<DatabaseConfig>,
+) -> Result<Json<DataResponse<Vec<UsageSummary>>>, Json<StandardErrorResponse>> {
+    admin_only(&auth)?;
+    let (from, to) = date_range(from.as_deref(), to.as_deref())?;
+    let pool = pool(db_config)?;
+    let tenant_id = admin_tenant_id(pool, tenant.as_deref()).await?;
+    let rows = service_usage::summary(pool, from, to, tenant_id)
+        .await
+        .map_err(db_error)?;
+
+    Ok(Json(DataResponse::success(
+        format!(
+            "Service usage from {} to {}",
+            from.format("%Y-%m-%d"),
+            to.format("%Y-%m-%d %H:%M")
+        ),
+        rows,
+        None,
+    )))
+}
+
+/// GET /admin/service-usage/export — one CSV line per call.
+pub async fn service_usage_csv_handler(
+    from: Option<String>,
+    to: Option<String>,
+    tenant: Option<String>,
+    auth: AuthenticatedUser,
+    db_config: &State<DatabaseConfig>,
+) -> Result<(ContentType, String), Json<StandardErrorResponse>> {
+    admin_only(&auth)?;
+    let (from, to) = date_range(from.as_deref(), to.as_deref())?;
+    let pool = pool(db_config)?;
+    let tenant_id = admin_tenant_id(pool, tenant.as_deref()).await?;
+    let calls = service_usage::calls(pool, from, to, tenant_id)
+        .await
+        .map_err(db_error)?;
+    let csv = service_usage::to_csv(&calls).map_err(|e| {
+        Json(StandardErrorResponse::new(
+            format!("CSV export failed: {}", e),
+            "EXPORT_FAILED".to_string(),
+            vec![],
+            None,
+        ))
+    })?;
+
+    Ok((ContentType::CSV, csv))
+}
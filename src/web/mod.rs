@@ -1,28 +1,39 @@
 // src/web/mod.rs
+pub mod conversations;
 pub mod file_handlers;
 pub mod handlers;
+pub mod proxy;
+pub mod sandbox;
+pub mod static_files;
+pub mod tls;
 pub mod types;
 use crate::auth::{AuthConfig, AuthenticatedUser, OptionalAuth};
+use crate::config::OutputFormat;
 use crate::core::database::DatabaseConfig;
 use crate::linkedin_analysis::JobAnalysisRequest;
-use crate::types::response::{OptimizeResponse, TranslateResponse};
 use crate::web::handlers::cover_letter::CoverLetterRequest;
 use crate::web::handlers::cover_letter::CoverLetterResult;
-use crate::web::handlers::translate::TranslateCvRequest;
 use crate::web::handlers::{
     cover_letter_handler,
     cover_letter_export_handler,
     delete_account_handler,
-    get_cv_data_handler, put_cv_data_handler,
+    get_cv_data_handler, put_cv_data_handler, get_cv_section_handler, put_cv_section_handler,
+    upgrade_cv_schema_handler,
     optimize_and_generate_handler, optimize_cv_handler, save_optimized_handler, translate_cv_handler,
     upload_and_convert_cv_handler, import_text_cv_handler,
     generate_portfolio_handler,
 };
 use crate::web::handlers::cv_handlers::GeneratePortfolioRequest;
 use crate::web::handlers::cv_handlers::ImportTextRequest;
+use crate::web::handlers::cv_handlers::ProfileComparison;
+use crate::web::handlers::cv_handlers::{
+    profile_from_notes_handler, ProfileFromNotesRequest, ProfileFromNotesResult,
+};
 use crate::web::handlers::cv_handlers::CoverLetterExportRequest;
 use crate::core::database::{get_tenant_folder_path, TenantRepository};
-use crate::core::FsOps;
+use crate::core::secrets::SecretStore;
+use crate::core::template_engine::SharedTemplateEngine;
+use crate::core::{data_residency, FsOps};
 use crate::web::handlers::cv_data::CvFormData;
 use crate::web::handlers::payment_handlers::{
     ConfirmPaymentRequest, CreateIntentRequest, GetBalanceResponse, TransactionsResponse,
@@ -31,6 +42,24 @@ use crate::web::handlers::payment_handlers::{
     admin_credit_users_handler, admin_user_transactions_handler,
 };
 use crate::web::handlers::referral_handlers::{get_referral_link_handler, ReferralLinkResponse};
+use crate::web::handlers::bench_handlers::{bench_match_handler, BenchMatchRequest, BenchMatchResult};
+use crate::web::handlers::calendar_handlers::{
+    calendar_ics_handler, get_calendar_feed_handler, CalendarFeed,
+};
+use crate::web::handlers::conversation_handlers::{
+    delete_conversation_handler, get_conversation_handler, list_conversations_handler,
+};
+use crate::core::conversation_store::{ConversationInfo, ConversationTurn};
+use crate::web::handlers::usage_handlers::{service_usage_csv_handler, service_usage_handler};
+use crate::core::generation_outputs::DeletedGeneration;
+use crate::core::import_retry::FailedImport;
+use crate::core::ai_budget::BudgetStatus;
+use crate::core::service_usage::UsageSummary;
+use crate::core::upload_sessions::UploadProgress;
+use crate::web::handlers::intake_handlers::{
+    get_intake_address_handler, inbound_email_handler, rotate_intake_address_handler, InboundSecret,
+    IntakeAddress,
+};
 use crate::web::handlers::feedback_handlers::{
     feedback_eligible_handler, submit_feedback_handler, admin_feedbacks_handler,
     SubmitFeedbackRequest, SubmitFeedbackResponse, FeedbackEligibleResponse,
@@ -56,10 +85,12 @@ use rocket::data::ByteUnit;
 use rocket::fairing::{Fairing, Info, Kind};
 use rocket::form::Form;
 use rocket::http::Method;
-use rocket::http::{Header, Status};
+use rocket::http::{ContentType, Header, Status};
 use rocket::serde::json::Json;
-use rocket::{catchers, delete, get, post, put, routes, Request, Response, State};
+use rocket::{catchers, delete, get, patch, post, put, routes, Request, Response, State};
 use rocket::fs::NamedFile;
+use rocket::response::stream::{Event, EventStream};
+use futures::stream::Stream;
 use std::path::PathBuf;
 pub use types::*;
 mod cors_utils;
@@ -86,11 +117,14 @@ impl Fairing for Cors {
     }
 
     async fn on_request(&self, request: &mut Request<'_>, _: &mut rocket::Data<'_>) {
+        let client = proxy::ProxyInfo::from_request(request);
         app_log!(
             info,
-            "CORS: Request method: {:?}, Origin: {:?}",
+            "CORS: Request method: {:?}, Origin: {:?}, client: {:?}, proto: {:?}",
             request.method(),
-            request.headers().get_one("Origin")
+            request.headers().get_one("Origin"),
+            client.client_ip,
+            client.proto
         );
     }
 
@@ -104,9 +138,13 @@ impl Fairing for Cors {
             "http://localhost:3000",
             "http://127.0.0.1:4001",
         ];
+        // Extra origins for self-hosted deployments behind their own domain.
+        let extra_origins = std::env::var("CORS_ALLOWED_ORIGINS").unwrap_or_default();
 
         if let Some(origin) = origin {
-            if allowed_origins.contains(&origin) {
+            if allowed_origins.contains(&origin)
+                || extra_origins.split(',').any(|o| o.trim() == origin)
+            {
                 response.set_header(Header::new("Access-Control-Allow-Origin", origin));
             }
         } else {
@@ -116,14 +154,16 @@ impl Fairing for Cors {
             ));
         }
 
+        // The allowed origin is echoed per request — tell proxy caches so.
+        response.set_header(Header::new("Vary", "Origin"));
         response.set_header(Header::new("Access-Control-Allow-Credentials", "true"));
         response.set_header(Header::new(
             "Access-Control-Allow-Headers",
-            "authorization, content-type, accept, origin, x-requested-with, x-referral-code",
+            "authorization, content-type, accept, origin, x-requested-with, x-referral-code, x-request-id, upload-offset, x-sandbox",
         ));
         response.set_header(Header::new(
             "Access-Control-Allow-Methods",
-            "GET, POST, PUT, DELETE, OPTIONS",
+            "GET, POST, PUT, PATCH, DELETE, OPTIONS",
         ));
 
         // Ensure OPTIONS requests always return 200
@@ -133,9 +173,51 @@ impl Fairing for Cors {
     }
 }
 
+/// GET /outputs/<file..> — generated files; 410 for one its owner deleted
 #[get("/outputs/<file..>")]
-pub async fn get_output_file(file: PathBuf, config: &State<ServerConfig>) -> Option<NamedFile> {
-    NamedFile::open(config.output_dir.join(file)).await.ok()
+pub async fn get_output_file(
+    file: PathBuf,
+    config: &State<ServerConfig>,
+    db_config: &State<DatabaseConfig>,
+) -> Result<Option<NamedFile>, (Status, Json<StandardErrorResponse>)> {
+    let path = data_residency::output_file(&config.output_dir, &file);
+    if let Ok(named) = NamedFile::open(path).await {
+        return Ok(Some(named));
+    }
+    match handlers::generation_handlers::output_tombstone(&file, db_config).await {
+        Some(gone) => Err(gone),
+        None => Ok(None),
+    }
+}
+
+/// DELETE /generations/<id> — delete one generated CV
+#[delete("/generations/<id>")]
+pub async fn delete_generation(
+    id: String,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    db_config: &State<DatabaseConfig>,
+) -> Result<Json<DataResponse<DeletedGeneration>>, Json<StandardErrorResponse>> {
+    handlers::generation_handlers::delete_generation_handler(id, auth, config, db_config).await
+}
+
+/// DELETE /profiles/<name>/generations?older_than_days= — purge a person's generated CVs
+#[delete("/profiles/<name>/generations?<older_than_days>")]
+pub async fn purge_generations(
+    name: String,
+    older_than_days: Option<u32>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    db_config: &State<DatabaseConfig>,
+) -> Result<Json<DataResponse<Vec<DeletedGeneration>>>, Json<StandardErrorResponse>> {
+    handlers::generation_handlers::purge_generations_handler(
+        name,
+        older_than_days,
+        auth,
+        config,
+        db_config,
+    )
+    .await
 }
 
 #[post("/analyze-job-fit", data = "<request>")]
@@ -145,8 +227,36 @@ pub async fn analyze_job_fit(
     config: &State<ServerConfig>,
     cv_service_url: &State<String>,
     db_config: &State<DatabaseConfig>,
+    request_id: ClientRequestId,
 ) -> Result<Json<TextResponse>, Json<StandardErrorResponse>> {
-    handlers::analyze_job_fit_handler(request, auth, config, cv_service_url, db_config).await
+    handlers::analyze_job_fit_handler(request, auth, config, cv_service_url, db_config, request_id).await
+}
+
+/// POST /analyze-job-fit/stream — job fit analysis as server-sent events
+#[post("/analyze-job-fit/stream", data = "<request>")]
+pub async fn analyze_job_fit_stream(
+    request: Json<StandardRequest<JobAnalysisRequest>>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    cv_service_url: &State<String>,
+    db_config: &State<DatabaseConfig>,
+    request_id: ClientRequestId,
+) -> Result<EventStream<impl Stream<Item = Event>>, Json<StandardErrorResponse>> {
+    handlers::analyze_job_fit_stream_handler(request, auth, config, cv_service_url, db_config, request_id)
+        .await
+}
+
+/// POST /jobs/bench-match — rank all (or selected) profiles against one job
+/// Request body: { "job_url": "...", "job_description": "optional", "profiles": ["optional"] }
+#[post("/jobs/bench-match", data = "<request>")]
+pub async fn bench_match(
+    request: Json<StandardRequest<BenchMatchRequest>>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    cv_service_url: &State<String>,
+    db_config: &State<DatabaseConfig>,
+) -> Result<Json<DataResponse<BenchMatchResult>>, Json<StandardErrorResponse>> {
+    bench_match_handler(request, auth, config, cv_service_url, db_config).await
 }
 
 #[rocket::put("/profiles/<old_name>/rename", data = "<request>")]
@@ -155,8 +265,9 @@ pub async fn rename_profile_handler(
     request: Json<StandardRequest<RenameProfileRequest>>,
     auth: AuthenticatedUser,
     config: &State<ServerConfig>,
+    db_config: &State<DatabaseConfig>,
 ) -> Result<Json<ActionResponse>, Json<StandardErrorResponse>> {
-    handlers::rename_profile_handler(old_name, request, auth, config).await
+    handlers::rename_profile_handler(old_name, request, auth, config, db_config).await
 }
 
 #[rocket::put("/profiles/<profile_name>/change-language", data = "<request>")]
@@ -169,14 +280,559 @@ pub async fn change_profile_language_handler(
     handlers::change_profile_language_handler(profile_name, request, auth, config).await
 }
 
+/// PUT /profiles/<name>/type — mark a profile as the user's own or a collaborator
+#[put("/profiles/<name>/type", data = "<request>")]
+pub async fn set_profile_type(
+    name: String,
+    request: Json<crate::web::types::SetProfileTypeRequest>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+) -> Result<Json<ActionResponse>, Json<StandardErrorResponse>> {
+    handlers::set_profile_type_handler(name, request, auth, config).await
+}
+
 #[post("/generate", data = "<request>")]
 pub async fn generate_cv(
     request: Json<StandardRequest<GenerateRequest>>,
     auth: AuthenticatedUser,
     config: &State<ServerConfig>,
     db_config: &State<DatabaseConfig>,
-) -> Result<Json<GeneratePdfResponse>, Json<StandardErrorResponse>> {
-    handlers::generate_cv_handler(request, auth, config, db_config).await
+    proxy: proxy::ProxyInfo,
+    request_id: ClientRequestId,
+) -> Result<handlers::GenerateResponse, Json<StandardErrorResponse>> {
+    use handlers::GenerateResponse;
+    if request.data.langs.is_some() {
+        return handlers::generate_languages_handler(
+            request, auth, config, db_config, proxy, request_id,
+        )
+        .await
+        .map(GenerateResponse::Languages);
+    }
+    match OutputFormat::parse(request.data.format.as_deref()) {
+        Some(OutputFormat::Docx) => {
+            handlers::generate_docx_handler(request, auth, config, db_config, proxy, request_id)
+                .await
+                .map(GenerateResponse::Docx)
+        }
+        Some(OutputFormat::Html) => {
+            handlers::generate_html_handler(request, auth, config, db_config, proxy, request_id)
+                .await
+                .map(GenerateResponse::Html)
+        }
+        _ => handlers::generate_cv_handler(request, auth, config, db_config, proxy, request_id)
+            .await
+            .map(GenerateResponse::link),
+    }
+}
+
+/// POST /generate/batch — the CVs of several persons (or all) as one ZIP
+/// with a manifest of what failed
+#[post("/generate/batch", data = "<request>")]
+pub async fn generate_cv_batch(
+    request: Json<StandardRequest<handlers::BatchGenerateRequest>>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    db_config: &State<DatabaseConfig>,
+    proxy: proxy::ProxyInfo,
+) -> Result<crate::web::types::ZipResponse, Json<StandardErrorResponse>> {
+    handlers::generate_batch_handler(request, auth, config, db_config, proxy).await
+}
+
+/// POST /generate/async — queue a generation and return its job
+#[post("/generate/async", data = "<request>")]
+pub async fn generate_cv_async(
+    request: Json<StandardRequest<GenerateRequest>>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    db_config: &State<DatabaseConfig>,
+    proxy: proxy::ProxyInfo,
+) -> Result<Json<DataResponse<crate::core::jobs::GenerationJob>>, Json<StandardErrorResponse>> {
+    handlers::generate_async_handler(request, auth, config, db_config, proxy).await
+}
+
+/// GET /jobs/<id> — status of an asynchronous generation
+#[get("/jobs/<id>")]
+pub async fn get_generation_job(
+    id: String,
+    auth: AuthenticatedUser,
+    db_config: &State<DatabaseConfig>,
+) -> Result<Json<DataResponse<crate::core::jobs::GenerationJob>>, Json<StandardErrorResponse>> {
+    handlers::job_status_handler(id, auth, db_config).await
+}
+
+/// GET /jobs/<id>/result — the PDF of a finished asynchronous generation
+#[get("/jobs/<id>/result")]
+pub async fn get_generation_job_result(
+    id: String,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    db_config: &State<DatabaseConfig>,
+) -> Result<PdfResponse, Json<StandardErrorResponse>> {
+    handlers::job_result_handler(id, auth, config, db_config).await
+}
+
+/// POST /generate-preview-set — first-page PNG of one person in several
+/// templates, as JSON (base64 per template) or a ZIP
+#[post("/generate-preview-set", data = "<request>")]
+pub async fn generate_preview_set(
+    request: Json<StandardRequest<handlers::cv_handlers::PreviewSetRequest>>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    db_config: &State<DatabaseConfig>,
+) -> Result<handlers::cv_handlers::PreviewSetResponse, Json<StandardErrorResponse>> {
+    handlers::cv_handlers::preview_set_handler(request, auth, config, db_config).await
+}
+
+/// POST /requests/<id>/cancel — cancel a request sent with `X-Request-Id`
+#[post("/requests/<id>/cancel")]
+pub async fn cancel_request(
+    id: String,
+    auth: AuthenticatedUser,
+) -> Result<Json<ActionResponse>, Json<StandardErrorResponse>> {
+    handlers::cancel_request_handler(id, auth).await
+}
+
+/// GET /profiles/<name>/latest-pdf?lang=&template= → newest generated PDF for
+/// the person, served without recompiling (generated once if none exists yet)
+/// (cancellable with `X-Request-Id` while it generates)
+#[get("/profiles/<name>/latest-pdf?<query..>")]
+pub async fn get_latest_pdf(
+    name: String,
+    query: handlers::LatestPdfQuery,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    db_config: &State<DatabaseConfig>,
+    proxy: proxy::ProxyInfo,
+    request_id: ClientRequestId,
+) -> Result<PdfResponse, Json<StandardErrorResponse>> {
+    handlers::latest_pdf_handler(name, query, auth, config, db_config, proxy, request_id).await
+}
+
+/// GET /profiles/<name>/checklist → onboarding steps (personal info, photo,
+/// experiences, first generation) computed from the person's files
+#[get("/profiles/<name>/checklist")]
+pub async fn get_profile_checklist(
+    name: String,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+) -> Result<Json<DataResponse<handlers::OnboardingChecklist>>, Json<StandardErrorResponse>> {
+    handlers::checklist_handler(name, auth, config).await
+}
+
+/// GET /profiles/<name>/check?<compile> → integrity report (required files,
+/// TOML, Typst compile, images); `compile=false` skips the compile
+#[get("/profiles/<name>/check?<compile>")]
+pub async fn get_profile_integrity(
+    name: String,
+    compile: Option<bool>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+) -> Result<
+    Json<DataResponse<crate::core::profile_integrity::IntegrityReport>>,
+    Json<StandardErrorResponse>,
+> {
+    handlers::integrity_handler(name, compile, auth, config).await
+}
+
+/// GET /profiles/<name>/template-versions → template pins and the versions
+/// recent generations used
+#[get("/profiles/<name>/template-versions")]
+pub async fn get_profile_template_versions(
+    name: String,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+) -> Result<
+    Json<DataResponse<crate::core::template_versions::ProfileTemplateVersions>>,
+    Json<StandardErrorResponse>,
+> {
+    handlers::template_versions_handler(name, auth, config).await
+}
+
+/// GET /profiles/<name>/translations → per language whether it is an
+/// original, a translation, edited, or an untranslated copy
+#[get("/profiles/<name>/translations")]
+pub async fn get_profile_translations(
+    name: String,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+) -> Result<
+    Json<DataResponse<Vec<crate::core::translation_status::LanguageStatus>>>,
+    Json<StandardErrorResponse>,
+> {
+    handlers::translation_status_handler(name, auth, config).await
+}
+
+/// PUT /profiles/<name>/template-pin — pin a template to a version, or unpin
+#[put("/profiles/<name>/template-pin", data = "<request>")]
+pub async fn pin_profile_template(
+    name: String,
+    request: Json<crate::web::types::PinTemplateRequest>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+) -> Result<
+    Json<DataResponse<crate::core::template_versions::ProfileTemplateVersions>>,
+    Json<StandardErrorResponse>,
+> {
+    handlers::pin_template_handler(name, request, auth, config).await
+}
+
+/// GET /profiles/<name>/approval?<owner> → review state and history of the
+/// profile; `owner` names another member's profile (reviewers)
+#[get("/profiles/<name>/approval?<owner>")]
+pub async fn get_profile_approval(
+    name: String,
+    owner: Option<String>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    db_config: &State<DatabaseConfig>,
+) -> Result<
+    Json<DataResponse<crate::core::profile_approval::ProfileApproval>>,
+    Json<StandardErrorResponse>,
+> {
+    handlers::approval_handler(name, owner, auth, config, db_config).await
+}
+
+/// POST /profiles/<name>/approval/request-review — submit the profile for review
+#[post("/profiles/<name>/approval/request-review", data = "<request>")]
+pub async fn request_profile_review(
+    name: String,
+    request: Option<Json<crate::web::types::ApprovalRequest>>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    db_config: &State<DatabaseConfig>,
+) -> Result<
+    Json<DataResponse<crate::core::profile_approval::ProfileApproval>>,
+    Json<StandardErrorResponse>,
+> {
+    use crate::core::profile_approval::ApprovalAction;
+    let action = ApprovalAction::RequestReview;
+    handlers::approval_action_handler(name, None, action, request, auth, config, db_config).await
+}
+
+/// POST /profiles/<name>/approval/approve?<owner> — approve a profile in
+/// review (reviewers: the tenant's editors and the admin)
+#[post("/profiles/<name>/approval/approve?<owner>", data = "<request>")]
+pub async fn approve_profile(
+    name: String,
+    owner: Option<String>,
+    request: Option<Json<crate::web::types::ApprovalRequest>>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    db_config: &State<DatabaseConfig>,
+) -> Result<
+    Json<DataResponse<crate::core::profile_approval::ProfileApproval>>,
+    Json<StandardErrorResponse>,
+> {
+    use crate::core::profile_approval::ApprovalAction;
+    let action = ApprovalAction::Approve;
+    handlers::approval_action_handler(name, owner, action, request, auth, config, db_config).await
+}
+
+/// POST /profiles/<name>/approval/reject?<owner> — send a profile in review
+/// back to draft with a comment (reviewers)
+#[post("/profiles/<name>/approval/reject?<owner>", data = "<request>")]
+pub async fn reject_profile(
+    name: String,
+    owner: Option<String>,
+    request: Option<Json<crate::web::types::ApprovalRequest>>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    db_config: &State<DatabaseConfig>,
+) -> Result<
+    Json<DataResponse<crate::core::profile_approval::ProfileApproval>>,
+    Json<StandardErrorResponse>,
+> {
+    use crate::core::profile_approval::ApprovalAction;
+    let action = ApprovalAction::Reject;
+    handlers::approval_action_handler(name, owner, action, request, auth, config, db_config).await
+}
+
+/// GET /approvals/pending → profiles of the caller's tenant in review, with
+/// who requested it (reviewers)
+#[get("/approvals/pending")]
+pub async fn list_pending_reviews(
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    db_config: &State<DatabaseConfig>,
+) -> Result<
+    Json<DataResponse<Vec<crate::core::profile_approval::PendingReview>>>,
+    Json<StandardErrorResponse>,
+> {
+    handlers::pending_reviews_handler(auth, config, db_config).await
+}
+
+/// GET /profiles/<name>/comments?<path>&<resolved> → reviewer comments on the
+/// profile or one of its files; resolved ones only with `resolved=true`
+#[get("/profiles/<name>/comments?<path>&<resolved>")]
+pub async fn list_profile_comments(
+    name: String,
+    path: Option<String>,
+    resolved: Option<bool>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    db_config: &State<DatabaseConfig>,
+) -> Result<
+    Json<DataResponse<Vec<crate::core::profile_comments::ProfileComment>>>,
+    Json<StandardErrorResponse>,
+> {
+    handlers::list_comments_handler(name, path, resolved, auth, config, db_config).await
+}
+
+/// POST /profiles/<name>/comments — comment on a file, optionally a line range
+#[post("/profiles/<name>/comments", data = "<request>")]
+pub async fn create_profile_comment(
+    name: String,
+    request: Json<crate::core::profile_comments::NewComment>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    db_config: &State<DatabaseConfig>,
+) -> Result<
+    Json<DataResponse<crate::core::profile_comments::ProfileComment>>,
+    Json<StandardErrorResponse>,
+> {
+    handlers::create_comment_handler(name, request, auth, config, db_config).await
+}
+
+/// POST /profiles/<name>/comments/<id>/resolve — mark a comment resolved
+#[post("/profiles/<name>/comments/<id>/resolve")]
+pub async fn resolve_profile_comment(
+    name: String,
+    id: i64,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    db_config: &State<DatabaseConfig>,
+) -> Result<
+    Json<DataResponse<crate::core::profile_comments::ProfileComment>>,
+    Json<StandardErrorResponse>,
+> {
+    handlers::resolve_comment_handler(name, id, auth, config, db_config).await
+}
+
+/// GET /persons?<tags>&<attrs> → the user's persons with their tags and
+/// attributes, filtered by `tags=bench,senior` and `attrs=key:value,…`
+#[get("/persons?<tags>&<attrs>")]
+pub async fn list_persons(
+    tags: Option<String>,
+    attrs: Option<String>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    db_config: &State<DatabaseConfig>,
+) -> Result<Json<DataResponse<Vec<handlers::PersonSummary>>>, Json<StandardErrorResponse>> {
+    handlers::list_persons_handler(tags, attrs, auth, config, db_config).await
+}
+
+/// GET /profiles/<name>/tags → the person's tags and attributes
+#[get("/profiles/<name>/tags")]
+pub async fn get_person_tags(
+    name: String,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    db_config: &State<DatabaseConfig>,
+) -> Result<
+    Json<DataResponse<crate::core::person_tags::PersonTags>>,
+    Json<StandardErrorResponse>,
+> {
+    handlers::person_tags_handler(name, auth, config, db_config).await
+}
+
+/// PUT /profiles/<name>/tags — replace the person's tags and attributes
+#[put("/profiles/<name>/tags", data = "<request>")]
+pub async fn set_person_tags(
+    name: String,
+    request: Json<crate::core::person_tags::PersonTags>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    db_config: &State<DatabaseConfig>,
+) -> Result<
+    Json<DataResponse<crate::core::person_tags::PersonTags>>,
+    Json<StandardErrorResponse>,
+> {
+    handlers::set_person_tags_handler(name, request, auth, config, db_config).await
+}
+
+/// POST /sandbox/<path..> — validate a mutation and answer with a fake
+/// response; see [`sandbox`]
+#[post("/sandbox/<path..>", data = "<data>")]
+pub async fn sandbox_post(
+    path: std::path::PathBuf,
+    content_type: Option<&ContentType>,
+    data: rocket::Data<'_>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    proxy: proxy::ProxyInfo,
+) -> Result<Json<serde_json::Value>, Json<StandardErrorResponse>> {
+    sandbox::mutation(Method::Post, path, content_type, data, auth, config, proxy).await
+}
+
+/// PUT /sandbox/<path..>
+#[put("/sandbox/<path..>", data = "<data>")]
+pub async fn sandbox_put(
+    path: std::path::PathBuf,
+    content_type: Option<&ContentType>,
+    data: rocket::Data<'_>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    proxy: proxy::ProxyInfo,
+) -> Result<Json<serde_json::Value>, Json<StandardErrorResponse>> {
+    sandbox::mutation(Method::Put, path, content_type, data, auth, config, proxy).await
+}
+
+/// PATCH /sandbox/<path..>
+#[patch("/sandbox/<path..>", data = "<data>")]
+pub async fn sandbox_patch(
+    path: std::path::PathBuf,
+    content_type: Option<&ContentType>,
+    data: rocket::Data<'_>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    proxy: proxy::ProxyInfo,
+) -> Result<Json<serde_json::Value>, Json<StandardErrorResponse>> {
+    sandbox::mutation(Method::Patch, path, content_type, data, auth, config, proxy).await
+}
+
+/// DELETE /sandbox/<path..>
+#[delete("/sandbox/<path..>", data = "<data>")]
+pub async fn sandbox_delete(
+    path: std::path::PathBuf,
+    content_type: Option<&ContentType>,
+    data: rocket::Data<'_>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    proxy: proxy::ProxyInfo,
+) -> Result<Json<serde_json::Value>, Json<StandardErrorResponse>> {
+    sandbox::mutation(Method::Delete, path, content_type, data, auth, config, proxy).await
+}
+
+/// GET /sandbox/profiles/<name>/latest-pdf — a placeholder PDF, nothing is
+/// compiled or charged
+#[get("/sandbox/profiles/<name>/latest-pdf")]
+pub async fn sandbox_latest_pdf(
+    name: String,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+) -> Result<PdfResponse, Json<StandardErrorResponse>> {
+    sandbox::latest_pdf(name, auth, config).await
+}
+
+/// GET /sandbox/intake/email-address — a placeholder, no address is created
+#[get("/sandbox/intake/email-address")]
+pub async fn sandbox_intake_address(_auth: AuthenticatedUser) -> Json<DataResponse<IntakeAddress>> {
+    sandbox::intake_address()
+}
+
+/// GET /sandbox/calendar/feed — a placeholder, no feed token is created
+#[get("/sandbox/calendar/feed")]
+pub async fn sandbox_calendar_feed(
+    _auth: AuthenticatedUser,
+    proxy: proxy::ProxyInfo,
+) -> Json<DataResponse<CalendarFeed>> {
+    sandbox::calendar_feed(&proxy)
+}
+
+/// GET /sandbox/referral/my-link — the link, with no referral counted
+#[get("/sandbox/referral/my-link")]
+pub async fn sandbox_referral_link(auth: AuthenticatedUser) -> Json<ReferralLinkResponse> {
+    sandbox::referral_link(&auth)
+}
+
+/// GET /sandbox/outputs/<file..> — the placeholder PDF a sandbox generation
+/// links to
+#[get("/sandbox/outputs/<file..>")]
+pub async fn sandbox_output_file(file: PathBuf) -> PdfResponse {
+    sandbox::output_file(file)
+}
+
+/// GET /smart-lists → the user's saved person searches
+#[get("/smart-lists")]
+pub async fn list_smart_lists(
+    auth: AuthenticatedUser,
+    db_config: &State<DatabaseConfig>,
+) -> Result<
+    Json<DataResponse<Vec<crate::core::smart_lists::SmartList>>>,
+    Json<StandardErrorResponse>,
+> {
+    handlers::list_smart_lists_handler(auth, db_config).await
+}
+
+/// POST /smart-lists — save a person search under a name (editors)
+#[post("/smart-lists", data = "<request>")]
+pub async fn save_smart_list(
+    request: Json<handlers::SaveSmartListRequest>,
+    auth: AuthenticatedUser,
+    db_config: &State<DatabaseConfig>,
+) -> Result<
+    Json<DataResponse<crate::core::smart_lists::SmartList>>,
+    Json<StandardErrorResponse>,
+> {
+    handlers::save_smart_list_handler(request, auth, db_config).await
+}
+
+/// GET /smart-lists/<name>/persons → the persons matching the list now
+#[get("/smart-lists/<name>/persons")]
+pub async fn evaluate_smart_list(
+    name: String,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    db_config: &State<DatabaseConfig>,
+) -> Result<Json<DataResponse<handlers::SmartListResult>>, Json<StandardErrorResponse>> {
+    handlers::evaluate_smart_list_handler(name, auth, config, db_config).await
+}
+
+/// DELETE /smart-lists/<name> (editors)
+#[delete("/smart-lists/<name>")]
+pub async fn delete_smart_list(
+    name: String,
+    auth: AuthenticatedUser,
+    db_config: &State<DatabaseConfig>,
+) -> Result<
+    Json<DataResponse<crate::core::smart_lists::SmartList>>,
+    Json<StandardErrorResponse>,
+> {
+    handlers::delete_smart_list_handler(name, auth, db_config).await
+}
+
+/// PUT /profiles/<name>/watch — watch a profile, or change how the watch
+/// notifies (email, webhook)
+#[put("/profiles/<name>/watch", data = "<request>")]
+pub async fn watch_profile(
+    name: String,
+    request: Option<Json<crate::core::profile_watch::WatchOptions>>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    db_config: &State<DatabaseConfig>,
+) -> Result<
+    Json<DataResponse<crate::core::profile_watch::ProfileWatch>>,
+    Json<StandardErrorResponse>,
+> {
+    handlers::watch_profile_handler(name, request, auth, config, db_config).await
+}
+
+/// DELETE /profiles/<name>/watch — stop watching a profile
+#[delete("/profiles/<name>/watch")]
+pub async fn unwatch_profile(
+    name: String,
+    auth: AuthenticatedUser,
+    db_config: &State<DatabaseConfig>,
+) -> Result<Json<DataResponse<bool>>, Json<StandardErrorResponse>> {
+    handlers::unwatch_profile_handler(name, auth, db_config).await
+}
+
+/// GET /watches → profiles the caller watches
+#[get("/watches")]
+pub async fn list_watches(
+    auth: AuthenticatedUser,
+    db_config: &State<DatabaseConfig>,
+) -> Result<
+    Json<DataResponse<Vec<crate::core::profile_watch::ProfileWatch>>>,
+    Json<StandardErrorResponse>,
+> {
+    handlers::list_watches_handler(auth, db_config).await
+}
+
+/// GET /watches/events — changes to watched profiles as server-sent events
+#[get("/watches/events")]
+pub fn watch_events(auth: AuthenticatedUser) -> EventStream<impl Stream<Item = Event>> {
+    handlers::watch_events_handler(auth)
 }
 
 #[post("/create", data = "<request>")]
@@ -208,14 +864,196 @@ pub async fn upload_picture(
     handlers::upload_picture_handler(upload, auth, config, db_config).await
 }
 
+/// GET /profiles/<name>/picture?w=200&format=webp → resized/re-encoded profile
+/// picture (cached on disk). Omit `format` to negotiate WebP via `Accept`.
+#[get("/profiles/<name>/picture?<w>&<format>")]
+pub async fn get_profile_picture(
+    name: String,
+    w: Option<u32>,
+    format: Option<String>,
+    accept: Option<&rocket::http::Accept>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+) -> Result<ImageResponse, Json<StandardErrorResponse>> {
+    handlers::get_picture_handler(name, w, format, accept, auth, config).await
+}
+
+/// POST /profiles/<name>/assets/<slot> → multipart upload (field `file`) of
+/// the image for a template-declared slot such as `signature`.
+#[post("/profiles/<name>/assets/<slot>", data = "<upload>")]
+pub async fn upload_profile_asset(
+    name: String,
+    slot: String,
+    upload: Form<crate::web::types::AssetUploadForm<'_>>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    templates: &State<SharedTemplateEngine>,
+) -> Result<Json<ActionResponse>, Json<StandardErrorResponse>> {
+    handlers::asset_handlers::upload_asset_handler(name, slot, upload, auth, config, templates)
+        .await
+}
+
+/// DELETE /profiles/<name>/assets/<slot> → remove the slot's image.
+#[delete("/profiles/<name>/assets/<slot>")]
+pub async fn delete_profile_asset(
+    name: String,
+    slot: String,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    templates: &State<SharedTemplateEngine>,
+) -> Result<Json<ActionResponse>, Json<StandardErrorResponse>> {
+    handlers::asset_handlers::delete_asset_handler(name, slot, auth, config, templates).await
+}
+
+/// GET /profiles/<name>/client-logos → keys of the profile's client logos.
+#[get("/profiles/<name>/client-logos")]
+pub async fn list_client_logos(
+    name: String,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+) -> Result<Json<DataResponse<Vec<String>>>, Json<StandardErrorResponse>> {
+    handlers::asset_handlers::list_client_logos_handler(name, auth, config).await
+}
+
+/// POST /profiles/<name>/client-logos/<key> → multipart upload (field `file`)
+/// of a client logo that experiences show with `logo: "<key>"`.
+#[post("/profiles/<name>/client-logos/<key>", data = "<upload>")]
+pub async fn upload_client_logo(
+    name: String,
+    key: String,
+    upload: Form<crate::web::types::AssetUploadForm<'_>>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+) -> Result<Json<ActionResponse>, Json<StandardErrorResponse>> {
+    handlers::asset_handlers::upload_client_logo_handler(name, key, upload, auth, config).await
+}
+
+/// DELETE /profiles/<name>/client-logos/<key> → remove a client logo.
+#[delete("/profiles/<name>/client-logos/<key>")]
+pub async fn delete_client_logo(
+    name: String,
+    key: String,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+) -> Result<Json<ActionResponse>, Json<StandardErrorResponse>> {
+    handlers::asset_handlers::delete_client_logo_handler(name, key, auth, config).await
+}
+
 #[post("/cv/upload", data = "<upload>")]
 pub async fn upload_and_convert_cv(
     upload: Form<CvUploadForm<'_>>,
     auth: AuthenticatedUser,
     config: &State<ServerConfig>,
     cv_service_url: &State<String>,
+    db_config: &State<DatabaseConfig>,
 ) -> Result<Json<ActionResponse>, Json<StandardErrorResponse>> {
-    upload_and_convert_cv_handler(upload, auth, config, cv_service_url).await
+    upload_and_convert_cv_handler(upload, auth, config, cv_service_url, db_config).await
+}
+
+/// POST /cv/upload/<id> — convert a complete resumable upload
+#[post("/cv/upload/<id>")]
+pub async fn convert_upload_session(
+    id: String,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    cv_service_url: &State<String>,
+    db_config: &State<DatabaseConfig>,
+) -> Result<Json<ActionResponse>, Json<StandardErrorResponse>> {
+    handlers::convert_upload_session_handler(id, auth, config, cv_service_url, db_config).await
+}
+
+/// POST /cv/upload/presigned/<id> — convert a file sent to object storage
+#[post("/cv/upload/presigned/<id>")]
+pub async fn convert_presigned_upload(
+    id: String,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    cv_service_url: &State<String>,
+    db_config: &State<DatabaseConfig>,
+) -> Result<Json<ActionResponse>, Json<StandardErrorResponse>> {
+    handlers::convert_presigned_upload_handler(id, auth, config, cv_service_url, db_config).await
+}
+
+/// GET /imports/failed — conversions that failed, kept for retrying
+#[get("/imports/failed")]
+pub async fn list_failed_imports(
+    auth: AuthenticatedUser,
+    db_config: &State<DatabaseConfig>,
+) -> Result<Json<DataResponse<Vec<FailedImport>>>, Json<StandardErrorResponse>> {
+    handlers::list_failed_imports_handler(auth, db_config).await
+}
+
+/// POST /imports/failed/<id>/retry — convert a kept upload again
+#[post("/imports/failed/<id>/retry")]
+pub async fn retry_failed_import(
+    id: String,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    cv_service_url: &State<String>,
+    db_config: &State<DatabaseConfig>,
+) -> Result<Json<ActionResponse>, Json<StandardErrorResponse>> {
+    handlers::retry_failed_import_handler(id, auth, config, cv_service_url, db_config).await
+}
+
+/// POST /uploads — open a resumable upload
+#[post("/uploads", data = "<request>")]
+pub async fn create_upload(
+    request: Json<CreateUploadRequest>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+) -> Result<Json<DataResponse<UploadProgress>>, Json<StandardErrorResponse>> {
+    handlers::upload_handlers::create_upload_handler(request, auth, config).await
+}
+
+/// POST /uploads/presigned — pre-signed URL to send a CV to object storage
+#[post("/uploads/presigned", data = "<request>")]
+pub async fn presign_upload(
+    request: Json<CreateUploadRequest>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+) -> Result<
+    Json<DataResponse<crate::core::presigned_uploads::PresignedUpload>>,
+    Json<StandardErrorResponse>,
+> {
+    handlers::upload_handlers::presign_upload_handler(request, auth, config).await
+}
+
+/// PATCH /uploads/<id> — append bytes at the `Upload-Offset` header
+#[patch("/uploads/<id>", data = "<data>")]
+pub async fn upload_chunk(
+    id: String,
+    data: rocket::Data<'_>,
+    offset: UploadOffset,
+    auth: AuthenticatedUser,
+) -> Result<Json<DataResponse<UploadProgress>>, Json<StandardErrorResponse>> {
+    handlers::upload_handlers::upload_chunk_handler(id, data, offset, auth).await
+}
+
+/// GET /uploads/<id> — bytes received so far
+#[get("/uploads/<id>")]
+pub async fn upload_progress(
+    id: String,
+    auth: AuthenticatedUser,
+) -> Result<Json<DataResponse<UploadProgress>>, Json<StandardErrorResponse>> {
+    handlers::upload_handlers::upload_progress_handler(id, auth).await
+}
+
+/// GET /uploads/<id>/events — progress as server-sent events
+#[get("/uploads/<id>/events")]
+pub async fn upload_events(
+    id: String,
+    auth: AuthenticatedUser,
+) -> Result<EventStream<impl Stream<Item = Event>>, Json<StandardErrorResponse>> {
+    handlers::upload_handlers::upload_events_handler(id, auth).await
+}
+
+/// DELETE /uploads/<id> — abandon an upload
+#[delete("/uploads/<id>")]
+pub async fn cancel_upload(
+    id: String,
+    auth: AuthenticatedUser,
+) -> Result<Json<DataResponse<bool>>, Json<StandardErrorResponse>> {
+    handlers::upload_handlers::cancel_upload_handler(id, auth).await
 }
 
 /// POST /cv/import-text
@@ -227,18 +1065,130 @@ pub async fn import_cv_from_text(
     auth: AuthenticatedUser,
     config: &State<ServerConfig>,
     cv_service_url: &State<String>,
+    db_config: &State<DatabaseConfig>,
 ) -> Result<Json<ActionResponse>, Json<StandardErrorResponse>> {
-    import_text_cv_handler(request, auth, config, cv_service_url).await
+    import_text_cv_handler(request, auth, config, cv_service_url, db_config).await
 }
 
-#[get("/templates")]
-pub async fn get_templates(config: &State<ServerConfig>) -> Json<DataResponse<Vec<TemplateInfo>>> {
-    handlers::get_templates_handler(config).await
+/// POST /profiles/from-notes
+/// Create a profile from free-form intake notes (dictated or typed during a call).
+/// Request body: { "notes": "...", "profile_name": "optional-name" }
+#[post("/profiles/from-notes", data = "<request>")]
+pub async fn create_profile_from_notes(
+    request: Json<StandardRequest<ProfileFromNotesRequest>>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    cv_service_url: &State<String>,
+    db_config: &State<DatabaseConfig>,
+) -> Result<Json<DataResponse<ProfileFromNotesResult>>, Json<StandardErrorResponse>> {
+    profile_from_notes_handler(request, auth, config, cv_service_url, db_config).await
+}
+
+/// GET /templates?lang=fr — installed templates, descriptions in `lang` when
+/// the manifest translates them.
+#[get("/templates?<lang>")]
+pub async fn get_templates(
+    lang: Option<String>,
+    auth: OptionalAuth,
+    config: &State<ServerConfig>,
+    templates: &State<SharedTemplateEngine>,
+) -> Json<DataResponse<Vec<TemplateInfo>>> {
+    handlers::get_templates_handler(lang, auth, config, templates).await
 }
 
+/// POST /templates/upload → multipart upload, field name `file`: a ZIP of a
+/// template directory, installed for the caller's tenant (editors only)
+#[post("/templates/upload", data = "<upload>")]
+pub async fn upload_template(
+    upload: rocket::form::Form<crate::web::types::TemplateUploadForm<'_>>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    templates: &State<SharedTemplateEngine>,
+) -> Result<Json<DataResponse<String>>, Json<StandardErrorResponse>> {
+    handlers::upload_template_handler(upload, auth, config, templates).await
+}
+
+/// POST /templates/<id>/validate?lang=fr — dry compile against the sample
+/// person: Typst diagnostics, missing variables and fonts (editors only)
+#[post("/templates/<id>/validate?<lang>")]
+pub async fn validate_template(
+    id: String,
+    lang: Option<String>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    templates: &State<SharedTemplateEngine>,
+) -> Result<
+    Json<DataResponse<crate::core::template_health::TemplateValidation>>,
+    Json<StandardErrorResponse>,
+> {
+    handlers::validate_template_handler(id, lang, auth, config, templates).await
+}
+
+/// GET /templates/<id>/preview?lang=fr — first page of the template's
+/// sample person, as a PNG
+#[get("/templates/<id>/preview?<lang>")]
+pub async fn get_template_preview(
+    id: String,
+    lang: Option<String>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    templates: &State<SharedTemplateEngine>,
+) -> Result<(ContentType, Vec<u8>), Json<StandardErrorResponse>> {
+    handlers::template_preview_handler(id, lang, auth, config, templates).await
+}
+
+/// GET /templates/catalog — curated templates from the remote registry (admin only)
+#[get("/templates/catalog")]
+pub async fn get_template_catalog(
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+) -> Result<
+    Json<DataResponse<Vec<handlers::catalog_handlers::CatalogItem>>>,
+    Json<StandardErrorResponse>,
+> {
+    handlers::catalog_handlers::catalog_handler(auth, config).await
+}
+
+/// POST /templates/install/<id> — verify and install a registry template (admin only)
+#[post("/templates/install/<id>")]
+pub async fn install_template(
+    id: String,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    templates: &State<SharedTemplateEngine>,
+) -> Result<Json<ActionResponse>, Json<StandardErrorResponse>> {
+    handlers::catalog_handlers::install_template_handler(id, auth, config, templates).await
+}
+
+/// GET /admin/templates/state — loaded templates with their versions and
+/// health (admin only)
+#[get("/admin/templates/state")]
+pub async fn admin_template_state(
+    auth: AuthenticatedUser,
+    templates: &State<SharedTemplateEngine>,
+) -> Result<
+    Json<DataResponse<handlers::catalog_handlers::TemplateState>>,
+    Json<StandardErrorResponse>,
+> {
+    handlers::catalog_handlers::template_state_handler(auth, templates).await
+}
+
+/// GET /me — the caller's identity and own profile; creates nothing.
 #[get("/me")]
-pub async fn get_current_user(auth: AuthenticatedUser) -> Json<DataResponse<UserInfo>> {
-    handlers::get_current_user_handler(auth).await
+pub async fn get_current_user(
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+) -> Json<DataResponse<UserInfo>> {
+    handlers::get_current_user_handler(auth, config).await
+}
+
+/// POST /me/provision — create the caller's own profile on first login
+#[post("/me/provision")]
+pub async fn provision_current_user(
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+) -> Json<DataResponse<UserInfo>> {
+    handlers::provision_current_user_handler(auth, config).await
 }
 
 #[get("/me", rank = 2)]
@@ -289,9 +1239,23 @@ pub async fn update_preferences(
     Ok(Json(serde_json::json!({ "success": true })))
 }
 
+/// GET /health — liveness; free space / inodes of the volumes when authenticated
 #[get("/health")]
-pub async fn health(auth: OptionalAuth) -> Json<TextResponse> {
-    handlers::health_handler(auth).await
+pub async fn health(
+    auth: OptionalAuth,
+    config: &State<ServerConfig>,
+) -> Json<DataResponse<handlers::system_handlers::HealthStatus>> {
+    handlers::health_handler(auth, config).await
+}
+
+/// GET /capabilities — subsystems enabled on this deployment, limits and the
+/// template inventory
+#[get("/capabilities")]
+pub async fn get_capabilities(
+    subsystems: &State<handlers::system_handlers::Subsystems>,
+    templates: &State<SharedTemplateEngine>,
+) -> Json<DataResponse<handlers::system_handlers::Capabilities>> {
+    handlers::capabilities_handler(subsystems, templates).await
 }
 
 #[get("/files/content?<path>")]
@@ -389,6 +1353,17 @@ pub async fn delete_brand_logo(
 
 // ── CV form-data routes ───────────────────────────────────────────────────────
 
+/// GET /profiles/compare?names=a,b,c
+/// Skills, years of experience, languages and availability side by side.
+#[get("/profiles/compare?<names>")]
+pub async fn compare_profiles(
+    names: String,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+) -> Result<Json<DataResponse<ProfileComparison>>, Json<StandardErrorResponse>> {
+    handlers::compare_profiles_handler(names, auth, config).await
+}
+
 /// GET /profiles/:name/cv-data?lang=en
 /// Returns a unified CvFormData JSON parsed from cv_params.toml + experiences_{lang}.typ.
 #[get("/profiles/<name>/cv-data?<lang>")]
@@ -397,32 +1372,169 @@ pub async fn get_cv_data(
     lang: Option<String>,
     auth: AuthenticatedUser,
     config: &State<ServerConfig>,
-) -> Result<Json<CvFormData>, Json<StandardErrorResponse>> {
-    get_cv_data_handler(name, lang, auth, config).await
+) -> Result<Json<CvFormData>, Json<StandardErrorResponse>> {
+    get_cv_data_handler(name, lang, auth, config).await
+}
+
+/// PUT /profiles/:name/cv-data?lang=en
+/// Accepts CvFormData JSON, regenerates cv_params.toml and experiences_{lang}.typ.
+#[put("/profiles/<name>/cv-data?<lang>", data = "<request>")]
+pub async fn put_cv_data(
+    name: String,
+    lang: Option<String>,
+    request: Json<CvFormData>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    db_config: &State<DatabaseConfig>,
+) -> Result<Json<serde_json::Value>, Json<StandardErrorResponse>> {
+    put_cv_data_handler(name, lang, request, auth, config, db_config).await
+}
+
+/// POST /profiles/:name/cv-data/upgrade-schema
+/// Rewrites a legacy cv_params.toml layout in the current schema, keeping
+/// the original as cv_params.toml.bak.
+#[post("/profiles/<name>/cv-data/upgrade-schema")]
+pub async fn upgrade_cv_schema(
+    name: String,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    db_config: &State<DatabaseConfig>,
+) -> Result<Json<serde_json::Value>, Json<StandardErrorResponse>> {
+    upgrade_cv_schema_handler(name, auth, config, db_config).await
+}
+
+/// GET /profiles/:name/cv-data/:section?lang=en
+/// Returns one section (personal, links, skills, education, languages, styling,
+/// experiences) of the CV form data.
+#[get("/profiles/<name>/cv-data/<section>?<lang>")]
+pub async fn get_cv_section(
+    name: String,
+    section: String,
+    lang: Option<String>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+) -> Result<Json<serde_json::Value>, Json<StandardErrorResponse>> {
+    get_cv_section_handler(name, section, lang, auth, config).await
+}
+
+/// PUT /profiles/:name/cv-data/:section?lang=en
+/// Rewrites only that section; the rest of cv_params.toml is preserved.
+#[put("/profiles/<name>/cv-data/<section>?<lang>", data = "<request>")]
+pub async fn put_cv_section(
+    name: String,
+    section: String,
+    lang: Option<String>,
+    request: Json<serde_json::Value>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    db_config: &State<DatabaseConfig>,
+) -> Result<Json<serde_json::Value>, Json<StandardErrorResponse>> {
+    put_cv_section_handler(name, section, lang, request, auth, config, db_config).await
+}
+
+/// GET /files/tree?include=images,pdfs → tenant file tree; every file node
+/// carries an `access` tag (editable / downloadable / readonly).
+/// POST /files/draft — autosave unsaved editor content (per user and file)
+#[post("/files/draft", data = "<request>")]
+pub async fn save_file_draft(
+    request: Json<StandardRequest<SaveFileRequest>>,
+    auth: AuthenticatedUser,
+    db_config: &State<DatabaseConfig>,
+) -> Result<
+    Json<DataResponse<crate::core::file_drafts::DraftView>>,
+    Json<StandardErrorResponse>,
+> {
+    file_handlers::save_file_draft_handler(request, auth, db_config).await
+}
+
+/// GET /files/draft?<path> — the autosaved draft of a file
+#[get("/files/draft?<path>")]
+pub async fn get_file_draft(
+    path: String,
+    auth: AuthenticatedUser,
+    db_config: &State<DatabaseConfig>,
+) -> Result<
+    Json<DataResponse<crate::core::file_drafts::DraftView>>,
+    Json<StandardErrorResponse>,
+> {
+    file_handlers::get_file_draft_handler(path, auth, db_config).await
+}
+
+/// DELETE /files/draft?<path> — discard the autosaved draft of a file
+#[delete("/files/draft?<path>")]
+pub async fn discard_file_draft(
+    path: String,
+    auth: AuthenticatedUser,
+    db_config: &State<DatabaseConfig>,
+) -> Result<Json<ActionResponse>, Json<StandardErrorResponse>> {
+    file_handlers::discard_file_draft_handler(path, auth, db_config).await
+}
+
+#[get("/files/tree?<include>")]
+pub async fn get_tenant_files(
+    include: Option<String>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+) -> Result<Json<serde_json::Value>, Status> {
+    // Changed return type
+    file_handlers::get_tenant_files_handler(include, auth, config).await
+}
+
+/// GET /files/download?path= → image or PDF from the tenant directory
+#[get("/files/download?<path>")]
+pub async fn download_tenant_file(
+    path: String,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    db_config: &State<DatabaseConfig>,
+) -> Result<(rocket::http::ContentType, Vec<u8>), Status> {
+    file_handlers::download_tenant_file_handler(path, auth, config, db_config).await
+}
+
+/// POST /cv/optimize — ATS-optimize a profile against a job posting
+/// (`conversation_id` is echoed back)
+#[post("/cv/optimize", data = "<request>")]
+pub async fn cv_optimize(
+    request: Json<StandardRequest<OptimizeCvRequest>>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    db_config: &State<DatabaseConfig>,
+    cv_service_url: &State<String>,
+    request_id: ClientRequestId,
+) -> Result<Json<DataResponse<OptimizeResponse>>, Json<StandardErrorResponse>> {
+    optimize_cv_handler(request, auth, config, db_config, cv_service_url, request_id).await
 }
 
-/// PUT /profiles/:name/cv-data?lang=en
-/// Accepts CvFormData JSON, regenerates cv_params.toml and experiences_{lang}.typ.
-#[put("/profiles/<name>/cv-data?<lang>", data = "<request>")]
-pub async fn put_cv_data(
-    name: String,
-    lang: Option<String>,
-    request: Json<CvFormData>,
+/// POST /cv/optimize/stream — `/cv/optimize` as server-sent events, relaying
+/// the analysis while it is written
+#[post("/cv/optimize/stream", data = "<request>")]
+pub async fn cv_optimize_stream(
+    request: Json<StandardRequest<OptimizeCvRequest>>,
     auth: AuthenticatedUser,
     config: &State<ServerConfig>,
-) -> Result<Json<serde_json::Value>, Json<StandardErrorResponse>> {
-    put_cv_data_handler(name, lang, request, auth, config).await
+    db_config: &State<DatabaseConfig>,
+    cv_service_url: &State<String>,
+    request_id: ClientRequestId,
+) -> Result<EventStream<impl Stream<Item = Event>>, Json<StandardErrorResponse>> {
+    handlers::optimize_cv_stream_handler(request, auth, config, db_config, cv_service_url, request_id)
+        .await
 }
 
-#[get("/files/tree")]
-pub async fn get_tenant_files(
+/// POST /cv/translate — translate a profile and save the result into it
+/// (`conversation_id` is echoed back)
+#[post("/cv/translate", data = "<request>")]
+pub async fn cv_translate(
+    request: Json<StandardRequest<TranslateCvRequest>>,
     auth: AuthenticatedUser,
     config: &State<ServerConfig>,
-) -> Result<Json<serde_json::Value>, Status> {
-    // Changed return type
-    file_handlers::get_tenant_files_handler(auth, config).await
+    db_config: &State<DatabaseConfig>,
+    cv_service_url: &State<String>,
+    request_id: ClientRequestId,
+) -> Result<Json<DataResponse<TranslateResponse>>, Json<StandardErrorResponse>> {
+    translate_cv_handler(request, auth, config, db_config, cv_service_url, request_id).await
 }
 
+/// POST /optimize — legacy path of `/cv/optimize`
 #[post("/optimize", data = "<request>")]
 pub async fn optimize_cv(
     request: Json<StandardRequest<OptimizeCvRequest>>,
@@ -430,8 +1542,9 @@ pub async fn optimize_cv(
     config: &State<ServerConfig>,
     db_config: &State<DatabaseConfig>,
     cv_service_url: &State<String>,
+    request_id: ClientRequestId,
 ) -> Result<Json<DataResponse<OptimizeResponse>>, Json<StandardErrorResponse>> {
-    optimize_cv_handler(request, auth, config, db_config, cv_service_url).await
+    optimize_cv_handler(request, auth, config, db_config, cv_service_url, request_id).await
 }
 
 /// Optimize the CV with ATS keyword injection **and** immediately compile + stream the PDF.
@@ -443,8 +1556,10 @@ pub async fn optimize_and_generate(
     config: &State<ServerConfig>,
     db_config: &State<DatabaseConfig>,
     cv_service_url: &State<String>,
+    proxy: proxy::ProxyInfo,
+    request_id: ClientRequestId,
 ) -> Result<Json<GeneratePdfResponse>, Json<StandardErrorResponse>> {
-    optimize_and_generate_handler(request, auth, config, db_config, cv_service_url).await
+    optimize_and_generate_handler(request, auth, config, db_config, cv_service_url, proxy, request_id).await
 }
 
 /// Save an optimized CV under a new profile name.
@@ -459,6 +1574,7 @@ pub async fn save_optimized_cv(
     save_optimized_handler(request, auth, config).await
 }
 
+/// POST /translate — legacy path of `/cv/translate`
 #[post("/translate", data = "<request>")]
 pub async fn translate_cv(
     request: Json<StandardRequest<TranslateCvRequest>>,
@@ -466,8 +1582,9 @@ pub async fn translate_cv(
     config: &State<ServerConfig>,
     db_config: &State<DatabaseConfig>,
     cv_service_url: &State<String>,
+    request_id: ClientRequestId,
 ) -> Result<Json<DataResponse<TranslateResponse>>, Json<StandardErrorResponse>> {
-    translate_cv_handler(request, auth, config, db_config, cv_service_url).await
+    translate_cv_handler(request, auth, config, db_config, cv_service_url, request_id).await
 }
 
 /// POST /cover-letter — generate a cover letter from CV data + job description.
@@ -479,8 +1596,9 @@ pub async fn generate_cover_letter(
     config: &State<ServerConfig>,
     db_config: &State<DatabaseConfig>,
     cv_service_url: &State<String>,
+    request_id: ClientRequestId,
 ) -> Result<Json<DataResponse<CoverLetterResult>>, Json<StandardErrorResponse>> {
-    cover_letter_handler(request, auth, config, db_config, cv_service_url).await
+    cover_letter_handler(request, auth, config, db_config, cv_service_url, request_id).await
 }
 
 /// POST /cover-letter/export — export a cover letter text as .docx (no credit cost)
@@ -562,8 +1680,7 @@ pub async fn admin_announce_template(
     auth: AuthenticatedUser,
     db_config: &State<DatabaseConfig>,
 ) -> Result<Json<serde_json::Value>, Json<StandardErrorResponse>> {
-    const ADMIN_EMAIL: &str = "mohamed.bennekrouf@gmail.com";
-    if auth.email().to_lowercase() != ADMIN_EMAIL {
+    if !crate::web::handlers::common::is_admin(&auth) {
         return Err(Json(crate::web::types::StandardErrorResponse::new(
             "Unauthorized".to_string(),
             "UNAUTHORIZED".to_string(),
@@ -750,8 +1867,9 @@ pub async fn generate_portfolio(
     config: &State<ServerConfig>,
     db_config: &State<DatabaseConfig>,
     cv_service_url: &State<String>,
+    proxy: proxy::ProxyInfo,
 ) -> Result<Json<GeneratePdfResponse>, Json<StandardErrorResponse>> {
-    generate_portfolio_handler(request, auth, config, db_config, cv_service_url).await
+    generate_portfolio_handler(request, auth, config, db_config, cv_service_url, proxy).await
 }
 
 /// GET /referral/my-link — return the authenticated user's referral link and stats
@@ -763,6 +1881,460 @@ pub async fn get_my_referral_link(
     get_referral_link_handler(auth, db_config).await
 }
 
+/// GET /intake/email-address — the caller's private CV intake address
+#[get("/intake/email-address")]
+pub async fn get_intake_address(
+    auth: AuthenticatedUser,
+    db_config: &State<DatabaseConfig>,
+) -> Result<Json<DataResponse<IntakeAddress>>, Json<StandardErrorResponse>> {
+    get_intake_address_handler(auth, db_config).await
+}
+
+/// POST /intake/email-address/rotate — replace the intake address
+#[post("/intake/email-address/rotate")]
+pub async fn rotate_intake_address(
+    auth: AuthenticatedUser,
+    db_config: &State<DatabaseConfig>,
+) -> Result<Json<DataResponse<IntakeAddress>>, Json<StandardErrorResponse>> {
+    rotate_intake_address_handler(auth, db_config).await
+}
+
+/// POST /intake/email — inbound mail webhook (X-Inbound-Secret required)
+#[post("/intake/email", data = "<email>")]
+pub async fn inbound_email(
+    _secret: InboundSecret,
+    email: rocket::Data<'_>,
+    config: &State<ServerConfig>,
+    db_config: &State<DatabaseConfig>,
+    cv_service_url: &State<String>,
+) -> Result<Json<ActionResponse>, Json<StandardErrorResponse>> {
+    inbound_email_handler(email, config, db_config, cv_service_url).await
+}
+
+/// GET /calendar/feed — the caller's profile-task calendar subscription URL
+#[get("/calendar/feed")]
+pub async fn get_calendar_feed(
+    auth: AuthenticatedUser,
+    db_config: &State<DatabaseConfig>,
+    proxy: proxy::ProxyInfo,
+) -> Result<Json<DataResponse<CalendarFeed>>, Json<StandardErrorResponse>> {
+    get_calendar_feed_handler(auth, db_config, proxy).await
+}
+
+/// GET /calendar/<token>/tasks.ics — iCal feed (token in the URL, no bearer auth)
+#[get("/calendar/<token>/tasks.ics")]
+pub async fn get_calendar_ics(
+    token: String,
+    config: &State<ServerConfig>,
+    db_config: &State<DatabaseConfig>,
+) -> Result<(ContentType, String), Status> {
+    calendar_ics_handler(token, config, db_config).await
+}
+
+/// GET /conversations — the caller's recorded conversations, most recent first
+#[get("/conversations?<limit>")]
+pub async fn list_conversations(
+    limit: Option<i64>,
+    auth: AuthenticatedUser,
+    db_config: &State<DatabaseConfig>,
+) -> Result<Json<DataResponse<Vec<ConversationInfo>>>, Json<StandardErrorResponse>> {
+    list_conversations_handler(limit, auth, db_config).await
+}
+
+/// GET /conversations/<id> — request/response summaries of one conversation
+#[get("/conversations/<conversation_id>?<limit>")]
+pub async fn get_conversation(
+    conversation_id: String,
+    limit: Option<i64>,
+    auth: AuthenticatedUser,
+    db_config: &State<DatabaseConfig>,
+) -> Result<Json<DataResponse<Vec<ConversationTurn>>>, Json<StandardErrorResponse>> {
+    get_conversation_handler(conversation_id, limit, auth, db_config).await
+}
+
+/// DELETE /conversations/<id> — forget a conversation
+#[delete("/conversations/<conversation_id>")]
+pub async fn delete_conversation(
+    conversation_id: String,
+    auth: AuthenticatedUser,
+    db_config: &State<DatabaseConfig>,
+) -> Result<Json<ActionResponse>, Json<StandardErrorResponse>> {
+    delete_conversation_handler(conversation_id, auth, db_config).await
+}
+
+/// GET /admin/service-usage — cv-import calls aggregated per tenant and endpoint (admin only)
+#[get("/admin/service-usage?<from>&<to>&<tenant>")]
+pub async fn admin_service_usage(
+    from: Option<String>,
+    to: Option<String>,
+    tenant: Option<String>,
+    auth: AuthenticatedUser,
+    db_config: &State<DatabaseConfig>,
+) -> Result<Json<DataResponse<Vec<UsageSummary>>>, Json<StandardErrorResponse>> {
+    service_usage_handler(from, to, tenant, auth, db_config).await
+}
+
+/// GET /admin/service-usage/export — per-call CSV for chargeback (admin only)
+#[get("/admin/service-usage/export?<from>&<to>&<tenant>")]
+pub async fn admin_service_usage_export(
+    from: Option<String>,
+    to: Option<String>,
+    tenant: Option<String>,
+    auth: AuthenticatedUser,
+    db_config: &State<DatabaseConfig>,
+) -> Result<(ContentType, String), Json<StandardErrorResponse>> {
+    service_usage_csv_handler(from, to, tenant, auth, db_config).await
+}
+
+/// GET /usage — AI budgets of the caller's tenant and this month's use
+#[get("/usage")]
+pub async fn tenant_usage(
+    auth: AuthenticatedUser,
+    db_config: &State<DatabaseConfig>,
+) -> Result<Json<DataResponse<Vec<BudgetStatus>>>, Json<StandardErrorResponse>> {
+    handlers::usage_handlers::tenant_usage_handler(auth, db_config).await
+}
+
+/// PUT /admin/ai-budget — set a tenant's monthly budget for an AI feature (admin only)
+#[put("/admin/ai-budget", data = "<request>")]
+pub async fn admin_set_ai_budget(
+    request: Json<crate::web::types::AiBudgetRequest>,
+    auth: AuthenticatedUser,
+    db_config: &State<DatabaseConfig>,
+) -> Result<Json<ActionResponse>, Json<StandardErrorResponse>> {
+    handlers::usage_handlers::set_ai_budget_handler(request, auth, db_config).await
+}
+
+/// GET /audit/report — signed PDF of tenant activity over a date range
+#[get("/audit/report?<from>&<to>&<tenant>")]
+pub async fn audit_report(
+    from: Option<String>,
+    to: Option<String>,
+    tenant: Option<String>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    db_config: &State<DatabaseConfig>,
+) -> Result<PdfResponse, Json<StandardErrorResponse>> {
+    handlers::audit_handlers::audit_report_handler(from, to, tenant, auth, config, db_config)
+        .await
+}
+
+/// GET /secrets — names, kinds and dates of the tenant's secrets (editors)
+#[get("/secrets")]
+pub async fn list_secrets(
+    auth: AuthenticatedUser,
+    secret_store: &State<SecretStore>,
+    db_config: &State<DatabaseConfig>,
+) -> Result<
+    Json<DataResponse<Vec<crate::core::secrets::SecretInfo>>>,
+    Json<StandardErrorResponse>,
+> {
+    handlers::secret_handlers::list_secrets_handler(auth, secret_store, db_config).await
+}
+
+/// PUT /secrets/<name> — create or replace an encrypted secret (editors)
+#[put("/secrets/<name>", data = "<request>")]
+pub async fn put_secret(
+    name: String,
+    request: Json<crate::web::types::PutSecretRequest>,
+    auth: AuthenticatedUser,
+    secret_store: &State<SecretStore>,
+    db_config: &State<DatabaseConfig>,
+) -> Result<Json<ActionResponse>, Json<StandardErrorResponse>> {
+    handlers::secret_handlers::put_secret_handler(name, request, auth, secret_store, db_config)
+        .await
+}
+
+/// DELETE /secrets/<name> (editors)
+#[delete("/secrets/<name>")]
+pub async fn delete_secret(
+    name: String,
+    auth: AuthenticatedUser,
+    secret_store: &State<SecretStore>,
+    db_config: &State<DatabaseConfig>,
+) -> Result<Json<ActionResponse>, Json<StandardErrorResponse>> {
+    handlers::secret_handlers::delete_secret_handler(name, auth, secret_store, db_config).await
+}
+
+/// PUT /admin/roles — grant a user the member or editor role (admin only)
+#[put("/admin/roles", data = "<request>")]
+pub async fn admin_set_role(
+    request: Json<crate::web::types::SetMemberRoleRequest>,
+    auth: AuthenticatedUser,
+    db_config: &State<DatabaseConfig>,
+) -> Result<Json<ActionResponse>, Json<StandardErrorResponse>> {
+    handlers::role_handlers::set_member_role_handler(request, auth, db_config).await
+}
+
+/// POST /admin/tenants/bulk — onboard a company: domain tenant, member roles
+/// and optional profile scaffolds, from JSON or CSV (admin only)
+#[post("/admin/tenants/bulk", data = "<request>")]
+pub async fn admin_bulk_provision(
+    request: Json<crate::web::types::BulkProvisionRequest>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    db_config: &State<DatabaseConfig>,
+) -> Result<
+    Json<DataResponse<crate::core::bulk_provisioning::BulkProvisionReport>>,
+    Json<StandardErrorResponse>,
+> {
+    handlers::role_handlers::bulk_provision_handler(request, auth, config, db_config).await
+}
+
+/// POST /admin/tenants/<tenant>/rename — rename the tenant of a member's
+/// email or of a domain (admin only)
+#[post("/admin/tenants/<tenant>/rename", data = "<request>")]
+pub async fn admin_rename_tenant(
+    tenant: String,
+    request: Json<crate::web::types::RenameTenantRequest>,
+    auth: AuthenticatedUser,
+    db_config: &State<DatabaseConfig>,
+) -> Result<
+    Json<DataResponse<crate::core::tenant_rename::TenantRenameReport>>,
+    Json<StandardErrorResponse>,
+> {
+    handlers::role_handlers::rename_tenant_handler(tenant, request, auth, db_config).await
+}
+
+/// GET /admin/tenants/<name>/bundle — export a tenant's settings, member
+/// roles and installed templates as YAML (admin only)
+#[get("/admin/tenants/<name>/bundle?<templates>")]
+pub async fn admin_export_tenant_bundle(
+    name: String,
+    templates: Option<bool>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    db_config: &State<DatabaseConfig>,
+) -> Result<(ContentType, String), Json<StandardErrorResponse>> {
+    handlers::role_handlers::export_bundle_handler(name, templates, auth, config, db_config).await
+}
+
+/// POST /admin/tenants/bundle — apply a YAML tenant bundle idempotently
+/// (admin only)
+#[post("/admin/tenants/bundle?<dry_run>", data = "<data>")]
+pub async fn admin_apply_tenant_bundle(
+    data: rocket::Data<'_>,
+    dry_run: Option<bool>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    db_config: &State<DatabaseConfig>,
+) -> Result<
+    Json<DataResponse<crate::core::tenant_bundle::BundleApplyReport>>,
+    Json<StandardErrorResponse>,
+> {
+    handlers::role_handlers::apply_bundle_handler(data, dry_run, auth, config, db_config).await
+}
+
+/// POST /admin/benchmark — sample compilations with latency percentiles and
+/// CPU/memory usage, for capacity planning (admin only)
+#[post("/admin/benchmark", data = "<request>")]
+pub async fn admin_benchmark(
+    request: Json<crate::web::types::BenchmarkRequest>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+) -> Result<
+    Json<DataResponse<crate::core::compile_benchmark::BenchmarkReport>>,
+    Json<StandardErrorResponse>,
+> {
+    handlers::maintenance_handlers::benchmark_handler(request, auth, config).await
+}
+
+/// PUT /admin/typst-lint — set a tenant's editor Typst lint level (off, warn,
+/// block) (admin only)
+#[put("/admin/typst-lint", data = "<request>")]
+pub async fn admin_set_typst_lint(
+    request: Json<crate::web::types::TypstLintLevelRequest>,
+    auth: AuthenticatedUser,
+    db_config: &State<DatabaseConfig>,
+) -> Result<Json<ActionResponse>, Json<StandardErrorResponse>> {
+    handlers::maintenance_handlers::set_typst_lint_level_handler(request, auth, db_config).await
+}
+
+/// PUT /admin/approval-policy — set what generating an unapproved profile
+/// does for a tenant (off, watermark, block) (admin only)
+#[put("/admin/approval-policy", data = "<request>")]
+pub async fn admin_set_approval_policy(
+    request: Json<crate::web::types::ApprovalPolicyRequest>,
+    auth: AuthenticatedUser,
+    db_config: &State<DatabaseConfig>,
+) -> Result<Json<ActionResponse>, Json<StandardErrorResponse>> {
+    handlers::maintenance_handlers::set_approval_policy_handler(request, auth, db_config).await
+}
+
+/// GET /admin/tenant-regions — tenants stored outside the default region
+/// (admin only)
+#[get("/admin/tenant-regions")]
+pub async fn admin_list_tenant_regions(
+    auth: AuthenticatedUser,
+) -> Result<
+    Json<DataResponse<Vec<crate::core::data_residency::TenantRegion>>>,
+    Json<StandardErrorResponse>,
+> {
+    handlers::maintenance_handlers::list_tenant_regions_handler(auth).await
+}
+
+/// PUT /admin/tenant-region — store a tenant's files in a region of
+/// DATA_REGIONS (admin only)
+#[put("/admin/tenant-region", data = "<request>")]
+pub async fn admin_set_tenant_region(
+    request: Json<crate::web::types::TenantRegionRequest>,
+    auth: AuthenticatedUser,
+    db_config: &State<DatabaseConfig>,
+) -> Result<
+    Json<DataResponse<crate::core::data_residency::TenantRegion>>,
+    Json<StandardErrorResponse>,
+> {
+    handlers::maintenance_handlers::set_tenant_region_handler(request, auth, db_config).await
+}
+
+/// POST /admin/tenants/archive — move a user's files to cold storage and
+/// lock the account (admin only)
+#[post("/admin/tenants/archive", data = "<request>")]
+pub async fn admin_archive_tenant(
+    request: Json<crate::web::types::TenantArchiveRequest>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    db_config: &State<DatabaseConfig>,
+) -> Result<
+    Json<DataResponse<crate::core::tenant_archive::ArchiveReport>>,
+    Json<StandardErrorResponse>,
+> {
+    handlers::maintenance_handlers::tenant_archive_handler(request, false, auth, config, db_config)
+        .await
+}
+
+/// POST /admin/tenants/restore — bring an archived user back (admin only)
+#[post("/admin/tenants/restore", data = "<request>")]
+pub async fn admin_restore_tenant(
+    request: Json<crate::web::types::TenantArchiveRequest>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    db_config: &State<DatabaseConfig>,
+) -> Result<
+    Json<DataResponse<crate::core::tenant_archive::ArchiveReport>>,
+    Json<StandardErrorResponse>,
+> {
+    handlers::maintenance_handlers::tenant_archive_handler(request, true, auth, config, db_config)
+        .await
+}
+
+/// POST /admin/profiles/check — integrity reports for every profile of one
+/// user, broken ones first (admin only)
+#[post("/admin/profiles/check", data = "<request>")]
+pub async fn admin_check_profiles(
+    request: Json<crate::web::types::IntegrityCheckRequest>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+) -> Result<
+    Json<DataResponse<crate::core::profile_integrity::TenantIntegrity>>,
+    Json<StandardErrorResponse>,
+> {
+    handlers::maintenance_handlers::check_profiles_handler(request, auth, config).await
+}
+
+/// POST /admin/profiles/sanitize — dry-run or apply profile directory name
+/// cleanup for one user (admin only)
+#[post("/admin/profiles/sanitize", data = "<request>")]
+pub async fn admin_sanitize_profiles(
+    request: Json<crate::web::types::SanitizeProfilesRequest>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    db_config: &State<DatabaseConfig>,
+) -> Result<
+    Json<DataResponse<crate::core::profile_names::RenameReport>>,
+    Json<StandardErrorResponse>,
+> {
+    handlers::maintenance_handlers::sanitize_profiles_handler(request, auth, config, db_config)
+        .await
+}
+
+/// POST /admin/consistency — dry-run or repair database records that name
+/// profiles missing on disk, for one user (admin only)
+#[post("/admin/consistency", data = "<request>")]
+pub async fn admin_consistency(
+    request: Json<crate::web::types::ConsistencyCheckRequest>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    db_config: &State<DatabaseConfig>,
+) -> Result<
+    Json<DataResponse<crate::core::consistency::ConsistencyReport>>,
+    Json<StandardErrorResponse>,
+> {
+    handlers::maintenance_handlers::consistency_handler(request, auth, config, db_config).await
+}
+
+/// POST /admin/data-encryption/rotate — add a new data key for a tenant and
+/// optionally re-encrypt its files (admin only)
+#[post("/admin/data-encryption/rotate", data = "<request>")]
+pub async fn admin_rotate_data_key(
+    request: Json<crate::web::types::DataKeyRotationRequest>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+) -> Result<
+    Json<DataResponse<crate::core::data_encryption::KeyRotationReport>>,
+    Json<StandardErrorResponse>,
+> {
+    handlers::maintenance_handlers::rotate_data_key_handler(request, auth, config).await
+}
+
+/// POST /admin/profiles/language-files — dry-run or delete duplicate and
+/// placeholder experiences files for one user (admin only)
+#[post("/admin/profiles/language-files", data = "<request>")]
+pub async fn admin_cleanup_language_files(
+    request: Json<crate::web::types::LanguageFilesCleanupRequest>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+) -> Result<
+    Json<DataResponse<crate::core::language_files::LanguageCleanupReport>>,
+    Json<StandardErrorResponse>,
+> {
+    handlers::maintenance_handlers::cleanup_language_files_handler(request, auth, config).await
+}
+
+/// POST /admin/profiles/schema — dry-run or upgrade legacy cv_params.toml
+/// layouts of one user's profiles (admin only)
+#[post("/admin/profiles/schema", data = "<request>")]
+pub async fn admin_upgrade_profile_schema(
+    request: Json<crate::web::types::SchemaUpgradeRequest>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+) -> Result<
+    Json<DataResponse<crate::core::profile_schema::SchemaUpgradeReport>>,
+    Json<StandardErrorResponse>,
+> {
+    handlers::maintenance_handlers::upgrade_profile_schema_handler(request, auth, config).await
+}
+
+/// POST /admin/regenerations — regenerate a tenant's CVs in the background
+/// after a template update (admin only)
+#[post("/admin/regenerations", data = "<request>")]
+pub async fn admin_start_regeneration(
+    request: Json<crate::web::types::RegenerationRequest>,
+    auth: AuthenticatedUser,
+    config: &State<ServerConfig>,
+    db_config: &State<DatabaseConfig>,
+) -> Result<
+    Json<DataResponse<crate::core::bulk_regeneration::RegenerationReport>>,
+    Json<StandardErrorResponse>,
+> {
+    handlers::maintenance_handlers::start_regeneration_handler(request, auth, config, db_config)
+        .await
+}
+
+/// GET /admin/regenerations/<id> — progress and summary of a bulk
+/// regeneration (admin only)
+#[get("/admin/regenerations/<id>")]
+pub async fn admin_regeneration_report(
+    id: String,
+    auth: AuthenticatedUser,
+    db_config: &State<DatabaseConfig>,
+) -> Result<
+    Json<DataResponse<crate::core::bulk_regeneration::RegenerationReport>>,
+    Json<StandardErrorResponse>,
+> {
+    handlers::maintenance_handlers::regeneration_report_handler(id, auth, db_config).await
+}
+
 /// GET /feedback/eligible — check if user can submit feedback today
 #[get("/feedback/eligible")]
 pub async fn feedback_eligible(
@@ -805,6 +2377,16 @@ pub fn bad_request() -> Json<StandardErrorResponse> {
     ))
 }
 
+#[rocket::catch(423)]
+pub fn tenant_archived() -> Json<StandardErrorResponse> {
+    Json(StandardErrorResponse::new(
+        "This account is archived".to_string(),
+        "TENANT_ARCHIVED".to_string(),
+        vec!["Contact support to restore your account".to_string()],
+        None,
+    ))
+}
+
 #[rocket::catch(500)]
 pub fn internal_error() -> Json<StandardErrorResponse> {
     Json(StandardErrorResponse::new(
@@ -825,10 +2407,11 @@ pub async fn start_web_server(
     database_path: PathBuf,
     port: u16,
     cv_service_url: String,
+    tls: Option<crate::core::config_manager::TlsSettings>,
 ) -> Result<()> {
     let server_config = ServerConfig {
         data_dir: data_dir.clone(),
-        output_dir,
+        output_dir: output_dir.clone(),
         templates_dir,
     };
 
@@ -850,7 +2433,10 @@ pub async fn start_web_server(
         .expect("CVENOM_GOOGLE_PROJECT_ID env var is required");
     let auth_config = AuthConfig::new(google_project_id);
 
-    if let Err(e) = auth_config.update_firebase_keys().await {
+    if let Err(e) = auth_config
+        .init_firebase_keys(crate::auth::AuthStartupMode::from_env())
+        .await
+    {
         app_log!(error, "Failed to fetch Firebase keys: {}", e);
         return Err(e);
     }
@@ -863,11 +2449,26 @@ pub async fn start_web_server(
         }
     }
 
+    crate::core::data_residency::init(&data_dir, &output_dir);
+    if let Ok(pool) = db_config.pool() {
+        if let Err(e) = crate::core::data_residency::load(pool).await {
+            app_log!(error, "Failed to load tenant regions: {}", e);
+            return Err(e);
+        }
+    }
+    crate::core::data_encryption::init(&data_dir);
+
+    // Roll back CV imports a previous run was killed in the middle of.
+    for root in crate::core::data_residency::data_roots(&data_dir) {
+        crate::core::import_staging::recover(&root).await;
+    }
+
     // ── Data-retention background task ────────────────────────────────────────
     // Runs once per day. Deletes email-based tenants inactive for DATA_RETENTION_DAYS
     // (default 365). Domain tenants are never auto-deleted.
     if let Ok(cleanup_pool) = db_config.pool().map(|p| p.clone()) {
         let cleanup_data_dir = data_dir.clone();
+        let cleanup_output_dir = output_dir.clone();
         let retention_days = std::env::var("DATA_RETENTION_DAYS")
             .ok()
             .and_then(|v| v.parse::<i64>().ok())
@@ -886,10 +2487,14 @@ pub async fn start_web_server(
                         for tenant in &stale {
                             if let Some(email) = &tenant.email {
                                 // Delete files
-                                let dir = get_tenant_folder_path(email, &cleanup_data_dir);
-                                if dir.exists() {
-                                    if let Err(e) = FsOps::remove_dir_all(&dir).await {
-                                        app_log!(error, "[retention] Failed to delete files for {}: {}", email, e);
+                                for dir in [
+                                    get_tenant_folder_path(email, &cleanup_data_dir),
+                                    crate::core::database::get_tenant_output_path(email, &cleanup_output_dir),
+                                ] {
+                                    if dir.exists() {
+                                        if let Err(e) = FsOps::remove_dir_all(&dir).await {
+                                            app_log!(error, "[retention] Failed to delete files for {}: {}", email, e);
+                                        }
                                     }
                                 }
                                 // Hard-delete DB record
@@ -966,6 +2571,193 @@ pub async fn start_web_server(
         });
     }
 
+    // ── Analytics event export background task ────────────────────────────────
+    // Rotates the event spool into NDJSON batches (and pushes them when the
+    // `event-push` feature is on). Only runs when EVENT_EXPORT_DIR is set.
+    if let Some(export_dir) = crate::core::event_export::export_dir() {
+        let flush_every = crate::core::event_export::flush_interval();
+        app_log!(info, "[events] Exporting analytics events to {} every {}s", export_dir.display(), flush_every.as_secs());
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(flush_every);
+            loop {
+                interval.tick().await;
+                match crate::core::event_export::flush(&export_dir).await {
+                    Ok(0) => {}
+                    Ok(n) => app_log!(info, "[events] Exported {} batch(es)", n),
+                    Err(e) => app_log!(error, "[events] Flush failed: {}", e),
+                }
+            }
+        });
+    }
+
+    // ── Pinned Firebase keys refresh task ─────────────────────────────────────
+    // Keeps FIREBASE_KEYS_FILE current whenever Google is reachable, so an
+    // offline restart verifies tokens with recent keys. Clones share the cache.
+    if auth_config.firebase_keys_file.is_some() {
+        let refresh_auth = auth_config.clone();
+        let refresh_every = std::env::var("FIREBASE_KEYS_REFRESH_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(6 * 3600);
+        tokio::spawn(async move {
+            let mut interval =
+                tokio::time::interval(std::time::Duration::from_secs(refresh_every.max(60)));
+            interval.tick().await; // keys were just loaded at startup
+            loop {
+                interval.tick().await;
+                if let Err(e) = refresh_auth.update_firebase_keys().await {
+                    app_log!(warn, "[auth] Firebase key refresh failed (keeping current keys): {}", e);
+                }
+            }
+        });
+    }
+
+    // ── Cached service answers ────────────────────────────────────────────────
+    // Deletes the cv-import answers past SERVICE_CACHE_TTL_HOURS once a day,
+    // see core::service_cache.
+    {
+        let cache_data_dir = data_dir.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(std::time::Duration::from_secs(24 * 3600));
+            loop {
+                interval.tick().await;
+                for root in crate::core::data_residency::data_roots(&cache_data_dir) {
+                    let pruned = tokio::task::spawn_blocking(move || {
+                        crate::core::service_cache::prune(&root, true)
+                    })
+                    .await;
+                    match pruned {
+                        Ok(Ok((0, _))) => {}
+                        Ok(Ok((deleted, _))) => {
+                            app_log!(info, "[service-cache] Deleted {} expired answer(s)", deleted)
+                        }
+                        Ok(Err(e)) => app_log!(warn, "[service-cache] Prune failed: {}", e),
+                        Err(e) => app_log!(warn, "[service-cache] Prune task failed: {}", e),
+                    }
+                }
+            }
+        });
+    }
+
+    // ── Template compile-check background task ────────────────────────────────
+    // Compiles each template against sample data whenever its files change and
+    // hides the ones that fail. TEMPLATE_HEALTH_INTERVAL_SECS=0 disables it.
+    if let Some(check_every) = crate::core::template_health::check_interval() {
+        let health_templates_dir = server_config.templates_dir.clone();
+        tokio::spawn(async move {
+            let mut seen = std::collections::HashMap::new();
+            let mut interval = tokio::time::interval(check_every);
+            loop {
+                interval.tick().await;
+                crate::core::template_health::run_pass(&health_templates_dir, &mut seen).await;
+            }
+        });
+    }
+
+    // ── Template and font asset sync task ─────────────────────────────────────
+    // Keeps registry templates and fonts identical across instances through
+    // the ASSET_SYNC_URL bucket, see core::asset_sync.
+    if let Some(s3) = crate::core::asset_sync::config() {
+        let sync_templates_dir = server_config.templates_dir.clone();
+        let sync_every = crate::core::asset_sync::interval();
+        app_log!(info, "[assets] Syncing templates and fonts every {}s", sync_every.as_secs());
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(sync_every);
+            loop {
+                crate::core::asset_sync::wait(&mut interval).await;
+                crate::core::asset_sync::run_pass(s3, &sync_templates_dir).await;
+            }
+        });
+    }
+
+    // ── Failed import retry task ──────────────────────────────────────────────
+    // Retries cv-import conversions that failed for a temporary reason, see
+    // core::import_retry. IMPORT_RETRY_ATTEMPTS=0 disables it.
+    if crate::core::import_retry::max_attempts() > 0 {
+        if let Ok(retry_pool) = db_config.pool().map(|p| p.clone()) {
+            let retry_data_dir = data_dir.clone();
+            let retry_service_url = cv_service_url.clone();
+            tokio::spawn(async move {
+                match crate::core::import_retry::release_running(&retry_pool).await {
+                    Ok(0) => {}
+                    Ok(n) => app_log!(info, "[imports] Rescheduled {} interrupted retries", n),
+                    Err(e) => app_log!(error, "[imports] Failed to reschedule retries: {}", e),
+                }
+                let mut interval = tokio::time::interval(crate::core::import_retry::poll_interval());
+                loop {
+                    interval.tick().await;
+                    let due = match crate::core::import_retry::claim_due(&retry_pool).await {
+                        Ok(due) => due,
+                        Err(e) => {
+                            app_log!(error, "[imports] Failed to claim due retries: {}", e);
+                            continue;
+                        }
+                    };
+                    for job in due {
+                        let _ = handlers::retry_failed_import(
+                            &retry_pool,
+                            &retry_data_dir,
+                            &retry_service_url,
+                            job,
+                        )
+                        .await;
+                    }
+                }
+            });
+        }
+    }
+
+    // ── Failed import expiry ──────────────────────────────────────────────────
+    // Deletes the jobs left past IMPORT_RETRY_KEEP_DAYS, and their files, once
+    // a day.
+    if let Ok(expiry_pool) = db_config.pool().map(|p| p.clone()) {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(std::time::Duration::from_secs(24 * 3600));
+            loop {
+                interval.tick().await;
+                match crate::core::import_retry::prune_expired(&expiry_pool).await {
+                    Ok(0) => {}
+                    Ok(n) => app_log!(info, "[imports] Deleted {} expired failed import(s)", n),
+                    Err(e) => app_log!(warn, "[imports] Failed to delete expired imports: {}", e),
+                }
+            }
+        });
+    }
+
+    if let Ok(jobs_pool) = db_config.pool().map(|p| p.clone()) {
+        tokio::spawn(async move {
+            match crate::core::jobs::fail_interrupted(&jobs_pool).await {
+                Ok(0) => {}
+                Ok(n) => app_log!(info, "[jobs] Failed {} generations interrupted by a restart", n),
+                Err(e) => app_log!(error, "[jobs] Failed to close interrupted generations: {}", e),
+            }
+        });
+    }
+
+    if let Ok(regeneration_pool) = db_config.pool().map(|p| p.clone()) {
+        let dirs = crate::core::bulk_regeneration::Directories {
+            data_dir: server_config.data_dir.clone(),
+            output_dir: server_config.output_dir.clone(),
+            templates_dir: server_config.templates_dir.clone(),
+        };
+        tokio::spawn(async move {
+            let ids = match crate::core::bulk_regeneration::resume(&regeneration_pool).await {
+                Ok(ids) => ids,
+                Err(e) => {
+                    app_log!(error, "[regeneration] Failed to list interrupted runs: {}", e);
+                    return;
+                }
+            };
+            for id in ids {
+                app_log!(info, "[regeneration] Resuming {}", id);
+                let (pool, dirs) = (regeneration_pool.clone(), dirs.clone());
+                if let Err(e) = crate::core::bulk_regeneration::run(pool, dirs, id.clone()).await {
+                    app_log!(error, "[regeneration] {} stopped: {:#}", id, e);
+                }
+            }
+        });
+    }
+
     app_log!(info, "Starting CVenom Multi-tenant API server");
     app_log!(info, "Database: {}", db_config.database_path.display());
     app_log!(
@@ -974,14 +2766,47 @@ pub async fn start_web_server(
     );
     app_log!(info, "Attempting to bind to port: {}", port);
 
-    let _rocket = build_rocket(server_config, auth_config, db_config, cv_service_url, port)
-        .launch()
-        .await;
+    // With TLS, a certificate renewal shuts the instance down and the loop
+    // launches a fresh one with the new files (Rocket 0.5 loads certs once).
+    loop {
+        let mut rocket = build_rocket(
+            server_config.clone(),
+            auth_config.clone(),
+            db_config.clone(),
+            cv_service_url.clone(),
+            port,
+        );
+        if let Some(tls_settings) = &tls {
+            rocket = tls::apply(rocket, tls_settings);
+        }
+        let rocket = rocket.ignite().await?;
+
+        let reload = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let watcher = tls.clone().map(|tls_settings| {
+            tokio::spawn(tls::watch_for_renewal(
+                tls_settings,
+                rocket.shutdown(),
+                reload.clone(),
+            ))
+        });
+
+        let _rocket = rocket.launch().await;
+        if let Some(watcher) = watcher {
+            watcher.abort();
+        }
+        if !reload.load(std::sync::atomic::Ordering::SeqCst) {
+            break;
+        }
+        app_log!(info, "Relaunching with renewed TLS certificate");
+    }
 
     app_log!(info, "Server shutting down");
     Ok(())
 }
 
+/// Largest request body (uploads, forms) the server accepts.
+pub const MAX_UPLOAD_MB: u64 = 10;
+
 /// Build the Rocket instance from already-initialised state.
 /// Called by `start_web_server` in production and by tests with mocked state.
 pub fn build_rocket(
@@ -995,43 +2820,131 @@ pub fn build_rocket(
         port,
         log_level: LogLevel::Off,
         limits: rocket::data::Limits::default()
-            .limit("file", ByteUnit::Megabyte(10))
-            .limit("data-form", ByteUnit::Megabyte(10))
-            .limit("form", ByteUnit::Megabyte(10)),
+            .limit("file", ByteUnit::Megabyte(MAX_UPLOAD_MB))
+            .limit("data-form", ByteUnit::Megabyte(MAX_UPLOAD_MB))
+            .limit("form", ByteUnit::Megabyte(MAX_UPLOAD_MB)),
         ..Config::default()
     };
 
-    rocket::custom(config)
+    // CVENOM_BASE_PATH lets the API live under e.g. /cv-api behind nginx.
+    let base_path = proxy::base_path();
+    if base_path != "/" {
+        app_log!(info, "Mounting API under base path {}", base_path);
+    }
+
+    let templates = SharedTemplateEngine::load(server_config.templates_dir.clone());
+    let secrets = SecretStore::from_env();
+    let subsystems = handlers::system_handlers::Subsystems::detect(&cv_service_url, &secrets);
+
+    let rocket = rocket::custom(config)
         .configure(rocket::Config::figment().merge(("port", port)))
         .attach(Cors)
+        .attach(sandbox::SandboxRouter)
+        .attach(conversations::ConversationRecorder)
         .manage(server_config)
+        .manage(templates)
+        .manage(secrets)
+        .manage(subsystems)
         .manage(auth_config)
         .manage(db_config)
         .manage(cv_service_url)
-        .register("/", catchers![bad_request, internal_error])
+        .register(base_path.as_str(), catchers![bad_request, tenant_archived, internal_error])
         .mount(
-            "/",
+            base_path.as_str(),
             routes![
                 analyze_job_fit,
+                analyze_job_fit_stream,
+                bench_match,
                 generate_cv,
+                generate_cv_async,
+                generate_cv_batch,
+                get_generation_job,
+                get_generation_job_result,
+                generate_preview_set,
+                cancel_request,
+                get_latest_pdf,
+                delete_generation,
+                purge_generations,
+                get_profile_checklist,
+                get_profile_integrity,
+                get_profile_template_versions,
+                get_profile_translations,
+                pin_profile_template,
+                list_profile_comments,
+                create_profile_comment,
+                resolve_profile_comment,
+                list_persons,
+                get_person_tags,
+                set_person_tags,
+                list_smart_lists,
+                save_smart_list,
+                evaluate_smart_list,
+                delete_smart_list,
+                sandbox_post,
+                sandbox_put,
+                sandbox_patch,
+                sandbox_delete,
+                sandbox_latest_pdf,
+                sandbox_intake_address,
+                sandbox_calendar_feed,
+                sandbox_referral_link,
+                sandbox_output_file,
+                watch_profile,
+                unwatch_profile,
+                list_watches,
+                watch_events,
+                get_profile_approval,
+                request_profile_review,
+                approve_profile,
+                reject_profile,
+                list_pending_reviews,
                 create_profile,
                 delete_profile,
                 upload_picture,
+                get_profile_picture,
                 upload_and_convert_cv,
+                convert_upload_session,
+                convert_presigned_upload,
+                list_failed_imports,
+                retry_failed_import,
+                create_upload,
+                presign_upload,
+                upload_chunk,
+                upload_progress,
+                upload_events,
+                cancel_upload,
                 import_cv_from_text,
+                create_profile_from_notes,
+                compare_profiles,
                 get_templates,
+                get_template_catalog,
+                get_template_preview,
+                install_template,
+                upload_template,
+                validate_template,
+                admin_template_state,
                 get_current_user,
+                provision_current_user,
                 health,
+                get_capabilities,
                 get_tenant_files,
+                download_tenant_file,
                 get_tenant_file_content,
                 save_tenant_file_content,
+                save_file_draft,
+                get_file_draft,
+                discard_file_draft,
                 universal_options_handler,
                 rename_profile_handler,
                 change_profile_language_handler,
+                set_profile_type,
                 optimize_cv,
                 optimize_and_generate,
                 save_optimized_cv,
                 translate_cv,
+                cv_optimize,
+                cv_optimize_stream,
+                cv_translate,
                 generate_cover_letter,
                 export_cover_letter,
                 payment_intent,
@@ -1039,17 +2952,61 @@ pub fn build_rocket(
                 payment_balance,
                 payment_transactions,
                 get_cv_data,
+                upgrade_cv_schema,
                 put_cv_data,
+                get_cv_section,
+                put_cv_section,
                 list_brands,
                 get_brand,
                 put_brand,
                 delete_brand,
                 upload_brand_logo,
+                upload_profile_asset,
+                delete_profile_asset,
+                list_client_logos,
+                upload_client_logo,
+                delete_client_logo,
                 get_brand_logo,
                 delete_brand_logo,
                 delete_me,
                 generate_portfolio,
                 get_my_referral_link,
+                get_intake_address,
+                rotate_intake_address,
+                inbound_email,
+                get_calendar_feed,
+                get_calendar_ics,
+                list_conversations,
+                get_conversation,
+                delete_conversation,
+                admin_service_usage,
+                tenant_usage,
+                admin_set_ai_budget,
+                admin_service_usage_export,
+                admin_set_role,
+                admin_bulk_provision,
+                admin_rename_tenant,
+                admin_export_tenant_bundle,
+                admin_apply_tenant_bundle,
+                admin_sanitize_profiles,
+                admin_consistency,
+                admin_cleanup_language_files,
+                admin_upgrade_profile_schema,
+                admin_start_regeneration,
+                admin_regeneration_report,
+                admin_rotate_data_key,
+                admin_list_tenant_regions,
+                admin_set_tenant_region,
+                admin_archive_tenant,
+                admin_restore_tenant,
+                admin_check_profiles,
+                admin_set_typst_lint,
+                admin_set_approval_policy,
+                admin_benchmark,
+                audit_report,
+                list_secrets,
+                put_secret,
+                delete_secret,
                 bd_register,
                 bd_me,
                 bd_customers,
@@ -1073,6 +3030,8 @@ pub fn build_rocket(
                 get_preferences,
                 update_preferences,
             ],
-        )
+        );
+
+    static_files::mount(rocket)
 }
 
@@ -0,0 +1,121 @@
+// src/web/proxy.rs
+//! Base-path mounting and reverse-proxy awareness.
+//!
+//! * `CVENOM_BASE_PATH` (e.g. `/cv-api`) mounts every route under that prefix,
+//!   for nginx setups that forward `/cv-api/...` without stripping it.
+//! * `CVENOM_TRUST_PROXY=true` makes [`ProxyInfo`] honour `X-Forwarded-For`,
+//!   `X-Forwarded-Proto` and `X-Forwarded-Host`. Off by default — the headers
+//!   are client-controlled unless a proxy overwrites them.
+//! * Public links (`/outputs/...` download URLs) use `PUBLIC_BASE_URL` when set,
+//!   otherwise the forwarded scheme/host plus the base path.
+
+use rocket::request::{FromRequest, Outcome};
+use rocket::Request;
+use std::net::IpAddr;
+
+const DEFAULT_PUBLIC_BASE_URL: &str = "https://api.cvenom.com";
+
+/// Normalised mount prefix: `/` or `/segment[/segment]` without trailing slash.
+pub fn base_path() -> String {
+    normalize_base_path(&std::env::var("CVENOM_BASE_PATH").unwrap_or_default())
+}
+
+fn normalize_base_path(raw: &str) -> String {
+    let trimmed = raw.trim().trim_matches('/');
+    if trimmed.is_empty() {
+        "/".to_string()
+    } else {
+        format!("/{}", trimmed)
+    }
+}
+
+pub fn trust_proxy() -> bool {
+    std::env::var("CVENOM_TRUST_PROXY")
+        .map(|v| matches!(v.to_lowercase().as_str(), "1" | "true" | "yes"))
+        .unwrap_or(false)
+}
+
+/// Client address and public origin of a request, as seen through the proxy.
+#[derive(Debug, Clone)]
+pub struct ProxyInfo {
+    pub client_ip: Option<IpAddr>,
+    /// `https` / `http` — forwarded proto when trusted, else `None`.
+    pub proto: Option<String>,
+    /// Forwarded host (or `Host` header) when trusted, else `None`.
+    pub host: Option<String>,
+}
+
+impl ProxyInfo {
+    pub fn from_request(req: &Request<'_>) -> Self {
+        if !trust_proxy() {
+            return Self {
+                client_ip: req.remote().map(|addr| addr.ip()),
+                proto: None,
+                host: None,
+            };
+        }
+
+        let headers = req.headers();
+        // Left-most entry is the original client; later ones are proxies.
+        let client_ip = headers
+            .get_one("X-Forwarded-For")
+            .and_then(|v| v.split(',').next())
+            .and_then(|ip| ip.trim().parse().ok())
+            .or_else(|| req.real_ip())
+            .or_else(|| req.remote().map(|addr| addr.ip()));
+        let first = |name: &str| {
+            headers
+                .get_one(name)
+                .and_then(|v| v.split(',').next())
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+        };
+
+        Self {
+            client_ip,
+            proto: first("X-Forwarded-Proto"),
+            host: first("X-Forwarded-Host").or_else(|| first("Host")),
+        }
+    }
+
+    /// Base URL for links handed back to clients (no trailing slash).
+    pub fn public_base_url(&self) -> String {
+        if let Ok(url) = std::env::var("PUBLIC_BASE_URL") {
+            return url.trim_end_matches('/').to_string();
+        }
+        match &self.host {
+            Some(host) => {
+                let proto = self.proto.as_deref().unwrap_or("https");
+                let base = base_path();
+                if base == "/" {
+                    format!("{}://{}", proto, host)
+                } else {
+                    format!("{}://{}{}", proto, host, base)
+                }
+            }
+            None => DEFAULT_PUBLIC_BASE_URL.to_string(),
+        }
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for ProxyInfo {
+    type Error = ();
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        Outcome::Success(ProxyInfo::from_request(req))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn base_path_is_normalised() {
+        assert_eq!(normalize_base_path(""), "/");
+        assert_eq!(normalize_base_path("/"), "/");
+        assert_eq!(normalize_base_path("cv-api/"), "/cv-api");
+        assert_eq!(normalize_base_path(" /cv-api/v1/ "), "/cv-api/v1");
+    }
+}
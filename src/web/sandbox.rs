@@ -0,0 +1,562 @@
+// src/web/sandbox.rs
+//! Sandbox mode, for frontend developers building against production.
+//!
+//! A request under `/sandbox/…` (below the base path) or carrying
+//! `X-Sandbox: true` is a sandbox request. Reads go to the real endpoints.
+//! Mutations (POST, PUT, PATCH, DELETE) are routed by [`SandboxRouter`] to
+//! [`mutation`], which authenticates the caller, validates the body and
+//! answers in the shape of the real endpoint without writing anything. A
+//! mutation no real route serves is a `404 NOT_FOUND`, as it would be
+//! outside the sandbox. The reads that have side effects
+//! ([`side_effect_read`]: `latest-pdf` may compile and charge, the intake
+//! address, calendar feed and referral link are created on first use) are
+//! routed the same way, to [`latest_pdf`], [`intake_address`],
+//! [`calendar_feed`] and [`referral_link`]. Generated files are placeholder PDFs served by
+//! [`output_file`] under `/sandbox/outputs/…`.
+//! Every sandbox response carries `X-Sandbox: true`, and conversation turns
+//! of sandbox requests are not recorded.
+
+use std::path::{Path, PathBuf};
+
+use chrono::Utc;
+use graflog::app_log;
+use rocket::data::ToByteUnit;
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::http::uri::Origin;
+use rocket::http::{ContentType, Header, Method, Status};
+use rocket::serde::json::Json;
+use rocket::{Data, Request, Response};
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+
+use crate::auth::AuthenticatedUser;
+use crate::core::database::get_tenant_folder_path;
+use crate::core::jobs::{GenerationJob, JobStatus};
+use crate::core::person_tags::PersonTags;
+use crate::core::{email_intake, review_calendar};
+use crate::utils::normalize_profile_name;
+use crate::web::handlers::calendar_handlers::CalendarFeed;
+use crate::web::handlers::cv_handlers::{BatchGenerateRequest, SaveSmartListRequest};
+use crate::web::handlers::intake_handlers::IntakeAddress;
+use crate::web::handlers::referral_handlers::{ReferralLinkData, ReferralLinkResponse};
+use crate::web::proxy::{self, ProxyInfo};
+use crate::web::types::{
+    ActionResponse, CreateProfileRequest, DataResponse, DeleteProfileRequest, GeneratePdfResponse,
+    GenerateRequest, PdfResponse, ResponseType, ServerConfig, StandardErrorResponse,
+    StandardRequest,
+};
+
+pub const SANDBOX_HEADER: &str = "X-Sandbox";
+const SANDBOX_PREFIX: &str = "/sandbox";
+const MAX_BODY_MIB: usize = 10;
+
+/// Marks a sandbox request for the other fairings.
+struct Sandboxed(bool);
+
+pub fn is_sandboxed(request: &Request<'_>) -> bool {
+    request.local_cache(|| Sandboxed(false)).0
+}
+
+/// Marks a sandbox mutation no real route serves.
+struct Unrouted(bool);
+
+/// `rest` (starting with `/`) below the base path.
+fn join(base: &str, rest: &str) -> String {
+    match (base, rest) {
+        ("/", "") => "/".to_string(),
+        ("/", _) => rest.to_string(),
+        _ => format!("{}{}", base, rest),
+    }
+}
+
+/// Reads (`relative` to the base path) that must not reach the real endpoint
+/// in the sandbox.
+fn side_effect_read(relative: &str) -> bool {
+    let segments: Vec<&str> = relative.split('/').filter(|s| !s.is_empty()).collect();
+    matches!(
+        segments.as_slice(),
+        ["profiles", _, "latest-pdf"]
+            | ["outputs", ..]
+            | ["intake", "email-address"]
+            | ["calendar", "feed"]
+            | ["referral", "my-link"]
+    )
+}
+
+/// Whether the route path `pattern` matches `path`: `<param>` stands for one
+/// segment, `<param..>` for all the remaining ones.
+fn route_matches(pattern: &str, path: &str) -> bool {
+    let mut path = path.split('/').filter(|s| !s.is_empty());
+    for segment in pattern.split('/').filter(|s| !s.is_empty()) {
+        if segment.starts_with('<') && segment.ends_with("..>") {
+            return true;
+        }
+        match path.next() {
+            Some(_) if segment.starts_with('<') => {}
+            Some(given) if given == segment => {}
+            _ => return false,
+        }
+    }
+    path.next().is_none()
+}
+
+/// Whether a route, other than the sandbox's own below `sandbox_root`,
+/// serves `method` at `path`.
+fn has_route(request: &Request<'_>, method: Method, path: &str, sandbox_root: &str) -> bool {
+    let sandbox_routes = format!("{}/", sandbox_root.trim_end_matches('/'));
+    request.rocket().routes().any(|route| {
+        route.method == method
+            && !route.uri.path().starts_with(&sandbox_routes)
+            && route_matches(route.uri.path(), path)
+    })
+}
+
+/// Path a request is routed to, or `None` when it is not a sandbox request:
+/// the real endpoint for reads, `<base>/sandbox/…` for mutations and
+/// [`side_effect_read`]s.
+fn sandbox_path(path: &str, base: &str, header: bool, mutating: bool) -> Option<String> {
+    let relative = match path.strip_prefix(&join(base, SANDBOX_PREFIX)) {
+        Some(rest) if rest.is_empty() || rest.starts_with('/') => rest.to_string(),
+        _ if header => match base {
+            "/" => path.to_string(),
+            _ => path.strip_prefix(base).unwrap_or(path).to_string(),
+        },
+        _ => return None,
+    };
+    Some(match mutating || side_effect_read(&relative) {
+        true => join(base, &format!("{}{}", SANDBOX_PREFIX, relative)),
+        false => join(base, &relative),
+    })
+}
+
+pub struct SandboxRouter;
+
+#[rocket::async_trait]
+impl Fairing for SandboxRouter {
+    fn info(&self) -> Info {
+        Info {
+            name: "Route sandbox requests",
+            kind: Kind::Request | Kind::Response,
+        }
+    }
+
+    async fn on_request(&self, request: &mut Request<'_>, _: &mut Data<'_>) {
+        let header = request
+            .headers()
+            .get_one(SANDBOX_HEADER)
+            .is_some_and(|v| matches!(v.to_lowercase().as_str(), "1" | "true" | "yes"));
+        let mutating = matches!(
+            request.method(),
+            Method::Post | Method::Put | Method::Patch | Method::Delete
+        );
+        let path = request.uri().path().to_string();
+        let base = proxy::base_path();
+        let Some(mut target) = sandbox_path(&path, &base, header, mutating) else {
+            return;
+        };
+        request.local_cache(|| Sandboxed(true));
+        if mutating {
+            // Sent to the real path, where nothing answers but the 404.
+            let sandbox_root = join(&base, SANDBOX_PREFIX);
+            let real = match target.strip_prefix(&sandbox_root) {
+                Some(rest) => join(&base, rest),
+                None => target.clone(),
+            };
+            if !has_route(request, request.method(), &real, &sandbox_root) {
+                request.local_cache(|| Unrouted(true));
+                target = real;
+            }
+        }
+        if target == path {
+            return;
+        }
+        let uri = match request.uri().query() {
+            Some(query) => format!("{}?{}", target, query),
+            None => target,
+        };
+        if let Ok(origin) = Origin::parse_owned(uri) {
+            request.set_uri(origin);
+        }
+    }
+
+    async fn on_response<'r>(&self, request: &'r Request<'_>, response: &mut Response<'r>) {
+        if !is_sandboxed(request) {
+            return;
+        }
+        response.set_header(Header::new(SANDBOX_HEADER, "true"));
+        if request.local_cache(|| Unrouted(false)).0 {
+            let error = sandbox_error(
+                format!("No {} route at {}", request.method(), request.uri().path()),
+                "NOT_FOUND",
+                vec!["Check the endpoint path and method".to_string()],
+            );
+            let body = serde_json::to_string(&error.into_inner()).unwrap_or_default();
+            response.set_status(Status::NotFound);
+            response.set_header(ContentType::JSON);
+            response.set_sized_body(body.len(), std::io::Cursor::new(body));
+        }
+    }
+}
+
+fn sandbox_error(message: String, code: &str, suggestions: Vec<String>) -> Json<StandardErrorResponse> {
+    Json(StandardErrorResponse::new(message, code.to_string(), suggestions, None))
+}
+
+/// The body as the endpoint's request type.
+fn parse<T: DeserializeOwned>(body: &Value) -> Result<T, Json<StandardErrorResponse>> {
+    serde_json::from_value(body.clone()).map_err(|e| {
+        sandbox_error(
+            format!("Invalid request body: {}", e),
+            "INVALID_INPUT",
+            vec!["Send the body the real endpoint expects".to_string()],
+        )
+    })
+}
+
+fn invalid(e: anyhow::Error) -> Json<StandardErrorResponse> {
+    sandbox_error(e.to_string(), "INVALID_INPUT", vec![])
+}
+
+/// `profile`, normalized, when it exists in `tenant_data_dir`.
+fn existing_profile(
+    tenant_data_dir: &Path,
+    profile: &str,
+) -> Result<String, Json<StandardErrorResponse>> {
+    let profile = normalize_profile_name(profile);
+    if profile.is_empty() || !tenant_data_dir.join(&profile).is_dir() {
+        return Err(sandbox_error(
+            format!("Profile '{}' not found in your account", profile),
+            "PROFILE_NOT_FOUND",
+            vec!["Check the profile name spelling".to_string()],
+        ));
+    }
+    Ok(profile)
+}
+
+/// A one-page PDF saying that `name` was not really generated.
+fn placeholder_pdf(name: &str) -> Vec<u8> {
+    let name: String = name
+        .chars()
+        .filter(|c| c.is_ascii_graphic() && !matches!(c, '(' | ')' | '\\'))
+        .collect();
+    let content = format!("BT /F1 18 Tf 72 760 Td (Sandbox: {} was not generated) Tj ET", name);
+    let objects = [
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        "<< /Type /Pages /Kids [3 0 R] /Count 1 >>".to_string(),
+        "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 595 842] \
+         /Resources << /Font << /F1 4 0 R >> >> /Contents 5 0 R >>"
+            .to_string(),
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica >>".to_string(),
+        format!("<< /Length {} >>\nstream\n{}\nendstream", content.len(), content),
+    ];
+    let mut pdf = b"%PDF-1.4\n".to_vec();
+    let mut offsets = Vec::new();
+    for (i, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        pdf.extend_from_slice(format!("{} 0 obj\n{}\nendobj\n", i + 1, object).as_bytes());
+    }
+    let xref = pdf.len();
+    let mut tail = format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1);
+    for offset in offsets {
+        tail.push_str(&format!("{:010} 00000 n \n", offset));
+    }
+    tail.push_str(&format!(
+        "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+        objects.len() + 1,
+        xref
+    ));
+    pdf.extend_from_slice(tail.as_bytes());
+    pdf
+}
+
+/// What the real endpoint would answer, for the endpoints the studio calls
+/// most; a generic acknowledgement for the other routed mutations (see
+/// [`SandboxRouter`] for the unrouted ones).
+fn fake_response(
+    method: Method,
+    path: &str,
+    body: &Value,
+    auth: &AuthenticatedUser,
+    config: &ServerConfig,
+    proxy: &ProxyInfo,
+) -> Result<Value, Json<StandardErrorResponse>> {
+    let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+    let tenant_data_dir = get_tenant_folder_path(auth.email(), &config.data_dir);
+    let existing = |profile: &str| existing_profile(&tenant_data_dir, profile);
+    let conversation_id = body
+        .get("conversation_id")
+        .and_then(|v| v.as_str())
+        .map(str::to_string);
+
+    let response = match (method, segments.as_slice()) {
+        (Method::Post, ["generate"]) => {
+            let request: StandardRequest<GenerateRequest> = parse(body)?;
+            let profile = existing(&request.data.profile)?;
+            let lang = request.data.lang.as_deref().unwrap_or("en");
+            let filename = format!("{}_{}.pdf", profile, lang);
+            json!(GeneratePdfResponse {
+                response_type: ResponseType::File,
+                success: true,
+                message: "CV generated successfully".to_string(),
+                download_url: format!(
+                    "{}{}/outputs/{}",
+                    proxy.public_base_url(),
+                    SANDBOX_PREFIX,
+                    filename
+                ),
+                filename,
+                profile,
+                warnings: Vec::new(),
+                conversation_id,
+                cache_hit: false,
+            })
+        }
+        (Method::Post, ["generate", "async"]) => {
+            let request: StandardRequest<GenerateRequest> = parse(body)?;
+            let now = Utc::now();
+            json!(DataResponse::success(
+                "Generation queued".to_string(),
+                GenerationJob {
+                    id: format!("sandbox-{}", uuid::Uuid::new_v4()),
+                    profile: existing(&request.data.profile)?,
+                    status: JobStatus::Queued,
+                    filename: None,
+                    error: None,
+                    created_at: now,
+                    updated_at: now,
+                },
+                conversation_id,
+            ))
+        }
+        (Method::Post, ["generate", "batch"]) => {
+            parse::<StandardRequest<BatchGenerateRequest>>(body)?;
+            json!(DataResponse::success(
+                "Sandbox: the batch would be returned as a ZIP".to_string(),
+                json!({ "generated": 0, "failed": 0, "persons": [] }),
+                conversation_id,
+            ))
+        }
+        (Method::Post, ["create"]) => {
+            let request: StandardRequest<CreateProfileRequest> = parse(body)?;
+            let profile = normalize_profile_name(&request.data.profile);
+            if profile.is_empty() || tenant_data_dir.join(&profile).exists() {
+                return Err(sandbox_error(
+                    format!("Profile '{}' already exists", request.data.profile),
+                    "PROFILE_EXISTS",
+                    vec!["Choose another name".to_string()],
+                ));
+            }
+            json!(ActionResponse::success(
+                format!("Profile '{}' created successfully", request.data.profile),
+                "created".to_string(),
+                conversation_id,
+            ))
+        }
+        (Method::Post, ["delete-profile"]) => {
+            let request: StandardRequest<DeleteProfileRequest> = parse(body)?;
+            existing(&request.data.profile)?;
+            json!(ActionResponse::success(
+                format!("Profile '{}' deleted successfully", request.data.profile),
+                "deleted".to_string(),
+                conversation_id,
+            ))
+        }
+        (Method::Put, ["profiles", name, "tags"]) => {
+            existing(name)?;
+            let tags = parse::<PersonTags>(body)?.normalized().map_err(invalid)?;
+            json!(DataResponse::success("Tags saved".to_string(), tags, None))
+        }
+        (Method::Post, ["smart-lists"]) => {
+            let request: SaveSmartListRequest = parse(body)?;
+            let name = crate::core::smart_lists::normalize_list_name(&request.name)
+                .map_err(invalid)?;
+            let criteria = request.criteria.normalized().map_err(invalid)?;
+            json!(DataResponse::success(
+                format!("Smart list '{}' saved", name),
+                json!({
+                    "name": name,
+                    "criteria": criteria,
+                    "updated_at": Utc::now().to_rfc3339(),
+                }),
+                None,
+            ))
+        }
+        _ => json!(DataResponse::success(
+            format!("Sandbox: {} /{} accepted, nothing was changed", method, segments.join("/")),
+            json!({ "sandbox": true }),
+            conversation_id,
+        )),
+    };
+    Ok(response)
+}
+
+/// A sandbox mutation: the body is read and validated, nothing is written.
+pub async fn mutation(
+    method: Method,
+    path: PathBuf,
+    content_type: Option<&ContentType>,
+    data: Data<'_>,
+    auth: AuthenticatedUser,
+    config: &ServerConfig,
+    proxy: ProxyInfo,
+) -> Result<Json<Value>, Json<StandardErrorResponse>> {
+    let body = data.open(MAX_BODY_MIB.mebibytes()).into_bytes().await.map_err(|e| {
+        sandbox_error(format!("Failed to read the body: {}", e), "INVALID_INPUT", vec![])
+    })?;
+    if !body.is_complete() {
+        return Err(sandbox_error(
+            format!("The body is larger than {} MiB", MAX_BODY_MIB),
+            "PAYLOAD_TOO_LARGE",
+            vec![],
+        ));
+    }
+    let json_body = content_type.map_or(!body.is_empty(), |ct| ct.is_json());
+    let body = if json_body {
+        serde_json::from_slice(&body).map_err(|e| {
+            sandbox_error(
+                format!("The body is not valid JSON: {}", e),
+                "INVALID_JSON",
+                vec!["Send a JSON body with Content-Type: application/json".to_string()],
+            )
+        })?
+    } else {
+        Value::Null
+    };
+    let path = path.to_string_lossy().to_string();
+    fake_response(method, &path, &body, &auth, config, &proxy).map(Json)
+}
+
+/// `GET /profiles/<name>/latest-pdf` in the sandbox: a placeholder, since the
+/// real endpoint compiles and charges when the person has no PDF yet.
+pub async fn latest_pdf(
+    name: String,
+    auth: AuthenticatedUser,
+    config: &ServerConfig,
+) -> Result<PdfResponse, Json<StandardErrorResponse>> {
+    let tenant_data_dir = get_tenant_folder_path(auth.email(), &config.data_dir);
+    let profile = existing_profile(&tenant_data_dir, &name)?;
+    let filename = format!("{}_sandbox.pdf", profile);
+    Ok(PdfResponse::with_filename(placeholder_pdf(&profile), filename))
+}
+
+/// `GET /intake/email-address` in the sandbox: a placeholder address, since
+/// the real endpoint creates one on first use.
+pub fn intake_address() -> Json<DataResponse<IntakeAddress>> {
+    Json(DataResponse::success(
+        "Sandbox: mail to this address is not imported".to_string(),
+        IntakeAddress {
+            address: email_intake::intake_address("sandbox"),
+            enabled: false,
+        },
+        None,
+    ))
+}
+
+/// `GET /calendar/feed` in the sandbox: a feed URL that serves nothing, since
+/// the real endpoint creates the token on first use.
+pub fn calendar_feed(proxy: &ProxyInfo) -> Json<DataResponse<CalendarFeed>> {
+    let url = format!("{}/calendar/sandbox/tasks.ics", proxy.public_base_url());
+    let webcal_url = match url.split_once("://") {
+        Some((_, rest)) => format!("webcal://{}", rest),
+        None => url.clone(),
+    };
+    Json(DataResponse::success(
+        "Sandbox: this feed URL serves no calendar".to_string(),
+        CalendarFeed {
+            url,
+            webcal_url,
+            refresh_interval_days: review_calendar::refresh_interval_days(),
+        },
+        None,
+    ))
+}
+
+/// `GET /referral/my-link` in the sandbox: the caller's link, with no
+/// referral counted.
+pub fn referral_link(auth: &AuthenticatedUser) -> Json<ReferralLinkResponse> {
+    let referral_code = auth.tenant_name().to_string();
+    Json(ReferralLinkResponse {
+        success: true,
+        data: ReferralLinkData {
+            referral_url: format!("https://cvenom.com?ref={}", referral_code),
+            referral_code,
+            total_referrals: 0,
+            credited_referrals: 0,
+            credits_earned: 0,
+        },
+    })
+}
+
+/// `GET /sandbox/outputs/<file>`: the placeholder a sandbox generation links to.
+pub fn output_file(file: PathBuf) -> PdfResponse {
+    let filename = file
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "sandbox.pdf".to_string());
+    let stem = filename.trim_end_matches(".pdf").to_string();
+    PdfResponse::with_filename(placeholder_pdf(&stem), filename)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sandbox_requests_are_routed_by_method() {
+        assert_eq!(
+            sandbox_path("/sandbox/generate", "/", false, true).as_deref(),
+            Some("/sandbox/generate")
+        );
+        assert_eq!(
+            sandbox_path("/sandbox/profiles", "/", false, false).as_deref(),
+            Some("/profiles")
+        );
+        assert_eq!(
+            sandbox_path("/cv-api/create", "/cv-api", true, true).as_deref(),
+            Some("/cv-api/sandbox/create")
+        );
+        assert_eq!(sandbox_path("/generate", "/", false, true), None);
+
+        // Reads with side effects never reach the real endpoint.
+        assert_eq!(
+            sandbox_path("/cv-api/profiles/jane/latest-pdf", "/cv-api", true, false).as_deref(),
+            Some("/cv-api/sandbox/profiles/jane/latest-pdf")
+        );
+        assert_eq!(sandbox_path("/me", "/", true, false).as_deref(), Some("/me"));
+        assert_eq!(
+            sandbox_path("/sandbox/outputs/jane_en.pdf", "/", false, false).as_deref(),
+            Some("/sandbox/outputs/jane_en.pdf")
+        );
+        assert_eq!(sandbox_path("/outputs/jane_en.pdf", "/", false, false), None);
+        assert_eq!(sandbox_path("/sandboxes", "/", false, true), None);
+        assert_eq!(
+            sandbox_path("/calendar/feed", "/", true, false).as_deref(),
+            Some("/sandbox/calendar/feed")
+        );
+    }
+
+    #[test]
+    fn only_mutations_with_a_real_route_are_faked() {
+        assert!(route_matches("/cv-api/profiles/<name>/tags", "/cv-api/profiles/jane/tags"));
+        assert!(route_matches("/files/<path..>", "/files/a/b.typ"));
+        assert!(route_matches("/", "/"));
+        assert!(!route_matches("/cv-api/profiles/<name>/tags", "/cv-api/profiles/jane"));
+        assert!(!route_matches("/generate", "/generat"));
+        assert!(!route_matches("/generate", "/generate/async"));
+    }
+
+    #[test]
+    fn placeholder_pdf_has_a_valid_xref() {
+        let pdf = placeholder_pdf("jane (en)");
+        let text = String::from_utf8(pdf).unwrap();
+        assert!(text.starts_with("%PDF-1.4\n") && text.ends_with("%%EOF\n"));
+        let tail = text.rsplit("startxref\n").next().unwrap();
+        let startxref: usize = tail.lines().next().unwrap().parse().unwrap();
+        assert!(text[startxref..].starts_with("xref\n"));
+        for (i, line) in text[startxref..].lines().skip(3).take(5).enumerate() {
+            let offset: usize = line[..10].parse().unwrap();
+            assert!(text[offset..].starts_with(&format!("{} 0 obj", i + 1)));
+        }
+        assert!(text.contains("(Sandbox: janeen was not generated)"));
+    }
+}
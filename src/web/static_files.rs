@@ -0,0 +1,57 @@
+// src/web/static_files.rs
+//! Optional hosting of the built studio frontend.
+//!
+//! When `CVENOM_STATIC_DIR` points at the frontend build output, its files are
+//! served under `/` and any other extension-less GET falls back to
+//! `index.html` so client-side routes survive a reload. API routes always win
+//! (they rank higher); set `CVENOM_BASE_PATH=/api` to keep API and page paths
+//! from overlapping.
+
+use rocket::fs::{FileServer, NamedFile};
+use rocket::{get, routes, State};
+use std::path::PathBuf;
+
+/// Rank of the file server — after every API route. The SPA fallback below
+/// uses rank 30 so real files are found first.
+const FILE_SERVER_RANK: isize = 20;
+
+pub struct StaticDir(pub PathBuf);
+
+/// `CVENOM_STATIC_DIR`, if set and non-empty.
+pub fn static_dir() -> Option<PathBuf> {
+    std::env::var("CVENOM_STATIC_DIR")
+        .ok()
+        .filter(|v| !v.trim().is_empty())
+        .map(PathBuf::from)
+}
+
+/// Serve `index.html` for client-side routes. Paths with an extension are
+/// missing assets and stay 404s instead of returning HTML.
+#[get("/<path..>", rank = 30)]
+pub async fn spa_fallback(path: PathBuf, dir: &State<StaticDir>) -> Option<NamedFile> {
+    if path.extension().is_some() {
+        return None;
+    }
+    NamedFile::open(dir.0.join("index.html")).await.ok()
+}
+
+/// Mount the frontend bundle when `CVENOM_STATIC_DIR` is configured.
+pub fn mount(rocket: rocket::Rocket<rocket::Build>) -> rocket::Rocket<rocket::Build> {
+    let Some(dir) = static_dir() else {
+        return rocket;
+    };
+    // FileServer panics on a missing directory; startup validation reports it.
+    if !dir.is_dir() {
+        graflog::app_log!(
+            error,
+            "CVENOM_STATIC_DIR {} is not a directory — frontend not served",
+            dir.display()
+        );
+        return rocket;
+    }
+    graflog::app_log!(info, "Serving studio frontend from {}", dir.display());
+    rocket
+        .mount("/", FileServer::from(&dir).rank(FILE_SERVER_RANK))
+        .mount("/", routes![spa_fallback])
+        .manage(StaticDir(dir))
+}
@@ -0,0 +1,63 @@
+// src/web/tls.rs
+//! Standalone HTTPS with certificate hot reload.
+//!
+//! Rocket 0.5 reads the certificate once at launch, so "reload" means: watch
+//! the cert/key files, and when a renewal lands (certbot, acme.sh, …) shut the
+//! current instance down gracefully and launch a fresh one with the new files.
+//! `start_web_server` drives the relaunch loop; in-flight requests get
+//! Rocket's shutdown grace period.
+
+use crate::core::config_manager::TlsSettings;
+use graflog::app_log;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Point Rocket's TLS config at the certificate files.
+pub fn apply(
+    rocket: rocket::Rocket<rocket::Build>,
+    tls: &TlsSettings,
+) -> rocket::Rocket<rocket::Build> {
+    let figment = rocket
+        .figment()
+        .clone()
+        .merge(("tls.certs", tls.cert_path.display().to_string()))
+        .merge(("tls.key", tls.key_path.display().to_string()));
+    rocket.configure(figment)
+}
+
+/// Size + mtime of both files; changes whenever either is replaced.
+fn fingerprint(tls: &TlsSettings) -> Option<u64> {
+    let mut hasher = DefaultHasher::new();
+    for path in [&tls.cert_path, &tls.key_path] {
+        let meta = std::fs::metadata(path).ok()?;
+        meta.len().hash(&mut hasher);
+        meta.modified().ok()?.hash(&mut hasher);
+    }
+    Some(hasher.finish())
+}
+
+/// Poll the certificate files and trigger `shutdown` once they change,
+/// setting `reload` so the caller relaunches instead of exiting. A half
+/// written renewal (one file missing) is ignored until both are readable.
+pub async fn watch_for_renewal(
+    tls: TlsSettings,
+    shutdown: rocket::Shutdown,
+    reload: Arc<AtomicBool>,
+) {
+    let initial = fingerprint(&tls);
+    let mut interval = tokio::time::interval(Duration::from_secs(tls.reload_interval_secs.max(5)));
+    interval.tick().await;
+    loop {
+        interval.tick().await;
+        let current = fingerprint(&tls);
+        if current.is_some() && current != initial {
+            app_log!(info, "[tls] Certificate change detected — reloading");
+            reload.store(true, Ordering::SeqCst);
+            shutdown.notify();
+            return;
+        }
+    }
+}
@@ -2,15 +2,24 @@
 
 use rocket::form::FromForm;
 use rocket::fs::TempFile;
-use rocket::http::ContentType;
+use rocket::http::{ContentType, Status};
+use rocket::request::{FromRequest, Outcome};
 use rocket::response::{self, Responder};
 use rocket::serde::{Deserialize, Serialize};
 use rocket::{Request, Response};
 use std::path::PathBuf;
 
+use crate::core::{request_cancel, upload_sessions};
+
 pub struct PdfResponse {
     pub data: Vec<u8>,
     pub filename: Option<String>,
+    /// Sent as `X-Generation-Id` when known.
+    pub generation_id: Option<String>,
+    /// Sent as `X-Cache: HIT|MISS` when the handler knows whether it recompiled.
+    pub cache_hit: Option<bool>,
+    /// Sent as `X-Report-Signature` for signed reports.
+    pub signature: Option<String>,
 }
 
 impl PdfResponse {
@@ -18,29 +27,55 @@ impl PdfResponse {
         Self {
             data,
             filename: None,
+            generation_id: None,
+            cache_hit: None,
+            signature: None,
         }
     }
 
     pub fn with_filename(data: Vec<u8>, filename: String) -> Self {
         Self {
-            data,
             filename: Some(filename),
+            ..Self::new(data)
         }
     }
+
+    pub fn with_generation_id(mut self, generation_id: Option<String>) -> Self {
+        self.generation_id = generation_id;
+        self
+    }
+
+    pub fn with_cache_hit(mut self, hit: bool) -> Self {
+        self.cache_hit = Some(hit);
+        self
+    }
+
+    pub fn with_signature(mut self, signature: String) -> Self {
+        self.signature = Some(signature);
+        self
+    }
 }
 
 impl<'r> Responder<'r, 'static> for PdfResponse {
     fn respond_to(self, _: &'r Request<'_>) -> response::Result<'static> {
+        let filename = self.filename.unwrap_or_else(|| "cv.pdf".to_string());
         let mut binding = Response::build();
         let mut response = binding
             .header(ContentType::PDF)
+            .raw_header(
+                "Content-Disposition",
+                crate::utils::content_disposition(&filename),
+            )
             .sized_body(self.data.len(), std::io::Cursor::new(self.data));
 
-        if let Some(filename) = self.filename {
-            response = response.raw_header(
-                "Content-Disposition",
-                format!("attachment; filename=\"{}\"", filename),
-            );
+        if let Some(id) = self.generation_id {
+            response = response.raw_header("X-Generation-Id", id);
+        }
+        if let Some(hit) = self.cache_hit {
+            response = response.raw_header("X-Cache", if hit { "HIT" } else { "MISS" });
+        }
+        if let Some(signature) = self.signature {
+            response = response.raw_header("X-Report-Signature", signature);
         }
 
         response.ok()
@@ -67,13 +102,70 @@ impl<'r> Responder<'r, 'static> for DocxResponse {
             ))
             .raw_header(
                 "Content-Disposition",
-                format!("attachment; filename=\"{}\"", self.filename),
+                crate::utils::content_disposition(&self.filename),
+            )
+            .sized_body(self.data.len(), std::io::Cursor::new(self.data))
+            .ok()
+    }
+}
+
+pub struct ZipResponse {
+    pub data: Vec<u8>,
+    pub filename: String,
+}
+
+impl ZipResponse {
+    pub fn new(data: Vec<u8>, filename: String) -> Self {
+        Self { data, filename }
+    }
+}
+
+impl<'r> Responder<'r, 'static> for ZipResponse {
+    fn respond_to(self, _: &'r Request<'_>) -> response::Result<'static> {
+        Response::build()
+            .header(ContentType::ZIP)
+            .raw_header(
+                "Content-Disposition",
+                crate::utils::content_disposition(&self.filename),
             )
             .sized_body(self.data.len(), std::io::Cursor::new(self.data))
             .ok()
     }
 }
 
+/// Image bytes with client-cache headers. The ETag is the variant cache key,
+/// so it changes whenever the source picture is replaced; a request whose
+/// `If-None-Match` holds it gets a bodiless 304.
+pub struct ImageResponse {
+    pub data: Vec<u8>,
+    pub content_type: ContentType,
+    pub etag: String,
+}
+
+impl<'r> Responder<'r, 'static> for ImageResponse {
+    fn respond_to(self, req: &'r Request<'_>) -> response::Result<'static> {
+        let etag = format!("\"{}\"", self.etag);
+        let not_modified = req.headers().get("If-None-Match").any(|value| {
+            value
+                .split(',')
+                .map(|tag| tag.trim().trim_start_matches("W/"))
+                .any(|tag| tag == etag || tag == "*")
+        });
+        let mut response = Response::build();
+        response
+            .raw_header("Cache-Control", "private, max-age=86400")
+            .raw_header("ETag", etag)
+            .raw_header("Vary", "Accept");
+        if not_modified {
+            return response.status(Status::NotModified).ok();
+        }
+        response
+            .header(self.content_type)
+            .sized_body(self.data.len(), std::io::Cursor::new(self.data))
+            .ok()
+    }
+}
+
 #[derive(Serialize)]
 #[serde(crate = "rocket::serde")]
 pub struct ErrorResponse {
@@ -97,9 +189,194 @@ pub struct ChangeLanguageRequest {
     pub from_lang: Option<String>,
 }
 
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct SetProfileTypeRequest {
+    /// `self` or `collaborator`.
+    pub profile_type: String,
+}
+
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct PinTemplateRequest {
+    pub template: String,
+    /// Version to pin; omit or `null` to follow the current version again.
+    pub version: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct ApprovalRequest {
+    /// Reviewer feedback; required when rejecting.
+    pub comment: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct PutSecretRequest {
+    /// `smtp_credential`, `webhook_secret`, `signing_key`, `api_token` or
+    /// `other`.
+    pub kind: String,
+    pub value: String,
+}
+
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct SetMemberRoleRequest {
+    pub email: String,
+    /// `member` or `editor`.
+    pub role: String,
+}
+
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct RenameTenantRequest {
+    pub new_name: String,
+}
+
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct BulkProvisionRequest {
+    /// Email domain of the company, e.g. `acme.ch`.
+    pub domain: String,
+    /// Name of the domain tenant, used when it does not exist yet.
+    pub tenant_name: String,
+    #[serde(default)]
+    pub members: Vec<crate::core::bulk_provisioning::BulkMember>,
+    /// Members as CSV (`email,name,role,scaffold`), instead of or on top of
+    /// `members`.
+    #[serde(default)]
+    pub csv: Option<String>,
+    /// Validate and report without writing anything.
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct BenchmarkRequest {
+    /// Compilations to run (default 20, max 200).
+    pub runs: Option<usize>,
+    /// Compilations in parallel (default: CPU cores, max 32).
+    pub concurrency: Option<usize>,
+    /// Benchmark one template instead of every healthy one.
+    pub template: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct ApprovalPolicyRequest {
+    /// A member's email address or the tenant's domain.
+    pub tenant: String,
+    /// `off`, `watermark` or `block`.
+    pub policy: String,
+}
+
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct AiBudgetRequest {
+    /// A member's email address or the tenant's domain.
+    pub tenant: String,
+    /// `analysis`, `optimization` or `translation`.
+    pub feature: String,
+    /// Successful calls allowed per month; `null` for the server default.
+    pub monthly_calls: Option<i64>,
+}
+
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct TypstLintLevelRequest {
+    /// A member's email address or the tenant's domain.
+    pub tenant: String,
+    /// `off`, `warn` or `block`.
+    pub level: String,
+}
+
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct IntegrityCheckRequest {
+    pub email: String,
+    /// Run the Typst compile check (default true); it is the slow part.
+    pub compile: Option<bool>,
+}
+
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct SanitizeProfilesRequest {
+    pub email: String,
+    /// Apply the renames; omit for a dry run.
+    #[serde(default)]
+    pub execute: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct ConsistencyCheckRequest {
+    pub email: String,
+    /// Repair the mismatches; omit for a dry run.
+    #[serde(default)]
+    pub execute: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct LanguageFilesCleanupRequest {
+    pub email: String,
+    /// Delete the redundant files; omit for a dry run.
+    #[serde(default)]
+    pub execute: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct SchemaUpgradeRequest {
+    pub email: String,
+    /// Rewrite the legacy files; omit for a dry run.
+    #[serde(default)]
+    pub execute: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct TenantRegionRequest {
+    /// Tenant folder name.
+    pub tenant: String,
+    /// A region of `DATA_REGIONS`, or `default`.
+    pub region: String,
+}
+
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct TenantArchiveRequest {
+    /// Email of the (email) tenant to archive or restore.
+    pub email: String,
+}
+
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct RegenerationRequest {
+    /// Tenant folder name.
+    pub tenant: String,
+    /// Only regenerate CVs last generated with this template.
+    #[serde(default)]
+    pub template: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct DataKeyRotationRequest {
+    /// Tenant folder name.
+    pub tenant: String,
+    /// Rewrite the tenant's files with the new key right away.
+    #[serde(default)]
+    pub reencrypt: bool,
+}
+
 // OptimizeCvRequest / SaveOptimizedRequest are defined in cv_handlers and re-exported.
 pub use crate::web::handlers::cv_handlers::OptimizeCvRequest;
 pub use crate::web::handlers::cv_handlers::SaveOptimizedRequest;
+pub use crate::web::handlers::cv_handlers::TranslateCvRequest;
+pub use crate::types::response::{OptimizeResponse, TranslateResponse};
 
 #[derive(Serialize)]
 #[serde(crate = "rocket::serde")]
@@ -150,6 +427,29 @@ pub struct GenerateRequest {
     /// logo override the profile's defaults for this generation. Absent /
     /// "default" / empty = no brand (current behavior).
     pub brand_slug: Option<String>,
+    /// Generation features to enable (`photo`, `logo`, `projects`,
+    /// `skill_matrix`, `qr_code`, `client_logos`). Absent = everything the
+    /// template supports.
+    pub features: Option<Vec<String>>,
+    /// Fail when an image (photo, logo, image slot) is unusable instead of
+    /// generating without it and reporting it in `warnings`.
+    pub strict_assets: Option<bool>,
+    /// Check every file the template and profile reference before compiling
+    /// and fail with `MISSING_ASSETS`, listing all of them.
+    pub strict: Option<bool>,
+    /// `pdf` (default), `docx` or `html`. A DOCX or HTML document is returned
+    /// as the file itself.
+    pub format: Option<String>,
+    /// Several languages in one request, e.g. `["en", "fr"]`; replaces `lang`.
+    /// PDF only.
+    pub langs: Option<Vec<String>>,
+    /// With `langs`: return the PDFs as one ZIP instead of links to each.
+    pub archive: Option<bool>,
+    /// Mark the PDF as a draft: DRAFT across every page and the generation
+    /// time at the foot. PDF only.
+    pub draft: Option<bool>,
+    /// Export as PDF/A-2b, for archiving. PDF only.
+    pub pdf_a: Option<bool>,
 }
 
 #[derive(Serialize)]
@@ -162,8 +462,14 @@ pub struct GeneratePdfResponse {
     pub download_url: String,
     pub filename: String,
     pub profile: String,
+    /// Requested options the template could not honour and images left out.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub conversation_id: Option<String>,
+    /// Sent as `Cache-Status` by `/generate`: the file was not recompiled.
+    #[serde(skip)]
+    pub cache_hit: bool,
 }
 
 #[derive(Deserialize)]
@@ -183,6 +489,16 @@ pub struct CvUploadForm<'f> {
     pub cv_file: TempFile<'f>,
 }
 
+/// Body of `POST /uploads`, which opens a resumable upload.
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct CreateUploadRequest {
+    pub filename: String,
+    /// Total size in bytes.
+    pub size: u64,
+    pub content_type: Option<String>,
+}
+
 /// Multipart body for `POST /brands/<slug>/logo` — just the file; the slug is
 /// in the URL path so we don't duplicate it here.
 #[derive(FromForm)]
@@ -190,6 +506,18 @@ pub struct BrandLogoUploadForm<'f> {
     pub file: TempFile<'f>,
 }
 
+/// Multipart body for `POST /templates/upload`: a ZIP of the template.
+#[derive(FromForm)]
+pub struct TemplateUploadForm<'f> {
+    pub file: TempFile<'f>,
+}
+
+/// Multipart body for `POST /profiles/<name>/assets/<slot>`.
+#[derive(FromForm)]
+pub struct AssetUploadForm<'f> {
+    pub file: TempFile<'f>,
+}
+
 #[derive(Serialize)]
 #[serde(crate = "rocket::serde")]
 pub struct CreateProfileResponse {
@@ -226,6 +554,15 @@ pub struct TemplateInfo {
     pub description: String,
     pub photo_recommended: bool,
     pub shows_logo: bool,
+    /// Current version from the template manifest.
+    pub version: String,
+    /// Versions a profile can be pinned to, oldest first.
+    pub versions: Vec<String>,
+    /// Extra images the template renders, uploaded via
+    /// `POST /profiles/<name>/assets/<slot>`.
+    pub image_slots: Vec<crate::core::image_slots::ImageSlot>,
+    /// `format` values `/generate` accepts for this template.
+    pub formats: Vec<String>,
 }
 
 #[derive(Serialize)]
@@ -243,6 +580,12 @@ pub struct UserInfo {
     pub name: Option<String>,
     pub picture: Option<String>,
     pub tenant_name: String,
+    /// The user's own profile, provisioned on first login.
+    pub self_profile: Option<String>,
+    /// True until the user edits their provisioned profile.
+    pub needs_onboarding: bool,
+    /// `member` edits only `self_profile`; `editor` edits every profile.
+    pub role: &'static str,
 }
 
 #[derive(Serialize)]
@@ -260,12 +603,31 @@ pub struct SaveFileRequest {
     pub content: String,
 }
 
+#[derive(Clone)]
 pub struct ServerConfig {
     pub data_dir: PathBuf,
     pub output_dir: PathBuf,
     pub templates_dir: PathBuf,
 }
 
+impl ServerConfig {
+    /// Where `person`'s generated files go: `<output_dir>/<tenant>/<user>/<person>`.
+    pub fn person_output_dir(&self, email: &str, person: &str) -> PathBuf {
+        crate::core::database::get_tenant_output_path(email, &self.output_dir).join(person)
+    }
+
+    /// `/outputs/...` path serving `file`, which must live under the output
+    /// root of its tenant's region.
+    pub fn output_url_path(&self, file: &std::path::Path) -> String {
+        let relative = crate::core::data_residency::output_relative(&self.output_dir, file);
+        let segments: Vec<String> = relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy().to_string())
+            .collect();
+        format!("/outputs/{}", segments.join("/"))
+    }
+}
+
 // NEW STANDARD RESPONSE TYPES FOR V2 API
 
 #[derive(Serialize)]
@@ -457,4 +819,55 @@ impl StandardErrorResponse {
             conversation_id,
         }
     }
+
+    /// The client cancelled the request; see [`crate::core::request_cancel`].
+    pub fn cancelled(conversation_id: Option<String>) -> Self {
+        Self::new(
+            "Request cancelled".to_string(),
+            "REQUEST_CANCELLED".to_string(),
+            vec![],
+            conversation_id,
+        )
+    }
+}
+
+/// The `X-Request-Id` a client tags a cancellable request with; see
+/// [`crate::core::request_cancel`].
+pub struct ClientRequestId(pub Option<String>);
+
+impl ClientRequestId {
+    /// Make this request cancellable by `email` until the registration drops.
+    pub fn register(&self, email: &str) -> request_cancel::Registration {
+        request_cancel::register(email, self.0.as_deref())
+    }
+}
+
+/// The `Upload-Offset` a chunk of a resumable upload starts at; see
+/// [`crate::core::upload_sessions`]. `None` when absent or not a number.
+pub struct UploadOffset(pub Option<u64>);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for UploadOffset {
+    type Error = std::convert::Infallible;
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        Outcome::Success(UploadOffset(
+            req.headers()
+                .get_one(upload_sessions::OFFSET_HEADER)
+                .and_then(|offset| offset.trim().parse().ok()),
+        ))
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for ClientRequestId {
+    type Error = std::convert::Infallible;
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        Outcome::Success(ClientRequestId(
+            req.headers()
+                .get_one(request_cancel::REQUEST_ID_HEADER)
+                .map(|id| id.trim().to_string()),
+        ))
+    }
 }
@@ -1,15 +1,70 @@
 // src/workspace.rs
-use crate::config::CvConfig;
-use crate::core::TemplateEngine;
+use crate::config::{CvConfig, OutputFormat};
+use crate::core::experiences_fallback::{self, experiences_file, Fallback};
+use crate::core::template_engine::SHARED_FILES;
+use crate::core::{
+    asset_check, client_logos, data_encryption, html_export, image_slots, profile_schema,
+    template_features, typst_compiler, TemplateEngine,
+};
 use graflog::app_log;
 
 use anyhow::{Context, Result};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
 use std::{fs, process::Command};
 
+/// Entry point compiled instead of `main.typ` when a watermark is set.
+const WATERMARK_FILE: &str = "watermarked.typ";
+/// Typst's HTML export, converted to DOCX with pandoc.
+const DOCX_SOURCE_FILE: &str = "cv.html";
+/// Experiences of each language compiled from one workspace, staged as
+/// `<prefix><lang>.typ` and swapped in as `experiences.typ` per compilation.
+const STAGED_EXPERIENCES_PREFIX: &str = "staged_experiences_";
+const DEFAULT_COMPILE_TIMEOUT_SECS: u64 = 120;
+
+/// How long one CV may compile (`GENERATION_TIMEOUT_SECS`, default 120)
+/// before its generation fails.
+fn compile_timeout() -> Duration {
+    let secs = std::env::var("GENERATION_TIMEOUT_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(DEFAULT_COMPILE_TIMEOUT_SECS);
+    Duration::from_secs(secs.max(1))
+}
+
+/// An image the CV would show is unusable and `strict_assets` is set.
+#[derive(Debug)]
+pub struct InvalidAsset {
+    pub asset: String,
+    pub reason: String,
+}
+
+impl std::fmt::Display for InvalidAsset {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} can't be used: {}", self.asset, self.reason)
+    }
+}
+
+impl std::error::Error for InvalidAsset {}
+
+/// A DOCX was requested but pandoc is not installed.
+#[derive(Debug)]
+pub struct DocxUnavailable;
+
+impl std::fmt::Display for DocxUnavailable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "DOCX export is not available on this server (pandoc is not installed)")
+    }
+}
+
+impl std::error::Error for DocxUnavailable {}
+
 pub struct WorkspaceManager<'a> {
     config: &'a CvConfig,
     template_engine: &'a TemplateEngine,
+    /// Images left out of the CV, with the reason.
+    skipped_assets: Mutex<Vec<String>>,
 }
 
 impl<'a> WorkspaceManager<'a> {
@@ -17,9 +72,37 @@ impl<'a> WorkspaceManager<'a> {
         Self {
             config,
             template_engine,
+            skipped_assets: Mutex::new(Vec::new()),
         }
     }
 
+    /// Images `prepare_workspace` left out, e.g. "Profile photo skipped:
+    /// Image file is empty".
+    pub fn skipped_assets(&self) -> Vec<String> {
+        self.skipped_assets
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    /// Leave `asset` out of the CV, or fail with [`InvalidAsset`] when the
+    /// caller asked for strict assets.
+    fn skip_asset(&self, asset: &str, reason: &str) -> Result<()> {
+        if self.config.strict_assets {
+            return Err(InvalidAsset {
+                asset: asset.to_string(),
+                reason: reason.to_string(),
+            }
+            .into());
+        }
+        app_log!(warn, "Skipping {}: {}", asset, reason);
+        self.skipped_assets
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .push(format!("{} skipped: {}", asset, reason));
+        Ok(())
+    }
+
     pub async fn prepare_workspace(&self) -> Result<()> {
         app_log!(info, "Preparing workspace in tmp_workspace/...");
 
@@ -30,10 +113,12 @@ impl<'a> WorkspaceManager<'a> {
                 .context("Failed to change to temporary workspace")?;
 
             self.copy_profile_files()?;
+            self.copy_experiences(&self.config.lang, Path::new("experiences.typ"))
+                .await?;
             self.copy_logo_files()?;
 
             // Copy shared Typst utilities into the workspace
-            for shared_file in &["font_config.typ", "common.typ"] {
+            for shared_file in SHARED_FILES {
                 let source = self.config.templates_dir.join(shared_file);
                 if source.exists() {
                     fs::copy(&source, PathBuf::from(shared_file))?;
@@ -41,6 +126,8 @@ impl<'a> WorkspaceManager<'a> {
             }
 
             self.prepare_template_files().await?;
+            self.copy_image_slots()?;
+            self.copy_client_logos()?;
 
             Ok(())
         };
@@ -96,16 +183,12 @@ impl<'a> WorkspaceManager<'a> {
             config_source.exists()
         );
 
-        fs::copy(&config_source, &config_dest).context("Failed to copy profile config")?;
-
-        // Copy experiences — optional: some document types (e.g. portfolio) don't use it
-        let exp_source = self.config.profile_experiences_path();
-        let exp_dest = PathBuf::from("experiences.typ");
-        if exp_source.exists() {
-            fs::copy(&exp_source, &exp_dest).context("Failed to copy profile experiences")?;
-        } else {
-            app_log!(info, "No experiences file found at {} — skipping (not required for this document type)", exp_source.display());
-        }
+        // Templates read the personal fields top-level, so a legacy layout
+        // is compiled from its upgraded form.
+        let params = data_encryption::read_to_string(&config_source)
+            .context("Failed to copy profile config")?;
+        fs::write(&config_dest, profile_schema::canonicalize(&params).as_bytes())
+            .context("Failed to copy profile config")?;
 
         // Copy profile image with validation
         let profile_image_png = self.config.profile_image_path();
@@ -146,7 +229,7 @@ impl<'a> WorkspaceManager<'a> {
                     app_log!(info, "✅ Copied valid profile image as {}", dest_name);
                 }
                 Err(error_msg) => {
-                    app_log!(info, "❌ Skipping corrupted image: {}", error_msg);
+                    self.skip_asset("Profile photo", &error_msg)?;
                 }
             }
         } else {
@@ -193,19 +276,27 @@ impl<'a> WorkspaceManager<'a> {
                 app_log!(info, "Brand logo copied successfully");
                 return Ok(());
             } else {
-                app_log!(
-                    warn,
-                    "Brand logo at {:?} is not a valid PNG — skipping and falling back to profile/tenant logo",
-                    brand_logo
-                );
+                self.skip_asset("Brand logo", "not a valid PNG")?;
+            }
+        }
+        // A broken logo is reported only when no other logo takes its place.
+        let mut broken = None;
+        for (source, asset, label) in [
+            (&profile_logo_source, "Profile logo", "Profile"),
+            (&tenant_logo_source, "Account logo", "Tenant"),
+        ] {
+            if !source.exists() {
+                continue;
+            }
+            if is_valid_png(source) {
+                fs::copy(source, &logo_dest)?;
+                app_log!(info, "{} logo copied successfully", label);
+                return Ok(());
             }
+            broken.get_or_insert(asset);
         }
-        if profile_logo_source.exists() && is_valid_png(&profile_logo_source) {
-            fs::copy(&profile_logo_source, &logo_dest)?;
-            app_log!(info, "Profile logo copied successfully");
-        } else if tenant_logo_source.exists() && is_valid_png(&tenant_logo_source) {
-            fs::copy(&tenant_logo_source, &logo_dest)?;
-            app_log!(info, "Tenant logo copied successfully");
+        if let Some(asset) = broken {
+            self.skip_asset(asset, "not a valid PNG")?;
         }
 
         Ok(())
@@ -213,10 +304,22 @@ impl<'a> WorkspaceManager<'a> {
 
     async fn prepare_template_files(&self) -> Result<()> {
         self.template_engine
-            .prepare_template_workspace(&self.config.template, &PathBuf::from("."))
+            .prepare_template_version_workspace(
+                &self.config.template,
+                self.config.template_version.as_deref(),
+                &PathBuf::from("."),
+            )
             .await
             .context("Failed to prepare template workspace")?;
 
+        self.template_engine
+            .check_dependencies(
+                &self.config.template,
+                &PathBuf::from("."),
+                &self.config.profile_data_dir(),
+            )
+            .await?;
+
         app_log!(
             info,
             "Workspace prepared with template: {}",
@@ -225,6 +328,59 @@ impl<'a> WorkspaceManager<'a> {
         Ok(())
     }
 
+    /// Copy the person's images for the template's declared slots. A slot
+    /// without a valid PNG is left out; the template renders without it.
+    fn copy_image_slots(&self) -> Result<()> {
+        let Some(template) = self.template_engine.get_template(&self.config.template) else {
+            return Ok(());
+        };
+        let person_dir = self.config.profile_data_dir();
+        for slot in image_slots::declared(&template.manifest) {
+            let source = image_slots::asset_path(&person_dir, &slot.name);
+            let Ok(bytes) = fs::read(&source) else {
+                continue;
+            };
+            if !image_slots::is_png(&bytes) {
+                self.skip_asset(&format!("Image '{}'", slot.name), "not a valid PNG")?;
+                continue;
+            }
+            fs::write(&slot.file, bytes)?;
+            app_log!(info, "Image slot '{}' copied to {}", slot.name, slot.file);
+        }
+        Ok(())
+    }
+
+    /// Copy the person's client logos when the `client_logos` feature is on.
+    fn copy_client_logos(&self) -> Result<()> {
+        if !self.enabled_features().iter().any(|f| f == "client_logos") {
+            return Ok(());
+        }
+        let (copied, invalid) =
+            client_logos::copy_to_workspace(&self.config.profile_data_dir(), &PathBuf::from("."))?;
+        for key in invalid {
+            self.skip_asset(&format!("Client logo '{}'", key), "not a valid PNG")?;
+        }
+        if !copied.is_empty() {
+            app_log!(info, "Client logos copied: {}", copied.join(", "));
+        }
+        Ok(())
+    }
+
+    /// Features requested for this generation, or all the template supports.
+    fn enabled_features(&self) -> Vec<String> {
+        match &self.config.features {
+            Some(features) => features.clone(),
+            None => self
+                .template_engine
+                .get_template(&self.config.template)
+                .map(|t| template_features::supported(&t.manifest))
+                .unwrap_or_default()
+                .into_iter()
+                .map(String::from)
+                .collect(),
+        }
+    }
+
     fn restore_directory_and_cleanup(&self, original_dir: &PathBuf) -> Result<()> {
         if let Err(restore_err) = std::env::set_current_dir(original_dir) {
             app_log!(
@@ -265,22 +421,138 @@ impl<'a> WorkspaceManager<'a> {
         Ok(())
     }
 
-    pub fn compile_cv(&self) -> Result<PathBuf> {
-        let output_path = PathBuf::from("..")
-            .join(&self.config.output_dir)
-            .join(format!(
-                "{}_{}_{}.pdf",
-                self.config.profile_name,
-                self.config.template.as_str(),
-                self.config.lang
-            ));
+    /// Write the experiences of `lang` to `dest`, or those of its fallback
+    /// (see [`experiences_fallback`]). Without either nothing is written:
+    /// some document types (e.g. portfolio) don't use experiences.
+    async fn copy_experiences(&self, lang: &str, dest: &Path) -> Result<()> {
+        let profile_dir = self.config.profile_data_dir();
+        let source = match experiences_fallback::resolve(&profile_dir, lang) {
+            Ok(None) => experiences_file(lang),
+            Ok(Some(Fallback::Language(other))) => {
+                app_log!(info, "No {} experiences, using the {} ones", lang, other);
+                experiences_file(&other)
+            }
+            Ok(Some(Fallback::Template)) => {
+                app_log!(info, "No {} experiences, using placeholder experiences", lang);
+                let starter = self.template_engine.starter_experiences().await?;
+                return fs::write(dest, starter).context("Failed to write placeholder experiences");
+            }
+            Err(missing) => {
+                app_log!(info, "{} — skipping (not required for this document type)", missing);
+                return Ok(());
+            }
+        };
+        data_encryption::copy_plain(&profile_dir.join(source), dest)
+            .with_context(|| format!("Failed to copy {} experiences", lang))
+    }
 
-        let mut cmd = Command::new("typst");
-        cmd.arg("compile").arg("main.typ").arg(&output_path);
-        cmd.arg("--input").arg(format!("lang={}", self.config.lang));
+    /// Copy the experiences of each of `langs` into the prepared workspace,
+    /// for [`compile_language`](Self::compile_language). A language without
+    /// experiences or fallback is skipped; compiling it then fails.
+    pub async fn stage_languages(&self, langs: &[String]) -> Result<()> {
+        for lang in langs {
+            let staged = PathBuf::from(format!("{}{}.typ", STAGED_EXPERIENCES_PREFIX, lang));
+            self.copy_experiences(lang, &staged).await?;
+        }
+        Ok(())
+    }
+
+    /// Compile the workspace in `lang`, staged by [`stage_languages`](Self::stage_languages).
+    pub async fn compile_language(&self, lang: &str) -> Result<PathBuf> {
+        let staged = PathBuf::from(format!("{}{}.typ", STAGED_EXPERIENCES_PREFIX, lang));
+        if !staged.exists() {
+            anyhow::bail!("No experiences_{}.typ in profile {}", lang, self.config.profile_name);
+        }
+        fs::copy(&staged, "experiences.typ").context("Failed to switch experiences")?;
+        self.compile_in(lang).await
+    }
+
+    pub async fn compile_cv(&self) -> Result<PathBuf> {
+        self.compile_in(&self.config.lang).await
+    }
+
+    async fn compile_in(&self, lang: &str) -> Result<PathBuf> {
+        let file_name = if self.config.watermark.is_some() {
+            crate::utils::draft_pdf_filename
+        } else {
+            crate::utils::generated_pdf_filename
+        };
+        let mut output_path = PathBuf::from("..").join(&self.config.output_dir).join(file_name(
+            &self.config.profile_name,
+            self.config.template.as_str(),
+            lang,
+        ));
+        let format = self.config.format;
+        if self.config.preview_ppi.is_some() {
+            output_path.set_extension("png");
+        } else {
+            output_path.set_extension(format.extension());
+        }
+
+        // The watermark wraps the template instead of relying on it: page
+        // settings made before the include apply to the whole document.
+        let main_file = match &self.config.watermark {
+            Some(text) => {
+                let plain = |s: &str| s.replace(['[', ']', '#', '\\'], "");
+                let footer = match &self.config.draft_generated_at {
+                    Some(at) => format!(
+                        " + place(bottom + center, dy: -12pt, \
+                         text(size: 8pt, fill: rgb(200, 0, 0, 160))[Draft generated {}])",
+                        plain(at)
+                    ),
+                    None => String::new(),
+                };
+                fs::write(
+                    WATERMARK_FILE,
+                    format!(
+                        "#set page(foreground: place(center + horizon, rotate(-45deg, \
+                         text(size: 96pt, weight: \"bold\", fill: rgb(200, 0, 0, 50))[{}])){})\n\
+                         #include \"main.typ\"\n",
+                        plain(text),
+                        footer
+                    ),
+                )
+                .context("Failed to write watermark wrapper")?;
+                WATERMARK_FILE
+            }
+            None => "main.typ",
+        };
+
+        let (target, output) = match (format, self.config.preview_ppi) {
+            (OutputFormat::Pdf, Some(ppi)) => (
+                output_path.clone(),
+                typst_compiler::Output::Png { ppi: ppi as f32 },
+            ),
+            (OutputFormat::Pdf, None) if self.config.pdf_a => {
+                (output_path.clone(), typst_compiler::Output::PdfA)
+            }
+            (OutputFormat::Pdf, None) => (output_path.clone(), typst_compiler::Output::Pdf),
+            (OutputFormat::Docx, _) => (
+                PathBuf::from(DOCX_SOURCE_FILE),
+                typst_compiler::Output::Html,
+            ),
+            (OutputFormat::Html, _) => (output_path.clone(), typst_compiler::Output::Html),
+        };
+
+        // `sys.inputs` of the document.
+        let mut inputs: Vec<(String, String)> = vec![("lang".to_string(), lang.to_string())];
+        inputs.extend(self.template_engine.text_inputs(lang));
+        inputs.extend(template_features::typst_inputs(&self.enabled_features()));
+        inputs.extend(self.config.inputs.iter().cloned());
+        let logos = client_logos::in_workspace(&PathBuf::from("."));
+        if !logos.is_empty() {
+            inputs.push((client_logos::LOGOS_DIR.to_string(), logos.join(",")));
+        }
 
         if PathBuf::from("company_logo.png").exists() {
-            cmd.arg("--input").arg("company_logo.png=company_logo.png");
+            inputs.push(("company_logo.png".to_string(), "company_logo.png".to_string()));
+        }
+        if let Some(template) = self.template_engine.get_template(&self.config.template) {
+            for slot in image_slots::declared(&template.manifest) {
+                if PathBuf::from(&slot.file).exists() {
+                    inputs.push((format!("image.{}", slot.name), slot.file.clone()));
+                }
+            }
         }
 
         // Add picture input only if a valid image was copied to the workspace.
@@ -295,13 +567,13 @@ impl<'a> WorkspaceManager<'a> {
         };
 
         if let Some(pic_file) = workspace_pic {
-            app_log!(info, "✅ Adding picture input to Typst command: {}", pic_file);
-            cmd.arg("--input").arg(format!("picture={}", pic_file));
+            app_log!(info, "✅ Adding picture input to Typst: {}", pic_file);
+            inputs.push(("picture".to_string(), pic_file.to_string()));
         } else {
             app_log!(info, "ℹ️  No profile image in workspace - generating without photo");
         }
 
-        // Forward branding to Typst as `--input k=v` flags. The resolver emits
+        // Forward branding to Typst as inputs. The resolver emits
         // only explicit overrides (and vibe-preset values); keys it omits fall
         // through to each template's literal defaults, so legacy profiles that
         // only set primary/secondary render unchanged.
@@ -363,23 +635,86 @@ impl<'a> WorkspaceManager<'a> {
 
             if let Some(styling) = styling {
                 for (k, v) in crate::core::branding::resolve(&styling) {
-                    cmd.arg("--input").arg(format!("{}={}", k, v));
+                    inputs.push((k.to_string(), v));
                 }
             }
         }
 
-        let output = cmd.output().context("Failed to execute typst command")?;
-
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            let stdout = String::from_utf8_lossy(&output.stdout);
-            anyhow::bail!(
-                "Typst compilation failed: stderr={}, stdout={}",
-                stderr,
-                stdout
-            );
+        let workspace = std::env::current_dir().context("Failed to locate the workspace")?;
+        if self.config.strict {
+            // Images left out while preparing count as problems too.
+            let mut problems = self.skipped_assets();
+            problems.extend(asset_check::check(&workspace, main_file));
+            if !problems.is_empty() {
+                return Err(asset_check::MissingAssets { problems }.into());
+            }
+        }
+        // Off the async workers: a pathological template must not stall them.
+        let compiled = typst_compiler::compile_with_metadata_timeout(
+            &workspace,
+            main_file,
+            inputs,
+            output,
+            self.config.pdf_metadata.clone(),
+            compile_timeout(),
+        )
+        .await?;
+        for warning in &compiled.warnings {
+            app_log!(warn, "Typst warning for {}: {}", self.config.profile_name, warning);
         }
+        fs::write(&target, compiled.bytes)
+            .with_context(|| format!("Failed to write {}", target.display()))?;
 
+        match format {
+            OutputFormat::Docx => convert_to_docx(&output_path)?,
+            OutputFormat::Html => inline_html_assets(&output_path)?,
+            OutputFormat::Pdf => {}
+        }
         Ok(output_path)
     }
 }
+
+/// Embed the pictures and the template's stylesheet in the HTML export at
+/// `output_path`, so it can be published on its own.
+fn inline_html_assets(output_path: &Path) -> Result<()> {
+    let html = fs::read_to_string(output_path).context("Failed to read HTML export")?;
+    let css = fs::read_to_string(html_export::STYLESHEET).ok();
+    let html = html_export::make_self_contained(&html, css.as_deref(), |source| {
+        let path = Path::new(source);
+        if path.is_absolute() || source.contains("..") {
+            return None;
+        }
+        fs::read(path).ok()
+    });
+    fs::write(output_path, html).context("Failed to write HTML export")
+}
+
+/// Convert the HTML export in the workspace to `output_path`, styled by the
+/// template's `reference.docx` when it ships one.
+fn convert_to_docx(output_path: &Path) -> Result<()> {
+    let mut cmd = Command::new("pandoc");
+    cmd.arg(DOCX_SOURCE_FILE)
+        .arg("--from")
+        .arg("html")
+        .arg("--to")
+        .arg("docx")
+        .arg("--resource-path")
+        .arg(".")
+        .arg("--output")
+        .arg(output_path);
+    if PathBuf::from("reference.docx").exists() {
+        cmd.arg("--reference-doc").arg("reference.docx");
+    }
+    let output = match cmd.output() {
+        Ok(output) => output,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Err(DocxUnavailable.into()),
+        Err(e) => return Err(e).context("Failed to execute pandoc"),
+    };
+    if !output.status.success() {
+        anyhow::bail!(
+            "DOCX conversion failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,22 @@
+#import "template.typ": dated_experience, experience_details
+
+#let get_work_experience() = {
+  dated_experience(
+    "Associate Professor",
+    date: "2020 – Present",
+    company: "University of Lausanne, Switzerland",
+    description: "Computational Biology group, eight researchers.",
+    content: [
+      #experience_details("Leads research on statistical models for single-cell atlases")
+      #experience_details("Teaches the master course in statistical genomics")
+    ]
+  )
+  dated_experience(
+    "Postdoctoral Fellow",
+    date: "2014 – 2020",
+    company: "EMBL, Germany",
+    content: [
+      #experience_details("Developed an open-source clustering toolkit used by 200+ labs")
+    ]
+  )
+}
@@ -1,5 +1,5 @@
 #import "font_config.typ": font_config, get_icon
-#import "common.typ": get_lang, join_dicts, get_default_icons, process_links, skill_label, nonempty
+#import "common.typ": get_lang, join_dicts, get_default_icons, process_links, skill_label, nonempty, localized
 
 // ── Palette ───────────────────────────────────────────────────────────────────
 #let rule_clr   = rgb("#90A4AE")   // soft separator (fixed)
@@ -15,7 +15,6 @@
 
 // ── Language helpers ───────────────────────────────────────────────────────────
 #let get_text(key) = {
-  let lang = get_lang()
   let texts = (
     "en": (
       "technical_skills":         "Methods & Tools",
@@ -54,7 +53,7 @@
       "summary":                  "Forschungsprofil",
     )
   )
-  texts.at(lang, default: texts.en).at(key, default: key)
+  localized(texts, key)
 }
 
 // ── Section heading — classic academic style ───────────────────────────────────
@@ -87,7 +86,7 @@
 }
 
 // ── Academic experience entry ──────────────────────────────────────────────────
-#let dated_experience(title, date: none, description: none, content: none, company: none) = {
+#let dated_experience(title, date: none, description: none, content: none, company: none, logo: none) = {
   grid(
     columns: (1fr, auto),
     align: (left + top, right + top),
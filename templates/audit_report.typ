@@ -0,0 +1,72 @@
+// templates/audit_report.typ
+// Tenant activity report for compliance teams. Data comes from report.json,
+// written by src/core/audit_log.rs next to this file.
+
+#let report = json("report.json")
+#let day(ts) = ts.slice(0, 10)
+#let moment(ts) = ts.slice(0, 10) + " " + ts.slice(11, 16)
+
+#let action_labels = (
+  cv_generated: "CV generated",
+  data_exported: "Data exported",
+  profile_deleted: "Profile deleted",
+  account_deleted: "Account deleted",
+  secret_changed: "Secret changed",
+  tenant_renamed: "Tenant renamed",
+  generation_deleted: "Generated CV deleted",
+)
+
+#set document(title: "Activity report — " + report.tenant)
+#set page(
+  paper: "a4",
+  margin: (x: 2cm, y: 2.2cm),
+  footer: context [
+    #set text(8pt, fill: luma(120))
+    Report #report.report_id
+    #h(1fr)
+    #counter(page).display("1 / 1", both: true)
+  ],
+)
+#set text(size: 10pt)
+
+#text(18pt, weight: "bold")[Activity report]
+#v(-0.4em)
+#text(12pt)[#report.tenant]
+
+#grid(
+  columns: (auto, 1fr),
+  column-gutter: 1em,
+  row-gutter: 0.5em,
+  [*Period*], [#day(report.from) to #day(report.to) (UTC, end exclusive)],
+  [*Generated*], [#moment(report.generated_at) UTC by #report.generated_by],
+  [*CV generations*], [#report.totals.generations],
+  [*Data exports*], [#report.totals.exports],
+  [*Deletions*], [#report.totals.deletions],
+)
+
+#v(1em)
+
+#if report.entries.len() == 0 [
+  _No recorded activity in this period._
+] else {
+  let rows = report.entries.map(e => (
+    moment(e.created_at),
+    e.user_email,
+    action_labels.at(e.action, default: e.action),
+    e.subject,
+  ))
+  table(
+    columns: (auto, auto, auto, 1fr),
+    stroke: 0.4pt + luma(180),
+    inset: 5pt,
+    table.header([*Date (UTC)*], [*User*], [*Action*], [*Subject*]),
+    ..rows.flatten(),
+  )
+}
+
+#v(1.5em)
+#block(stroke: 0.4pt + luma(180), inset: 8pt, width: 100%)[
+  #set text(8pt)
+  *Signature* (HMAC-SHA256 of the report data) \
+  #raw(report.digest)
+]
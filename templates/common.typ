@@ -6,6 +6,20 @@
 // ── Language helpers ───────────────────────────────────────────────────────────
 #let get_lang() = { sys.inputs.at("lang", default: "en") }
 
+// ── Central text registry ─────────────────────────────────────────────────────
+// The server injects the strings of templates/translations.toml for the
+// requested language as `--input text.<key>=…`. A template's own table wins for
+// the languages it lists, so its wording is kept; the registry covers every
+// other language and any key the template lacks. English (then `fallback`, then
+// the key itself) is the last resort.
+#let localized(texts, key, fallback: none) = {
+  let own = texts.at(get_lang(), default: (:))
+  if key in own { return own.at(key) }
+  let injected = sys.inputs.at("text." + key, default: none)
+  if injected != none { return injected }
+  texts.at("en", default: (:)).at(key, default: if fallback == none { key } else { fallback })
+}
+
 // ── Value helper ───────────────────────────────────────────────────────────────
 // True when the value is something we should render: not `none`, not an empty
 // string, not whitespace-only. Trimming matters because LLM imports sometimes
@@ -17,12 +31,23 @@
   v != none and not (type(v) == str and v.trim() == "")
 }
 
+// ── Client logos ───────────────────────────────────────────────────────────────
+// Logo of an experience's client (`logo: "acme"` in `dated_experience`), or
+// `none` when the `client_logos` feature is off or the person has no logo for
+// that key. The server lists the keys it copied in `--input client_logos=…`.
+#let client_logo(key, height: 1.6em) = {
+  let enabled = sys.inputs.at("feature.client_logos", default: "false") == "true"
+  let keys = sys.inputs.at("client_logos", default: "").split(",")
+  if enabled and key != none and key in keys {
+    box(height: height, image("client_logos/" + key + ".png", height: height))
+  }
+}
+
 // ── Skill subsection label translation ─────────────────────────────────────────
 // Translates skill keys (technical, programming_languages, …) used as labels
 // inside the Skills section. Falls back to a humanized version of the key if
 // unknown (replaces underscores with spaces and capitalizes).
 #let skill_label(key) = {
-  let lang = get_lang()
   let labels = (
     "en": (
       "technical": "Technical",
@@ -49,12 +74,10 @@
       "certifications": "Zertifizierungen",
     ),
   )
-  let dict = labels.at(lang, default: labels.en)
-  dict.at(key, default: {
-    // Humanize unknown keys: snake_case → "Snake Case"
-    let parts = key.split("_")
-    parts.map(p => if p.len() > 0 { upper(p.slice(0, 1)) + p.slice(1) } else { p }).join(" ")
-  })
+  // Humanize unknown keys: snake_case → "Snake Case"
+  let parts = key.split("_")
+  let humanized = parts.map(p => if p.len() > 0 { upper(p.slice(0, 1)) + p.slice(1) } else { p }).join(" ")
+  localized(labels, key, fallback: humanized)
 }
 
 // ── Dictionary merge ──────────────────────────────────────────────────────────
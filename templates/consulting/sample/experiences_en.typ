@@ -0,0 +1,22 @@
+#import "template.typ": dated_experience, experience_details
+
+#let get_work_experience() = {
+  dated_experience(
+    "Program Director — core banking migration",
+    date: "2022 – Present",
+    company: "Retail bank, France",
+    description: "Mission of 30 months, team of 45.",
+    content: [
+      #experience_details("Steered the migration of 4 million accounts without downtime")
+      #experience_details("Set up the program governance with the executive committee")
+    ]
+  )
+  dated_experience(
+    "Engagement Manager — claims digitalization",
+    date: "2019 – 2022",
+    company: "Insurer, Belgium",
+    content: [
+      #experience_details("Cut claim handling time by 35% with a new operating model")
+    ]
+  )
+}
@@ -1,5 +1,5 @@
 #import "font_config.typ": font_config, get_icon
-#import "common.typ": get_lang, join_dicts, get_default_icons, process_links, skill_label, nonempty
+#import "common.typ": get_lang, join_dicts, get_default_icons, process_links, skill_label, nonempty, localized, client_logo
 
 // ── Palette ───────────────────────────────────────────────────────────────────
 #let light_bg   = rgb("#EFF6FF")   // very light blue tint (fixed)
@@ -15,7 +15,6 @@
 
 // ── Language helpers ───────────────────────────────────────────────────────────
 #let get_text(key) = {
-  let lang = get_lang()
   let texts = (
     "en": (
       "technical_skills":         "Technical Skills",
@@ -54,7 +53,7 @@
       "certifications":           "Zertifizierungen",
     )
   )
-  texts.at(lang, default: texts.en).at(key, default: key)
+  localized(texts, key)
 }
 
 // ── Section heading — solid blue band ─────────────────────────────────────────
@@ -162,15 +161,18 @@
 }
 
 // ── Mission experience entry ───────────────────────────────────────────────────
-#let dated_experience(title, date: none, description: none, content: none, company: none) = {
+#let dated_experience(title, date: none, description: none, content: none, company: none, logo: none) = {
+  let mark = client_logo(logo)
   block(
     width: 100%,
     stroke: (left: 3pt + accent),
     inset: (left: 10pt, top: 4pt, bottom: 4pt, right: 4pt),
   )[
     #grid(
-      columns: (1fr, auto),
-      align: (left + top, right + top),
+      columns: (auto, 1fr, auto),
+      align: (left + top, left + top, right + top),
+      column-gutter: if mark != none { 8pt } else { 0pt },
+      if mark != none { mark } else { [] },
       [
         #if nonempty(title) [
           #text(size: 11pt, weight: "bold", fill: primary, title)
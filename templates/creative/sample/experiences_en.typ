@@ -0,0 +1,22 @@
+#import "template.typ": dated_experience, experience_details
+
+#let get_work_experience() = {
+  dated_experience(
+    "Lead Designer",
+    date: "2021 – Present",
+    company: "Studio Norr, Stockholm",
+    description: "Identity and product design for clients in culture and tech.",
+    content: [
+      #experience_details("Redesigned the identity of a national museum, print to signage")
+      #experience_details("Built a design system shared by four product teams")
+    ]
+  )
+  dated_experience(
+    "Product Designer",
+    date: "2016 – 2021",
+    company: "Fintech startup, Sweden",
+    content: [
+      #experience_details("Designed the mobile app onboarding, +20% activation")
+    ]
+  )
+}
@@ -1,5 +1,5 @@
 #import "font_config.typ": font_config, get_icon
-#import "common.typ": get_lang, join_dicts, get_default_icons, process_links, skill_label, nonempty
+#import "common.typ": get_lang, join_dicts, get_default_icons, process_links, skill_label, nonempty, localized
 
 // ── Palette ───────────────────────────────────────────────────────────────────
 #let primary    = rgb("#1C1C1E")   // near-black (fixed)
@@ -17,7 +17,6 @@
 
 // ── Language helpers ───────────────────────────────────────────────────────────
 #let get_text(key) = {
-  let lang = get_lang()
   let texts = (
     "en": (
       "technical_skills":         "Tools & Skills",
@@ -50,7 +49,7 @@
       "disciplines":              "Disziplinen",
     )
   )
-  texts.at(lang, default: texts.en).at(key, default: key)
+  localized(texts, key)
 }
 
 // ── Sidebar section heading ────────────────────────────────────────────────────
@@ -153,7 +152,7 @@
 }
 
 // ── Experience entry ───────────────────────────────────────────────────────────
-#let dated_experience(title, date: none, description: none, content: none, company: none) = {
+#let dated_experience(title, date: none, description: none, content: none, company: none, logo: none) = {
   grid(
     columns: (1fr, auto),
     align: (left + top, right + top),
@@ -0,0 +1,22 @@
+#import "template.typ": dated_experience, experience_details
+
+#let get_work_experience() = {
+  dated_experience(
+    "Senior Software Engineer",
+    date: "2020 – Present",
+    company: "Acme Corp, Switzerland",
+    description: "Cloud-native platform team.",
+    content: [
+      #experience_details("Designed and delivered microservices in Rust")
+      #experience_details("Led a team of 5 engineers across two time zones")
+    ]
+  )
+  dated_experience(
+    "Software Engineer",
+    date: "2015 – 2020",
+    company: "Startup SA, France",
+    content: [
+      #experience_details("Built React/Node.js full-stack application")
+    ]
+  )
+}
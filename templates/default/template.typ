@@ -1,6 +1,6 @@
 
 #import "font_config.typ": font_config, get_icon
-#import "common.typ": get_lang, join_dicts, get_default_icons, process_links, skill_label, nonempty
+#import "common.typ": get_lang, join_dicts, get_default_icons, process_links, skill_label, nonempty, localized
 // global variables
 // Resolve from user customization (--input primary_color=...) or fall back to brand defaults
 #let _u_primary = sys.inputs.at("primary_color",   default: none)
@@ -26,7 +26,6 @@
 
 // Language-specific text content
 #let get_text(key) = {
-  let lang = get_lang()
   let texts = (
     "en": (
       "technical_skills": "Technical Skills",
@@ -80,7 +79,7 @@
       "website": "www.mycompany.ch"
     )
   )
-  texts.at(lang, default: texts.en).at(key, default: key)
+  localized(texts, key)
 }
 
 /* function that applies a color to a link */
@@ -136,7 +135,7 @@
 }
 
 /* experience that has an optional date and an optional description */
-#let dated_experience(title, date: none, description: none, content: none, company: none) = {
+#let dated_experience(title, date: none, description: none, content: none, company: none, logo: none) = {
   [
     #block(
       stroke: (bottom: 0.5pt + default_primary_color),
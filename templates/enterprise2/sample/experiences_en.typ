@@ -0,0 +1,22 @@
+#import "template.typ": dated_experience, experience_details
+
+#let get_work_experience() = {
+  dated_experience(
+    "Lead Solution Architect",
+    date: "2019 – Present",
+    company: "Insurance group, Zurich",
+    description: "Integration platform for 12 business units.",
+    content: [
+      #experience_details("Defined the event-driven target architecture and its roadmap")
+      #experience_details("Moved 40 legacy interfaces to a Kafka backbone")
+    ]
+  )
+  dated_experience(
+    "Senior Developer",
+    date: "2012 – 2019",
+    company: "Bank, Basel",
+    content: [
+      #experience_details("Delivered the payments API used by the mobile banking app")
+    ]
+  )
+}
@@ -1,5 +1,5 @@
 #import "font_config.typ": font_config, get_icon
-#import "common.typ": get_lang, join_dicts, get_default_icons, process_links, skill_label, nonempty
+#import "common.typ": get_lang, join_dicts, get_default_icons, process_links, skill_label, nonempty, localized
 
 // ── Palette ───────────────────────────────────────────────────────────────────
 // User-customizable via `sys.inputs` (see core/branding.rs). Every default
@@ -22,7 +22,6 @@
 
 // ── Language helpers ───────────────────────────────────────────────────────────
 #let get_text(key) = {
-  let lang = get_lang()
   let texts = (
     "en": (
       "technical_skills":         "Technical Specializations",
@@ -70,7 +69,7 @@
       "cgi_experience":           "Erfahrung",
     )
   )
-  texts.at(lang, default: texts.en).at(key, default: key)
+  localized(texts, key)
 }
 
 // ── Sidebar section heading ────────────────────────────────────────────────────
@@ -159,7 +158,7 @@
 }
 
 // ── Mission entry (main content area) ─────────────────────────────────────────
-#let dated_experience(title, date: none, description: none, content: none, company: none) = {
+#let dated_experience(title, date: none, description: none, content: none, company: none, logo: none) = {
   block(
     width: 100%,
     stroke: (left: 2.5pt + accent),
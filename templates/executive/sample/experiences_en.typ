@@ -0,0 +1,22 @@
+#import "template.typ": dated_experience, experience_details
+
+#let get_work_experience() = {
+  dated_experience(
+    "Chief Operating Officer",
+    date: "2018 – Present",
+    company: "Industrial group, France",
+    description: "2,400 employees, 9 plants.",
+    content: [
+      #experience_details("Runs operations, supply chain and procurement")
+      #experience_details("Sponsored the group-wide lean transformation")
+    ]
+  )
+  dated_experience(
+    "Managing Director",
+    date: "2010 – 2018",
+    company: "Services company, Italy",
+    content: [
+      #experience_details("Turned the subsidiary around to profitability in two years")
+    ]
+  )
+}
@@ -1,5 +1,5 @@
 #import "font_config.typ": font_config, get_icon
-#import "common.typ": get_lang, join_dicts, get_default_icons, process_links, skill_label, nonempty
+#import "common.typ": get_lang, join_dicts, get_default_icons, process_links, skill_label, nonempty, localized
 
 // ── Palette ───────────────────────────────────────────────────────────────────
 #let primary    = rgb("#1A1A2E")   // deep navy (fixed)
@@ -15,7 +15,6 @@
 
 // ── Language helpers ───────────────────────────────────────────────────────────
 #let get_text(key) = {
-  let lang = get_lang()
   let texts = (
     "en": (
       "technical_skills":         "Core Competencies",
@@ -45,7 +44,7 @@
       "summary":                  "Executive Summary",
     )
   )
-  texts.at(lang, default: texts.en).at(key, default: key)
+  localized(texts, key)
 }
 
 // ── Gold divider ───────────────────────────────────────────────────────────────
@@ -79,7 +78,7 @@
 }
 
 // ── Experience entry ───────────────────────────────────────────────────────────
-#let dated_experience(title, date: none, description: none, content: none, company: none) = {
+#let dated_experience(title, date: none, description: none, content: none, company: none, logo: none) = {
   grid(
     columns: (1fr, auto),
     align: (left + top, right + top),
@@ -0,0 +1,23 @@
+#import "template.typ": dated_experience, experience_details, structured_experience
+
+#let get_work_experience() = {
+  structured_experience(
+    "Technical Lead",
+    date: "2021 – Present",
+    company: "Private bank, Geneva",
+    description: "Mission on the client portfolio platform.",
+    context_info: ("Team of 6 developers", "Move from on-premise to AWS"),
+    responsibility_list: (
+      "Leads the design and review of the portfolio services",
+      "Runs the migration of the services to AWS",
+    ),
+  )
+  dated_experience(
+    "Senior Java Developer",
+    date: "2016 – 2021",
+    company: "Logistics company, Lausanne",
+    content: [
+      #experience_details("Built the shipment tracking services and their Kafka pipeline")
+    ]
+  )
+}
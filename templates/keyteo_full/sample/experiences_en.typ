@@ -0,0 +1,26 @@
+#import "template.typ": dated_experience, experience_details, structured_experience_full
+
+#let get_work_experience() = {
+  structured_experience_full(
+    "Software Architect",
+    date: "2021 – Present",
+    company: "Private bank, Geneva",
+    description: "Mission on the client portfolio platform.",
+    context_info: ("Team of 6 developers", "Move from on-premise to AWS"),
+    responsibilities: (
+      "Architecture": (
+        "Target architecture of the portfolio services",
+        "Review of every service design",
+      ),
+      "Delivery": ("Migration of the services to AWS",),
+    ),
+  )
+  dated_experience(
+    "Senior Java Developer",
+    date: "2016 – 2021",
+    company: "Logistics company, Lausanne",
+    content: [
+      #experience_details("Built the shipment tracking services and their Kafka pipeline")
+    ]
+  )
+}
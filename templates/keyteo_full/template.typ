@@ -1,3 +1,4 @@
+#import "common.typ": localized
 // global variables — resolve from user customization or fall back to brand defaults
 #let _u_primary = sys.inputs.at("primary_color",   default: none)
 #let _u_sec     = sys.inputs.at("secondary_color",  default: none)
@@ -26,7 +27,6 @@
 
 // Localized labels for skill subsections (technical, programming_languages, …)
 #let skill_label(key) = {
-  let lang = get_lang()
   let labels = (
     "en": (
       "technical": "Technical",
@@ -53,16 +53,13 @@
       "certifications": "Zertifizierungen",
     ),
   )
-  let dict = labels.at(lang, default: labels.en)
-  dict.at(key, default: {
-    let parts = key.split("_")
-    parts.map(p => if p.len() > 0 { upper(p.slice(0, 1)) + p.slice(1) } else { p }).join(" ")
-  })
+  let parts = key.split("_")
+  let humanized = parts.map(p => if p.len() > 0 { upper(p.slice(0, 1)) + p.slice(1) } else { p }).join(" ")
+  localized(labels, key, fallback: humanized)
 }
 
 // Language-specific text content
 #let get_text(key) = {
-  let lang = get_lang()
   let texts = (
     "en": (
       "technical_skills": "Technical Skills",
@@ -116,7 +113,7 @@
       "website": "www.mycompany.ch"
     )
   )
-  texts.at(lang, default: texts.en).at(key, default: key)
+  localized(texts, key)
 }
 
 // dictionary of common icons and values
@@ -259,7 +256,7 @@
 }
 
 /* experience that has an optional date and an optional description */
-#let dated_experience(title, date: none, description: none, content: none, company: none) = {
+#let dated_experience(title, date: none, description: none, content: none, company: none, logo: none) = {
   [
     #block(
       stroke: (bottom: 0.5pt + default_primary_color),
@@ -0,0 +1,22 @@
+#import "template.typ": dated_experience, experience_details
+
+#let get_work_experience() = {
+  dated_experience(
+    "Senior Associate",
+    date: "2019 – Present",
+    company: "Business law firm, Zurich",
+    description: "Corporate and M&A team.",
+    content: [
+      #experience_details("Advised on 20+ acquisitions up to CHF 500M")
+      #experience_details("Drafts financing rounds for technology start-ups")
+    ]
+  )
+  dated_experience(
+    "Associate",
+    date: "2015 – 2019",
+    company: "International law firm, Geneva",
+    content: [
+      #experience_details("Handled commercial contracts and corporate housekeeping for multinationals")
+    ]
+  )
+}
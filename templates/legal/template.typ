@@ -1,5 +1,5 @@
 #import "font_config.typ": font_config, get_icon
-#import "common.typ": get_lang, join_dicts, get_default_icons, process_links, skill_label, nonempty
+#import "common.typ": get_lang, join_dicts, get_default_icons, process_links, skill_label, nonempty, localized
 
 // ── Palette ───────────────────────────────────────────────────────────────────
 // Conservative: dark navy + burgundy accents — conveys authority and tradition.
@@ -16,7 +16,6 @@
 
 // ── Language helpers ──────────────────────────────────────────────────────────
 #let get_text(key) = {
-  let lang = get_lang()
   let texts = (
     "en": (
       "technical_skills":         "Areas of Expertise",
@@ -49,7 +48,7 @@
       "summary":                  "Berufsprofil",
     )
   )
-  texts.at(lang, default: texts.en).at(key, default: key)
+  localized(texts, key)
 }
 
 // ── Dividers ──────────────────────────────────────────────────────────────────
@@ -87,7 +86,7 @@
 }
 
 // ── Experience entry ──────────────────────────────────────────────────────────
-#let dated_experience(title, date: none, description: none, content: none, company: none) = {
+#let dated_experience(title, date: none, description: none, content: none, company: none, logo: none) = {
   grid(
     columns: (1fr, auto),
     align: (left + top, right + top),
@@ -0,0 +1,13 @@
+#import "template.typ": dated_experience, experience_details
+
+// The portfolio template lists projects; this is only its experiences file.
+#let get_work_experience() = {
+  dated_experience(
+    "Full-stack Developer",
+    date: "2019 – Present",
+    company: "Freelance, London",
+    content: [
+      #experience_details("Builds web products for start-ups")
+    ]
+  )
+}
@@ -0,0 +1,22 @@
+#import "template.typ": dated_experience, experience_details
+
+#let get_work_experience() = {
+  dated_experience(
+    "Senior SRE",
+    date: "2020 – Present",
+    company: "E-commerce platform, Berlin",
+    description: "Platform team running 60 Kubernetes clusters.",
+    content: [
+      #experience_details("Brought availability from 99.9% to 99.99% over two years")
+      #experience_details("Automated cluster upgrades with Terraform and Argo CD")
+    ]
+  )
+  dated_experience(
+    "DevOps Engineer",
+    date: "2014 – 2020",
+    company: "Telecom operator, India",
+    content: [
+      #experience_details("Built the monitoring stack with Prometheus and Grafana")
+    ]
+  )
+}
@@ -1,3 +1,4 @@
+#import "common.typ": localized
 // global variables
 #let default_primary_color = rgb("#14A4E6")
 #let default_secondary_color = rgb("#757575")
@@ -18,7 +19,6 @@
 
 // Language-specific text content
 #let get_text(key) = {
-  let lang = get_lang()
   let texts = (
     "en": (
       "technical_skills": "Technical Skills",
@@ -55,7 +55,7 @@
       "website": "www.mycompany.ch"
     )
   )
-  texts.at(lang, default: texts.en).at(key, default: key)
+  localized(texts, key)
 }
 
 // dictionary of common icons and values
@@ -198,7 +198,7 @@
 }
 
 /* experience that has an optional date and an optional description */
-#let dated_experience(title, date: none, description: none, content: none, company: none) = {
+#let dated_experience(title, date: none, description: none, content: none, company: none, logo: none) = {
   [
     #block(
       stroke: (bottom: 0.5pt + rgb("#14A4E6")),
@@ -1,118 +1,21 @@
-// Integration tests: compile each Typst template to PDF using the real typst binary.
-// Set TYPST_BIN env var to override the binary path (useful in CI).
-
-use std::path::PathBuf;
-use std::process::Command;
-
-const MIN_TOML: &str = r#"
-name = "Test User"
-job_title = "Software Engineer"
-summary = "12 years of experience in software development and architecture."
-key_competencies = ["Rust", "DevOps", "System Architecture"]
-sectors = ["Finance", "Public Sector", "LegalTech"]
-tools = "Docker, Git, GitHub Actions, VS Code"
-areas_of_expertise = ["CI/CD implementation", "Hexagonal architecture", "Team leadership"]
-
-[[projects]]
-title = "cvenom"
-role = "Tech Lead"
-date = "2024 – Present"
-description = "AI-powered CV generator with Typst backend and multi-tenant architecture."
-technologies = ["Rust", "Typst", "Next.js", "SQLite"]
-highlights = ["Built multi-tenant PDF pipeline", "Integrated GPT-4 for CV optimisation"]
-url = "https://cvenom.com"
-
-[[projects]]
-title = "Open Source CLI"
-role = "Author"
-date = "2023"
-description = "Developer productivity tool written in Rust."
-technologies = ["Rust", "WASM"]
-highlights = ["1000+ GitHub stars"]
-url = ""
-
-[languages]
-native = ["French"]
-fluent = ["English"]
-
-[skills]
-"Backend" = ["Rust", "Node.js", "Java"]
-"DevOps" = ["Docker", "GitHub Actions", "Kubernetes"]
-"Frontend" = ["React", "TypeScript"]
-
-[[education]]
-type = "diploma"
-title = "MSc Computer Science, University of Lyon"
-date = "2005"
-
-[[education]]
-type = "certification"
-title = "AWS Certified Solutions Architect"
-date = "2022"
-"#;
-
-// Stub experiences.typ covering all function signatures used across templates.
-const EXPERIENCES_STUB: &str = r#"
-#import "template.typ": dated_experience, experience_details
-
-#let get_work_experience() = {
-  dated_experience(
-    "Senior Software Engineer",
-    date: "2020 – Present",
-    company: "Acme Corp, Switzerland",
-    description: "Cloud-native platform team.",
-    content: [
-      #experience_details("Designed and delivered microservices in Rust")
-      #experience_details("Led a team of 5 engineers across two time zones")
-    ]
-  )
-  dated_experience(
-    "Software Engineer",
-    date: "2015 – 2020",
-    company: "Startup SA, France",
-    content: [
-      #experience_details("Built React/Node.js full-stack application")
-    ]
-  )
-}
-
-#let get_key_insights() = (
-  "Experienced technical lead with 12+ years delivering complex systems",
-  "Expert in Rust, Node.js, and cloud-native architectures",
-  "Strong background in DevOps, CI/CD, and team coaching",
-)
-
-#let structured_experience_full(..args) = { get_work_experience() }
-"#;
-
-fn typst_bin() -> String {
-    std::env::var("TYPST_BIN").unwrap_or_else(|_| "typst".to_string())
+// Integration tests: compile each Typst template to PDF with the in-process
+// compiler the server uses.
+
+use cv_generator::core::typst_compiler::{self, Output};
+use std::path::{Path, PathBuf};
+
+/// Copy the template's sample person (`sample/` of its manifest) into `dir`.
+fn copy_sample(tpl_dir: &Path, dir: &Path) -> Result<(), String> {
+    let sample = tpl_dir.join("sample");
+    std::fs::copy(sample.join("cv_params.toml"), dir.join("cv_params.toml"))
+        .map_err(|e| format!("sample/cv_params.toml: {e}"))?;
+    std::fs::copy(sample.join("experiences_en.typ"), dir.join("experiences.typ"))
+        .map_err(|e| format!("sample/experiences_en.typ: {e}"))?;
+    Ok(())
 }
 
 /// Copy template + shared utilities into a temp dir, returning the tempdir handle.
-fn setup_template_workspace(template_name: &str) -> tempfile::TempDir {
-    let templates_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("templates");
-    let tpl_dir = templates_dir.join(template_name);
-    let tmp = tempfile::tempdir().unwrap();
-
-    for entry in std::fs::read_dir(&tpl_dir).unwrap() {
-        let entry = entry.unwrap();
-        if entry.path().is_file() {
-            std::fs::copy(entry.path(), tmp.path().join(entry.file_name())).unwrap();
-        }
-    }
-    for shared in &["font_config.typ", "common.typ"] {
-        let src = templates_dir.join(shared);
-        if src.exists() {
-            std::fs::copy(&src, tmp.path().join(shared)).unwrap();
-        }
-    }
-    std::fs::write(tmp.path().join("cv_params.toml"), MIN_TOML).unwrap();
-    std::fs::write(tmp.path().join("experiences.typ"), EXPERIENCES_STUB).unwrap();
-    tmp
-}
-
-fn compile_template(template_name: &str) -> Result<(), String> {
+fn setup_template_workspace(template_name: &str) -> Result<tempfile::TempDir, String> {
     let templates_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("templates");
     let tpl_dir = templates_dir.join(template_name);
 
@@ -135,28 +38,30 @@ fn compile_template(template_name: &str) -> Result<(), String> {
     for shared in &["font_config.typ", "common.typ"] {
         let src = templates_dir.join(shared);
         if src.exists() {
-            std::fs::copy(&src, tmp.path().join(shared))
-                .map_err(|e| e.to_string())?;
+            std::fs::copy(&src, tmp.path().join(shared)).map_err(|e| e.to_string())?;
         }
     }
 
-    std::fs::write(tmp.path().join("cv_params.toml"), MIN_TOML).map_err(|e| e.to_string())?;
-    std::fs::write(tmp.path().join("experiences.typ"), EXPERIENCES_STUB)
-        .map_err(|e| e.to_string())?;
-
-    let out = Command::new(typst_bin())
-        .args(["compile", "main.typ", "output.pdf", "--input", "lang=en"])
-        .current_dir(tmp.path())
-        .output()
-        .map_err(|e| format!("could not run typst (set TYPST_BIN if needed): {e}"))?;
+    copy_sample(&tpl_dir, tmp.path())?;
+    Ok(tmp)
+}
 
-    if out.status.success() {
+fn compile_template_in(template_name: &str, lang: &str) -> Result<(), String> {
+    let tmp = setup_template_workspace(template_name)?;
+    let inputs = [("lang".to_string(), lang.to_string())];
+    let pdf = typst_compiler::compile(tmp.path(), "main.typ", &inputs, Output::Pdf)
+        .map_err(|e| format!("{e:#}"))?;
+    if pdf.bytes.starts_with(b"%PDF") {
         Ok(())
     } else {
-        Err(String::from_utf8_lossy(&out.stderr).to_string())
+        Err("output is not a PDF".to_string())
     }
 }
 
+fn compile_template(template_name: &str) -> Result<(), String> {
+    compile_template_in(template_name, "en")
+}
+
 #[test]
 fn portfolio_compiles_en() {
     compile_template("portfolio").expect("portfolio (en) failed to compile");
@@ -164,12 +69,9 @@ fn portfolio_compiles_en() {
 
 #[test]
 fn portfolio_compiles_fr() {
-    let tmp = setup_template_workspace("portfolio");
-    let out = Command::new(typst_bin())
-        .args(["compile", "main.typ", "output.pdf", "--input", "lang=fr"])
-        .current_dir(tmp.path())
-        .output().expect("could not run typst");
-    assert!(out.status.success(), "portfolio (fr) failed:\n{}", String::from_utf8_lossy(&out.stderr));
+    if let Err(e) = compile_template_in("portfolio", "fr") {
+        panic!("portfolio (fr) failed:\n{e}");
+    }
 }
 
 #[test]
@@ -179,24 +81,16 @@ fn enterprise2_compiles_en() {
 
 #[test]
 fn enterprise2_compiles_fr() {
-    let tmp = setup_template_workspace("enterprise2");
-    let out = Command::new(typst_bin())
-        .args(["compile", "main.typ", "output.pdf", "--input", "lang=fr"])
-        .current_dir(tmp.path())
-        .output()
-        .expect("could not run typst");
-    assert!(out.status.success(), "enterprise2 (fr) failed:\n{}", String::from_utf8_lossy(&out.stderr));
+    if let Err(e) = compile_template_in("enterprise2", "fr") {
+        panic!("enterprise2 (fr) failed:\n{e}");
+    }
 }
 
 #[test]
 fn enterprise2_compiles_de() {
-    let tmp = setup_template_workspace("enterprise2");
-    let out = Command::new(typst_bin())
-        .args(["compile", "main.typ", "output.pdf", "--input", "lang=de"])
-        .current_dir(tmp.path())
-        .output()
-        .expect("could not run typst");
-    assert!(out.status.success(), "enterprise2 (de) failed:\n{}", String::from_utf8_lossy(&out.stderr));
+    if let Err(e) = compile_template_in("enterprise2", "de") {
+        panic!("enterprise2 (de) failed:\n{e}");
+    }
 }
 
 // ── Legal template ───────────────────────────────────────────────────────────
@@ -208,13 +102,9 @@ fn legal_compiles_en() {
 
 #[test]
 fn legal_compiles_fr() {
-    let tmp = setup_template_workspace("legal");
-    let out = Command::new(typst_bin())
-        .args(["compile", "main.typ", "output.pdf", "--input", "lang=fr"])
-        .current_dir(tmp.path())
-        .output()
-        .expect("could not run typst");
-    assert!(out.status.success(), "legal (fr) failed:\n{}", String::from_utf8_lossy(&out.stderr));
+    if let Err(e) = compile_template_in("legal", "fr") {
+        panic!("legal (fr) failed:\n{e}");
+    }
 }
 
 // Smoke-test every template so a change to shared font_config.typ